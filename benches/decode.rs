@@ -0,0 +1,55 @@
+//! Decoder throughput: fresh event vector per buffer vs. reused vector
+//!
+//! Usage:
+//!   cargo bench --bench decode
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use delila_rs::reader::decoder::RawData;
+use delila_rs::reader::Psd2Decoder;
+
+/// Events per raw buffer (a typical aggregate at high rate)
+const EVENTS_PER_BUFFER: usize = 10_000;
+
+/// Build a PSD2 data aggregate of two-word events (big-endian 64-bit words)
+fn psd2_buffer(num_events: usize) -> RawData {
+    let total_words = 1 + 2 * num_events as u64;
+    let mut words = Vec::with_capacity(total_words as usize);
+    words.push((0x2u64 << 60) | total_words);
+    for i in 0..num_events as u64 {
+        let channel = i % 32;
+        words.push((channel << 56) | (i * 100));
+        words.push((1u64 << 63) | (200 << 26) | (1000 + i % 3000));
+    }
+    let data: Vec<u8> = words.iter().flat_map(|w| w.to_be_bytes()).collect();
+    RawData {
+        size: data.len(),
+        data,
+        n_events: num_events as u32,
+    }
+}
+
+fn bench_decode(c: &mut Criterion) {
+    let raw = psd2_buffer(EVENTS_PER_BUFFER);
+
+    let mut group = c.benchmark_group("psd2_decode");
+    group.throughput(Throughput::Elements(EVENTS_PER_BUFFER as u64));
+
+    group.bench_function("fresh_vec", |b| {
+        let mut decoder = Psd2Decoder::with_defaults();
+        b.iter(|| black_box(decoder.decode(black_box(&raw))).len())
+    });
+
+    group.bench_function("reused_vec", |b| {
+        let mut decoder = Psd2Decoder::with_defaults();
+        let mut events = Vec::new();
+        b.iter(|| {
+            decoder.decode_into(black_box(&raw), &mut events);
+            black_box(events.len())
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_decode);
+criterion_main!(benches);
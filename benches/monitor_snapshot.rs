@@ -0,0 +1,50 @@
+//! Monitor list query cost: full histogram clone vs. count summary
+//!
+//! Usage:
+//!   cargo bench --bench monitor_snapshot
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use delila_rs::common::EventData;
+use delila_rs::monitor::{HistogramConfig, MonitorState};
+
+/// Modules x channels of a large multi-digitizer setup
+const NUM_MODULES: u8 = 16;
+const CHANNELS_PER_MODULE: u8 = 64;
+
+/// Monitor state with one filled 4096-bin histogram per channel
+fn filled_state() -> MonitorState {
+    let mut state = MonitorState::new(HistogramConfig {
+        num_bins: 4096,
+        min_value: 0.0,
+        max_value: 4096.0,
+        ..HistogramConfig::default()
+    });
+    for module in 0..NUM_MODULES {
+        for channel in 0..CHANNELS_PER_MODULE {
+            for energy in (0..4096u32).step_by(64) {
+                state.process_event(&EventData::new(module, channel, energy, 0, 0.0, 0));
+            }
+        }
+    }
+    state
+}
+
+fn bench_list_query(c: &mut Criterion) {
+    let state = filled_state();
+
+    let mut group = c.benchmark_group("monitor_list_query");
+
+    // Previous behaviour: every list/status query cloned all histograms
+    group.bench_function("clone_histograms", |b| {
+        b.iter(|| black_box(state.histograms.clone()).len())
+    });
+
+    group.bench_function("summary", |b| {
+        b.iter(|| black_box(state.summary()).channels.len())
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_list_query);
+criterion_main!(benches);
@@ -4,6 +4,30 @@ use std::env;
 use std::path::PathBuf;
 
 fn main() {
+    // Version info for `common::version` (GET /api/version, GetStatus).
+    // No rerun trigger on .git, which would re-run bindgen on every commit.
+    println!("cargo:rerun-if-env-changed=DELILA_GIT_COMMIT");
+    let git_commit = env::var("DELILA_GIT_COMMIT")
+        .ok()
+        .filter(|commit| !commit.is_empty())
+        .or_else(|| {
+            std::process::Command::new("git")
+                .args(["rev-parse", "--short", "HEAD"])
+                .output()
+                .ok()
+                .filter(|output| output.status.success())
+                .and_then(|output| String::from_utf8(output.stdout).ok())
+                .map(|commit| commit.trim().to_string())
+                .filter(|commit| !commit.is_empty())
+        })
+        .unwrap_or_else(|| "unknown".to_string());
+    let build_timestamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    println!("cargo:rustc-env=DELILA_GIT_COMMIT={}", git_commit);
+    println!("cargo:rustc-env=DELILA_BUILD_TIMESTAMP={}", build_timestamp);
+
     // Tell cargo to look for shared libraries in /usr/local/lib
     println!("cargo:rustc-link-search=/usr/local/lib");
 
@@ -284,6 +284,7 @@ fn test_data_readout(handle: &CaenHandle, decode_enabled: bool) {
             module_id: 0,
             dump_enabled: true, // Enable dump for debugging
             num_channels: 32,
+            adc_bits: 16,
         };
         Some(Psd2Decoder::new(config))
     } else {
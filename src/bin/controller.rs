@@ -6,6 +6,7 @@
 //!   cargo run --bin controller -- start tcp://localhost:5560 --run 123
 //!   cargo run --bin controller -- stop tcp://localhost:5560
 //!   cargo run --bin controller -- reset tcp://localhost:5560
+//!   cargo run --bin controller -- recover tcp://localhost:5560
 //!   cargo run --bin controller -- status tcp://localhost:5560
 
 use clap::{Parser, Subcommand};
@@ -18,7 +19,7 @@ use tmq::{request_reply, Context};
     about = "DELILA controller - send commands to DAQ components"
 )]
 #[command(
-    after_help = "State Machine:\n  Idle → Configure → Configured → Arm → Armed → Start → Running\n  Running → Stop → Configured (quick restart possible)\n  Any → Reset → Idle"
+    after_help = "State Machine:\n  Idle → Configure → Configured → Arm → Armed → Start → Running\n  Running → Stop → Configured (quick restart possible)\n  Running → Pause → Paused → Resume → Running\n  Any → Reset → Idle\n  Error → Recover → Idle"
 )]
 struct Args {
     #[command(subcommand)]
@@ -51,16 +52,36 @@ enum ControllerCommand {
         #[arg(long = "run")]
         run_number: u32,
     },
-    /// Stop acquisition (Running → Configured)
+    /// Stop acquisition (Running/Paused → Configured)
     Stop {
         /// Target component's command address
         address: String,
     },
+    /// Suspend data flow, keeping the run open (Running → Paused)
+    Pause {
+        /// Target component's command address
+        address: String,
+    },
+    /// Continue a paused run (Paused → Running)
+    Resume {
+        /// Target component's command address
+        address: String,
+    },
+    /// Show the component's effective configuration
+    Config {
+        /// Target component's command address
+        address: String,
+    },
     /// Reset to idle state (Any → Idle)
     Reset {
         /// Target component's command address
         address: String,
     },
+    /// Leave the Error state (Error → Idle, rejected otherwise)
+    Recover {
+        /// Target component's command address
+        address: String,
+    },
     /// Query current status
     Status {
         /// Target component's command address
@@ -85,7 +106,11 @@ impl ControllerCommand {
                 run_number: *run_number,
             },
             ControllerCommand::Stop { .. } => Command::Stop,
+            ControllerCommand::Pause { .. } => Command::Pause,
+            ControllerCommand::Resume { .. } => Command::Resume,
+            ControllerCommand::Config { .. } => Command::GetConfig,
             ControllerCommand::Reset { .. } => Command::Reset,
+            ControllerCommand::Recover { .. } => Command::RecoverError,
             ControllerCommand::Status { .. } => Command::GetStatus,
         }
     }
@@ -96,7 +121,11 @@ impl ControllerCommand {
             | ControllerCommand::Arm { address }
             | ControllerCommand::Start { address, .. }
             | ControllerCommand::Stop { address }
+            | ControllerCommand::Pause { address }
+            | ControllerCommand::Resume { address }
+            | ControllerCommand::Config { address }
             | ControllerCommand::Reset { address }
+            | ControllerCommand::Recover { address }
             | ControllerCommand::Status { address } => address,
         }
     }
@@ -136,6 +165,9 @@ async fn main() -> anyhow::Result<()> {
             println!("  Error:   {}", code);
         }
         println!("  Message: {}", response.message);
+        if let Some(data) = response.data {
+            println!("  Data:    {}", serde_json::to_string_pretty(&data)?);
+        }
     } else {
         eprintln!("Empty response received");
     }
@@ -4,13 +4,14 @@
 //!   cargo run --bin data_sink                       # Use config.toml
 //!   cargo run --bin data_sink -- -f config.toml     # Explicit config file
 //!   cargo run --bin data_sink -- -a tcp://localhost:5557
+//!   cargo run --bin data_sink -- --sample-ratio 0.1  # Process every 10th event
+//!   cargo run --bin data_sink -- --csv-output events.csv
 
 use clap::Parser;
-use delila_rs::common::{setup_shutdown_with_message, DataSinkArgs};
+use delila_rs::common::{logging, setup_shutdown_with_message, DataSinkArgs, LogContext};
 use delila_rs::config::Config;
 use delila_rs::data_sink::{DataSink, DataSinkConfig};
 use tracing::info;
-use tracing_subscriber::EnvFilter;
 
 #[derive(Parser, Debug)]
 #[command(
@@ -24,13 +25,15 @@ struct Args {
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
-    // Initialize tracing (logging)
-    tracing_subscriber::fmt()
-        .with_env_filter(EnvFilter::from_default_env().add_directive("delila_rs=info".parse()?))
-        .init();
-
     let args = Args::parse();
 
+    // Initialize tracing (logging)
+    logging::init(
+        args.sink.common.log_format,
+        LogContext::component("data_sink"),
+        &["delila_rs=info"],
+    )?;
+
     // Load configuration
     let config = Config::load(&args.sink.common.config_file)?;
     info!(config_file = %args.sink.common.config_file, "Loaded configuration");
@@ -53,12 +56,33 @@ async fn main() -> anyhow::Result<()> {
         )
     };
 
+    let (finish_on_all_eos, expected_source_ids) = config
+        .network
+        .recorder
+        .as_ref()
+        .map(|r| (r.finish_on_all_eos, r.expected_source_ids.clone()))
+        .unwrap_or_default();
+
+    // Same source as the subscribe address: recorder first, then monitor
+    let (subscribe_topics, socket_options) =
+        match (&config.network.recorder, &config.network.monitor) {
+            (Some(recorder), _) => (recorder.subscribe_topics.clone(), recorder.socket_options()),
+            (None, Some(monitor)) => (monitor.subscribe_topics.clone(), monitor.socket_options()),
+            (None, None) => Default::default(),
+        };
+
     // CLI overrides config file
     let sink_config = DataSinkConfig {
         address: args.sink.address.unwrap_or(subscribe_addr),
         command_address: command_addr,
         stats_interval_secs: 1,
         channel_capacity: 1000,
+        finish_on_all_eos,
+        expected_source_ids,
+        subscribe_topics,
+        sample_ratio: args.sink.sample_ratio,
+        csv_output: args.sink.csv_output,
+        socket_options,
     };
 
     // Setup shutdown handling
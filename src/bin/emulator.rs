@@ -5,13 +5,15 @@
 //!   cargo run --bin emulator -- --config config.toml   # Use config file
 //!   cargo run --bin emulator -- --batches 10           # Run for 10 batches
 //!   cargo run --bin emulator -- --source-id 1          # Use specific source
+//!   cargo run --bin emulator -- --replay run.delila    # Replay a recorded file
 
 use clap::Parser;
-use delila_rs::common::{setup_shutdown_with_message, SourceArgs};
+use delila_rs::common::{
+    logging, setup_shutdown_with_message, LogContext, SourceArgs, DEFAULT_MAX_MESSAGE_BYTES,
+};
 use delila_rs::config::Config;
-use delila_rs::data_source_emulator::{Emulator, EmulatorConfig};
+use delila_rs::data_source_emulator::{Emulator, EmulatorConfig, ReplayConfig, ReplaySource};
 use tracing::info;
-use tracing_subscriber::EnvFilter;
 
 /// Emulator - publishes dummy event data via ZeroMQ
 #[derive(Parser, Debug)]
@@ -31,23 +33,43 @@ struct Args {
     /// Events per batch
     #[arg(short, long)]
     events: Option<usize>,
+
+    /// Replay a recorded .delila file instead of generating events
+    #[arg(long)]
+    replay: Option<String>,
+
+    /// Replay speed relative to the recording (0 = as fast as possible)
+    #[arg(long, default_value_t = 1.0)]
+    speed: f64,
+
+    /// Restart the replay at the end of the file
+    #[arg(long = "loop")]
+    loop_playback: bool,
 }
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
-    // Initialize tracing (logging)
-    tracing_subscriber::fmt()
-        .with_env_filter(EnvFilter::from_default_env().add_directive("delila_rs=info".parse()?))
-        .init();
-
     // Parse command line arguments
     let args = Args::parse();
 
+    // Initialize tracing (logging)
+    logging::init(
+        args.source.common.log_format,
+        LogContext {
+            component: "emulator",
+            source_id: Some(args.source.source_id.unwrap_or(0)),
+        },
+        &["delila_rs=info"],
+    )?;
+
     // Build configuration
     let config_path = &args.source.common.config_file;
     let emulator_config = if std::path::Path::new(config_path).exists() {
         // Load from config file
         let config = Config::load(config_path)?;
+        #[cfg(feature = "mongodb")]
+        let settings = config.settings.get_settings_async().await?;
+        #[cfg(not(feature = "mongodb"))]
         let settings = config.settings.get_settings()?;
 
         // Find source config by ID
@@ -84,6 +106,17 @@ async fn main() -> anyhow::Result<()> {
             enable_waveform: settings.enable_waveform,
             waveform_probes: settings.waveform_probes,
             waveform_samples: settings.waveform_samples,
+            max_message_bytes: source_net
+                .map(|s| s.max_message_bytes)
+                .unwrap_or(DEFAULT_MAX_MESSAGE_BYTES),
+            seed: source_net.and_then(|s| s.seed),
+            peaks: settings.peaks,
+            background_ratio: settings.background_ratio,
+            topic_prefix: source_net.and_then(|s| s.topic_prefix.clone()),
+            channel_mask: source_net.and_then(|s| s.channel_mask),
+            compress_waveforms: source_net.is_some_and(|s| s.compress_waveforms),
+            wide_energy: source_net.is_some_and(|s| s.wide_energy),
+            socket_options: source_net.map(|s| s.socket_options()).unwrap_or_default(),
         }
     } else {
         // Use defaults with CLI overrides
@@ -104,6 +137,34 @@ async fn main() -> anyhow::Result<()> {
         }
     };
 
+    if let Some(path) = args.replay {
+        let replay_config = ReplayConfig {
+            path: path.into(),
+            address: emulator_config.address.clone(),
+            speed: args.speed,
+            loop_playback: args.loop_playback,
+            max_message_bytes: emulator_config.max_message_bytes,
+            topic_prefix: emulator_config.topic_prefix.clone(),
+            socket_options: emulator_config.socket_options,
+        };
+        let mut replay = ReplaySource::new(replay_config.clone()).await?;
+        println!(
+            "Replaying {} to {} (speed {}).",
+            replay_config.path.display(),
+            replay_config.address,
+            replay_config.speed
+        );
+
+        let (_shutdown_tx, shutdown_rx) =
+            setup_shutdown_with_message("Received Ctrl+C, shutting down...");
+        let stats = replay.run(shutdown_rx).await?;
+        println!(
+            "Replay stopped: {} batches, {} events, {} passes.",
+            stats.batches, stats.events, stats.passes
+        );
+        return Ok(());
+    }
+
     // Create emulator
     let mut emulator = Emulator::new(emulator_config.clone()).await?;
 
@@ -7,7 +7,7 @@
 
 use anyhow::Result;
 use clap::Parser;
-use delila_rs::common::{setup_shutdown, MergerArgs};
+use delila_rs::common::{logging, setup_shutdown, LogContext, MergerArgs};
 use delila_rs::config::Config;
 use delila_rs::merger::{Merger, MergerConfig};
 use tracing::info;
@@ -24,17 +24,15 @@ struct Args {
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    // Initialize logging
-    tracing_subscriber::fmt()
-        .with_env_filter(
-            tracing_subscriber::EnvFilter::from_default_env()
-                .add_directive("delila_rs=debug".parse()?)
-                .add_directive("merger=debug".parse()?),
-        )
-        .init();
-
     let args = Args::parse();
 
+    // Initialize logging
+    logging::init(
+        args.merger.common.log_format,
+        LogContext::component("merger"),
+        &["delila_rs=debug", "merger=debug"],
+    )?;
+
     // Load configuration
     let config = Config::load(&args.merger.common.config_file)?;
     let merger_net = config
@@ -45,6 +43,7 @@ async fn main() -> Result<()> {
     info!(config_file = %args.merger.common.config_file, "Loaded configuration");
 
     // CLI overrides config file
+    let socket_options = merger_net.socket_options();
     let merger_config = MergerConfig {
         sub_addresses: if args.merger.sub_addresses.is_empty() {
             merger_net.subscribe
@@ -55,6 +54,20 @@ async fn main() -> Result<()> {
         command_address: merger_net
             .command
             .unwrap_or_else(|| "tcp://*:5570".to_string()),
+        merge_by_timestamp: merger_net.merge_by_timestamp,
+        sort_margin_ns: merger_net.sort_margin_ns,
+        max_buffered_events: merger_net.max_buffered_events,
+        max_residency_ms: merger_net.max_residency_ms,
+        adaptive_sort_margin: merger_net.adaptive_sort_margin,
+        dedup: merger_net.dedup,
+        coincidence: merger_net.coincidence,
+        channel_capacity: merger_net.channel_capacity,
+        backpressure: merger_net.backpressure,
+        subscribe_topics: merger_net.subscribe_topics,
+        heartbeat_timeout_ms: merger_net.heartbeat_timeout_ms,
+        stats_interval_secs: merger_net.stats_interval_secs,
+        socket_options,
+        topic_prefix: merger_net.topic_prefix,
     };
 
     info!(?merger_config, "Starting merger");
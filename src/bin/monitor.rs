@@ -6,11 +6,10 @@
 //!   cargo run --bin monitor -- -a tcp://localhost:5557 -p 8080
 
 use clap::Parser;
-use delila_rs::common::{setup_shutdown_with_message, MonitorArgs};
+use delila_rs::common::{logging, setup_shutdown_with_message, LogContext, MonitorArgs};
 use delila_rs::config::Config;
-use delila_rs::monitor::{HistogramConfig, Monitor, MonitorConfig};
+use delila_rs::monitor::{Histogram2DConfig, HistogramConfig, Monitor, MonitorConfig};
 use tracing::info;
-use tracing_subscriber::EnvFilter;
 
 #[derive(Parser, Debug)]
 #[command(
@@ -24,21 +23,58 @@ struct Args {
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
-    // Initialize tracing (logging)
-    tracing_subscriber::fmt()
-        .with_env_filter(EnvFilter::from_default_env().add_directive("delila_rs=info".parse()?))
-        .init();
-
     let args = Args::parse();
 
+    // Initialize tracing (logging)
+    logging::init(
+        args.monitor.common.log_format,
+        LogContext::component("monitor"),
+        &["delila_rs=info"],
+    )?;
+
     // Load configuration
     let config = Config::load(&args.monitor.common.config_file)?;
     info!(config_file = %args.monitor.common.config_file, "Loaded configuration");
 
-    let (subscribe_addr, http_port) = if let Some(ref monitor) = config.network.monitor {
-        (monitor.subscribe.clone(), monitor.http_port)
-    } else {
-        ("tcp://localhost:5557".to_string(), 8081)
+    let (subscribe_addr, http_port, rate_history_len) =
+        if let Some(ref monitor) = config.network.monitor {
+            (
+                monitor.subscribe.clone(),
+                monitor.http_port,
+                monitor.rate_history_len,
+            )
+        } else {
+            ("tcp://localhost:5557".to_string(), 8081, 3600)
+        };
+
+    let subscribe_topics = config
+        .network
+        .monitor
+        .as_ref()
+        .map(|m| m.subscribe_topics.clone())
+        .unwrap_or_default();
+    let socket_options = config
+        .network
+        .monitor
+        .as_ref()
+        .map(|m| m.socket_options())
+        .unwrap_or_default();
+    let labels = config
+        .network
+        .monitor
+        .as_ref()
+        .map(|m| m.labels.clone())
+        .unwrap_or_default();
+    let scaler_thresholds = config
+        .network
+        .monitor
+        .as_ref()
+        .map(|m| m.scaler_thresholds.clone())
+        .unwrap_or_default();
+
+    let histogram_config = match config.network.monitor.as_ref().and_then(|m| m.adc_bits) {
+        Some(bits) => HistogramConfig::for_adc_bits(bits),
+        None => HistogramConfig::default(),
     };
 
     // CLI overrides config file
@@ -46,8 +82,15 @@ async fn main() -> anyhow::Result<()> {
         subscribe_address: args.monitor.address.unwrap_or(subscribe_addr),
         command_address: "tcp://*:5590".to_string(),
         http_port: args.monitor.port.unwrap_or(http_port),
-        histogram_config: HistogramConfig::default(),
+        histogram_config,
+        histogram_2d_config: Histogram2DConfig::default(),
         channel_capacity: 1000,
+        ws_interval_ms: 500,
+        rate_history_len,
+        subscribe_topics,
+        socket_options,
+        labels,
+        scaler_thresholds,
     };
 
     // Setup shutdown handling
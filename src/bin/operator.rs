@@ -14,6 +14,7 @@
 //!   POST /api/start     - Start data acquisition
 //!   POST /api/stop      - Stop data acquisition
 //!   POST /api/reset     - Reset all components
+//!   POST /api/config/reload - Re-read components from the config file
 //!
 //! Swagger UI: http://localhost:8080/swagger-ui/
 
@@ -21,14 +22,13 @@ use std::net::SocketAddr;
 use std::path::PathBuf;
 
 use clap::Parser;
-use delila_rs::common::OperatorArgs;
+use delila_rs::common::{logging, LogContext, OperatorArgs};
 use delila_rs::config::Config;
 use delila_rs::operator::{
-    ComponentConfig, DigitizerConfigRepository, EmulatorSettings, OperatorConfig, RouterBuilder,
-    RunRepository,
+    components_from_config, ComponentConfig, DigitizerConfigRepository, EmulatorSettings,
+    OperatorConfig, RouterBuilder, RunRepository,
 };
-use tracing::{info, warn, Level};
-use tracing_subscriber::FmtSubscriber;
+use tracing::{info, warn};
 
 #[derive(Parser, Debug)]
 #[command(
@@ -53,9 +53,10 @@ fn load_config(config_file: &str) -> (Vec<ComponentConfig>, OperatorConfig, Emul
     // Try to load from config file
     if let Ok(config) = Config::load(config_file) {
         info!("Loaded configuration from {}", config_file);
-        let components = build_components_from_config(&config);
+        let components = components_from_config(&config);
         let operator_config = OperatorConfig {
             experiment_name: config.operator.experiment_name,
+            status_interval_ms: config.operator.status_interval_ms,
             ..OperatorConfig::default()
         };
         // Load emulator settings from config
@@ -123,94 +124,16 @@ fn load_config(config_file: &str) -> (Vec<ComponentConfig>, OperatorConfig, Emul
     )
 }
 
-/// Build ComponentConfig list from parsed Config
-fn build_components_from_config(config: &Config) -> Vec<ComponentConfig> {
-    let mut components = Vec::new();
-
-    // Add sources (emulators/readers)
-    for source in &config.network.sources {
-        let name = if source.name.is_empty() {
-            format!("Source {}", source.id)
-        } else {
-            source.name.clone()
-        };
-        // Convert bind address (tcp://*:port) to connect address (tcp://localhost:port)
-        let address = source
-            .command_address()
-            .replace("tcp://*:", "tcp://localhost:");
-        components.push(ComponentConfig {
-            name,
-            address,
-            pipeline_order: source.pipeline_order,
-            is_master: source.is_master_digitizer(),
-            source_id: Some(source.id),
-            is_digitizer: source.is_digitizer(),
-        });
-    }
-
-    // Add merger
-    if let Some(ref merger) = config.network.merger {
-        let address = merger
-            .command
-            .clone()
-            .unwrap_or_else(|| "tcp://*:5570".to_string())
-            .replace("tcp://*:", "tcp://localhost:");
-        components.push(ComponentConfig {
-            name: "Merger".to_string(),
-            address,
-            pipeline_order: merger.pipeline_order,
-            is_master: false,
-            source_id: None,
-            is_digitizer: false,
-        });
-    }
-
-    // Add recorder
-    if let Some(ref recorder) = config.network.recorder {
-        let address = recorder
-            .command
-            .clone()
-            .unwrap_or_else(|| "tcp://*:5580".to_string())
-            .replace("tcp://*:", "tcp://localhost:");
-        components.push(ComponentConfig {
-            name: "Recorder".to_string(),
-            address,
-            pipeline_order: recorder.pipeline_order,
-            is_master: false,
-            source_id: None,
-            is_digitizer: false,
-        });
-    }
-
-    // Add monitor
-    if let Some(ref monitor) = config.network.monitor {
-        let address = monitor
-            .command
-            .clone()
-            .unwrap_or_else(|| "tcp://*:5590".to_string())
-            .replace("tcp://*:", "tcp://localhost:");
-        components.push(ComponentConfig {
-            name: "Monitor".to_string(),
-            address,
-            pipeline_order: monitor.pipeline_order,
-            is_master: false,
-            source_id: None,
-            is_digitizer: false,
-        });
-    }
-
-    components
-}
-
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     let args = Args::parse();
 
     // Initialize logging
-    let subscriber = FmtSubscriber::builder()
-        .with_max_level(Level::INFO)
-        .finish();
-    tracing::subscriber::set_global_default(subscriber)?;
+    logging::init(
+        args.operator.common.log_format,
+        LogContext::component("operator"),
+        &["info"],
+    )?;
 
     // Load component, operator, and emulator configuration
     let (components, operator_config, emulator_settings) =
@@ -266,13 +189,15 @@ async fn main() -> anyhow::Result<()> {
     };
 
     // Create router with builder
-    let app = RouterBuilder::new(components)
+    let (app, tasks) = RouterBuilder::new(components)
         .config(operator_config)
+        .config_path(PathBuf::from(&args.operator.common.config_file))
         .config_dir(PathBuf::from("./config/digitizers"))
         .run_repo(run_repo)
         .digitizer_repo(digitizer_repo)
         .emulator_settings(emulator_settings)
         .build();
+    tasks.start();
 
     // Start server
     let port = args.operator.port;
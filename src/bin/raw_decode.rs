@@ -0,0 +1,104 @@
+//! raw_decode - Offline decoder for raw digitizer buffer files
+//!
+//! Decodes a `.dlraw` file recorded by the Reader (`raw_record_dir` or
+//! `dump_raw`) with the current decoders and writes a regular `.delila` file.
+//!
+//! Usage:
+//!   raw_decode <file.dlraw> [--output <path>]
+
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use clap::Parser;
+use delila_rs::reader::raw_file::decode_raw_file;
+use delila_rs::recorder::{ChecksumCalculator, FileFooter, FileHeader};
+
+#[derive(Parser)]
+#[command(name = "raw_decode")]
+#[command(about = "Decode raw digitizer buffer files into DELILA data files")]
+#[command(version)]
+struct Args {
+    /// Path to the .dlraw file
+    file: PathBuf,
+
+    /// Output path (default: <input>.delila)
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+}
+
+fn main() {
+    let args = Args::parse();
+
+    if let Err(e) = decode_file(&args.file, args.output) {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    }
+}
+
+fn decode_file(input: &Path, output: Option<PathBuf>) -> Result<(), Box<dyn std::error::Error>> {
+    println!("Decoding: {}", input.display());
+
+    let batches = decode_raw_file(input)?;
+    let raw_header = batches.header().clone();
+    println!(
+        "  Firmware: {:?}, source {}, run {}",
+        raw_header.firmware, raw_header.source_id, raw_header.run_number
+    );
+
+    let output = output.unwrap_or_else(|| input.with_extension("delila"));
+    println!("  Output: {}", output.display());
+
+    let mut header = FileHeader::new(raw_header.run_number, String::new(), 0);
+    header.source_ids = vec![raw_header.source_id];
+    header
+        .metadata
+        .insert("decoded_from".to_string(), input.display().to_string());
+    header
+        .metadata
+        .insert("firmware".to_string(), format!("{:?}", raw_header.firmware));
+    if let Some(config) = raw_header.config_snapshot {
+        header
+            .metadata
+            .insert("digitizer_config".to_string(), config);
+    }
+
+    let mut writer = BufWriter::with_capacity(64 * 1024, File::create(&output)?);
+    writer.write_all(&header.to_bytes()?)?;
+
+    let mut footer = FileFooter::new();
+    let mut checksum = ChecksumCalculator::new();
+    let mut batches_written = 0u64;
+    let mut events_written = 0u64;
+
+    for batch in batches {
+        let batch = batch?;
+        if let (Some(first), Some(last)) = (batch.events.first(), batch.events.last()) {
+            footer.update_timestamp_range(first.timestamp_ns, last.timestamp_ns);
+        }
+
+        let data = batch.to_msgpack()?;
+        let len_bytes = (data.len() as u32).to_le_bytes();
+        writer.write_all(&len_bytes)?;
+        writer.write_all(&data)?;
+        checksum.update(&len_bytes);
+        checksum.update(&data);
+
+        batches_written += 1;
+        events_written += batch.events.len() as u64;
+    }
+
+    footer.total_events = events_written;
+    footer.data_checksum = checksum.finalize();
+    footer.data_bytes = checksum.bytes_processed();
+    footer.finalize();
+    writer.write_all(&footer.to_bytes())?;
+    writer.flush()?;
+
+    println!();
+    println!("\x1b[32m✓ Decode complete\x1b[0m");
+    println!("  Batches written: {}", batches_written);
+    println!("  Events written:  {}", events_written);
+
+    Ok(())
+}
@@ -5,19 +5,20 @@
 //!   cargo run --bin reader -- --url dig2://172.18.4.56 --source-id 0
 //!   cargo run --bin reader -- --config config.toml --source-id 0
 
+use std::collections::HashMap;
+
+use delila_rs::common::{logging, LogContext, LogFormat, SocketOptions, DEFAULT_MAX_MESSAGE_BYTES};
 use delila_rs::config::Config;
-use delila_rs::reader::{FirmwareType, Reader, ReaderConfig};
+use delila_rs::reader::{
+    DecodeQueuePolicy, DecoderRegistry, EventFilter, FirmwareType, Reader, ReaderConfig,
+    DEFAULT_ADC_BITS, DEFAULT_MAX_RECONNECT_ATTEMPTS, DEFAULT_RECENT_EVENTS_CAPACITY,
+    DEFAULT_RECONNECT_BACKOFF_MS,
+};
 use tokio::sync::broadcast;
 use tracing::info;
-use tracing_subscriber::EnvFilter;
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
-    // Initialize tracing (logging)
-    tracing_subscriber::fmt()
-        .with_env_filter(EnvFilter::from_default_env().add_directive("delila_rs=info".parse()?))
-        .init();
-
     // Parse command line arguments
     let args: Vec<String> = std::env::args().collect();
     let mut config_path: Option<String> = None;
@@ -27,6 +28,7 @@ async fn main() -> anyhow::Result<()> {
     let mut command_address: Option<String> = None;
     let mut module_id: Option<u8> = None;
     let mut time_step_ns: Option<f64> = None;
+    let mut log_format = LogFormat::from_env();
 
     let mut i = 1;
     while i < args.len() {
@@ -94,6 +96,17 @@ async fn main() -> anyhow::Result<()> {
                     std::process::exit(1);
                 }
             }
+            "--log-format" => {
+                match args.get(i + 1).map(|s| s.as_str()) {
+                    Some("text") => log_format = LogFormat::Text,
+                    Some("json") => log_format = LogFormat::Json,
+                    _ => {
+                        eprintln!("Error: --log-format requires text or json");
+                        std::process::exit(1);
+                    }
+                }
+                i += 2;
+            }
             "--help" | "-h" => {
                 println!("Reader - reads data from CAEN digitizers");
                 println!();
@@ -109,6 +122,9 @@ async fn main() -> anyhow::Result<()> {
                 println!(
                     "  --time-step, -t <NS>         ADC time step in nanoseconds [default: 2.0]"
                 );
+                println!(
+                    "  --log-format <FORMAT>        Log output: text or json [env: DELILA_LOG_FORMAT]"
+                );
                 println!("  --help, -h                   Show this help message");
                 println!();
                 println!("Examples:");
@@ -124,6 +140,16 @@ async fn main() -> anyhow::Result<()> {
         }
     }
 
+    // Initialize tracing (logging)
+    logging::init(
+        log_format,
+        LogContext {
+            component: "reader",
+            source_id: Some(source_id),
+        },
+        &["delila_rs=info"],
+    )?;
+
     // Build configuration from file or CLI arguments
     let config = if let Some(path) = config_path {
         // Load from config file
@@ -172,16 +198,42 @@ async fn main() -> anyhow::Result<()> {
             url: url.clone(),
             data_address: data_address
                 .unwrap_or_else(|| format!("tcp://*:{}", 5555 + source_id as u16)),
+            extra_data_addresses: Vec::new(),
             command_address: command_address
                 .unwrap_or_else(|| format!("tcp://*:{}", 5560 + source_id as u16)),
             source_id,
             firmware: FirmwareType::PSD2,
             module_id: module_id.unwrap_or(source_id as u8),
+            module_map: HashMap::new(),
             read_timeout_ms: 100,
             buffer_size: 1024 * 1024,
             heartbeat_interval_ms: 1000,
             time_step_ns: time_step_ns.unwrap_or(2.0),
+            adc_bits: DEFAULT_ADC_BITS,
             config_file: None, // No config file when using CLI directly
+            apply_defaults: false,
+            strict_validation: false,
+            reconnect_backoff_ms: DEFAULT_RECONNECT_BACKOFF_MS,
+            max_reconnect_attempts: DEFAULT_MAX_RECONNECT_ATTEMPTS,
+            max_message_bytes: DEFAULT_MAX_MESSAGE_BYTES,
+            raw_record_dir: None,
+            dump_raw: false,
+            dump_dir: "./dump".to_string(),
+            is_master: true, // Standalone reader starts its own digitizer
+            decode_channel_capacity: 256,
+            decode_queue_policy: DecodeQueuePolicy::Block,
+            decode_workers: 1,
+            topic_prefix: None,
+            read_core: None,
+            decode_core: None,
+            compress_waveforms: false,
+            wide_energy: false,
+            timestamp_sanity_window_ns: None,
+            drop_timestamp_outliers: false,
+            decoders: DecoderRegistry::default(),
+            socket_options: SocketOptions::default(),
+            recent_events_capacity: DEFAULT_RECENT_EVENTS_CAPACITY,
+            event_filter: EventFilter::default(),
         }
     };
 
@@ -8,11 +8,10 @@
 use std::path::PathBuf;
 
 use clap::Parser;
-use delila_rs::common::{setup_shutdown_with_message, RecorderArgs};
+use delila_rs::common::{logging, setup_shutdown_with_message, LogContext, RecorderArgs};
 use delila_rs::config::Config;
-use delila_rs::recorder::{Recorder, RecorderConfig};
+use delila_rs::recorder::{Recorder, RecorderConfig, DEFAULT_FILENAME_TEMPLATE};
 use tracing::info;
-use tracing_subscriber::EnvFilter;
 
 #[derive(Parser, Debug)]
 #[command(
@@ -26,13 +25,15 @@ struct Args {
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
-    // Initialize tracing (logging)
-    tracing_subscriber::fmt()
-        .with_env_filter(EnvFilter::from_default_env().add_directive("delila_rs=info".parse()?))
-        .init();
-
     let args = Args::parse();
 
+    // Initialize tracing (logging)
+    logging::init(
+        args.recorder.common.log_format,
+        LogContext::component("recorder"),
+        &["delila_rs=info"],
+    )?;
+
     // Load configuration
     let config = Config::load(&args.recorder.common.config_file)?;
     info!(config_file = %args.recorder.common.config_file, "Loaded configuration");
@@ -59,6 +60,77 @@ async fn main() -> anyhow::Result<()> {
             )
         };
 
+    let psd_routing = config
+        .network
+        .recorder
+        .as_ref()
+        .and_then(|r| r.psd_routing.clone());
+    let compression = config
+        .network
+        .recorder
+        .as_ref()
+        .map(|r| r.compression)
+        .unwrap_or_default();
+    let write_checksums = config
+        .network
+        .recorder
+        .as_ref()
+        .is_some_and(|r| r.write_checksums);
+    let wide_energy = config
+        .network
+        .recorder
+        .as_ref()
+        .is_some_and(|r| r.wide_energy);
+    let format = config
+        .network
+        .recorder
+        .as_ref()
+        .map(|r| r.format)
+        .unwrap_or_default();
+    let finish_on_all_eos = config
+        .network
+        .recorder
+        .as_ref()
+        .is_some_and(|r| r.finish_on_all_eos);
+    let expected_source_ids = config
+        .network
+        .recorder
+        .as_ref()
+        .map(|r| r.expected_source_ids.clone())
+        .unwrap_or_default();
+
+    let subscribe_topics = config
+        .network
+        .recorder
+        .as_ref()
+        .map(|r| r.subscribe_topics.clone())
+        .unwrap_or_default();
+    let max_file_events = config
+        .network
+        .recorder
+        .as_ref()
+        .and_then(|r| r.max_file_events);
+    let min_free_space_mb = config
+        .network
+        .recorder
+        .as_ref()
+        .map_or(0, |r| r.min_free_space_mb);
+    let fsync_interval_batches = config
+        .network
+        .recorder
+        .as_ref()
+        .map_or(0, |r| r.fsync_interval_batches);
+    let socket_options = config
+        .network
+        .recorder
+        .as_ref()
+        .map(|r| r.socket_options())
+        .unwrap_or_default();
+    let filename_template = config.network.recorder.as_ref().map_or_else(
+        || DEFAULT_FILENAME_TEMPLATE.to_string(),
+        |r| r.filename_template.clone(),
+    );
+
     // CLI overrides config file
     let recorder_config = RecorderConfig {
         subscribe_address: args.recorder.address.unwrap_or(subscribe_addr),
@@ -66,6 +138,19 @@ async fn main() -> anyhow::Result<()> {
         output_dir: PathBuf::from(args.recorder.output_dir.unwrap_or(out_dir)),
         max_file_size: max_size_mb * 1024 * 1024,
         max_file_duration_secs: max_duration_sec,
+        max_file_events,
+        psd_routing,
+        compression,
+        write_checksums,
+        format,
+        wide_energy,
+        finish_on_all_eos,
+        expected_source_ids,
+        subscribe_topics,
+        min_free_bytes: min_free_space_mb * 1024 * 1024,
+        fsync_interval_batches,
+        socket_options,
+        filename_template,
     };
 
     // Setup shutdown handling
@@ -89,7 +174,16 @@ async fn main() -> anyhow::Result<()> {
         "  Max duration:   {} sec",
         recorder_config.max_file_duration_secs
     );
+    if let Some(max_events) = recorder_config.max_file_events {
+        println!("  Max events:     {} per file", max_events);
+    }
     println!("  Mode:           Raw (unsorted)");
+    if let Some(ref routing) = recorder_config.psd_routing {
+        println!(
+            "  PSD routing:    {} / {}",
+            routing.inside_stream, routing.outside_stream
+        );
+    }
     println!();
     println!("  Press Ctrl+C to stop.");
     println!("========================================");
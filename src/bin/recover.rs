@@ -65,6 +65,11 @@ enum Commands {
         /// Output flat binary path
         #[arg(short, long)]
         output: PathBuf,
+
+        /// Write 32-bit energies (DLDUMP02, 24 bytes/event) instead of the
+        /// legacy 22-byte records, which clamp energies to 16 bits
+        #[arg(long)]
+        wide_energy: bool,
     },
 }
 
@@ -99,8 +104,12 @@ fn main() {
                 std::process::exit(1);
             }
         }
-        Commands::Dump { files, output } => {
-            if let Err(e) = dump_files(&files, &output) {
+        Commands::Dump {
+            files,
+            output,
+            wide_energy,
+        } => {
+            if let Err(e) = dump_files(&files, &output, wide_energy) {
                 eprintln!("Error: {}", e);
                 std::process::exit(1);
             }
@@ -228,8 +237,9 @@ fn recover_file(
     let out_file = File::create(&output)?;
     let mut writer = BufWriter::with_capacity(64 * 1024, out_file);
 
-    // Write header (copy from original)
-    let header = result.header.clone().ok_or("No header found")?;
+    // Write header (copy from original; batches are re-encoded in the current layout)
+    let mut header = result.header.clone().ok_or("No header found")?;
+    header.batch_version = delila_rs::common::MESSAGE_WIRE_VERSION;
     let header_bytes = header
         .to_bytes()
         .map_err(|e| format!("Failed to serialize header: {}", e))?;
@@ -402,13 +412,23 @@ fn list_files(directory: &Path, recursive: bool) -> Result<(), Box<dyn std::erro
     Ok(())
 }
 
-/// Flat binary dump magic
+/// Flat binary dump magic (legacy 16-bit energy records)
 const DUMP_MAGIC: &[u8; 8] = b"DLDUMP01";
 
+/// Flat binary dump magic with 32-bit energy records
+const DUMP_MAGIC_WIDE: &[u8; 8] = b"DLDUMP02";
+
 /// Per-event record size: module(1) + channel(1) + energy(2) + energy_short(2) + flags(8) + timestamp_ns(8) = 22
 const EVENT_RECORD_SIZE: usize = 22;
 
-fn dump_files(files: &[PathBuf], output: &Path) -> Result<(), Box<dyn std::error::Error>> {
+/// Per-event record size with a 32-bit energy (DLDUMP02)
+const EVENT_RECORD_SIZE_WIDE: usize = 24;
+
+fn dump_files(
+    files: &[PathBuf],
+    output: &Path,
+    wide_energy: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
     // First pass: count total events across all files
     let mut total_events = 0u64;
     for path in files {
@@ -442,10 +462,16 @@ fn dump_files(files: &[PathBuf], output: &Path) -> Result<(), Box<dyn std::error
     let mut writer = BufWriter::with_capacity(64 * 1024, out_file);
 
     // Header: magic (8) + n_events (8) = 16 bytes
-    writer.write_all(DUMP_MAGIC)?;
+    let (magic, record_size) = if wide_energy {
+        (DUMP_MAGIC_WIDE, EVENT_RECORD_SIZE_WIDE)
+    } else {
+        (DUMP_MAGIC, EVENT_RECORD_SIZE)
+    };
+    writer.write_all(magic)?;
     writer.write_all(&total_events.to_le_bytes())?;
 
     let mut written = 0u64;
+    let mut clamped = 0u64;
     for path in files {
         let file = File::open(path)?;
         let reader = BufReader::new(file);
@@ -454,10 +480,17 @@ fn dump_files(files: &[PathBuf], output: &Path) -> Result<(), Box<dyn std::error
         for batch_result in data_reader.data_blocks() {
             let batch = batch_result?;
             for ev in &batch.events {
-                // Fixed 22-byte record per event (all Little-Endian)
+                // Fixed 22/24-byte record per event (all Little-Endian)
                 writer.write_all(&[ev.module])?;
                 writer.write_all(&[ev.channel])?;
-                writer.write_all(&ev.energy.to_le_bytes())?;
+                if wide_energy {
+                    writer.write_all(&ev.energy.to_le_bytes())?;
+                } else {
+                    if ev.energy > u16::MAX as u32 {
+                        clamped += 1;
+                    }
+                    writer.write_all(&ev.energy_u16().to_le_bytes())?;
+                }
                 writer.write_all(&ev.energy_short.to_le_bytes())?;
                 writer.write_all(&ev.flags.to_le_bytes())?;
                 writer.write_all(&ev.timestamp_ns.to_le_bytes())?;
@@ -470,10 +503,17 @@ fn dump_files(files: &[PathBuf], output: &Path) -> Result<(), Box<dyn std::error
     writer.get_ref().sync_all()?;
 
     let file_size = std::fs::metadata(output)?.len();
-    let expected_size = 16 + written * EVENT_RECORD_SIZE as u64;
+    let expected_size = 16 + written * record_size as u64;
     println!("  Events written: {}", written);
     println!("  Output size:    {} bytes", file_size);
 
+    if clamped > 0 {
+        eprintln!(
+            "  Warning: {} energies above 65535 clamped (use --wide-energy to keep them)",
+            clamped
+        );
+    }
+
     if file_size != expected_size {
         eprintln!(
             "  Warning: size mismatch (expected {}, got {})",
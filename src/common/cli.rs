@@ -7,12 +7,18 @@
 
 use clap::Parser;
 
+use super::logging::LogFormat;
+
 /// Common arguments shared across all DELILA components
 #[derive(Parser, Debug, Clone)]
 pub struct CommonArgs {
     /// Path to configuration file
     #[arg(short = 'f', long = "config", default_value = "config.toml")]
     pub config_file: String,
+
+    /// Log output format
+    #[arg(long, env = "DELILA_LOG_FORMAT", value_enum, default_value_t = LogFormat::Text)]
+    pub log_format: LogFormat,
 }
 
 /// Arguments for source components (Reader, Emulator)
@@ -91,6 +97,14 @@ pub struct DataSinkArgs {
     /// ZMQ address to subscribe to
     #[arg(short = 'a', long = "address")]
     pub address: Option<String>,
+
+    /// Fraction of events to process for quick-look (rates count every event)
+    #[arg(long, default_value = "1.0")]
+    pub sample_ratio: f64,
+
+    /// Append processed events to this CSV file
+    #[arg(long)]
+    pub csv_output: Option<std::path::PathBuf>,
 }
 
 /// Arguments for Operator (Web UI / Control API)
@@ -140,6 +154,13 @@ mod tests {
         assert_eq!(args.config_file, "custom.toml");
     }
 
+    #[test]
+    fn test_common_args_log_format() {
+        let args = CommonArgs::try_parse_from(["test", "--log-format", "json"]).unwrap();
+        assert_eq!(args.log_format, LogFormat::Json);
+        assert!(CommonArgs::try_parse_from(["test", "--log-format", "xml"]).is_err());
+    }
+
     #[test]
     fn test_common_args_long_config() {
         let args = CommonArgs::try_parse_from(["test", "--config", "my_config.toml"]).unwrap();
@@ -17,21 +17,29 @@
 //!       │                      │                  │
 //!       │                      │ Start            │
 //!       │                      ▼                  │
-//!       │                ┌──────────┐             │
-//!       │                │ Running  │ ────────────┘
-//!       │                └──────────┘
-//!       │                      │
+//!       │                ┌──────────┐   Pause   ┌────────┐
+//!       │                │ Running  │ ────────► │ Paused │
+//!       │                │          │ ◄──────── │        │
+//!       │                └──────────┘   Resume  └────────┘
+//!       │                      │          (Stop from either)
 //!       │                      │ (on error)
 //!       │                      ▼
 //!       │                ┌──────────┐
 //!       └─────────────── │  Error   │
-//!                        └──────────┘
+//!        Reset /         └──────────┘
+//!        RecoverError
 //! ```
+//!
+//! `RecoverError` is the deliberate way out of `Error`: unlike `Reset` it is
+//! rejected in every other state.
+//!
+//! `Paused` keeps the run open: sources stop producing data but the digitizer
+//! stays armed and no EOS is sent, so `Resume` continues the same run.
 
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
 
-/// Component state (Phase B: 5-state machine)
+/// Component state (Phase B state machine)
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default, ToSchema)]
 pub enum ComponentState {
     /// Initial state, no configuration loaded
@@ -43,6 +51,8 @@ pub enum ComponentState {
     Armed,
     /// Actively acquiring/processing data
     Running,
+    /// Run in progress, data flow suspended (hardware stays armed)
+    Paused,
     /// Recoverable error occurred
     Error,
 }
@@ -58,27 +68,45 @@ impl ComponentState {
             | (Configured, Armed)    // Arm
             | (Armed, Running)       // Start
             | (Running, Configured)  // Stop (return to Configured for quick restart)
+            | (Running, Paused)      // Pause
+            | (Paused, Running)      // Resume
+            | (Paused, Configured)   // Stop while paused
             // Reset from any state
             | (Configured, Idle)
             | (Armed, Idle)
             | (Running, Idle)
+            | (Paused, Idle)
             | (Error, Idle)
             // Error can happen from any active state
             | (Configured, Error)
             | (Armed, Error)
             | (Running, Error)
+            | (Paused, Error)
         )
     }
 
+    /// Whether a run is in progress (Running or Paused)
+    pub fn in_run(&self) -> bool {
+        matches!(self, ComponentState::Running | ComponentState::Paused)
+    }
+
     /// Get valid commands for current state
     pub fn valid_commands(&self) -> &'static [&'static str] {
         use ComponentState::*;
         match self {
-            Idle => &["Configure", "Detect", "GetStatus"],
-            Configured => &["Arm", "Reset", "GetStatus"],
-            Armed => &["Start", "Reset", "GetStatus"],
-            Running => &["Stop", "GetStatus"],
-            Error => &["Reset", "GetStatus"],
+            Idle => &["Configure", "Detect", "GetStatus", "GetConfig"],
+            Configured => &[
+                "Arm",
+                "SetTriggerMode",
+                "ReconfigureDigitizer",
+                "Reset",
+                "GetStatus",
+                "GetConfig",
+            ],
+            Armed => &["Start", "Reset", "GetStatus", "GetConfig"],
+            Running => &["Pause", "Stop", "GetStatus", "GetConfig"],
+            Paused => &["Resume", "Stop", "GetStatus", "GetConfig"],
+            Error => &["RecoverError", "Reset", "GetStatus", "GetConfig"],
         }
     }
 }
@@ -90,6 +118,7 @@ impl std::fmt::Display for ComponentState {
             ComponentState::Configured => write!(f, "Configured"),
             ComponentState::Armed => write!(f, "Armed"),
             ComponentState::Running => write!(f, "Running"),
+            ComponentState::Paused => write!(f, "Paused"),
             ComponentState::Error => write!(f, "Error"),
         }
     }
@@ -126,6 +155,80 @@ pub struct EmulatorRuntimeConfig {
     pub waveform_samples: u32,
 }
 
+/// Smallest `max_file_size` accepted by `UpdateRecorderTuning` (1 MiB)
+pub const MIN_RECORDER_FILE_SIZE: u64 = 1024 * 1024;
+
+/// Recorder write thresholds adjustable at runtime (`UpdateRecorderTuning`)
+///
+/// Unset fields keep their current value. Rotation limits are checked again
+/// on the next batch, so they apply to the open file as well.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct RecorderTuning {
+    /// Rotate after this many bytes (at least 1 MiB)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_file_size: Option<u64>,
+    /// Rotate after this many seconds (at least 1)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_file_duration_secs: Option<u64>,
+    /// Rotate after this many events (0 = unlimited)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_file_events: Option<u64>,
+    /// Minimum free disk space to open a new file (0 = no check)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_free_bytes: Option<u64>,
+    /// fsync the open file every N batches (0 = only on close)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fsync_interval_batches: Option<u64>,
+}
+
+impl RecorderTuning {
+    /// Check that every set value is in range
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(size) = self.max_file_size {
+            if size < MIN_RECORDER_FILE_SIZE {
+                return Err(format!(
+                    "max_file_size must be at least {} bytes, got {}",
+                    MIN_RECORDER_FILE_SIZE, size
+                ));
+            }
+        }
+        if self.max_file_duration_secs == Some(0) {
+            return Err("max_file_duration_secs must be > 0".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Digitizer trigger mode for `SetTriggerMode` (Reader-only)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TriggerMode {
+    /// Channels trigger on their own discriminator
+    SelfTrigger,
+    /// Trigger from the front-panel TRG-IN
+    External,
+    /// Trigger by software command only
+    Software,
+}
+
+impl std::fmt::Display for TriggerMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TriggerMode::SelfTrigger => write!(f, "SelfTrigger"),
+            TriggerMode::External => write!(f, "External"),
+            TriggerMode::Software => write!(f, "Software"),
+        }
+    }
+}
+
+/// Digitizer configuration for `ReconfigureDigitizer` (Reader-only)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum DigitizerConfigSource {
+    /// Path of a digitizer JSON file, read by the Reader
+    Path(String),
+    /// The digitizer configuration itself (same schema as the file)
+    Json(serde_json::Value),
+}
+
 /// Commands sent from controller to components
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum Command {
@@ -136,12 +239,21 @@ pub enum Command {
     /// Begin data acquisition (Armed → Running)
     /// run_number is passed at start time to allow changing it without re-configuring hardware
     Start { run_number: u32 },
-    /// Stop data acquisition (Running → Configured)
+    /// Stop data acquisition (Running/Paused → Configured)
     Stop,
+    /// Suspend data flow without ending the run (Running → Paused)
+    Pause,
+    /// Continue a paused run (Paused → Running)
+    Resume,
     /// Reset to initial state (Any → Idle)
     Reset,
+    /// Acknowledge an error and return to initial state (Error → Idle only)
+    RecoverError,
     /// Query current status
     GetStatus,
+    /// Query the component's effective configuration (any state)
+    /// Returned as JSON in `CommandResponse::data`. Does not change state.
+    GetConfig,
     /// Update emulator runtime configuration (Emulator-specific)
     /// Can be sent in any state, takes effect on next batch generation
     UpdateEmulatorConfig(EmulatorRuntimeConfig),
@@ -149,6 +261,21 @@ pub enum Command {
     /// Temporarily connects to digitizer, reads DeviceInfo, and disconnects.
     /// Does not change state.
     Detect,
+    /// Switch digitizer trigger mode (Reader-only, Configured state)
+    /// Does not change state.
+    SetTriggerMode(TriggerMode),
+    /// Validate and apply a digitizer configuration without Reset (Reader-only, Configured state)
+    /// Lasts until the next Configure, which applies `config_file` again. Does not change state.
+    ReconfigureDigitizer(DigitizerConfigSource),
+    /// Adjust Recorder write thresholds (Recorder-only, any state)
+    /// Out-of-range values reject the whole update. Does not change state.
+    UpdateRecorderTuning(RecorderTuning),
+    /// Flush and fsync the Recorder's open file without rotating (Recorder-only, any state)
+    /// Queued behind batches already received. Does not change state.
+    Flush,
+    /// Query up to n most recently decoded events, oldest first (Reader-only, any state)
+    /// Returned as a JSON array in `CommandResponse::data`. Does not change state.
+    GetRecentEvents(usize),
 }
 
 impl std::fmt::Display for Command {
@@ -158,12 +285,26 @@ impl std::fmt::Display for Command {
             Command::Arm => write!(f, "Arm"),
             Command::Start { run_number } => write!(f, "Start(run={})", run_number),
             Command::Stop => write!(f, "Stop"),
+            Command::Pause => write!(f, "Pause"),
+            Command::Resume => write!(f, "Resume"),
             Command::Reset => write!(f, "Reset"),
+            Command::RecoverError => write!(f, "RecoverError"),
             Command::GetStatus => write!(f, "GetStatus"),
+            Command::GetConfig => write!(f, "GetConfig"),
             Command::UpdateEmulatorConfig(cfg) => {
                 write!(f, "UpdateEmulatorConfig(events={})", cfg.events_per_batch)
             }
             Command::Detect => write!(f, "Detect"),
+            Command::SetTriggerMode(mode) => write!(f, "SetTriggerMode({})", mode),
+            Command::ReconfigureDigitizer(DigitizerConfigSource::Path(path)) => {
+                write!(f, "ReconfigureDigitizer({})", path)
+            }
+            Command::ReconfigureDigitizer(DigitizerConfigSource::Json(_)) => {
+                write!(f, "ReconfigureDigitizer(inline)")
+            }
+            Command::UpdateRecorderTuning(_) => write!(f, "UpdateRecorderTuning"),
+            Command::Flush => write!(f, "Flush"),
+            Command::GetRecentEvents(n) => write!(f, "GetRecentEvents({})", n),
         }
     }
 }
@@ -189,6 +330,9 @@ pub struct CommandResponse {
     /// Generic data payload (e.g., DeviceInfo from Detect)
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub data: Option<serde_json::Value>,
+    /// Component build version (for status queries)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
 }
 
 impl CommandResponse {
@@ -202,6 +346,7 @@ impl CommandResponse {
             error_code: None,
             metrics: None,
             data: None,
+            version: None,
         }
     }
 
@@ -219,6 +364,7 @@ impl CommandResponse {
             error_code: None,
             metrics: None,
             data: None,
+            version: None,
         }
     }
 
@@ -232,6 +378,7 @@ impl CommandResponse {
             error_code: None,
             metrics: None,
             data: None,
+            version: None,
         }
     }
 
@@ -249,6 +396,7 @@ impl CommandResponse {
             error_code: Some(error_code),
             metrics: None,
             data: None,
+            version: None,
         }
     }
 
@@ -316,6 +464,48 @@ mod tests {
         }
     }
 
+    #[test]
+    fn recorder_tuning_roundtrip_and_validation() {
+        let tuning = RecorderTuning {
+            max_file_size: Some(MIN_RECORDER_FILE_SIZE),
+            fsync_interval_batches: Some(10),
+            ..Default::default()
+        };
+        let bytes = Command::UpdateRecorderTuning(tuning.clone())
+            .to_json()
+            .unwrap();
+        match Command::from_json(&bytes).unwrap() {
+            Command::UpdateRecorderTuning(decoded) => assert_eq!(decoded, tuning),
+            other => panic!("unexpected command: {}", other),
+        }
+        assert!(tuning.validate().is_ok());
+
+        let too_small = RecorderTuning {
+            max_file_size: Some(MIN_RECORDER_FILE_SIZE - 1),
+            ..Default::default()
+        };
+        assert!(too_small.validate().is_err());
+        let zero_duration = RecorderTuning {
+            max_file_duration_secs: Some(0),
+            ..Default::default()
+        };
+        assert!(zero_duration.validate().is_err());
+    }
+
+    #[test]
+    fn get_config_roundtrip() {
+        let bytes = Command::GetConfig.to_json().unwrap();
+        assert!(matches!(
+            Command::from_json(&bytes).unwrap(),
+            Command::GetConfig
+        ));
+
+        let resp = CommandResponse::success(ComponentState::Idle, "Effective configuration")
+            .with_data(serde_json::json!({ "output_dir": "./data" }));
+        let decoded = CommandResponse::from_json(&resp.to_json().unwrap()).unwrap();
+        assert_eq!(decoded.data.unwrap()["output_dir"], "./data");
+    }
+
     #[test]
     fn response_json_roundtrip() {
         let resp = CommandResponse::success(ComponentState::Running, "Started");
@@ -359,6 +549,7 @@ mod tests {
         assert_eq!(format!("{}", ComponentState::Configured), "Configured");
         assert_eq!(format!("{}", ComponentState::Armed), "Armed");
         assert_eq!(format!("{}", ComponentState::Running), "Running");
+        assert_eq!(format!("{}", ComponentState::Paused), "Paused");
         assert_eq!(format!("{}", ComponentState::Error), "Error");
     }
 
@@ -380,8 +571,26 @@ mod tests {
             "Start(run=1)"
         );
         assert_eq!(format!("{}", Command::Stop), "Stop");
+        assert_eq!(format!("{}", Command::Pause), "Pause");
+        assert_eq!(format!("{}", Command::Resume), "Resume");
         assert_eq!(format!("{}", Command::Reset), "Reset");
         assert_eq!(format!("{}", Command::GetStatus), "GetStatus");
+        assert_eq!(format!("{}", Command::GetConfig), "GetConfig");
+        assert_eq!(
+            format!("{}", Command::SetTriggerMode(TriggerMode::External)),
+            "SetTriggerMode(External)"
+        );
+        assert_eq!(
+            format!(
+                "{}",
+                Command::ReconfigureDigitizer(DigitizerConfigSource::Path("dig0.json".to_string()))
+            ),
+            "ReconfigureDigitizer(dig0.json)"
+        );
+        assert_eq!(
+            format!("{}", Command::GetRecentEvents(10)),
+            "GetRecentEvents(10)"
+        );
     }
 
     #[test]
@@ -412,6 +621,28 @@ mod tests {
         assert!(!Error.can_transition_to(Running)); // Must Reset first
     }
 
+    #[test]
+    fn pause_transitions() {
+        use ComponentState::*;
+
+        assert!(Running.can_transition_to(Paused));
+        assert!(Paused.can_transition_to(Running));
+        assert!(Paused.can_transition_to(Configured));
+        assert!(Paused.can_transition_to(Idle));
+        assert!(Paused.can_transition_to(Error));
+
+        // Only a running acquisition can be paused
+        assert!(!Idle.can_transition_to(Paused));
+        assert!(!Configured.can_transition_to(Paused));
+        assert!(!Armed.can_transition_to(Paused));
+        assert!(!Error.can_transition_to(Paused));
+        assert!(!Paused.can_transition_to(Armed));
+
+        assert!(Running.in_run());
+        assert!(Paused.in_run());
+        assert!(!Armed.in_run());
+    }
+
     #[test]
     fn valid_commands_per_state() {
         use ComponentState::*;
@@ -421,14 +652,25 @@ mod tests {
 
         assert!(Configured.valid_commands().contains(&"Arm"));
         assert!(Configured.valid_commands().contains(&"Reset"));
+        assert!(Configured
+            .valid_commands()
+            .contains(&"ReconfigureDigitizer"));
+        assert!(!Running.valid_commands().contains(&"ReconfigureDigitizer"));
 
         assert!(Armed.valid_commands().contains(&"Start"));
         assert!(!Armed.valid_commands().contains(&"Configure"));
 
         assert!(Running.valid_commands().contains(&"Stop"));
         assert!(!Running.valid_commands().contains(&"Start"));
+        assert!(Running.valid_commands().contains(&"Pause"));
+
+        assert!(Paused.valid_commands().contains(&"Resume"));
+        assert!(Paused.valid_commands().contains(&"Stop"));
+        assert!(!Paused.valid_commands().contains(&"Pause"));
 
         assert!(Error.valid_commands().contains(&"Reset"));
+        assert!(Error.valid_commands().contains(&"RecoverError"));
+        assert!(Error.valid_commands().contains(&"GetConfig"));
         assert!(!Error.valid_commands().contains(&"Start"));
     }
 }
@@ -0,0 +1,118 @@
+//! End-of-stream tracking for downstream components
+//!
+//! A run that ends upstream is signalled by one `EndOfStream` message per
+//! source. [`EosTracker`] collects them against the configured source IDs so
+//! a sink can finalize once the last source has finished, and
+//! [`finish_run`] moves the component from Running back to Configured as if
+//! a Stop command had been received.
+
+use std::collections::BTreeSet;
+
+use tokio::sync::{watch, Mutex};
+use tracing::info;
+
+use super::{ComponentSharedState, ComponentState};
+
+/// Set of sources that have sent EOS in the current run
+#[derive(Debug, Clone, Default)]
+pub struct EosTracker {
+    expected: BTreeSet<u32>,
+    received: BTreeSet<u32>,
+}
+
+impl EosTracker {
+    /// Track EOS from the given source IDs
+    ///
+    /// With no expected sources, the first EOS completes the run.
+    pub fn new(expected: impl IntoIterator<Item = u32>) -> Self {
+        Self {
+            expected: expected.into_iter().collect(),
+            received: BTreeSet::new(),
+        }
+    }
+
+    /// Record EOS from `source_id`; returns true once all sources have signaled
+    pub fn record(&mut self, source_id: u32) -> bool {
+        self.received.insert(source_id);
+        self.is_complete()
+    }
+
+    /// Whether every expected source has sent EOS
+    pub fn is_complete(&self) -> bool {
+        if self.expected.is_empty() {
+            return !self.received.is_empty();
+        }
+        self.expected.is_subset(&self.received)
+    }
+
+    /// Expected sources that have not sent EOS yet
+    pub fn pending(&self) -> Vec<u32> {
+        self.expected.difference(&self.received).copied().collect()
+    }
+
+    /// Forget received EOS (new run)
+    pub fn reset(&mut self) {
+        self.received.clear();
+    }
+}
+
+/// Transition a running component back to Configured after the run ended upstream
+///
+/// Returns false if the component was no longer Running (e.g. a Stop command
+/// arrived first).
+pub async fn finish_run(
+    shared_state: &Mutex<ComponentSharedState>,
+    state_tx: &watch::Sender<ComponentState>,
+    component_name: &str,
+) -> bool {
+    let mut state = shared_state.lock().await;
+    if state.state != ComponentState::Running {
+        return false;
+    }
+    state.state = ComponentState::Configured;
+    let _ = state_tx.send(ComponentState::Configured);
+    info!(
+        component = component_name,
+        "All sources sent EOS - run finished"
+    );
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_complete_after_last_source() {
+        let mut tracker = EosTracker::new([0, 1, 2]);
+        assert!(!tracker.record(1));
+        assert!(!tracker.record(1));
+        assert!(!tracker.record(7));
+        assert!(!tracker.record(0));
+        assert_eq!(tracker.pending(), vec![2]);
+        assert!(tracker.record(2));
+
+        tracker.reset();
+        assert!(!tracker.is_complete());
+        assert_eq!(tracker.pending(), vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn test_no_expected_sources_completes_on_first_eos() {
+        let mut tracker = EosTracker::new([]);
+        assert!(!tracker.is_complete());
+        assert!(tracker.record(5));
+    }
+
+    #[tokio::test]
+    async fn test_finish_run_only_from_running() {
+        let shared = Mutex::new(ComponentSharedState::new());
+        let (state_tx, state_rx) = watch::channel(ComponentState::Idle);
+        assert!(!finish_run(&shared, &state_tx, "Test").await);
+
+        shared.lock().await.state = ComponentState::Running;
+        assert!(finish_run(&shared, &state_tx, "Test").await);
+        assert_eq!(shared.lock().await.state, ComponentState::Configured);
+        assert_eq!(*state_rx.borrow(), ComponentState::Configured);
+    }
+}
@@ -0,0 +1,328 @@
+//! Size-aware batch splitting for the publish path
+//!
+//! Waveform-heavy batches can grow to hundreds of megabytes, which stalls the
+//! ZMQ pipeline and blows up receive buffers downstream. Sources therefore cap
+//! the serialized size of a single message at `max_message_bytes` and split
+//! larger batches into fragments.
+//!
+//! # Split protocol
+//!
+//! - Every fragment is an ordinary `Message::Data(EventDataBatch)`.
+//! - All fragments of one batch carry the **same** `source_id`,
+//!   `sequence_number`, `epoch` and `timestamp` as the original batch.
+//! - Each fragment carries `fragment = Some(BatchFragment { index, count })`,
+//!   with `index` running `0..count` in publish order. Events keep their
+//!   original order across fragments.
+//! - Unsplit batches have `fragment = None` (nil on the wire, keeping the
+//!   trailing `waveform_encoding` field in place); frames from before
+//!   fragmentation omit it and still decode.
+//! - A single event larger than the limit is sent alone in its own fragment
+//!   (events are never cut).
+//!
+//! Consumers that treat batches as event containers (Merger, Recorder,
+//! Monitor, DataSink) may process fragments independently: sequence gap
+//! detection sees a repeated sequence number, not a gap. Consumers that need
+//! the original batch use [`BatchReassembler`].
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use super::{EventDataBatch, Message};
+
+/// Default upper bound for a single serialized message (64 MiB)
+pub const DEFAULT_MAX_MESSAGE_BYTES: usize = 64 * 1024 * 1024;
+
+/// Bytes reserved for the message envelope and batch header when packing fragments
+const ENVELOPE_OVERHEAD: usize = 64;
+
+/// Position of a fragment within a split batch
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BatchFragment {
+    /// Zero-based fragment index
+    pub index: u32,
+    /// Total number of fragments for this sequence number
+    pub count: u32,
+}
+
+impl EventDataBatch {
+    /// Split the batch so that each part serializes to at most `max_bytes`
+    ///
+    /// Returns the batch unchanged (as a single element) if it already fits,
+    /// if `max_bytes` is 0 (splitting disabled), or if it holds at most one event.
+    pub fn split_by_size(self, max_bytes: usize) -> Result<Vec<Self>, rmp_serde::encode::Error> {
+        if max_bytes == 0 || self.events.len() <= 1 {
+            return Ok(vec![self]);
+        }
+        if rmp_serde::to_vec(&self)?.len() + ENVELOPE_OVERHEAD <= max_bytes {
+            return Ok(vec![self]);
+        }
+
+        let budget = max_bytes.saturating_sub(ENVELOPE_OVERHEAD);
+        let mut groups: Vec<Vec<_>> = Vec::new();
+        let mut current = Vec::new();
+        let mut current_bytes = 0;
+
+        for event in self.events {
+            let size = rmp_serde::to_vec(&event)?.len();
+            if !current.is_empty() && current_bytes + size > budget {
+                groups.push(std::mem::take(&mut current));
+                current_bytes = 0;
+            }
+            current_bytes += size;
+            current.push(event);
+        }
+        if !current.is_empty() {
+            groups.push(current);
+        }
+
+        let count = groups.len() as u32;
+        Ok(groups
+            .into_iter()
+            .enumerate()
+            .map(|(index, events)| Self {
+                source_id: self.source_id,
+                sequence_number: self.sequence_number,
+                timestamp: self.timestamp,
+                events,
+                fragment: (count > 1).then_some(BatchFragment {
+                    index: index as u32,
+                    count,
+                }),
+                epoch: self.epoch,
+                waveform_encoding: self.waveform_encoding,
+            })
+            .collect())
+    }
+
+    /// Check if this batch is one fragment of a split batch
+    pub fn is_fragment(&self) -> bool {
+        self.fragment.is_some()
+    }
+}
+
+/// Serialize a message, splitting oversized data batches into fragments
+///
+/// Non-data messages are always returned as a single buffer.
+pub fn encode_with_limit(
+    message: &Message,
+    max_bytes: usize,
+) -> Result<Vec<Vec<u8>>, rmp_serde::encode::Error> {
+    let bytes = message.to_msgpack()?;
+    let batch = match message {
+        Message::Data(batch) if max_bytes > 0 && bytes.len() > max_bytes => batch,
+        _ => return Ok(vec![bytes]),
+    };
+
+    batch
+        .clone()
+        .split_by_size(max_bytes)?
+        .into_iter()
+        .map(|part| Message::Data(part).to_msgpack())
+        .collect()
+}
+
+/// Collects fragments back into complete batches
+///
+/// Partial batches are keyed by `(source_id, sequence_number)`. When a source
+/// moves on to a newer sequence number, its incomplete older batches are
+/// dropped (a fragment was lost in transit).
+#[derive(Debug, Default)]
+pub struct BatchReassembler {
+    pending: HashMap<(u32, u64), Vec<Option<EventDataBatch>>>,
+    dropped: u64,
+}
+
+impl BatchReassembler {
+    /// Create an empty reassembler
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feed a received batch; returns a complete batch when one is available
+    ///
+    /// Unfragmented batches are returned immediately.
+    pub fn push(&mut self, batch: EventDataBatch) -> Option<EventDataBatch> {
+        let Some(fragment) = batch.fragment else {
+            return Some(batch);
+        };
+        if fragment.count == 0 || fragment.index >= fragment.count {
+            warn!(
+                source_id = batch.source_id,
+                seq = batch.sequence_number,
+                index = fragment.index,
+                count = fragment.count,
+                "Invalid fragment header, discarding"
+            );
+            self.dropped += 1;
+            return None;
+        }
+
+        let (source_id, seq) = (batch.source_id, batch.sequence_number);
+        let stale: Vec<_> = self
+            .pending
+            .keys()
+            .filter(|(s, q)| *s == source_id && *q < seq)
+            .copied()
+            .collect();
+        for key in stale {
+            warn!(
+                source_id = key.0,
+                seq = key.1,
+                "Incomplete fragmented batch dropped"
+            );
+            self.pending.remove(&key);
+            self.dropped += 1;
+        }
+
+        let slots = self
+            .pending
+            .entry((source_id, seq))
+            .or_insert_with(|| vec![None; fragment.count as usize]);
+        if slots.len() != fragment.count as usize {
+            warn!(source_id, seq, "Fragment count mismatch, discarding batch");
+            self.pending.remove(&(source_id, seq));
+            self.dropped += 1;
+            return None;
+        }
+        slots[fragment.index as usize] = Some(batch);
+
+        if slots.iter().any(Option::is_none) {
+            return None;
+        }
+
+        let parts = self.pending.remove(&(source_id, seq))?;
+        let mut parts = parts.into_iter().flatten();
+        let mut whole = parts.next()?;
+        for part in parts {
+            whole.events.extend(part.events);
+        }
+        whole.fragment = None;
+        Some(whole)
+    }
+
+    /// Number of batches with fragments still outstanding
+    pub fn pending(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Number of incomplete or malformed batches discarded so far
+    pub fn dropped(&self) -> u64 {
+        self.dropped
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::{EventData, MessageHeader, Waveform};
+
+    fn waveform_batch(events: usize, samples: usize) -> EventDataBatch {
+        let mut batch = EventDataBatch::new(7, 42);
+        for i in 0..events {
+            let wf = Waveform {
+                analog_probe1: vec![i as i16; samples],
+                analog_probe2: vec![0; samples],
+                digital_probe1: vec![1; samples],
+                digital_probe2: vec![],
+                digital_probe3: vec![],
+                digital_probe4: vec![],
+                time_resolution: 1,
+                trigger_threshold: 100,
+                packed_analog: Vec::new(),
+            };
+            batch.push(EventData::with_waveform(
+                0,
+                (i % 16) as u8,
+                i as u32,
+                0,
+                i as f64,
+                0,
+                wf,
+            ));
+        }
+        batch
+    }
+
+    #[test]
+    fn test_oversized_waveform_batch_split_and_reassembled() {
+        let batch = waveform_batch(20, 4096);
+        let original = batch.clone();
+        let max_bytes = 64 * 1024;
+        assert!(Message::Data(batch.clone()).to_msgpack().unwrap().len() > max_bytes);
+
+        let encoded = encode_with_limit(&Message::Data(batch), max_bytes).unwrap();
+        assert!(encoded.len() > 1);
+
+        let mut reassembler = BatchReassembler::new();
+        let mut complete = None;
+        for (i, bytes) in encoded.iter().enumerate() {
+            assert!(bytes.len() <= max_bytes);
+            // Merger-style zero-copy header parse still works on fragments
+            match MessageHeader::parse(bytes) {
+                Some(MessageHeader::Data {
+                    source_id,
+                    sequence_number,
+                    ..
+                }) => {
+                    assert_eq!(source_id, 7);
+                    assert_eq!(sequence_number, 42);
+                }
+                other => panic!("Expected Data header, got {:?}", other),
+            }
+
+            let Message::Data(part) = Message::from_msgpack(bytes).unwrap() else {
+                panic!("Expected Data message");
+            };
+            let fragment = part.fragment.unwrap();
+            assert_eq!(fragment.index, i as u32);
+            assert_eq!(fragment.count, encoded.len() as u32);
+
+            let result = reassembler.push(part);
+            if i + 1 < encoded.len() {
+                assert!(result.is_none());
+            } else {
+                complete = result;
+            }
+        }
+
+        let complete = complete.expect("batch should be reassembled");
+        assert_eq!(complete.sequence_number, original.sequence_number);
+        assert_eq!(complete.fragment, None);
+        assert_eq!(complete.events, original.events);
+        assert_eq!(reassembler.pending(), 0);
+    }
+
+    #[test]
+    fn test_small_batch_not_split() {
+        let batch = waveform_batch(2, 8);
+        let bytes = Message::Data(batch.clone()).to_msgpack().unwrap();
+
+        let encoded = encode_with_limit(&Message::Data(batch.clone()), 1024 * 1024).unwrap();
+        assert_eq!(encoded, vec![bytes]);
+
+        let parts = batch.split_by_size(0).unwrap();
+        assert_eq!(parts.len(), 1);
+        assert!(!parts[0].is_fragment());
+    }
+
+    #[test]
+    fn test_reassembler_drops_incomplete_batch() {
+        let parts = waveform_batch(10, 2048).split_by_size(16 * 1024).unwrap();
+        assert!(parts.len() > 1);
+
+        let mut reassembler = BatchReassembler::new();
+        assert!(reassembler.push(parts[0].clone()).is_none());
+        assert_eq!(reassembler.pending(), 1);
+
+        // Next sequence arrives before the rest of seq 42
+        let mut next = waveform_batch(1, 8);
+        next.sequence_number = 43;
+        assert!(reassembler.push(next).is_some());
+        let mut next_frag = parts[1].clone();
+        next_frag.sequence_number = 44;
+        assert!(reassembler.push(next_frag).is_none());
+        assert_eq!(reassembler.dropped(), 1);
+    }
+}
@@ -0,0 +1,269 @@
+//! Log subscriber setup shared by the component binaries
+//!
+//! `text` is the human-readable `tracing_subscriber` format. `json` writes
+//! one object per line for log aggregators:
+//!
+//! ```text
+//! {"timestamp":"2026-10-18T09:12:01.123456Z","level":"INFO","target":"delila_rs::merger",
+//!  "component":"merger","source_id":3,"message":"Received EOS","fields":{"seq":12}}
+//! ```
+//!
+//! `component` is set by the binary; `source_id` comes from the binary for
+//! sources and otherwise from a `source_id` field of the event. Other event
+//! fields go under `fields`, enclosing span names under `spans`. The format
+//! is chosen with `--log-format` or the `DELILA_LOG_FORMAT` environment
+//! variable; `RUST_LOG` filters as before.
+
+use std::fmt;
+
+use serde_json::{Map, Value};
+use thiserror::Error;
+use tracing::field::{Field, Visit};
+use tracing::{Event, Subscriber};
+use tracing_subscriber::fmt::format::Writer;
+use tracing_subscriber::fmt::{FmtContext, FormatEvent, FormatFields, MakeWriter};
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::EnvFilter;
+
+/// Environment variable selecting the log format
+pub const LOG_FORMAT_ENV: &str = "DELILA_LOG_FORMAT";
+
+/// Output format of the log subscriber
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum LogFormat {
+    /// Human-readable lines (default)
+    #[default]
+    Text,
+    /// One JSON object per line
+    Json,
+}
+
+impl LogFormat {
+    /// Format from [`LOG_FORMAT_ENV`]; unset or unknown values select `text`
+    pub fn from_env() -> Self {
+        std::env::var(LOG_FORMAT_ENV)
+            .ok()
+            .and_then(|value| <Self as clap::ValueEnum>::from_str(value.trim(), true).ok())
+            .unwrap_or_default()
+    }
+}
+
+/// Fields added to every JSON log line
+#[derive(Debug, Clone, Copy)]
+pub struct LogContext {
+    /// Component name, e.g. "reader"
+    pub component: &'static str,
+    /// Source ID of a Reader or Emulator
+    pub source_id: Option<u32>,
+}
+
+impl LogContext {
+    /// Context of a component without a source ID
+    pub fn component(component: &'static str) -> Self {
+        Self {
+            component,
+            source_id: None,
+        }
+    }
+}
+
+/// Log setup errors
+#[derive(Error, Debug)]
+pub enum LoggingError {
+    #[error("Invalid log directive: {0}")]
+    Directive(#[from] tracing_subscriber::filter::ParseError),
+
+    #[error("Global log subscriber already set: {0}")]
+    AlreadySet(#[from] tracing::subscriber::SetGlobalDefaultError),
+}
+
+/// Install the global log subscriber
+///
+/// `directives` are added to the `RUST_LOG` filter (e.g. `"delila_rs=info"`).
+pub fn init(
+    format: LogFormat,
+    context: LogContext,
+    directives: &[&str],
+) -> Result<(), LoggingError> {
+    let subscriber = build(format, context, directives, std::io::stdout)?;
+    tracing::subscriber::set_global_default(subscriber)?;
+    Ok(())
+}
+
+/// Subscriber writing to `writer`, not installed
+fn build<W>(
+    format: LogFormat,
+    context: LogContext,
+    directives: &[&str],
+    writer: W,
+) -> Result<Box<dyn Subscriber + Send + Sync>, LoggingError>
+where
+    W: for<'a> MakeWriter<'a> + Send + Sync + 'static,
+{
+    let mut filter = EnvFilter::from_default_env();
+    for directive in directives {
+        filter = filter.add_directive(directive.parse()?);
+    }
+    let builder = tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(writer);
+    Ok(match format {
+        LogFormat::Text => Box::new(builder.finish()),
+        LogFormat::Json => Box::new(builder.event_format(JsonFormat { context }).finish()),
+    })
+}
+
+/// Event formatter for [`LogFormat::Json`]
+struct JsonFormat {
+    context: LogContext,
+}
+
+impl<S, N> FormatEvent<S, N> for JsonFormat
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+{
+    fn format_event(
+        &self,
+        ctx: &FmtContext<'_, S, N>,
+        mut writer: Writer<'_>,
+        event: &Event<'_>,
+    ) -> fmt::Result {
+        let meta = event.metadata();
+        let mut fields = JsonFields::default();
+        event.record(&mut fields);
+        let mut fields = fields.0;
+
+        let mut line = Map::new();
+        line.insert(
+            "timestamp".into(),
+            chrono::Utc::now()
+                .to_rfc3339_opts(chrono::SecondsFormat::Micros, true)
+                .into(),
+        );
+        line.insert("level".into(), meta.level().as_str().into());
+        line.insert("target".into(), meta.target().into());
+        line.insert("component".into(), self.context.component.into());
+        match self.context.source_id {
+            Some(source_id) => {
+                line.insert("source_id".into(), source_id.into());
+            }
+            None => {
+                if let Some(source_id) = fields.remove("source_id") {
+                    line.insert("source_id".into(), source_id);
+                }
+            }
+        }
+        if let Some(message) = fields.remove("message") {
+            line.insert("message".into(), message);
+        }
+        if let Some(scope) = ctx.event_scope() {
+            let spans: Vec<Value> = scope.from_root().map(|span| span.name().into()).collect();
+            line.insert("spans".into(), spans.into());
+        }
+        if !fields.is_empty() {
+            line.insert("fields".into(), Value::Object(fields));
+        }
+
+        writeln!(writer, "{}", Value::Object(line))
+    }
+}
+
+/// Event fields as JSON values
+#[derive(Default)]
+struct JsonFields(Map<String, Value>);
+
+impl Visit for JsonFields {
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.0.insert(field.name().into(), value.into());
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.0.insert(field.name().into(), value.into());
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.0.insert(field.name().into(), value.into());
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.0.insert(field.name().into(), value.into());
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name().into(), value.into());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.0
+            .insert(field.name().into(), format!("{value:?}").into());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+
+    /// Log output collected in memory
+    #[derive(Clone, Default)]
+    struct Captured(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Captured {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn log_with(format: LogFormat, context: LogContext) -> String {
+        let captured = Captured::default();
+        let output = captured.clone();
+        let subscriber = build(format, context, &["delila_rs=info"], move || output.clone())
+            .expect("subscriber builds");
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::info!(source_id = 7u32, seq = 12u64, "Received EOS");
+            tracing::debug!("filtered out");
+        });
+        let bytes = captured.0.lock().unwrap().clone();
+        String::from_utf8(bytes).unwrap()
+    }
+
+    #[test]
+    fn test_text_format() {
+        let output = log_with(LogFormat::Text, LogContext::component("merger"));
+        assert_eq!(output.lines().count(), 1);
+        assert!(output.contains("Received EOS"));
+    }
+
+    #[test]
+    fn test_json_format_has_structured_fields() {
+        let output = log_with(LogFormat::Json, LogContext::component("merger"));
+        let lines: Vec<Value> = output
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 1);
+        let line = &lines[0];
+        assert_eq!(line["level"], "INFO");
+        assert_eq!(line["component"], "merger");
+        assert_eq!(line["source_id"], 7);
+        assert_eq!(line["message"], "Received EOS");
+        assert_eq!(line["fields"]["seq"], 12);
+
+        // A source's own ID wins over event fields
+        let context = LogContext {
+            component: "reader",
+            source_id: Some(2),
+        };
+        let output = log_with(LogFormat::Json, context);
+        let line: Value = serde_json::from_str(output.lines().next().unwrap()).unwrap();
+        assert_eq!(line["source_id"], 2);
+    }
+}
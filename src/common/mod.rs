@@ -15,7 +15,10 @@ pub use cli::{
 
 // Re-export command types
 pub mod command;
-pub use command::{Command, CommandResponse, ComponentState, EmulatorRuntimeConfig, RunConfig};
+pub use command::{
+    Command, CommandResponse, ComponentState, DigitizerConfigSource, EmulatorRuntimeConfig,
+    RecorderTuning, RunConfig, TriggerMode, MIN_RECORDER_FILE_SIZE,
+};
 
 // Shared state and command handling infrastructure
 pub mod state;
@@ -37,6 +40,37 @@ pub use error::{PipelineError, PipelineResult};
 pub mod shutdown;
 pub use shutdown::{setup_shutdown, setup_shutdown_with_message, ShutdownReceiver, ShutdownSender};
 
+// Panic isolation for worker tasks
+pub mod supervisor;
+pub use supervisor::{enter_error_state, enter_error_state_blocking, isolate, isolate_blocking};
+
+// Size-aware batch splitting and reassembly
+pub mod fragment;
+pub use fragment::{encode_with_limit, BatchFragment, BatchReassembler, DEFAULT_MAX_MESSAGE_BYTES};
+
+// End-of-stream tracking for sinks
+pub mod eos;
+pub use eos::{finish_run, EosTracker};
+
+// Topic frames for per-source subscribe filtering
+pub mod topic;
+
+// ZMQ high-water marks and linger for the data sockets
+pub mod socket_options;
+pub use socket_options::SocketOptions;
+
+// Delta coding of analog waveform probes
+pub mod wave_codec;
+pub use wave_codec::{WaveCodecError, WaveformEncoding};
+
+// Build version, git commit and timestamp
+pub mod version;
+pub use version::VERSION_STRING;
+
+// Text or JSON log output for the binaries
+pub mod logging;
+pub use logging::{LogContext, LogFormat};
+
 /// Heartbeat message for liveness detection
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Heartbeat {
@@ -71,12 +105,40 @@ pub struct ComponentMetrics {
     pub bytes_transferred: u64,
     /// Current queue size
     pub queue_size: u32,
-    /// Maximum queue capacity
+    /// Highest queue size since run start (high-water mark)
     pub queue_max: u32,
     /// Events per second
     pub event_rate: f64,
     /// Bytes per second
     pub data_rate: f64,
+    /// Per-channel dead-time estimate for the current run (Reader only)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub channel_dead_time: Vec<ChannelDeadTime>,
+    /// Event filter counts since start (Reader with a filter only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub event_filter: Option<FilterCounts>,
+}
+
+/// Dead-time estimate of one digitizer channel
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct ChannelDeadTime {
+    /// Channel number
+    pub channel: u8,
+    /// Events decoded on this channel
+    pub events: u64,
+    /// Events carrying a trigger-lost flag
+    pub lost_flagged: u64,
+    /// `lost_flagged / events` (0 when no events)
+    pub busy_fraction: f64,
+}
+
+/// Events passed and dropped by a source-side event filter
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct FilterCounts {
+    /// Events that passed the filter
+    pub passed: u64,
+    /// Events the filter dropped
+    pub dropped: u64,
 }
 
 /// Flag bit definitions for event status
@@ -91,6 +153,10 @@ pub mod flags {
     pub const FLAG_1024_TRIGGER: u64 = 0x08;
     /// N lost triggers
     pub const FLAG_N_LOST_TRIGGER: u64 = 0x10;
+    /// Energy above the configured ADC resolution (set by the decoder)
+    pub const FLAG_ENERGY_OUT_OF_RANGE: u64 = 0x4000_0000;
+    /// Timestamp jumped outside the Reader's sanity window (set by the Reader)
+    pub const FLAG_TIMESTAMP_OUTLIER: u64 = 0x8000_0000;
 }
 
 /// Waveform data from digitizer
@@ -112,6 +178,10 @@ pub struct Waveform {
     pub time_resolution: u8,
     /// Trigger threshold
     pub trigger_threshold: u16,
+    /// Both analog probes in compressed form, set while the batch is
+    /// [`WaveformEncoding::DeltaZigzag`] (see [`wave_codec`])
+    #[serde(default, with = "serde_bytes")]
+    pub packed_analog: Vec<u8>,
 }
 
 /// Event data with optional waveform
@@ -125,7 +195,14 @@ pub struct EventData {
     /// Channel within module (0-255)
     pub channel: u8,
     /// Primary energy measurement
-    pub energy: u16,
+    ///
+    /// 32 bits wide for firmware with energies beyond 16 bits. MsgPack
+    /// stores integers in their smallest form, so data written when this
+    /// field was `u16` decodes unchanged. Sources only publish values above
+    /// 65535 with `wide_energy` set (see [`EventDataBatch::clamp_energies`]),
+    /// so 16-bit consumers keep working; use [`EventData::energy_u16`] where
+    /// a 16-bit value is required.
+    pub energy: u32,
     /// Short gate energy (for PSD)
     pub energy_short: u16,
     /// Timestamp in nanoseconds (includes fine time)
@@ -142,7 +219,7 @@ impl EventData {
     pub fn new(
         module: u8,
         channel: u8,
-        energy: u16,
+        energy: u32,
         energy_short: u16,
         timestamp_ns: f64,
         flags: u64,
@@ -162,7 +239,7 @@ impl EventData {
     pub fn with_waveform(
         module: u8,
         channel: u8,
-        energy: u16,
+        energy: u32,
         energy_short: u16,
         timestamp_ns: f64,
         flags: u64,
@@ -192,6 +269,12 @@ impl EventData {
         }
     }
 
+    /// Energy clamped to 16 bits, for outputs with a 16-bit energy field
+    #[inline]
+    pub fn energy_u16(&self) -> u16 {
+        self.energy.min(u16::MAX as u32) as u16
+    }
+
     /// Check if this event has waveform data
     #[inline]
     pub fn has_waveform(&self) -> bool {
@@ -230,10 +313,22 @@ pub struct EventDataBatch {
     pub source_id: u32,
     /// Sequence number for ordering and loss detection
     pub sequence_number: u64,
+    /// Run epoch of the source, a new one on every run (see [`next_epoch`])
+    ///
+    /// Consumers treat a new epoch as a restart of `sequence_number`. It is
+    /// serialized next to the sequence number so [`MessageHeader::parse`]
+    /// reads it without walking the events.
+    pub epoch: u32,
     /// Batch creation timestamp (Unix time in nanoseconds)
     pub timestamp: u64,
     /// Event data
     pub events: Vec<EventData>,
+    /// Fragment position when the batch was split for size (see [`crate::common::fragment`])
+    #[serde(default)]
+    pub fragment: Option<BatchFragment>,
+    /// How the analog waveform probes of this batch are stored
+    #[serde(default)]
+    pub waveform_encoding: WaveformEncoding,
 }
 
 impl EventDataBatch {
@@ -247,6 +342,9 @@ impl EventDataBatch {
                 .unwrap_or_default()
                 .as_nanos() as u64,
             events: Vec::new(),
+            fragment: None,
+            epoch: 0,
+            waveform_encoding: WaveformEncoding::Raw,
         }
     }
 
@@ -260,6 +358,9 @@ impl EventDataBatch {
                 .unwrap_or_default()
                 .as_nanos() as u64,
             events: Vec::with_capacity(capacity),
+            fragment: None,
+            epoch: 0,
+            waveform_encoding: WaveformEncoding::Raw,
         }
     }
 
@@ -278,6 +379,21 @@ impl EventDataBatch {
         self.events.push(event);
     }
 
+    /// Clamp every energy to 16 bits; returns how many were above 65535
+    ///
+    /// Sources call this before publishing unless `wide_energy` is set, so
+    /// consumers built for the 16-bit energy field never see wider values.
+    pub fn clamp_energies(&mut self) -> u64 {
+        let mut clamped = 0;
+        for event in &mut self.events {
+            if event.energy > u16::MAX as u32 {
+                event.energy = u16::MAX as u32;
+                clamped += 1;
+            }
+        }
+        clamped
+    }
+
     /// Serialize to MessagePack bytes
     pub fn to_msgpack(&self) -> Result<Vec<u8>, rmp_serde::encode::Error> {
         rmp_serde::to_vec(self)
@@ -287,11 +403,83 @@ impl EventDataBatch {
     pub fn from_msgpack(bytes: &[u8]) -> Result<Self, rmp_serde::decode::Error> {
         rmp_serde::from_slice(bytes)
     }
+
+    /// Deserialize from MessagePack bytes written with batch layout `version`
+    ///
+    /// Version 1 is the layout before [`MESSAGE_WIRE_VERSION`] 2, with the
+    /// epoch (if any) after the events.
+    pub fn from_msgpack_version(
+        bytes: &[u8],
+        version: u8,
+    ) -> Result<Self, rmp_serde::decode::Error> {
+        if version >= MESSAGE_WIRE_VERSION {
+            Self::from_msgpack(bytes)
+        } else {
+            rmp_serde::from_slice::<EventDataBatchV1>(bytes).map(Self::from)
+        }
+    }
 }
 
+/// [`EventDataBatch`] as laid out by wire version 1 and older files
+#[derive(Deserialize)]
+struct EventDataBatchV1 {
+    source_id: u32,
+    sequence_number: u64,
+    timestamp: u64,
+    events: Vec<EventData>,
+    #[serde(default)]
+    fragment: Option<BatchFragment>,
+    #[serde(default)]
+    epoch: u32,
+    #[serde(default)]
+    waveform_encoding: WaveformEncoding,
+}
+
+impl From<EventDataBatchV1> for EventDataBatch {
+    fn from(v1: EventDataBatchV1) -> Self {
+        Self {
+            source_id: v1.source_id,
+            sequence_number: v1.sequence_number,
+            epoch: v1.epoch,
+            timestamp: v1.timestamp,
+            events: v1.events,
+            fragment: v1.fragment,
+            waveform_encoding: v1.waveform_encoding,
+        }
+    }
+}
+
+/// Epoch for a new run of a source
+///
+/// Seconds since the Unix epoch, so a restarted process does not reuse the
+/// epoch of its previous run; always differs from `previous`.
+pub fn next_epoch(previous: u32) -> u32 {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs() as u32;
+    now.max(previous.wrapping_add(1))
+}
+
+/// Wire format version written in front of every serialized [`Message`]
+///
+/// Version 2 moved the batch epoch in front of the events.
+pub const MESSAGE_WIRE_VERSION: u8 = 2;
+
+/// Previous wire version, still decoded
+const MESSAGE_WIRE_VERSION_V1: u8 = 1;
+
+/// First byte of a legacy unversioned frame (msgpack fixmap with 1 entry)
+///
+/// Frames from components built before the version byte was introduced are
+/// still decoded; this fallback is kept for one release.
+const LEGACY_FRAME_MARKER: u8 = 0x81;
+
 /// Message type for pipeline communication
 ///
 /// Wraps either event data or control signals (like EOS/Heartbeat).
+/// On the wire a message is one version byte ([`MESSAGE_WIRE_VERSION`])
+/// followed by the MessagePack-encoded enum.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum Message {
     /// Event data batch
@@ -302,6 +490,24 @@ pub enum Message {
     Heartbeat(Heartbeat),
 }
 
+/// [`Message`] as encoded by wire version 1 and legacy frames
+#[derive(Deserialize)]
+enum MessageV1 {
+    Data(EventDataBatchV1),
+    EndOfStream { source_id: u32 },
+    Heartbeat(Heartbeat),
+}
+
+impl From<MessageV1> for Message {
+    fn from(v1: MessageV1) -> Self {
+        match v1 {
+            MessageV1::Data(batch) => Self::Data(batch.into()),
+            MessageV1::EndOfStream { source_id } => Self::EndOfStream { source_id },
+            MessageV1::Heartbeat(hb) => Self::Heartbeat(hb),
+        }
+    }
+}
+
 impl Message {
     /// Create a data message
     pub fn data(batch: EventDataBatch) -> Self {
@@ -337,14 +543,48 @@ impl Message {
         Self::Heartbeat(Heartbeat::new(source_id, counter))
     }
 
-    /// Serialize to MessagePack bytes
+    /// Serialize to the versioned wire format
     pub fn to_msgpack(&self) -> Result<Vec<u8>, rmp_serde::encode::Error> {
-        rmp_serde::to_vec(self)
+        let mut bytes = vec![MESSAGE_WIRE_VERSION];
+        rmp_serde::encode::write(&mut bytes, self)?;
+        Ok(bytes)
     }
 
-    /// Deserialize from MessagePack bytes
+    /// Deserialize from the wire format (current, version 1 or legacy)
+    ///
+    /// An unknown version byte is rejected instead of being decoded as garbage.
+    /// Compressed waveforms are restored to plain samples.
     pub fn from_msgpack(bytes: &[u8]) -> Result<Self, rmp_serde::decode::Error> {
-        rmp_serde::from_slice(bytes)
+        let mut message: Self = match Self::wire_body(bytes) {
+            Ok((MESSAGE_WIRE_VERSION, body)) => rmp_serde::from_slice(body)?,
+            Ok((_, body)) => rmp_serde::from_slice::<MessageV1>(body)?.into(),
+            Err(version) => {
+                return Err(serde::de::Error::custom(format!(
+                    "unsupported message wire version {} (expected {})",
+                    version, MESSAGE_WIRE_VERSION
+                )))
+            }
+        };
+        if let Self::Data(ref mut batch) = message {
+            batch
+                .decompress_waveforms()
+                .map_err(<rmp_serde::decode::Error as serde::de::Error>::custom)?;
+        }
+        Ok(message)
+    }
+
+    /// Strip the version byte, returning the version and MessagePack body
+    ///
+    /// Legacy frames are returned unchanged as version 1; an unknown leading
+    /// byte is returned as the error.
+    fn wire_body(bytes: &[u8]) -> Result<(u8, &[u8]), u8> {
+        match bytes.first() {
+            Some(&version @ (MESSAGE_WIRE_VERSION | MESSAGE_WIRE_VERSION_V1)) => {
+                Ok((version, &bytes[1..]))
+            }
+            Some(&LEGACY_FRAME_MARKER) | None => Ok((MESSAGE_WIRE_VERSION_V1, bytes)),
+            Some(&version) => Err(version),
+        }
     }
 }
 
@@ -352,10 +592,11 @@ impl Message {
 /// Used for zero-copy forwarding where only metadata is needed
 #[derive(Debug, Clone, Copy)]
 pub enum MessageHeader {
-    /// Data batch with source_id and sequence_number
+    /// Data batch with source_id, sequence_number and epoch
     Data {
         source_id: u32,
         sequence_number: u64,
+        epoch: u32,
     },
     /// End of stream
     EndOfStream { source_id: u32 },
@@ -366,13 +607,18 @@ pub enum MessageHeader {
 impl MessageHeader {
     /// Extract header info from raw MessagePack bytes without full deserialization
     ///
+    /// The version byte is skipped (legacy frames have none); frames with an
+    /// unknown version are not parsed. Data frames older than wire version 2
+    /// report epoch 0.
+    ///
     /// MessagePack format for Message enum:
     /// - fixmap with 1 entry: 0x81 (map of 1)
     /// - key: fixstr "Data", "EndOfStream", or "Heartbeat"
     /// - value: the actual data
     ///
-    /// For Data variant, we need source_id and sequence_number from MinimalEventDataBatch
+    /// For Data variant, we need source_id, sequence_number and epoch from EventDataBatch
     pub fn parse(bytes: &[u8]) -> Option<Self> {
+        let (version, bytes) = Message::wire_body(bytes).ok()?;
         if bytes.is_empty() {
             return None;
         }
@@ -404,7 +650,7 @@ impl MessageHeader {
             b"Data" => {
                 // Data variant: value is MinimalEventDataBatch
                 // It's a map with source_id, sequence_number, timestamp, events
-                Self::parse_data_header(&bytes[value_start..])
+                Self::parse_data_header(&bytes[value_start..], version)
             }
             b"EndOfStream" => {
                 // EndOfStream variant: value is a map with source_id
@@ -418,27 +664,22 @@ impl MessageHeader {
         }
     }
 
-    /// Parse Data variant header to extract source_id and sequence_number
-    fn parse_data_header(bytes: &[u8]) -> Option<Self> {
+    /// Parse Data variant header to extract source_id, sequence_number and epoch
+    fn parse_data_header(bytes: &[u8], version: u8) -> Option<Self> {
         // rmp_serde serializes structs as arrays by default:
-        // EventDataBatch is [source_id, sequence_number, timestamp, events]
-        // We only need source_id (index 0) and sequence_number (index 1)
+        // EventDataBatch is [source_id, sequence_number, epoch, timestamp, events, ...]
+        // We need source_id (index 0), sequence_number (index 1) and epoch
+        // (index 2; version 1 frames put it after the events, so it is not read)
 
         if bytes.is_empty() {
             return None;
         }
 
         // Should be an array (fixarray 0x90-0x9f or array16 0xdc or array32 0xdd)
-        let (_array_size, mut pos) = match bytes[0] {
-            b if (0x90..=0x9f).contains(&b) => ((b & 0x0f) as usize, 1),
-            0xdc if bytes.len() >= 3 => {
-                let size = u16::from_be_bytes([bytes[1], bytes[2]]) as usize;
-                (size, 3)
-            }
-            0xdd if bytes.len() >= 5 => {
-                let size = u32::from_be_bytes([bytes[1], bytes[2], bytes[3], bytes[4]]) as usize;
-                (size, 5)
-            }
+        let mut pos = match bytes[0] {
+            b if (0x90..=0x9f).contains(&b) => 1,
+            0xdc if bytes.len() >= 3 => 3,
+            0xdd if bytes.len() >= 5 => 5,
             _ => return None,
         };
 
@@ -448,9 +689,17 @@ impl MessageHeader {
         // Parse sequence_number (second element)
         let sequence_number = Self::parse_u64(&bytes[pos..], &mut pos)?;
 
+        // Parse epoch (third element)
+        let epoch = if version >= MESSAGE_WIRE_VERSION {
+            Self::parse_u32(&bytes[pos..], &mut pos)?
+        } else {
+            0
+        };
+
         Some(MessageHeader::Data {
             source_id,
             sequence_number,
+            epoch,
         })
     }
 
@@ -596,6 +845,7 @@ mod tests {
             digital_probe4: vec![],
             time_resolution: 1,
             trigger_threshold: 500,
+            packed_analog: Vec::new(),
         };
 
         let event = EventData::with_waveform(1, 2, 1000, 800, 123456789.0, 0, wf);
@@ -624,6 +874,38 @@ mod tests {
         assert_eq!(batch.events[1], decoded.events[1]);
     }
 
+    #[test]
+    fn wide_energy_roundtrip() {
+        let event = EventData::new(3, 4, 1_000_000, 800, 5000.0, 0);
+
+        let bytes = rmp_serde::to_vec(&event).unwrap();
+        let decoded: EventData = rmp_serde::from_slice(&bytes).unwrap();
+
+        assert_eq!(decoded.energy, 1_000_000);
+        assert_eq!(decoded.energy_u16(), u16::MAX);
+        assert_eq!(EventData::new(0, 0, 1234, 0, 0.0, 0).energy_u16(), 1234);
+
+        // Events encoded with the former 16-bit energy still decode
+        let legacy = rmp_serde::to_vec(&(1u8, 2u8, 65535u16, 800u16, 1000.0f64, 0u64)).unwrap();
+        let decoded: EventData = rmp_serde::from_slice(&legacy).unwrap();
+        assert_eq!(decoded, EventData::new(1, 2, 65535, 800, 1000.0, 0));
+    }
+
+    #[test]
+    fn clamped_batch_encodes_16_bit_energies() {
+        let mut batch = EventDataBatch::new(0, 0);
+        batch.push(EventData::new(0, 0, 1_000_000, 0, 0.0, 0));
+        batch.push(EventData::new(0, 1, 1234, 0, 1.0, 0));
+        assert_eq!(batch.clamp_energies(), 1);
+        let energies: Vec<u32> = batch.events.iter().map(|e| e.energy).collect();
+        assert_eq!(energies, vec![65535, 1234]);
+
+        // Same bytes as the 16-bit field: uint16 marker (0xcd), never uint32 (0xce)
+        let bytes = rmp_serde::to_vec(&batch.events[0]).unwrap();
+        let legacy = rmp_serde::to_vec(&(0u8, 0u8, 65535u16, 0u16, 0.0f64, 0u64)).unwrap();
+        assert_eq!(bytes, legacy);
+    }
+
     #[test]
     fn flag_helpers() {
         let event = EventData::new(0, 0, 0, 0, 0.0, flags::FLAG_PILEUP | flags::FLAG_OVER_RANGE);
@@ -666,7 +948,21 @@ mod tests {
     fn message_header_parse_data() {
         let mut batch = EventDataBatch::new(42, 1);
         batch.sequence_number = 12345;
+        batch.epoch = 7;
         batch.push(EventData::new(0, 0, 100, 80, 1000.0, 0));
+        batch.push(EventData::with_waveform(
+            0,
+            1,
+            100,
+            80,
+            2000.0,
+            0,
+            Waveform {
+                analog_probe1: vec![-300, 0, 1200, 32767],
+                digital_probe1: vec![0, 1, 1, 0],
+                ..Default::default()
+            },
+        ));
 
         let msg = Message::data(batch);
         let bytes = msg.to_msgpack().unwrap();
@@ -689,9 +985,11 @@ mod tests {
             MessageHeader::Data {
                 source_id,
                 sequence_number,
+                epoch,
             } => {
                 assert_eq!(source_id, 42);
                 assert_eq!(sequence_number, 12345);
+                assert_eq!(epoch, 7);
             }
             _ => panic!("Expected Data variant"),
         }
@@ -713,4 +1011,128 @@ mod tests {
             _ => panic!("Expected EndOfStream variant"),
         }
     }
+
+    #[test]
+    fn message_wire_version_roundtrip() {
+        let mut batch = EventDataBatch::new(7, 3);
+        batch.push(EventData::new(0, 1, 100, 80, 1000.0, 0));
+
+        let bytes = Message::data(batch).to_msgpack().unwrap();
+        assert_eq!(bytes[0], MESSAGE_WIRE_VERSION);
+
+        match Message::from_msgpack(&bytes).unwrap() {
+            Message::Data(decoded) => {
+                assert_eq!(decoded.source_id, 7);
+                assert_eq!(decoded.sequence_number, 3);
+                assert_eq!(decoded.events.len(), 1);
+            }
+            other => panic!("Expected Data variant, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn message_legacy_frame_still_decodes() {
+        let legacy = rmp_serde::to_vec(&Message::eos(5)).unwrap();
+        assert_eq!(legacy[0], LEGACY_FRAME_MARKER);
+
+        let decoded = Message::from_msgpack(&legacy).unwrap();
+        assert!(decoded.is_eos());
+        assert_eq!(decoded.source_id(), 5);
+        assert!(matches!(
+            MessageHeader::parse(&legacy),
+            Some(MessageHeader::EndOfStream { source_id: 5 })
+        ));
+    }
+
+    #[test]
+    fn batch_without_epoch_decodes_as_epoch_zero() {
+        // Shape of a data frame written before the epoch field existed
+        #[derive(Serialize)]
+        struct OldBatch {
+            source_id: u32,
+            sequence_number: u64,
+            timestamp: u64,
+            events: Vec<EventData>,
+        }
+        #[derive(Serialize)]
+        enum OldMessage {
+            Data(OldBatch),
+        }
+
+        let old = OldMessage::Data(OldBatch {
+            source_id: 3,
+            sequence_number: 9,
+            timestamp: 1,
+            events: vec![EventData::new(0, 1, 100, 80, 1000.0, 0)],
+        });
+        let mut bytes = vec![MESSAGE_WIRE_VERSION_V1];
+        rmp_serde::encode::write(&mut bytes, &old).unwrap();
+
+        let Message::Data(batch) = Message::from_msgpack(&bytes).unwrap() else {
+            panic!("Expected Data variant");
+        };
+        assert_eq!(batch.epoch, 0);
+        assert_eq!(batch.events.len(), 1);
+        assert!(matches!(
+            MessageHeader::parse(&bytes),
+            Some(MessageHeader::Data {
+                source_id: 3,
+                sequence_number: 9,
+                epoch: 0
+            })
+        ));
+    }
+
+    #[test]
+    fn v1_batch_keeps_trailing_epoch() {
+        // Wire version 1 layout: epoch after the events
+        #[derive(Serialize)]
+        struct V1Batch {
+            source_id: u32,
+            sequence_number: u64,
+            timestamp: u64,
+            events: Vec<EventData>,
+            fragment: Option<BatchFragment>,
+            epoch: u32,
+        }
+        let v1 = V1Batch {
+            source_id: 2,
+            sequence_number: 5,
+            timestamp: 1,
+            events: vec![EventData::new(0, 1, 100, 80, 1000.0, 0)],
+            fragment: None,
+            epoch: 4,
+        };
+        let body = rmp_serde::to_vec(&v1).unwrap();
+
+        let batch = EventDataBatch::from_msgpack_version(&body, MESSAGE_WIRE_VERSION_V1).unwrap();
+        assert_eq!(batch.epoch, 4);
+        assert_eq!(batch.sequence_number, 5);
+        assert_eq!(batch.events.len(), 1);
+        // The current layout does not accept it
+        assert!(EventDataBatch::from_msgpack(&body).is_err());
+    }
+
+    #[test]
+    fn next_epoch_never_repeats_previous() {
+        let first = next_epoch(0);
+        assert!(first > 0);
+        assert_ne!(next_epoch(first), first);
+        assert_eq!(next_epoch(u32::MAX - 1), u32::MAX);
+    }
+
+    #[test]
+    fn message_unknown_wire_version_rejected() {
+        let mut bytes = Message::heartbeat(1, 0).to_msgpack().unwrap();
+        bytes[0] = 0x07;
+
+        let err = Message::from_msgpack(&bytes).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("unsupported message wire version 7"),
+            "{}",
+            err
+        );
+        assert!(MessageHeader::parse(&bytes).is_none());
+    }
 }
@@ -0,0 +1,123 @@
+//! ZMQ high-water marks and linger for the data sockets
+//!
+//! ZMQ queues messages per peer up to a high-water mark (HWM, default 1000
+//! messages). What happens at the mark depends on the socket type, and for
+//! the data path it is always a drop:
+//!
+//! - A PUB socket never blocks: once a subscriber's queue reaches
+//!   `send_hwm`, further messages to that subscriber are discarded.
+//! - A SUB socket queues up to `recv_hwm` messages that its component has
+//!   not read yet; beyond that the publisher's queue fills and drops.
+//!
+//! These drops happen below the application and are not counted anywhere;
+//! downstream they show up only as sequence gaps. The app-level policies
+//! (`decode_queue_policy` in the Reader, `backpressure` in the Merger) act on
+//! the internal channels *after* the receiver task has drained the socket, so
+//! `block` there cannot push back through ZMQ to the upstream component. Raise
+//! the HWMs to ride out bursts at the cost of memory (0 = unlimited), and keep
+//! the receiving component fast enough that its socket queue stays short.
+//!
+//! `linger_ms` bounds how long unsent messages (e.g. the final EOS) are kept
+//! when a socket closes: -1 waits until delivered, 0 discards immediately.
+
+use serde::Deserialize;
+
+/// Optional overrides of ZMQ's defaults; `None` leaves the default in place
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+pub struct SocketOptions {
+    /// Messages queued per peer on a sending socket (ZMQ default: 1000, 0 = unlimited)
+    #[serde(default)]
+    pub send_hwm: Option<i32>,
+    /// Messages queued per peer on a receiving socket (ZMQ default: 1000, 0 = unlimited)
+    #[serde(default)]
+    pub recv_hwm: Option<i32>,
+    /// Milliseconds to keep unsent messages on close (ZMQ default: -1 = until sent)
+    #[serde(default)]
+    pub linger_ms: Option<i32>,
+}
+
+impl SocketOptions {
+    /// Apply the overrides to an open socket
+    ///
+    /// HWM changes also reach connections that already exist (libzmq >= 4.2),
+    /// so this can run right after `bind`/`connect`.
+    pub fn apply(&self, socket: &zmq::Socket) -> tmq::Result<()> {
+        if let Some(hwm) = self.send_hwm {
+            socket.set_sndhwm(hwm)?;
+        }
+        if let Some(hwm) = self.recv_hwm {
+            socket.set_rcvhwm(hwm)?;
+        }
+        if let Some(linger) = self.linger_ms {
+            socket.set_linger(linger)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::topic;
+    use futures::{SinkExt, StreamExt};
+    use std::time::Duration;
+    use tmq::{publish, subscribe, AsZmqSocket, Context};
+
+    #[tokio::test]
+    async fn test_tiny_hwm_drops_flooded_messages() {
+        let address = "inproc://socket_options_test";
+        let tiny = SocketOptions {
+            send_hwm: Some(1),
+            recv_hwm: Some(1),
+            linger_ms: Some(0),
+        };
+
+        let context = Context::new();
+        let mut publisher = publish(&context).bind(address).unwrap();
+        tiny.apply(publisher.get_socket()).unwrap();
+        let mut subscriber = subscribe(&context)
+            .connect(address)
+            .unwrap()
+            .subscribe(b"")
+            .unwrap();
+        tiny.apply(subscriber.get_socket()).unwrap();
+
+        assert_eq!(publisher.get_socket().get_sndhwm().unwrap(), 1);
+        assert_eq!(subscriber.get_socket().get_rcvhwm().unwrap(), 1);
+        assert_eq!(publisher.get_socket().get_linger().unwrap(), 0);
+
+        // Let the subscription reach the publisher (slow joiner)
+        tokio::time::sleep(Duration::from_millis(200)).await;
+
+        // Flood without reading: PUB drops instead of blocking once the queue is full
+        const SENT: u32 = 10_000;
+        for i in 0..SENT {
+            publisher
+                .send(topic::data_message(None, &i.to_le_bytes()))
+                .await
+                .unwrap();
+        }
+
+        let mut received = 0;
+        while let Ok(Some(message)) =
+            tokio::time::timeout(Duration::from_millis(200), subscriber.next()).await
+        {
+            message.unwrap();
+            received += 1;
+        }
+        assert!(received > 0, "nothing received");
+        assert!(received < SENT, "all {} messages were queued", SENT);
+    }
+
+    #[test]
+    fn test_default_leaves_socket_untouched() {
+        let context = zmq::Context::new();
+        let socket = context.socket(zmq::PUB).unwrap();
+        let before = (socket.get_sndhwm().unwrap(), socket.get_linger().unwrap());
+        SocketOptions::default().apply(&socket).unwrap();
+        assert_eq!(
+            (socket.get_sndhwm().unwrap(), socket.get_linger().unwrap()),
+            before
+        );
+    }
+}
@@ -3,7 +3,10 @@
 //! This module provides common state management and command handling
 //! that is shared across all DAQ components (Emulator, Reader, Merger, DataSink).
 
-use super::command::{Command, CommandResponse, ComponentState, EmulatorRuntimeConfig, RunConfig};
+use super::command::{
+    Command, CommandResponse, ComponentState, DigitizerConfigSource, EmulatorRuntimeConfig,
+    RecorderTuning, RunConfig, TriggerMode,
+};
 use tokio::sync::watch;
 use tracing::info;
 
@@ -13,10 +16,12 @@ use tracing::info;
 /// to be shared between multiple tasks within a component.
 #[derive(Debug, Clone)]
 pub struct ComponentSharedState {
-    /// Current component state (Idle, Configured, Armed, Running, Error)
+    /// Current component state (Idle, Configured, Armed, Running, Paused, Error)
     pub state: ComponentState,
     /// Current run configuration (if configured)
     pub run_config: Option<RunConfig>,
+    /// Why the component entered Error (cleared on Reset/RecoverError)
+    pub error_message: Option<String>,
 }
 
 impl Default for ComponentSharedState {
@@ -31,9 +36,16 @@ impl ComponentSharedState {
         Self {
             state: ComponentState::Idle,
             run_config: None,
+            error_message: None,
         }
     }
 
+    /// Enter Error and remember the reason (reported by GetStatus)
+    pub fn set_error(&mut self, message: impl Into<String>) {
+        self.state = ComponentState::Error;
+        self.error_message = Some(message.into());
+    }
+
     /// Get current run number (if configured)
     pub fn run_number(&self) -> Option<u32> {
         self.run_config.as_ref().map(|c| c.run_number)
@@ -71,6 +83,16 @@ pub trait CommandHandlerExt {
         Ok(())
     }
 
+    /// Called before Pause transition
+    fn on_pause(&mut self) -> Result<(), String> {
+        Ok(())
+    }
+
+    /// Called before Resume transition
+    fn on_resume(&mut self) -> Result<(), String> {
+        Ok(())
+    }
+
     /// Called before Reset transition
     fn on_reset(&mut self) -> Result<(), String> {
         Ok(())
@@ -87,6 +109,11 @@ pub trait CommandHandlerExt {
         None
     }
 
+    /// Effective configuration for the GetConfig command (None = not supported)
+    fn effective_config(&self) -> Option<serde_json::Value> {
+        None
+    }
+
     /// Called when UpdateEmulatorConfig command is received
     /// Only implemented by Emulator; other components return error
     fn on_update_emulator_config(&mut self, _config: &EmulatorRuntimeConfig) -> Result<(), String> {
@@ -99,9 +126,42 @@ pub trait CommandHandlerExt {
     fn on_detect(&mut self) -> Result<serde_json::Value, String> {
         Err("Detect not supported by this component".to_string())
     }
+
+    /// Called when SetTriggerMode command is received (Reader-only)
+    /// Returns the parameters written as a JSON value.
+    fn on_set_trigger_mode(&mut self, _mode: TriggerMode) -> Result<serde_json::Value, String> {
+        Err("SetTriggerMode not supported by this component".to_string())
+    }
+
+    /// Called when ReconfigureDigitizer command is received (Reader-only)
+    /// Returns the number of parameters applied.
+    fn on_reconfigure_digitizer(
+        &mut self,
+        _source: &DigitizerConfigSource,
+    ) -> Result<usize, String> {
+        Err("ReconfigureDigitizer not supported by this component".to_string())
+    }
+
+    /// Called when UpdateRecorderTuning command is received (Recorder-only)
+    /// The tuning has already passed `RecorderTuning::validate`.
+    fn on_update_recorder_tuning(&mut self, _tuning: &RecorderTuning) -> Result<(), String> {
+        Err("UpdateRecorderTuning not supported by this component".to_string())
+    }
+
+    /// Called when Flush command is received (Recorder-only)
+    /// Returns once the flushed data is on disk.
+    fn on_flush(&mut self) -> Result<(), String> {
+        Err("Flush not supported by this component".to_string())
+    }
+
+    /// Called when GetRecentEvents command is received (Reader-only)
+    /// Returns up to `n` recent events as a JSON array, oldest first.
+    fn on_get_recent_events(&mut self, _n: usize) -> Result<serde_json::Value, String> {
+        Err("GetRecentEvents not supported by this component".to_string())
+    }
 }
 
-/// Handle a command using the 5-state machine logic
+/// Handle a command using the component state machine logic
 ///
 /// This function implements the common state machine logic used by all components.
 /// Components can customize behavior by implementing `CommandHandlerExt`.
@@ -175,7 +235,10 @@ pub fn handle_command<E: CommandHandlerExt>(
         }
 
         Command::Start { run_number } => {
-            if !current.can_transition_to(ComponentState::Running) {
+            // Paused -> Running is Resume: Start would begin a new run
+            if current == ComponentState::Paused
+                || !current.can_transition_to(ComponentState::Running)
+            {
                 return CommandResponse::error(
                     current,
                     format!("Cannot start from {} state", current),
@@ -201,7 +264,7 @@ pub fn handle_command<E: CommandHandlerExt>(
         }
 
         Command::Stop => {
-            if current != ComponentState::Running {
+            if !current.in_run() {
                 return CommandResponse::error(current, "Not running");
             }
 
@@ -219,6 +282,50 @@ pub fn handle_command<E: CommandHandlerExt>(
             CommandResponse::success_with_run(ComponentState::Configured, "Stopped", run_number)
         }
 
+        Command::Pause => {
+            if !current.can_transition_to(ComponentState::Paused) {
+                return CommandResponse::error(
+                    current,
+                    format!("Cannot pause from {} state", current),
+                );
+            }
+
+            if let Some(ref mut e) = ext {
+                if let Err(msg) = e.on_pause() {
+                    return CommandResponse::error(current, msg);
+                }
+            }
+
+            state.state = ComponentState::Paused;
+            let _ = state_tx.send(ComponentState::Paused);
+
+            info!(component = component_name, "Paused");
+            let run_number = state.run_number().unwrap_or(0);
+            CommandResponse::success_with_run(ComponentState::Paused, "Paused", run_number)
+        }
+
+        Command::Resume => {
+            if current != ComponentState::Paused {
+                return CommandResponse::error(
+                    current,
+                    format!("Cannot resume from {} state", current),
+                );
+            }
+
+            if let Some(ref mut e) = ext {
+                if let Err(msg) = e.on_resume() {
+                    return CommandResponse::error(current, msg);
+                }
+            }
+
+            state.state = ComponentState::Running;
+            let _ = state_tx.send(ComponentState::Running);
+
+            info!(component = component_name, "Resumed");
+            let run_number = state.run_number().unwrap_or(0);
+            CommandResponse::success_with_run(ComponentState::Running, "Resumed", run_number)
+        }
+
         Command::Reset => {
             if let Some(ref mut e) = ext {
                 if let Err(msg) = e.on_reset() {
@@ -228,18 +335,49 @@ pub fn handle_command<E: CommandHandlerExt>(
 
             state.state = ComponentState::Idle;
             state.run_config = None;
+            state.error_message = None;
             let _ = state_tx.send(ComponentState::Idle);
 
             info!(component = component_name, "Reset");
             CommandResponse::success(ComponentState::Idle, "Reset to Idle")
         }
 
+        Command::RecoverError => {
+            if current != ComponentState::Error {
+                return CommandResponse::error(
+                    current,
+                    format!(
+                        "RecoverError only available from Error state, currently {}",
+                        current
+                    ),
+                );
+            }
+
+            // Same cleanup as Reset
+            if let Some(ref mut e) = ext {
+                if let Err(msg) = e.on_reset() {
+                    return CommandResponse::error(current, msg);
+                }
+            }
+
+            let error = state.error_message.take();
+            state.state = ComponentState::Idle;
+            state.run_config = None;
+            let _ = state_tx.send(ComponentState::Idle);
+
+            info!(component = component_name, ?error, "Recovered from error");
+            CommandResponse::success(ComponentState::Idle, "Recovered from Error")
+        }
+
         Command::GetStatus => {
-            let base_msg = if let Some(ref cfg) = state.run_config {
+            let mut base_msg = if let Some(ref cfg) = state.run_config {
                 format!("State: {}, Run: {}", state.state, cfg.run_number)
             } else {
                 format!("State: {}", state.state)
             };
+            if let Some(ref error) = state.error_message {
+                base_msg.push_str(&format!(", Error: {}", error));
+            }
 
             let msg = if let Some(ref e) = ext {
                 if let Some(details) = e.status_details() {
@@ -253,6 +391,7 @@ pub fn handle_command<E: CommandHandlerExt>(
 
             let mut resp = CommandResponse::success(state.state, msg);
             resp.run_number = state.run_number();
+            resp.version = Some(super::VERSION_STRING.to_string());
 
             // Add metrics if available
             if let Some(ref e) = ext {
@@ -264,6 +403,13 @@ pub fn handle_command<E: CommandHandlerExt>(
             resp
         }
 
+        Command::GetConfig => match ext.as_ref().and_then(|e| e.effective_config()) {
+            Some(config) => {
+                CommandResponse::success(current, "Effective configuration").with_data(config)
+            }
+            None => CommandResponse::error(current, "GetConfig not supported by this component"),
+        },
+
         Command::UpdateEmulatorConfig(ref config) => {
             // This command can be received in any state
             if let Some(ref mut e) = ext {
@@ -311,6 +457,124 @@ pub fn handle_command<E: CommandHandlerExt>(
                 CommandResponse::error(current, "Detect not supported by this component")
             }
         }
+
+        Command::SetTriggerMode(mode) => {
+            // Hardware settings may only change between runs
+            if current != ComponentState::Configured {
+                return CommandResponse::error(
+                    current,
+                    format!(
+                        "SetTriggerMode only available in Configured state, currently {}",
+                        current
+                    ),
+                );
+            }
+
+            if let Some(ref mut e) = ext {
+                match e.on_set_trigger_mode(mode) {
+                    Ok(params) => {
+                        info!(component = component_name, %mode, "Trigger mode set");
+                        CommandResponse::success(current, format!("Trigger mode set to {}", mode))
+                            .with_data(params)
+                    }
+                    Err(msg) => CommandResponse::error(current, msg),
+                }
+            } else {
+                CommandResponse::error(current, "SetTriggerMode not supported by this component")
+            }
+        }
+
+        Command::ReconfigureDigitizer(ref source) => {
+            // Hardware settings may only change between runs
+            if current != ComponentState::Configured {
+                return CommandResponse::error(
+                    current,
+                    format!(
+                        "ReconfigureDigitizer only available in Configured state, currently {}",
+                        current
+                    ),
+                );
+            }
+
+            if let Some(ref mut e) = ext {
+                match e.on_reconfigure_digitizer(source) {
+                    Ok(applied) => {
+                        info!(
+                            component = component_name,
+                            applied, "Digitizer reconfigured"
+                        );
+                        CommandResponse::success(
+                            current,
+                            format!("Digitizer reconfigured, {} parameters applied", applied),
+                        )
+                        .with_data(serde_json::json!({ "applied": applied }))
+                    }
+                    Err(msg) => CommandResponse::error(current, msg),
+                }
+            } else {
+                CommandResponse::error(
+                    current,
+                    "ReconfigureDigitizer not supported by this component",
+                )
+            }
+        }
+
+        Command::UpdateRecorderTuning(ref tuning) => {
+            // Can be sent in any state; rejected as a whole if any value is out of range
+            if let Err(msg) = tuning.validate() {
+                return CommandResponse::error(current, msg);
+            }
+
+            if let Some(ref mut e) = ext {
+                match e.on_update_recorder_tuning(tuning) {
+                    Ok(()) => {
+                        info!(
+                            component = component_name,
+                            ?tuning,
+                            "Recorder tuning updated"
+                        );
+                        CommandResponse::success(current, "Recorder tuning updated")
+                    }
+                    Err(msg) => CommandResponse::error(current, msg),
+                }
+            } else {
+                CommandResponse::error(
+                    current,
+                    "UpdateRecorderTuning not supported by this component",
+                )
+            }
+        }
+
+        Command::Flush => {
+            // Can be sent in any state; a Recorder without an open file has nothing to flush
+            if let Some(ref mut e) = ext {
+                match e.on_flush() {
+                    Ok(()) => {
+                        info!(component = component_name, "Flushed");
+                        CommandResponse::success(current, "Flushed")
+                    }
+                    Err(msg) => CommandResponse::error(current, msg),
+                }
+            } else {
+                CommandResponse::error(current, "Flush not supported by this component")
+            }
+        }
+
+        Command::GetRecentEvents(n) => {
+            // Read-only snapshot, valid in any state
+            if let Some(ref mut e) = ext {
+                match e.on_get_recent_events(n) {
+                    Ok(events) => {
+                        let count = events.as_array().map_or(0, |a| a.len());
+                        CommandResponse::success(current, format!("{} recent events", count))
+                            .with_data(events)
+                    }
+                    Err(msg) => CommandResponse::error(current, msg),
+                }
+            } else {
+                CommandResponse::error(current, "GetRecentEvents not supported by this component")
+            }
+        }
     }
 }
 
@@ -345,6 +609,7 @@ mod tests {
         start_called: bool,
         stop_called: bool,
         reset_called: bool,
+        reconfigure_called: bool,
     }
 
     impl TestComponent {
@@ -355,6 +620,7 @@ mod tests {
                 start_called: false,
                 stop_called: false,
                 reset_called: false,
+                reconfigure_called: false,
             }
         }
     }
@@ -392,6 +658,19 @@ mod tests {
         fn status_details(&self) -> Option<String> {
             Some("custom details".to_string())
         }
+
+        fn on_reconfigure_digitizer(
+            &mut self,
+            source: &DigitizerConfigSource,
+        ) -> Result<usize, String> {
+            match source {
+                DigitizerConfigSource::Path(path) => Err(format!("cannot read {}", path)),
+                DigitizerConfigSource::Json(_) => {
+                    self.reconfigure_called = true;
+                    Ok(3)
+                }
+            }
+        }
     }
 
     #[test]
@@ -462,6 +741,193 @@ mod tests {
         assert_eq!(state.state, ComponentState::Idle);
     }
 
+    #[test]
+    fn test_recover_error_returns_to_idle() {
+        let mut state = ComponentSharedState::new();
+        let (state_tx, state_rx) = watch::channel(ComponentState::Idle);
+        let mut ext = TestComponent::new();
+
+        state.run_config = Some(RunConfig::default());
+        state.set_error("Disk full");
+        let resp = handle_command(&mut state, &state_tx, Command::GetStatus, Some(&mut ext));
+        assert_eq!(resp.state, ComponentState::Error);
+        assert!(resp.message.contains("Error: Disk full"));
+        assert_eq!(resp.version.as_deref(), Some(super::super::VERSION_STRING));
+
+        let resp = handle_command(&mut state, &state_tx, Command::RecoverError, Some(&mut ext));
+        assert!(resp.success);
+        assert_eq!(state.state, ComponentState::Idle);
+        assert_eq!(*state_rx.borrow(), ComponentState::Idle);
+        assert!(state.error_message.is_none());
+        assert!(state.run_config.is_none());
+        assert!(ext.reset_called);
+    }
+
+    #[test]
+    fn test_error_rejects_other_transitions() {
+        let mut state = ComponentSharedState::new();
+        let (state_tx, _state_rx) = watch::channel(ComponentState::Idle);
+        state.set_error("Digitizer open failed");
+
+        for cmd in [
+            Command::Configure(RunConfig::default()),
+            Command::Arm,
+            Command::Start { run_number: 1 },
+            Command::Stop,
+        ] {
+            let resp = handle_command_simple(&mut state, &state_tx, cmd, "Test");
+            assert!(!resp.success);
+            assert_eq!(state.state, ComponentState::Error);
+        }
+        assert_eq!(
+            state.error_message.as_deref(),
+            Some("Digitizer open failed")
+        );
+
+        // RecoverError is only accepted from Error
+        for current in [
+            ComponentState::Idle,
+            ComponentState::Configured,
+            ComponentState::Armed,
+            ComponentState::Running,
+        ] {
+            state.state = current;
+            let resp = handle_command_simple(&mut state, &state_tx, Command::RecoverError, "Test");
+            assert!(!resp.success);
+            assert_eq!(state.state, current);
+        }
+    }
+
+    #[test]
+    fn test_pause_resume_keeps_run() {
+        let mut state = ComponentSharedState::new();
+        let (state_tx, state_rx) = watch::channel(ComponentState::Running);
+        let mut ext = TestComponent::new();
+        state.state = ComponentState::Running;
+        state.run_config = Some(RunConfig {
+            run_number: 7,
+            ..Default::default()
+        });
+
+        let resp = handle_command(&mut state, &state_tx, Command::Pause, Some(&mut ext));
+        assert!(resp.success);
+        assert_eq!(state.state, ComponentState::Paused);
+        assert_eq!(*state_rx.borrow(), ComponentState::Paused);
+        assert_eq!(resp.run_number, Some(7));
+        assert!(!ext.stop_called);
+
+        let resp = handle_command(&mut state, &state_tx, Command::Resume, Some(&mut ext));
+        assert!(resp.success);
+        assert_eq!(state.state, ComponentState::Running);
+        assert_eq!(state.run_number(), Some(7));
+
+        // Stop is accepted while paused
+        handle_command(&mut state, &state_tx, Command::Pause, Some(&mut ext));
+        let resp = handle_command(&mut state, &state_tx, Command::Stop, Some(&mut ext));
+        assert!(resp.success);
+        assert_eq!(state.state, ComponentState::Configured);
+        assert!(ext.stop_called);
+    }
+
+    #[test]
+    fn test_pause_resume_rejections() {
+        let mut state = ComponentSharedState::new();
+        let (state_tx, _state_rx) = watch::channel(ComponentState::Idle);
+
+        // Pause needs Running, Resume needs Paused
+        for current in [
+            ComponentState::Idle,
+            ComponentState::Configured,
+            ComponentState::Armed,
+            ComponentState::Error,
+        ] {
+            state.state = current;
+            for cmd in [Command::Pause, Command::Resume] {
+                let resp = handle_command_simple(&mut state, &state_tx, cmd, "Test");
+                assert!(!resp.success);
+                assert_eq!(state.state, current);
+            }
+        }
+
+        state.state = ComponentState::Running;
+        let resp = handle_command_simple(&mut state, &state_tx, Command::Resume, "Test");
+        assert!(!resp.success);
+
+        // A paused run is resumed, not restarted or paused again
+        state.state = ComponentState::Paused;
+        for cmd in [
+            Command::Start { run_number: 2 },
+            Command::Pause,
+            Command::Arm,
+        ] {
+            let resp = handle_command_simple(&mut state, &state_tx, cmd, "Test");
+            assert!(!resp.success);
+            assert_eq!(state.state, ComponentState::Paused);
+        }
+    }
+
+    #[test]
+    fn test_set_trigger_mode_requires_configured() {
+        let mut state = ComponentSharedState::new();
+        let (state_tx, _state_rx) = watch::channel(ComponentState::Idle);
+
+        let resp = handle_command_simple(
+            &mut state,
+            &state_tx,
+            Command::SetTriggerMode(TriggerMode::External),
+            "Test",
+        );
+        assert!(!resp.success);
+        assert!(resp.message.contains("Configured"));
+
+        // Configured but component has no trigger support
+        state.state = ComponentState::Configured;
+        let resp = handle_command_simple(
+            &mut state,
+            &state_tx,
+            Command::SetTriggerMode(TriggerMode::External),
+            "Test",
+        );
+        assert!(!resp.success);
+        assert_eq!(state.state, ComponentState::Configured);
+    }
+
+    #[test]
+    fn test_reconfigure_digitizer_only_in_configured() {
+        let mut state = ComponentSharedState::new();
+        let (state_tx, _state_rx) = watch::channel(ComponentState::Idle);
+        let mut ext = TestComponent::new();
+        let inline =
+            || Command::ReconfigureDigitizer(DigitizerConfigSource::Json(serde_json::json!({})));
+
+        state.state = ComponentState::Configured;
+        let resp = handle_command(&mut state, &state_tx, inline(), Some(&mut ext));
+        assert!(resp.success, "{}", resp.message);
+        assert_eq!(state.state, ComponentState::Configured);
+        assert_eq!(resp.data, Some(serde_json::json!({ "applied": 3 })));
+        assert!(ext.reconfigure_called);
+
+        // Load/validation errors are reported without changing state
+        let resp = handle_command(
+            &mut state,
+            &state_tx,
+            Command::ReconfigureDigitizer(DigitizerConfigSource::Path("missing.json".to_string())),
+            Some(&mut ext),
+        );
+        assert!(!resp.success);
+        assert!(resp.message.contains("missing.json"));
+        assert_eq!(state.state, ComponentState::Configured);
+
+        // Rejected during a run, before the hardware is touched
+        let mut ext = TestComponent::new();
+        state.state = ComponentState::Running;
+        let resp = handle_command(&mut state, &state_tx, inline(), Some(&mut ext));
+        assert!(!resp.success);
+        assert!(resp.message.contains("Configured"));
+        assert_eq!(state.state, ComponentState::Running);
+        assert!(!ext.reconfigure_called);
+    }
+
     #[test]
     fn test_status_with_details() {
         let mut state = ComponentSharedState::new();
@@ -481,6 +947,18 @@ mod tests {
         assert_eq!(resp.run_number, Some(99));
     }
 
+    #[test]
+    fn test_get_config_unsupported() {
+        let mut state = ComponentSharedState::new();
+        let (state_tx, _state_rx) = watch::channel(ComponentState::Idle);
+        let mut ext = TestComponent::new();
+
+        let resp = handle_command(&mut state, &state_tx, Command::GetConfig, Some(&mut ext));
+        assert!(!resp.success);
+        assert!(resp.data.is_none());
+        assert_eq!(state.state, ComponentState::Idle);
+    }
+
     #[test]
     fn test_simple_handler() {
         let mut state = ComponentSharedState::new();
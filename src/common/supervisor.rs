@@ -0,0 +1,177 @@
+//! Panic isolation for component worker tasks
+//!
+//! A panic inside a data-path task (e.g. a Reader's decode loop) must not take
+//! the whole process down or leave the component reporting `Running` while no
+//! data flows. These helpers run a task body under `catch_unwind` and, on
+//! panic, move only the owning component into `Error`.
+//!
+//! Each source runs its own command task and state machine, so the operator
+//! sees the failed source as `Error` while all other sources stay `Running`.
+//!
+//! A failed task is not restarted. `Reset` clears the `Error` state, but the
+//! component only handles data again after its process is restarted.
+
+use std::any::Any;
+use std::future::Future;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::Arc;
+
+use futures::FutureExt;
+use tokio::sync::{watch, Mutex};
+use tracing::error;
+
+use super::command::ComponentState;
+use super::state::ComponentSharedState;
+
+/// Extract a human-readable message from a panic payload
+pub fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        (*s).to_string()
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "unknown panic payload".to_string()
+    }
+}
+
+/// Move the component into `Error` from a blocking (non-async) context
+///
+/// Used when a worker task gives up on an unrecoverable error. `message` is
+/// reported by GetStatus until the next Reset/RecoverError.
+/// Must not be called from within an async context (uses `blocking_lock`).
+pub fn enter_error_state_blocking(
+    shared_state: &Mutex<ComponentSharedState>,
+    state_tx: &watch::Sender<ComponentState>,
+    message: impl Into<String>,
+) {
+    shared_state.blocking_lock().set_error(message);
+    let _ = state_tx.send(ComponentState::Error);
+}
+
+/// Move the component into `Error` from an async context
+///
+/// Used when a worker task hits a condition it cannot continue from
+/// (e.g. the Recorder's output disk is full).
+pub async fn enter_error_state(
+    shared_state: &Mutex<ComponentSharedState>,
+    state_tx: &watch::Sender<ComponentState>,
+    message: impl Into<String>,
+) {
+    shared_state.lock().await.set_error(message);
+    let _ = state_tx.send(ComponentState::Error);
+}
+
+/// Run an async task body, converting a panic into the component `Error` state
+///
+/// Returns `None` if the task panicked, otherwise the task's output.
+pub async fn isolate<F>(
+    task_name: &'static str,
+    shared_state: Arc<Mutex<ComponentSharedState>>,
+    state_tx: watch::Sender<ComponentState>,
+    fut: F,
+) -> Option<F::Output>
+where
+    F: Future,
+{
+    match AssertUnwindSafe(fut).catch_unwind().await {
+        Ok(output) => Some(output),
+        Err(payload) => {
+            let message = panic_message(payload.as_ref());
+            error!(task = task_name, panic = %message, "Task panicked, entering Error state");
+            let message = format!("{} panicked: {}", task_name, message);
+            enter_error_state(&shared_state, &state_tx, message).await;
+            None
+        }
+    }
+}
+
+/// Blocking counterpart of [`isolate`] for tasks run via `spawn_blocking`
+///
+/// Must not be called from within an async context (uses `blocking_lock`).
+pub fn isolate_blocking<F, R>(
+    task_name: &'static str,
+    shared_state: Arc<Mutex<ComponentSharedState>>,
+    state_tx: watch::Sender<ComponentState>,
+    f: F,
+) -> Option<R>
+where
+    F: FnOnce() -> R,
+{
+    match catch_unwind(AssertUnwindSafe(f)) {
+        Ok(output) => Some(output),
+        Err(payload) => {
+            let message = panic_message(payload.as_ref());
+            error!(task = task_name, panic = %message, "Task panicked, entering Error state");
+            let message = format!("{} panicked: {}", task_name, message);
+            enter_error_state_blocking(&shared_state, &state_tx, message);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup() -> (
+        Arc<Mutex<ComponentSharedState>>,
+        watch::Sender<ComponentState>,
+        watch::Receiver<ComponentState>,
+    ) {
+        let mut shared = ComponentSharedState::new();
+        shared.state = ComponentState::Running;
+        let (tx, rx) = watch::channel(ComponentState::Running);
+        (Arc::new(Mutex::new(shared)), tx, rx)
+    }
+
+    #[tokio::test]
+    async fn test_isolate_panic_sets_error_only_for_failed_source() {
+        let (state_a, tx_a, rx_a) = setup();
+        let (state_b, tx_b, rx_b) = setup();
+
+        let failed = tokio::spawn(isolate("decode", state_a.clone(), tx_a, async {
+            panic!("injected decode failure");
+        }));
+        let healthy = tokio::spawn(isolate("decode", state_b.clone(), tx_b, async { 42 }));
+
+        assert_eq!(failed.await.unwrap(), None::<()>);
+        assert_eq!(healthy.await.unwrap(), Some(42));
+
+        assert_eq!(state_a.lock().await.state, ComponentState::Error);
+        assert_eq!(
+            state_a.lock().await.error_message.as_deref(),
+            Some("decode panicked: injected decode failure")
+        );
+        assert_eq!(*rx_a.borrow(), ComponentState::Error);
+        assert_eq!(state_b.lock().await.state, ComponentState::Running);
+        assert_eq!(*rx_b.borrow(), ComponentState::Running);
+    }
+
+    #[tokio::test]
+    async fn test_isolate_blocking_panic_sets_error() {
+        let (state, tx, rx) = setup();
+
+        let result = tokio::task::spawn_blocking({
+            let state = state.clone();
+            move || {
+                isolate_blocking("read", state, tx, || -> u32 {
+                    panic!("injected read failure");
+                })
+            }
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(result, None);
+        assert_eq!(state.lock().await.state, ComponentState::Error);
+        assert_eq!(*rx.borrow(), ComponentState::Error);
+    }
+
+    #[test]
+    fn test_panic_message() {
+        let payload: Box<dyn Any + Send> = Box::new("static message");
+        assert_eq!(panic_message(payload.as_ref()), "static message");
+        let payload: Box<dyn Any + Send> = Box::new(String::from("owned"));
+        assert_eq!(panic_message(payload.as_ref()), "owned");
+    }
+}
@@ -0,0 +1,99 @@
+//! ZMQ topic framing for the data PUB/SUB sockets
+//!
+//! A publisher with a topic prefix sends every message as two frames,
+//! `[topic, payload]`; without one it sends the payload alone. SUB sockets
+//! filter on the first frame, so a consumer with `subscribe_topics` receives
+//! only messages whose topic starts with one of them, while a consumer
+//! without topics receives everything. The payload is always the last frame.
+
+use tmq::{subscribe, AsZmqSocket};
+
+/// Build the multipart message for `payload`, prefixed by `topic` if set
+pub fn data_message(topic: Option<&str>, payload: &[u8]) -> tmq::Multipart {
+    match topic {
+        Some(topic) => vec![
+            tmq::Message::from(topic.as_bytes()),
+            tmq::Message::from(payload),
+        ]
+        .into(),
+        None => vec![tmq::Message::from(payload)].into(),
+    }
+}
+
+/// Take the payload (last frame) of a received message
+pub fn payload(multipart: tmq::Multipart) -> Option<tmq::Message> {
+    multipart.into_iter().last()
+}
+
+/// Subscribe to `topics`, or to everything if the list is empty
+pub fn subscribe_topics(
+    socket: subscribe::SubscribeWithoutTopic,
+    topics: &[String],
+) -> tmq::Result<subscribe::Subscribe> {
+    let Some((first, rest)) = topics.split_first() else {
+        return socket.subscribe(b"");
+    };
+    let socket = socket.subscribe(first.as_bytes())?;
+    for topic in rest {
+        socket.get_socket().set_subscribe(topic.as_bytes())?;
+    }
+    Ok(socket)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::{SinkExt, StreamExt};
+    use std::time::Duration;
+    use tmq::{publish, Context};
+
+    #[tokio::test]
+    async fn test_subscriber_receives_only_matching_topics() {
+        let address = "tcp://127.0.0.1:15581";
+        let context = Context::new();
+        let mut publisher = publish(&context).bind(address).unwrap();
+        let mut subscriber = subscribe_topics(
+            subscribe(&context).connect(address).unwrap(),
+            &["src0".to_string()],
+        )
+        .unwrap();
+
+        // Let the subscription reach the publisher (slow joiner)
+        tokio::time::sleep(Duration::from_millis(200)).await;
+
+        publisher
+            .send(data_message(Some("src1"), b"other"))
+            .await
+            .unwrap();
+        publisher
+            .send(data_message(None, b"untagged"))
+            .await
+            .unwrap();
+        publisher
+            .send(data_message(Some("src0"), b"wanted"))
+            .await
+            .unwrap();
+
+        let received = tokio::time::timeout(Duration::from_secs(5), subscriber.next())
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        assert_eq!(received.len(), 2);
+        assert_eq!(&payload(received).unwrap()[..], b"wanted");
+
+        // Nothing else matched the filter
+        assert!(
+            tokio::time::timeout(Duration::from_millis(200), subscriber.next())
+                .await
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_untagged_message_is_single_frame() {
+        let message = data_message(None, b"payload");
+        assert_eq!(message.len(), 1);
+        assert_eq!(&payload(message).unwrap()[..], b"payload");
+    }
+}
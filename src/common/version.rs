@@ -0,0 +1,28 @@
+//! Build version information
+//!
+//! The git commit and build time are recorded by `build.rs` when it runs.
+//! It does not re-run on every commit (that would rebuild the CAEN bindings),
+//! so a release build sets `DELILA_GIT_COMMIT` in the environment, which
+//! both pins the commit and re-runs the script when it changes. Every
+//! component reports [`VERSION_STRING`] in its GetStatus response, so the
+//! Operator can spot components built from different sources.
+
+/// Crate version from Cargo.toml
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Short git commit the binary was built from ("unknown" outside a checkout)
+///
+/// Without `DELILA_GIT_COMMIT` set at build time this is the commit of the
+/// last build-script run, which may be older than the sources built.
+pub const GIT_COMMIT: &str = env!("DELILA_GIT_COMMIT");
+
+/// Build time in seconds since the Unix epoch
+pub const BUILD_TIMESTAMP: &str = env!("DELILA_BUILD_TIMESTAMP");
+
+/// Version and commit, e.g. "0.1.0 (a79caa0)"
+pub const VERSION_STRING: &str = concat!(
+    env!("CARGO_PKG_VERSION"),
+    " (",
+    env!("DELILA_GIT_COMMIT"),
+    ")"
+);
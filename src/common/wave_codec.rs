@@ -0,0 +1,226 @@
+//! Lossless compression of analog waveform probes
+//!
+//! Analog probes (one `i16` per sample) dominate the message size when
+//! waveforms are enabled. Pulse shapes change slowly from sample to sample,
+//! so each probe is stored as the differences between successive samples,
+//! zigzag-mapped to unsigned values and written as LEB128 varints: a flat
+//! baseline costs one byte per sample instead of up to three.
+//!
+//! # Batch flag
+//!
+//! A source with `compress_waveforms` enabled calls
+//! [`EventDataBatch::compress_waveforms`] before publishing. The analog
+//! probes then move into [`Waveform::packed_analog`] and the batch carries
+//! [`WaveformEncoding::DeltaZigzag`]. [`Message::from_msgpack`] restores the
+//! probes, so consumers always see plain samples.
+//!
+//! [`Message::from_msgpack`]: super::Message::from_msgpack
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::{EventDataBatch, Waveform};
+
+/// Encoding of the analog probes of every waveform in a batch
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WaveformEncoding {
+    /// Samples in `analog_probe1` / `analog_probe2`
+    #[default]
+    Raw,
+    /// Both probes delta + zigzag + varint coded in `packed_analog`
+    DeltaZigzag,
+}
+
+/// Errors when unpacking compressed analog probes
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum WaveCodecError {
+    #[error("packed waveform truncated at byte {0}")]
+    Truncated(usize),
+
+    #[error("invalid varint at byte {0}")]
+    InvalidVarint(usize),
+}
+
+/// Append `samples` (length prefix, then zigzag-coded deltas) to `out`
+pub fn encode_delta_zigzag(samples: &[i16], out: &mut Vec<u8>) {
+    write_varint(samples.len() as u32, out);
+    let mut prev = 0i16;
+    for &sample in samples {
+        let delta = sample.wrapping_sub(prev);
+        prev = sample;
+        write_varint(((delta << 1) ^ (delta >> 15)) as u16 as u32, out);
+    }
+}
+
+/// Decode one probe written by [`encode_delta_zigzag`], advancing `pos`
+pub fn decode_delta_zigzag(bytes: &[u8], pos: &mut usize) -> Result<Vec<i16>, WaveCodecError> {
+    let len = read_varint(bytes, pos)? as usize;
+    // Every sample takes at least one byte, which bounds the allocation
+    if bytes.len().saturating_sub(*pos) < len {
+        return Err(WaveCodecError::Truncated(bytes.len()));
+    }
+    let mut samples = Vec::with_capacity(len);
+    let mut prev = 0i16;
+    for _ in 0..len {
+        let start = *pos;
+        let zigzag = read_varint(bytes, pos)?;
+        if zigzag > u16::MAX as u32 {
+            return Err(WaveCodecError::InvalidVarint(start));
+        }
+        let zigzag = zigzag as u16;
+        let delta = ((zigzag >> 1) as i16) ^ -((zigzag & 1) as i16);
+        prev = prev.wrapping_add(delta);
+        samples.push(prev);
+    }
+    Ok(samples)
+}
+
+fn write_varint(mut value: u32, out: &mut Vec<u8>) {
+    while value >= 0x80 {
+        out.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+fn read_varint(bytes: &[u8], pos: &mut usize) -> Result<u32, WaveCodecError> {
+    let start = *pos;
+    let mut value = 0u32;
+    for shift in (0..35).step_by(7) {
+        let byte = *bytes.get(*pos).ok_or(WaveCodecError::Truncated(*pos))?;
+        *pos += 1;
+        value |= ((byte & 0x7f) as u32) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err(WaveCodecError::InvalidVarint(start))
+}
+
+impl Waveform {
+    /// Move both analog probes into `packed_analog`
+    pub fn pack_analog(&mut self) {
+        let mut packed =
+            Vec::with_capacity(self.analog_probe1.len() + self.analog_probe2.len() + 4);
+        encode_delta_zigzag(&self.analog_probe1, &mut packed);
+        encode_delta_zigzag(&self.analog_probe2, &mut packed);
+        self.packed_analog = packed;
+        self.analog_probe1 = Vec::new();
+        self.analog_probe2 = Vec::new();
+    }
+
+    /// Restore the analog probes from `packed_analog`
+    pub fn unpack_analog(&mut self) -> Result<(), WaveCodecError> {
+        let mut pos = 0;
+        self.analog_probe1 = decode_delta_zigzag(&self.packed_analog, &mut pos)?;
+        self.analog_probe2 = decode_delta_zigzag(&self.packed_analog, &mut pos)?;
+        self.packed_analog = Vec::new();
+        Ok(())
+    }
+}
+
+impl EventDataBatch {
+    /// Pack the analog probes of every waveform (no-op if already packed)
+    pub fn compress_waveforms(&mut self) {
+        if self.waveform_encoding == WaveformEncoding::DeltaZigzag {
+            return;
+        }
+        for wf in self.events.iter_mut().filter_map(|e| e.waveform.as_mut()) {
+            wf.pack_analog();
+        }
+        self.waveform_encoding = WaveformEncoding::DeltaZigzag;
+    }
+
+    /// Restore plain analog samples (no-op for raw batches)
+    pub fn decompress_waveforms(&mut self) -> Result<(), WaveCodecError> {
+        if self.waveform_encoding == WaveformEncoding::Raw {
+            return Ok(());
+        }
+        for wf in self.events.iter_mut().filter_map(|e| e.waveform.as_mut()) {
+            wf.unpack_analog()?;
+        }
+        self.waveform_encoding = WaveformEncoding::Raw;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::{EventData, Message};
+
+    /// Baseline with a fast rise and exponential decay, plus a little ripple
+    fn pulse(samples: usize) -> Vec<i16> {
+        (0..samples)
+            .map(|i| {
+                let ripple = (i % 3) as f64 - 1.0;
+                let signal = if i < 100 {
+                    0.0
+                } else {
+                    -8000.0 * (-((i - 100) as f64) / 80.0).exp()
+                };
+                (200.0 + ripple + signal) as i16
+            })
+            .collect()
+    }
+
+    fn waveform_batch() -> EventDataBatch {
+        let mut batch = EventDataBatch::new(1, 0);
+        for ch in 0..8u8 {
+            let wf = Waveform {
+                analog_probe1: pulse(1024),
+                analog_probe2: vec![i16::MIN, i16::MAX, 0, -1, 1],
+                ..Default::default()
+            };
+            batch.push(EventData::with_waveform(0, ch, 1000, 800, 10.0, 0, wf));
+        }
+        batch.push(EventData::new(0, 9, 500, 400, 20.0, 0));
+        batch
+    }
+
+    #[test]
+    fn test_delta_zigzag_roundtrip() {
+        let samples = pulse(512);
+        let mut bytes = Vec::new();
+        encode_delta_zigzag(&samples, &mut bytes);
+        encode_delta_zigzag(&[i16::MIN, i16::MAX, i16::MIN], &mut bytes);
+
+        let mut pos = 0;
+        assert_eq!(decode_delta_zigzag(&bytes, &mut pos).unwrap(), samples);
+        assert_eq!(
+            decode_delta_zigzag(&bytes, &mut pos).unwrap(),
+            vec![i16::MIN, i16::MAX, i16::MIN]
+        );
+        assert_eq!(pos, bytes.len());
+
+        // Cut off inside the second probe
+        let mut pos = 0;
+        let cut = &bytes[..bytes.len() - 1];
+        decode_delta_zigzag(cut, &mut pos).unwrap();
+        assert!(decode_delta_zigzag(cut, &mut pos).is_err());
+    }
+
+    #[test]
+    fn test_compressed_batch_roundtrip_and_size() {
+        let raw = waveform_batch();
+        let raw_bytes = Message::data(raw.clone()).to_msgpack().unwrap();
+
+        let mut packed = raw.clone();
+        packed.compress_waveforms();
+        assert_eq!(packed.waveform_encoding, WaveformEncoding::DeltaZigzag);
+        let packed_bytes = Message::data(packed).to_msgpack().unwrap();
+        assert!(
+            packed_bytes.len() * 3 < raw_bytes.len() * 2,
+            "packed {} bytes, raw {} bytes",
+            packed_bytes.len(),
+            raw_bytes.len()
+        );
+
+        // Receivers get plain samples back
+        let Message::Data(decoded) = Message::from_msgpack(&packed_bytes).unwrap() else {
+            panic!("Expected Data variant");
+        };
+        assert_eq!(decoded.waveform_encoding, WaveformEncoding::Raw);
+        assert_eq!(decoded.events, raw.events);
+    }
+}
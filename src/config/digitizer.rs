@@ -96,8 +96,20 @@ fn default_num_channels() -> u8 {
     32
 }
 
+/// DC offset is a percentage of the ADC range
+fn check_dc_offset(section: &str, config: &ChannelConfig, problems: &mut Vec<String>) {
+    if let Some(offset) = config.dc_offset {
+        if !(0.0..=100.0).contains(&offset) {
+            problems.push(format!(
+                "{}.dc_offset = {} is outside 0-100%",
+                section, offset
+            ));
+        }
+    }
+}
+
 /// Supported firmware types
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, ToSchema)]
 pub enum FirmwareType {
     /// DPP-PSD firmware (legacy x725/x730)
     PSD1,
@@ -105,6 +117,8 @@ pub enum FirmwareType {
     PSD2,
     /// DPP-PHA firmware (for spectroscopy)
     PHA,
+    /// DPP-ZLE firmware (x27xx series, zero length encoded waveforms)
+    ZLE,
 }
 
 impl FirmwareType {
@@ -114,13 +128,14 @@ impl FirmwareType {
             FirmwareType::PSD1 => "dig1://",
             FirmwareType::PSD2 => "dig2://",
             FirmwareType::PHA => "dig2://", // PHA uses same scheme as PSD2
+            FirmwareType::ZLE => "dig2://",
         }
     }
 
     /// Whether the readout endpoint needs N_EVENTS configured.
-    /// DIG2 (PSD2) requires DATA + SIZE + N_EVENTS; DIG1 (PSD1/PHA) uses DATA + SIZE only.
+    /// DIG2 (PSD2/ZLE) requires DATA + SIZE + N_EVENTS; DIG1 (PSD1/PHA) uses DATA + SIZE only.
     pub fn includes_n_events(&self) -> bool {
-        matches!(self, FirmwareType::PSD2)
+        matches!(self, FirmwareType::PSD2 | FirmwareType::ZLE)
     }
 
     /// Whether this firmware uses the DIG1 (legacy) protocol.
@@ -227,6 +242,81 @@ pub struct CaenParameter {
     pub value: String,
 }
 
+impl CaenParameter {
+    /// Parameter name without the path prefix (e.g., "TriggerThr")
+    pub fn name(&self) -> &str {
+        self.path
+            .rsplit('/')
+            .find(|s| !s.is_empty())
+            .unwrap_or(&self.path)
+    }
+}
+
+/// Declared limits of a parameter (DevTree `minvalue`/`maxvalue`/`allowedvalues`)
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ParamRange {
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    pub allowed_values: Vec<String>,
+}
+
+impl ParamRange {
+    /// Check whether a value satisfies the declared limits
+    ///
+    /// Enum values are compared case-insensitively, as FELib does.
+    /// A range with no limits accepts any value.
+    pub fn contains(&self, value: &str) -> bool {
+        if !self.allowed_values.is_empty() {
+            return self
+                .allowed_values
+                .iter()
+                .any(|v| v.eq_ignore_ascii_case(value));
+        }
+        if self.min.is_none() && self.max.is_none() {
+            return true;
+        }
+        match value.trim().parse::<f64>() {
+            Ok(v) => self.min.is_none_or(|min| v >= min) && self.max.is_none_or(|max| v <= max),
+            Err(_) => false,
+        }
+    }
+
+    /// Human-readable description of the allowed values
+    pub fn describe(&self) -> String {
+        if !self.allowed_values.is_empty() {
+            return format!("one of [{}]", self.allowed_values.join(", "));
+        }
+        let min = self
+            .min
+            .map_or_else(|| "-inf".to_string(), |v| v.to_string());
+        let max = self
+            .max
+            .map_or_else(|| "+inf".to_string(), |v| v.to_string());
+        format!("[{}, {}]", min, max)
+    }
+}
+
+/// A parameter value rejected by [`DigitizerConfig::validate`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValidationError {
+    /// Full CAEN parameter path
+    pub path: String,
+    /// Value the configuration attempted to write
+    pub value: String,
+    /// Description of the allowed range
+    pub allowed: String,
+}
+
+impl std::fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} = {} is out of range (allowed: {})",
+            self.path, self.value, self.allowed
+        )
+    }
+}
+
 /// Error type for digitizer configuration
 #[derive(Debug)]
 pub enum DigitizerConfigError {
@@ -285,7 +375,7 @@ impl DigitizerConfig {
     pub fn new(digitizer_id: u32, name: impl Into<String>, firmware: FirmwareType) -> Self {
         let num_channels = match firmware {
             FirmwareType::PSD1 => 8,
-            FirmwareType::PSD2 | FirmwareType::PHA => 32,
+            FirmwareType::PSD2 | FirmwareType::PHA | FirmwareType::ZLE => 32,
         };
 
         Self {
@@ -303,6 +393,51 @@ impl DigitizerConfig {
         }
     }
 
+    /// Built-in defaults for a fresh board of the given firmware
+    ///
+    /// All channels enabled and self-triggering on negative pulses with a
+    /// moderate threshold, waveforms off (except ZLE, whose data are
+    /// waveforms). Start and sync sources are left as they are so that
+    /// master/slave cabling keeps working.
+    pub fn firmware_defaults(firmware: FirmwareType) -> Self {
+        let mut config = Self::new(0, format!("{:?} defaults", firmware), firmware);
+        let channel = &mut config.channel_defaults;
+        channel.dc_offset = Some(20.0);
+        channel.polarity = Some("Negative".to_string());
+        match firmware {
+            FirmwareType::PSD1 => {
+                config.board.record_length = Some(1024);
+                channel.enabled = Some("TRUE".to_string());
+                channel.trigger_threshold = Some(100);
+                channel.gate_long_ns = Some(400);
+                channel.gate_short_ns = Some(100);
+                channel.gate_pre_ns = Some(50);
+            }
+            FirmwareType::PHA => {
+                config.board.record_length = Some(1024);
+                channel.enabled = Some("True".to_string());
+                channel.trigger_threshold = Some(100);
+            }
+            FirmwareType::PSD2 => {
+                config.board.waveforms_enabled = Some(false);
+                channel.enabled = Some("True".to_string());
+                channel.trigger_threshold = Some(500);
+                channel.gate_long_ns = Some(400);
+                channel.gate_short_ns = Some(100);
+                channel.event_trigger_source = Some("ChSelfTrigger".to_string());
+                channel.wave_trigger_source = Some("Disabled".to_string());
+            }
+            FirmwareType::ZLE => {
+                config.board.waveforms_enabled = Some(true);
+                channel.enabled = Some("True".to_string());
+                channel.trigger_threshold = Some(500);
+                channel.event_trigger_source = Some("ChSelfTrigger".to_string());
+                channel.wave_trigger_source = Some("ChSelfTrigger".to_string());
+            }
+        }
+        config
+    }
+
     /// Create a master digitizer config
     pub fn new_master(digitizer_id: u32, name: impl Into<String>, firmware: FirmwareType) -> Self {
         let mut config = Self::new(digitizer_id, name, firmware);
@@ -393,6 +528,58 @@ impl DigitizerConfig {
         params
     }
 
+    /// Validate all parameters against declared limits before writing them
+    ///
+    /// `lookup` returns the limits for a parameter (typically from the device
+    /// DevTree); parameters without known limits are not checked.
+    pub fn validate<F>(&self, mut lookup: F) -> Vec<ValidationError>
+    where
+        F: FnMut(&CaenParameter) -> Option<ParamRange>,
+    {
+        self.to_caen_parameters()
+            .into_iter()
+            .filter_map(|param| {
+                let range = lookup(&param)?;
+                if range.contains(&param.value) {
+                    None
+                } else {
+                    Some(ValidationError {
+                        allowed: range.describe(),
+                        path: param.path,
+                        value: param.value,
+                    })
+                }
+            })
+            .collect()
+    }
+
+    /// Check the configuration for inconsistencies that need no device limits
+    ///
+    /// Returns one message per problem (empty if consistent). Run this before
+    /// [`Self::to_caen_parameters`]: a zero `num_channels` has no channel range.
+    pub fn check_structure(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        if self.num_channels == 0 {
+            problems.push("num_channels must be at least 1".to_string());
+        }
+
+        check_dc_offset("channel_defaults", &self.channel_defaults, &mut problems);
+
+        let mut channels: Vec<u8> = self.channel_overrides.keys().copied().collect();
+        channels.sort_unstable();
+        for ch in channels {
+            if ch >= self.num_channels {
+                problems.push(format!(
+                    "channel_overrides has channel {}, but the digitizer has {} channels",
+                    ch, self.num_channels
+                ));
+            }
+            let section = format!("channel_overrides.{}", ch);
+            check_dc_offset(&section, &self.channel_overrides[&ch], &mut problems);
+        }
+        problems
+    }
+
     fn add_board_parameters(&self, params: &mut Vec<CaenParameter>) {
         let board = &self.board;
 
@@ -522,7 +709,7 @@ impl DigitizerConfig {
         // Parameter names differ between PSD1 and PSD2
         let (enable_name, offset_name, polarity_name, threshold_name) = match self.firmware {
             FirmwareType::PSD1 => ("ch_enabled", "ch_dcoffset", "ch_polarity", "ch_threshold"),
-            FirmwareType::PSD2 | FirmwareType::PHA => {
+            FirmwareType::PSD2 | FirmwareType::PHA | FirmwareType::ZLE => {
                 ("ChEnable", "DCOffset", "PulsePolarity", "TriggerThr")
             }
         };
@@ -941,4 +1128,117 @@ mod tests {
         let ch1 = config.get_channel_config(1);
         assert_eq!(ch1.enabled, Some("False".to_string())); // Overridden
     }
+
+    #[test]
+    fn test_psd2_defaults_parameters() {
+        let config = DigitizerConfig::firmware_defaults(FirmwareType::PSD2);
+        assert!(config.check_structure().is_empty());
+
+        let params = config.to_caen_parameters();
+        let names: Vec<&str> = params.iter().map(|p| p.name()).collect();
+        for key in [
+            "ChEnable",
+            "DCOffset",
+            "PulsePolarity",
+            "TriggerThr",
+            "GateLongLengthT",
+            "GateShortLengthT",
+            "EventTriggerSource",
+        ] {
+            assert!(names.contains(&key), "missing {}", key);
+        }
+        // Channel settings cover every channel through the range path
+        assert!(params
+            .iter()
+            .any(|p| p.path == "/ch/0..31/par/TriggerThr" && p.value == "500"));
+        // Start source is left to the board / sync setup
+        assert!(!names.contains(&"startsource"));
+        // Defaults pass range validation with the usual threshold limits
+        assert!(config.validate(threshold_range).is_empty());
+    }
+
+    #[test]
+    fn test_defaults_for_every_firmware_are_consistent() {
+        for firmware in [
+            FirmwareType::PSD1,
+            FirmwareType::PSD2,
+            FirmwareType::PHA,
+            FirmwareType::ZLE,
+        ] {
+            let config = DigitizerConfig::firmware_defaults(firmware);
+            assert_eq!(config.firmware, firmware);
+            assert!(config.check_structure().is_empty(), "{:?}", firmware);
+            assert!(!config.to_caen_parameters().is_empty(), "{:?}", firmware);
+        }
+    }
+
+    fn threshold_range(param: &CaenParameter) -> Option<ParamRange> {
+        (param.name() == "TriggerThr").then(|| ParamRange {
+            min: Some(0.0),
+            max: Some(16383.0),
+            allowed_values: vec![],
+        })
+    }
+
+    #[test]
+    fn test_validate_rejects_out_of_range_value() {
+        let mut config = DigitizerConfig::new(0, "test", FirmwareType::PSD2);
+        config.channel_defaults.trigger_threshold = Some(500);
+        config.channel_overrides.insert(
+            3,
+            ChannelConfig {
+                trigger_threshold: Some(20000),
+                ..Default::default()
+            },
+        );
+
+        let errors = config.validate(threshold_range);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].path, "/ch/3/par/TriggerThr");
+        assert_eq!(errors[0].value, "20000");
+        assert_eq!(errors[0].allowed, "[0, 16383]");
+    }
+
+    #[test]
+    fn test_validate_accepts_valid_value() {
+        let mut config = DigitizerConfig::new(0, "test", FirmwareType::PSD2);
+        config.channel_defaults.trigger_threshold = Some(16383);
+
+        assert!(config.validate(threshold_range).is_empty());
+    }
+
+    #[test]
+    fn test_check_structure() {
+        let mut config = DigitizerConfig::new(0, "test", FirmwareType::PSD1);
+        config.channel_defaults.dc_offset = Some(20.0);
+        assert!(config.check_structure().is_empty());
+
+        config.channel_overrides.insert(
+            16,
+            ChannelConfig {
+                dc_offset: Some(120.0),
+                ..Default::default()
+            },
+        );
+        assert_eq!(
+            config.check_structure(),
+            vec![
+                "channel_overrides has channel 16, but the digitizer has 8 channels",
+                "channel_overrides.16.dc_offset = 120 is outside 0-100%",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_param_range_allowed_values() {
+        let range = ParamRange {
+            min: None,
+            max: None,
+            allowed_values: vec!["Positive".to_string(), "Negative".to_string()],
+        };
+        assert!(range.contains("negative"));
+        assert!(!range.contains("Bipolar"));
+        assert_eq!(range.describe(), "one of [Positive, Negative]");
+        assert!(ParamRange::default().contains("anything"));
+    }
 }
@@ -0,0 +1,148 @@
+//! Environment variable substitution for config files
+//!
+//! Before the TOML is parsed, every `${VAR}` is replaced by the value of
+//! `VAR` from the process environment, and every `${VAR:-default}` by that
+//! value or `default` when `VAR` is unset or empty. A `${VAR}` whose variable
+//! is unset is an error rather than an empty string, so a typo cannot
+//! silently produce an address like `tcp://:5555`.
+//!
+//! Placeholders are expanded anywhere in the file (comments included); they
+//! are intended for address fields such as `bind`, `subscribe`, `publish`,
+//! `command` and `digitizer_url`. Write `$${` for a literal `${`.
+
+use super::ConfigError;
+
+/// Expand `${VAR}` / `${VAR:-default}` placeholders using `lookup`
+pub(crate) fn expand_env_vars(
+    content: &str,
+    lookup: impl Fn(&str) -> Option<String>,
+) -> Result<String, ConfigError> {
+    let mut out = String::with_capacity(content.len());
+    let mut rest = content;
+
+    while let Some(start) = rest.find('$') {
+        out.push_str(&rest[..start]);
+        let tail = &rest[start..];
+
+        if let Some(escaped) = tail.strip_prefix("$${") {
+            out.push_str("${");
+            rest = escaped;
+            continue;
+        }
+        let Some(placeholder) = tail.strip_prefix("${") else {
+            out.push('$');
+            rest = &tail[1..];
+            continue;
+        };
+
+        let end = placeholder
+            .find('}')
+            .ok_or_else(|| ConfigError::InvalidPlaceholder(truncate(tail)))?;
+        let body = &placeholder[..end];
+        let (name, default) = match body.split_once(":-") {
+            Some((name, default)) => (name, Some(default)),
+            None => (body, None),
+        };
+        if !is_valid_name(name) {
+            return Err(ConfigError::InvalidPlaceholder(format!("${{{}}}", body)));
+        }
+
+        let value = match (lookup(name).filter(|v| !v.is_empty()), default) {
+            (Some(value), _) => value,
+            (None, Some(default)) => default.to_string(),
+            (None, None) => return Err(ConfigError::MissingEnvVar(name.to_string())),
+        };
+        out.push_str(&value);
+        rest = &placeholder[end + 1..];
+    }
+
+    out.push_str(rest);
+    Ok(out)
+}
+
+fn is_valid_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Short excerpt of an unterminated placeholder for the error message
+fn truncate(s: &str) -> String {
+    s.lines().next().unwrap_or(s).chars().take(40).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    fn lookup(name: &str) -> Option<String> {
+        match name {
+            "HOST" => Some("daq01".to_string()),
+            "EMPTY" => Some(String::new()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_expand_placeholders() {
+        let expanded = expand_env_vars(
+            "a = \"tcp://${HOST}:5555\"\nb = \"${PORT:-5556}\"\nc = \"${EMPTY:-x}\"\nd = \"$HOME $${LIT}\"",
+            lookup,
+        )
+        .unwrap();
+        assert_eq!(
+            expanded,
+            "a = \"tcp://daq01:5555\"\nb = \"5556\"\nc = \"x\"\nd = \"$HOME ${LIT}\""
+        );
+    }
+
+    #[test]
+    fn test_missing_and_malformed_placeholders() {
+        assert!(matches!(
+            expand_env_vars("${NOPE}", lookup),
+            Err(ConfigError::MissingEnvVar(ref name)) if name == "NOPE"
+        ));
+        assert!(matches!(
+            expand_env_vars("bind = \"${HOST\"", lookup),
+            Err(ConfigError::InvalidPlaceholder(_))
+        ));
+        assert!(matches!(
+            expand_env_vars("${1BAD}", lookup),
+            Err(ConfigError::InvalidPlaceholder(_))
+        ));
+    }
+
+    #[test]
+    fn test_load_resolves_env_vars() {
+        std::env::set_var("DELILA_TEST_ENV_BIND_HOST", "10.0.0.5");
+        std::env::remove_var("DELILA_TEST_ENV_UNSET_PORT");
+
+        let path = std::env::temp_dir().join(format!(
+            "delila_env_config_test_{}.toml",
+            std::process::id()
+        ));
+        std::fs::write(
+            &path,
+            r#"
+[network]
+[[network.sources]]
+id = 0
+bind = "tcp://${DELILA_TEST_ENV_BIND_HOST}:5555"
+digitizer_url = "dig2://${DELILA_TEST_ENV_BIND_HOST}"
+command = "tcp://*:${DELILA_TEST_ENV_UNSET_PORT:-5560}"
+"#,
+        )
+        .unwrap();
+
+        let config = Config::load(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+
+        let source = &config.network.sources[0];
+        assert_eq!(source.bind, "tcp://10.0.0.5:5555");
+        assert_eq!(source.digitizer_url.as_deref(), Some("dig2://10.0.0.5"));
+        assert_eq!(source.command.as_deref(), Some("tcp://*:5560"));
+    }
+}
@@ -12,13 +12,18 @@
 //! ```
 
 pub mod digitizer;
+mod env;
+#[cfg(feature = "mongodb")]
+mod mongo_settings;
 
 pub use digitizer::{
     BoardConfig, CaenParameter, ChannelConfig, DigitizerConfig, DigitizerConfigError, FirmwareType,
     SyncConfig,
 };
 
-use serde::Deserialize;
+use crate::common::SocketOptions;
+use serde::{Deserialize, Deserializer};
+use std::collections::HashMap;
 use std::path::Path;
 use thiserror::Error;
 
@@ -37,8 +42,25 @@ pub enum ConfigError {
     #[error("Missing required field: {0}")]
     MissingField(String),
 
-    #[error("MongoDB not yet supported")]
-    MongoDbNotSupported,
+    #[error("Environment variable {0} is not set and has no default")]
+    MissingEnvVar(String),
+
+    #[error("Invalid placeholder: {0}")]
+    InvalidPlaceholder(String),
+
+    #[error("Invalid network topology: {}", .0.join("; "))]
+    Invalid(Vec<String>),
+
+    #[error("MongoDB settings must be loaded with SettingsConfig::get_settings_async (requires the `mongodb` feature)")]
+    MongoDbRequiresAsync,
+
+    #[cfg(feature = "mongodb")]
+    #[error("MongoDB error: {0}")]
+    MongoDb(#[from] mongodb::error::Error),
+
+    #[cfg(feature = "mongodb")]
+    #[error("Invalid settings document: {0}")]
+    SettingsDocument(#[from] mongodb::bson::de::Error),
 }
 
 /// Top-level configuration
@@ -58,12 +80,16 @@ pub struct OperatorFileConfig {
     /// Experiment name (server-authoritative, not editable by UI)
     #[serde(default = "default_experiment_name")]
     pub experiment_name: String,
+    /// Interval between WebSocket status pushes in milliseconds
+    #[serde(default = "default_status_interval_ms")]
+    pub status_interval_ms: u64,
 }
 
 impl Default for OperatorFileConfig {
     fn default() -> Self {
         Self {
             experiment_name: default_experiment_name(),
+            status_interval_ms: default_status_interval_ms(),
         }
     }
 }
@@ -72,11 +98,22 @@ fn default_experiment_name() -> String {
     "DefaultExp".to_string()
 }
 
+fn default_status_interval_ms() -> u64 {
+    1000
+}
+
 impl Config {
     /// Load configuration from a TOML file
+    ///
+    /// `${VAR}` and `${VAR:-default}` placeholders are replaced from the
+    /// process environment before parsing (see `config/env.rs`). Precedence for an
+    /// address is: command-line option, then the environment variable, then
+    /// the placeholder default; fields without placeholders are taken as written.
     pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, ConfigError> {
         let content = std::fs::read_to_string(path)?;
+        let content = env::expand_env_vars(&content, |name| std::env::var(name).ok())?;
         let config: Config = toml::from_str(&content)?;
+        config.validate()?;
         Ok(config)
     }
 
@@ -90,6 +127,168 @@ impl Config {
     pub fn get_source(&self, source_id: u32) -> Option<&SourceNetworkConfig> {
         self.network.sources.iter().find(|s| s.id == source_id)
     }
+
+    /// Check the network topology for consistency
+    ///
+    /// Every problem found is reported, not just the first:
+    /// - source IDs are unique
+    /// - each merger `subscribe` matches a source `bind`
+    /// - recorder/monitor `subscribe` matches the merger `publish` or a source `bind`
+    /// - at most one digitizer source is the master
+    /// - every subscriber has a higher `pipeline_order` than what it subscribes to
+    /// - no data address uses `inproc://` (components run as separate processes)
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let network = &self.network;
+        let mut problems = Vec::new();
+
+        for (i, source) in network.sources.iter().enumerate() {
+            if network.sources[..i].iter().any(|s| s.id == source.id) {
+                problems.push(format!("duplicate source id {}", source.id));
+            }
+        }
+
+        let masters: Vec<String> = network
+            .sources
+            .iter()
+            .filter(|s| s.is_master_digitizer())
+            .map(|s| s.id.to_string())
+            .collect();
+        if masters.len() > 1 {
+            problems.push(format!(
+                "only one master digitizer is allowed, found sources {}",
+                masters.join(", ")
+            ));
+        }
+
+        // (name, bind/publish address, pipeline_order) of everything that publishes
+        let sources: Vec<(String, &str, u32)> = network
+            .sources
+            .iter()
+            .flat_map(|s| {
+                std::iter::once(&s.bind)
+                    .chain(&s.extra_binds)
+                    .map(move |bind| (format!("source {}", s.id), bind.as_str(), s.pipeline_order))
+            })
+            .collect();
+        let mut upstream = sources.clone();
+        if let Some(ref merger) = network.merger {
+            upstream.push((
+                "merger".to_string(),
+                merger.publish.as_str(),
+                merger.pipeline_order,
+            ));
+        }
+
+        if let Some(ref merger) = network.merger {
+            for address in &merger.subscribe {
+                check_subscribe(
+                    &mut problems,
+                    "merger",
+                    address,
+                    merger.pipeline_order,
+                    &sources,
+                );
+            }
+        }
+        if let Some(ref recorder) = network.recorder {
+            check_subscribe(
+                &mut problems,
+                "recorder",
+                &recorder.subscribe,
+                recorder.pipeline_order,
+                &upstream,
+            );
+        }
+        if let Some(ref monitor) = network.monitor {
+            check_subscribe(
+                &mut problems,
+                "monitor",
+                &monitor.subscribe,
+                monitor.pipeline_order,
+                &upstream,
+            );
+        }
+
+        // inproc endpoints live in one ZMQ context, and every component creates its own
+        let mut data_addresses: Vec<(String, &str)> = sources
+            .iter()
+            .map(|(name, bind, _)| (name.clone(), *bind))
+            .collect();
+        if let Some(ref merger) = network.merger {
+            data_addresses.extend(
+                merger
+                    .subscribe
+                    .iter()
+                    .map(|a| ("merger".to_string(), a.as_str())),
+            );
+            data_addresses.push(("merger".to_string(), merger.publish.as_str()));
+        }
+        if let Some(ref recorder) = network.recorder {
+            data_addresses.push(("recorder".to_string(), recorder.subscribe.as_str()));
+        }
+        if let Some(ref monitor) = network.monitor {
+            data_addresses.push(("monitor".to_string(), monitor.subscribe.as_str()));
+        }
+        for (name, address) in data_addresses {
+            if address.starts_with("inproc://") {
+                problems.push(format!(
+                    "{} uses {}, which cannot cross processes (use ipc:// instead)",
+                    name, address
+                ));
+            }
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(ConfigError::Invalid(problems))
+        }
+    }
+}
+
+/// Check that `address` reaches one of `producers` and comes after it in the pipeline
+fn check_subscribe(
+    problems: &mut Vec<String>,
+    consumer: &str,
+    address: &str,
+    order: u32,
+    producers: &[(String, &str, u32)],
+) {
+    match producers
+        .iter()
+        .find(|(_, bind, _)| endpoints_match(bind, address))
+    {
+        None => problems.push(format!(
+            "{} subscribes to {}, which nothing binds",
+            consumer, address
+        )),
+        Some((producer, _, producer_order)) if *producer_order >= order => problems.push(format!(
+            "{} (pipeline_order {}) must come after {} (pipeline_order {})",
+            consumer, order, producer, producer_order
+        )),
+        Some(_) => {}
+    }
+}
+
+/// Whether a connect address reaches a bind address
+///
+/// TCP endpoints match on port when the hosts are equal or the bind host is a
+/// wildcard (`*`, `0.0.0.0`), since the connecting side names a concrete host
+/// such as `localhost`. The config does not say which host a component runs
+/// on, so remote hosts are not checked: `tcp://daq02:5557` reaches a
+/// `tcp://*:5557` bind like `tcp://localhost:5557` does. Other transports
+/// (e.g. `ipc://` socket paths) must match exactly.
+fn endpoints_match(bind: &str, connect: &str) -> bool {
+    fn split_tcp(address: &str) -> Option<(&str, &str)> {
+        address.strip_prefix("tcp://")?.rsplit_once(':')
+    }
+
+    match (split_tcp(bind), split_tcp(connect)) {
+        (Some((bind_host, bind_port)), Some((host, port))) => {
+            bind_port == port && (bind_host == host || bind_host == "*" || bind_host == "0.0.0.0")
+        }
+        _ => bind == connect,
+    }
 }
 
 // =============================================================================
@@ -137,7 +336,7 @@ pub enum SourceType {
     /// CAEN PHA firmware (via CAEN library)
     #[serde(alias = "PHA1", alias = "pha1")]
     Pha1,
-    /// CAEN DPP-ZLE firmware (future)
+    /// CAEN DPP-ZLE firmware (via dig2 library)
     #[serde(alias = "ZLE", alias = "zle")]
     Zle,
 }
@@ -154,6 +353,43 @@ impl std::fmt::Display for SourceType {
     }
 }
 
+/// Default wait between Reader reconnection attempts (ms)
+pub const DEFAULT_RECONNECT_BACKOFF_MS: u64 = 1000;
+
+/// Default number of Reader reconnection attempts
+pub const DEFAULT_MAX_RECONNECT_ATTEMPTS: u32 = 5;
+
+/// What the Reader's read loop does when the decode queue is full
+#[derive(Debug, Clone, Copy, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum DecodeQueuePolicy {
+    /// Wait for the decoder (lossless; the digitizer buffers meanwhile)
+    #[default]
+    Block,
+    /// Discard the buffer and count it in `dropped_raw`
+    Drop,
+}
+
+/// Events the Reader drops before publishing (all conditions must pass)
+///
+/// Declarative on purpose: a few cheap checks per event, no expression
+/// language. The default filter passes everything.
+#[derive(Debug, Clone, Default, Deserialize, PartialEq, Eq)]
+pub struct EventFilter {
+    /// Drop events with a lower energy (default: no limit)
+    #[serde(default)]
+    pub min_energy: Option<u32>,
+    /// Drop events with a higher energy (default: no limit)
+    #[serde(default)]
+    pub max_energy: Option<u32>,
+    /// Keep only these channels (default: all)
+    #[serde(default)]
+    pub channels: Option<Vec<u8>>,
+    /// Drop events flagged as pileup
+    #[serde(default)]
+    pub drop_pileup: bool,
+}
+
 /// Data source (emulator/digitizer) network config
 #[derive(Debug, Clone, Deserialize)]
 pub struct SourceNetworkConfig {
@@ -171,6 +407,13 @@ pub struct SourceNetworkConfig {
     /// ZMQ bind address for data (e.g., "tcp://*:5555")
     pub bind: String,
 
+    /// Further data bind addresses publishing the same stream (Reader only)
+    ///
+    /// Lets e.g. a local Monitor subscribe next to the Merger without a
+    /// broker; every message is published once and reaches all endpoints.
+    #[serde(default)]
+    pub extra_binds: Vec<String>,
+
     /// ZMQ bind address for commands (e.g., "tcp://*:5560")
     #[serde(default)]
     pub command: Option<String>,
@@ -180,6 +423,11 @@ pub struct SourceNetworkConfig {
     #[serde(default)]
     pub config_file: Option<String>,
 
+    /// Without `config_file`, apply built-in defaults for the firmware on
+    /// Configure instead of keeping the board's settings (default: false)
+    #[serde(default)]
+    pub apply_defaults: bool,
+
     /// Digitizer URL (e.g., "dig2://172.18.4.56")
     /// Required for PSD2; optional for PSD1/PHA1 (uses USB/Optical)
     #[serde(default)]
@@ -189,10 +437,20 @@ pub struct SourceNetworkConfig {
     #[serde(default)]
     pub module_id: Option<u8>,
 
+    /// Channel → module overrides for boards split into logical modules
+    ///
+    /// e.g. `module_map = { 0 = 1, 16 = 2 }`. Unlisted channels keep `module_id`.
+    #[serde(default, deserialize_with = "deserialize_channel_map")]
+    pub module_map: HashMap<u8, u8>,
+
     /// ADC time step in nanoseconds (default: 2.0 for 500 MHz)
     #[serde(default)]
     pub time_step_ns: Option<f64>,
 
+    /// Energy resolution in bits (default: 16, e.g. 14 for a 14-bit board)
+    #[serde(default)]
+    pub adc_bits: Option<u8>,
+
     /// Pipeline order for Start/Stop sequencing (1 = upstream, default: 1)
     #[serde(default = "default_source_pipeline_order")]
     pub pipeline_order: u32,
@@ -208,13 +466,183 @@ pub struct SourceNetworkConfig {
     /// 2. Start master only → Slaves auto-start via TrgOut
     #[serde(default)]
     pub is_master: bool,
+
+    /// Reject Configure if any digitizer parameter is outside its declared range
+    #[serde(default)]
+    pub strict_validation: bool,
+
+    /// Maximum serialized size of one published message in bytes (0 = no limit)
+    ///
+    /// Larger batches are split into fragments (see `common::fragment`).
+    #[serde(default = "default_max_message_bytes")]
+    pub max_message_bytes: usize,
+
+    /// Directory for raw (undecoded) digitizer buffer files
+    ///
+    /// When set, the Reader records every buffer before decoding so the run
+    /// can be re-decoded offline with `raw_decode`.
+    #[serde(default)]
+    pub raw_record_dir: Option<String>,
+
+    /// Dump every raw aggregate to `dump_dir/dump_{id}_{timestamp}.dlraw`
+    /// and print decoder diagnostics (default: false)
+    ///
+    /// Same format as `raw_record_dir`, so a problematic run can be replayed
+    /// through the decoder with `raw_decode`.
+    #[serde(default)]
+    pub dump_raw: bool,
+
+    /// Directory for raw dump files (default: "./dump")
+    #[serde(default = "default_dump_dir")]
+    pub dump_dir: String,
+
+    /// Raw buffers queued between the Reader's read and decode loops (default: 256)
+    #[serde(default = "default_decode_channel_capacity")]
+    pub decode_channel_capacity: usize,
+
+    /// Wait between the Reader's reconnection attempts after a lost link
+    /// in ms (default: 1000)
+    #[serde(default = "default_reconnect_backoff_ms")]
+    pub reconnect_backoff_ms: u64,
+
+    /// Reconnection attempts before the Reader gives up and enters Error
+    /// (default: 5, 0 = no reconnection)
+    #[serde(default = "default_max_reconnect_attempts")]
+    pub max_reconnect_attempts: u32,
+
+    /// Behaviour when the decode queue is full (default: block)
+    #[serde(default)]
+    pub decode_queue_policy: DecodeQueuePolicy,
+
+    /// Threads decoding the Reader's event buffers in parallel (default: 1)
+    ///
+    /// Batches are still published in read order with monotonic sequence
+    /// numbers; more workers only help when decoding is the bottleneck.
+    #[serde(default = "default_decode_workers")]
+    pub decode_workers: usize,
+
+    /// Emulator RNG seed for reproducible runs (default: random)
+    #[serde(default)]
+    pub seed: Option<u64>,
+
+    /// Emulator channels that generate events, one bit per channel (default: all)
+    #[serde(default)]
+    pub channel_mask: Option<u64>,
+
+    /// Topic frame sent before every data message (default: none)
+    ///
+    /// Consumers list topic prefixes in `subscribe_topics` to receive only
+    /// some of the sources sharing a PUB address (see `common::topic`).
+    #[serde(default)]
+    pub topic_prefix: Option<String>,
+
+    /// CPU core for the Reader's read thread (default: unpinned)
+    #[serde(default)]
+    pub read_core: Option<usize>,
+
+    /// CPU core for the Reader's decode thread (default: unpinned)
+    ///
+    /// With `decode_workers > 1`, the workers are pinned to the cores that
+    /// follow it (`decode_core + 1`, `decode_core + 2`, ...).
+    #[serde(default)]
+    pub decode_core: Option<usize>,
+
+    /// Delta-code analog waveform probes before publishing (default: false)
+    #[serde(default)]
+    pub compress_waveforms: bool,
+
+    /// Publish energies above 16 bits (default: false = clamp to 65535, as
+    /// consumers built for the 16-bit energy field expect)
+    #[serde(default)]
+    pub wide_energy: bool,
+
+    /// Flag Reader events whose timestamp jumps further than this from the
+    /// last good one (default: no check)
+    #[serde(default)]
+    pub timestamp_sanity_window_ns: Option<f64>,
+
+    /// Drop flagged timestamp outliers instead of publishing them (default: false)
+    #[serde(default)]
+    pub drop_timestamp_outliers: bool,
+
+    /// Decoded events the Reader keeps for GetRecentEvents (default: 1000, 0 = off)
+    #[serde(default = "default_recent_events_capacity")]
+    pub recent_events_capacity: usize,
+
+    /// Events the Reader drops at the source (default: none)
+    #[serde(default)]
+    pub event_filter: EventFilter,
+
+    /// ZMQ send high-water mark of the data socket (default: ZMQ's 1000)
+    #[serde(default)]
+    pub send_hwm: Option<i32>,
+
+    /// ZMQ linger of the data socket in ms (default: ZMQ's -1 = until sent)
+    #[serde(default)]
+    pub linger_ms: Option<i32>,
+}
+
+/// Read a `{ channel = module }` table (TOML keys are strings)
+fn deserialize_channel_map<'de, D>(deserializer: D) -> Result<HashMap<u8, u8>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    HashMap::<String, u8>::deserialize(deserializer)?
+        .into_iter()
+        .map(|(channel, module)| {
+            channel
+                .trim()
+                .parse::<u8>()
+                .map(|channel| (channel, module))
+                .map_err(|_| {
+                    serde::de::Error::custom(format!("invalid channel in module_map: {channel:?}"))
+                })
+        })
+        .collect()
 }
 
 fn default_source_pipeline_order() -> u32 {
     1 // Sources are upstream
 }
 
+fn default_max_message_bytes() -> usize {
+    crate::common::DEFAULT_MAX_MESSAGE_BYTES
+}
+
+fn default_dump_dir() -> String {
+    "./dump".to_string()
+}
+
+fn default_decode_channel_capacity() -> usize {
+    256
+}
+
+fn default_decode_workers() -> usize {
+    1
+}
+
+fn default_reconnect_backoff_ms() -> u64 {
+    DEFAULT_RECONNECT_BACKOFF_MS
+}
+
+fn default_max_reconnect_attempts() -> u32 {
+    DEFAULT_MAX_RECONNECT_ATTEMPTS
+}
+
+fn default_recent_events_capacity() -> usize {
+    1000
+}
+
 impl SourceNetworkConfig {
+    /// ZMQ options for the data PUB socket
+    pub fn socket_options(&self) -> SocketOptions {
+        SocketOptions {
+            send_hwm: self.send_hwm,
+            recv_hwm: None,
+            linger_ms: self.linger_ms,
+        }
+    }
+
     /// Check if this source is a real digitizer (not emulator)
     pub fn is_digitizer(&self) -> bool {
         self.source_type != SourceType::Emulator
@@ -283,12 +711,109 @@ pub struct MergerNetworkConfig {
     /// Pipeline order for Start/Stop sequencing (default: 2)
     #[serde(default = "default_merger_pipeline_order")]
     pub pipeline_order: u32,
+
+    /// Re-order events from all sources by timestamp (default: false)
+    #[serde(default)]
+    pub merge_by_timestamp: bool,
+
+    /// Late-arrival margin for timestamp merging in ns (default: 1 ms)
+    #[serde(default = "default_sort_margin_ns")]
+    pub sort_margin_ns: f64,
+
+    /// Upper bound on events held in the sort buffer (default: 1,000,000)
+    #[serde(default = "default_max_buffered_events")]
+    pub max_buffered_events: usize,
+
+    /// Release sorted events after this many ms in the buffer, bounding
+    /// latency at low rates (default: 0 = only when the horizon passes)
+    #[serde(default)]
+    pub max_residency_ms: u64,
+
+    /// Widen the sort margin to cover the observed skew between sources
+    /// (default: false; `sort_margin_ns` is the lower bound)
+    #[serde(default)]
+    pub adaptive_sort_margin: bool,
+
+    /// Duplicate event removal (optional, implies timestamp merging)
+    #[serde(default)]
+    pub dedup: Option<crate::merger::DedupConfig>,
+
+    /// Coincidence filter (optional, implies timestamp merging)
+    #[serde(default)]
+    pub coincidence: Option<crate::merger::CoincidenceConfig>,
+
+    /// Receiver → sender channel capacity in messages (default: 10000)
+    #[serde(default = "default_merger_channel_capacity")]
+    pub channel_capacity: usize,
+
+    /// Full-channel policy: "drop", "block" or { block_with_timeout = ms } (default: block)
+    #[serde(default)]
+    pub backpressure: crate::merger::BackpressurePolicy,
+
+    /// Only receive upstream messages whose topic starts with one of these (default: all)
+    #[serde(default)]
+    pub subscribe_topics: Vec<String>,
+
+    /// Flag a source as stale after this long without data or heartbeat (default: 5000, 0 = off)
+    #[serde(default = "default_heartbeat_timeout_ms")]
+    pub heartbeat_timeout_ms: u64,
+
+    /// Log counters and rates every this many seconds during a run (default: 10, 0 = off)
+    #[serde(default = "default_merger_stats_interval_secs")]
+    pub stats_interval_secs: u64,
+
+    /// Topic frame sent before every merged message (default: none)
+    #[serde(default)]
+    pub topic_prefix: Option<String>,
+
+    /// ZMQ send high-water mark of the PUB socket (default: ZMQ's 1000)
+    #[serde(default)]
+    pub send_hwm: Option<i32>,
+
+    /// ZMQ receive high-water mark of the SUB socket (default: ZMQ's 1000)
+    #[serde(default)]
+    pub recv_hwm: Option<i32>,
+
+    /// ZMQ linger of both data sockets in ms (default: ZMQ's -1 = until sent)
+    #[serde(default)]
+    pub linger_ms: Option<i32>,
+}
+
+impl MergerNetworkConfig {
+    /// ZMQ options for the SUB and PUB data sockets
+    pub fn socket_options(&self) -> SocketOptions {
+        SocketOptions {
+            send_hwm: self.send_hwm,
+            recv_hwm: self.recv_hwm,
+            linger_ms: self.linger_ms,
+        }
+    }
 }
 
 fn default_merger_pipeline_order() -> u32 {
     2 // Merger is in the middle
 }
 
+fn default_sort_margin_ns() -> f64 {
+    1_000_000.0
+}
+
+fn default_max_buffered_events() -> usize {
+    1_000_000
+}
+
+fn default_merger_channel_capacity() -> usize {
+    10_000
+}
+
+fn default_heartbeat_timeout_ms() -> u64 {
+    5000
+}
+
+fn default_merger_stats_interval_secs() -> u64 {
+    10
+}
+
 /// Recorder network configuration
 #[derive(Debug, Clone, Deserialize)]
 pub struct RecorderNetworkConfig {
@@ -311,9 +836,78 @@ pub struct RecorderNetworkConfig {
     #[serde(default = "default_max_file_duration_sec")]
     pub max_file_duration_sec: u64,
 
+    /// Maximum events per file (default: unlimited)
+    #[serde(default)]
+    pub max_file_events: Option<u64>,
+
     /// Pipeline order for Start/Stop sequencing (default: 3)
     #[serde(default = "default_sink_pipeline_order")]
     pub pipeline_order: u32,
+
+    /// Route events into two file streams by a PSD cut (optional)
+    #[serde(default)]
+    pub psd_routing: Option<crate::recorder::PsdRouting>,
+
+    /// Output file compression: "none", "gzip" or "zstd" (default: none)
+    #[serde(default)]
+    pub compression: crate::recorder::CompressionKind,
+
+    /// Write a CRC32 in front of every data block (default: false)
+    #[serde(default)]
+    pub write_checksums: bool,
+
+    /// Output format: "msgpack", "roottree" or "jsonlines" (default: msgpack)
+    #[serde(default)]
+    pub format: crate::recorder::RecorderFormat,
+
+    /// Write a 32-bit `energy` branch in ROOT files (default: false = the
+    /// 16-bit branch existing analysis macros read, clamped to 65535)
+    #[serde(default)]
+    pub wide_energy: bool,
+
+    /// Finish the run once every expected source sent EOS (default: false)
+    #[serde(default)]
+    pub finish_on_all_eos: bool,
+
+    /// Source IDs expected to send EOS (default: empty = first EOS finishes)
+    #[serde(default)]
+    pub expected_source_ids: Vec<u32>,
+
+    /// Only receive messages whose topic starts with one of these (default: all)
+    #[serde(default)]
+    pub subscribe_topics: Vec<String>,
+
+    /// Refuse to open a new file with less free disk space in MB (default: 0 = no check)
+    #[serde(default)]
+    pub min_free_space_mb: u64,
+
+    /// fsync the open file every N batches (default: 0 = only on close)
+    #[serde(default)]
+    pub fsync_interval_batches: u64,
+
+    /// Output file stem, e.g. "{exp}_{date}_run{run}_{seq}"; must contain
+    /// {run} and {seq} (default: "run{run}_{seq}_{exp}")
+    #[serde(default = "default_filename_template")]
+    pub filename_template: String,
+
+    /// ZMQ receive high-water mark of the SUB socket (default: ZMQ's 1000)
+    #[serde(default)]
+    pub recv_hwm: Option<i32>,
+
+    /// ZMQ linger of the SUB socket in ms (default: ZMQ's -1)
+    #[serde(default)]
+    pub linger_ms: Option<i32>,
+}
+
+impl RecorderNetworkConfig {
+    /// ZMQ options for the data SUB socket
+    pub fn socket_options(&self) -> SocketOptions {
+        SocketOptions {
+            send_hwm: None,
+            recv_hwm: self.recv_hwm,
+            linger_ms: self.linger_ms,
+        }
+    }
 }
 
 fn default_output_dir() -> String {
@@ -332,6 +926,10 @@ fn default_sink_pipeline_order() -> u32 {
     3 // Sinks (Recorder/Monitor) are downstream
 }
 
+fn default_filename_template() -> String {
+    crate::recorder::DEFAULT_FILENAME_TEMPLATE.to_string()
+}
+
 /// Monitor network configuration
 #[derive(Debug, Clone, Deserialize)]
 pub struct MonitorNetworkConfig {
@@ -349,12 +947,80 @@ pub struct MonitorNetworkConfig {
     /// Pipeline order for Start/Stop sequencing (default: 3)
     #[serde(default = "default_sink_pipeline_order")]
     pub pipeline_order: u32,
+
+    /// Maximum number of rate-history points kept (one per second, default: 3600)
+    #[serde(default = "default_rate_history_len")]
+    pub rate_history_len: usize,
+
+    /// Only receive messages whose topic starts with one of these (default: all)
+    #[serde(default)]
+    pub subscribe_topics: Vec<String>,
+
+    /// ADC resolution in bits; sets the default energy histogram range
+    /// (default: 16-bit, 65536 bins)
+    #[serde(default)]
+    pub adc_bits: Option<u8>,
+
+    /// Detector names as `{ "module:channel" = "name" }` (default: none)
+    #[serde(default, deserialize_with = "deserialize_channel_key_map")]
+    pub labels: HashMap<(u8, u8), String>,
+
+    /// Scaler thresholds as `{ "module:channel" = energy }`; events with a
+    /// higher energy count as above threshold (default: 0 for every channel)
+    #[serde(default, deserialize_with = "deserialize_channel_key_map")]
+    pub scaler_thresholds: HashMap<(u8, u8), u32>,
+
+    /// ZMQ receive high-water mark of the SUB socket (default: ZMQ's 1000)
+    #[serde(default)]
+    pub recv_hwm: Option<i32>,
+
+    /// ZMQ linger of the SUB socket in ms (default: ZMQ's -1)
+    #[serde(default)]
+    pub linger_ms: Option<i32>,
+}
+
+impl MonitorNetworkConfig {
+    /// ZMQ options for the data SUB socket
+    pub fn socket_options(&self) -> SocketOptions {
+        SocketOptions {
+            send_hwm: None,
+            recv_hwm: self.recv_hwm,
+            linger_ms: self.linger_ms,
+        }
+    }
+}
+
+/// Read a `{ "module:channel" = value }` table (TOML keys are strings)
+fn deserialize_channel_key_map<'de, D, T>(deserializer: D) -> Result<HashMap<(u8, u8), T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    HashMap::<String, T>::deserialize(deserializer)?
+        .into_iter()
+        .map(|(key, value)| {
+            key.split_once(':')
+                .and_then(|(module, channel)| {
+                    Some((module.trim().parse().ok()?, channel.trim().parse().ok()?))
+                })
+                .map(|key| (key, value))
+                .ok_or_else(|| {
+                    serde::de::Error::custom(format!(
+                        "invalid channel key {key:?} (expected \"module:channel\")"
+                    ))
+                })
+        })
+        .collect()
 }
 
 fn default_http_port() -> u16 {
     8081
 }
 
+fn default_rate_history_len() -> usize {
+    3600
+}
+
 // =============================================================================
 // Settings Configuration
 // =============================================================================
@@ -380,7 +1046,7 @@ pub struct SettingsConfig {
     #[serde(default)]
     pub file: FileSettings,
 
-    /// MongoDB connection settings (future)
+    /// MongoDB connection settings (used with `source = "mongodb"`)
     pub mongodb: Option<MongoDbSettings>,
 }
 
@@ -396,10 +1062,13 @@ impl Default for SettingsConfig {
 
 impl SettingsConfig {
     /// Get the effective settings based on the configured source
+    ///
+    /// MongoDB settings need a database round-trip; use `get_settings_async`
+    /// (enabled by the `mongodb` feature) for them.
     pub fn get_settings(&self) -> Result<Settings, ConfigError> {
         match self.source {
             SettingsSource::File => Ok(Settings::from(&self.file)),
-            SettingsSource::MongoDB => Err(ConfigError::MongoDbNotSupported),
+            SettingsSource::MongoDB => Err(ConfigError::MongoDbRequiresAsync),
         }
     }
 }
@@ -434,6 +1103,14 @@ pub struct FileSettings {
     /// Number of waveform samples
     #[serde(default = "default_waveform_samples")]
     pub waveform_samples: usize,
+
+    /// Emulator energy peaks (empty = one peak per channel)
+    #[serde(default)]
+    pub peaks: Vec<crate::data_source_emulator::PeakSpec>,
+
+    /// Emulator fraction of uniform background events
+    #[serde(default = "default_background_ratio")]
+    pub background_ratio: f64,
 }
 
 impl Default for FileSettings {
@@ -446,6 +1123,8 @@ impl Default for FileSettings {
             enable_waveform: false,
             waveform_probes: default_waveform_probes(),
             waveform_samples: default_waveform_samples(),
+            peaks: Vec::new(),
+            background_ratio: default_background_ratio(),
         }
     }
 }
@@ -468,8 +1147,11 @@ fn default_waveform_probes() -> u8 {
 fn default_waveform_samples() -> usize {
     512
 }
+fn default_background_ratio() -> f64 {
+    0.3
+}
 
-/// MongoDB connection settings (future)
+/// MongoDB connection settings
 #[derive(Debug, Clone, Deserialize)]
 pub struct MongoDbSettings {
     /// MongoDB URI
@@ -497,6 +1179,8 @@ pub struct Settings {
     pub enable_waveform: bool,
     pub waveform_probes: u8,
     pub waveform_samples: usize,
+    pub peaks: Vec<crate::data_source_emulator::PeakSpec>,
+    pub background_ratio: f64,
 }
 
 impl From<&FileSettings> for Settings {
@@ -509,6 +1193,8 @@ impl From<&FileSettings> for Settings {
             enable_waveform: file.enable_waveform,
             waveform_probes: file.waveform_probes,
             waveform_samples: file.waveform_samples,
+            peaks: file.peaks.clone(),
+            background_ratio: file.background_ratio,
         }
     }
 }
@@ -609,7 +1295,7 @@ cluster_name = "test"
     }
 
     #[test]
-    fn mongodb_not_supported() {
+    fn mongodb_requires_async() {
         let toml = r#"
 [network]
 cluster_name = "test"
@@ -622,7 +1308,10 @@ uri = "mongodb://localhost:27017"
 database = "delila"
 "#;
         let config = Config::from_toml(toml).unwrap();
-        assert!(config.settings.get_settings().is_err());
+        assert!(matches!(
+            config.settings.get_settings(),
+            Err(ConfigError::MongoDbRequiresAsync)
+        ));
     }
 
     #[test]
@@ -652,6 +1341,28 @@ time_step_ns = 4.0
         assert_eq!(source.module_id, Some(1));
         assert_eq!(source.time_step_ns, Some(4.0));
         assert_eq!(source.command_address(), "tcp://*:5560".to_string());
+        assert!(source.module_map.is_empty());
+    }
+
+    #[test]
+    fn parse_module_map() {
+        let toml = r#"
+[network]
+cluster_name = "test"
+
+[[network.sources]]
+id = 0
+type = "psd2"
+bind = "tcp://*:5555"
+digitizer_url = "dig2://172.18.4.56"
+module_map = { 0 = 1, 16 = 2 }
+"#;
+        let config = Config::from_toml(toml).unwrap();
+        let source = &config.network.sources[0];
+        assert_eq!(source.module_map, HashMap::from([(0, 1), (16, 2)]));
+
+        let bad = toml.replace("16 = 2", "x = 2");
+        assert!(Config::from_toml(&bad).is_err());
     }
 
     #[test]
@@ -851,6 +1562,172 @@ is_master = true
         assert!(!source.is_master_digitizer());
     }
 
+    const TOPOLOGY: &str = r#"
+[network]
+[[network.sources]]
+id = 0
+bind = "tcp://*:5555"
+
+[[network.sources]]
+id = 1
+bind = "tcp://*:5556"
+
+[network.merger]
+subscribe = ["tcp://localhost:5555", "tcp://localhost:5556"]
+publish = "tcp://*:5557"
+
+[network.recorder]
+subscribe = "tcp://localhost:5557"
+"#;
+
+    fn problems(toml: &str) -> Vec<String> {
+        match Config::from_toml(toml).unwrap().validate() {
+            Ok(()) => Vec::new(),
+            Err(ConfigError::Invalid(problems)) => problems,
+            Err(e) => panic!("unexpected error: {}", e),
+        }
+    }
+
+    #[test]
+    fn validate_accepts_consistent_topology() {
+        assert!(problems(TOPOLOGY).is_empty());
+    }
+
+    #[test]
+    fn validate_rejects_duplicate_source_ids() {
+        let toml = TOPOLOGY.replace("id = 1", "id = 0");
+        assert_eq!(problems(&toml), vec!["duplicate source id 0".to_string()]);
+    }
+
+    #[test]
+    fn validate_rejects_dangling_subscribe() {
+        let toml = TOPOLOGY
+            .replace("tcp://localhost:5556", "tcp://localhost:5599")
+            .replace(
+                "subscribe = \"tcp://localhost:5557\"",
+                "subscribe = \"tcp://daq02:5557\"",
+            );
+        let problems = problems(&toml);
+        // The recorder's remote host is not checked, only the port
+        assert_eq!(problems.len(), 1, "{:?}", problems);
+        assert!(problems[0].contains("merger subscribes to tcp://localhost:5599"));
+    }
+
+    #[test]
+    fn validate_rejects_master_and_order_problems() {
+        let toml = r#"
+[network]
+[[network.sources]]
+id = 0
+type = "psd2"
+bind = "tcp://*:5555"
+is_master = true
+
+[[network.sources]]
+id = 1
+type = "psd2"
+bind = "tcp://*:5556"
+is_master = true
+
+[network.monitor]
+subscribe = "tcp://localhost:5556"
+pipeline_order = 1
+"#;
+        let problems = problems(toml);
+        assert_eq!(problems.len(), 2, "{:?}", problems);
+        assert!(problems[0].contains("only one master digitizer"));
+        assert!(problems[1].contains("monitor (pipeline_order 1) must come after source 1"));
+    }
+
+    #[test]
+    fn socket_options_parse_with_zmq_defaults() {
+        let toml = TOPOLOGY.replace(
+            "publish = \"tcp://*:5557\"",
+            "publish = \"tcp://*:5557\"\nsend_hwm = 100\nrecv_hwm = 0\nlinger_ms = 500",
+        );
+        let config = Config::from_toml(&toml).unwrap();
+        let network = &config.network;
+        assert_eq!(
+            network.merger.as_ref().unwrap().socket_options(),
+            SocketOptions {
+                send_hwm: Some(100),
+                recv_hwm: Some(0),
+                linger_ms: Some(500),
+            }
+        );
+        assert_eq!(
+            network.recorder.as_ref().unwrap().socket_options(),
+            SocketOptions::default()
+        );
+        assert_eq!(
+            network.sources[0].socket_options(),
+            SocketOptions::default()
+        );
+    }
+
+    #[test]
+    fn monitor_labels_parse_module_channel_keys() {
+        let monitor: MonitorNetworkConfig = toml::from_str(
+            r#"
+            subscribe = "tcp://localhost:5557"
+            labels = { "0:5" = "HPGe-1", "1:0" = "LaBr-A" }
+            "#,
+        )
+        .unwrap();
+        assert_eq!(monitor.labels.len(), 2);
+        assert_eq!(monitor.labels[&(0, 5)], "HPGe-1");
+        assert_eq!(monitor.labels[&(1, 0)], "LaBr-A");
+        assert!(monitor.scaler_thresholds.is_empty());
+
+        let bad = toml::from_str::<MonitorNetworkConfig>(
+            r#"
+            subscribe = "tcp://localhost:5557"
+            labels = { "5" = "HPGe-1" }
+            "#,
+        );
+        assert!(bad.unwrap_err().to_string().contains("module:channel"));
+    }
+
+    #[test]
+    fn event_filter_parses_inline_table() {
+        let toml = TOPOLOGY.replace(
+            "bind = \"tcp://*:5555\"",
+            "bind = \"tcp://*:5555\"\nevent_filter = { min_energy = 100, channels = [0, 1, 2] }",
+        );
+        let config = Config::from_toml(&toml).unwrap();
+        assert_eq!(
+            config.network.sources[0].event_filter,
+            EventFilter {
+                min_energy: Some(100),
+                max_energy: None,
+                channels: Some(vec![0, 1, 2]),
+                drop_pileup: false,
+            }
+        );
+
+        let config = Config::from_toml(TOPOLOGY).unwrap();
+        assert_eq!(
+            config.network.sources[0].event_filter,
+            EventFilter::default()
+        );
+    }
+
+    #[test]
+    fn validate_accepts_ipc_and_rejects_inproc() {
+        let toml = TOPOLOGY
+            .replace("tcp://*:5555", "ipc:///tmp/delila-source0")
+            .replace("tcp://localhost:5555", "ipc:///tmp/delila-source0");
+        assert!(problems(&toml).is_empty());
+
+        let toml = TOPOLOGY
+            .replace("tcp://*:5557", "inproc://merged")
+            .replace("tcp://localhost:5557", "inproc://merged");
+        let problems = problems(&toml);
+        assert_eq!(problems.len(), 2, "{:?}", problems);
+        assert!(problems[0].contains("merger uses inproc://merged"));
+        assert!(problems[1].contains("recorder uses inproc://merged"));
+    }
+
     #[test]
     fn load_digitizer_config_no_file() {
         let toml = r#"
@@ -0,0 +1,90 @@
+//! Operational settings loaded from MongoDB (`mongodb` feature)
+//!
+//! The settings collection holds one document per revision, with the same
+//! field names as `[settings.file]`. The most recently inserted document
+//! (highest `_id`) is used; fields it leaves out take the file defaults.
+
+use mongodb::{
+    bson::{doc, Document},
+    options::FindOneOptions,
+    Client,
+};
+
+use super::{ConfigError, FileSettings, Settings, SettingsConfig, SettingsSource};
+
+impl SettingsConfig {
+    /// Get the effective settings, querying MongoDB if that is the configured source
+    pub async fn get_settings_async(&self) -> Result<Settings, ConfigError> {
+        match self.source {
+            SettingsSource::File => Ok(Settings::from(&self.file)),
+            SettingsSource::MongoDB => {
+                let mongo = self
+                    .mongodb
+                    .as_ref()
+                    .ok_or_else(|| ConfigError::MissingField("settings.mongodb".to_string()))?;
+
+                let client = Client::with_uri_str(&mongo.uri).await?;
+                let collection = client
+                    .database(&mongo.database)
+                    .collection::<Document>(&mongo.collection);
+                let options = FindOneOptions::builder().sort(doc! { "_id": -1 }).build();
+
+                let document = collection
+                    .find_one(doc! {})
+                    .with_options(options)
+                    .await?
+                    .ok_or_else(|| {
+                        ConfigError::MissingField(format!(
+                            "settings document in {}.{}",
+                            mongo.database, mongo.collection
+                        ))
+                    })?;
+                settings_from_document(document)
+            }
+        }
+    }
+}
+
+/// Map a settings document onto [`Settings`]
+fn settings_from_document(document: Document) -> Result<Settings, ConfigError> {
+    let file: FileSettings = mongodb::bson::from_document(document)?;
+    Ok(Settings::from(&file))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mongodb::bson::oid::ObjectId;
+
+    #[test]
+    fn test_settings_from_document() {
+        let document = doc! {
+            "_id": ObjectId::new(),
+            "events_per_batch": 500_i32,
+            "batch_interval_ms": 20_i64,
+            "num_modules": 4_i32,
+            "enable_waveform": true,
+            "waveform_samples": 1024_i64,
+            "background_ratio": 0.1,
+            "comment": "beam test",
+        };
+
+        let settings = settings_from_document(document).unwrap();
+        assert_eq!(settings.events_per_batch, 500);
+        assert_eq!(settings.batch_interval_ms, 20);
+        assert_eq!(settings.num_modules, 4);
+        assert!(settings.enable_waveform);
+        assert_eq!(settings.waveform_samples, 1024);
+        assert_eq!(settings.background_ratio, 0.1);
+        // Missing fields fall back to the file defaults
+        assert_eq!(settings.channels_per_module, 16);
+        assert_eq!(settings.waveform_probes, 3);
+        assert!(settings.peaks.is_empty());
+
+        let bad = doc! { "events_per_batch": "many" };
+        assert!(matches!(
+            settings_from_document(bad),
+            Err(ConfigError::SettingsDocument(_))
+        ));
+    }
+}
@@ -0,0 +1,84 @@
+//! CSV export of received events
+//!
+//! With `csv_output` set, the DataSink appends one row per processed event
+//! (`module,channel,energy,energy_short,timestamp_ns,flags`) for quick
+//! analysis without running the Recorder. The header is written only when
+//! the file is new or empty, so successive runs append to the same table.
+
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use crate::common::EventData;
+
+/// Column header of the CSV file
+pub const CSV_HEADER: &str = "module,channel,energy,energy_short,timestamp_ns,flags";
+
+/// Appending CSV writer with periodic flushing
+#[derive(Debug)]
+pub struct CsvExport {
+    writer: BufWriter<File>,
+    path: PathBuf,
+    rows: u64,
+    flush_interval: Duration,
+    last_flush: Instant,
+}
+
+impl CsvExport {
+    /// Open `path` for appending, writing the header if the file is empty
+    pub fn open(path: &Path, flush_interval: Duration) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        let is_empty = file.metadata()?.len() == 0;
+        let mut writer = BufWriter::new(file);
+        if is_empty {
+            writeln!(writer, "{}", CSV_HEADER)?;
+        }
+        Ok(Self {
+            writer,
+            path: path.to_path_buf(),
+            rows: 0,
+            flush_interval,
+            last_flush: Instant::now(),
+        })
+    }
+
+    /// Append one event row
+    pub fn write_event(&mut self, event: &EventData) -> io::Result<()> {
+        self.rows += 1;
+        writeln!(
+            self.writer,
+            "{},{},{},{},{},{}",
+            event.module,
+            event.channel,
+            event.energy,
+            event.energy_short,
+            event.timestamp_ns,
+            event.flags
+        )
+    }
+
+    /// Flush if the flush interval has passed since the last flush
+    pub fn flush_if_due(&mut self) -> io::Result<()> {
+        if self.last_flush.elapsed() >= self.flush_interval {
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Flush buffered rows to the file
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.last_flush = Instant::now();
+        self.writer.flush()
+    }
+
+    /// Path of the CSV file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Number of rows written since opening
+    pub fn rows(&self) -> u64 {
+        self.rows
+    }
+}
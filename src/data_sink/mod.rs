@@ -6,24 +6,35 @@
 //! - Command task: REP socket for control commands
 //!
 //! This module provides a data consumer that subscribes to event data
-//! and outputs statistics to the console.
+//! and outputs statistics to the console, optionally exporting events to CSV.
 
-use std::collections::HashMap;
+mod csv;
+
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::path::PathBuf;
 use std::sync::atomic::{AtomicU64, Ordering};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
 use futures::StreamExt;
 use thiserror::Error;
-use tmq::{subscribe, Context};
+use tmq::{subscribe, AsZmqSocket, Context};
 use tokio::sync::{mpsc, watch};
 use tracing::{debug, info, warn};
 
 use crate::common::{
-    handle_command, run_command_task, CommandHandlerExt, ComponentSharedState, ComponentState,
-    EventDataBatch, Message,
+    finish_run, handle_command, run_command_task, topic, CommandHandlerExt, ComponentSharedState,
+    ComponentState, EosTracker, EventDataBatch, Message, SocketOptions,
 };
 
+pub use csv::{CsvExport, CSV_HEADER};
+
+/// How often buffered CSV rows are flushed to disk
+const CSV_FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Missing sequence ranges remembered per source (oldest are evicted)
+pub const MAX_MISSING_RANGES: usize = 32;
+
 /// DataSink configuration
 #[derive(Debug, Clone)]
 pub struct DataSinkConfig {
@@ -35,6 +46,18 @@ pub struct DataSinkConfig {
     pub stats_interval_secs: u64,
     /// Internal channel capacity
     pub channel_capacity: usize,
+    /// Finish the run (back to Configured) once every expected source sent EOS
+    pub finish_on_all_eos: bool,
+    /// Source IDs expected to send EOS (empty = finish on the first EOS)
+    pub expected_source_ids: Vec<u32>,
+    /// Topic prefixes to receive (empty = everything)
+    pub subscribe_topics: Vec<String>,
+    /// Fraction of events to process (1.0 = all); rates still count every event
+    pub sample_ratio: f64,
+    /// Append processed events to this CSV file (None = no export)
+    pub csv_output: Option<PathBuf>,
+    /// ZMQ receive HWM / linger of the SUB socket (default: ZMQ's)
+    pub socket_options: SocketOptions,
 }
 
 impl Default for DataSinkConfig {
@@ -44,6 +67,12 @@ impl Default for DataSinkConfig {
             command_address: "tcp://*:5580".to_string(),
             stats_interval_secs: 1,
             channel_capacity: 1000,
+            finish_on_all_eos: false,
+            expected_source_ids: Vec::new(),
+            subscribe_topics: Vec::new(),
+            sample_ratio: 1.0,
+            csv_output: None,
+            socket_options: SocketOptions::default(),
         }
     }
 }
@@ -56,31 +85,43 @@ pub enum DataSinkError {
 
     #[error("Deserialization error: {0}")]
     Deserialization(#[from] rmp_serde::decode::Error),
+
+    #[error("CSV export error: {0}")]
+    Io(#[from] std::io::Error),
 }
 
 /// Per-source statistics with sequence tracking
 #[derive(Debug, Default, Clone)]
 pub struct SourceStats {
+    /// Sequence of the last batch; None until the source sends data
     pub last_sequence: Option<u64>,
+    /// Epoch of the last batch; a new epoch means the source restarted
+    pub last_epoch: u32,
     pub total_batches: u64,
     pub total_events: u64,
     pub gaps_detected: u64,
     pub total_gap_size: u64,
+    /// Most recent missing ranges `(from_seq, to_seq)`, inclusive, oldest first
+    pub missing_ranges: VecDeque<(u64, u64)>,
     pub restart_count: u32,
 }
 
 impl SourceStats {
-    fn update(&mut self, batch: &EventDataBatch) {
+    /// Update from one batch; returns true if it revealed a gap
+    fn update(&mut self, batch: &EventDataBatch) -> bool {
+        let mut gap_found = false;
         let seq = batch.sequence_number;
 
         if let Some(last) = self.last_sequence {
-            // Detect restart: sequence dropped significantly
-            if seq < last.saturating_sub(100) {
+            // Detect restart: a new epoch, or (for sources that send no
+            // epoch) a sequence number that dropped significantly
+            if batch.epoch != self.last_epoch || seq < last.saturating_sub(100) {
                 self.restart_count += 1;
                 info!(
                     source_id = batch.source_id,
                     last_seq = last,
                     new_seq = seq,
+                    epoch = batch.epoch,
                     restarts = self.restart_count,
                     "Source restart detected"
                 );
@@ -91,6 +132,11 @@ impl SourceStats {
                     let gap = seq - expected;
                     self.gaps_detected += 1;
                     self.total_gap_size += gap;
+                    if self.missing_ranges.len() == MAX_MISSING_RANGES {
+                        self.missing_ranges.pop_front();
+                    }
+                    self.missing_ranges.push_back((expected, seq - 1));
+                    gap_found = true;
                     warn!(
                         source_id = batch.source_id,
                         expected = expected,
@@ -104,8 +150,132 @@ impl SourceStats {
         }
 
         self.last_sequence = Some(seq);
+        self.last_epoch = batch.epoch;
         self.total_batches += 1;
         self.total_events += batch.len() as u64;
+        gap_found
+    }
+
+    /// Totals reported per source
+    pub fn totals(&self) -> SourceTotals {
+        SourceTotals {
+            total_events: self.total_events,
+            total_batches: self.total_batches,
+            gaps: self.gaps_detected,
+        }
+    }
+}
+
+/// Per-source totals for GetStatus and the final statistics
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct SourceTotals {
+    pub total_events: u64,
+    pub total_batches: u64,
+    pub gaps: u64,
+}
+
+/// Deterministic event subsampler
+///
+/// Every event adds `ratio` (in parts per million) to a credit, and an event
+/// is sampled each time the credit reaches one. A ratio of 0.1 selects every
+/// 10th event; non-integer strides are spread evenly.
+#[derive(Debug, Clone)]
+struct EventSampler {
+    step_ppm: u64,
+    credit_ppm: u64,
+}
+
+impl EventSampler {
+    const ONE_PPM: u64 = 1_000_000;
+
+    fn new(ratio: f64) -> Self {
+        let ratio = if ratio.is_nan() {
+            1.0
+        } else {
+            ratio.clamp(0.0, 1.0)
+        };
+        Self {
+            step_ppm: (ratio * Self::ONE_PPM as f64).round() as u64,
+            credit_ppm: 0,
+        }
+    }
+
+    /// Whether the next event is sampled
+    #[inline]
+    fn sample(&mut self) -> bool {
+        self.credit_ppm += self.step_ppm;
+        if self.credit_ppm >= Self::ONE_PPM {
+            self.credit_ppm -= Self::ONE_PPM;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// Processing applied to the sampled events of each batch
+#[derive(Debug)]
+struct EventProcessor {
+    sampler: EventSampler,
+    csv: Option<CsvExport>,
+}
+
+impl EventProcessor {
+    fn new(sample_ratio: f64, csv: Option<CsvExport>) -> Self {
+        Self {
+            sampler: EventSampler::new(sample_ratio),
+            csv,
+        }
+    }
+
+    /// Process the sampled events of `batch`; returns how many were sampled
+    fn process(&mut self, batch: &EventDataBatch) -> u64 {
+        let mut sampled = 0;
+        for event in batch.events.iter().filter(|_| self.sampler.sample()) {
+            sampled += 1;
+            if let Some(export) = self.csv.as_mut() {
+                let result = export.write_event(event);
+                Self::check_csv(&mut self.csv, result);
+            }
+        }
+        if let Some(export) = self.csv.as_mut() {
+            let result = export.flush_if_due();
+            Self::check_csv(&mut self.csv, result);
+        }
+        sampled
+    }
+
+    /// Flush buffered CSV rows
+    fn flush(&mut self) {
+        if let Some(export) = self.csv.as_mut() {
+            let result = export.flush();
+            Self::check_csv(&mut self.csv, result);
+        }
+    }
+
+    /// Flush and close the CSV export
+    fn finish(mut self) {
+        self.flush();
+        if let Some(export) = self.csv {
+            info!(
+                path = %export.path().display(),
+                rows = export.rows(),
+                "CSV export closed"
+            );
+        }
+    }
+
+    /// Disable the CSV export after an I/O error (the sink keeps running)
+    fn check_csv(csv: &mut Option<CsvExport>, result: std::io::Result<()>) {
+        if let Err(e) = result {
+            if let Some(export) = csv.take() {
+                warn!(
+                    path = %export.path().display(),
+                    error = %e,
+                    "CSV export failed, disabling it"
+                );
+            }
+        }
     }
 }
 
@@ -114,7 +284,11 @@ impl SourceStats {
 pub struct DataSinkStats {
     pub sources: HashMap<u32, SourceStats>,
     pub total_batches: u64,
+    /// Events received (all of them; rates are based on this)
     pub total_events: u64,
+    /// Events selected by `sample_ratio` for processing
+    /// (equal to `total_events` when not sampling)
+    pub sampled_events: u64,
     pub eos_received: u64,
     pub start_time_secs: Option<f64>,
     events_since_last_report: u64,
@@ -122,14 +296,21 @@ pub struct DataSinkStats {
 }
 
 impl DataSinkStats {
-    fn update(&mut self, batch: &EventDataBatch) {
-        self.sources
+    /// Update from one batch; returns true if it revealed a gap
+    fn update(&mut self, batch: &EventDataBatch) -> bool {
+        let gap_found = self
+            .sources
             .entry(batch.source_id)
             .or_default()
             .update(batch);
         self.total_batches += 1;
         self.total_events += batch.len() as u64;
         self.events_since_last_report += batch.len() as u64;
+        gap_found
+    }
+
+    fn record_sampled(&mut self, events: u64) {
+        self.sampled_events += events;
     }
 
     fn record_eos(&mut self) {
@@ -146,6 +327,23 @@ impl DataSinkStats {
         self.sources.values().map(|s| s.total_gap_size).sum()
     }
 
+    /// Recent missing ranges of every source that has any, by source ID
+    pub fn missing_ranges(&self) -> BTreeMap<u32, Vec<(u64, u64)>> {
+        self.sources
+            .iter()
+            .filter(|(_, s)| !s.missing_ranges.is_empty())
+            .map(|(&id, s)| (id, s.missing_ranges.iter().copied().collect()))
+            .collect()
+    }
+
+    /// Totals of every source, by source ID
+    pub fn source_totals(&self) -> BTreeMap<u32, SourceTotals> {
+        self.sources
+            .iter()
+            .map(|(&id, s)| (id, s.totals()))
+            .collect()
+    }
+
     fn report(&mut self, total_elapsed: f64, interval_elapsed: f64) -> String {
         let events_per_sec = if interval_elapsed > 0.0 {
             self.events_since_last_report as f64 / interval_elapsed
@@ -158,7 +356,7 @@ impl DataSinkStats {
             0.0
         };
 
-        let report = format!(
+        let mut report = format!(
             "Events: {} total ({:.0}/s avg, {:.0}/s current) | Batches: {} | Gaps: {} | Missing: {}",
             self.total_events,
             total_rate,
@@ -167,6 +365,9 @@ impl DataSinkStats {
             self.total_gaps(),
             self.total_missing()
         );
+        if self.sampled_events < self.total_events {
+            report.push_str(&format!(" | Sampled: {}", self.sampled_events));
+        }
 
         self.events_since_last_report = 0;
         self.last_report_elapsed_secs = total_elapsed;
@@ -181,6 +382,12 @@ struct AtomicStats {
     processed_batches: AtomicU64,
     dropped_batches: AtomicU64,
     eos_received: AtomicU64,
+    /// Copy of the processor's missing ranges for GetStatus
+    /// (locked only when a gap is found)
+    missing_ranges: Mutex<BTreeMap<u32, Vec<(u64, u64)>>>,
+    /// Copy of the processor's per-source totals for GetStatus
+    /// (locked only on a gap, a stats report, EOS and at the end)
+    source_totals: Mutex<BTreeMap<u32, SourceTotals>>,
 }
 
 impl AtomicStats {
@@ -190,6 +397,8 @@ impl AtomicStats {
             processed_batches: AtomicU64::new(0),
             dropped_batches: AtomicU64::new(0),
             eos_received: AtomicU64::new(0),
+            missing_ranges: Mutex::new(BTreeMap::new()),
+            source_totals: Mutex::new(BTreeMap::new()),
         }
     }
 
@@ -214,6 +423,14 @@ impl AtomicStats {
         self.eos_received.fetch_add(1, Ordering::Relaxed);
     }
 
+    fn set_missing_ranges(&self, ranges: BTreeMap<u32, Vec<(u64, u64)>>) {
+        *self.missing_ranges.lock().unwrap() = ranges;
+    }
+
+    fn set_source_totals(&self, totals: BTreeMap<u32, SourceTotals>) {
+        *self.source_totals.lock().unwrap() = totals;
+    }
+
     fn snapshot(&self) -> (u64, u64, u64, u64) {
         (
             self.received_batches.load(Ordering::Relaxed),
@@ -242,10 +459,19 @@ impl CommandHandlerExt for DataSinkCommandExt {
 
     fn status_details(&self) -> Option<String> {
         let (recv, proc, drop, eos) = self.atomic_stats.snapshot();
-        Some(format!(
+        let mut details = format!(
             "Received: {}, Processed: {}, Dropped: {}, EOS: {}",
             recv, proc, drop, eos
-        ))
+        );
+        let missing = self.atomic_stats.missing_ranges.lock().unwrap();
+        if !missing.is_empty() {
+            details.push_str(&format!(", Missing: {}", format_missing_ranges(&missing)));
+        }
+        let sources = self.atomic_stats.source_totals.lock().unwrap();
+        if !sources.is_empty() {
+            details.push_str(&format!(", Sources: {}", format_source_totals(&sources)));
+        }
+        Some(details)
     }
 }
 
@@ -295,11 +521,23 @@ impl DataSink {
         // Create channel for receiver → processor (unbounded - memory growth indicates bottleneck)
         let (proc_tx, proc_rx) = mpsc::unbounded_channel::<ProcessorMessage>();
 
+        // Open the CSV file up front so a bad path fails at startup
+        let csv = match self.config.csv_output {
+            Some(ref path) => {
+                let export = CsvExport::open(path, CSV_FLUSH_INTERVAL)?;
+                info!(path = %path.display(), "Exporting events to CSV");
+                Some(export)
+            }
+            None => None,
+        };
+
         // Create SUB socket
         let context = Context::new();
-        let socket = subscribe(&context)
-            .connect(&self.config.address)?
-            .subscribe(b"")?;
+        let socket = topic::subscribe_topics(
+            subscribe(&context).connect(&self.config.address)?,
+            &self.config.subscribe_topics,
+        )?;
+        self.config.socket_options.apply(socket.get_socket())?;
 
         info!(address = %self.config.address, "DataSink connected to upstream");
         info!(
@@ -349,8 +587,24 @@ impl DataSink {
         // Spawn processor task
         let atomic_stats_for_proc = self.atomic_stats.clone();
         let stats_interval_secs = self.config.stats_interval_secs;
+        let events = EventProcessor::new(self.config.sample_ratio, csv);
+        let eos_tracker = self
+            .config
+            .finish_on_all_eos
+            .then(|| EosTracker::new(self.config.expected_source_ids.iter().copied()));
+        let shared_state_for_proc = self.shared_state.clone();
+        let state_tx_for_proc = self.state_tx.clone();
         let proc_handle = tokio::spawn(async move {
-            Self::processor_task(proc_rx, atomic_stats_for_proc, stats_interval_secs).await
+            Self::processor_task(
+                proc_rx,
+                atomic_stats_for_proc,
+                stats_interval_secs,
+                events,
+                eos_tracker,
+                shared_state_for_proc,
+                state_tx_for_proc,
+            )
+            .await
         });
 
         // Wait for shutdown signal
@@ -387,7 +641,8 @@ impl DataSink {
         mut state_rx: watch::Receiver<ComponentState>,
     ) {
         loop {
-            let is_running = *state_rx.borrow() == ComponentState::Running;
+            // Paused keeps in-flight data of the current run
+            let is_running = state_rx.borrow().in_run();
 
             tokio::select! {
                 biased;
@@ -413,7 +668,7 @@ impl DataSink {
                                 continue;
                             }
 
-                            if let Some(data) = multipart.into_iter().next() {
+                            if let Some(data) = topic::payload(multipart) {
                                 match Message::from_msgpack(&data) {
                                     Ok(Message::Data(batch)) => {
                                         atomic_stats.record_received();
@@ -436,6 +691,8 @@ impl DataSink {
                                         let _ = tx.send(ProcessorMessage::Eos { source_id });
                                     }
                                     Ok(Message::Heartbeat(hb)) => {
+                                        // Not forwarded: the processor's sequence
+                                        // tracking starts at the first data batch
                                         debug!(source_id = hb.source_id, counter = hb.counter, "Received heartbeat");
                                     }
                                     Err(e) => {
@@ -458,24 +715,62 @@ impl DataSink {
     }
 
     /// Processor task: channel → stats + console output
+    ///
+    /// Only the events selected by `sample_ratio` are processed (counted as
+    /// sampled and written to the CSV export); every event still counts
+    /// towards the received totals and rates. A CSV write error disables the
+    /// export but keeps the sink running.
+    ///
+    /// With an [`EosTracker`], the run is finished (state back to Configured)
+    /// once every expected source has sent EOS.
     async fn processor_task(
         mut rx: mpsc::UnboundedReceiver<ProcessorMessage>,
         atomic_stats: Arc<AtomicStats>,
         stats_interval_secs: u64,
+        mut events: EventProcessor,
+        mut eos_tracker: Option<EosTracker>,
+        shared_state: Arc<tokio::sync::Mutex<ComponentSharedState>>,
+        state_tx: watch::Sender<ComponentState>,
     ) {
         let mut stats = DataSinkStats::default();
         let start_time = Instant::now();
         let mut last_report_time = Instant::now();
         let stats_interval = Duration::from_secs(stats_interval_secs);
+        let mut state_rx = state_tx.subscribe();
+        let mut last_state = *state_rx.borrow();
+
+        loop {
+            let msg = tokio::select! {
+                msg = rx.recv() => match msg {
+                    Some(msg) => msg,
+                    None => break,
+                },
+                Ok(()) = state_rx.changed() => {
+                    // EOS from a previous (possibly aborted) run must not count;
+                    // a Resume continues the current run
+                    let current = *state_rx.borrow();
+                    if current == ComponentState::Running && last_state != ComponentState::Paused {
+                        if let Some(ref mut tracker) = eos_tracker {
+                            tracker.reset();
+                        }
+                    }
+                    last_state = current;
+                    continue;
+                }
+            };
 
-        while let Some(msg) = rx.recv().await {
             match msg {
                 ProcessorMessage::Data(batch) => {
-                    stats.update(&batch);
+                    if stats.update(&batch) {
+                        atomic_stats.set_missing_ranges(stats.missing_ranges());
+                        atomic_stats.set_source_totals(stats.source_totals());
+                    }
+                    stats.record_sampled(events.process(&batch));
                     atomic_stats.record_processed();
 
                     // Check if should report
                     if last_report_time.elapsed() >= stats_interval {
+                        atomic_stats.set_source_totals(stats.source_totals());
                         let total_elapsed = start_time.elapsed().as_secs_f64();
                         let interval_elapsed = last_report_time.elapsed().as_secs_f64();
                         let report = stats.report(total_elapsed, interval_elapsed);
@@ -485,11 +780,31 @@ impl DataSink {
                 }
                 ProcessorMessage::Eos { source_id } => {
                     stats.record_eos();
+                    atomic_stats.set_source_totals(stats.source_totals());
                     info!(source_id = source_id, "Processed EOS");
+
+                    let Some(ref mut tracker) = eos_tracker else {
+                        continue;
+                    };
+                    if !tracker.record(source_id) {
+                        debug!(pending = ?tracker.pending(), "Waiting for EOS");
+                        continue;
+                    }
+                    tracker.reset();
+
+                    let total_elapsed = start_time.elapsed().as_secs_f64();
+                    let interval_elapsed = last_report_time.elapsed().as_secs_f64();
+                    println!("{}", stats.report(total_elapsed, interval_elapsed));
+                    last_report_time = Instant::now();
+                    events.flush();
+                    finish_run(&shared_state, &state_tx, "DataSink").await;
                 }
             }
         }
 
+        events.finish();
+        atomic_stats.set_source_totals(stats.source_totals());
+
         // Final stats report
         let total_elapsed = start_time.elapsed().as_secs_f64();
         let event_rate = if total_elapsed > 0.0 {
@@ -507,6 +822,9 @@ impl DataSink {
         println!("========== Final Statistics ==========");
         println!("Duration:     {:.2} s", total_elapsed);
         println!("Total Events: {}", stats.total_events);
+        if stats.sampled_events < stats.total_events {
+            println!("Sampled:      {}", stats.sampled_events);
+        }
         println!("Total Batches: {}", stats.total_batches);
         println!(
             "Event Rate:   {:.0} events/s ({:.2} MHz)",
@@ -520,6 +838,16 @@ impl DataSink {
             stats.total_missing()
         );
         println!("Sources:      {}", stats.sources.len());
+        for (source_id, totals) in stats.source_totals() {
+            println!(
+                "  src {:<8}  {} events, {} batches, {} gaps",
+                source_id, totals.total_events, totals.total_batches, totals.gaps
+            );
+        }
+        let missing = stats.missing_ranges();
+        if !missing.is_empty() {
+            println!("Missing:      {}", format_missing_ranges(&missing));
+        }
         println!("=======================================");
 
         info!("Processor task completed");
@@ -531,6 +859,35 @@ impl DataSink {
     }
 }
 
+/// "src 0: 1..4, 6..9; src 2: 17..17" (inclusive ranges)
+fn format_missing_ranges(missing: &BTreeMap<u32, Vec<(u64, u64)>>) -> String {
+    missing
+        .iter()
+        .map(|(source_id, ranges)| {
+            let ranges: Vec<String> = ranges
+                .iter()
+                .map(|(from, to)| format!("{}..{}", from, to))
+                .collect();
+            format!("src {}: {}", source_id, ranges.join(", "))
+        })
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+/// "src 0: 1000 events/10 batches/0 gaps; src 1: ..."
+fn format_source_totals(sources: &BTreeMap<u32, SourceTotals>) -> String {
+    sources
+        .iter()
+        .map(|(source_id, t)| {
+            format!(
+                "src {}: {} events/{} batches/{} gaps",
+                source_id, t.total_events, t.total_batches, t.gaps
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -574,6 +931,96 @@ mod tests {
         assert_eq!(stats.total_missing(), 4);
     }
 
+    #[tokio::test]
+    async fn heartbeats_before_first_batch_are_not_a_gap() {
+        let atomic = Arc::new(AtomicStats::new());
+        let (state_tx, _state_rx) = watch::channel(ComponentState::Running);
+        let (tx, rx) = mpsc::unbounded_channel();
+        // Batch 42 is the first data after a heartbeat-only period
+        tx.send(ProcessorMessage::Data(EventDataBatch::new(3, 42)))
+            .unwrap();
+        tx.send(ProcessorMessage::Data(EventDataBatch::new(3, 43)))
+            .unwrap();
+        drop(tx);
+
+        DataSink::processor_task(
+            rx,
+            atomic.clone(),
+            60,
+            EventProcessor::new(1.0, None),
+            None,
+            Arc::new(tokio::sync::Mutex::new(ComponentSharedState::new())),
+            state_tx,
+        )
+        .await;
+
+        assert!(atomic.missing_ranges.lock().unwrap().is_empty());
+        assert_eq!(
+            atomic.source_totals.lock().unwrap()[&3],
+            SourceTotals {
+                total_events: 0,
+                total_batches: 2,
+                gaps: 0
+            }
+        );
+    }
+
+    #[test]
+    fn missing_ranges_recorded() {
+        let mut stats = DataSinkStats::default();
+        assert!(!stats.update(&EventDataBatch::new(0, 0)));
+        assert!(stats.update(&EventDataBatch::new(0, 5)));
+        assert!(stats.update(&EventDataBatch::new(0, 10)));
+        stats.update(&EventDataBatch::new(1, 0));
+
+        let missing = stats.missing_ranges();
+        assert_eq!(missing.len(), 1);
+        assert_eq!(missing[&0], vec![(1, 4), (6, 9)]);
+        assert_eq!(format_missing_ranges(&missing), "src 0: 1..4, 6..9");
+
+        // The list keeps only the most recent ranges
+        let source = stats.sources.get_mut(&0).unwrap();
+        for i in 0..MAX_MISSING_RANGES as u64 {
+            source.update(&EventDataBatch::new(0, 12 + 2 * i));
+        }
+        assert_eq!(source.missing_ranges.len(), MAX_MISSING_RANGES);
+        assert_eq!(source.missing_ranges.front(), Some(&(11, 11)));
+        assert_eq!(source.gaps_detected, 2 + MAX_MISSING_RANGES as u64);
+    }
+
+    #[test]
+    fn restart_detected_by_epoch() {
+        let mut stats = SourceStats::default();
+        let batch = |seq: u64, epoch: u32| {
+            let mut b = EventDataBatch::new(0, seq);
+            b.epoch = epoch;
+            b
+        };
+
+        stats.update(&batch(0, 1));
+        stats.update(&batch(1, 1));
+        // Restart close to the old sequence: no longer mistaken for a gap
+        stats.update(&batch(0, 2));
+        assert_eq!(stats.restart_count, 1);
+        assert_eq!(stats.gaps_detected, 0);
+
+        // A large jump within an epoch is a gap, not a restart
+        stats.update(&batch(500, 2));
+        assert_eq!(stats.restart_count, 1);
+        assert_eq!(stats.gaps_detected, 1);
+        assert_eq!(stats.total_gap_size, 499);
+
+        // A restart after a long run is detected even though seq is low
+        stats.update(&batch(5, 3));
+        assert_eq!(stats.restart_count, 2);
+        assert_eq!(stats.last_epoch, 3);
+
+        // Same epoch (a source that sends none): a large drop is a restart
+        stats.update(&batch(200, 3));
+        stats.update(&batch(2, 3));
+        assert_eq!(stats.restart_count, 3);
+    }
+
     #[test]
     fn multi_source_tracking() {
         let mut stats = DataSinkStats::default();
@@ -592,6 +1039,57 @@ mod tests {
         assert_eq!(stats.total_missing(), 12); // 3 + 9
     }
 
+    #[test]
+    fn per_source_breakdown() {
+        let batch = |source_id: u32, seq: u64, events: usize| {
+            let mut b = EventDataBatch::new(source_id, seq);
+            for _ in 0..events {
+                b.push(EventData::zeroed());
+            }
+            b
+        };
+        let mut stats = DataSinkStats::default();
+        let atomic = AtomicStats::new();
+
+        // Source 0: 3 batches of 4 events with one gap; source 1: a single batch
+        for b in [
+            batch(0, 0, 4),
+            batch(1, 0, 7),
+            batch(0, 1, 4),
+            batch(0, 3, 4),
+        ] {
+            stats.update(&b);
+        }
+        atomic.set_source_totals(stats.source_totals());
+
+        let totals = stats.source_totals();
+        assert_eq!(
+            totals[&0],
+            SourceTotals {
+                total_events: 12,
+                total_batches: 3,
+                gaps: 1
+            }
+        );
+        assert_eq!(
+            totals[&1],
+            SourceTotals {
+                total_events: 7,
+                total_batches: 1,
+                gaps: 0
+            }
+        );
+        assert_eq!(stats.total_events, 19);
+
+        let ext = DataSinkCommandExt {
+            atomic_stats: Arc::new(atomic),
+        };
+        let details = ext.status_details().unwrap();
+        assert!(details.ends_with(
+            "Sources: src 0: 12 events/3 batches/1 gaps; src 1: 7 events/1 batches/0 gaps"
+        ));
+    }
+
     #[test]
     fn atomic_stats() {
         let stats = AtomicStats::new();
@@ -606,4 +1104,120 @@ mod tests {
         assert_eq!(drop, 1);
         assert_eq!(eos, 0);
     }
+
+    #[test]
+    fn sampling_selects_one_in_ten() {
+        let mut batch = EventDataBatch::new(0, 0);
+        for _ in 0..100_000 {
+            batch.push(EventData::zeroed());
+        }
+
+        let mut stats = DataSinkStats::default();
+        stats.update(&batch);
+        stats.record_sampled(EventProcessor::new(0.1, None).process(&batch));
+
+        assert_eq!(stats.total_events, 100_000);
+        assert_eq!(stats.sampled_events, 10_000);
+        assert!(stats.report(1.0, 1.0).contains("Sampled: 10000"));
+
+        // Non-integer strides and the bounds
+        let mut sampler = EventSampler::new(0.3);
+        assert_eq!((0..1000).filter(|_| sampler.sample()).count(), 300);
+        let mut sampler = EventSampler::new(1.0);
+        assert!((0..1000).all(|_| sampler.sample()));
+        let mut sampler = EventSampler::new(0.0);
+        assert!(!(0..1000).any(|_| sampler.sample()));
+    }
+
+    /// Run the processor over `messages` in a Running component; returns the final state
+    async fn run_processor(tracker: EosTracker, messages: Vec<ProcessorMessage>) -> ComponentState {
+        let shared_state = Arc::new(tokio::sync::Mutex::new(ComponentSharedState::new()));
+        shared_state.lock().await.state = ComponentState::Running;
+        let (state_tx, _state_rx) = watch::channel(ComponentState::Running);
+        let (tx, rx) = mpsc::unbounded_channel();
+        for msg in messages {
+            tx.send(msg).unwrap();
+        }
+        drop(tx);
+
+        DataSink::processor_task(
+            rx,
+            Arc::new(AtomicStats::new()),
+            1,
+            EventProcessor::new(1.0, None),
+            Some(tracker),
+            shared_state.clone(),
+            state_tx,
+        )
+        .await;
+        let state = shared_state.lock().await.state;
+        state
+    }
+
+    #[tokio::test]
+    async fn finish_on_all_eos_waits_for_last_source() {
+        let tracker = EosTracker::new([0, 1, 2]);
+        let partial = vec![
+            ProcessorMessage::Data(EventDataBatch::new(0, 0)),
+            ProcessorMessage::Eos { source_id: 0 },
+            ProcessorMessage::Eos { source_id: 2 },
+        ];
+        assert_eq!(
+            run_processor(tracker.clone(), partial).await,
+            ComponentState::Running
+        );
+
+        let all = vec![
+            ProcessorMessage::Eos { source_id: 0 },
+            ProcessorMessage::Eos { source_id: 2 },
+            ProcessorMessage::Eos { source_id: 1 },
+        ];
+        assert_eq!(
+            run_processor(tracker, all).await,
+            ComponentState::Configured
+        );
+    }
+
+    #[tokio::test]
+    async fn csv_export_writes_received_events() {
+        let path = std::env::temp_dir().join(format!(
+            "delila_data_sink_csv_test_{}.csv",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+
+        let mut first = EventDataBatch::new(0, 0);
+        first.push(EventData::new(0, 3, 1200, 300, 1000.0, 0));
+        first.push(EventData::new(0, 4, 1500, 350, 1250.5, 0x01));
+        let mut second = EventDataBatch::new(1, 0);
+        second.push(EventData::new(1, 0, 800, 100, 2000.0, 0x02));
+
+        let (state_tx, _state_rx) = watch::channel(ComponentState::Running);
+        let (tx, rx) = mpsc::unbounded_channel();
+        tx.send(ProcessorMessage::Data(first)).unwrap();
+        tx.send(ProcessorMessage::Data(second)).unwrap();
+        drop(tx);
+
+        let csv = CsvExport::open(&path, Duration::from_secs(60)).unwrap();
+        DataSink::processor_task(
+            rx,
+            Arc::new(AtomicStats::new()),
+            1,
+            EventProcessor::new(1.0, Some(csv)),
+            None,
+            Arc::new(tokio::sync::Mutex::new(ComponentSharedState::new())),
+            state_tx,
+        )
+        .await;
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(
+            contents,
+            "module,channel,energy,energy_short,timestamp_ns,flags\n\
+             0,3,1200,300,1000,0\n\
+             0,4,1500,350,1250.5,1\n\
+             1,0,800,100,2000,2\n"
+        );
+    }
 }
@@ -6,24 +6,34 @@
 //! Architecture:
 //! - Main task: generates and publishes data when Running
 //! - Command task: handles REQ/REP commands, updates shared state via watch channel
+//!
+//! [`ReplaySource`] republishes a recorded `.delila` file instead of random data.
 
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 
 use futures::SinkExt;
-use rand::Rng;
-use rand_distr::{Distribution, Normal};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 use thiserror::Error;
-use tmq::{publish, Context};
+use tmq::{publish, AsZmqSocket, Context};
 use tokio::sync::{watch, Mutex};
 use tokio::time::interval;
 use tracing::{debug, info};
 
 use std::sync::atomic::{AtomicU64, Ordering};
 
+mod replay;
+mod spectrum;
+pub use replay::{ReplayConfig, ReplaySource, ReplayStats};
+pub use spectrum::PeakSpec;
+
+use spectrum::EnergySpectrum;
+
 use crate::common::{
-    flags, handle_command, run_command_task, CommandHandlerExt, ComponentSharedState,
-    ComponentState, EmulatorRuntimeConfig, EventData, EventDataBatch, Message, Waveform,
+    encode_with_limit, flags, handle_command, next_epoch, run_command_task, topic,
+    CommandHandlerExt, ComponentSharedState, ComponentState, EmulatorRuntimeConfig, EventData,
+    EventDataBatch, Message, SocketOptions, Waveform, DEFAULT_MAX_MESSAGE_BYTES,
 };
 
 /// Waveform probe bit masks
@@ -74,6 +84,24 @@ pub struct EmulatorConfig {
     pub waveform_probes: u8,
     /// Number of samples per waveform
     pub waveform_samples: usize,
+    /// Maximum serialized message size; larger batches are split (0 = no limit)
+    pub max_message_bytes: usize,
+    /// RNG seed for reproducible event sequences (None = seeded from entropy)
+    pub seed: Option<u64>,
+    /// Gaussian peaks of the energy spectrum (empty = one peak per channel)
+    pub peaks: Vec<PeakSpec>,
+    /// Fraction of events drawn from the uniform background
+    pub background_ratio: f64,
+    /// Topic frame sent before every data message (None = payload only)
+    pub topic_prefix: Option<String>,
+    /// Bit per channel; only set bits generate events (None = all channels)
+    pub channel_mask: Option<u64>,
+    /// Delta-code analog waveform probes before publishing
+    pub compress_waveforms: bool,
+    /// Publish energies above 16 bits instead of clamping them
+    pub wide_energy: bool,
+    /// ZMQ send HWM / linger of the data socket (default: ZMQ's)
+    pub socket_options: SocketOptions,
 }
 
 impl Default for EmulatorConfig {
@@ -90,10 +118,31 @@ impl Default for EmulatorConfig {
             enable_waveform: false,
             waveform_probes: waveform_probes::ALL_ANALOG, // analog_probe1 & 2 by default
             waveform_samples: 512,
+            max_message_bytes: DEFAULT_MAX_MESSAGE_BYTES,
+            seed: None,
+            peaks: Vec::new(),
+            background_ratio: 0.3,
+            topic_prefix: None,
+            channel_mask: None,
+            compress_waveforms: false,
+            wide_energy: false,
+            socket_options: SocketOptions::default(),
         }
     }
 }
 
+impl EmulatorConfig {
+    /// Channels that generate events, in ascending order
+    pub fn enabled_channels(&self) -> Vec<u8> {
+        (0..self.channels_per_module)
+            .filter(|&ch| match self.channel_mask {
+                Some(mask) => ch < 64 && mask & (1 << ch) != 0,
+                None => true,
+            })
+            .collect()
+    }
+}
+
 /// Emulator errors
 #[derive(Error, Debug)]
 pub enum EmulatorError {
@@ -105,6 +154,18 @@ pub enum EmulatorError {
 
     #[error("JSON error: {0}")]
     Json(#[from] serde_json::Error),
+
+    #[error("Invalid energy spectrum: {0}")]
+    InvalidSpectrum(String),
+
+    #[error("Channel mask {0:#x} enables none of the configured channels")]
+    NoEnabledChannels(u64),
+
+    #[error("Replay file error: {0}")]
+    ReplayFile(#[from] crate::recorder::FileFormatError),
+
+    #[error("Invalid replay config: {0}")]
+    InvalidReplay(String),
 }
 
 /// Lock-free statistics for emulator
@@ -275,6 +336,7 @@ impl CommandHandlerExt for EmulatorCommandExt {
             queue_max: 0,
             event_rate: self.rate_tracker.get_rate(),
             data_rate: 0.0,
+            ..Default::default()
         })
     }
 
@@ -304,15 +366,31 @@ pub struct Emulator {
     stats: Arc<AtomicStats>,
     rate_tracker: Arc<RateTracker>,
     sequence_number: u64,
+    /// Run epoch stamped on every batch (a new one on every Start)
+    epoch: u32,
     timestamp_ns: f64,
     heartbeat_counter: u64,
+    rng: StdRng,
+    spectrum: EnergySpectrum,
+    /// Channels drawn from in `generate_batch`
+    channels: Vec<u8>,
 }
 
 impl Emulator {
     /// Create a new emulator with the given configuration
     pub async fn new(config: EmulatorConfig) -> Result<Self, EmulatorError> {
+        let spectrum = EnergySpectrum::new(&config.peaks, config.background_ratio)
+            .map_err(EmulatorError::InvalidSpectrum)?;
+        let channels = config.enabled_channels();
+        if channels.is_empty() {
+            return Err(EmulatorError::NoEnabledChannels(
+                config.channel_mask.unwrap_or(0),
+            ));
+        }
+
         let context = Context::new();
         let data_socket = publish(&context).bind(&config.address)?;
+        config.socket_options.apply(data_socket.get_socket())?;
 
         info!(
             data_address = %config.address,
@@ -322,6 +400,10 @@ impl Emulator {
 
         let (state_tx, state_rx) = watch::channel(ComponentState::Idle);
         let runtime_settings = Arc::new(RuntimeSettings::new(&config));
+        let rng = match config.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        };
 
         Ok(Self {
             config,
@@ -333,8 +415,12 @@ impl Emulator {
             stats: Arc::new(AtomicStats::new()),
             rate_tracker: Arc::new(RateTracker::new()),
             sequence_number: 0,
+            epoch: next_epoch(0),
             timestamp_ns: 0.0,
             heartbeat_counter: 0,
+            rng,
+            spectrum,
+            channels,
         })
     }
 
@@ -347,18 +433,17 @@ impl Emulator {
     ///
     /// Creates a realistic pulse shape: baseline -> fast rise -> exponential decay
     /// The pulse timing is randomized within the waveform window.
-    fn generate_waveform(&self, energy: u16) -> Waveform {
-        let mut rng = rand::thread_rng();
+    fn generate_waveform(&mut self, energy: u32) -> Waveform {
         // Use runtime settings for waveform parameters
         let n = self.runtime_settings.waveform_samples();
         let probes = self.runtime_settings.waveform_probes();
 
         // Pulse parameters
-        let baseline: i16 = rng.gen_range(-50..50); // Small baseline fluctuation
-        let amplitude = (energy as f64 / 65535.0 * 8000.0) as i16; // Scale to ~8000 max
+        let baseline: i16 = self.rng.gen_range(-50..50); // Small baseline fluctuation
+        let amplitude = (energy as f64 / 65535.0 * 8000.0).min(8000.0) as i16; // Scale to ~8000 max
         let rise_time = 5; // samples
         let decay_tau = 50.0; // decay time constant in samples
-        let pulse_start = rng.gen_range(n / 4..n / 2); // Random trigger position
+        let pulse_start = self.rng.gen_range(n / 4..n / 2); // Random trigger position
 
         // Generate analog probe 1 (main signal)
         let analog_probe1 = if probes & waveform_probes::ANALOG_PROBE1 != 0 {
@@ -451,19 +536,20 @@ impl Emulator {
             digital_probe4,
             time_resolution: 0, // 1x resolution
             trigger_threshold: 100,
+            packed_analog: Vec::new(),
         }
     }
 
-    /// Generate a batch of random events with Gaussian peak + uniform background
+    /// Generate a batch of random events with Gaussian peaks + uniform background
     ///
-    /// Energy distribution:
-    /// - 70% Gaussian peak: mean = module * 1000 + channel * 50 + 500, sigma = 50
-    /// - 30% Uniform background: 0 to 4095 (simulating random noise/cosmic rays)
+    /// Energy distribution (see [`EmulatorConfig::peaks`]):
+    /// - `background_ratio` (default 30%): uniform 0 to 4095 (random noise/cosmic rays)
+    /// - otherwise a mixture of the configured peaks, or by default one peak per
+    ///   channel: mean = module * 1000 + channel * 50 + 500, sigma = 50
     ///
-    /// This creates distinct peaks for each channel with a realistic background,
+    /// This creates distinct peaks with a realistic background,
     /// useful for testing fitting algorithms.
     fn generate_batch(&mut self) -> EventDataBatch {
-        let mut rng = rand::thread_rng();
         // Use runtime settings for events_per_batch
         let events_per_batch = self.runtime_settings.events_per_batch();
         let enable_waveform = self.runtime_settings.enable_waveform();
@@ -473,38 +559,25 @@ impl Emulator {
             self.sequence_number,
             events_per_batch,
         );
+        batch.epoch = self.epoch;
 
         // Module number = source_id (each emulator represents one digitizer module)
         let module = self.config.source_id as u8;
 
-        // Background ratio: 30% uniform, 70% Gaussian peak
-        const BACKGROUND_RATIO: f64 = 0.3;
-
         for _ in 0..events_per_batch {
-            let channel = rng.gen_range(0..self.config.channels_per_module);
+            let channel = self.channels[self.rng.gen_range(0..self.channels.len())];
 
-            let energy: u16 = if rng.gen_bool(BACKGROUND_RATIO) {
-                // Uniform background: 0 to 4095 (12-bit ADC range)
-                rng.gen_range(0..4096)
-            } else {
-                // Gaussian peak: mean = module*1000 + channel*50 + 500, sigma = 50
-                let mean = (module as f64) * 1000.0 + (channel as f64) * 50.0 + 500.0;
-                let sigma = 50.0;
-                let normal = Normal::new(mean, sigma).unwrap();
-                let energy_f64 = normal.sample(&mut rng);
-                // Clamp to valid u16 range
-                energy_f64.clamp(0.0, 65535.0) as u16
-            };
+            let energy = self.spectrum.sample(&mut self.rng, module, channel);
 
             // Short gate energy: ~70-80% of long gate with some noise
-            let short_ratio = 0.75 + rng.gen_range(-0.05..0.05);
+            let short_ratio = 0.75 + self.rng.gen_range(-0.05..0.05);
             let energy_short: u16 = ((energy as f64) * short_ratio).clamp(0.0, 65535.0) as u16;
 
-            self.timestamp_ns += rng.gen_range(10.0..1000.0);
+            self.timestamp_ns += self.rng.gen_range(10.0..1000.0);
 
-            let flags = if rng.gen_ratio(1, 100) {
+            let flags = if self.rng.gen_ratio(1, 100) {
                 flags::FLAG_PILEUP
-            } else if rng.gen_ratio(1, 1000) {
+            } else if self.rng.gen_ratio(1, 1000) {
                 flags::FLAG_OVER_RANGE
             } else {
                 0
@@ -534,6 +607,12 @@ impl Emulator {
 
             batch.push(event);
         }
+        if !self.config.wide_energy {
+            batch.clamp_energies();
+        }
+        if self.config.compress_waveforms {
+            batch.compress_waveforms();
+        }
 
         self.sequence_number += 1;
         batch
@@ -541,10 +620,12 @@ impl Emulator {
 
     /// Publish a message via ZMQ
     async fn publish_message(&mut self, message: &Message) -> Result<(), EmulatorError> {
-        let bytes = message.to_msgpack()?;
-        let bytes_len = bytes.len() as u64;
-        let msg: tmq::Multipart = vec![tmq::Message::from(bytes.as_slice())].into();
-        self.data_socket.send(msg).await?;
+        let mut bytes_len = 0u64;
+        for bytes in encode_with_limit(message, self.config.max_message_bytes)? {
+            bytes_len += bytes.len() as u64;
+            let msg = topic::data_message(self.config.topic_prefix.as_deref(), &bytes);
+            self.data_socket.send(msg).await?;
+        }
 
         match message {
             Message::Data(batch) => {
@@ -591,10 +672,22 @@ impl Emulator {
         self.publish_message(&hb).await
     }
 
+    /// Reset per-run counters on Start; a Resume from Paused continues the run
+    fn on_state_change(&mut self, previous: ComponentState, current: ComponentState) {
+        if current == ComponentState::Running && previous != ComponentState::Paused {
+            self.sequence_number = 0;
+            self.epoch = next_epoch(self.epoch);
+            self.timestamp_ns = 0.0;
+            self.heartbeat_counter = 0;
+            info!(epoch = self.epoch, "Sequence number reset to 0 on Start");
+        }
+    }
+
     /// Run the emulator with command control
     ///
     /// Spawns command task in separate tokio task.
-    /// Main task generates data when state is Running.
+    /// Main task generates data when state is Running; heartbeats continue
+    /// while Paused.
     /// If batch_interval_ms is 0, runs at full speed without delay.
     pub async fn run(
         &mut self,
@@ -647,6 +740,7 @@ impl Emulator {
 
         // Main data generation loop
         let mut state_rx = self.state_rx.clone();
+        let mut last_state = *state_rx.borrow();
 
         loop {
             if use_ticker {
@@ -662,13 +756,8 @@ impl Emulator {
                     _ = state_rx.changed() => {
                         let current = *state_rx.borrow();
                         info!(state = %current, "State changed");
-                        // Reset sequence number on Start
-                        if current == ComponentState::Running {
-                            self.sequence_number = 0;
-                            self.timestamp_ns = 0.0;
-                            self.heartbeat_counter = 0;
-                            info!("Sequence number reset to 0 on Start");
-                        }
+                        self.on_state_change(last_state, current);
+                        last_state = current;
                     }
 
                     _ = ticker.tick(), if *state_rx.borrow() == ComponentState::Running => {
@@ -677,7 +766,7 @@ impl Emulator {
                         self.publish_message(&msg).await?;
                     }
 
-                    _ = heartbeat_ticker.tick(), if use_heartbeat && *state_rx.borrow() == ComponentState::Running => {
+                    _ = heartbeat_ticker.tick(), if use_heartbeat && state_rx.borrow().in_run() => {
                         self.send_heartbeat().await?;
                     }
                 }
@@ -695,17 +784,12 @@ impl Emulator {
                     _ = state_rx.changed() => {
                         let current = *state_rx.borrow();
                         info!(state = %current, "State changed");
-                        // Reset sequence number on Start
-                        if current == ComponentState::Running {
-                            self.sequence_number = 0;
-                            self.timestamp_ns = 0.0;
-                            self.heartbeat_counter = 0;
-                            info!("Sequence number reset to 0 on Start");
-                        }
+                        self.on_state_change(last_state, current);
+                        last_state = current;
                         continue;
                     }
 
-                    _ = heartbeat_ticker.tick(), if use_heartbeat && *state_rx.borrow() == ComponentState::Running => {
+                    _ = heartbeat_ticker.tick(), if use_heartbeat && state_rx.borrow().in_run() => {
                         self.send_heartbeat().await?;
                         continue;
                     }
@@ -727,8 +811,8 @@ impl Emulator {
             }
         }
 
-        // Send EOS if we were running
-        if *self.state_rx.borrow() == ComponentState::Running {
+        // Send EOS if we were running (or paused mid-run)
+        if self.state_rx.borrow().in_run() {
             self.send_eos().await?;
         }
 
@@ -798,6 +882,19 @@ mod tests {
             enable_waveform: true,
             waveform_probes: waveform_probes::ALL,
             waveform_samples: 1024,
+            max_message_bytes: 1024 * 1024,
+            seed: Some(7),
+            peaks: vec![PeakSpec {
+                mean: 1000.0,
+                sigma: 20.0,
+                intensity: 1.0,
+            }],
+            background_ratio: 0.1,
+            topic_prefix: None,
+            channel_mask: Some(0b1001),
+            compress_waveforms: true,
+            wide_energy: false,
+            socket_options: SocketOptions::default(),
         };
         assert_eq!(config.source_id, 42);
         assert_eq!(config.events_per_batch, 200);
@@ -805,6 +902,8 @@ mod tests {
         assert_eq!(config.num_modules, 2);
         assert!(config.enable_waveform);
         assert_eq!(config.waveform_samples, 1024);
+        assert_eq!(config.max_message_bytes, 1024 * 1024);
+        assert_eq!(config.seed, Some(7));
     }
 
     #[test]
@@ -863,6 +962,188 @@ mod tests {
         assert_eq!(emulator.heartbeat_counter, 0);
     }
 
+    /// Run `run_batches` with `seed` and collect the serialized events it published
+    async fn seeded_run_events(seed: u64, port: u16) -> Vec<u8> {
+        use futures::StreamExt;
+
+        let config = EmulatorConfig {
+            address: format!("tcp://127.0.0.1:{}", port),
+            command_address: format!("tcp://127.0.0.1:{}", port + 1),
+            events_per_batch: 50,
+            batch_interval_ms: 1,
+            enable_waveform: true,
+            waveform_samples: 64,
+            seed: Some(seed),
+            ..Default::default()
+        };
+        let mut emulator = Emulator::new(config.clone()).await.unwrap();
+
+        let context = Context::new();
+        let mut sub = tmq::subscribe(&context)
+            .connect(&config.address)
+            .unwrap()
+            .subscribe(b"")
+            .unwrap();
+        // Let the subscription propagate before publishing (slow joiner)
+        tokio::time::sleep(Duration::from_millis(300)).await;
+
+        emulator.run_batches(5).await.unwrap();
+
+        let mut events = Vec::new();
+        loop {
+            let multipart = tokio::time::timeout(Duration::from_secs(5), sub.next())
+                .await
+                .expect("message before timeout")
+                .unwrap()
+                .unwrap();
+            let bytes = multipart.into_iter().next().unwrap();
+            match Message::from_msgpack(&bytes).unwrap() {
+                // Batch headers carry a wall-clock timestamp; compare the events
+                Message::Data(batch) => events.extend(batch.events),
+                Message::EndOfStream { .. } => break,
+                Message::Heartbeat(_) => {}
+            }
+        }
+        assert_eq!(events.len(), 5 * 50);
+        rmp_serde::to_vec(&events).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_same_seed_reproduces_events() {
+        let first = seeded_run_events(1234, 15570).await;
+        let second = seeded_run_events(1234, 15572).await;
+        assert_eq!(first, second);
+
+        let other = seeded_run_events(4321, 15574).await;
+        assert_ne!(first, other);
+    }
+
+    #[tokio::test]
+    async fn test_two_peak_spectrum_has_two_maxima() {
+        let config = EmulatorConfig {
+            address: "tcp://127.0.0.1:15576".to_string(),
+            command_address: "tcp://127.0.0.1:15577".to_string(),
+            events_per_batch: 1000,
+            seed: Some(99),
+            peaks: vec![
+                PeakSpec {
+                    mean: 1000.0,
+                    sigma: 30.0,
+                    intensity: 1.0,
+                },
+                PeakSpec {
+                    mean: 3000.0,
+                    sigma: 30.0,
+                    intensity: 2.0,
+                },
+            ],
+            background_ratio: 0.05,
+            ..Default::default()
+        };
+        let mut emulator = Emulator::new(config).await.unwrap();
+
+        // 100-channel bins over the 12-bit range
+        let mut histogram = [0u32; 41];
+        for _ in 0..20 {
+            for event in emulator.generate_batch().events {
+                histogram[(event.energy / 100).min(40) as usize] += 1;
+            }
+        }
+
+        let maxima: Vec<usize> = (1..histogram.len() - 1)
+            .filter(|&i| histogram[i] > histogram[i - 1] && histogram[i] >= histogram[i + 1])
+            .filter(|&i| histogram[i] > 1000)
+            .collect();
+        assert_eq!(maxima.len(), 2, "histogram: {:?}", histogram);
+        assert!((9..=10).contains(&maxima[0]));
+        assert!((29..=30).contains(&maxima[1]));
+        assert!(histogram[maxima[1]] > histogram[maxima[0]]);
+        assert!(histogram[20] < histogram[maxima[0]] / 10);
+    }
+
+    #[tokio::test]
+    async fn test_invalid_spectrum_rejected_on_creation() {
+        let config = EmulatorConfig {
+            address: "tcp://127.0.0.1:15578".to_string(),
+            command_address: "tcp://127.0.0.1:15579".to_string(),
+            background_ratio: 2.0,
+            ..Default::default()
+        };
+        assert!(matches!(
+            Emulator::new(config).await,
+            Err(EmulatorError::InvalidSpectrum(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_channel_mask_limits_channels() {
+        let config = EmulatorConfig {
+            address: "tcp://127.0.0.1:15587".to_string(),
+            command_address: "tcp://127.0.0.1:15588".to_string(),
+            events_per_batch: 500,
+            seed: Some(3),
+            channel_mask: Some(0b1001),
+            ..Default::default()
+        };
+        assert_eq!(config.enabled_channels(), vec![0, 3]);
+        let mut emulator = Emulator::new(config).await.unwrap();
+
+        let mut seen = [false; 2];
+        for event in emulator.generate_batch().events {
+            assert!(
+                event.channel == 0 || event.channel == 3,
+                "channel {} is masked out",
+                event.channel
+            );
+            seen[(event.channel == 3) as usize] = true;
+        }
+        assert_eq!(seen, [true, true]);
+
+        // A mask without any configured channel is rejected
+        let config = EmulatorConfig {
+            address: "tcp://127.0.0.1:15589".to_string(),
+            command_address: "tcp://127.0.0.1:15590".to_string(),
+            channels_per_module: 4,
+            channel_mask: Some(0b1_0000),
+            ..Default::default()
+        };
+        assert!(matches!(
+            Emulator::new(config).await,
+            Err(EmulatorError::NoEnabledChannels(0x10))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_energies_clamped_unless_wide() {
+        for wide_energy in [false, true] {
+            let config = EmulatorConfig {
+                address: "tcp://127.0.0.1:15591".to_string(),
+                command_address: "tcp://127.0.0.1:15592".to_string(),
+                peaks: vec![PeakSpec {
+                    mean: 200_000.0,
+                    sigma: 30.0,
+                    intensity: 1.0,
+                }],
+                background_ratio: 0.0,
+                wide_energy,
+                ..Default::default()
+            };
+            let mut emulator = Emulator::new(config).await.unwrap();
+            let max = emulator
+                .generate_batch()
+                .events
+                .iter()
+                .map(|e| e.energy)
+                .max()
+                .unwrap();
+            if wide_energy {
+                assert!(max > u16::MAX as u32);
+            } else {
+                assert_eq!(max, u16::MAX as u32);
+            }
+        }
+    }
+
     #[test]
     fn test_flag_constants() {
         // Verify flag constants are defined correctly
@@ -0,0 +1,305 @@
+//! Replay source - republishes batches from a recorded `.delila` file
+//!
+//! Batches are published in file order with the pacing of the recording:
+//! each batch is sent when the time elapsed since the start of the pass
+//! matches its first event's `timestamp_ns` offset, divided by `speed`.
+//! Compressed recordings (`.delila.gz` / `.delila.zst`) are decompressed in
+//! memory before replay. Reading and pacing are done by
+//! [`crate::recorder::replay_file`].
+//!
+//! At the end of the file the replay either starts over (`loop_playback`) or
+//! sends one EOS per recorded source and stops. Sequence numbers are
+//! published as recorded, so every loop pass repeats them.
+
+use std::collections::BTreeSet;
+use std::path::PathBuf;
+
+use futures::SinkExt;
+use tmq::{publish, AsZmqSocket, Context};
+use tokio::time::{sleep_until, Instant};
+use tracing::{debug, info};
+
+use super::EmulatorError;
+use crate::common::{
+    encode_with_limit, topic, EventDataBatch, Message, SocketOptions, DEFAULT_MAX_MESSAGE_BYTES,
+};
+use crate::recorder::{replay_file, FileReplay};
+
+/// Replay configuration
+#[derive(Debug, Clone, serde::Deserialize)]
+#[serde(default)]
+pub struct ReplayConfig {
+    /// Recorded `.delila` file to replay
+    pub path: PathBuf,
+    /// ZMQ bind address for data (e.g., "tcp://*:5555")
+    pub address: String,
+    /// Playback speed relative to the recording (1.0 = original timing,
+    /// 0.0 = as fast as possible)
+    pub speed: f64,
+    /// Start over at the end of the file instead of stopping
+    pub loop_playback: bool,
+    /// Maximum serialized message size; larger batches are split (0 = no limit)
+    pub max_message_bytes: usize,
+    /// Topic frame sent before every data message (None = payload only)
+    pub topic_prefix: Option<String>,
+    /// ZMQ send HWM / linger of the data socket (default: ZMQ's)
+    pub socket_options: SocketOptions,
+}
+
+impl Default for ReplayConfig {
+    fn default() -> Self {
+        Self {
+            path: PathBuf::new(),
+            address: "tcp://*:5555".to_string(),
+            speed: 1.0,
+            loop_playback: false,
+            max_message_bytes: DEFAULT_MAX_MESSAGE_BYTES,
+            topic_prefix: None,
+            socket_options: SocketOptions::default(),
+        }
+    }
+}
+
+/// Totals of a finished replay
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ReplayStats {
+    /// Batches published
+    pub batches: u64,
+    /// Events published
+    pub events: u64,
+    /// Completed passes over the file
+    pub passes: u64,
+}
+
+/// Replay source publishing recorded data via ZeroMQ
+pub struct ReplaySource {
+    config: ReplayConfig,
+    data_socket: publish::Publish,
+    stats: ReplayStats,
+}
+
+impl ReplaySource {
+    /// Bind the data socket and check the replay file can be opened
+    pub async fn new(config: ReplayConfig) -> Result<Self, EmulatorError> {
+        if !config.speed.is_finite() || config.speed < 0.0 {
+            return Err(EmulatorError::InvalidReplay(format!(
+                "speed must be a finite value >= 0, got {}",
+                config.speed
+            )));
+        }
+        Self::open(&config)?;
+
+        let context = Context::new();
+        let data_socket = publish(&context).bind(&config.address)?;
+        config.socket_options.apply(data_socket.get_socket())?;
+
+        info!(
+            path = %config.path.display(),
+            address = %config.address,
+            speed = config.speed,
+            loop_playback = config.loop_playback,
+            "Replay source bound to data address"
+        );
+
+        Ok(Self {
+            config,
+            data_socket,
+            stats: ReplayStats::default(),
+        })
+    }
+
+    /// Replay until the end of the file (or shutdown when looping)
+    pub async fn run(
+        &mut self,
+        mut shutdown: tokio::sync::broadcast::Receiver<()>,
+    ) -> Result<ReplayStats, EmulatorError> {
+        let mut sources = BTreeSet::new();
+
+        loop {
+            let pass = tokio::select! {
+                biased;
+
+                _ = shutdown.recv() => None,
+                result = self.replay_pass(&mut sources) => Some(result),
+            };
+
+            let Some(result) = pass else {
+                info!("Replay received shutdown signal");
+                break;
+            };
+            result?;
+            self.stats.passes += 1;
+            if !self.config.loop_playback {
+                break;
+            }
+        }
+
+        for source_id in sources {
+            self.publish(&Message::eos(source_id)).await?;
+            info!(source_id, "Published EOS");
+        }
+
+        info!(
+            batches = self.stats.batches,
+            events = self.stats.events,
+            passes = self.stats.passes,
+            "Replay finished"
+        );
+        Ok(self.stats)
+    }
+
+    /// Publish every batch of the file once, paced by `speed`
+    async fn replay_pass(&mut self, sources: &mut BTreeSet<u32>) -> Result<(), EmulatorError> {
+        let mut replay = Self::open(&self.config)?;
+
+        while let Some(next) = replay.next_batch() {
+            let (batch, due) = next?;
+            if self.config.speed > 0.0 {
+                sleep_until(Instant::from_std(due)).await;
+            }
+
+            sources.insert(batch.source_id);
+            self.publish_batch(batch).await?;
+        }
+        Ok(())
+    }
+
+    /// Open the replay file for one pass over all of it
+    fn open(config: &ReplayConfig) -> Result<FileReplay, EmulatorError> {
+        Ok(replay_file(&config.path, config.speed, None, None)?)
+    }
+
+    async fn publish_batch(&mut self, batch: EventDataBatch) -> Result<(), EmulatorError> {
+        let events = batch.len() as u64;
+        debug!(
+            source_id = batch.source_id,
+            seq = batch.sequence_number,
+            events,
+            "Replaying batch"
+        );
+        self.publish(&Message::data(batch)).await?;
+        self.stats.batches += 1;
+        self.stats.events += events;
+        Ok(())
+    }
+
+    async fn publish(&mut self, message: &Message) -> Result<(), EmulatorError> {
+        for bytes in encode_with_limit(message, self.config.max_message_bytes)? {
+            let msg = topic::data_message(self.config.topic_prefix.as_deref(), &bytes);
+            self.data_socket.send(msg).await?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    use futures::StreamExt;
+    use tokio::time::Duration;
+
+    use crate::common::EventData;
+    use crate::recorder::{ChecksumCalculator, FileFooter, FileHeader};
+
+    /// Write a minimal recorder file with `batches` batches of 10 events each
+    fn write_recording(path: &std::path::Path, batches: u64) {
+        let mut buf = Vec::new();
+        FileHeader::new(1, "REPLAY".to_string(), 0)
+            .write_to(&mut buf)
+            .unwrap();
+
+        let mut checksum = ChecksumCalculator::new();
+        let mut footer = FileFooter::new();
+        for seq in 0..batches {
+            let mut batch = EventDataBatch::new((seq % 2) as u32, seq / 2);
+            for i in 0..10u16 {
+                let ts = (seq * 1_000 + i as u64) as f64;
+                batch.push(EventData::new(0, i as u8, i.into(), i, ts, 0));
+            }
+            let data = batch.to_msgpack().unwrap();
+            let len = (data.len() as u32).to_le_bytes();
+            buf.write_all(&len).unwrap();
+            buf.write_all(&data).unwrap();
+            checksum.update(&len);
+            checksum.update(&data);
+            footer.total_events += 10;
+        }
+        footer.data_checksum = checksum.finalize();
+        footer.data_bytes = checksum.bytes_processed();
+        footer.finalize();
+        footer.write_to(&mut buf).unwrap();
+        std::fs::write(path, buf).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_replay_publishes_recorded_events() {
+        let dir = std::env::temp_dir().join(format!("delila_replay_test_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("run0001_0000_REPLAY.delila");
+        write_recording(&path, 6);
+
+        let config = ReplayConfig {
+            path: path.clone(),
+            address: "tcp://127.0.0.1:15580".to_string(),
+            speed: 0.0,
+            ..Default::default()
+        };
+        let mut replay = ReplaySource::new(config.clone()).await.unwrap();
+
+        let context = Context::new();
+        let mut sub = tmq::subscribe(&context)
+            .connect(&config.address)
+            .unwrap()
+            .subscribe(b"")
+            .unwrap();
+        // Let the subscription propagate before publishing (slow joiner)
+        tokio::time::sleep(Duration::from_millis(300)).await;
+
+        let (_shutdown_tx, shutdown_rx) = tokio::sync::broadcast::channel(1);
+        let stats = replay.run(shutdown_rx).await.unwrap();
+        assert_eq!(
+            stats,
+            ReplayStats {
+                batches: 6,
+                events: 60,
+                passes: 1
+            }
+        );
+
+        let mut events = 0;
+        let mut eos = BTreeSet::new();
+        while eos.len() < 2 {
+            let multipart = tokio::time::timeout(Duration::from_secs(5), sub.next())
+                .await
+                .expect("message before timeout")
+                .unwrap()
+                .unwrap();
+            let bytes = multipart.into_iter().next().unwrap();
+            match Message::from_msgpack(&bytes).unwrap() {
+                Message::Data(batch) => events += batch.len(),
+                Message::EndOfStream { source_id } => {
+                    eos.insert(source_id);
+                }
+                Message::Heartbeat(_) => {}
+            }
+        }
+        assert_eq!(events, 60);
+        assert_eq!(eos.into_iter().collect::<Vec<_>>(), vec![0, 1]);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_replay_rejects_negative_speed() {
+        let config = ReplayConfig {
+            speed: -1.0,
+            ..Default::default()
+        };
+        assert!(matches!(
+            ReplaySource::new(config).await,
+            Err(EmulatorError::InvalidReplay(_))
+        ));
+    }
+}
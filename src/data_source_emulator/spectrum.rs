@@ -0,0 +1,139 @@
+//! Energy spectrum model for generated events
+//!
+//! Energies are drawn from a mixture of Gaussian peaks on top of a uniform
+//! 12-bit background. Without configured peaks, every channel gets a single
+//! peak at `module * 1000 + channel * 50 + 500` (sigma 50), which gives each
+//! channel a distinct line for fitting tests. Peaks may lie beyond 16 bits
+//! to emulate firmware with wide energy words.
+
+use rand::distributions::WeightedIndex;
+use rand::Rng;
+use rand_distr::{Distribution, Normal};
+use serde::Deserialize;
+
+/// Upper bound (exclusive) of the uniform background (12-bit ADC range)
+const BACKGROUND_MAX: u32 = 4096;
+
+/// Sigma of the default per-channel peak
+const DEFAULT_SIGMA: f64 = 50.0;
+
+/// One Gaussian line of the energy spectrum
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct PeakSpec {
+    /// Peak centre (ADC channels)
+    pub mean: f64,
+    /// Peak width (ADC channels)
+    pub sigma: f64,
+    /// Relative intensity among the configured peaks
+    pub intensity: f64,
+}
+
+/// Sampler for the configured spectrum
+#[derive(Debug, Clone)]
+pub(crate) struct EnergySpectrum {
+    peaks: Vec<Normal<f64>>,
+    weights: Option<WeightedIndex<f64>>,
+    background_ratio: f64,
+}
+
+impl EnergySpectrum {
+    /// Build the sampler; an empty `peaks` list selects the default per-channel peak
+    pub(crate) fn new(peaks: &[PeakSpec], background_ratio: f64) -> Result<Self, String> {
+        if !(0.0..=1.0).contains(&background_ratio) {
+            return Err(format!(
+                "background_ratio must be within 0..=1, got {}",
+                background_ratio
+            ));
+        }
+
+        // Normal::new accepts a negative std-dev (it mirrors the distribution)
+        if let Some(p) = peaks
+            .iter()
+            .find(|p| !(p.sigma.is_finite() && p.sigma > 0.0))
+        {
+            return Err(format!(
+                "peak at {}: sigma must be positive, got {}",
+                p.mean, p.sigma
+            ));
+        }
+        let normals = peaks
+            .iter()
+            .map(|p| Normal::new(p.mean, p.sigma).map_err(|e| format!("peak at {}: {}", p.mean, e)))
+            .collect::<Result<Vec<_>, _>>()?;
+        let weights = if peaks.is_empty() {
+            None
+        } else {
+            Some(
+                WeightedIndex::new(peaks.iter().map(|p| p.intensity))
+                    .map_err(|e| format!("peak intensities: {}", e))?,
+            )
+        };
+
+        Ok(Self {
+            peaks: normals,
+            weights,
+            background_ratio,
+        })
+    }
+
+    /// Draw one energy for an event on `module`/`channel`
+    pub(crate) fn sample<R: Rng>(&self, rng: &mut R, module: u8, channel: u8) -> u32 {
+        if rng.gen_bool(self.background_ratio) {
+            return rng.gen_range(0..BACKGROUND_MAX);
+        }
+
+        let energy = match self.weights {
+            Some(ref weights) => self.peaks[weights.sample(rng)].sample(rng),
+            None => {
+                let mean = (module as f64) * 1000.0 + (channel as f64) * 50.0 + 500.0;
+                Normal::new(mean, DEFAULT_SIGMA)
+                    .expect("valid default sigma")
+                    .sample(rng)
+            }
+        };
+        // Clamp to valid u32 range
+        energy.clamp(0.0, u32::MAX as f64) as u32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_invalid_spectrum_rejected() {
+        let zero_weights = [PeakSpec {
+            mean: 100.0,
+            sigma: 10.0,
+            intensity: 0.0,
+        }];
+        assert!(EnergySpectrum::new(&zero_weights, 0.3).is_err());
+
+        let negative_sigma = [PeakSpec {
+            mean: 100.0,
+            sigma: -1.0,
+            intensity: 1.0,
+        }];
+        assert!(EnergySpectrum::new(&negative_sigma, 0.3).is_err());
+
+        assert!(EnergySpectrum::new(&[], 1.5).is_err());
+        assert!(EnergySpectrum::new(&[], 0.3).is_ok());
+    }
+
+    #[test]
+    fn test_peak_beyond_16_bits() {
+        use rand::SeedableRng;
+
+        let peaks = [PeakSpec {
+            mean: 200_000.0,
+            sigma: 100.0,
+            intensity: 1.0,
+        }];
+        let spectrum = EnergySpectrum::new(&peaks, 0.0).unwrap();
+        let mut rng = rand::rngs::StdRng::seed_from_u64(7);
+        for _ in 0..100 {
+            let energy = spectrum.sample(&mut rng, 0, 0);
+            assert!((199_000..201_000).contains(&energy), "energy {energy}");
+        }
+    }
+}
@@ -0,0 +1,179 @@
+//! Coincidence filtering on a time-ordered event stream
+//!
+//! Events are grouped starting from the first unassigned event: every event
+//! within `window_ns` of the group's first event joins the group. A group is
+//! forwarded when it holds events from at least `multiplicity` distinct
+//! (module, channel) pairs of the configured set; otherwise its events are
+//! dropped as singles. Events from channels outside the set never open or
+//! join a group and are dropped.
+//!
+//! The filter expects its input sorted by `timestamp_ns` (the output of
+//! [`TimeSorter`](super::TimeSorter)).
+
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+
+use crate::common::EventData;
+
+/// Coincidence condition for the Merger
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CoincidenceConfig {
+    /// Coincidence window in ns, measured from the first event of a group
+    pub window_ns: f64,
+    /// (module, channel) pairs taking part in the coincidence (empty = all)
+    #[serde(default)]
+    pub channels: Vec<(u8, u8)>,
+    /// Minimum number of distinct channels in a group (default: 2)
+    #[serde(default = "default_multiplicity")]
+    pub multiplicity: usize,
+}
+
+fn default_multiplicity() -> usize {
+    2
+}
+
+/// Streaming coincidence filter
+#[derive(Debug)]
+pub struct CoincidenceFilter {
+    window_ns: f64,
+    channels: HashSet<(u8, u8)>,
+    multiplicity: usize,
+    group: Vec<EventData>,
+    coincidences: u64,
+    singles: u64,
+}
+
+impl CoincidenceFilter {
+    /// Create a filter from its configuration
+    pub fn new(config: &CoincidenceConfig) -> Self {
+        Self {
+            window_ns: config.window_ns,
+            channels: config.channels.iter().copied().collect(),
+            multiplicity: config.multiplicity.max(1),
+            group: Vec::new(),
+            coincidences: 0,
+            singles: 0,
+        }
+    }
+
+    /// Feed time-ordered events; returns events belonging to matched groups
+    ///
+    /// The last group stays open until a later event falls outside its window
+    /// or [`flush`](Self::flush) is called.
+    pub fn process(&mut self, events: Vec<EventData>) -> Vec<EventData> {
+        let mut out = Vec::new();
+        for event in events {
+            if !self.accepts(&event) {
+                self.singles += 1;
+                continue;
+            }
+            if let Some(start) = self.group.first().map(|e| e.timestamp_ns) {
+                let dt = event.timestamp_ns - start;
+                // Time going backwards means a new run: never group across it
+                if !(0.0..=self.window_ns).contains(&dt) {
+                    self.close_group(&mut out);
+                }
+            }
+            self.group.push(event);
+        }
+        out
+    }
+
+    /// Close the open group (end of run)
+    pub fn flush(&mut self) -> Vec<EventData> {
+        let mut out = Vec::new();
+        self.close_group(&mut out);
+        out
+    }
+
+    /// Return and reset the (coincidences found, singles dropped) counters
+    pub fn take_counters(&mut self) -> (u64, u64) {
+        (
+            std::mem::take(&mut self.coincidences),
+            std::mem::take(&mut self.singles),
+        )
+    }
+
+    fn accepts(&self, event: &EventData) -> bool {
+        self.channels.is_empty() || self.channels.contains(&(event.module, event.channel))
+    }
+
+    fn close_group(&mut self, out: &mut Vec<EventData>) {
+        if self.group.is_empty() {
+            return;
+        }
+        let distinct: HashSet<(u8, u8)> =
+            self.group.iter().map(|e| (e.module, e.channel)).collect();
+        if distinct.len() >= self.multiplicity {
+            self.coincidences += 1;
+            out.append(&mut self.group);
+        } else {
+            self.singles += self.group.len() as u64;
+            self.group.clear();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(module: u8, channel: u8, ts: f64) -> EventData {
+        EventData::new(module, channel, 100, 50, ts, 0)
+    }
+
+    fn filter(window_ns: f64) -> CoincidenceFilter {
+        CoincidenceFilter::new(&CoincidenceConfig {
+            window_ns,
+            channels: vec![(0, 0), (1, 0)],
+            multiplicity: 2,
+        })
+    }
+
+    #[test]
+    fn test_in_window_pair_passes() {
+        let mut filter = filter(10.0);
+        let mut out = filter.process(vec![event(0, 0, 100.0), event(1, 0, 105.0)]);
+        out.extend(filter.flush());
+
+        assert_eq!(out.len(), 2);
+        assert_eq!(out[0].timestamp_ns, 100.0);
+        assert_eq!(out[1].timestamp_ns, 105.0);
+        assert_eq!(filter.take_counters(), (1, 0));
+    }
+
+    #[test]
+    fn test_out_of_window_pair_dropped() {
+        let mut filter = filter(10.0);
+        let mut out = filter.process(vec![event(0, 0, 100.0), event(1, 0, 125.0)]);
+        out.extend(filter.flush());
+
+        assert!(out.is_empty());
+        assert_eq!(filter.take_counters(), (0, 2));
+    }
+
+    #[test]
+    fn test_same_channel_and_foreign_channel_do_not_count() {
+        let mut filter = filter(10.0);
+        let out = filter.process(vec![
+            // Same channel twice: multiplicity 1
+            event(0, 0, 0.0),
+            event(0, 0, 5.0),
+            // Channel outside the configured set
+            event(2, 0, 50.0),
+            // Real coincidence, split across calls
+            event(1, 0, 100.0),
+        ]);
+        assert!(out.is_empty());
+
+        let mut out = filter.process(vec![event(0, 0, 108.0), event(1, 0, 500.0)]);
+        out.extend(filter.flush());
+
+        let timestamps: Vec<f64> = out.iter().map(|e| e.timestamp_ns).collect();
+        assert_eq!(timestamps, vec![100.0, 108.0]);
+        // Singles: two same-channel events, the foreign one, and the lone 500
+        assert_eq!(filter.take_counters(), (1, 4));
+        assert_eq!(filter.take_counters(), (0, 0));
+    }
+}
@@ -0,0 +1,176 @@
+//! Duplicate event removal on a time-ordered event stream
+//!
+//! Redundant trigger paths can make two sources emit the same physical
+//! event. An event is a duplicate when an earlier event with the same
+//! (module, channel) and a timestamp within `tolerance_ns` is still tracked.
+//! Events are tracked for `window_ns` behind the newest timestamp seen, and
+//! at most `max_tracked_events` of them, so memory stays bounded; an
+//! identical event arriving after its twin left the window is kept.
+//!
+//! Like [`CoincidenceFilter`](super::CoincidenceFilter), the filter expects
+//! input sorted by `timestamp_ns` (the output of
+//! [`TimeSorter`](super::TimeSorter)).
+
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+
+use crate::common::EventData;
+
+/// Duplicate removal settings for the Merger
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DedupConfig {
+    /// How long an event is remembered, behind the newest timestamp (ns)
+    pub window_ns: f64,
+    /// Largest timestamp difference still treated as the same event (default: 0 = exact)
+    #[serde(default)]
+    pub tolerance_ns: f64,
+    /// Upper bound on remembered events (default: 100,000)
+    #[serde(default = "default_max_tracked_events")]
+    pub max_tracked_events: usize,
+}
+
+fn default_max_tracked_events() -> usize {
+    100_000
+}
+
+/// Streaming duplicate filter
+#[derive(Debug)]
+pub struct Deduplicator {
+    window_ns: f64,
+    tolerance_ns: f64,
+    max_tracked: usize,
+    /// (module, channel, timestamp_ns) in arrival order
+    recent: VecDeque<(u8, u8, f64)>,
+    newest_ns: f64,
+    dropped: u64,
+}
+
+impl Deduplicator {
+    /// Create a filter from its configuration
+    pub fn new(config: &DedupConfig) -> Self {
+        Self {
+            window_ns: config.window_ns.max(0.0),
+            tolerance_ns: config.tolerance_ns.max(0.0),
+            max_tracked: config.max_tracked_events.max(1),
+            recent: VecDeque::new(),
+            newest_ns: f64::NEG_INFINITY,
+            dropped: 0,
+        }
+    }
+
+    /// Feed time-ordered events; returns them with duplicates removed
+    pub fn process(&mut self, events: Vec<EventData>) -> Vec<EventData> {
+        let mut out = Vec::with_capacity(events.len());
+        for event in events {
+            if self.is_duplicate(&event) {
+                self.dropped += 1;
+                continue;
+            }
+            self.remember(&event);
+            out.push(event);
+        }
+        out
+    }
+
+    /// Forget all tracked events (end of run)
+    pub fn reset(&mut self) {
+        self.recent.clear();
+        self.newest_ns = f64::NEG_INFINITY;
+    }
+
+    /// Return and reset the number of duplicates dropped
+    pub fn take_dropped(&mut self) -> u64 {
+        std::mem::take(&mut self.dropped)
+    }
+
+    fn is_duplicate(&self, event: &EventData) -> bool {
+        // Sorted input: only the newest entries can be within tolerance
+        for &(module, channel, ts) in self.recent.iter().rev() {
+            if ts < event.timestamp_ns - self.tolerance_ns {
+                return false;
+            }
+            if module == event.module
+                && channel == event.channel
+                && (ts - event.timestamp_ns).abs() <= self.tolerance_ns
+            {
+                return true;
+            }
+        }
+        false
+    }
+
+    fn remember(&mut self, event: &EventData) {
+        self.newest_ns = self.newest_ns.max(event.timestamp_ns);
+        let oldest_kept = self.newest_ns - self.window_ns;
+        while let Some(&(_, _, ts)) = self.recent.front() {
+            if ts >= oldest_kept && self.recent.len() < self.max_tracked {
+                break;
+            }
+            self.recent.pop_front();
+        }
+        self.recent
+            .push_back((event.module, event.channel, event.timestamp_ns));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(module: u8, channel: u8, ts: f64) -> EventData {
+        EventData::new(module, channel, 100, 50, ts, 0)
+    }
+
+    fn dedup(window_ns: f64, tolerance_ns: f64) -> Deduplicator {
+        Deduplicator::new(&DedupConfig {
+            window_ns,
+            tolerance_ns,
+            max_tracked_events: 1000,
+        })
+    }
+
+    #[test]
+    fn test_duplicate_within_window_dropped() {
+        let mut dedup = dedup(100.0, 2.0);
+        let out = dedup.process(vec![
+            event(0, 1, 1000.0),
+            event(0, 1, 1000.0), // exact duplicate
+            event(0, 1, 1001.5), // within tolerance
+            event(0, 2, 1001.5), // other channel
+            event(0, 1, 1005.0), // outside tolerance
+        ]);
+
+        let kept: Vec<(u8, f64)> = out.iter().map(|e| (e.channel, e.timestamp_ns)).collect();
+        assert_eq!(kept, vec![(1, 1000.0), (2, 1001.5), (1, 1005.0)]);
+        assert_eq!(dedup.take_dropped(), 2);
+        assert_eq!(dedup.take_dropped(), 0);
+    }
+
+    #[test]
+    fn test_identical_event_outside_window_kept() {
+        let mut dedup = dedup(100.0, 0.0);
+        let out = dedup.process(vec![event(0, 1, 1000.0), event(0, 3, 1200.0)]);
+        assert_eq!(out.len(), 2);
+
+        // The first event has left the window, so its twin is kept
+        let out = dedup.process(vec![event(0, 1, 1000.0)]);
+        assert_eq!(out.len(), 1);
+        assert_eq!(dedup.take_dropped(), 0);
+    }
+
+    #[test]
+    fn test_tracked_events_are_bounded() {
+        let mut dedup = Deduplicator::new(&DedupConfig {
+            window_ns: f64::MAX,
+            tolerance_ns: 0.0,
+            max_tracked_events: 4,
+        });
+        let events: Vec<EventData> = (0..10).map(|i| event(0, 0, i as f64)).collect();
+        assert_eq!(dedup.process(events).len(), 10);
+        assert_eq!(dedup.recent.len(), 4);
+
+        dedup.reset();
+        assert!(dedup.recent.is_empty());
+    }
+}
@@ -1,30 +1,87 @@
 //! Merger - receives from multiple upstream sources and forwards downstream
 //!
 //! Architecture (Zero-Copy):
-//! - Receiver task: SUB socket → mpsc channel (raw bytes, header-only parsing)
+//! - Receiver task: SUB socket → bounded mpsc channel (raw bytes, header-only
+//!   parsing); a full channel is handled per [`BackpressurePolicy`]
 //! - Sender task: mpsc channel → PUB socket (direct byte forwarding)
 //! - Command task: REP socket for control commands
 //! - NO serialization/deserialization on the hot path
 //!
+//! With `merge_by_timestamp` enabled, a merge task sits between receiver and
+//! sender: it deserializes batches, re-orders events from all sources by
+//! `timestamp_ns` (see [`TimeSorter`]) and publishes merged batches under
+//! [`MERGED_SOURCE_ID`]. This trades the zero-copy path for a single
+//! time-ordered stream. An optional `dedup` setting drops events that two
+//! sources reported twice (see [`Deduplicator`]), and an optional
+//! `coincidence` condition then forwards only groups of events that fall
+//! within a time window (see [`CoincidenceFilter`]); both imply the merge
+//! stage.
+//!
+//! Liveness: every data message and heartbeat refreshes the source's
+//! last-seen time. While Running, a source silent for longer than
+//! `heartbeat_timeout_ms` is reported as stale in [`MergerStats`] and
+//! GetStatus until it is heard from again.
+//!
+//! Sending: a PUB socket drops messages at its high-water mark instead of
+//! failing, so a send only fails on a socket error. Such a message is not
+//! re-sent (tmq keeps the unsent frames buffered and flushes them before the
+//! next send); it is counted in [`MergerStats`] apart from channel drops.
+//!
 //! Performance: Uses AtomicU64 for hot-path counters to avoid mutex contention
 
+mod coincidence;
+mod dedup;
+mod sorter;
+
+pub use coincidence::{CoincidenceConfig, CoincidenceFilter};
+pub use dedup::{DedupConfig, Deduplicator};
+pub use sorter::{TimeSorter, ADAPTIVE_MARGIN_FACTOR};
+
 use std::collections::HashMap;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use bytes::Bytes;
 use dashmap::DashMap;
 use futures::{SinkExt, StreamExt};
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 use tmq::{publish, subscribe, AsZmqSocket, Context};
 use tokio::sync::{mpsc, watch};
 use tracing::{info, trace, warn};
 
 use crate::common::{
-    handle_command, run_command_task, CommandHandlerExt, ComponentSharedState, ComponentState,
-    MessageHeader,
+    handle_command, run_command_task, topic, CommandHandlerExt, ComponentSharedState,
+    ComponentState, EventData, EventDataBatch, Message, MessageHeader, SocketOptions,
 };
 
+/// Source ID used for batches produced by timestamp merging
+pub const MERGED_SOURCE_ID: u32 = u32::MAX;
+
+/// What the receiver does when the forwarding channel is full
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BackpressurePolicy {
+    /// Drop the batch and count it in `dropped_batches`
+    Drop,
+    /// Wait until downstream catches up (ZMQ buffers upstream in the meantime)
+    #[default]
+    Block,
+    /// Wait up to the given number of milliseconds, then drop
+    BlockWithTimeout(u64),
+}
+
+impl std::fmt::Display for BackpressurePolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BackpressurePolicy::Drop => write!(f, "Drop"),
+            BackpressurePolicy::Block => write!(f, "Block"),
+            BackpressurePolicy::BlockWithTimeout(ms) => write!(f, "BlockWithTimeout({}ms)", ms),
+        }
+    }
+}
+
 /// Merger configuration
 #[derive(Debug, Clone)]
 pub struct MergerConfig {
@@ -34,6 +91,34 @@ pub struct MergerConfig {
     pub pub_address: String,
     /// ZMQ bind address for commands (e.g., "tcp://*:5570")
     pub command_address: String,
+    /// Re-order events from all sources by timestamp instead of forwarding as-is
+    pub merge_by_timestamp: bool,
+    /// Late-arrival margin for timestamp merging (ns)
+    pub sort_margin_ns: f64,
+    /// Upper bound on events held in the sort buffer
+    pub max_buffered_events: usize,
+    /// Release sorted events after this long in the buffer (0 = horizon only)
+    pub max_residency_ms: u64,
+    /// Widen the sort margin to cover the observed skew between sources
+    pub adaptive_sort_margin: bool,
+    /// Drop duplicated events (enables timestamp merging)
+    pub dedup: Option<DedupConfig>,
+    /// Only forward coincident events (enables timestamp merging)
+    pub coincidence: Option<CoincidenceConfig>,
+    /// Capacity of the receiver → sender channel (in messages)
+    pub channel_capacity: usize,
+    /// Behavior when the channel is full
+    pub backpressure: BackpressurePolicy,
+    /// Upstream topic prefixes to receive (empty = everything)
+    pub subscribe_topics: Vec<String>,
+    /// Silence after which a source is reported stale (0 = no liveness check)
+    pub heartbeat_timeout_ms: u64,
+    /// Interval of the periodic stats log line (0 = only at shutdown)
+    pub stats_interval_secs: u64,
+    /// Topic frame sent before every published message (None = payload only)
+    pub topic_prefix: Option<String>,
+    /// ZMQ HWMs / linger of the SUB and PUB sockets (default: ZMQ's)
+    pub socket_options: SocketOptions,
 }
 
 impl Default for MergerConfig {
@@ -42,6 +127,20 @@ impl Default for MergerConfig {
             sub_addresses: vec!["tcp://localhost:5555".to_string()],
             pub_address: "tcp://*:5556".to_string(),
             command_address: "tcp://*:5570".to_string(),
+            merge_by_timestamp: false,
+            sort_margin_ns: 1_000_000.0,
+            max_buffered_events: 1_000_000,
+            max_residency_ms: 0,
+            adaptive_sort_margin: false,
+            dedup: None,
+            coincidence: None,
+            channel_capacity: 10_000,
+            backpressure: BackpressurePolicy::Block,
+            subscribe_topics: Vec::new(),
+            heartbeat_timeout_ms: 5000,
+            stats_interval_secs: 10,
+            topic_prefix: None,
+            socket_options: SocketOptions::default(),
         }
     }
 }
@@ -71,17 +170,26 @@ pub enum MergerError {
 /// Per-source statistics with sequence tracking
 #[derive(Debug, Default, Clone)]
 pub struct SourceStats {
+    /// Sequence of the last data batch (heartbeats never set it, so the
+    /// first batch after a heartbeat-only period is the baseline, not a gap)
     pub last_sequence: Option<u64>,
+    /// Epoch of the last batch; a new epoch means the source restarted
+    pub last_epoch: u32,
     pub total_batches: u64,
     pub restart_count: u32,
     pub gaps_detected: u64,
     pub total_gap_size: u64,
+    /// Last data or heartbeat from this source (None after its EOS)
+    pub last_seen: Option<Instant>,
+    /// Nothing received for longer than the heartbeat timeout
+    pub stale: bool,
 }
 
 impl SourceStats {
-    fn update(&mut self, seq: u64) -> bool {
+    fn update(&mut self, seq: u64, epoch: u32) -> bool {
         let restarted = if let Some(last) = self.last_sequence {
-            if seq < last.saturating_sub(100) {
+            // New epoch, or a sequence drop from a source that sends no epoch
+            if epoch != self.last_epoch || seq < last.saturating_sub(100) {
                 self.restart_count += 1;
                 true
             } else {
@@ -98,6 +206,7 @@ impl SourceStats {
         };
 
         self.last_sequence = Some(seq);
+        self.last_epoch = epoch;
         self.total_batches += 1;
         restarted
     }
@@ -107,8 +216,16 @@ impl SourceStats {
 struct AtomicStats {
     received_batches: AtomicU64,
     sent_batches: AtomicU64,
+    sent_bytes: AtomicU64,
     dropped_batches: AtomicU64,
     eos_received: AtomicU64,
+    coincidences_found: AtomicU64,
+    singles_dropped: AtomicU64,
+    late_events: AtomicU64,
+    duplicates_dropped: AtomicU64,
+    send_failures: AtomicU64,
+    /// Effective sort margin in ns (f64 bits)
+    sort_margin_ns: AtomicU64,
 }
 
 impl AtomicStats {
@@ -116,8 +233,15 @@ impl AtomicStats {
         Self {
             received_batches: AtomicU64::new(0),
             sent_batches: AtomicU64::new(0),
+            sent_bytes: AtomicU64::new(0),
             dropped_batches: AtomicU64::new(0),
             eos_received: AtomicU64::new(0),
+            coincidences_found: AtomicU64::new(0),
+            singles_dropped: AtomicU64::new(0),
+            late_events: AtomicU64::new(0),
+            duplicates_dropped: AtomicU64::new(0),
+            send_failures: AtomicU64::new(0),
+            sort_margin_ns: AtomicU64::new(0),
         }
     }
 
@@ -127,12 +251,12 @@ impl AtomicStats {
     }
 
     #[inline]
-    fn record_sent(&self) {
+    fn record_sent(&self, bytes: usize) {
         self.sent_batches.fetch_add(1, Ordering::Relaxed);
+        self.sent_bytes.fetch_add(bytes as u64, Ordering::Relaxed);
     }
 
     #[inline]
-    #[allow(dead_code)] // Reserved for future bounded channel debugging
     fn record_drop(&self) {
         self.dropped_batches.fetch_add(1, Ordering::Relaxed);
     }
@@ -142,6 +266,38 @@ impl AtomicStats {
         self.eos_received.fetch_add(1, Ordering::Relaxed);
     }
 
+    #[inline]
+    fn record_coincidence(&self, coincidences: u64, singles: u64) {
+        self.coincidences_found
+            .fetch_add(coincidences, Ordering::Relaxed);
+        self.singles_dropped.fetch_add(singles, Ordering::Relaxed);
+    }
+
+    #[inline]
+    fn record_late(&self, events: u64) {
+        self.late_events.fetch_add(events, Ordering::Relaxed);
+    }
+
+    #[inline]
+    fn record_duplicates(&self, events: u64) {
+        self.duplicates_dropped.fetch_add(events, Ordering::Relaxed);
+    }
+
+    #[inline]
+    fn record_send_failure(&self) {
+        self.send_failures.fetch_add(1, Ordering::Relaxed);
+    }
+
+    #[inline]
+    fn set_sort_margin(&self, margin_ns: f64) {
+        self.sort_margin_ns
+            .store(margin_ns.to_bits(), Ordering::Relaxed);
+    }
+
+    fn sort_margin(&self) -> f64 {
+        f64::from_bits(self.sort_margin_ns.load(Ordering::Relaxed))
+    }
+
     fn snapshot(&self) -> (u64, u64, u64, u64) {
         (
             self.received_batches.load(Ordering::Relaxed),
@@ -154,8 +310,14 @@ impl AtomicStats {
     fn clear(&self) {
         self.received_batches.store(0, Ordering::Relaxed);
         self.sent_batches.store(0, Ordering::Relaxed);
+        self.sent_bytes.store(0, Ordering::Relaxed);
         self.dropped_batches.store(0, Ordering::Relaxed);
         self.eos_received.store(0, Ordering::Relaxed);
+        self.coincidences_found.store(0, Ordering::Relaxed);
+        self.singles_dropped.store(0, Ordering::Relaxed);
+        self.late_events.store(0, Ordering::Relaxed);
+        self.duplicates_dropped.store(0, Ordering::Relaxed);
+        self.send_failures.store(0, Ordering::Relaxed);
     }
 }
 
@@ -164,8 +326,22 @@ impl AtomicStats {
 pub struct MergerStats {
     pub received_batches: u64,
     pub sent_batches: u64,
+    /// Payload bytes published downstream
+    pub sent_bytes: u64,
     pub dropped_batches: u64,
     pub eos_received: u64,
+    /// Coincidence groups forwarded (coincidence mode only)
+    pub coincidences_found: u64,
+    /// Events dropped for lack of a coincidence partner
+    pub singles_dropped: u64,
+    /// Events that arrived older than the already sorted output (merge mode only)
+    pub late_events: u64,
+    /// Events dropped as duplicates (dedup mode only)
+    pub duplicates_dropped: u64,
+    /// Messages lost to a failed send on the PUB socket
+    pub send_failures: u64,
+    /// Sort margin in use (ns, 0 without timestamp merging)
+    pub sort_margin_ns: f64,
     pub sources: HashMap<u32, SourceStats>,
 }
 
@@ -179,14 +355,60 @@ impl MergerStats {
     pub fn total_missing(&self) -> u64 {
         self.sources.values().map(|s| s.total_gap_size).sum()
     }
+
+    /// Source IDs currently flagged as stale, ascending
+    pub fn stale_sources(&self) -> Vec<u32> {
+        let mut stale: Vec<u32> = self
+            .sources
+            .iter()
+            .filter(|(_, s)| s.stale)
+            .map(|(&id, _)| id)
+            .collect();
+        stale.sort_unstable();
+        stale
+    }
+}
+
+/// Sent counters at one point in time
+#[derive(Debug, Clone, Copy)]
+struct RateSnapshot {
+    at: Instant,
+    batches: u64,
+    bytes: u64,
 }
 
+impl RateSnapshot {
+    /// Batch and byte rates (per second) from `earlier` to this snapshot
+    fn rates_since(&self, earlier: &RateSnapshot) -> (f64, f64) {
+        let secs = self.at.saturating_duration_since(earlier.at).as_secs_f64();
+        if secs <= 0.0 {
+            return (0.0, 0.0);
+        }
+        (
+            self.batches.saturating_sub(earlier.batches) as f64 / secs,
+            self.bytes.saturating_sub(earlier.bytes) as f64 / secs,
+        )
+    }
+}
+
+/// Rates over the last completed interval
+#[derive(Debug, Default)]
+struct RateState {
+    last: Option<RateSnapshot>,
+    rates: (f64, f64),
+}
+
+/// Shortest interval over which rates are computed
+const RATE_INTERVAL: Duration = Duration::from_secs(1);
+
 /// Extended state for Merger (statistics and sequence tracking)
 struct MergerExtState {
     // Sequence tracking per source (lock-free concurrent map)
     source_stats: DashMap<u32, SourceStats>,
     // Hot-path counters (lock-free)
     atomic_stats: AtomicStats,
+    // Sent batch / byte rates, updated by GetStatus and the stats task
+    rates: std::sync::Mutex<RateState>,
 }
 
 impl MergerExtState {
@@ -194,7 +416,27 @@ impl MergerExtState {
         Self {
             source_stats: DashMap::new(),
             atomic_stats: AtomicStats::new(),
+            rates: std::sync::Mutex::new(RateState::default()),
+        }
+    }
+
+    /// Sent batches/s and bytes/s, recomputed once per [`RATE_INTERVAL`]
+    fn rates_at(&self, now: Instant) -> (f64, f64) {
+        let current = RateSnapshot {
+            at: now,
+            batches: self.atomic_stats.sent_batches.load(Ordering::Relaxed),
+            bytes: self.atomic_stats.sent_bytes.load(Ordering::Relaxed),
+        };
+        let mut state = self.rates.lock().unwrap();
+        match state.last {
+            Some(last) if now.saturating_duration_since(last.at) < RATE_INTERVAL => {}
+            Some(last) => {
+                state.rates = current.rates_since(&last);
+                state.last = Some(current);
+            }
+            None => state.last = Some(current),
         }
+        state.rates
     }
 
     fn get_stats(&self) -> MergerStats {
@@ -208,20 +450,98 @@ impl MergerExtState {
         MergerStats {
             received_batches: received,
             sent_batches: sent,
+            sent_bytes: self.atomic_stats.sent_bytes.load(Ordering::Relaxed),
             dropped_batches: dropped,
             eos_received: eos,
+            coincidences_found: self.atomic_stats.coincidences_found.load(Ordering::Relaxed),
+            singles_dropped: self.atomic_stats.singles_dropped.load(Ordering::Relaxed),
+            late_events: self.atomic_stats.late_events.load(Ordering::Relaxed),
+            duplicates_dropped: self.atomic_stats.duplicates_dropped.load(Ordering::Relaxed),
+            send_failures: self.atomic_stats.send_failures.load(Ordering::Relaxed),
+            sort_margin_ns: self.atomic_stats.sort_margin(),
             sources,
         }
     }
 
     fn clear(&self) {
         self.source_stats.clear();
+        *self.rates.lock().unwrap() = RateState::default();
+    }
+
+    /// Update counters, sequence tracking and liveness for a received message
+    ///
+    /// Only data batches take part in sequence tracking; heartbeats just
+    /// mark the source as alive.
+    fn record_header(&self, header: MessageHeader) {
+        match header {
+            MessageHeader::Data {
+                source_id,
+                sequence_number,
+                epoch,
+            } => {
+                self.atomic_stats.record_received();
+                self.source_stats
+                    .entry(source_id)
+                    .or_default()
+                    .update(sequence_number, epoch);
+                self.record_seen(source_id);
+                trace!(source = source_id, seq = sequence_number, "Received data");
+            }
+            MessageHeader::EndOfStream { source_id } => {
+                self.atomic_stats.record_eos();
+                self.record_finished(source_id);
+                info!(source = source_id, "Received EOS");
+            }
+            MessageHeader::Heartbeat { source_id } => {
+                self.record_seen(source_id);
+                trace!(source = source_id, "Received heartbeat");
+            }
+        }
+    }
+
+    /// Note that a source is alive (data or heartbeat received)
+    fn record_seen(&self, source_id: u32) {
+        self.record_seen_at(source_id, Instant::now());
+    }
+
+    fn record_seen_at(&self, source_id: u32, now: Instant) {
+        let mut entry = self.source_stats.entry(source_id).or_default();
+        entry.last_seen = Some(now);
+        if entry.stale {
+            entry.stale = false;
+            info!(source = source_id, "Source alive again");
+        }
+    }
+
+    /// A source that sent EOS is expected to go quiet
+    fn record_finished(&self, source_id: u32) {
+        let mut entry = self.source_stats.entry(source_id).or_default();
+        entry.last_seen = None;
+        entry.stale = false;
+    }
+
+    /// Flag sources not heard from within `timeout` as stale
+    fn check_liveness_at(&self, now: Instant, timeout: Duration) {
+        for mut entry in self.source_stats.iter_mut() {
+            let Some(last_seen) = entry.last_seen else {
+                continue;
+            };
+            if !entry.stale && now.saturating_duration_since(last_seen) > timeout {
+                entry.stale = true;
+                warn!(
+                    source = *entry.key(),
+                    timeout_ms = timeout.as_millis() as u64,
+                    "No data or heartbeat from source - marked stale"
+                );
+            }
+        }
     }
 }
 
 /// Command handler extension for Merger with custom GetStatus
 struct MergerCommandExt {
     ext_state: Arc<MergerExtState>,
+    backpressure: BackpressurePolicy,
 }
 
 impl CommandHandlerExt for MergerCommandExt {
@@ -243,26 +563,45 @@ impl CommandHandlerExt for MergerCommandExt {
 
     fn status_details(&self) -> Option<String> {
         let stats = self.ext_state.get_stats();
+        let stale = stats.stale_sources();
+        let stale = if stale.is_empty() {
+            String::new()
+        } else {
+            format!(", Stale: {:?}", stale)
+        };
+        let margin = if stats.sort_margin_ns > 0.0 {
+            format!(", Sort margin: {:.0} ns", stats.sort_margin_ns)
+        } else {
+            String::new()
+        };
         Some(format!(
-            "Received: {}, Sent: {}, Dropped: {}, Gaps: {}, Missing: {}",
+            "Received: {}, Sent: {}, Dropped: {}, Send failures: {}, Gaps: {}, Missing: {}, Late: {}, Duplicates: {}, Backpressure: {}{}{}",
             stats.received_batches,
             stats.sent_batches,
             stats.dropped_batches,
+            stats.send_failures,
             stats.total_gaps(),
-            stats.total_missing()
+            stats.total_missing(),
+            stats.late_events,
+            stats.duplicates_dropped,
+            self.backpressure,
+            margin,
+            stale
         ))
     }
 
     fn get_metrics(&self) -> Option<crate::common::ComponentMetrics> {
         let stats = self.ext_state.get_stats();
+        let (batch_rate, byte_rate) = self.ext_state.rates_at(Instant::now());
         Some(crate::common::ComponentMetrics {
-            // Merger forwards batches, so we report batch counts
+            // Merger forwards batches, so we report batch counts and rates
             events_processed: stats.sent_batches,
-            bytes_transferred: 0, // Merger doesn't track bytes
+            bytes_transferred: stats.sent_bytes,
             queue_size: 0,
             queue_max: 0,
-            event_rate: 0.0, // Will be calculated in Phase 2
-            data_rate: 0.0,
+            event_rate: batch_rate,
+            data_rate: byte_rate,
+            ..Default::default()
         })
     }
 }
@@ -299,8 +638,9 @@ impl Merger {
         &mut self,
         mut shutdown: tokio::sync::broadcast::Receiver<()>,
     ) -> Result<(), MergerError> {
-        // Use unbounded channel - if memory grows, it indicates downstream bottleneck
-        let (tx, rx) = mpsc::unbounded_channel::<Bytes>();
+        // Bounded channel: a full channel is handled per the backpressure policy
+        let capacity = self.config.channel_capacity.max(1);
+        let (tx, rx) = mpsc::channel::<Bytes>(capacity);
 
         let context = Context::new();
 
@@ -310,8 +650,12 @@ impl Merger {
             .first()
             .ok_or(MergerError::NoUpstreamAddresses)?;
 
-        let sub_socket = subscribe(&context).connect(first_addr)?.subscribe(b"")?;
+        let sub_socket = topic::subscribe_topics(
+            subscribe(&context).connect(first_addr)?,
+            &self.config.subscribe_topics,
+        )?;
 
+        self.config.socket_options.apply(sub_socket.get_socket())?;
         info!(address = %first_addr, "Merger subscribed to upstream");
 
         for addr in self.config.sub_addresses.iter().skip(1) {
@@ -320,6 +664,7 @@ impl Merger {
         }
 
         let pub_socket = publish(&context).bind(&self.config.pub_address)?;
+        self.config.socket_options.apply(pub_socket.get_socket())?;
         info!(address = %self.config.pub_address, "Merger publishing to downstream");
 
         info!(state = %self.state(), "Merger ready, waiting for commands");
@@ -330,6 +675,7 @@ impl Merger {
         let state_tx = self.state_tx.clone();
         let shutdown_for_cmd = shutdown.resubscribe();
         let ext_state_for_cmd = self.ext_state.clone();
+        let backpressure = self.config.backpressure;
 
         let cmd_handle = tokio::spawn(async move {
             run_command_task(
@@ -340,6 +686,7 @@ impl Merger {
                 move |state, tx, cmd| {
                     let mut ext = MergerCommandExt {
                         ext_state: ext_state_for_cmd.clone(),
+                        backpressure,
                     };
                     handle_command(state, tx, cmd, Some(&mut ext))
                 },
@@ -356,6 +703,7 @@ impl Merger {
             Self::receiver_task(
                 sub_socket,
                 tx,
+                backpressure,
                 shutdown_rx,
                 ext_state_for_recv,
                 state_rx_for_recv,
@@ -363,12 +711,91 @@ impl Merger {
             .await
         });
 
+        // Liveness check (flags sources that went silent)
+        let liveness_handle = (self.config.heartbeat_timeout_ms > 0).then(|| {
+            tokio::spawn(Self::liveness_task(
+                Duration::from_millis(self.config.heartbeat_timeout_ms),
+                self.ext_state.clone(),
+                self.state_rx.clone(),
+                shutdown.resubscribe(),
+            ))
+        });
+
+        // Optional merge stage between receiver and sender
+        let merge = self.config.merge_by_timestamp
+            || self.config.dedup.is_some()
+            || self.config.coincidence.is_some();
+        let (rx, merge_handle) = if merge {
+            let (merged_tx, merged_rx) = mpsc::channel::<Bytes>(capacity);
+            let mut sorter = TimeSorter::new(
+                self.config.sort_margin_ns,
+                self.config.max_buffered_events,
+                self.config.sub_addresses.len(),
+            );
+            if self.config.max_residency_ms > 0 {
+                sorter =
+                    sorter.with_max_residency(Duration::from_millis(self.config.max_residency_ms));
+            }
+            if self.config.adaptive_sort_margin {
+                sorter = sorter.with_adaptive_margin();
+            }
+            self.ext_state
+                .atomic_stats
+                .set_sort_margin(sorter.margin_ns());
+            info!(
+                margin_ns = self.config.sort_margin_ns,
+                max_buffered = self.config.max_buffered_events,
+                max_residency_ms = self.config.max_residency_ms,
+                adaptive_margin = self.config.adaptive_sort_margin,
+                "Timestamp merging enabled"
+            );
+            let dedup = self.config.dedup.as_ref().map(|d| {
+                info!(
+                    window_ns = d.window_ns,
+                    tolerance_ns = d.tolerance_ns,
+                    max_tracked = d.max_tracked_events,
+                    "Duplicate removal enabled"
+                );
+                Deduplicator::new(d)
+            });
+            let filter = self.config.coincidence.as_ref().map(|c| {
+                info!(
+                    window_ns = c.window_ns,
+                    channels = c.channels.len(),
+                    multiplicity = c.multiplicity,
+                    "Coincidence filtering enabled"
+                );
+                CoincidenceFilter::new(c)
+            });
+            let handle = tokio::spawn(Self::merge_task(
+                rx,
+                merged_tx,
+                sorter,
+                dedup,
+                filter,
+                self.ext_state.clone(),
+            ));
+            (merged_rx, Some(handle))
+        } else {
+            (rx, None)
+        };
+
+        // Periodic stats log
+        let stats_handle = (self.config.stats_interval_secs > 0).then(|| {
+            tokio::spawn(Self::stats_task(
+                Duration::from_secs(self.config.stats_interval_secs),
+                self.ext_state.clone(),
+                self.state_rx.clone(),
+                shutdown.resubscribe(),
+            ))
+        });
+
         // Spawn sender task (zero-copy: forwards raw bytes)
         let ext_state_for_send = self.ext_state.clone();
-        let sender_handle =
-            tokio::spawn(
-                async move { Self::sender_task(rx, pub_socket, ext_state_for_send).await },
-            );
+        let topic_prefix = self.config.topic_prefix.clone();
+        let sender_handle = tokio::spawn(async move {
+            Self::sender_task(rx, pub_socket, topic_prefix, ext_state_for_send).await
+        });
 
         // Wait for shutdown signal
         let _ = shutdown.recv().await;
@@ -376,6 +803,15 @@ impl Merger {
 
         // Wait for tasks to complete
         let _ = receiver_handle.await;
+        if let Some(handle) = liveness_handle {
+            let _ = handle.await;
+        }
+        if let Some(handle) = stats_handle {
+            let _ = handle.await;
+        }
+        if let Some(handle) = merge_handle {
+            let _ = handle.await;
+        }
         let _ = sender_handle.await;
         let _ = cmd_handle.await;
 
@@ -388,6 +824,9 @@ impl Merger {
             eos = stats.eos_received,
             gaps = stats.total_gaps(),
             missing = stats.total_missing(),
+            coincidences = stats.coincidences_found,
+            singles_dropped = stats.singles_dropped,
+            duplicates = stats.duplicates_dropped,
             "Merger stopped"
         );
 
@@ -400,13 +839,15 @@ impl Merger {
     /// When not Running, data is discarded immediately.
     async fn receiver_task(
         mut socket: subscribe::Subscribe,
-        tx: mpsc::UnboundedSender<Bytes>,
+        tx: mpsc::Sender<Bytes>,
+        backpressure: BackpressurePolicy,
         mut shutdown: tokio::sync::broadcast::Receiver<()>,
         ext_state: Arc<MergerExtState>,
         mut state_rx: watch::Receiver<ComponentState>,
     ) {
         loop {
-            let is_running = *state_rx.borrow() == ComponentState::Running;
+            // Paused keeps in-flight data of the current run
+            let is_running = state_rx.borrow().in_run();
 
             tokio::select! {
                 biased;
@@ -432,36 +873,18 @@ impl Merger {
                                 continue;
                             }
 
-                            if let Some(data) = multipart.into_iter().next() {
+                            if let Some(data) = topic::payload(multipart) {
                                 // Zero-copy: convert to Bytes (reference counted)
                                 let raw_bytes: Bytes = Bytes::copy_from_slice(&data);
 
                                 // Lightweight header parsing (no full deserialization)
-                                match MessageHeader::parse(&raw_bytes) {
-                                    Some(MessageHeader::Data { source_id, sequence_number }) => {
-                                        ext_state.atomic_stats.record_received();
-                                        // Update per-source sequence tracking
-                                        ext_state.source_stats
-                                            .entry(source_id)
-                                            .or_default()
-                                            .update(sequence_number);
-                                        trace!(source = source_id, seq = sequence_number, "Received data");
-                                    }
-                                    Some(MessageHeader::EndOfStream { source_id }) => {
-                                        ext_state.atomic_stats.record_eos();
-                                        info!(source = source_id, "Received EOS");
-                                    }
-                                    Some(MessageHeader::Heartbeat { source_id }) => {
-                                        trace!(source = source_id, "Received heartbeat");
-                                    }
-                                    None => {
-                                        warn!("Failed to parse message header");
-                                        continue;
-                                    }
-                                }
+                                let Some(header) = MessageHeader::parse(&raw_bytes) else {
+                                    warn!("Failed to parse message header");
+                                    continue;
+                                };
+                                ext_state.record_header(header);
 
-                                // Send raw bytes (unbounded channel never blocks)
-                                if tx.send(raw_bytes).is_err() {
+                                if !Self::forward(&tx, raw_bytes, backpressure, &ext_state).await {
                                     info!("Channel closed, receiver exiting");
                                     break;
                                 }
@@ -481,22 +904,274 @@ impl Merger {
         }
     }
 
+    /// Liveness task: periodically flag silent sources as stale (Running only)
+    async fn liveness_task(
+        timeout: Duration,
+        ext_state: Arc<MergerExtState>,
+        state_rx: watch::Receiver<ComponentState>,
+        mut shutdown: tokio::sync::broadcast::Receiver<()>,
+    ) {
+        let mut ticker = tokio::time::interval((timeout / 4).max(Duration::from_millis(100)));
+        loop {
+            tokio::select! {
+                _ = shutdown.recv() => break,
+                _ = ticker.tick() => {
+                    // Nothing is received outside Running, so silence is expected there
+                    if *state_rx.borrow() == ComponentState::Running {
+                        ext_state.check_liveness_at(Instant::now(), timeout);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Stats task: log counters and rates every `interval` during a run
+    async fn stats_task(
+        interval: Duration,
+        ext_state: Arc<MergerExtState>,
+        state_rx: watch::Receiver<ComponentState>,
+        mut shutdown: tokio::sync::broadcast::Receiver<()>,
+    ) {
+        let mut ticker = tokio::time::interval(interval);
+        // The first tick fires immediately
+        ticker.tick().await;
+        loop {
+            tokio::select! {
+                _ = shutdown.recv() => break,
+                _ = ticker.tick() => {
+                    if !state_rx.borrow().in_run() {
+                        continue;
+                    }
+                    let stats = ext_state.get_stats();
+                    let (batch_rate, byte_rate) = ext_state.rates_at(Instant::now());
+                    info!(
+                        received = stats.received_batches,
+                        sent = stats.sent_batches,
+                        dropped = stats.dropped_batches,
+                        gaps = stats.total_gaps(),
+                        missing = stats.total_missing(),
+                        late = stats.late_events,
+                        batch_rate,
+                        byte_rate,
+                        "Merger stats"
+                    );
+                }
+            }
+        }
+    }
+
+    /// Hand a message to the forwarding channel according to the policy
+    ///
+    /// Returns false if the channel is closed.
+    async fn forward(
+        tx: &mpsc::Sender<Bytes>,
+        raw_bytes: Bytes,
+        policy: BackpressurePolicy,
+        ext_state: &MergerExtState,
+    ) -> bool {
+        let result = match policy {
+            BackpressurePolicy::Drop => match tx.try_send(raw_bytes) {
+                Err(mpsc::error::TrySendError::Full(_)) => Err(true),
+                Err(mpsc::error::TrySendError::Closed(_)) => Err(false),
+                Ok(()) => Ok(()),
+            },
+            BackpressurePolicy::Block => tx.send(raw_bytes).await.map_err(|_| false),
+            BackpressurePolicy::BlockWithTimeout(ms) => {
+                match tx.send_timeout(raw_bytes, Duration::from_millis(ms)).await {
+                    Err(mpsc::error::SendTimeoutError::Timeout(_)) => Err(true),
+                    Err(mpsc::error::SendTimeoutError::Closed(_)) => Err(false),
+                    Ok(()) => Ok(()),
+                }
+            }
+        };
+
+        match result {
+            Ok(()) => true,
+            Err(full) => {
+                if full {
+                    ext_state.atomic_stats.record_drop();
+                    trace!(policy = %policy, "Channel full, batch dropped");
+                }
+                full
+            }
+        }
+    }
+
+    /// Merge task: channel → TimeSorter → (Deduplicator) → (CoincidenceFilter) → channel
+    ///
+    /// EOS messages are held back until every source has finished, so that
+    /// downstream only sees them after the last merged batch. With a maximum
+    /// residency, expired events are flushed on a timer even without input.
+    async fn merge_task(
+        mut rx: mpsc::Receiver<Bytes>,
+        tx: mpsc::Sender<Bytes>,
+        mut sorter: TimeSorter,
+        mut dedup: Option<Deduplicator>,
+        mut filter: Option<CoincidenceFilter>,
+        ext_state: Arc<MergerExtState>,
+    ) {
+        let mut sequence = 0u64;
+        let mut held_eos: Vec<Bytes> = Vec::new();
+        let residency = sorter.max_residency();
+        let mut flush_ticker = tokio::time::interval(
+            residency
+                .map(|r| (r / 4).max(Duration::from_millis(10)))
+                .unwrap_or(Duration::from_secs(3600)),
+        );
+
+        loop {
+            let raw_bytes = tokio::select! {
+                msg = rx.recv() => match msg {
+                    Some(raw_bytes) => raw_bytes,
+                    None => break,
+                },
+                _ = flush_ticker.tick(), if residency.is_some() => {
+                    let expired = sorter.flush_expired(Instant::now());
+                    let expired = Self::apply_dedup(&mut dedup, expired, &ext_state);
+                    let expired = Self::apply_filter(&mut filter, expired, false, &ext_state);
+                    if !Self::emit_merged(&tx, expired, &mut sequence).await {
+                        return;
+                    }
+                    continue;
+                }
+            };
+            let ready = match Message::from_msgpack(&raw_bytes) {
+                Ok(Message::Data(batch)) => {
+                    let late_before = sorter.late_events();
+                    let ready = sorter.push(batch);
+                    ext_state
+                        .atomic_stats
+                        .record_late(sorter.late_events() - late_before);
+                    ext_state.atomic_stats.set_sort_margin(sorter.margin_ns());
+                    ready
+                }
+                Ok(Message::EndOfStream { source_id }) => {
+                    held_eos.push(raw_bytes);
+                    let (ready, all_done) = sorter.end_of_stream(source_id);
+                    if all_done {
+                        let ready = Self::apply_dedup(&mut dedup, ready, &ext_state);
+                        if let Some(ref mut dedup) = dedup {
+                            dedup.reset();
+                        }
+                        let ready = Self::apply_filter(&mut filter, ready, true, &ext_state);
+                        if !Self::emit_merged(&tx, ready, &mut sequence).await {
+                            return;
+                        }
+                        for eos in held_eos.drain(..) {
+                            if tx.send(eos).await.is_err() {
+                                return;
+                            }
+                        }
+                        sequence = 0;
+                        continue;
+                    }
+                    ready
+                }
+                Ok(Message::Heartbeat(_)) => {
+                    if tx.send(raw_bytes).await.is_err() {
+                        return;
+                    }
+                    continue;
+                }
+                Err(e) => {
+                    warn!(error = %e, "Failed to deserialize message for merging");
+                    continue;
+                }
+            };
+
+            let ready = Self::apply_dedup(&mut dedup, ready, &ext_state);
+            let ready = Self::apply_filter(&mut filter, ready, false, &ext_state);
+            if !Self::emit_merged(&tx, ready, &mut sequence).await {
+                return;
+            }
+        }
+
+        // Upstream closed: flush whatever is left
+        let remaining = Self::apply_dedup(&mut dedup, sorter.drain_all(), &ext_state);
+        let remaining = Self::apply_filter(&mut filter, remaining, true, &ext_state);
+        Self::emit_merged(&tx, remaining, &mut sequence).await;
+        for eos in held_eos {
+            let _ = tx.send(eos).await;
+        }
+        info!("Merge task completed");
+    }
+
+    /// Remove duplicated events from the sorted stream, if configured
+    fn apply_dedup(
+        dedup: &mut Option<Deduplicator>,
+        events: Vec<EventData>,
+        ext_state: &MergerExtState,
+    ) -> Vec<EventData> {
+        let Some(dedup) = dedup else {
+            return events;
+        };
+        let kept = dedup.process(events);
+        ext_state
+            .atomic_stats
+            .record_duplicates(dedup.take_dropped());
+        kept
+    }
+
+    /// Pass sorted events through the coincidence filter, if configured
+    fn apply_filter(
+        filter: &mut Option<CoincidenceFilter>,
+        events: Vec<EventData>,
+        end_of_run: bool,
+        ext_state: &MergerExtState,
+    ) -> Vec<EventData> {
+        let Some(filter) = filter else {
+            return events;
+        };
+        let mut matched = filter.process(events);
+        if end_of_run {
+            matched.extend(filter.flush());
+        }
+        let (coincidences, singles) = filter.take_counters();
+        ext_state
+            .atomic_stats
+            .record_coincidence(coincidences, singles);
+        matched
+    }
+
+    /// Serialize merged events as one batch; returns false if the channel is closed
+    async fn emit_merged(
+        tx: &mpsc::Sender<Bytes>,
+        events: Vec<EventData>,
+        sequence: &mut u64,
+    ) -> bool {
+        if events.is_empty() {
+            return true;
+        }
+        let mut batch = EventDataBatch::with_capacity(MERGED_SOURCE_ID, *sequence, events.len());
+        batch.events = events;
+        *sequence += 1;
+
+        match Message::Data(batch).to_msgpack() {
+            Ok(bytes) => tx.send(Bytes::from(bytes)).await.is_ok(),
+            Err(e) => {
+                warn!(error = %e, "Failed to serialize merged batch");
+                true
+            }
+        }
+    }
+
     /// Sender task: channel → PUB (zero-copy: direct byte forwarding)
     async fn sender_task(
-        mut rx: mpsc::UnboundedReceiver<Bytes>,
+        mut rx: mpsc::Receiver<Bytes>,
         mut socket: publish::Publish,
+        topic_prefix: Option<String>,
         ext_state: Arc<MergerExtState>,
     ) {
         while let Some(raw_bytes) = rx.recv().await {
             // Zero-copy: directly send raw bytes to ZMQ
-            let bytes_slice: &[u8] = raw_bytes.as_ref();
-            let msg: tmq::Multipart = vec![tmq::Message::from(bytes_slice)].into();
+            let msg = topic::data_message(topic_prefix.as_deref(), raw_bytes.as_ref());
             match socket.send(msg).await {
                 Ok(()) => {
-                    ext_state.atomic_stats.record_sent();
+                    ext_state.atomic_stats.record_sent(raw_bytes.len());
                     trace!("Sender forwarded message");
                 }
                 Err(e) => {
+                    ext_state.atomic_stats.record_send_failure();
                     warn!(error = %e, "Failed to send message");
                 }
             }
@@ -528,6 +1203,20 @@ mod tests {
             sub_addresses: vec!["tcp://localhost:6000".to_string()],
             pub_address: "tcp://*:6001".to_string(),
             command_address: "tcp://*:6002".to_string(),
+            merge_by_timestamp: true,
+            sort_margin_ns: 500.0,
+            max_buffered_events: 1000,
+            max_residency_ms: 500,
+            adaptive_sort_margin: true,
+            dedup: None,
+            coincidence: None,
+            channel_capacity: 100,
+            backpressure: BackpressurePolicy::Block,
+            subscribe_topics: vec!["src0".to_string()],
+            heartbeat_timeout_ms: 2000,
+            stats_interval_secs: 5,
+            topic_prefix: Some("merged".to_string()),
+            socket_options: SocketOptions::default(),
         };
         assert_eq!(config.sub_addresses.len(), 1);
     }
@@ -536,28 +1225,38 @@ mod tests {
     fn source_stats_update() {
         let mut stats = SourceStats::default();
 
-        assert!(!stats.update(0));
+        assert!(!stats.update(0, 0));
         assert_eq!(stats.total_batches, 1);
 
-        assert!(!stats.update(1));
+        assert!(!stats.update(1, 0));
         assert_eq!(stats.total_batches, 2);
 
         // Gap detection
-        assert!(!stats.update(200));
+        assert!(!stats.update(200, 0));
         assert_eq!(stats.total_batches, 3);
         assert_eq!(stats.gaps_detected, 1);
         assert_eq!(stats.total_gap_size, 198);
 
-        // Restart detection
-        assert!(stats.update(0));
+        // Restart detection: new epoch, even with a nearby sequence number
+        assert!(stats.update(0, 1));
         assert_eq!(stats.restart_count, 1);
+        assert_eq!(stats.gaps_detected, 1);
+
+        // A lower sequence within the same epoch is not a restart
+        assert!(!stats.update(0, 1));
+        assert_eq!(stats.restart_count, 1);
+
+        // ...unless it dropped significantly (sources that send no epoch)
+        assert!(!stats.update(150, 1));
+        assert!(stats.update(3, 1));
+        assert_eq!(stats.restart_count, 2);
     }
 
     #[test]
     fn source_stats_no_gap_sequential() {
         let mut stats = SourceStats::default();
         for i in 0..100 {
-            stats.update(i);
+            stats.update(i, 0);
         }
         assert_eq!(stats.total_batches, 100);
         assert_eq!(stats.gaps_detected, 0);
@@ -568,20 +1267,54 @@ mod tests {
     #[test]
     fn source_stats_multiple_gaps() {
         let mut stats = SourceStats::default();
-        stats.update(0);
-        stats.update(5); // gap of 4
-        stats.update(10); // gap of 4
-        stats.update(100); // gap of 89
+        stats.update(0, 0);
+        stats.update(5, 0); // gap of 4
+        stats.update(10, 0); // gap of 4
+        stats.update(100, 0); // gap of 89
         assert_eq!(stats.gaps_detected, 3);
         assert_eq!(stats.total_gap_size, 4 + 4 + 89);
     }
 
+    #[test]
+    fn rates_over_two_snapshots() {
+        let start = Instant::now();
+        let earlier = RateSnapshot {
+            at: start,
+            batches: 100,
+            bytes: 10_000,
+        };
+        let later = RateSnapshot {
+            at: start + Duration::from_secs(2),
+            batches: 300,
+            bytes: 50_000,
+        };
+        assert_eq!(later.rates_since(&earlier), (100.0, 20_000.0));
+        assert_eq!(earlier.rates_since(&earlier), (0.0, 0.0));
+
+        // Through the ext state: first sample only sets the baseline
+        let ext_state = MergerExtState::new();
+        assert_eq!(ext_state.rates_at(start), (0.0, 0.0));
+        for _ in 0..50 {
+            ext_state.atomic_stats.record_sent(1000);
+        }
+        // Too soon for a new interval: rates unchanged
+        assert_eq!(
+            ext_state.rates_at(start + Duration::from_millis(500)),
+            (0.0, 0.0)
+        );
+        assert_eq!(
+            ext_state.rates_at(start + Duration::from_secs(2)),
+            (25.0, 25_000.0)
+        );
+        assert_eq!(ext_state.get_stats().sent_bytes, 50_000);
+    }
+
     #[test]
     fn atomic_stats() {
         let stats = AtomicStats::new();
         stats.record_received();
         stats.record_received();
-        stats.record_sent();
+        stats.record_sent(100);
         stats.record_drop();
 
         let (recv, sent, drop, eos) = stats.snapshot();
@@ -646,6 +1379,66 @@ mod tests {
         assert_eq!(state.source_stats.len(), 0);
     }
 
+    #[test]
+    fn liveness_flags_silent_source_only() {
+        let state = MergerExtState::new();
+        let timeout = Duration::from_millis(5000);
+        let t0 = Instant::now();
+
+        state.record_seen_at(0, t0);
+        state.record_seen_at(1, t0);
+        state.record_seen_at(1, t0 + Duration::from_secs(4));
+        // A finished source is not expected to send anything
+        state.record_seen_at(2, t0);
+        state.record_finished(2);
+
+        state.check_liveness_at(t0 + Duration::from_millis(5500), timeout);
+        let stats = state.get_stats();
+        assert_eq!(stats.stale_sources(), vec![0]);
+
+        let ext = MergerCommandExt {
+            ext_state: Arc::new(state),
+            backpressure: BackpressurePolicy::Drop,
+        };
+        assert!(ext.status_details().unwrap().ends_with(", Stale: [0]"));
+
+        // Hearing from the source again clears the flag
+        ext.ext_state.record_seen_at(0, t0 + Duration::from_secs(6));
+        assert!(ext.ext_state.get_stats().stale_sources().is_empty());
+        assert!(!ext.status_details().unwrap().contains("Stale"));
+    }
+
+    #[test]
+    fn heartbeats_before_first_batch_are_not_a_gap() {
+        let state = MergerExtState::new();
+        let data = |sequence_number| MessageHeader::Data {
+            source_id: 3,
+            sequence_number,
+            epoch: 1,
+        };
+
+        // The source idles with heartbeats before its first batch
+        for _ in 0..5 {
+            state.record_header(MessageHeader::Heartbeat { source_id: 3 });
+        }
+        let stats = state.get_stats();
+        assert_eq!(stats.sources[&3].last_sequence, None);
+        assert!(stats.sources[&3].last_seen.is_some());
+
+        // A late first sequence number is the baseline
+        state.record_header(data(42));
+        state.record_header(MessageHeader::Heartbeat { source_id: 3 });
+        state.record_header(data(43));
+
+        let stats = state.get_stats();
+        let source = &stats.sources[&3];
+        assert_eq!(source.last_sequence, Some(43));
+        assert_eq!(source.total_batches, 2);
+        assert_eq!(source.restart_count, 0);
+        assert_eq!(stats.total_gaps(), 0);
+        assert_eq!(stats.received_batches, 2);
+    }
+
     #[test]
     fn merger_creation() {
         let config = MergerConfig::default();
@@ -673,10 +1466,172 @@ mod tests {
         assert!(msg.contains("Channel"));
     }
 
+    #[tokio::test]
+    async fn merge_task_orders_two_sources_and_holds_eos() {
+        let (in_tx, in_rx) = mpsc::channel::<Bytes>(64);
+        let (out_tx, mut out_rx) = mpsc::channel::<Bytes>(64);
+        let handle = tokio::spawn(Merger::merge_task(
+            in_rx,
+            out_tx,
+            TimeSorter::new(5.0, 10_000, 2),
+            None,
+            None,
+            Arc::new(MergerExtState::new()),
+        ));
+
+        let send = |msg: Message| {
+            in_tx
+                .try_send(Bytes::from(msg.to_msgpack().unwrap()))
+                .unwrap()
+        };
+        let data = |source_id: u32, seq: u64, timestamps: &[f64]| {
+            let mut batch = EventDataBatch::new(source_id, seq);
+            for &ts in timestamps {
+                batch.push(EventData::new(source_id as u8, 0, 100, 50, ts, 0));
+            }
+            Message::Data(batch)
+        };
+
+        send(data(0, 0, &[0.0, 20.0, 40.0]));
+        send(data(1, 0, &[10.0, 30.0, 50.0]));
+        send(data(0, 1, &[60.0, 80.0]));
+        send(data(1, 1, &[45.0, 70.0, 90.0]));
+        send(Message::eos(0));
+        send(Message::eos(1));
+        drop(in_tx);
+        handle.await.unwrap();
+
+        let mut timestamps = Vec::new();
+        let mut eos_count = 0;
+        while let Some(bytes) = out_rx.recv().await {
+            match Message::from_msgpack(&bytes).unwrap() {
+                Message::Data(batch) => {
+                    assert_eq!(eos_count, 0, "data after EOS");
+                    assert_eq!(batch.source_id, MERGED_SOURCE_ID);
+                    timestamps.extend(batch.events.iter().map(|e| e.timestamp_ns));
+                }
+                Message::EndOfStream { .. } => eos_count += 1,
+                Message::Heartbeat(_) => {}
+            }
+        }
+
+        assert_eq!(eos_count, 2);
+        assert_eq!(timestamps.len(), 11);
+        assert!(timestamps.windows(2).all(|w| w[0] <= w[1]));
+    }
+
+    #[tokio::test]
+    async fn merge_task_drops_duplicates_across_sources() {
+        let (in_tx, in_rx) = mpsc::channel::<Bytes>(64);
+        let (out_tx, mut out_rx) = mpsc::channel::<Bytes>(64);
+        let ext_state = Arc::new(MergerExtState::new());
+        let dedup = Deduplicator::new(&DedupConfig {
+            window_ns: 1000.0,
+            tolerance_ns: 0.0,
+            max_tracked_events: 1000,
+        });
+        let handle = tokio::spawn(Merger::merge_task(
+            in_rx,
+            out_tx,
+            TimeSorter::new(0.0, 10_000, 2),
+            Some(dedup),
+            None,
+            ext_state.clone(),
+        ));
+
+        // Both sources saw the trigger at 200 ns on module 0, channel 3
+        let mut src0 = EventDataBatch::new(0, 0);
+        src0.push(EventData::new(0, 3, 100, 50, 100.0, 0));
+        src0.push(EventData::new(0, 3, 100, 50, 200.0, 0));
+        let mut src1 = EventDataBatch::new(1, 0);
+        src1.push(EventData::new(0, 3, 100, 50, 200.0, 0));
+        src1.push(EventData::new(0, 3, 100, 50, 300.0, 0));
+
+        for msg in [
+            Message::Data(src0),
+            Message::Data(src1),
+            Message::eos(0),
+            Message::eos(1),
+        ] {
+            in_tx
+                .send(Bytes::from(msg.to_msgpack().unwrap()))
+                .await
+                .unwrap();
+        }
+        drop(in_tx);
+        handle.await.unwrap();
+
+        let mut timestamps = Vec::new();
+        while let Some(bytes) = out_rx.recv().await {
+            if let Message::Data(batch) = Message::from_msgpack(&bytes).unwrap() {
+                timestamps.extend(batch.events.iter().map(|e| e.timestamp_ns));
+            }
+        }
+        assert_eq!(timestamps, vec![100.0, 200.0, 300.0]);
+        assert_eq!(ext_state.get_stats().duplicates_dropped, 1);
+    }
+
+    #[tokio::test]
+    async fn merge_task_coincidence_counts_in_merger_stats() {
+        let (in_tx, in_rx) = mpsc::channel::<Bytes>(64);
+        let (out_tx, mut out_rx) = mpsc::channel::<Bytes>(64);
+        let ext_state = Arc::new(MergerExtState::new());
+        let filter = CoincidenceFilter::new(&CoincidenceConfig {
+            window_ns: 10.0,
+            channels: vec![(0, 0), (1, 0)],
+            multiplicity: 2,
+        });
+        let handle = tokio::spawn(Merger::merge_task(
+            in_rx,
+            out_tx,
+            TimeSorter::new(0.0, 10_000, 2),
+            None,
+            Some(filter),
+            ext_state.clone(),
+        ));
+
+        let mut src0 = EventDataBatch::new(0, 0);
+        src0.push(EventData::new(0, 0, 100, 50, 100.0, 0));
+        src0.push(EventData::new(0, 0, 100, 50, 300.0, 0));
+        let mut src1 = EventDataBatch::new(1, 0);
+        // 104 pairs with 100; 340 is 40 ns away from 300
+        src1.push(EventData::new(1, 0, 100, 50, 104.0, 0));
+        src1.push(EventData::new(1, 0, 100, 50, 340.0, 0));
+
+        for msg in [
+            Message::Data(src0),
+            Message::Data(src1),
+            Message::eos(0),
+            Message::eos(1),
+        ] {
+            in_tx
+                .send(Bytes::from(msg.to_msgpack().unwrap()))
+                .await
+                .unwrap();
+        }
+        drop(in_tx);
+        handle.await.unwrap();
+
+        let mut timestamps = Vec::new();
+        while let Some(bytes) = out_rx.recv().await {
+            if let Message::Data(batch) = Message::from_msgpack(&bytes).unwrap() {
+                timestamps.extend(batch.events.iter().map(|e| e.timestamp_ns));
+            }
+        }
+        assert_eq!(timestamps, vec![100.0, 104.0]);
+
+        let stats = ext_state.get_stats();
+        assert_eq!(stats.coincidences_found, 1);
+        assert_eq!(stats.singles_dropped, 2);
+    }
+
     #[test]
     fn merger_command_ext_component_name() {
         let ext_state = Arc::new(MergerExtState::new());
-        let ext = MergerCommandExt { ext_state };
+        let ext = MergerCommandExt {
+            ext_state,
+            backpressure: BackpressurePolicy::Drop,
+        };
         assert_eq!(ext.component_name(), "Merger");
     }
 
@@ -687,6 +1642,7 @@ mod tests {
 
         let mut ext = MergerCommandExt {
             ext_state: ext_state.clone(),
+            backpressure: BackpressurePolicy::Drop,
         };
         assert!(ext.on_reset().is_ok());
         assert_eq!(ext_state.source_stats.len(), 0);
@@ -696,13 +1652,71 @@ mod tests {
     fn merger_command_ext_status_details() {
         let ext_state = Arc::new(MergerExtState::new());
         ext_state.atomic_stats.record_received();
-        ext_state.atomic_stats.record_sent();
+        ext_state.atomic_stats.record_sent(100);
 
-        let ext = MergerCommandExt { ext_state };
+        let ext = MergerCommandExt {
+            ext_state,
+            backpressure: BackpressurePolicy::BlockWithTimeout(50),
+        };
         let details = ext.status_details();
         assert!(details.is_some());
         let s = details.unwrap();
         assert!(s.contains("Received: 1"));
         assert!(s.contains("Sent: 1"));
+        assert!(s.contains("Late: 0"));
+        assert!(s.contains("Backpressure: BlockWithTimeout(50ms)"));
+    }
+
+    #[tokio::test]
+    async fn forward_drop_policy_counts_drops_when_full() {
+        let (tx, mut rx) = mpsc::channel::<Bytes>(1);
+        let ext_state = MergerExtState::new();
+        let policy = BackpressurePolicy::Drop;
+
+        assert!(Merger::forward(&tx, Bytes::from_static(b"a"), policy, &ext_state).await);
+        assert!(Merger::forward(&tx, Bytes::from_static(b"b"), policy, &ext_state).await);
+        assert_eq!(ext_state.get_stats().dropped_batches, 1);
+        assert_eq!(rx.recv().await.unwrap(), Bytes::from_static(b"a"));
+    }
+
+    #[tokio::test]
+    async fn forward_block_policy_backpressures_without_drops() {
+        let (tx, mut rx) = mpsc::channel::<Bytes>(1);
+        let ext_state = Arc::new(MergerExtState::new());
+        tx.send(Bytes::from_static(b"a")).await.unwrap();
+
+        let blocked = {
+            let tx = tx.clone();
+            let ext_state = ext_state.clone();
+            tokio::spawn(async move {
+                Merger::forward(
+                    &tx,
+                    Bytes::from_static(b"b"),
+                    BackpressurePolicy::Block,
+                    &ext_state,
+                )
+                .await
+            })
+        };
+
+        // Still waiting for room in the channel
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!blocked.is_finished());
+
+        assert_eq!(rx.recv().await.unwrap(), Bytes::from_static(b"a"));
+        assert!(blocked.await.unwrap());
+        assert_eq!(rx.recv().await.unwrap(), Bytes::from_static(b"b"));
+        assert_eq!(ext_state.get_stats().dropped_batches, 0);
+    }
+
+    #[tokio::test]
+    async fn forward_timeout_policy_drops_after_timeout() {
+        let (tx, _rx) = mpsc::channel::<Bytes>(1);
+        let ext_state = MergerExtState::new();
+        tx.send(Bytes::from_static(b"a")).await.unwrap();
+
+        let policy = BackpressurePolicy::BlockWithTimeout(20);
+        assert!(Merger::forward(&tx, Bytes::from_static(b"b"), policy, &ext_state).await);
+        assert_eq!(ext_state.get_stats().dropped_batches, 1);
     }
 }
@@ -0,0 +1,506 @@
+//! Time-ordered merging of events from multiple sources
+//!
+//! Each source delivers events in (roughly) increasing `timestamp_ns`, but
+//! batches from different sources interleave arbitrarily. The sorter buffers
+//! events and releases those older than the *horizon*:
+//!
+//! ```text
+//! horizon = min(latest timestamp of each active source) - margin_ns
+//! ```
+//!
+//! Everything at or before the horizon can no longer be preceded by a later
+//! arrival (up to `margin_ns` of per-source jitter), so it is emitted sorted.
+//! Nothing is released until `expected_sources` sources have delivered data.
+//! A source that stops sending holds the horizon back; `max_buffered_events`
+//! bounds memory by force-releasing the oldest events.
+//!
+//! At low rates the horizon can lag for a long time. With a maximum residency
+//! ([`TimeSorter::with_max_residency`]), [`TimeSorter::flush_expired`]
+//! releases every event of a batch that has been buffered longer than that,
+//! together with the older events, so latency stays bounded regardless of
+//! how few events arrive.
+//!
+//! A sequence number going backwards marks a source restart: all buffered
+//! events are flushed and tracking starts over. When every source has sent
+//! EOS the remaining events are flushed.
+//!
+//! An event older than the last one already released is *late*: it arrived
+//! beyond the margin and will be emitted out of order. Late events are
+//! counted as a diagnostic for a margin that is too small.
+//!
+//! With an adaptive margin ([`TimeSorter::with_adaptive_margin`]) the sorter
+//! measures how far each arriving event lies behind the unmargined horizon
+//! (the skew between sources) and widens the margin to the largest distance
+//! seen times [`ADAPTIVE_MARGIN_FACTOR`]. That largest distance decays by
+//! [`ADAPTIVE_SKEW_DECAY`] on every flush, so a single outlier does not
+//! inflate the margin for good: it shrinks back towards the configured
+//! margin, which is the lower bound, once the skew is no longer seen.
+
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+
+use tracing::{info, warn};
+
+use crate::common::{EventData, EventDataBatch};
+
+/// Safety factor applied to the largest observed skew in adaptive mode
+pub const ADAPTIVE_MARGIN_FACTOR: f64 = 1.5;
+
+/// Share of the largest observed skew kept on every flush in adaptive mode
+pub const ADAPTIVE_SKEW_DECAY: f64 = 0.99;
+
+/// Per-source progress
+#[derive(Debug, Default, Clone)]
+struct SourceCursor {
+    last_sequence: Option<u64>,
+    latest_timestamp_ns: f64,
+    eos: bool,
+}
+
+/// Buffer that merges events from all sources into one chronological stream
+#[derive(Debug)]
+pub struct TimeSorter {
+    /// Effective margin (the configured one unless adapted)
+    margin_ns: f64,
+    /// Configured margin, lower bound of the adaptive margin
+    base_margin_ns: f64,
+    /// Widen the margin to cover the observed skew
+    adaptive: bool,
+    /// Largest distance an event arrived behind the unmargined horizon,
+    /// decayed on every flush
+    max_skew_ns: f64,
+    max_buffered_events: usize,
+    expected_sources: usize,
+    buffer: Vec<EventData>,
+    sources: HashMap<u32, SourceCursor>,
+    /// Timestamp of the newest event released so far in this run
+    last_released_ns: f64,
+    late_events: u64,
+    /// Time after which buffered events are released regardless of the horizon
+    max_residency: Option<Duration>,
+    /// Arrival time and newest timestamp of each batch (only with a residency)
+    arrivals: VecDeque<(Instant, f64)>,
+}
+
+impl TimeSorter {
+    /// Create a sorter with the given late-arrival margin and buffer bound
+    pub fn new(margin_ns: f64, max_buffered_events: usize, expected_sources: usize) -> Self {
+        Self {
+            margin_ns,
+            base_margin_ns: margin_ns,
+            adaptive: false,
+            max_skew_ns: 0.0,
+            max_buffered_events,
+            expected_sources,
+            buffer: Vec::new(),
+            sources: HashMap::new(),
+            last_released_ns: f64::NEG_INFINITY,
+            late_events: 0,
+            max_residency: None,
+            arrivals: VecDeque::new(),
+        }
+    }
+
+    /// Release events buffered longer than `residency` (see [`Self::flush_expired`])
+    pub fn with_max_residency(mut self, residency: Duration) -> Self {
+        self.max_residency = Some(residency);
+        self
+    }
+
+    /// Size the margin from the observed skew between sources
+    pub fn with_adaptive_margin(mut self) -> Self {
+        self.adaptive = true;
+        self
+    }
+
+    /// Margin currently in use (ns)
+    pub fn margin_ns(&self) -> f64 {
+        self.margin_ns
+    }
+
+    /// Configured maximum residency, if any
+    pub fn max_residency(&self) -> Option<Duration> {
+        self.max_residency
+    }
+
+    /// Add a batch; returns events that are now safe to emit, in time order
+    pub fn push(&mut self, batch: EventDataBatch) -> Vec<EventData> {
+        let mut ready = Vec::new();
+
+        let restarted = self
+            .sources
+            .get(&batch.source_id)
+            .and_then(|c| c.last_sequence)
+            .is_some_and(|last| batch.sequence_number < last);
+        if restarted {
+            info!(
+                source_id = batch.source_id,
+                seq = batch.sequence_number,
+                buffered = self.buffer.len(),
+                "Source restart detected, flushing sort buffer"
+            );
+            ready = self.drain_all();
+            self.reset_tracking();
+        }
+
+        if self.adaptive {
+            self.adapt_margin(&batch.events);
+        }
+
+        let cursor = self.sources.entry(batch.source_id).or_default();
+        cursor.last_sequence = Some(batch.sequence_number);
+        cursor.eos = false;
+        let newest = batch.events.iter().map(|e| e.timestamp_ns).reduce(f64::max);
+        if let Some(latest) = newest {
+            cursor.latest_timestamp_ns = cursor.latest_timestamp_ns.max(latest);
+            if self.max_residency.is_some() {
+                self.arrivals.push_back((Instant::now(), latest));
+            }
+        }
+
+        let late = batch
+            .events
+            .iter()
+            .filter(|e| e.timestamp_ns < self.last_released_ns)
+            .count() as u64;
+        if late > 0 {
+            self.late_events += late;
+            warn!(
+                source_id = batch.source_id,
+                late,
+                total_late = self.late_events,
+                released_ns = self.last_released_ns,
+                "Events older than the sorted output arrived; sort margin may be too small"
+            );
+        }
+
+        self.buffer.extend(batch.events);
+        ready.extend(self.drain_ready());
+        ready
+    }
+
+    /// Mark a source as finished
+    ///
+    /// Returns the events released by this EOS and whether all known sources
+    /// have now finished (in which case the buffer has been fully flushed and
+    /// source tracking reset for the next run).
+    pub fn end_of_stream(&mut self, source_id: u32) -> (Vec<EventData>, bool) {
+        self.sources.entry(source_id).or_default().eos = true;
+
+        if self.sources.values().all(|c| c.eos) {
+            let ready = self.drain_all();
+            self.reset_tracking();
+            (ready, true)
+        } else {
+            (self.drain_ready(), false)
+        }
+    }
+
+    /// Release events of batches that arrived more than the maximum residency
+    /// before `now`, plus every buffered event older than them, in time order
+    ///
+    /// Returns nothing when no residency is configured.
+    pub fn flush_expired(&mut self, now: Instant) -> Vec<EventData> {
+        let Some(residency) = self.max_residency else {
+            return Vec::new();
+        };
+        let mut limit = f64::NEG_INFINITY;
+        while let Some(&(arrived, newest)) = self.arrivals.front() {
+            if now.saturating_duration_since(arrived) < residency {
+                break;
+            }
+            limit = limit.max(newest);
+            self.arrivals.pop_front();
+        }
+        if self.buffer.is_empty() || limit == f64::NEG_INFINITY {
+            return Vec::new();
+        }
+        self.sort();
+
+        let cut = self.buffer.partition_point(|e| e.timestamp_ns <= limit);
+        let released: Vec<EventData> = self.buffer.drain(..cut).collect();
+        self.note_released(&released);
+        released
+    }
+
+    /// Flush every buffered event in time order
+    pub fn drain_all(&mut self) -> Vec<EventData> {
+        self.sort();
+        self.arrivals.clear();
+        let drained = std::mem::take(&mut self.buffer);
+        self.note_released(&drained);
+        drained
+    }
+
+    /// Number of events currently held back
+    pub fn buffered(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// Events that arrived older than already released output (since creation)
+    pub fn late_events(&self) -> u64 {
+        self.late_events
+    }
+
+    /// Forget source progress and released timestamps (new run or restart)
+    fn reset_tracking(&mut self) {
+        self.sources.clear();
+        self.last_released_ns = f64::NEG_INFINITY;
+    }
+
+    fn note_released(&mut self, released: &[EventData]) {
+        if let Some(last) = released.last() {
+            self.last_released_ns = self.last_released_ns.max(last.timestamp_ns);
+            if self.adaptive {
+                self.max_skew_ns *= ADAPTIVE_SKEW_DECAY;
+                self.margin_ns = self
+                    .base_margin_ns
+                    .max(self.max_skew_ns * ADAPTIVE_MARGIN_FACTOR);
+            }
+        }
+    }
+
+    /// Widen the margin to cover how far `events` lie behind the horizon
+    fn adapt_margin(&mut self, events: &[EventData]) {
+        let horizon = self.horizon() + self.margin_ns;
+        if !horizon.is_finite() {
+            return;
+        }
+        let Some(oldest) = events.iter().map(|e| e.timestamp_ns).reduce(f64::min) else {
+            return;
+        };
+        let skew = horizon - oldest;
+        if skew > self.max_skew_ns {
+            self.max_skew_ns = skew;
+            let margin = self.base_margin_ns.max(skew * ADAPTIVE_MARGIN_FACTOR);
+            if margin > self.margin_ns {
+                info!(
+                    skew_ns = skew,
+                    margin_ns = margin,
+                    "Observed source skew exceeds sort margin, widening it"
+                );
+                self.margin_ns = margin;
+            }
+        }
+    }
+
+    /// Timestamp up to which events are safe to emit
+    fn horizon(&self) -> f64 {
+        if self.sources.len() < self.expected_sources {
+            return f64::NEG_INFINITY;
+        }
+        self.sources
+            .values()
+            .filter(|c| !c.eos)
+            .map(|c| c.latest_timestamp_ns - self.margin_ns)
+            .reduce(f64::min)
+            // Only finished sources left: nothing can arrive late any more
+            .unwrap_or(f64::INFINITY)
+    }
+
+    fn drain_ready(&mut self) -> Vec<EventData> {
+        if self.buffer.is_empty() {
+            return Vec::new();
+        }
+        self.sort();
+
+        let horizon = self.horizon();
+        let mut cut = self.buffer.partition_point(|e| e.timestamp_ns <= horizon);
+        if self.buffer.len() - cut > self.max_buffered_events {
+            let forced = self.buffer.len() - self.max_buffered_events;
+            warn!(
+                buffered = self.buffer.len(),
+                forced = forced - cut,
+                "Sort buffer full, releasing oldest events early"
+            );
+            cut = forced;
+        }
+        let released: Vec<EventData> = self.buffer.drain(..cut).collect();
+        self.note_released(&released);
+        released
+    }
+
+    fn sort(&mut self) {
+        // Stable: equal timestamps keep arrival order
+        self.buffer
+            .sort_by(|a, b| a.timestamp_ns.total_cmp(&b.timestamp_ns));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn batch(source_id: u32, seq: u64, timestamps: &[f64]) -> EventDataBatch {
+        let mut batch = EventDataBatch::new(source_id, seq);
+        for &ts in timestamps {
+            batch.push(EventData::new(source_id as u8, 0, 100, 50, ts, 0));
+        }
+        batch
+    }
+
+    fn assert_sorted(events: &[EventData]) {
+        assert!(
+            events
+                .windows(2)
+                .all(|w| w[0].timestamp_ns <= w[1].timestamp_ns),
+            "events not sorted: {:?}",
+            events.iter().map(|e| e.timestamp_ns).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_two_sources_interleaved_are_globally_sorted() {
+        let mut sorter = TimeSorter::new(5.0, 10_000, 2);
+        let mut out = Vec::new();
+
+        // Source 0: even timestamps, source 1: odd timestamps, batches out of phase
+        out.extend(sorter.push(batch(0, 0, &[0.0, 20.0, 40.0])));
+        out.extend(sorter.push(batch(1, 0, &[10.0, 30.0])));
+        out.extend(sorter.push(batch(0, 1, &[60.0, 80.0])));
+        out.extend(sorter.push(batch(1, 1, &[50.0, 70.0, 90.0])));
+        // Late arrival within the margin (source 0 latest is 80, horizon 75 at most)
+        out.extend(sorter.push(batch(0, 2, &[78.0, 100.0])));
+
+        // Nothing past the horizon (min latest 90 - margin) has been released yet
+        assert!(out.iter().all(|e| e.timestamp_ns <= 90.0 - 5.0));
+        assert_sorted(&out);
+
+        let (rest, _) = sorter.end_of_stream(0);
+        out.extend(rest);
+        let (rest, all_done) = sorter.end_of_stream(1);
+        out.extend(rest);
+
+        assert!(all_done);
+        assert_eq!(out.len(), 12);
+        assert_sorted(&out);
+        assert_eq!(sorter.buffered(), 0);
+    }
+
+    #[test]
+    fn test_silent_source_holds_horizon_until_eos() {
+        let mut sorter = TimeSorter::new(0.0, 10_000, 2);
+        // Held until both sources are known
+        assert_eq!(sorter.push(batch(0, 0, &[1.0])).len(), 0);
+        assert_eq!(sorter.push(batch(1, 0, &[5.0])).len(), 1);
+        // Source 1 stays at 5.0 while source 0 moves on
+        assert_eq!(sorter.push(batch(0, 1, &[10.0, 20.0])).len(), 1);
+        assert_eq!(sorter.buffered(), 2);
+
+        // EOS on source 1 lifts its hold
+        let (ready, all_done) = sorter.end_of_stream(1);
+        assert!(!all_done);
+        assert_eq!(ready.len(), 2);
+    }
+
+    #[test]
+    fn test_restart_flushes_buffer() {
+        let mut sorter = TimeSorter::new(100.0, 10_000, 1);
+        assert!(sorter.push(batch(0, 5, &[1000.0, 1010.0])).is_empty());
+
+        // New run: sequence and timestamps start over
+        let ready = sorter.push(batch(0, 0, &[1.0]));
+        assert_eq!(ready.len(), 2);
+        assert_eq!(ready[0].timestamp_ns, 1000.0);
+        assert_eq!(sorter.buffered(), 1);
+    }
+
+    #[test]
+    fn test_event_older_than_released_is_counted_late() {
+        let mut sorter = TimeSorter::new(10.0, 10_000, 1);
+        let released = sorter.push(batch(0, 0, &[100.0, 110.0, 150.0]));
+        assert_eq!(released.len(), 2);
+        assert_eq!(sorter.late_events(), 0);
+
+        // 105 is older than the released 110 tail; 120 is still in time
+        sorter.push(batch(0, 1, &[105.0, 120.0, 200.0]));
+        assert_eq!(sorter.late_events(), 1);
+
+        // A new run starts the released tail over
+        let (_, all_done) = sorter.end_of_stream(0);
+        assert!(all_done);
+        sorter.push(batch(0, 0, &[1.0]));
+        assert_eq!(sorter.late_events(), 1);
+    }
+
+    #[test]
+    fn test_adaptive_margin_covers_observed_skew() {
+        let mut sorter = TimeSorter::new(1.0, 10_000, 2).with_adaptive_margin();
+        sorter.push(batch(0, 0, &[0.0, 100.0]));
+        sorter.push(batch(1, 0, &[90.0, 100.0]));
+        assert_eq!(sorter.margin_ns(), 1.0);
+
+        // Source 1 lags 40 ns behind the horizon: late this time, margin grows
+        sorter.push(batch(1, 1, &[60.0]));
+        assert_eq!(sorter.late_events(), 1);
+        assert!(sorter.margin_ns() >= 40.0 * ADAPTIVE_MARGIN_FACTOR);
+
+        // The same skew is now absorbed by the margin
+        sorter.push(batch(0, 1, &[200.0]));
+        sorter.push(batch(1, 2, &[200.0]));
+        sorter.push(batch(1, 3, &[160.0]));
+        assert_eq!(sorter.late_events(), 1);
+
+        // Without adaptation the margin stays fixed
+        let mut fixed = TimeSorter::new(1.0, 10_000, 1);
+        fixed.push(batch(0, 0, &[100.0]));
+        fixed.push(batch(0, 1, &[10.0]));
+        assert_eq!(fixed.margin_ns(), 1.0);
+    }
+
+    #[test]
+    fn test_adaptive_margin_shrinks_after_outlier() {
+        let mut sorter = TimeSorter::new(1.0, 10_000, 1).with_adaptive_margin();
+        sorter.push(batch(0, 0, &[1000.0]));
+        // One outlier 900 ns behind the horizon
+        sorter.push(batch(0, 1, &[100.0, 1010.0]));
+        let widened = sorter.margin_ns();
+        assert!(widened >= 900.0 * ADAPTIVE_MARGIN_FACTOR * ADAPTIVE_SKEW_DECAY);
+
+        // In-order data from here on: every flush decays the margin
+        let mut ts = 1010.0;
+        let mut previous = widened;
+        for seq in 2..12 {
+            ts += 10_000.0;
+            sorter.push(batch(0, seq, &[ts]));
+            assert!(sorter.margin_ns() < previous);
+            previous = sorter.margin_ns();
+        }
+        for seq in 12..2000 {
+            ts += 10_000.0;
+            sorter.push(batch(0, seq, &[ts]));
+        }
+        // Back at the configured lower bound
+        assert_eq!(sorter.margin_ns(), 1.0);
+    }
+
+    #[test]
+    fn test_residency_releases_low_rate_events() {
+        let residency = Duration::from_millis(500);
+        let mut sorter = TimeSorter::new(1e9, 10_000, 2).with_max_residency(residency);
+        assert!(sorter.push(batch(0, 0, &[30.0, 10.0])).is_empty());
+        assert!(sorter.push(batch(1, 0, &[20.0])).is_empty());
+
+        // Still within the residency: the horizon (huge margin) holds everything
+        assert!(sorter.flush_expired(Instant::now()).is_empty());
+        assert_eq!(sorter.buffered(), 3);
+
+        let ready = sorter.flush_expired(Instant::now() + residency + Duration::from_millis(1));
+        assert_eq!(ready.len(), 3);
+        assert_sorted(&ready);
+        assert_eq!(sorter.buffered(), 0);
+
+        // Without a residency nothing is flushed by time
+        let mut sorter = TimeSorter::new(1e9, 10_000, 1);
+        sorter.push(batch(0, 0, &[1.0]));
+        assert!(sorter.flush_expired(Instant::now() + residency).is_empty());
+    }
+
+    #[test]
+    fn test_buffer_bound_forces_release() {
+        let mut sorter = TimeSorter::new(1e9, 3, 1);
+        let ready = sorter.push(batch(0, 0, &[1.0, 2.0, 3.0, 4.0, 5.0]));
+        assert_eq!(ready.len(), 2);
+        assert_eq!(sorter.buffered(), 3);
+    }
+}
@@ -5,18 +5,25 @@
 //! - Histogram task: mpsc channel → histogram update (owns state, no locks in hot path)
 //! - Command task: REP socket for control commands
 //! - HTTP server: REST API + static files for web UI (reads histogram via channel query)
+//! - WebSocket `/ws`: histogram task broadcasts periodic deltas to all connected clients
+//! - Optional TOF histogram between two channels (see [`tof`])
 //!
 //! This module provides real-time monitoring of DAQ data with browser-based
 //! histogram display.
 
-use std::collections::HashMap;
+mod tof;
+
+pub use tof::{TofConfig, TofHistogram};
+
+use std::collections::{HashMap, VecDeque};
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 use axum::{
+    extract::ws::{Message as WsMessage, WebSocket, WebSocketUpgrade},
     extract::State,
-    http::StatusCode,
+    http::{header, StatusCode},
     response::{Html, IntoResponse, Json},
     routing::get,
     Router,
@@ -24,16 +31,17 @@ use axum::{
 use futures::StreamExt;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
-use tmq::{subscribe, Context};
+use tmq::{subscribe, AsZmqSocket, Context};
 use tokio::sync::{broadcast, mpsc, oneshot, watch};
 use tower_http::compression::CompressionLayer;
 use tower_http::cors::{Any, CorsLayer};
 use tracing::{debug, info, warn};
 
 use crate::common::{
-    handle_command, run_command_task, CommandHandlerExt, ComponentSharedState, ComponentState,
-    EventData, EventDataBatch, Message, Waveform,
+    handle_command, run_command_task, topic, CommandHandlerExt, ComponentSharedState,
+    ComponentState, EventData, EventDataBatch, Message, SocketOptions, Waveform,
 };
+use crate::reader::decoder::adc_max;
 
 /// Monitor configuration
 #[derive(Debug, Clone)]
@@ -46,8 +54,22 @@ pub struct MonitorConfig {
     pub http_port: u16,
     /// Default histogram configuration
     pub histogram_config: HistogramConfig,
+    /// PSD 2D histogram configuration (energy vs energy_short/energy)
+    pub histogram_2d_config: Histogram2DConfig,
     /// Internal channel capacity
     pub channel_capacity: usize,
+    /// Interval between WebSocket histogram pushes in milliseconds
+    pub ws_interval_ms: u64,
+    /// Maximum number of rate-history points kept (oldest are dropped)
+    pub rate_history_len: usize,
+    /// Topic prefixes to receive (empty = everything)
+    pub subscribe_topics: Vec<String>,
+    /// ZMQ receive HWM / linger of the SUB socket (default: ZMQ's)
+    pub socket_options: SocketOptions,
+    /// Detector names by (module, channel), shown instead of the numbers
+    pub labels: HashMap<(u8, u8), String>,
+    /// Scaler thresholds by (module, channel); unlisted channels use 0
+    pub scaler_thresholds: HashMap<(u8, u8), u32>,
 }
 
 impl Default for MonitorConfig {
@@ -57,7 +79,14 @@ impl Default for MonitorConfig {
             command_address: "tcp://*:5590".to_string(),
             http_port: 8081,
             histogram_config: HistogramConfig::default(),
+            histogram_2d_config: Histogram2DConfig::default(),
             channel_capacity: 1000,
+            ws_interval_ms: 500,
+            rate_history_len: 3600,
+            subscribe_topics: Vec::new(),
+            socket_options: SocketOptions::default(),
+            labels: HashMap::new(),
+            scaler_thresholds: HashMap::new(),
         }
     }
 }
@@ -75,8 +104,66 @@ pub enum MonitorError {
     Http(String),
 }
 
+/// Quantity a 1D histogram is filled with
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FillSource {
+    /// Long-gate energy (ADC channels, or keV when calibrated)
+    #[default]
+    Energy,
+    /// Short-gate energy (ADC channels, or keV when calibrated)
+    EnergyShort,
+    /// PSD ratio `(energy - energy_short) / energy`, in [0, 1] for normal pulses
+    PsdRatio,
+}
+
+impl FillSource {
+    /// Whether the quantity is an energy that a calibration converts to keV
+    pub fn is_energy(self) -> bool {
+        matches!(self, FillSource::Energy | FillSource::EnergyShort)
+    }
+
+    /// Compute the quantity for an event (`None` when undefined)
+    ///
+    /// `pedestal` is subtracted from energies before calibration, so values
+    /// below the pedestal come out negative.
+    fn value(
+        self,
+        event: &EventData,
+        calibration: Option<&EnergyCalibration>,
+        pedestal: f32,
+    ) -> Option<f32> {
+        let adc = match self {
+            FillSource::Energy => event.energy,
+            FillSource::EnergyShort => u32::from(event.energy_short),
+            FillSource::PsdRatio => {
+                // Ratio undefined for zero energy
+                if event.energy == 0 {
+                    return None;
+                }
+                let energy = event.energy as f32;
+                return Some((energy - event.energy_short as f32) / energy);
+            }
+        };
+        let adc = adc as f64 - pedestal as f64;
+        Some(match calibration {
+            Some(cal) => cal.apply(adc) as f32,
+            None => adc as f32,
+        })
+    }
+}
+
+/// Largest accepted `num_bins` (8 MiB of counts per histogram)
+pub const MAX_HISTOGRAM_BINS: u32 = 1 << 20;
+
 /// Histogram configuration
+///
+/// Omitted binning fields take the defaults of [`HistogramConfig::for_source`],
+/// so a `psd_ratio` histogram covers [0, 1] unless a range is given. With
+/// `adc_bits` set, energy histograms default to the ADC range instead
+/// ([`HistogramConfig::for_adc_bits`]).
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(from = "HistogramConfigSpec")]
 pub struct HistogramConfig {
     /// Number of bins
     pub num_bins: u32,
@@ -84,23 +171,155 @@ pub struct HistogramConfig {
     pub min_value: f32,
     /// Maximum value
     pub max_value: f32,
+    /// Filled with calibrated energies (keV) instead of ADC channels
+    pub calibrated: bool,
+    /// Quantity filled into the histogram (default: energy)
+    pub fill_source: FillSource,
+    /// ADC offset subtracted from energies before filling (default: 0)
+    ///
+    /// Combine with a negative `min_value` to keep events below the
+    /// pedestal in range instead of underflow.
+    pub pedestal: f32,
+}
+
+/// Serialized form of [`HistogramConfig`] with optional binning
+#[derive(Deserialize)]
+struct HistogramConfigSpec {
+    num_bins: Option<u32>,
+    min_value: Option<f32>,
+    max_value: Option<f32>,
+    #[serde(default)]
+    calibrated: bool,
+    #[serde(default)]
+    fill_source: FillSource,
+    #[serde(default)]
+    pedestal: f32,
+    adc_bits: Option<u8>,
+}
+
+impl From<HistogramConfigSpec> for HistogramConfig {
+    fn from(spec: HistogramConfigSpec) -> Self {
+        let defaults = match spec.adc_bits {
+            Some(bits) if spec.fill_source.is_energy() => HistogramConfig {
+                fill_source: spec.fill_source,
+                ..HistogramConfig::for_adc_bits(bits)
+            },
+            _ => HistogramConfig::for_source(spec.fill_source),
+        };
+        Self {
+            num_bins: spec.num_bins.unwrap_or(defaults.num_bins),
+            min_value: spec.min_value.unwrap_or(defaults.min_value),
+            max_value: spec.max_value.unwrap_or(defaults.max_value),
+            calibrated: spec.calibrated,
+            fill_source: spec.fill_source,
+            pedestal: spec.pedestal,
+        }
+    }
 }
 
 impl Default for HistogramConfig {
     fn default() -> Self {
+        Self::for_source(FillSource::Energy)
+    }
+}
+
+impl HistogramConfig {
+    /// Default binning for a fill source
+    ///
+    /// Energies get 1 bin per ADC channel (16-bit); the PSD ratio gets
+    /// 1000 bins over [0, 1].
+    pub fn for_source(fill_source: FillSource) -> Self {
+        let (num_bins, max_value) = match fill_source {
+            FillSource::Energy | FillSource::EnergyShort => (65536, 65536.0),
+            FillSource::PsdRatio => (1000, 1.0),
+        };
+        Self {
+            num_bins,
+            min_value: 0.0,
+            max_value,
+            calibrated: false,
+            fill_source,
+            pedestal: 0.0,
+        }
+    }
+
+    /// Energy binning matching an ADC resolution: 1 bin per code, including
+    /// the top code (`2^adc_bits - 1`); only values above it overflow
+    pub fn for_adc_bits(adc_bits: u8) -> Self {
+        let codes = adc_max(adc_bits) as u32 + 1;
         Self {
-            num_bins: 65536,
+            num_bins: codes,
             min_value: 0.0,
-            max_value: 65536.0, // 1 bin per ADC channel (16-bit)
+            max_value: codes as f32,
+            calibrated: false,
+            fill_source: FillSource::Energy,
+            pedestal: 0.0,
+        }
+    }
+
+    /// Check that the binning is usable
+    pub fn validate(&self) -> Result<(), String> {
+        if self.num_bins == 0 {
+            return Err("num_bins must be greater than 0".to_string());
+        }
+        if self.num_bins > MAX_HISTOGRAM_BINS {
+            return Err(format!(
+                "num_bins ({}) must not exceed {}",
+                self.num_bins, MAX_HISTOGRAM_BINS
+            ));
+        }
+        if self.min_value.is_nan() || self.max_value.is_nan() || self.max_value <= self.min_value {
+            return Err(format!(
+                "max_value ({}) must be greater than min_value ({})",
+                self.max_value, self.min_value
+            ));
+        }
+        if !self.pedestal.is_finite() {
+            return Err(format!("pedestal must be finite: {}", self.pedestal));
+        }
+        Ok(())
+    }
+}
+
+/// Per-channel energy calibration: `keV = a + b * ch + c * ch^2`
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct EnergyCalibration {
+    /// Offset (keV)
+    pub a: f64,
+    /// Gain (keV per ADC channel)
+    pub b: f64,
+    /// Quadratic term (default: 0 = linear)
+    #[serde(default)]
+    pub c: f64,
+}
+
+impl EnergyCalibration {
+    /// Check that all coefficients are finite
+    pub fn validate(&self) -> Result<(), String> {
+        if [self.a, self.b, self.c].iter().all(|v| v.is_finite()) {
+            Ok(())
+        } else {
+            Err(format!(
+                "calibration coefficients must be finite: {:?}",
+                self
+            ))
         }
     }
+
+    /// Convert an ADC value to keV
+    pub fn apply(&self, channel: f64) -> f64 {
+        self.a + self.b * channel + self.c * channel * channel
+    }
 }
 
 /// 1D Histogram for a single channel
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Histogram1D {
     pub module_id: u32,
     pub channel_id: u32,
+    /// Detector name from the `labels` config
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
     pub config: HistogramConfig,
     pub bins: Vec<u64>,
     pub total_counts: u64,
@@ -115,6 +334,7 @@ impl Histogram1D {
         Self {
             module_id,
             channel_id,
+            label: None,
             config,
             bins,
             total_counts: 0,
@@ -157,6 +377,125 @@ impl Histogram1D {
     }
 }
 
+/// 2D histogram configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Histogram2DConfig {
+    /// Number of X bins
+    pub x_bins: u32,
+    /// X minimum value
+    pub x_min: f32,
+    /// X maximum value
+    pub x_max: f32,
+    /// Number of Y bins
+    pub y_bins: u32,
+    /// Y minimum value
+    pub y_min: f32,
+    /// Y maximum value
+    pub y_max: f32,
+}
+
+impl Default for Histogram2DConfig {
+    fn default() -> Self {
+        Self {
+            x_bins: 512,
+            x_min: 0.0,
+            x_max: 65536.0, // energy (16-bit)
+            y_bins: 256,
+            y_min: 0.0,
+            y_max: 1.0, // energy_short / energy
+        }
+    }
+}
+
+/// 2D Histogram for a single channel (PSD plot)
+///
+/// `bins` is row-major: `bins[y_bin][x_bin]`.
+#[derive(Debug, Clone, Serialize)]
+pub struct Histogram2D {
+    pub module_id: u32,
+    pub channel_id: u32,
+    pub config: Histogram2DConfig,
+    pub bins: Vec<Vec<u64>>,
+    pub total_counts: u64,
+    pub x_overflow: u64,
+    pub x_underflow: u64,
+    pub y_overflow: u64,
+    pub y_underflow: u64,
+}
+
+impl Histogram2D {
+    /// Create a new 2D histogram with the given configuration
+    pub fn new(module_id: u32, channel_id: u32, config: Histogram2DConfig) -> Self {
+        let bins = vec![vec![0u64; config.x_bins as usize]; config.y_bins as usize];
+        Self {
+            module_id,
+            channel_id,
+            config,
+            bins,
+            total_counts: 0,
+            x_overflow: 0,
+            x_underflow: 0,
+            y_overflow: 0,
+            y_underflow: 0,
+        }
+    }
+
+    /// Bin index for a value, or Err(true) on overflow / Err(false) on underflow
+    fn bin_index(value: f32, min: f32, max: f32, num_bins: u32) -> Result<usize, bool> {
+        if value < min {
+            return Err(false);
+        }
+        if value >= max {
+            return Err(true);
+        }
+        let bin = ((value - min) / ((max - min) / num_bins as f32)) as usize;
+        if bin < num_bins as usize {
+            Ok(bin)
+        } else {
+            Err(true)
+        }
+    }
+
+    /// Fill the histogram with an (x, y) point
+    ///
+    /// Points outside the range are counted in the overflow/underflow counters
+    /// of each offending axis and not binned.
+    pub fn fill(&mut self, x: f32, y: f32) {
+        self.total_counts += 1;
+
+        let c = &self.config;
+        let x_bin = Self::bin_index(x, c.x_min, c.x_max, c.x_bins);
+        let y_bin = Self::bin_index(y, c.y_min, c.y_max, c.y_bins);
+
+        match x_bin {
+            Err(true) => self.x_overflow += 1,
+            Err(false) => self.x_underflow += 1,
+            Ok(_) => {}
+        }
+        match y_bin {
+            Err(true) => self.y_overflow += 1,
+            Err(false) => self.y_underflow += 1,
+            Ok(_) => {}
+        }
+
+        if let (Ok(xb), Ok(yb)) = (x_bin, y_bin) {
+            self.bins[yb][xb] += 1;
+        }
+    }
+
+    /// Clear the histogram
+    pub fn clear(&mut self) {
+        for row in &mut self.bins {
+            row.fill(0);
+        }
+        self.total_counts = 0;
+        self.x_overflow = 0;
+        self.x_underflow = 0;
+        self.y_overflow = 0;
+        self.y_underflow = 0;
+    }
+}
+
 /// Key for identifying a channel histogram
 #[derive(Debug, Clone, Copy, Hash, Eq, PartialEq, Serialize, Deserialize)]
 pub struct ChannelKey {
@@ -178,48 +517,250 @@ impl ChannelKey {
 pub struct LatestWaveform {
     pub module_id: u32,
     pub channel_id: u32,
-    pub energy: u16,
+    pub energy: u32,
     pub timestamp_ns: f64,
     pub waveform: Waveform,
 }
 
+/// Reduce `samples` to at most `max_points` points by min/max bucketing
+///
+/// The samples are split into `max_points / 2` buckets and each bucket
+/// contributes its minimum and maximum in time order, so narrow pulses keep
+/// their extreme values. `max_points` below 2 is treated as 2.
+pub fn decimate_min_max(samples: &[i16], max_points: usize) -> Vec<i16> {
+    if samples.len() <= max_points {
+        return samples.to_vec();
+    }
+
+    let buckets = (max_points / 2).max(1);
+    let mut points = Vec::with_capacity(buckets * 2);
+    for i in 0..buckets {
+        let start = i * samples.len() / buckets;
+        let end = (i + 1) * samples.len() / buckets;
+        let bucket = &samples[start..end];
+
+        let (mut min_idx, mut max_idx) = (0, 0);
+        for (j, &v) in bucket.iter().enumerate() {
+            if v < bucket[min_idx] {
+                min_idx = j;
+            }
+            if v > bucket[max_idx] {
+                max_idx = j;
+            }
+        }
+        if min_idx <= max_idx {
+            points.extend([bucket[min_idx], bucket[max_idx]]);
+        } else {
+            points.extend([bucket[max_idx], bucket[min_idx]]);
+        }
+    }
+    points
+}
+
+/// Interval between rate-history samples
+const RATE_HISTORY_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Counts of one channel at a rate-history sample
+#[derive(Debug, Clone, Serialize)]
+pub struct ChannelCount {
+    pub module_id: u32,
+    pub channel_id: u32,
+    pub counts: u64,
+}
+
+/// One sample of the rate history (cumulative counts since run start)
+#[derive(Debug, Clone, Serialize)]
+pub struct RatePoint {
+    pub elapsed_secs: f64,
+    pub total_events: u64,
+    pub channels: Vec<ChannelCount>,
+}
+
 /// Monitor state containing all histograms (owned by histogram task)
 #[derive(Debug, Default)]
 pub struct MonitorState {
     pub histograms: HashMap<ChannelKey, Histogram1D>,
+    pub histograms_2d: HashMap<ChannelKey, Histogram2D>,
     pub latest_waveforms: HashMap<ChannelKey, LatestWaveform>,
     pub total_events: u64,
     pub start_time: Option<Instant>,
     pub histogram_config: HistogramConfig,
+    pub histogram_2d_config: Histogram2DConfig,
+    /// Per-channel overrides of `histogram_config`
+    pub channel_configs: HashMap<ChannelKey, HistogramConfig>,
+    /// Per-channel energy calibrations (kept across `clear`, dropped by `reset`)
+    pub calibrations: HashMap<ChannelKey, EnergyCalibration>,
+    /// Periodic rate samples for trend plots (oldest first)
+    pub rate_history: VecDeque<RatePoint>,
+    /// Maximum length of `rate_history`
+    pub rate_history_len: usize,
+    /// Time-difference histogram between two channels, if configured
+    pub tof: Option<TofHistogram>,
+    /// Detector names copied into the histograms and summaries
+    pub labels: HashMap<ChannelKey, String>,
+    /// Scaler counts per channel: (total, above threshold)
+    pub scalers: HashMap<ChannelKey, (u64, u64)>,
+    /// Per-channel scaler thresholds (raw energy; unlisted channels use 0)
+    pub scaler_thresholds: HashMap<ChannelKey, u32>,
 }
 
 impl MonitorState {
     pub fn new(config: HistogramConfig) -> Self {
         Self {
             histograms: HashMap::new(),
+            histograms_2d: HashMap::new(),
             latest_waveforms: HashMap::new(),
             total_events: 0,
             start_time: None,
             histogram_config: config,
+            histogram_2d_config: Histogram2DConfig::default(),
+            channel_configs: HashMap::new(),
+            calibrations: HashMap::new(),
+            rate_history: VecDeque::new(),
+            rate_history_len: MonitorConfig::default().rate_history_len,
+            tof: None,
+            labels: HashMap::new(),
+            scalers: HashMap::new(),
+            scaler_thresholds: HashMap::new(),
+        }
+    }
+
+    /// State with the histogram settings and labels of `config`
+    pub fn from_config(config: &MonitorConfig) -> Self {
+        let mut state = Self::new(config.histogram_config.clone());
+        state.histogram_2d_config = config.histogram_2d_config.clone();
+        state.rate_history_len = config.rate_history_len;
+        state.labels = config
+            .labels
+            .iter()
+            .map(|(&(module, channel), label)| {
+                (
+                    ChannelKey::new(module as u32, channel as u32),
+                    label.clone(),
+                )
+            })
+            .collect();
+        state.scaler_thresholds = config
+            .scaler_thresholds
+            .iter()
+            .map(|(&(module, channel), &threshold)| {
+                (ChannelKey::new(module as u32, channel as u32), threshold)
+            })
+            .collect();
+        state
+    }
+
+    /// New empty histogram for `key`, labelled if configured
+    fn new_histogram(&self, key: ChannelKey, config: HistogramConfig) -> Histogram1D {
+        let mut histogram = Histogram1D::new(key.module_id, key.channel_id, config);
+        histogram.label = self.labels.get(&key).cloned();
+        histogram
+    }
+
+    /// Set a per-channel histogram configuration
+    ///
+    /// An existing histogram for the channel is discarded and re-created
+    /// empty with the new binning.
+    pub fn set_channel_config(
+        &mut self,
+        key: ChannelKey,
+        mut config: HistogramConfig,
+    ) -> Result<(), String> {
+        config.validate()?;
+        config.calibrated = config.fill_source.is_energy() && self.calibrations.contains_key(&key);
+        if self.histograms.contains_key(&key) {
+            let histogram = self.new_histogram(key, config.clone());
+            self.histograms.insert(key, histogram);
         }
+        self.channel_configs.insert(key, config);
+        Ok(())
+    }
+
+    /// Set the energy calibration of a channel
+    ///
+    /// An existing histogram for the channel is re-created empty, since its
+    /// contents are in different units.
+    pub fn set_calibration(
+        &mut self,
+        key: ChannelKey,
+        calibration: EnergyCalibration,
+    ) -> Result<(), String> {
+        calibration.validate()?;
+        self.calibrations.insert(key, calibration);
+        if self.histograms.contains_key(&key) {
+            let mut config = self
+                .channel_configs
+                .get(&key)
+                .unwrap_or(&self.histogram_config)
+                .clone();
+            config.calibrated = config.fill_source.is_energy();
+            let histogram = self.new_histogram(key, config);
+            self.histograms.insert(key, histogram);
+        }
+        Ok(())
+    }
+
+    /// Set the scaler threshold of a channel and restart its counts
+    pub fn set_scaler_threshold(&mut self, key: ChannelKey, threshold: u32) {
+        self.scaler_thresholds.insert(key, threshold);
+        self.scalers.remove(&key);
+    }
+
+    /// Configure the TOF pair (replaces any previous TOF histogram)
+    pub fn set_tof(&mut self, config: TofConfig) -> Result<(), String> {
+        self.tof = Some(TofHistogram::new(config)?);
+        Ok(())
     }
 
     /// Process an event and update histograms
     pub fn process_event(&mut self, event: &EventData) {
         self.total_events += 1;
 
+        if let Some(ref mut tof) = self.tof {
+            tof.process_event(event);
+        }
+
         let key = ChannelKey::new(event.module as u32, event.channel as u32);
 
+        let threshold = self.scaler_thresholds.get(&key).copied().unwrap_or(0);
+        let (total, above) = self.scalers.entry(key).or_default();
+        *total += 1;
+        if event.energy > threshold {
+            *above += 1;
+        }
+
+        let calibration = self.calibrations.get(&key);
+        let config = self
+            .channel_configs
+            .get(&key)
+            .unwrap_or(&self.histogram_config);
+        let label = self.labels.get(&key);
         let histogram = self.histograms.entry(key).or_insert_with(|| {
-            Histogram1D::new(
-                event.module as u32,
-                event.channel as u32,
-                self.histogram_config.clone(),
-            )
+            let mut config = config.clone();
+            config.calibrated = config.fill_source.is_energy() && calibration.is_some();
+            let mut histogram = Histogram1D::new(event.module as u32, event.channel as u32, config);
+            histogram.label = label.cloned();
+            histogram
         });
 
-        // Fill with energy (long gate)
-        histogram.fill(event.energy as f32);
+        // Fill with the configured quantity, energies calibrated to keV if configured
+        let fill_source = histogram.config.fill_source;
+        let calibration = calibration.filter(|_| fill_source.is_energy());
+        if let Some(value) = fill_source.value(event, calibration, histogram.config.pedestal) {
+            histogram.fill(value);
+        }
+
+        // PSD plot: energy_short/energy vs energy (ratio undefined for zero energy)
+        if event.energy > 0 {
+            let config = &self.histogram_2d_config;
+            let histogram_2d = self.histograms_2d.entry(key).or_insert_with(|| {
+                Histogram2D::new(event.module as u32, event.channel as u32, config.clone())
+            });
+            histogram_2d.fill(
+                event.energy as f32,
+                event.energy_short as f32 / event.energy as f32,
+            );
+        }
 
         // Store latest waveform if present
         if let Some(ref wf) = event.waveform {
@@ -243,17 +784,71 @@ impl MonitorState {
         }
     }
 
+    /// Append a rate-history sample, dropping the oldest beyond `rate_history_len`
+    pub fn record_rate_point(&mut self, elapsed_secs: f64) {
+        let mut channels: Vec<ChannelCount> = self
+            .histograms
+            .values()
+            .map(|h| ChannelCount {
+                module_id: h.module_id,
+                channel_id: h.channel_id,
+                counts: h.total_counts,
+            })
+            .collect();
+        channels.sort_by_key(|c| (c.module_id, c.channel_id));
+
+        self.rate_history.push_back(RatePoint {
+            elapsed_secs,
+            total_events: self.total_events,
+            channels,
+        });
+        while self.rate_history.len() > self.rate_history_len {
+            self.rate_history.pop_front();
+        }
+    }
+
     /// Clear all histograms and waveforms
     pub fn clear(&mut self) {
         for histogram in self.histograms.values_mut() {
             histogram.clear();
         }
+        for histogram in self.histograms_2d.values_mut() {
+            histogram.clear();
+        }
+        if let Some(ref mut tof) = self.tof {
+            tof.clear();
+        }
         self.latest_waveforms.clear();
+        self.rate_history.clear();
+        self.scalers.clear();
         self.total_events = 0;
     }
 
-    /// Create a snapshot for HTTP responses
-    fn snapshot(&self) -> MonitorStateSnapshot {
+    /// Clear all histograms and drop energy calibrations
+    ///
+    /// Calibrated histograms are removed so they are re-created in ADC units.
+    pub fn reset(&mut self) {
+        self.clear();
+        for (key, _) in self.calibrations.drain() {
+            self.histograms.remove(&key);
+        }
+    }
+
+    /// Histograms whose counts changed since the last call
+    ///
+    /// `last_counts` holds the per-channel counts at the previous push and is updated.
+    fn changed_histograms(&self, last_counts: &mut HashMap<ChannelKey, u64>) -> Vec<Histogram1D> {
+        self.histograms
+            .iter()
+            .filter(|(key, hist)| {
+                last_counts.insert(**key, hist.total_counts) != Some(hist.total_counts)
+            })
+            .map(|(_, hist)| hist.clone())
+            .collect()
+    }
+
+    /// Elapsed time and event rate since start
+    fn rate(&self) -> (f64, f64) {
         let elapsed_secs = self
             .start_time
             .map(|t| t.elapsed().as_secs_f64())
@@ -264,6 +859,92 @@ impl MonitorState {
         } else {
             0.0
         };
+        (elapsed_secs, event_rate)
+    }
+
+    /// Per-channel counts and rates without copying any bins
+    ///
+    /// Served for list and status queries, so their cost does not grow with
+    /// the number of bins and the histogram task is not held up by clones.
+    pub fn summary(&self) -> MonitorSummary {
+        let (elapsed_secs, event_rate) = self.rate();
+        let mut channels: Vec<ChannelSummary> = self
+            .histograms
+            .values()
+            .map(|h| ChannelSummary {
+                module_id: h.module_id,
+                channel_id: h.channel_id,
+                label: h.label.clone(),
+                total_counts: h.total_counts,
+            })
+            .collect();
+        channels.sort_by_key(|c| (c.module_id, c.channel_id));
+
+        MonitorSummary {
+            total_events: self.total_events,
+            elapsed_secs,
+            event_rate,
+            channels,
+        }
+    }
+
+    /// Scaler counts and rates of every channel that has seen events
+    pub fn scalers(&self) -> ScalerSummary {
+        let elapsed_secs = self.rate().0;
+        let per_sec = |count: u64| {
+            if elapsed_secs > 0.0 {
+                count as f64 / elapsed_secs
+            } else {
+                0.0
+            }
+        };
+        let mut channels: Vec<ChannelScaler> = self
+            .scalers
+            .iter()
+            .map(|(key, &(total, above_threshold))| ChannelScaler {
+                module_id: key.module_id,
+                channel_id: key.channel_id,
+                label: self.labels.get(key).cloned(),
+                threshold: self.scaler_thresholds.get(key).copied().unwrap_or(0),
+                total,
+                above_threshold,
+                total_rate: per_sec(total),
+                above_threshold_rate: per_sec(above_threshold),
+            })
+            .collect();
+        channels.sort_by_key(|c| (c.module_id, c.channel_id));
+
+        ScalerSummary {
+            elapsed_secs,
+            channels,
+        }
+    }
+
+    /// Every histogram with run metadata, for archiving spectra
+    pub fn export(&self, run_number: Option<u32>) -> HistogramExport {
+        let elapsed_secs = self.rate().0;
+        let exported_at = chrono::Utc::now();
+        let run_started_at = self.start_time.and_then(|_| {
+            chrono::Duration::from_std(Duration::from_secs_f64(elapsed_secs))
+                .ok()
+                .map(|elapsed| exported_at - elapsed)
+        });
+        let mut histograms: Vec<Histogram1D> = self.histograms.values().cloned().collect();
+        histograms.sort_by_key(|h| (h.module_id, h.channel_id));
+
+        HistogramExport {
+            run_number,
+            run_started_at,
+            exported_at,
+            elapsed_secs,
+            total_events: self.total_events,
+            histograms,
+        }
+    }
+
+    /// Full snapshot with every histogram (initial WebSocket frame only)
+    fn snapshot(&self) -> MonitorStateSnapshot {
+        let (elapsed_secs, event_rate) = self.rate();
 
         MonitorStateSnapshot {
             total_events: self.total_events,
@@ -274,7 +955,92 @@ impl MonitorState {
     }
 }
 
-/// Snapshot of monitor state for HTTP responses
+/// Lightweight view of the monitor state (`GET /api/histograms`)
+#[derive(Debug, Clone, Serialize)]
+pub struct MonitorSummary {
+    pub total_events: u64,
+    pub elapsed_secs: f64,
+    pub event_rate: f64,
+    /// Sorted by module, then channel
+    pub channels: Vec<ChannelSummary>,
+}
+
+/// Counts of one channel's histogram
+#[derive(Debug, Clone, Serialize)]
+pub struct ChannelSummary {
+    pub module_id: u32,
+    pub channel_id: u32,
+    /// Detector name from the `labels` config
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    pub total_counts: u64,
+}
+
+/// Scaler counts of every channel (`GET /api/scalers`)
+#[derive(Debug, Clone, Serialize)]
+pub struct ScalerSummary {
+    pub elapsed_secs: f64,
+    /// Sorted by module, then channel
+    pub channels: Vec<ChannelScaler>,
+}
+
+/// Scaler counts of one channel
+#[derive(Debug, Clone, Serialize)]
+pub struct ChannelScaler {
+    pub module_id: u32,
+    pub channel_id: u32,
+    /// Detector name from the `labels` config
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    /// Events count as above threshold when their energy is higher
+    pub threshold: u32,
+    pub total: u64,
+    pub above_threshold: u64,
+    /// Counts per second since the start
+    pub total_rate: f64,
+    pub above_threshold_rate: f64,
+}
+
+/// All 1D histograms of a run (`GET /api/histograms/export`)
+///
+/// Serialized as MessagePack with named fields so the file can be read
+/// without this crate (e.g. `msgpack.unpack` in Python). Each histogram
+/// carries its own binning, total_counts, overflow and underflow.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistogramExport {
+    /// Run number from the component state, if a run is configured
+    pub run_number: Option<u32>,
+    /// When the monitor started filling (derived from the elapsed time)
+    pub run_started_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub exported_at: chrono::DateTime<chrono::Utc>,
+    pub elapsed_secs: f64,
+    pub total_events: u64,
+    /// Sorted by module, then channel
+    pub histograms: Vec<Histogram1D>,
+}
+
+impl HistogramExport {
+    /// Encode as MessagePack
+    pub fn to_msgpack(&self) -> Result<Vec<u8>, rmp_serde::encode::Error> {
+        rmp_serde::to_vec_named(self)
+    }
+
+    /// Decode from MessagePack
+    pub fn from_msgpack(bytes: &[u8]) -> Result<Self, rmp_serde::decode::Error> {
+        rmp_serde::from_slice(bytes)
+    }
+
+    /// Download file name, e.g. `histograms_run0042_20260101T120000Z.msgpack`
+    pub fn file_name(&self) -> String {
+        let stamp = self.exported_at.format("%Y%m%dT%H%M%SZ");
+        match self.run_number {
+            Some(run) => format!("histograms_run{:04}_{}.msgpack", run, stamp),
+            None => format!("histograms_{}.msgpack", stamp),
+        }
+    }
+}
+
+/// Snapshot of monitor state including all histograms
 #[derive(Debug, Clone)]
 struct MonitorStateSnapshot {
     total_events: u64,
@@ -283,6 +1049,36 @@ struct MonitorStateSnapshot {
     histograms: HashMap<ChannelKey, Histogram1D>,
 }
 
+/// Frame pushed to WebSocket clients
+///
+/// The first frame after connecting is a `snapshot` with every histogram;
+/// subsequent `delta` frames carry only channels whose counts changed.
+#[derive(Debug, Serialize)]
+struct LiveFrame {
+    #[serde(rename = "type")]
+    kind: &'static str,
+    total_events: u64,
+    elapsed_secs: f64,
+    event_rate: f64,
+    histograms: Vec<Histogram1D>,
+}
+
+impl LiveFrame {
+    fn snapshot(snapshot: MonitorStateSnapshot) -> Self {
+        Self {
+            kind: "snapshot",
+            total_events: snapshot.total_events,
+            elapsed_secs: snapshot.elapsed_secs,
+            event_rate: snapshot.event_rate,
+            histograms: snapshot.histograms.into_values().collect(),
+        }
+    }
+
+    fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+}
+
 /// Atomic counters for hot-path statistics (lock-free)
 struct AtomicStats {
     received_batches: AtomicU64,
@@ -334,10 +1130,40 @@ impl AtomicStats {
 enum HistogramMessage {
     /// Clear all histograms
     Clear,
-    /// Get current state snapshot
+    /// Clear all histograms and drop energy calibrations
+    Reset,
+    /// Get counts and rates (no bins)
+    GetSummary(oneshot::Sender<MonitorSummary>),
+    /// Get a full snapshot with every histogram
     GetSnapshot(oneshot::Sender<MonitorStateSnapshot>),
+    /// Export every histogram with metadata for the given run number
+    Export(Option<u32>, oneshot::Sender<HistogramExport>),
     /// Get specific histogram
     GetHistogram(ChannelKey, oneshot::Sender<Option<Histogram1D>>),
+    /// Get specific 2D (PSD) histogram
+    GetHistogram2D(ChannelKey, oneshot::Sender<Option<Histogram2D>>),
+    /// Override histogram binning for one channel (re-creates its histogram)
+    SetChannelConfig(
+        ChannelKey,
+        HistogramConfig,
+        oneshot::Sender<Result<(), String>>,
+    ),
+    /// Set the energy calibration of one channel
+    SetCalibration(
+        ChannelKey,
+        EnergyCalibration,
+        oneshot::Sender<Result<(), String>>,
+    ),
+    /// Configure the TOF pair (re-creates the TOF histogram)
+    SetTof(TofConfig, oneshot::Sender<Result<(), String>>),
+    /// Get the TOF histogram
+    GetTof(oneshot::Sender<Option<TofHistogram>>),
+    /// Get the rate history (oldest first)
+    GetRateHistory(oneshot::Sender<Vec<RatePoint>>),
+    /// Get the scaler counts
+    GetScalers(oneshot::Sender<ScalerSummary>),
+    /// Set the scaler threshold of one channel (restarts its counts)
+    SetScalerThreshold(ChannelKey, u32, oneshot::Sender<()>),
     /// Get latest waveform for a channel
     GetWaveform(ChannelKey, oneshot::Sender<Option<LatestWaveform>>),
     /// List all available waveforms
@@ -353,84 +1179,205 @@ pub struct AppState {
     histogram_tx: mpsc::UnboundedSender<HistogramMessage>,
     /// Component state for status
     pub component_state: Arc<tokio::sync::Mutex<ComponentSharedState>>,
+    /// Pre-serialized delta frames from the histogram task (shared by all WebSocket clients)
+    live_tx: broadcast::Sender<Arc<String>>,
 }
 
 // =============================================================================
 // HTTP API Handlers
 // =============================================================================
 
-/// Response for histogram list
-#[derive(Serialize)]
-struct HistogramListResponse {
-    total_events: u64,
-    elapsed_secs: f64,
-    event_rate: f64,
-    channels: Vec<ChannelSummary>,
+/// GET /api/histograms - List all histograms
+async fn list_histograms(State(state): State<AppState>) -> Json<MonitorSummary> {
+    let (tx, rx) = oneshot::channel();
+    let _ = state.histogram_tx.send(HistogramMessage::GetSummary(tx));
+
+    Json(rx.await.unwrap_or(MonitorSummary {
+        total_events: 0,
+        elapsed_secs: 0.0,
+        event_rate: 0.0,
+        channels: vec![],
+    }))
 }
 
-#[derive(Serialize)]
-struct ChannelSummary {
-    module_id: u32,
-    channel_id: u32,
-    total_counts: u64,
+/// GET /api/histograms/:module/:channel - Get specific histogram
+async fn get_histogram(
+    State(state): State<AppState>,
+    axum::extract::Path((module_id, channel_id)): axum::extract::Path<(u32, u32)>,
+) -> Result<Json<Histogram1D>, StatusCode> {
+    let (tx, rx) = oneshot::channel();
+    let key = ChannelKey::new(module_id, channel_id);
+    let _ = state
+        .histogram_tx
+        .send(HistogramMessage::GetHistogram(key, tx));
+
+    match rx.await {
+        Ok(Some(hist)) => Ok(Json(hist)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
 }
 
-/// GET /api/histograms - List all histograms
-async fn list_histograms(State(state): State<AppState>) -> Json<HistogramListResponse> {
+/// GET /api/histograms2d/:module/:channel - Get specific 2D (PSD) histogram
+async fn get_histogram_2d(
+    State(state): State<AppState>,
+    axum::extract::Path((module_id, channel_id)): axum::extract::Path<(u32, u32)>,
+) -> Result<Json<Histogram2D>, StatusCode> {
     let (tx, rx) = oneshot::channel();
-    let _ = state.histogram_tx.send(HistogramMessage::GetSnapshot(tx));
+    let key = ChannelKey::new(module_id, channel_id);
+    let _ = state
+        .histogram_tx
+        .send(HistogramMessage::GetHistogram2D(key, tx));
 
     match rx.await {
-        Ok(snapshot) => {
-            let mut channels: Vec<ChannelSummary> = snapshot
-                .histograms
-                .iter()
-                .map(|(key, hist)| ChannelSummary {
-                    module_id: key.module_id,
-                    channel_id: key.channel_id,
-                    total_counts: hist.total_counts,
-                })
-                .collect();
+        Ok(Some(hist)) => Ok(Json(hist)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
 
-            // Sort by module_id, then channel_id
-            channels.sort_by(|a, b| {
-                a.module_id
-                    .cmp(&b.module_id)
-                    .then(a.channel_id.cmp(&b.channel_id))
-            });
+/// POST /api/histograms/:module/:channel/config - Override binning for one channel
+async fn set_channel_config(
+    State(state): State<AppState>,
+    axum::extract::Path((module_id, channel_id)): axum::extract::Path<(u32, u32)>,
+    Json(config): Json<HistogramConfig>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let (tx, rx) = oneshot::channel();
+    let key = ChannelKey::new(module_id, channel_id);
+    let _ = state
+        .histogram_tx
+        .send(HistogramMessage::SetChannelConfig(key, config, tx));
 
-            Json(HistogramListResponse {
-                total_events: snapshot.total_events,
-                elapsed_secs: snapshot.elapsed_secs,
-                event_rate: snapshot.event_rate,
-                channels,
-            })
+    match rx.await {
+        Ok(Ok(())) => {
+            info!(module_id, channel_id, "Histogram config updated");
+            Ok(StatusCode::OK)
+        }
+        Ok(Err(e)) => Err((StatusCode::BAD_REQUEST, e)),
+        Err(_) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Histogram task unavailable".to_string(),
+        )),
+    }
+}
+
+/// POST /api/calibration/:module/:channel - Set the energy calibration of one channel
+async fn set_calibration(
+    State(state): State<AppState>,
+    axum::extract::Path((module_id, channel_id)): axum::extract::Path<(u32, u32)>,
+    Json(calibration): Json<EnergyCalibration>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let (tx, rx) = oneshot::channel();
+    let key = ChannelKey::new(module_id, channel_id);
+    let _ = state
+        .histogram_tx
+        .send(HistogramMessage::SetCalibration(key, calibration, tx));
+
+    match rx.await {
+        Ok(Ok(())) => {
+            info!(
+                module_id,
+                channel_id,
+                ?calibration,
+                "Energy calibration updated"
+            );
+            Ok(StatusCode::OK)
+        }
+        Ok(Err(e)) => Err((StatusCode::BAD_REQUEST, e)),
+        Err(_) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Histogram task unavailable".to_string(),
+        )),
+    }
+}
+
+/// GET /ws - Live histogram push over WebSocket
+async fn ws_handler(ws: WebSocketUpgrade, State(state): State<AppState>) -> impl IntoResponse {
+    ws.on_upgrade(move |socket| live_session(socket, state))
+}
+
+/// Query a full snapshot frame from the histogram task
+async fn snapshot_frame(state: &AppState) -> Option<String> {
+    let (tx, rx) = oneshot::channel();
+    let _ = state.histogram_tx.send(HistogramMessage::GetSnapshot(tx));
+    rx.await.ok().map(|s| LiveFrame::snapshot(s).to_json())
+}
+
+/// Serve one WebSocket client until it disconnects or the monitor shuts down
+async fn live_session(mut socket: WebSocket, state: AppState) {
+    // Subscribe before the snapshot so no delta is missed in between
+    let mut updates = state.live_tx.subscribe();
+    debug!("WebSocket client connected");
+
+    let Some(frame) = snapshot_frame(&state).await else {
+        return;
+    };
+    if socket.send(WsMessage::Text(frame)).await.is_err() {
+        return;
+    }
+
+    loop {
+        tokio::select! {
+            update = updates.recv() => {
+                let frame = match update {
+                    Ok(json) => json.as_ref().clone(),
+                    // Missed deltas: resynchronize with a full snapshot
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        debug!(skipped, "WebSocket client lagged, resending snapshot");
+                        match snapshot_frame(&state).await {
+                            Some(frame) => frame,
+                            None => break,
+                        }
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                };
+                if socket.send(WsMessage::Text(frame)).await.is_err() {
+                    break;
+                }
+            }
+            msg = socket.recv() => match msg {
+                Some(Ok(WsMessage::Close(_))) | Some(Err(_)) | None => break,
+                // Client messages are ignored (pings are answered by axum)
+                Some(Ok(_)) => {}
+            },
         }
-        Err(_) => Json(HistogramListResponse {
-            total_events: 0,
-            elapsed_secs: 0.0,
-            event_rate: 0.0,
-            channels: vec![],
-        }),
     }
+
+    debug!("WebSocket client disconnected");
 }
 
-/// GET /api/histograms/:module/:channel - Get specific histogram
-async fn get_histogram(
+/// GET /api/histograms/export - Download all histograms as one MessagePack file
+async fn export_histograms(
     State(state): State<AppState>,
-    axum::extract::Path((module_id, channel_id)): axum::extract::Path<(u32, u32)>,
-) -> Result<Json<Histogram1D>, StatusCode> {
+) -> Result<([(header::HeaderName, String); 2], Vec<u8>), StatusCode> {
+    let run_number = state.component_state.lock().await.run_number();
+
     let (tx, rx) = oneshot::channel();
-    let key = ChannelKey::new(module_id, channel_id);
     let _ = state
         .histogram_tx
-        .send(HistogramMessage::GetHistogram(key, tx));
-
-    match rx.await {
-        Ok(Some(hist)) => Ok(Json(hist)),
-        Ok(None) => Err(StatusCode::NOT_FOUND),
-        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
-    }
+        .send(HistogramMessage::Export(run_number, tx));
+    let export = rx.await.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let bytes = export.to_msgpack().map_err(|e| {
+        warn!(error = %e, "Failed to encode histogram export");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    info!(
+        run_number,
+        channels = export.histograms.len(),
+        "Histograms exported"
+    );
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/msgpack".to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{}\"", export.file_name()),
+            ),
+        ],
+        bytes,
+    ))
 }
 
 /// POST /api/histograms/clear - Clear all histograms
@@ -500,6 +1447,69 @@ async fn get_waveform(
     }
 }
 
+/// Query parameters for the decimated waveform endpoint
+#[derive(Deserialize)]
+struct WaveformQuery {
+    /// Maximum number of points returned (default: all samples)
+    max_points: Option<usize>,
+}
+
+/// Decimated analog probe 1 of the latest waveform
+#[derive(Serialize)]
+struct DecimatedWaveformResponse {
+    module_id: u32,
+    channel_id: u32,
+    energy: u32,
+    timestamp_ns: f64,
+    /// Number of samples before decimation
+    original_points: usize,
+    /// Min/max bucketed samples (see [`decimate_min_max`])
+    analog_probe1: Vec<i16>,
+}
+
+/// GET /api/waveform/:module/:channel?max_points=N - Get a decimated waveform
+async fn get_decimated_waveform(
+    State(state): State<AppState>,
+    axum::extract::Path((module_id, channel_id)): axum::extract::Path<(u32, u32)>,
+    axum::extract::Query(query): axum::extract::Query<WaveformQuery>,
+) -> Result<Json<DecimatedWaveformResponse>, (StatusCode, String)> {
+    if query.max_points.is_some_and(|n| n < 2) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "max_points must be at least 2".to_string(),
+        ));
+    }
+
+    let (tx, rx) = oneshot::channel();
+    let key = ChannelKey::new(module_id, channel_id);
+    let _ = state
+        .histogram_tx
+        .send(HistogramMessage::GetWaveform(key, tx));
+
+    match rx.await {
+        Ok(Some(latest)) => {
+            let samples = &latest.waveform.analog_probe1;
+            let analog_probe1 = match query.max_points {
+                Some(max_points) => decimate_min_max(samples, max_points),
+                None => samples.clone(),
+            };
+            Ok(Json(DecimatedWaveformResponse {
+                module_id,
+                channel_id,
+                energy: latest.energy,
+                timestamp_ns: latest.timestamp_ns,
+                original_points: samples.len(),
+                analog_probe1,
+            }))
+        }
+        Ok(None) => Err((StatusCode::NOT_FOUND, "No waveform".to_string())),
+        Err(_) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Histogram task unavailable".to_string(),
+        )),
+    }
+}
+
 /// GET /api/status - Get monitor status
 #[derive(Serialize)]
 struct StatusResponse {
@@ -516,15 +1526,15 @@ async fn get_status(State(state): State<AppState>) -> Json<StatusResponse> {
     drop(component);
 
     let (tx, rx) = oneshot::channel();
-    let _ = state.histogram_tx.send(HistogramMessage::GetSnapshot(tx));
+    let _ = state.histogram_tx.send(HistogramMessage::GetSummary(tx));
 
     match rx.await {
-        Ok(snapshot) => Json(StatusResponse {
+        Ok(summary) => Json(StatusResponse {
             state: component_state,
-            total_events: snapshot.total_events,
-            num_channels: snapshot.histograms.len(),
-            elapsed_secs: snapshot.elapsed_secs,
-            event_rate: snapshot.event_rate,
+            total_events: summary.total_events,
+            num_channels: summary.channels.len(),
+            elapsed_secs: summary.elapsed_secs,
+            event_rate: summary.event_rate,
         }),
         Err(_) => Json(StatusResponse {
             state: component_state,
@@ -536,6 +1546,101 @@ async fn get_status(State(state): State<AppState>) -> Json<StatusResponse> {
     }
 }
 
+/// POST /api/tof - Configure the TOF pair
+async fn set_tof(
+    State(state): State<AppState>,
+    Json(config): Json<TofConfig>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let (tx, rx) = oneshot::channel();
+    let _ = state
+        .histogram_tx
+        .send(HistogramMessage::SetTof(config.clone(), tx));
+
+    match rx.await {
+        Ok(Ok(())) => {
+            info!(?config, "TOF histogram configured");
+            Ok(StatusCode::OK)
+        }
+        Ok(Err(e)) => Err((StatusCode::BAD_REQUEST, e)),
+        Err(_) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Histogram task unavailable".to_string(),
+        )),
+    }
+}
+
+/// GET /api/tof - Get the TOF histogram
+async fn get_tof(State(state): State<AppState>) -> Result<Json<TofHistogram>, StatusCode> {
+    let (tx, rx) = oneshot::channel();
+    let _ = state.histogram_tx.send(HistogramMessage::GetTof(tx));
+
+    match rx.await {
+        Ok(Some(tof)) => Ok(Json(tof)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+/// GET /api/rate_history - Rate samples since run start for trend plots
+async fn get_rate_history(
+    State(state): State<AppState>,
+) -> Result<Json<Vec<RatePoint>>, StatusCode> {
+    let (tx, rx) = oneshot::channel();
+    let _ = state
+        .histogram_tx
+        .send(HistogramMessage::GetRateHistory(tx));
+
+    rx.await
+        .map(Json)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+/// GET /api/scalers - Total and above-threshold counts per channel
+async fn get_scalers(State(state): State<AppState>) -> Result<Json<ScalerSummary>, StatusCode> {
+    let (tx, rx) = oneshot::channel();
+    let _ = state.histogram_tx.send(HistogramMessage::GetScalers(tx));
+
+    rx.await
+        .map(Json)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+/// Body of `POST /api/scalers/:module/:channel/threshold`
+#[derive(Debug, Deserialize)]
+struct ScalerThresholdRequest {
+    threshold: u32,
+}
+
+/// POST /api/scalers/:module/:channel/threshold - Set one channel's scaler threshold
+async fn set_scaler_threshold(
+    State(state): State<AppState>,
+    axum::extract::Path((module_id, channel_id)): axum::extract::Path<(u32, u32)>,
+    Json(request): Json<ScalerThresholdRequest>,
+) -> StatusCode {
+    let (tx, rx) = oneshot::channel();
+    let key = ChannelKey::new(module_id, channel_id);
+    let _ = state
+        .histogram_tx
+        .send(HistogramMessage::SetScalerThreshold(
+            key,
+            request.threshold,
+            tx,
+        ));
+
+    match rx.await {
+        Ok(()) => {
+            info!(
+                module_id,
+                channel_id,
+                threshold = request.threshold,
+                "Scaler threshold updated"
+            );
+            StatusCode::OK
+        }
+        Err(_) => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
 /// GET / - Serve the web UI
 async fn serve_ui() -> impl IntoResponse {
     Html(include_str!("monitor_ui.html"))
@@ -553,15 +1658,41 @@ pub fn create_router(state: AppState) -> Router {
         .route("/", get(serve_ui))
         .route("/api/status", get(get_status))
         .route("/api/histograms", get(list_histograms))
+        .route("/api/histograms/export", get(export_histograms))
         .route("/api/histograms/:module_id/:channel_id", get(get_histogram))
         .route(
             "/api/histograms/clear",
             axum::routing::post(clear_histograms),
         )
+        .route(
+            "/api/histograms/:module_id/:channel_id/config",
+            axum::routing::post(set_channel_config),
+        )
+        .route("/api/rate_history", get(get_rate_history))
+        .route("/api/scalers", get(get_scalers))
+        .route(
+            "/api/scalers/:module_id/:channel_id/threshold",
+            axum::routing::post(set_scaler_threshold),
+        )
+        .route("/api/tof", get(get_tof).post(set_tof))
+        .route(
+            "/api/calibration/:module_id/:channel_id",
+            axum::routing::post(set_calibration),
+        )
+        .route(
+            "/api/histograms2d/:module_id/:channel_id",
+            get(get_histogram_2d),
+        )
         .route("/api/waveforms", get(list_waveforms))
         .route("/api/waveforms/:module_id/:channel_id", get(get_waveform))
+        .route(
+            "/api/waveform/:module_id/:channel_id",
+            get(get_decimated_waveform),
+        )
         .layer(cors)
         .layer(CompressionLayer::new())
+        // Added after the layers: upgrade responses must not be compressed
+        .route("/ws", get(ws_handler))
         .with_state(state)
 }
 
@@ -589,7 +1720,7 @@ impl CommandHandlerExt for MonitorCommandExt {
     }
 
     fn on_reset(&mut self) -> Result<(), String> {
-        let _ = self.histogram_tx.send(HistogramMessage::Clear);
+        let _ = self.histogram_tx.send(HistogramMessage::Reset);
         Ok(())
     }
 
@@ -610,6 +1741,7 @@ impl CommandHandlerExt for MonitorCommandExt {
             queue_max: 0,
             event_rate: 0.0, // Will be calculated in Phase 2
             data_rate: 0.0,
+            ..Default::default()
         })
     }
 }
@@ -657,19 +1789,25 @@ impl Monitor {
 
         // Create ZMQ SUB socket
         let context = Context::new();
-        let socket = subscribe(&context)
-            .connect(&self.config.subscribe_address)?
-            .subscribe(b"")?;
+        let socket = topic::subscribe_topics(
+            subscribe(&context).connect(&self.config.subscribe_address)?,
+            &self.config.subscribe_topics,
+        )?;
+        self.config.socket_options.apply(socket.get_socket())?;
 
         info!(
             address = %self.config.subscribe_address,
             "Monitor connected to upstream"
         );
 
+        // Live push channel; frames are dropped when no WebSocket client is attached
+        let (live_tx, _) = broadcast::channel::<Arc<String>>(16);
+
         // Start HTTP server
         let app_state = AppState {
             histogram_tx: hist_tx.clone(),
             component_state: self.shared_state.clone(),
+            live_tx: live_tx.clone(),
         };
         let router = create_router(app_state);
 
@@ -732,10 +1870,19 @@ impl Monitor {
         });
 
         // Spawn histogram task
-        let histogram_config = self.config.histogram_config.clone();
+        let monitor_state = MonitorState::from_config(&self.config);
         let atomic_stats_for_hist = self.atomic_stats.clone();
+        let ws_interval = Duration::from_millis(self.config.ws_interval_ms.max(50));
         let hist_handle = tokio::spawn(async move {
-            Self::histogram_task(hist_rx, data_rx, histogram_config, atomic_stats_for_hist).await
+            Self::histogram_task(
+                hist_rx,
+                data_rx,
+                monitor_state,
+                atomic_stats_for_hist,
+                live_tx,
+                ws_interval,
+            )
+            .await
         });
 
         info!(state = %self.state(), "Monitor ready, waiting for commands");
@@ -773,7 +1920,8 @@ impl Monitor {
         mut state_rx: watch::Receiver<ComponentState>,
     ) {
         loop {
-            let is_running = *state_rx.borrow() == ComponentState::Running;
+            // Paused keeps in-flight data of the current run
+            let is_running = state_rx.borrow().in_run();
 
             tokio::select! {
                 biased;
@@ -799,7 +1947,7 @@ impl Monitor {
                                 continue;
                             }
 
-                            if let Some(data) = multipart.into_iter().next() {
+                            if let Some(data) = topic::payload(multipart) {
                                 match Message::from_msgpack(&data) {
                                     Ok(Message::Data(batch)) => {
                                         atomic_stats.record_received();
@@ -851,10 +1999,17 @@ impl Monitor {
     async fn histogram_task(
         mut cmd_rx: mpsc::UnboundedReceiver<HistogramMessage>,
         mut data_rx: mpsc::UnboundedReceiver<EventDataBatch>,
-        histogram_config: HistogramConfig,
+        mut state: MonitorState,
         atomic_stats: Arc<AtomicStats>,
+        live_tx: broadcast::Sender<Arc<String>>,
+        live_interval: Duration,
     ) {
-        let mut state = MonitorState::new(histogram_config);
+        let mut rate_ticker = tokio::time::interval(RATE_HISTORY_INTERVAL);
+        rate_ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+
+        let mut live_ticker = tokio::time::interval(live_interval);
+        live_ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+        let mut pushed_counts: HashMap<ChannelKey, u64> = HashMap::new();
 
         loop {
             tokio::select! {
@@ -863,7 +2018,7 @@ impl Monitor {
                 // Command messages have priority (for responsiveness)
                 cmd = cmd_rx.recv() => {
                     match cmd {
-                        Some(HistogramMessage::Clear) => {
+                        Some(msg @ (HistogramMessage::Clear | HistogramMessage::Reset)) => {
                             // Drain any stale data from the data channel first
                             let mut drained = 0u64;
                             while data_rx.try_recv().is_ok() {
@@ -873,17 +2028,52 @@ impl Monitor {
                                 info!(drained, "Drained stale batches from previous run");
                             }
 
-                            state.clear();
+                            if matches!(msg, HistogramMessage::Reset) {
+                                state.reset();
+                            } else {
+                                state.clear();
+                            }
                             state.start_time = None;
                             atomic_stats.reset();
                             info!("Histograms and stats cleared");
                         }
+                        Some(HistogramMessage::GetSummary(tx)) => {
+                            let _ = tx.send(state.summary());
+                        }
                         Some(HistogramMessage::GetSnapshot(tx)) => {
                             let _ = tx.send(state.snapshot());
                         }
+                        Some(HistogramMessage::Export(run_number, tx)) => {
+                            let _ = tx.send(state.export(run_number));
+                        }
                         Some(HistogramMessage::GetHistogram(key, tx)) => {
                             let _ = tx.send(state.histograms.get(&key).cloned());
                         }
+                        Some(HistogramMessage::GetHistogram2D(key, tx)) => {
+                            let _ = tx.send(state.histograms_2d.get(&key).cloned());
+                        }
+                        Some(HistogramMessage::SetChannelConfig(key, config, tx)) => {
+                            let _ = tx.send(state.set_channel_config(key, config));
+                        }
+                        Some(HistogramMessage::SetCalibration(key, calibration, tx)) => {
+                            let _ = tx.send(state.set_calibration(key, calibration));
+                        }
+                        Some(HistogramMessage::SetTof(config, tx)) => {
+                            let _ = tx.send(state.set_tof(config));
+                        }
+                        Some(HistogramMessage::GetTof(tx)) => {
+                            let _ = tx.send(state.tof.clone());
+                        }
+                        Some(HistogramMessage::GetRateHistory(tx)) => {
+                            let _ = tx.send(state.rate_history.iter().cloned().collect());
+                        }
+                        Some(HistogramMessage::GetScalers(tx)) => {
+                            let _ = tx.send(state.scalers());
+                        }
+                        Some(HistogramMessage::SetScalerThreshold(key, threshold, tx)) => {
+                            state.set_scaler_threshold(key, threshold);
+                            let _ = tx.send(());
+                        }
                         Some(HistogramMessage::GetWaveform(key, tx)) => {
                             let _ = tx.send(state.latest_waveforms.get(&key).cloned());
                         }
@@ -901,6 +2091,25 @@ impl Monitor {
                     }
                 }
 
+                // Periodic delta push (skipped entirely when nobody is listening)
+                _ = live_ticker.tick(), if live_tx.receiver_count() > 0 => {
+                    let (elapsed_secs, event_rate) = state.rate();
+                    let frame = LiveFrame {
+                        kind: "delta",
+                        total_events: state.total_events,
+                        elapsed_secs,
+                        event_rate,
+                        histograms: state.changed_histograms(&mut pushed_counts),
+                    };
+                    let _ = live_tx.send(Arc::new(frame.to_json()));
+                }
+
+                // Rate-history sample (only while a run is in progress)
+                _ = rate_ticker.tick(), if state.start_time.is_some() => {
+                    let (elapsed_secs, _) = state.rate();
+                    state.record_rate_point(elapsed_secs);
+                }
+
                 // Data batches
                 batch = data_rx.recv() => {
                     match batch {
@@ -937,12 +2146,34 @@ mod tests {
         assert_eq!(config.max_value, 65536.0);
     }
 
+    #[test]
+    fn test_decimate_min_max_keeps_pulse_peak() {
+        // Baseline with a one-sample pulse and a one-sample undershoot
+        let mut samples = vec![10i16; 1024];
+        samples[301] = 8000;
+        samples[700] = -500;
+
+        let points = decimate_min_max(&samples, 100);
+        assert!(points.len() <= 100);
+        assert_eq!(points.iter().copied().max(), Some(8000));
+        assert_eq!(points.iter().copied().min(), Some(-500));
+
+        // Short waveforms are returned unchanged
+        assert_eq!(
+            decimate_min_max(&samples[..50], 100),
+            samples[..50].to_vec()
+        );
+    }
+
     #[test]
     fn test_histogram_fill() {
         let config = HistogramConfig {
             num_bins: 100,
             min_value: 0.0,
             max_value: 100.0,
+            calibrated: false,
+            fill_source: FillSource::Energy,
+            pedestal: 0.0,
         };
         let mut hist = Histogram1D::new(0, 0, config);
 
@@ -963,6 +2194,9 @@ mod tests {
             num_bins: 100,
             min_value: 0.0,
             max_value: 100.0,
+            calibrated: false,
+            fill_source: FillSource::Energy,
+            pedestal: 0.0,
         };
         let mut hist = Histogram1D::new(0, 0, config);
 
@@ -975,12 +2209,93 @@ mod tests {
         assert_eq!(hist.overflow, 2);
     }
 
+    #[test]
+    fn test_histogram_negative_min_bound() {
+        let config = HistogramConfig {
+            num_bins: 20,
+            min_value: -10.0,
+            max_value: 10.0,
+            ..HistogramConfig::default()
+        };
+        let mut hist = Histogram1D::new(0, 0, config);
+        hist.fill(-10.0); // bin 0 (min is inclusive)
+        hist.fill(-0.5); // bin 9
+        hist.fill(9.9); // bin 19
+        hist.fill(-10.5); // underflow
+        hist.fill(10.0); // overflow
+
+        assert_eq!(hist.total_counts, 5);
+        assert_eq!((hist.bins[0], hist.bins[9], hist.bins[19]), (1, 1, 1));
+        assert_eq!(hist.bins.iter().sum::<u64>(), 3);
+        assert_eq!(hist.underflow, 1);
+        assert_eq!(hist.overflow, 1);
+    }
+
+    #[test]
+    fn test_wide_energy_fill() {
+        let mut state = MonitorState::new(HistogramConfig::default());
+        let config: HistogramConfig =
+            serde_json::from_str(r#"{"num_bins": 256, "min_value": 0.0, "max_value": 262144.0}"#)
+                .unwrap();
+        state
+            .set_channel_config(ChannelKey::new(0, 0), config)
+            .unwrap();
+
+        // 100000 does not fit in 16 bits and must land in its own bin (1024 wide)
+        let event = EventData::new(0, 0, 100_000, 0, 0.0, 0);
+        let bytes = rmp_serde::to_vec(&event).unwrap();
+        state.process_event(&rmp_serde::from_slice(&bytes).unwrap());
+
+        let hist = &state.histograms[&ChannelKey::new(0, 0)];
+        assert_eq!(hist.bins[97], 1);
+        assert_eq!(hist.overflow, 0);
+        assert_eq!(hist.total_counts, 1);
+    }
+
+    #[test]
+    fn test_pedestal_subtracted_before_fill() {
+        let mut state = MonitorState::new(HistogramConfig::default());
+        let config: HistogramConfig = serde_json::from_str(
+            r#"{"num_bins": 200, "min_value": -100.0, "max_value": 100.0, "pedestal": 1000.0}"#,
+        )
+        .unwrap();
+        state
+            .set_channel_config(ChannelKey::new(0, 0), config)
+            .unwrap();
+
+        for energy in [950, 1000, 1099, 850, 1100] {
+            state.process_event(&EventData::new(0, 0, energy, 0, 0.0, 0));
+        }
+        // Channel without a pedestal keeps the raw ADC value
+        state.process_event(&EventData::new(0, 1, 950, 0, 0.0, 0));
+
+        let hist = &state.histograms[&ChannelKey::new(0, 0)];
+        assert_eq!(hist.bins[50], 1); // 950 -> -50
+        assert_eq!(hist.bins[100], 1); // 1000 -> 0
+        assert_eq!(hist.bins[199], 1); // 1099 -> 99
+        assert_eq!(hist.underflow, 1); // 850 -> -150
+        assert_eq!(hist.overflow, 1); // 1100 -> 100
+        assert_eq!(hist.total_counts, 5);
+        assert_eq!(state.histograms[&ChannelKey::new(0, 1)].bins[950], 1);
+
+        let invalid = HistogramConfig {
+            pedestal: f32::NAN,
+            ..HistogramConfig::default()
+        };
+        assert!(state
+            .set_channel_config(ChannelKey::new(0, 2), invalid)
+            .is_err());
+    }
+
     #[test]
     fn test_histogram_clear() {
         let config = HistogramConfig {
             num_bins: 100,
             min_value: 0.0,
             max_value: 100.0,
+            calibrated: false,
+            fill_source: FillSource::Energy,
+            pedestal: 0.0,
         };
         let mut hist = Histogram1D::new(0, 0, config);
 
@@ -1018,6 +2333,418 @@ mod tests {
         assert_eq!(hist.total_counts, 1);
     }
 
+    fn small_2d_config() -> Histogram2DConfig {
+        Histogram2DConfig {
+            x_bins: 10,
+            x_min: 0.0,
+            x_max: 100.0,
+            y_bins: 4,
+            y_min: 0.0,
+            y_max: 1.0,
+        }
+    }
+
+    #[test]
+    fn test_histogram_2d_fill() {
+        let mut hist = Histogram2D::new(0, 0, small_2d_config());
+
+        hist.fill(5.0, 0.1); // x bin 0, y bin 0
+        hist.fill(55.0, 0.6); // x bin 5, y bin 2
+        hist.fill(99.9, 0.99); // x bin 9, y bin 3
+
+        assert_eq!(hist.total_counts, 3);
+        assert_eq!(hist.bins[0][0], 1);
+        assert_eq!(hist.bins[2][5], 1);
+        assert_eq!(hist.bins[3][9], 1);
+    }
+
+    #[test]
+    fn test_histogram_2d_overflow_underflow() {
+        let mut hist = Histogram2D::new(0, 0, small_2d_config());
+
+        hist.fill(-1.0, 0.5); // x underflow
+        hist.fill(100.0, 0.5); // x overflow
+        hist.fill(50.0, 1.5); // y overflow
+        hist.fill(150.0, -0.1); // x overflow + y underflow
+
+        assert_eq!(hist.total_counts, 4);
+        assert_eq!(hist.x_underflow, 1);
+        assert_eq!(hist.x_overflow, 2);
+        assert_eq!(hist.y_overflow, 1);
+        assert_eq!(hist.y_underflow, 1);
+        assert_eq!(hist.bins.iter().flatten().sum::<u64>(), 0);
+
+        hist.clear();
+        assert_eq!(hist.total_counts, 0);
+        assert_eq!(hist.x_overflow, 0);
+    }
+
+    #[test]
+    fn test_monitor_state_fills_psd_histogram() {
+        let mut state = MonitorState::new(HistogramConfig::default());
+        state.histogram_2d_config = small_2d_config();
+
+        let event = EventData {
+            module: 1,
+            channel: 2,
+            energy: 50,
+            energy_short: 25,
+            timestamp_ns: 0.0,
+            flags: 0,
+            waveform: None,
+        };
+        state.process_event(&event);
+
+        let hist = state.histograms_2d.get(&ChannelKey::new(1, 2)).unwrap();
+        assert_eq!(hist.bins[2][5], 1); // ratio 0.5 → y bin 2, energy 50 → x bin 5
+    }
+
+    #[test]
+    fn test_per_channel_histogram_config() {
+        let mut state = MonitorState::new(HistogramConfig::default());
+        let event = |channel: u8, energy: u32| EventData::new(0, channel, energy, 0, 0.0, 0);
+
+        // Fill before override: histogram exists with default binning
+        state.process_event(&event(1, 100));
+        assert_eq!(state.histograms[&ChannelKey::new(0, 1)].total_counts, 1);
+
+        let custom = HistogramConfig {
+            num_bins: 100,
+            min_value: 0.0,
+            max_value: 1000.0,
+            calibrated: false,
+            fill_source: FillSource::Energy,
+            pedestal: 0.0,
+        };
+        state
+            .set_channel_config(ChannelKey::new(0, 1), custom)
+            .unwrap();
+
+        // Rebinned histogram starts empty
+        let hist = &state.histograms[&ChannelKey::new(0, 1)];
+        assert_eq!(hist.bins.len(), 100);
+        assert_eq!(hist.total_counts, 0);
+
+        state.process_event(&event(1, 150));
+        state.process_event(&event(2, 150));
+
+        let custom_hist = &state.histograms[&ChannelKey::new(0, 1)];
+        assert_eq!(custom_hist.bins[15], 1); // 10 ADC units per bin
+        let default_hist = &state.histograms[&ChannelKey::new(0, 2)];
+        assert_eq!(default_hist.bins.len(), 65536);
+        assert_eq!(default_hist.bins[150], 1);
+
+        // Invalid binning is rejected
+        let invalid = HistogramConfig {
+            num_bins: 0,
+            min_value: 0.0,
+            max_value: 1.0,
+            calibrated: false,
+            fill_source: FillSource::Energy,
+            pedestal: 0.0,
+        };
+        assert!(state
+            .set_channel_config(ChannelKey::new(0, 1), invalid.clone())
+            .is_err());
+        let too_many = HistogramConfig {
+            num_bins: MAX_HISTOGRAM_BINS + 1,
+            ..invalid
+        };
+        assert!(state
+            .set_channel_config(ChannelKey::new(0, 1), too_many)
+            .is_err());
+        assert!(HistogramConfig::for_adc_bits(16).validate().is_ok());
+    }
+
+    #[test]
+    fn test_calibrated_histogram_fill() {
+        let mut state = MonitorState::new(HistogramConfig::default());
+        let key = ChannelKey::new(0, 3);
+        state
+            .set_channel_config(
+                key,
+                HistogramConfig {
+                    num_bins: 1000,
+                    min_value: 0.0,
+                    max_value: 2000.0,
+                    calibrated: false,
+                    fill_source: FillSource::Energy,
+                    pedestal: 0.0,
+                },
+            )
+            .unwrap();
+        // keV = 10 + 0.5 * ch + 0.001 * ch^2
+        state
+            .set_calibration(
+                key,
+                EnergyCalibration {
+                    a: 10.0,
+                    b: 0.5,
+                    c: 0.001,
+                },
+            )
+            .unwrap();
+
+        // ch 1000 -> 10 + 500 + 1000 = 1510 keV -> bin 755 (2 keV per bin)
+        state.process_event(&EventData::new(0, 3, 1000, 0, 0.0, 0));
+        // Uncalibrated channel keeps ADC units
+        state.process_event(&EventData::new(0, 4, 1000, 0, 0.0, 0));
+
+        let hist = &state.histograms[&key];
+        assert!(hist.config.calibrated);
+        assert_eq!(hist.bins[755], 1);
+        let raw = &state.histograms[&ChannelKey::new(0, 4)];
+        assert!(!raw.config.calibrated);
+        assert_eq!(raw.bins[1000], 1);
+
+        let json = serde_json::to_value(hist).unwrap();
+        assert_eq!(json["config"]["calibrated"], true);
+
+        // Clear keeps the calibration
+        state.clear();
+        state.process_event(&EventData::new(0, 3, 1000, 0, 0.0, 0));
+        assert_eq!(state.histograms[&key].bins[755], 1);
+
+        // Reset drops it: the channel is histogrammed in ADC units again
+        state.reset();
+        assert!(state.calibrations.is_empty());
+        state.process_event(&EventData::new(0, 3, 1000, 0, 0.0, 0));
+        let hist = &state.histograms[&key];
+        assert!(!hist.config.calibrated);
+        assert_eq!(hist.bins[500], 1);
+
+        let invalid = EnergyCalibration {
+            a: f64::NAN,
+            b: 1.0,
+            c: 0.0,
+        };
+        assert!(state.set_calibration(key, invalid).is_err());
+    }
+
+    #[test]
+    fn test_histogram_config_for_adc_bits() {
+        let config = HistogramConfig::for_adc_bits(12);
+        assert_eq!(config.max_value, 4096.0);
+        assert_eq!(config.num_bins, 4096);
+
+        let mut state = MonitorState::new(config);
+        state.process_event(&EventData::new(0, 0, 4094, 0, 0.0, 0));
+        state.process_event(&EventData::new(0, 0, 4095, 0, 0.0, 0));
+        state.process_event(&EventData::new(0, 0, 4096, 0, 0.0, 0));
+        let hist = &state.histograms[&ChannelKey::new(0, 0)];
+        assert_eq!(hist.bins.len(), 4096);
+        assert_eq!(hist.bins[4094], 1);
+        // The top code has its own bin; only values above it overflow
+        assert_eq!(hist.bins[4095], 1);
+        assert_eq!(hist.overflow, 1);
+
+        // Derived when deserializing a config without an explicit range
+        let config: HistogramConfig = serde_json::from_str(r#"{"adc_bits": 14}"#).unwrap();
+        assert_eq!(config.max_value, 16384.0);
+        assert_eq!(config.num_bins, 16384);
+    }
+
+    #[test]
+    fn test_fill_source_energy_short() {
+        let mut state = MonitorState::new(HistogramConfig {
+            num_bins: 1000,
+            min_value: 0.0,
+            max_value: 1000.0,
+            calibrated: false,
+            fill_source: FillSource::EnergyShort,
+            pedestal: 0.0,
+        });
+        state.process_event(&EventData::new(0, 0, 800, 300, 0.0, 0));
+
+        let hist = &state.histograms[&ChannelKey::new(0, 0)];
+        assert_eq!(hist.bins[300], 1);
+        assert_eq!(hist.bins[800], 0);
+    }
+
+    #[test]
+    fn test_fill_source_psd_ratio() {
+        let config: HistogramConfig =
+            serde_json::from_str(r#"{"fill_source": "psd_ratio"}"#).unwrap();
+        assert_eq!(config.min_value, 0.0);
+        assert_eq!(config.max_value, 1.0);
+        assert_eq!(config.num_bins, 1000);
+
+        let mut state = MonitorState::new(HistogramConfig {
+            num_bins: 4,
+            ..config
+        });
+        // (1000 - 250) / 1000 = 0.75 -> bin 3
+        state.process_event(&EventData::new(0, 0, 1000, 250, 0.0, 0));
+        // Zero energy has no ratio
+        state.process_event(&EventData::new(0, 0, 0, 0, 0.0, 0));
+
+        let hist = &state.histograms[&ChannelKey::new(0, 0)];
+        assert_eq!(hist.bins[3], 1);
+        assert_eq!(hist.total_counts, 1);
+    }
+
+    #[test]
+    fn test_rate_history_grows_and_trims() {
+        let mut state = MonitorState::new(HistogramConfig::default());
+        state.rate_history_len = 3;
+
+        for second in 1..=5u32 {
+            for _ in 0..10 {
+                state.process_event(&EventData::new(0, 0, 100, 0, 0.0, 0));
+            }
+            state.process_event(&EventData::new(1, 2, 100, 0, 0.0, 0));
+            state.record_rate_point(second as f64);
+            assert_eq!(state.rate_history.len(), (second as usize).min(3));
+        }
+
+        // Oldest points dropped; the latest three remain in order
+        let elapsed: Vec<f64> = state.rate_history.iter().map(|p| p.elapsed_secs).collect();
+        assert_eq!(elapsed, vec![3.0, 4.0, 5.0]);
+        let last = state.rate_history.back().unwrap();
+        assert_eq!(last.total_events, 55);
+        assert_eq!(last.channels.len(), 2);
+        assert_eq!(
+            (last.channels[0].module_id, last.channels[0].counts),
+            (0, 50)
+        );
+        assert_eq!(
+            (last.channels[1].module_id, last.channels[1].counts),
+            (1, 5)
+        );
+
+        state.clear();
+        assert!(state.rate_history.is_empty());
+    }
+
+    #[test]
+    fn test_changed_histograms_only_reports_deltas() {
+        let mut state = MonitorState::new(HistogramConfig::default());
+        let mut pushed = HashMap::new();
+        state.process_event(&EventData::new(0, 1, 100, 0, 0.0, 0));
+        state.process_event(&EventData::new(0, 2, 100, 0, 0.0, 0));
+
+        assert_eq!(state.changed_histograms(&mut pushed).len(), 2);
+        assert!(state.changed_histograms(&mut pushed).is_empty());
+
+        state.process_event(&EventData::new(0, 2, 200, 0, 0.0, 0));
+        let changed = state.changed_histograms(&mut pushed);
+        assert_eq!(changed.len(), 1);
+        assert_eq!(changed[0].channel_id, 2);
+    }
+
+    #[test]
+    fn test_summary_lists_sorted_counts() {
+        let mut state = MonitorState::new(HistogramConfig::default());
+        state.process_event(&EventData::new(1, 0, 100, 0, 0.0, 0));
+        state.process_event(&EventData::new(0, 5, 100, 0, 0.0, 0));
+        state.process_event(&EventData::new(0, 5, 200, 0, 0.0, 0));
+
+        let summary = state.summary();
+        assert_eq!(summary.total_events, 3);
+        let channels: Vec<_> = summary
+            .channels
+            .iter()
+            .map(|c| (c.module_id, c.channel_id, c.total_counts))
+            .collect();
+        assert_eq!(channels, vec![(0, 5, 2), (1, 0, 1)]);
+    }
+
+    #[test]
+    fn test_labels_in_summary_and_histogram() {
+        let config = MonitorConfig {
+            labels: HashMap::from([((0, 5), "HPGe-1".to_string())]),
+            ..Default::default()
+        };
+        let mut state = MonitorState::from_config(&config);
+        state.process_event(&EventData::new(0, 5, 100, 0, 0.0, 0));
+        state.process_event(&EventData::new(1, 0, 100, 0, 0.0, 0));
+
+        let json = serde_json::to_value(state.summary()).unwrap();
+        assert_eq!(json["channels"][0]["module_id"], 0);
+        assert_eq!(json["channels"][0]["channel_id"], 5);
+        assert_eq!(json["channels"][0]["label"], "HPGe-1");
+        // Unlabelled channels carry no label field
+        assert!(json["channels"][1].get("label").is_none());
+
+        // Re-created histograms keep the label
+        let key = ChannelKey::new(0, 5);
+        state
+            .set_calibration(
+                key,
+                EnergyCalibration {
+                    a: 0.0,
+                    b: 0.5,
+                    c: 0.0,
+                },
+            )
+            .unwrap();
+        assert_eq!(state.histograms[&key].label.as_deref(), Some("HPGe-1"));
+    }
+
+    #[test]
+    fn test_scalers_count_above_threshold() {
+        let config = MonitorConfig {
+            scaler_thresholds: HashMap::from([((0, 1), 500)]),
+            ..Default::default()
+        };
+        let mut state = MonitorState::from_config(&config);
+        for energy in [100, 500, 501, 2000] {
+            state.process_event(&EventData::new(0, 1, energy, 0, 0.0, 0));
+        }
+        // Channel without a threshold: everything above 0 counts
+        for energy in [0, 10] {
+            state.process_event(&EventData::new(0, 2, energy, 0, 0.0, 0));
+        }
+
+        let ch1 = ChannelKey::new(0, 1);
+        assert_eq!(state.scalers[&ch1], (4, 2));
+        assert_eq!(state.scalers[&ChannelKey::new(0, 2)], (2, 1));
+
+        let summary = state.scalers();
+        assert_eq!(summary.channels.len(), 2);
+        assert_eq!(summary.channels[0].threshold, 500);
+        assert_eq!(summary.channels[0].above_threshold, 2);
+        assert_eq!(summary.channels[1].threshold, 0);
+
+        // A new threshold restarts the channel's counts
+        state.set_scaler_threshold(ch1, 1000);
+        state.process_event(&EventData::new(0, 1, 800, 0, 0.0, 0));
+        state.process_event(&EventData::new(0, 1, 1200, 0, 0.0, 0));
+        assert_eq!(state.scalers[&ch1], (2, 1));
+
+        state.clear();
+        assert!(state.scalers.is_empty());
+        assert_eq!(state.scaler_thresholds[&ch1], 1000);
+    }
+
+    #[test]
+    fn test_export_has_one_entry_per_channel() {
+        let mut state = MonitorState::new(HistogramConfig {
+            num_bins: 100,
+            max_value: 1000.0,
+            ..HistogramConfig::default()
+        });
+        state.process_event(&EventData::new(2, 3, 500, 0, 0.0, 0));
+        state.process_event(&EventData::new(0, 1, 100, 0, 0.0, 0));
+        state.process_event(&EventData::new(0, 1, 2000, 0, 0.0, 0));
+
+        let export = state.export(Some(42));
+        assert!(export.file_name().starts_with("histograms_run0042_"));
+
+        // Survives the file encoding
+        let export = HistogramExport::from_msgpack(&export.to_msgpack().unwrap()).unwrap();
+        assert_eq!(export.run_number, Some(42));
+        assert_eq!(export.total_events, 3);
+        let channels: Vec<_> = export
+            .histograms
+            .iter()
+            .map(|h| (h.module_id, h.channel_id, h.total_counts, h.overflow))
+            .collect();
+        assert_eq!(channels, vec![(0, 1, 2, 1), (2, 3, 1, 0)]);
+        assert_eq!(export.histograms[0].bins[10], 1);
+        assert_eq!(export.histograms[1].bins.iter().sum::<u64>(), 1);
+    }
+
     #[test]
     fn test_atomic_stats() {
         let stats = AtomicStats::new();
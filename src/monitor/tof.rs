@@ -0,0 +1,197 @@
+//! Time-difference (TOF) histogram between two channels
+//!
+//! The histogram task keeps the last few timestamps of the reference and
+//! target channels. Whenever one of them fires, it is paired with every
+//! buffered timestamp of the other channel that lies within `window_ns`, and
+//! `t(target) - t(reference)` is filled. Each pair is counted once, by
+//! whichever of its two events is processed second.
+
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+
+use super::{ChannelKey, FillSource, Histogram1D, HistogramConfig, MAX_HISTOGRAM_BINS};
+use crate::common::EventData;
+
+/// Timestamps kept per channel for pairing
+const TOF_BUFFER_LEN: usize = 64;
+
+fn default_tof_bins() -> u32 {
+    1000
+}
+
+/// TOF pair configuration (`POST /api/tof`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TofConfig {
+    /// Start channel
+    pub reference: ChannelKey,
+    /// Stop channel
+    pub target: ChannelKey,
+    /// Coincidence window (ns); the histogram spans `-window_ns..window_ns`
+    pub window_ns: f64,
+    /// Number of bins (default: 1000)
+    #[serde(default = "default_tof_bins")]
+    pub num_bins: u32,
+}
+
+impl TofConfig {
+    /// Validate the configuration
+    pub fn validate(&self) -> Result<(), String> {
+        if self.reference == self.target {
+            return Err("reference and target must be different channels".to_string());
+        }
+        if !self.window_ns.is_finite() || self.window_ns <= 0.0 {
+            return Err(format!(
+                "window_ns must be a positive finite value, got {}",
+                self.window_ns
+            ));
+        }
+        if self.num_bins == 0 {
+            return Err("num_bins must be greater than 0".to_string());
+        }
+        if self.num_bins > MAX_HISTOGRAM_BINS {
+            return Err(format!(
+                "num_bins ({}) must not exceed {}",
+                self.num_bins, MAX_HISTOGRAM_BINS
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// TOF histogram with its pairing buffers
+#[derive(Debug, Clone, Serialize)]
+pub struct TofHistogram {
+    pub config: TofConfig,
+    pub histogram: Histogram1D,
+    #[serde(skip)]
+    reference_times: VecDeque<f64>,
+    #[serde(skip)]
+    target_times: VecDeque<f64>,
+}
+
+impl TofHistogram {
+    /// Create an empty TOF histogram
+    pub fn new(config: TofConfig) -> Result<Self, String> {
+        config.validate()?;
+        let window = config.window_ns as f32;
+        let histogram = Histogram1D::new(
+            config.target.module_id,
+            config.target.channel_id,
+            HistogramConfig {
+                num_bins: config.num_bins,
+                min_value: -window,
+                max_value: window,
+                calibrated: false,
+                fill_source: FillSource::Energy,
+                pedestal: 0.0,
+            },
+        );
+        Ok(Self {
+            config,
+            histogram,
+            reference_times: VecDeque::with_capacity(TOF_BUFFER_LEN),
+            target_times: VecDeque::with_capacity(TOF_BUFFER_LEN),
+        })
+    }
+
+    /// Pair an event with buffered timestamps of the other channel
+    pub fn process_event(&mut self, event: &EventData) {
+        let key = ChannelKey::new(event.module as u32, event.channel as u32);
+        let ts = event.timestamp_ns;
+
+        if key == self.config.reference {
+            for &target in &self.target_times {
+                fill_within(&mut self.histogram, target - ts, self.config.window_ns);
+            }
+            push_bounded(&mut self.reference_times, ts);
+        } else if key == self.config.target {
+            for &reference in &self.reference_times {
+                fill_within(&mut self.histogram, ts - reference, self.config.window_ns);
+            }
+            push_bounded(&mut self.target_times, ts);
+        }
+    }
+
+    /// Clear the histogram and the pairing buffers
+    pub fn clear(&mut self) {
+        self.histogram.clear();
+        self.reference_times.clear();
+        self.target_times.clear();
+    }
+}
+
+fn fill_within(histogram: &mut Histogram1D, diff: f64, window_ns: f64) {
+    if diff.abs() < window_ns {
+        histogram.fill(diff as f32);
+    }
+}
+
+fn push_bounded(times: &mut VecDeque<f64>, ts: f64) {
+    if times.len() == TOF_BUFFER_LEN {
+        times.pop_front();
+    }
+    times.push_back(ts);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> TofConfig {
+        TofConfig {
+            reference: ChannelKey::new(0, 0),
+            target: ChannelKey::new(0, 1),
+            window_ns: 100.0,
+            num_bins: 200,
+        }
+    }
+
+    #[test]
+    fn test_tof_peak_at_offset() {
+        let mut tof = TofHistogram::new(config()).unwrap();
+
+        // Pairs 10 us apart; target 25 ns after reference, alternating arrival order
+        for i in 0..100 {
+            let t0 = i as f64 * 10_000.0;
+            let reference = EventData::new(0, 0, 100, 0, t0, 0);
+            let target = EventData::new(0, 1, 100, 0, t0 + 25.0, 0);
+            if i % 2 == 0 {
+                tof.process_event(&reference);
+                tof.process_event(&target);
+            } else {
+                tof.process_event(&target);
+                tof.process_event(&reference);
+            }
+            // Unrelated channel is ignored
+            tof.process_event(&EventData::new(1, 0, 100, 0, t0 + 5.0, 0));
+        }
+
+        let hist = &tof.histogram;
+        assert_eq!(hist.total_counts, 100);
+        // 1 ns bins starting at -100 ns: +25 ns lands in bin 125
+        let peak = hist
+            .bins
+            .iter()
+            .enumerate()
+            .max_by_key(|(_, &c)| c)
+            .map(|(i, _)| i)
+            .unwrap();
+        assert_eq!(peak, 125);
+        assert_eq!(hist.bins[125], 100);
+
+        tof.clear();
+        assert_eq!(tof.histogram.total_counts, 0);
+    }
+
+    #[test]
+    fn test_tof_config_rejected() {
+        let mut same_channel = config();
+        same_channel.target = same_channel.reference;
+        assert!(TofHistogram::new(same_channel).is_err());
+
+        let mut zero_window = config();
+        zero_window.window_ns = 0.0;
+        assert!(TofHistogram::new(zero_window).is_err());
+    }
+}
@@ -4,10 +4,10 @@ use std::collections::BTreeMap;
 use std::time::Duration;
 
 use futures::future::join_all;
-use tmq::{request_reply, Context};
+use tmq::{request_reply, Context, SocketExt};
 use tokio::time::timeout;
 
-use crate::common::{Command, CommandResponse, ComponentState, RunConfig};
+use crate::common::{Command, CommandResponse, ComponentState, RecorderTuning, RunConfig};
 
 use super::{CommandResult, ComponentConfig, ComponentStatus};
 
@@ -37,6 +37,11 @@ impl ComponentClient {
         let requester = request_reply::request(&self.context)
             .connect(address)
             .map_err(|e| format!("Failed to connect to {}: {}", address, e))?;
+        // A command left unsent to an offline component must not block
+        // closing the socket (and dropping the context) forever
+        requester
+            .set_linger(0)
+            .map_err(|e| format!("Failed to set linger for {}: {}", address, e))?;
 
         // Serialize command
         let cmd_bytes = command
@@ -83,6 +88,7 @@ impl ComponentClient {
                     None
                 },
                 online: true,
+                version: response.version,
             },
             Err(e) => ComponentStatus {
                 name: config.name.clone(),
@@ -92,6 +98,7 @@ impl ComponentClient {
                 metrics: None,
                 error: Some(e),
                 online: false,
+                version: None,
             },
         }
     }
@@ -136,6 +143,16 @@ impl ComponentClient {
         self.execute_command(config, Command::Reset).await
     }
 
+    /// Send new write thresholds to a Recorder
+    pub async fn update_recorder_tuning(
+        &self,
+        config: &ComponentConfig,
+        tuning: RecorderTuning,
+    ) -> CommandResult {
+        self.execute_command(config, Command::UpdateRecorderTuning(tuning))
+            .await
+    }
+
     /// Execute a command and return CommandResult
     async fn execute_command(&self, config: &ComponentConfig, command: Command) -> CommandResult {
         match self.send_command(&config.address, &command).await {
@@ -431,6 +448,27 @@ impl ComponentClient {
         results
     }
 
+    /// Return every component to Idle after a failed bring-up
+    ///
+    /// Components still in a run are stopped first (upstream first) so the
+    /// run is closed cleanly, then all components are reset.
+    pub async fn rollback_all(&self, configs: &[ComponentConfig]) -> Vec<CommandResult> {
+        let statuses = self.get_all_status(configs).await;
+        let in_run: Vec<_> = configs
+            .iter()
+            .filter(|c| {
+                statuses
+                    .iter()
+                    .any(|s| s.name == c.name && s.online && s.state.in_run())
+            })
+            .cloned()
+            .collect();
+
+        let mut results = self.stop_all(&in_run).await;
+        results.extend(self.reset_all(configs).await);
+        results
+    }
+
     /// Wait for all components to reach the expected state
     /// Returns true if all reached the state, false if timeout
     pub async fn wait_for_state(
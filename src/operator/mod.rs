@@ -12,9 +12,9 @@ pub use client::ComponentClient;
 pub use digitizer_repository::{
     DigitizerConfigDocument, DigitizerConfigRepository, DigitizerRepoError, RunConfigSnapshot,
 };
-pub use routes::{EmulatorSettings, RouterBuilder};
+pub use routes::{EmulatorSettings, OperatorTasks, RouterBuilder};
 pub use run_repository::{
-    CurrentRunInfo, ErrorLogEntry, LastRunInfo, RepositoryError, RunDocument, RunNote,
+    CurrentRunInfo, ErrorLogEntry, LastRunInfo, RepositoryError, RunDocument, RunLimits, RunNote,
     RunRepository, RunStats, RunStatus,
 };
 
@@ -22,6 +22,7 @@ use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
 
 use crate::common::{ComponentMetrics, ComponentState, RunConfig};
+use crate::config::Config;
 
 /// Component status returned by status endpoint
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
@@ -43,6 +44,20 @@ pub struct ComponentStatus {
     pub error: Option<String>,
     /// Whether communication succeeded
     pub online: bool,
+    /// Build version reported by the component (None if offline or older)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+}
+
+impl ComponentStatus {
+    /// Names of components whose reported version differs from `expected`
+    pub fn version_mismatch(components: &[ComponentStatus], expected: &str) -> Vec<String> {
+        components
+            .iter()
+            .filter(|c| c.version.as_deref().is_some_and(|v| v != expected))
+            .map(|c| c.name.clone())
+            .collect()
+    }
 }
 
 /// System-wide status
@@ -63,6 +78,9 @@ pub struct SystemStatus {
     /// Last run info for pre-filling comment (comment + notes from previous run)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub last_run_info: Option<LastRunInfo>,
+    /// Components built from a different version than the Operator
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub version_mismatch: Vec<String>,
 }
 
 /// Aggregated system state
@@ -76,6 +94,8 @@ pub enum SystemState {
     Armed,
     /// All components are Running
     Running,
+    /// All components are Paused
+    Paused,
     /// At least one component is in Error
     Error,
     /// Components are in mixed states
@@ -107,6 +127,7 @@ impl SystemState {
                 ComponentState::Configured => SystemState::Configured,
                 ComponentState::Armed => SystemState::Armed,
                 ComponentState::Running => SystemState::Running,
+                ComponentState::Paused => SystemState::Paused,
                 ComponentState::Error => SystemState::Error,
             }
         } else {
@@ -146,6 +167,67 @@ pub struct StartRequest {
     /// Comment for this run (optional, stored in MongoDB)
     #[serde(default)]
     pub comment: String,
+    /// Stop the run automatically once this many events have been recorded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stop_after_events: Option<u64>,
+    /// Stop the run automatically after this many seconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stop_after_secs: Option<u64>,
+}
+
+/// Request body for bring-up (configure, arm and start in one call)
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct BringUpRequest {
+    /// Run number
+    pub run_number: u32,
+    /// Optional comment (stored in MongoDB)
+    #[serde(default)]
+    pub comment: String,
+    /// Experiment name (used in output filenames)
+    #[serde(default)]
+    pub exp_name: String,
+    /// Stop the run automatically once this many events have been recorded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stop_after_events: Option<u64>,
+    /// Stop the run automatically after this many seconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stop_after_secs: Option<u64>,
+}
+
+impl BringUpRequest {
+    /// Configuration sent to the components
+    pub fn run_config(&self) -> RunConfig {
+        RunConfig {
+            run_number: self.run_number,
+            comment: self.comment.clone(),
+            exp_name: self.exp_name.clone(),
+        }
+    }
+
+    /// Automatic stop conditions requested for the run
+    pub fn limits(&self) -> RunLimits {
+        RunLimits {
+            stop_after_events: self.stop_after_events,
+            stop_after_secs: self.stop_after_secs,
+        }
+    }
+}
+
+/// Request body for aborting a run
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct AbortRequest {
+    /// Why the run was aborted (stored with the run, e.g. "beam lost")
+    pub reason: String,
+}
+
+impl StartRequest {
+    /// Automatic stop conditions requested for the run
+    pub fn limits(&self) -> RunLimits {
+        RunLimits {
+            stop_after_events: self.stop_after_events,
+            stop_after_secs: self.stop_after_secs,
+        }
+    }
 }
 
 /// Generic API response
@@ -199,7 +281,7 @@ impl ApiResponse {
 }
 
 /// Component configuration (from config file)
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct ComponentConfig {
     pub name: String,
     pub address: String,
@@ -222,6 +304,87 @@ pub struct ComponentConfig {
     pub is_digitizer: bool,
 }
 
+/// Build the component list from a parsed config file
+///
+/// Bind addresses (`tcp://*:port`) become connect addresses on localhost.
+pub fn components_from_config(config: &Config) -> Vec<ComponentConfig> {
+    let mut components = Vec::new();
+
+    // Add sources (emulators/readers)
+    for source in &config.network.sources {
+        let name = if source.name.is_empty() {
+            format!("Source {}", source.id)
+        } else {
+            source.name.clone()
+        };
+        // Convert bind address (tcp://*:port) to connect address (tcp://localhost:port)
+        let address = source
+            .command_address()
+            .replace("tcp://*:", "tcp://localhost:");
+        components.push(ComponentConfig {
+            name,
+            address,
+            pipeline_order: source.pipeline_order,
+            is_master: source.is_master_digitizer(),
+            source_id: Some(source.id),
+            is_digitizer: source.is_digitizer(),
+        });
+    }
+
+    // Add merger
+    if let Some(ref merger) = config.network.merger {
+        let address = merger
+            .command
+            .clone()
+            .unwrap_or_else(|| "tcp://*:5570".to_string())
+            .replace("tcp://*:", "tcp://localhost:");
+        components.push(ComponentConfig {
+            name: "Merger".to_string(),
+            address,
+            pipeline_order: merger.pipeline_order,
+            is_master: false,
+            source_id: None,
+            is_digitizer: false,
+        });
+    }
+
+    // Add recorder
+    if let Some(ref recorder) = config.network.recorder {
+        let address = recorder
+            .command
+            .clone()
+            .unwrap_or_else(|| "tcp://*:5580".to_string())
+            .replace("tcp://*:", "tcp://localhost:");
+        components.push(ComponentConfig {
+            name: "Recorder".to_string(),
+            address,
+            pipeline_order: recorder.pipeline_order,
+            is_master: false,
+            source_id: None,
+            is_digitizer: false,
+        });
+    }
+
+    // Add monitor
+    if let Some(ref monitor) = config.network.monitor {
+        let address = monitor
+            .command
+            .clone()
+            .unwrap_or_else(|| "tcp://*:5590".to_string())
+            .replace("tcp://*:", "tcp://localhost:");
+        components.push(ComponentConfig {
+            name: "Monitor".to_string(),
+            address,
+            pipeline_order: monitor.pipeline_order,
+            is_master: false,
+            source_id: None,
+            is_digitizer: false,
+        });
+    }
+
+    components
+}
+
 /// Operator configuration with timeouts
 #[derive(Debug, Clone)]
 pub struct OperatorConfig {
@@ -233,6 +396,8 @@ pub struct OperatorConfig {
     pub start_timeout_ms: u64,
     /// Experiment name (server-authoritative, from config file)
     pub experiment_name: String,
+    /// Interval between WebSocket status pushes (ms)
+    pub status_interval_ms: u64,
 }
 
 impl Default for OperatorConfig {
@@ -242,6 +407,7 @@ impl Default for OperatorConfig {
             arm_timeout_ms: 5000,
             start_timeout_ms: 5000,
             experiment_name: "DefaultExp".to_string(),
+            status_interval_ms: 1000,
         }
     }
 }
@@ -263,6 +429,7 @@ mod tests {
                 None
             },
             online,
+            version: None,
         }
     }
 
@@ -488,6 +655,23 @@ mod tests {
         assert_eq!(deserialized.state, ComponentState::Running);
     }
 
+    #[test]
+    fn test_version_mismatch() {
+        let mut components = vec![
+            make_status("A", ComponentState::Idle, true),
+            make_status("B", ComponentState::Idle, true),
+            make_status("C", ComponentState::Idle, false),
+        ];
+        components[0].version = Some("0.1.0 (abc1234)".to_string());
+        components[1].version = Some("0.1.0 (def5678)".to_string());
+
+        // Components without a version (offline or older builds) are not flagged
+        assert_eq!(
+            ComponentStatus::version_mismatch(&components, "0.1.0 (abc1234)"),
+            vec!["B".to_string()]
+        );
+    }
+
     #[test]
     fn test_system_status_serialization() {
         let status = SystemStatus {
@@ -497,6 +681,7 @@ mod tests {
             experiment_name: "TestExp".to_string(),
             next_run_number: Some(1),
             last_run_info: None,
+            version_mismatch: Vec::new(),
         };
         let json = serde_json::to_string(&status).unwrap();
         assert!(json.contains("\"system_state\":\"Idle\""));
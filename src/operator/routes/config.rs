@@ -0,0 +1,234 @@
+//! Network config hot-reload handler
+
+use std::sync::Arc;
+
+use axum::{extract::State, http::StatusCode, Json};
+use serde::{Deserialize, Serialize};
+use tokio::sync::RwLock;
+use tracing::info;
+use utoipa::ToSchema;
+
+use crate::config::Config;
+
+use super::super::{components_from_config, ComponentConfig, RunStatus};
+use super::AppState;
+
+/// Result of a config reload
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct ConfigReloadResponse {
+    /// Whether the new component list was applied
+    pub success: bool,
+    /// Human-readable message
+    pub message: String,
+    /// Components that were added
+    pub added: Vec<String>,
+    /// Components that were removed
+    pub removed: Vec<String>,
+    /// Components whose address or role changed
+    pub changed: Vec<String>,
+}
+
+impl ConfigReloadResponse {
+    fn error(message: impl Into<String>) -> Self {
+        Self {
+            success: false,
+            message: message.into(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Diff `new` against the current components and swap them in
+///
+/// While a run is active, removing or changing a component would pull its
+/// address out from under the run, so such reloads are rejected; adding
+/// components is allowed.
+pub(super) async fn apply_components(
+    components: &RwLock<Vec<ComponentConfig>>,
+    new: Vec<ComponentConfig>,
+    run_active: bool,
+) -> Result<ConfigReloadResponse, String> {
+    let mut current = components.write().await;
+
+    let added: Vec<String> = new
+        .iter()
+        .filter(|c| !current.iter().any(|old| old.name == c.name))
+        .map(|c| c.name.clone())
+        .collect();
+    let removed: Vec<&ComponentConfig> = current
+        .iter()
+        .filter(|old| !new.iter().any(|c| c.name == old.name))
+        .collect();
+    let changed: Vec<&ComponentConfig> = current
+        .iter()
+        .filter(|old| new.iter().any(|c| c.name == old.name && c != *old))
+        .collect();
+
+    if run_active && (!removed.is_empty() || !changed.is_empty()) {
+        let in_use: Vec<String> = removed
+            .iter()
+            .chain(changed.iter())
+            .map(|c| format!("{} ({})", c.name, c.address))
+            .collect();
+        return Err(format!(
+            "Cannot remove or change components during an active run: {}",
+            in_use.join(", ")
+        ));
+    }
+
+    let removed: Vec<String> = removed.iter().map(|c| c.name.clone()).collect();
+    let changed: Vec<String> = changed.iter().map(|c| c.name.clone()).collect();
+    for name in &added {
+        info!(component = %name, "Config reload: component added");
+    }
+    for name in &removed {
+        info!(component = %name, "Config reload: component removed");
+    }
+    for name in &changed {
+        info!(component = %name, "Config reload: component changed");
+    }
+
+    *current = new;
+    Ok(ConfigReloadResponse {
+        success: true,
+        message: format!("Reloaded {} component(s)", current.len()),
+        added,
+        removed,
+        changed,
+    })
+}
+
+/// Re-read the config file and update the component list
+#[utoipa::path(
+    post,
+    path = "/api/config/reload",
+    tag = "DAQ Control",
+    responses(
+        (status = 200, description = "Component list reloaded", body = ConfigReloadResponse),
+        (status = 400, description = "Config could not be loaded", body = ConfigReloadResponse),
+        (status = 409, description = "Components in use by the active run", body = ConfigReloadResponse)
+    )
+)]
+pub(super) async fn reload_config(
+    State(state): State<Arc<AppState>>,
+) -> (StatusCode, Json<ConfigReloadResponse>) {
+    let Some(ref path) = state.config_path else {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ConfigReloadResponse::error(
+                "Operator was started without a config file",
+            )),
+        );
+    };
+
+    let config = match Config::load(path) {
+        Ok(config) => config,
+        Err(e) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(ConfigReloadResponse::error(format!(
+                    "Failed to load {}: {}",
+                    path.display(),
+                    e
+                ))),
+            );
+        }
+    };
+
+    let run_active = state
+        .current_run
+        .read()
+        .await
+        .as_ref()
+        .is_some_and(|run| run.status == RunStatus::Running);
+
+    match apply_components(
+        &state.components,
+        components_from_config(&config),
+        run_active,
+    )
+    .await
+    {
+        Ok(response) => (StatusCode::OK, Json(response)),
+        Err(e) => (StatusCode::CONFLICT, Json(ConfigReloadResponse::error(e))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BASE: &str = r#"
+[network]
+[[network.sources]]
+id = 0
+name = "Emulator 0"
+bind = "tcp://*:5555"
+command = "tcp://*:5560"
+
+[network.recorder]
+subscribe = "tcp://localhost:5557"
+output_dir = "./data"
+command = "tcp://*:5580"
+"#;
+
+    const ADDED: &str = r#"
+[[network.sources]]
+id = 1
+name = "Emulator 1"
+bind = "tcp://*:5556"
+command = "tcp://*:5561"
+"#;
+
+    fn components(toml: &str) -> Vec<ComponentConfig> {
+        components_from_config(&Config::from_toml(toml).unwrap())
+    }
+
+    fn with_added_source() -> String {
+        format!("{}{}", BASE, ADDED)
+    }
+
+    #[tokio::test]
+    async fn test_reload_adds_component() {
+        let state = RwLock::new(components(BASE));
+        assert_eq!(state.read().await.len(), 2);
+
+        let response = apply_components(&state, components(&with_added_source()), false)
+            .await
+            .unwrap();
+        assert!(response.success);
+        assert_eq!(response.added, vec!["Emulator 1".to_string()]);
+        assert!(response.removed.is_empty());
+        assert!(response.changed.is_empty());
+
+        let current = state.read().await;
+        assert_eq!(current.len(), 3);
+        assert!(current
+            .iter()
+            .any(|c| c.name == "Emulator 1" && c.address == "tcp://localhost:5561"));
+    }
+
+    #[tokio::test]
+    async fn test_reload_rejects_changes_during_run() {
+        let state = RwLock::new(components(&with_added_source()));
+        let moved = with_added_source().replace("tcp://*:5561", "tcp://*:5562");
+
+        // Changing an address in use by the active run is rejected
+        let err = apply_components(&state, components(&moved), true)
+            .await
+            .unwrap_err();
+        assert!(err.contains("Emulator 1 (tcp://localhost:5561)"));
+        assert_eq!(state.read().await[1].address, "tcp://localhost:5561");
+
+        let response = apply_components(&state, components(&moved), false)
+            .await
+            .unwrap();
+        assert_eq!(response.changed, vec!["Emulator 1".to_string()]);
+        assert_eq!(state.read().await[1].address, "tcp://localhost:5562");
+
+        let response = apply_components(&state, components(BASE), false)
+            .await
+            .unwrap();
+        assert_eq!(response.removed, vec!["Emulator 1".to_string()]);
+    }
+}
@@ -12,6 +12,7 @@ use utoipa::ToSchema;
 
 use crate::common::Command;
 use crate::config::DigitizerConfig;
+use crate::reader::caen::CaenHandle;
 
 use super::super::{ApiResponse, DigitizerConfigDocument};
 use super::AppState;
@@ -75,6 +76,29 @@ pub struct RestoreVersionRequest {
     pub version: u32,
 }
 
+/// Request body for offline digitizer config validation
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ValidateDigitizerRequest {
+    /// Digitizer configuration in the same JSON form as the config files
+    #[schema(value_type = Object)]
+    pub config: serde_json::Value,
+    /// DevTree of the target device (e.g. saved with `caen_info`); enables range checks
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<Object>)]
+    pub devtree: Option<serde_json::Value>,
+}
+
+/// Result of offline digitizer config validation
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ValidateDigitizerResponse {
+    /// True if no problems were found
+    pub valid: bool,
+    /// One message per problem (parse errors, structure, out-of-range parameters)
+    pub problems: Vec<String>,
+    /// Whether parameter values were checked against the DevTree limits
+    pub range_checked: bool,
+}
+
 /// List all digitizer configurations
 #[utoipa::path(
     get,
@@ -112,7 +136,8 @@ pub(super) async fn detect_digitizers(
     State(state): State<Arc<AppState>>,
 ) -> (StatusCode, Json<DetectResponse>) {
     // Filter for physical digitizer components
-    let digitizer_components: Vec<_> = state.components.iter().filter(|c| c.is_digitizer).collect();
+    let components = state.components().await;
+    let digitizer_components: Vec<_> = components.iter().filter(|c| c.is_digitizer).collect();
 
     if digitizer_components.is_empty() {
         return (
@@ -278,6 +303,55 @@ pub(super) async fn get_digitizer(
     })
 }
 
+/// Validate a digitizer configuration without touching a device
+///
+/// Parses the posted JSON as a DigitizerConfig and checks its structure. If a
+/// DevTree is included, every generated parameter is also checked against the
+/// limits it declares, as the Reader does before Configure.
+#[utoipa::path(
+    post,
+    path = "/api/digitizer/validate",
+    tag = "Digitizer Config",
+    request_body = ValidateDigitizerRequest,
+    responses(
+        (status = 200, description = "Validation result", body = ValidateDigitizerResponse)
+    )
+)]
+pub(super) async fn validate_digitizer(
+    Json(request): Json<ValidateDigitizerRequest>,
+) -> Json<ValidateDigitizerResponse> {
+    let config: DigitizerConfig = match serde_json::from_value(request.config) {
+        Ok(config) => config,
+        Err(e) => {
+            return Json(ValidateDigitizerResponse {
+                valid: false,
+                problems: vec![format!("Invalid digitizer config: {}", e)],
+                range_checked: false,
+            })
+        }
+    };
+
+    let mut problems = config.check_structure();
+    // Parameter generation needs a consistent config (e.g. a channel range)
+    let range_checked = match &request.devtree {
+        Some(tree) if problems.is_empty() => {
+            problems.extend(
+                CaenHandle::validate_config_with_tree(tree, &config)
+                    .iter()
+                    .map(|violation| violation.to_string()),
+            );
+            true
+        }
+        _ => false,
+    };
+
+    Json(ValidateDigitizerResponse {
+        valid: problems.is_empty(),
+        problems,
+        range_checked,
+    })
+}
+
 /// Update a digitizer configuration (in memory)
 ///
 /// Updates the configuration in memory. Use POST /api/digitizers/{id}/save to persist to disk.
@@ -630,3 +704,79 @@ pub(super) async fn restore_digitizer_version(
         ),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn psd2_config(trigger_threshold: u32) -> serde_json::Value {
+        serde_json::json!({
+            "digitizer_id": 0,
+            "name": "PSD2 test",
+            "firmware": "PSD2",
+            "num_channels": 32,
+            "board": { "start_source": "SWcmd" },
+            "channel_defaults": {
+                "dc_offset": 50.0,
+                "polarity": "Negative",
+                "trigger_threshold": trigger_threshold
+            },
+            "channel_overrides": { "16": { "enabled": "True" } }
+        })
+    }
+
+    /// Channel folder of a DevTree declaring only the TriggerThr limits
+    fn devtree() -> serde_json::Value {
+        serde_json::json!({
+            "ch": { "0": { "par": { "TriggerThr": {
+                "datatype": { "value": "NUMBER" },
+                "minvalue": { "value": "0" },
+                "maxvalue": { "value": "16383" }
+            } } } }
+        })
+    }
+
+    async fn validate(config: serde_json::Value) -> ValidateDigitizerResponse {
+        validate_digitizer(Json(ValidateDigitizerRequest {
+            config,
+            devtree: Some(devtree()),
+        }))
+        .await
+        .0
+    }
+
+    #[tokio::test]
+    async fn test_validate_good_config() {
+        let response = validate(psd2_config(1000)).await;
+        assert!(response.valid);
+        assert!(response.problems.is_empty(), "{:?}", response.problems);
+        assert!(response.range_checked);
+    }
+
+    #[tokio::test]
+    async fn test_validate_reports_out_of_range_parameter() {
+        let response = validate(psd2_config(20000)).await;
+        assert!(!response.valid);
+        assert_eq!(
+            response.problems,
+            vec!["/ch/0..31/par/TriggerThr = 20000 is out of range (allowed: [0, 16383])"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_validate_reports_structure_and_parse_errors() {
+        let mut config = psd2_config(20000);
+        config["num_channels"] = serde_json::json!(8);
+        let response = validate(config).await;
+        // Range checks are skipped until the structure is consistent
+        assert!(!response.range_checked);
+        assert_eq!(
+            response.problems,
+            vec!["channel_overrides has channel 16, but the digitizer has 8 channels"]
+        );
+
+        let response = validate(serde_json::json!({ "name": "no id" })).await;
+        assert!(!response.valid);
+        assert!(response.problems[0].starts_with("Invalid digitizer config"));
+    }
+}
@@ -91,15 +91,15 @@ pub(super) async fn update_emulator_settings(
 
     // Filter for Emulator/Source components
     let emulator_components: Vec<_> = state
-        .components
-        .iter()
+        .components()
+        .await
+        .into_iter()
         .filter(|c| {
             c.name.to_lowercase().contains("emulator")
                 || c.name.to_lowercase().contains("source")
                 || c.name.to_lowercase().contains("digitizer")
                 || c.pipeline_order == 1 // upstream data source
         })
-        .cloned()
         .collect();
 
     if emulator_components.is_empty() {
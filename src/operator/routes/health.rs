@@ -0,0 +1,86 @@
+//! Liveness and readiness probes
+//!
+//! `GET /healthz` answers 200 as long as the Operator process serves HTTP.
+//! `GET /readyz` queries every component like `GET /api/status` and answers
+//! 503 while the system is `Degraded` (a component is offline) or in
+//! `Error`, so load balancers and orchestrators can hold traffic back.
+
+use std::sync::Arc;
+
+use axum::{extract::State, http::StatusCode, Json};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use super::super::SystemState;
+use super::AppState;
+
+/// Readiness probe result
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ReadinessResponse {
+    /// Whether the system can take commands and data
+    pub ready: bool,
+    /// Aggregated system state
+    pub system_state: SystemState,
+}
+
+/// Whether a system in `state` is ready
+fn is_ready(state: SystemState) -> bool {
+    !matches!(state, SystemState::Degraded | SystemState::Error)
+}
+
+/// Liveness probe
+#[utoipa::path(
+    get,
+    path = "/healthz",
+    tag = "DAQ Control",
+    responses(
+        (status = 200, description = "Operator is up", body = String, content_type = "text/plain")
+    )
+)]
+pub(super) async fn healthz() -> &'static str {
+    "ok"
+}
+
+/// Readiness probe (503 when Degraded or Error)
+#[utoipa::path(
+    get,
+    path = "/readyz",
+    tag = "DAQ Control",
+    responses(
+        (status = 200, description = "All components online and healthy", body = ReadinessResponse),
+        (status = 503, description = "A component is offline or in Error", body = ReadinessResponse)
+    )
+)]
+pub(super) async fn readyz(
+    State(state): State<Arc<AppState>>,
+) -> (StatusCode, Json<ReadinessResponse>) {
+    let components = state.client.get_all_status(&state.components().await).await;
+    let system_state = SystemState::from_components(&components);
+    let ready = is_ready(system_state);
+    let status = if ready {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    (
+        status,
+        Json(ReadinessResponse {
+            ready,
+            system_state,
+        }),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_readiness_by_state() {
+        assert!(is_ready(SystemState::Idle));
+        assert!(is_ready(SystemState::Running));
+        assert!(is_ready(SystemState::Mixed));
+        assert!(!is_ready(SystemState::Degraded));
+        assert!(!is_ready(SystemState::Error));
+    }
+}
@@ -0,0 +1,309 @@
+//! Prometheus metrics endpoint
+//!
+//! `GET /metrics` queries every component's status and renders it in the
+//! Prometheus text exposition format. Offline components only report
+//! `delila_up 0`; per-component counters are omitted for them rather than
+//! reported as zero, so dashboards show a gap instead of a false drop.
+
+use std::fmt::Write;
+use std::sync::Arc;
+
+use axum::{extract::State, http::header};
+
+use super::super::{ComponentStatus, SystemState};
+use super::AppState;
+
+/// Content type of the Prometheus text format
+const CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// All system states, in the order they are emitted
+const SYSTEM_STATES: [SystemState; 7] = [
+    SystemState::Idle,
+    SystemState::Configured,
+    SystemState::Armed,
+    SystemState::Running,
+    SystemState::Error,
+    SystemState::Mixed,
+    SystemState::Degraded,
+];
+
+/// Component metrics in Prometheus text format
+#[utoipa::path(
+    get,
+    path = "/metrics",
+    tag = "DAQ Control",
+    responses(
+        (status = 200, description = "Metrics in Prometheus text format", body = String, content_type = "text/plain")
+    )
+)]
+pub(super) async fn get_metrics(
+    State(state): State<Arc<AppState>>,
+) -> ([(header::HeaderName, &'static str); 1], String) {
+    let components = state.client.get_all_status(&state.components().await).await;
+    let system_state = SystemState::from_components(&components);
+    (
+        [(header::CONTENT_TYPE, CONTENT_TYPE)],
+        render_metrics(&components, system_state),
+    )
+}
+
+/// Render component statuses as Prometheus gauges
+fn render_metrics(components: &[ComponentStatus], system_state: SystemState) -> String {
+    let mut out = String::new();
+
+    gauge_header(
+        &mut out,
+        "delila_up",
+        "Whether the component answered GetStatus",
+    );
+    for c in components {
+        sample(&mut out, "delila_up", &c.name, u8::from(c.online));
+    }
+
+    let online: Vec<_> = components
+        .iter()
+        .filter(|c| c.online)
+        .filter_map(|c| c.metrics.as_ref().map(|m| (c.name.as_str(), m)))
+        .collect();
+
+    gauge_header(
+        &mut out,
+        "delila_events_processed",
+        "Total events processed since start",
+    );
+    for (name, m) in &online {
+        sample(
+            &mut out,
+            "delila_events_processed",
+            name,
+            m.events_processed,
+        );
+    }
+    gauge_header(
+        &mut out,
+        "delila_bytes_transferred",
+        "Total bytes transferred since start",
+    );
+    for (name, m) in &online {
+        sample(
+            &mut out,
+            "delila_bytes_transferred",
+            name,
+            m.bytes_transferred,
+        );
+    }
+    gauge_header(&mut out, "delila_event_rate", "Events per second");
+    for (name, m) in &online {
+        sample(&mut out, "delila_event_rate", name, m.event_rate);
+    }
+    gauge_header(&mut out, "delila_data_rate", "Bytes per second");
+    for (name, m) in &online {
+        sample(&mut out, "delila_data_rate", name, m.data_rate);
+    }
+    gauge_header(&mut out, "delila_queue_size", "Current queue size");
+    for (name, m) in &online {
+        sample(&mut out, "delila_queue_size", name, m.queue_size);
+    }
+    gauge_header(
+        &mut out,
+        "delila_queue_max",
+        "Highest queue size since run start",
+    );
+    for (name, m) in &online {
+        sample(&mut out, "delila_queue_max", name, m.queue_max);
+    }
+
+    gauge_header(
+        &mut out,
+        "delila_dead_time_fraction",
+        "Fraction of a channel's events flagged with lost triggers in the current run",
+    );
+    for (name, m) in &online {
+        for ch in &m.channel_dead_time {
+            let _ = writeln!(
+                out,
+                "delila_dead_time_fraction{{component=\"{}\",channel=\"{}\"}} {}",
+                escape_label(name),
+                ch.channel,
+                ch.busy_fraction
+            );
+        }
+    }
+
+    let filters: Vec<_> = online
+        .iter()
+        .filter_map(|(name, m)| m.event_filter.map(|f| (*name, f)))
+        .collect();
+    gauge_header(
+        &mut out,
+        "delila_filter_passed",
+        "Events passed by the source event filter since start",
+    );
+    for (name, f) in &filters {
+        sample(&mut out, "delila_filter_passed", name, f.passed);
+    }
+    gauge_header(
+        &mut out,
+        "delila_filter_dropped",
+        "Events dropped by the source event filter since start",
+    );
+    for (name, f) in &filters {
+        sample(&mut out, "delila_filter_dropped", name, f.dropped);
+    }
+
+    gauge_header(
+        &mut out,
+        "delila_system_state",
+        "Aggregated system state (1 for the current state)",
+    );
+    for s in SYSTEM_STATES {
+        let _ = writeln!(
+            out,
+            "delila_system_state{{state=\"{:?}\"}} {}",
+            s,
+            u8::from(s == system_state)
+        );
+    }
+
+    out
+}
+
+fn gauge_header(out: &mut String, name: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} gauge", name);
+}
+
+fn sample(out: &mut String, name: &str, component: &str, value: impl std::fmt::Display) {
+    let _ = writeln!(
+        out,
+        "{}{{component=\"{}\"}} {}",
+        name,
+        escape_label(component),
+        value
+    );
+}
+
+/// Escape a label value (backslash, double quote and newline)
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::ComponentConfig;
+    use super::super::RouterBuilder;
+    use crate::common::{
+        ChannelDeadTime, Command, CommandResponse, ComponentMetrics, ComponentState, FilterCounts,
+    };
+    use std::time::Duration;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    fn component(name: &str, address: &str, pipeline_order: u32) -> ComponentConfig {
+        ComponentConfig {
+            name: name.to_string(),
+            address: address.to_string(),
+            pipeline_order,
+            is_master: false,
+            source_id: None,
+            is_digitizer: false,
+        }
+    }
+
+    /// Answer GetStatus requests like a running Reader
+    async fn mock_reader(address: &str) {
+        let context = tmq::Context::new();
+        let mut receiver = tmq::request_reply::reply(&context).bind(address).unwrap();
+        loop {
+            let Ok((mut request, sender)) = receiver.recv().await else {
+                return;
+            };
+            let command = Command::from_json(&request.pop_front().unwrap()).unwrap();
+            assert!(matches!(command, Command::GetStatus));
+            let response = CommandResponse::success_with_run(ComponentState::Running, "ok", 42)
+                .with_metrics(ComponentMetrics {
+                    events_processed: 12345,
+                    bytes_transferred: 67890,
+                    queue_size: 3,
+                    queue_max: 1000,
+                    event_rate: 1500.5,
+                    data_rate: 2048.0,
+                    channel_dead_time: vec![ChannelDeadTime {
+                        channel: 3,
+                        events: 200,
+                        lost_flagged: 5,
+                        busy_fraction: 0.025,
+                    }],
+                    event_filter: Some(FilterCounts {
+                        passed: 900,
+                        dropped: 100,
+                    }),
+                });
+            let reply: tmq::Multipart =
+                vec![tmq::Message::from(response.to_json().unwrap().as_slice())].into();
+            receiver = sender.send(reply).await.unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_metrics_route_reports_components() {
+        tokio::spawn(mock_reader("tcp://127.0.0.1:15582"));
+
+        // Nothing listens on 15583, so the Recorder is reported offline
+        let (app, _tasks) = RouterBuilder::new(vec![
+            component("Reader", "tcp://127.0.0.1:15582", 1),
+            component("Recorder", "tcp://127.0.0.1:15583", 2),
+        ])
+        .config_dir(std::env::temp_dir().join("delila_metrics_test_no_configs"))
+        .build();
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        let mut stream = tokio::net::TcpStream::connect(addr).await.unwrap();
+        stream
+            .write_all(b"GET /metrics HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+            .await
+            .unwrap();
+        let mut response = String::new();
+        tokio::time::timeout(
+            Duration::from_secs(15),
+            stream.read_to_string(&mut response),
+        )
+        .await
+        .unwrap()
+        .unwrap();
+
+        let (head, body) = response.split_once("\r\n\r\n").unwrap();
+        assert!(head.starts_with("HTTP/1.1 200"));
+        assert!(head.contains("text/plain; version=0.0.4"));
+
+        let lines: Vec<&str> = body.lines().collect();
+        assert!(lines.contains(&"delila_up{component=\"Reader\"} 1"));
+        assert!(lines.contains(&"delila_up{component=\"Recorder\"} 0"));
+        assert!(lines.contains(&"delila_events_processed{component=\"Reader\"} 12345"));
+        assert!(lines.contains(&"delila_event_rate{component=\"Reader\"} 1500.5"));
+        assert!(lines.contains(&"delila_queue_size{component=\"Reader\"} 3"));
+        assert!(
+            lines.contains(&"delila_dead_time_fraction{component=\"Reader\",channel=\"3\"} 0.025")
+        );
+        assert!(lines.contains(&"delila_filter_passed{component=\"Reader\"} 900"));
+        assert!(lines.contains(&"delila_filter_dropped{component=\"Reader\"} 100"));
+        assert!(lines.contains(&"delila_system_state{state=\"Degraded\"} 1"));
+        assert!(lines.contains(&"delila_system_state{state=\"Running\"} 0"));
+        assert!(!body.contains("delila_events_processed{component=\"Recorder\"}"));
+
+        // Every sample line is `name{labels} value` with a numeric value
+        for line in lines.iter().filter(|l| !l.starts_with('#')) {
+            let (series, value) = line.rsplit_once(' ').unwrap();
+            assert!(
+                series.starts_with("delila_") && series.ends_with('}'),
+                "{line}"
+            );
+            assert!(value.parse::<f64>().is_ok(), "{line}");
+        }
+    }
+}
@@ -1,9 +1,16 @@
 //! REST API routes for DAQ control
 
+mod config;
 mod digitizer;
 mod emulator;
+mod health;
+mod metrics;
+mod recorder;
+mod restore;
 mod run;
 mod status;
+mod version;
+mod ws;
 
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -11,44 +18,61 @@ use std::path::PathBuf;
 use std::sync::Arc;
 
 use axum::{
+    middleware,
     routing::{get, post, put},
     Router,
 };
-use tokio::sync::RwLock;
+use tokio::sync::{broadcast, Notify, RwLock};
 use tower_http::cors::{Any, CorsLayer};
 use utoipa::{OpenApi, ToSchema};
 use utoipa_swagger_ui::SwaggerUi;
 
-use crate::common::{ComponentMetrics, ComponentState};
+use crate::common::{
+    ChannelDeadTime, ComponentMetrics, ComponentState, FilterCounts, RecorderTuning,
+};
 use crate::config::{DigitizerConfig, Settings as ConfigSettings};
 
 use super::{
-    ApiResponse, CommandResult, ComponentClient, ComponentConfig, ComponentStatus,
-    ConfigureRequest, CurrentRunInfo, DigitizerConfigRepository, LastRunInfo, OperatorConfig,
-    RunNote, RunRepository, RunStats, RunStatus, StartRequest, SystemState, SystemStatus,
+    AbortRequest, ApiResponse, BringUpRequest, CommandResult, ComponentClient, ComponentConfig,
+    ComponentStatus, ConfigureRequest, CurrentRunInfo, DigitizerConfigRepository, LastRunInfo,
+    OperatorConfig, RunLimits, RunNote, RunRepository, RunStats, RunStatus, StartRequest,
+    SystemState, SystemStatus,
 };
 
 // Re-export public types from sub-modules (used in OpenAPI schemas)
+pub use config::ConfigReloadResponse;
 pub use digitizer::{
     DetectResponse, DetectedDigitizer, DigitizerConfigHistoryItem, RestoreVersionRequest,
+    ValidateDigitizerRequest, ValidateDigitizerResponse,
 };
+pub use health::ReadinessResponse;
 pub use run::{AddNoteRequest, NextRunNumberResponse};
+pub use version::VersionResponse;
 
 // Import handler functions from sub-modules (used in router and ApiDoc)
+use config::reload_config;
 use digitizer::{
     detect_digitizers, get_digitizer, get_digitizer_by_serial, get_digitizer_history,
     list_digitizers, restore_digitizer_version, save_all_digitizers, save_digitizer,
-    save_digitizer_to_mongodb, update_digitizer,
+    save_digitizer_to_mongodb, update_digitizer, validate_digitizer,
 };
 use emulator::{get_emulator_settings, update_emulator_settings};
+use health::{healthz, readyz};
+use metrics::get_metrics;
+use recorder::update_recorder_tuning;
 use run::{add_run_note, get_next_run_number, get_run, get_run_config_snapshot, get_run_history};
-use status::{arm, configure, get_status, reset, run_start, start, stop};
+use status::{arm, configure, get_status, reset, run_abort, run_bringup, run_start, start, stop};
+use version::get_version;
+use ws::{refresh_status_after, status_poller, ws_status};
 
 /// Application state shared across handlers
 pub struct AppState {
     pub client: ComponentClient,
-    pub components: Vec<ComponentConfig>,
+    /// Controlled components (replaced by `POST /api/config/reload`)
+    pub components: RwLock<Vec<ComponentConfig>>,
     pub config: OperatorConfig,
+    /// Config file the components were loaded from (None = built-in defaults)
+    pub config_path: Option<PathBuf>,
     /// Digitizer configurations (keyed by digitizer_id)
     pub digitizer_configs: RwLock<HashMap<u32, DigitizerConfig>>,
     /// Directory for storing digitizer config files
@@ -61,6 +85,20 @@ pub struct AppState {
     pub current_run: RwLock<Option<CurrentRunInfo>>,
     /// Emulator settings (runtime-configurable)
     pub emulator_settings: RwLock<EmulatorSettings>,
+    /// Serialized `SystemStatus` frames for `/api/ws/status` clients
+    pub status_tx: broadcast::Sender<Arc<String>>,
+    /// Wakes the status poller early (after control commands)
+    pub status_refresh: Notify,
+}
+
+impl AppState {
+    /// Snapshot of the component list
+    ///
+    /// Handlers work on a snapshot so a concurrent config reload cannot
+    /// change the targets in the middle of a command sequence.
+    pub async fn components(&self) -> Vec<ComponentConfig> {
+        self.components.read().await.clone()
+    }
 }
 
 /// Emulator runtime settings (API model)
@@ -124,6 +162,14 @@ impl From<&ConfigSettings> for EmulatorSettings {
         status::stop,
         status::reset,
         status::run_start,
+        status::run_abort,
+        status::run_bringup,
+        config::reload_config,
+        metrics::get_metrics,
+        health::healthz,
+        health::readyz,
+        version::get_version,
+        recorder::update_recorder_tuning,
         digitizer::list_digitizers,
         digitizer::detect_digitizers,
         digitizer::get_digitizer_by_serial,
@@ -134,6 +180,7 @@ impl From<&ConfigSettings> for EmulatorSettings {
         digitizer::save_digitizer_to_mongodb,
         digitizer::get_digitizer_history,
         digitizer::restore_digitizer_version,
+        digitizer::validate_digitizer,
         run::get_run_config_snapshot,
         run::get_run_history,
         run::get_run,
@@ -148,16 +195,25 @@ impl From<&ConfigSettings> for EmulatorSettings {
         SystemState,
         ComponentState,
         ComponentMetrics,
+        ChannelDeadTime,
+        FilterCounts,
         ConfigureRequest,
         StartRequest,
+        BringUpRequest,
+        AbortRequest,
         ApiResponse,
         CommandResult,
+        ConfigReloadResponse,
+        ReadinessResponse,
+        VersionResponse,
+        RecorderTuning,
         DigitizerConfig,
         DetectedDigitizer,
         DetectResponse,
         CurrentRunInfo,
         RunStats,
         RunStatus,
+        RunLimits,
         NextRunNumberResponse,
         AddNoteRequest,
         RunNote,
@@ -165,6 +221,8 @@ impl From<&ConfigSettings> for EmulatorSettings {
         EmulatorSettings,
         DigitizerConfigHistoryItem,
         RestoreVersionRequest,
+        ValidateDigitizerRequest,
+        ValidateDigitizerResponse,
     )),
     tags(
         (name = "DAQ Control", description = "DAQ system control endpoints"),
@@ -185,14 +243,16 @@ struct ApiDoc;
 /// All fields have sensible defaults; only `components` is required.
 ///
 /// ```ignore
-/// let app = RouterBuilder::new(components)
+/// let (app, tasks) = RouterBuilder::new(components)
 ///     .config(operator_config)
 ///     .emulator_settings(settings)
 ///     .build();
+/// tasks.start();
 /// ```
 pub struct RouterBuilder {
     components: Vec<ComponentConfig>,
     config: OperatorConfig,
+    config_path: Option<PathBuf>,
     config_dir: PathBuf,
     run_repo: Option<RunRepository>,
     digitizer_repo: Option<DigitizerConfigRepository>,
@@ -204,6 +264,7 @@ impl RouterBuilder {
         Self {
             components,
             config: OperatorConfig::default(),
+            config_path: None,
             config_dir: PathBuf::from("./config/digitizers"),
             run_repo: None,
             digitizer_repo: None,
@@ -216,6 +277,12 @@ impl RouterBuilder {
         self
     }
 
+    /// Config file re-read by `POST /api/config/reload`
+    pub fn config_path(mut self, path: PathBuf) -> Self {
+        self.config_path = Some(path);
+        self
+    }
+
     pub fn config_dir(mut self, path: PathBuf) -> Self {
         self.config_dir = path;
         self
@@ -236,29 +303,38 @@ impl RouterBuilder {
         self
     }
 
-    pub fn build(self) -> Router {
+    /// Build the router and its background tasks
+    ///
+    /// The tasks are not running yet; see [`OperatorTasks::start`].
+    pub fn build(self) -> (Router, OperatorTasks) {
         let digitizer_configs = load_digitizer_configs(&self.config_dir).unwrap_or_default();
 
         let state = Arc::new(AppState {
             client: ComponentClient::new(),
-            components: self.components,
+            components: RwLock::new(self.components),
             config: self.config,
+            config_path: self.config_path,
             digitizer_configs: RwLock::new(digitizer_configs),
             config_dir: self.config_dir,
             run_repo: self.run_repo,
             digitizer_repo: self.digitizer_repo,
             current_run: RwLock::new(None),
             emulator_settings: RwLock::new(self.emulator_settings),
+            status_tx: broadcast::channel(16).0,
+            status_refresh: Notify::new(),
         });
 
+        let tasks = OperatorTasks {
+            state: state.clone(),
+        };
+
         let cors = CorsLayer::new()
             .allow_origin(Any)
             .allow_methods(Any)
             .allow_headers(Any);
 
-        Router::new()
-            // DAQ Control API routes
-            .route("/api/status", get(get_status))
+        // State-changing commands push a fresh status to WebSocket clients
+        let control = Router::new()
             .route("/api/configure", post(configure))
             .route("/api/arm", post(arm))
             .route("/api/start", post(start))
@@ -266,6 +342,29 @@ impl RouterBuilder {
             .route("/api/reset", post(reset))
             // Two-phase synchronized run control
             .route("/api/run/start", post(run_start))
+            .route("/api/run/abort", post(run_abort))
+            .route("/api/run/bringup", post(run_bringup))
+            .route_layer(middleware::from_fn_with_state(
+                state.clone(),
+                refresh_status_after,
+            ));
+
+        let router = Router::new()
+            // DAQ Control API routes
+            .route("/api/status", get(get_status))
+            .route("/api/ws/status", get(ws_status))
+            .merge(control)
+            // Re-read the component list from the config file
+            .route("/api/config/reload", post(reload_config))
+            // Runtime Recorder thresholds
+            .route("/api/recorder/tuning", put(update_recorder_tuning))
+            // Prometheus scrape endpoint
+            .route("/metrics", get(get_metrics))
+            // Liveness / readiness probes
+            .route("/healthz", get(healthz))
+            .route("/readyz", get(readyz))
+            // Build information
+            .route("/api/version", get(get_version))
             // Run history routes
             .route("/api/runs", get(get_run_history))
             .route("/api/runs/next", get(get_next_run_number))
@@ -291,6 +390,7 @@ impl RouterBuilder {
                 "/api/digitizers/:id/restore",
                 post(restore_digitizer_version),
             )
+            .route("/api/digitizer/validate", post(validate_digitizer))
             // Run config snapshots
             .route("/api/runs/:run_number/config", get(get_run_config_snapshot))
             // Emulator settings routes
@@ -299,7 +399,23 @@ impl RouterBuilder {
             // Swagger UI
             .merge(SwaggerUi::new("/swagger-ui").url("/api-docs/openapi.json", ApiDoc::openapi()))
             .layer(cors)
-            .with_state(state)
+            .with_state(state);
+        (router, tasks)
+    }
+}
+
+/// Background tasks of a router built by [`RouterBuilder`]
+pub struct OperatorTasks {
+    state: Arc<AppState>,
+}
+
+impl OperatorTasks {
+    /// Spawn run recovery and the WebSocket status poller
+    ///
+    /// Must be called from within a Tokio runtime.
+    pub fn start(self) {
+        tokio::spawn(restore::restore_current_run(self.state.clone()));
+        tokio::spawn(status_poller(self.state));
     }
 }
 
@@ -0,0 +1,61 @@
+//! Recorder tuning handler
+
+use std::sync::Arc;
+
+use axum::{extract::State, http::StatusCode, Json};
+
+use crate::common::RecorderTuning;
+
+use super::super::ApiResponse;
+use super::AppState;
+
+/// Adjust Recorder write thresholds at runtime
+///
+/// Only the fields present in the body change. Rotation limits are checked
+/// again on the next batch; the fsync interval applies from the next write.
+#[utoipa::path(
+    put,
+    path = "/api/recorder/tuning",
+    tag = "DAQ Control",
+    request_body = RecorderTuning,
+    responses(
+        (status = 200, description = "Tuning sent to every Recorder", body = ApiResponse),
+        (status = 400, description = "Value out of range", body = ApiResponse)
+    )
+)]
+pub(super) async fn update_recorder_tuning(
+    State(state): State<Arc<AppState>>,
+    Json(tuning): Json<RecorderTuning>,
+) -> (StatusCode, Json<ApiResponse>) {
+    if let Err(msg) = tuning.validate() {
+        return (StatusCode::BAD_REQUEST, Json(ApiResponse::error(msg)));
+    }
+
+    let recorders: Vec<_> = state
+        .components()
+        .await
+        .into_iter()
+        .filter(|c| c.name.to_lowercase().contains("recorder"))
+        .collect();
+    if recorders.is_empty() {
+        return (
+            StatusCode::OK,
+            Json(ApiResponse::error("No recorder components configured")),
+        );
+    }
+
+    let mut results = Vec::with_capacity(recorders.len());
+    for recorder in &recorders {
+        results.push(
+            state
+                .client
+                .update_recorder_tuning(recorder, tuning.clone())
+                .await,
+        );
+    }
+
+    (
+        StatusCode::OK,
+        Json(ApiResponse::success("Recorder tuning updated").with_results(results)),
+    )
+}
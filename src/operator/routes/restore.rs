@@ -0,0 +1,137 @@
+//! Run state recovery after an operator restart
+//!
+//! The current run is only kept in memory, so a restarted operator would
+//! forget a run that the components are still acquiring. At startup the
+//! components are asked for their status; if they are Running, the run is
+//! rebuilt from the run number they report and attached to the open MongoDB
+//! run document (or a new one is created if there is none).
+
+use std::sync::Arc;
+
+use tracing::{info, warn};
+
+use crate::common::ComponentState;
+
+use super::super::{ComponentStatus, CurrentRunInfo, RunLimits, RunStats, RunStatus};
+use super::AppState;
+
+/// Rebuild the current run from component statuses
+///
+/// Returns `None` unless at least one online component is Running with a
+/// run number. If components disagree, the most common run number wins.
+/// The start time is unknown at this point and is set to now.
+fn reconstruct_run(components: &[ComponentStatus], exp_name: &str) -> Option<CurrentRunInfo> {
+    let mut counts: Vec<(u32, usize)> = Vec::new();
+    for run_number in components
+        .iter()
+        .filter(|c| c.online && c.state == ComponentState::Running)
+        .filter_map(|c| c.run_number)
+    {
+        match counts.iter_mut().find(|(n, _)| *n == run_number) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((run_number, 1)),
+        }
+    }
+    if counts.len() > 1 {
+        warn!(?counts, "Running components report different run numbers");
+    }
+    let (run_number, _) = counts.into_iter().max_by_key(|&(_, count)| count)?;
+
+    Some(CurrentRunInfo {
+        run_number: run_number as i32,
+        exp_name: exp_name.to_string(),
+        comment: String::new(),
+        start_time: chrono::Utc::now(),
+        elapsed_secs: 0,
+        status: RunStatus::Running,
+        stats: RunStats::default(),
+        notes: Vec::new(),
+        limits: RunLimits::default(),
+    })
+}
+
+/// Query all components and repopulate `current_run` if a run is in progress
+pub(super) async fn restore_current_run(state: Arc<AppState>) {
+    let components = state.client.get_all_status(&state.components().await).await;
+    let Some(mut run) = reconstruct_run(&components, &state.config.experiment_name) else {
+        return;
+    };
+
+    if let Some(ref repo) = state.run_repo {
+        let open = match repo.get_current_run().await {
+            Ok(doc) => doc.filter(|d| d.run_number == run.run_number && d.exp_name == run.exp_name),
+            Err(e) => {
+                warn!("Failed to look up the open run document: {}", e);
+                None
+            }
+        };
+        match open {
+            Some(doc) => run = CurrentRunInfo::from_document(&doc),
+            None => match repo
+                .start_run(run.run_number, &run.exp_name, &run.comment, None)
+                .await
+            {
+                Ok(doc) => {
+                    info!(
+                        run_number = run.run_number,
+                        "Created run document for run in progress"
+                    );
+                    run = CurrentRunInfo::from_document(&doc);
+                }
+                Err(e) => warn!("Failed to record recovered run in MongoDB: {}", e),
+            },
+        }
+    }
+
+    let mut current = state.current_run.write().await;
+    if current.is_none() {
+        info!(run_number = run.run_number, "Recovered run in progress");
+        *current = Some(run);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status(name: &str, state: ComponentState, run_number: Option<u32>) -> ComponentStatus {
+        ComponentStatus {
+            name: name.to_string(),
+            address: String::new(),
+            state,
+            run_number,
+            metrics: None,
+            error: None,
+            online: true,
+            version: None,
+        }
+    }
+
+    #[test]
+    fn test_reconstruct_running_run() {
+        let components = vec![
+            status("Reader", ComponentState::Running, Some(7)),
+            status("Merger", ComponentState::Running, Some(7)),
+            status("Recorder", ComponentState::Running, Some(7)),
+        ];
+
+        let run = reconstruct_run(&components, "Exp1").unwrap();
+        assert_eq!(run.run_number, 7);
+        assert_eq!(run.exp_name, "Exp1");
+        assert_eq!(run.status, RunStatus::Running);
+    }
+
+    #[test]
+    fn test_reconstruct_ignores_idle_system() {
+        let components = vec![
+            status("Reader", ComponentState::Configured, Some(7)),
+            status("Recorder", ComponentState::Idle, None),
+        ];
+        assert!(reconstruct_run(&components, "Exp1").is_none());
+
+        // An offline component's stale run number is not trusted
+        let mut offline = status("Reader", ComponentState::Running, Some(7));
+        offline.online = false;
+        assert!(reconstruct_run(&[offline], "Exp1").is_none());
+    }
+}
@@ -27,6 +27,9 @@ pub struct RunHistoryItem {
     pub end_time: Option<chrono::DateTime<chrono::Utc>>,
     pub duration_secs: Option<i32>,
     pub status: RunStatus,
+    /// Why the run was aborted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end_reason: Option<String>,
     pub stats: RunStats,
 }
 
@@ -40,6 +43,7 @@ impl From<RunDocument> for RunHistoryItem {
             end_time: doc.end_time,
             duration_secs: doc.duration_secs,
             status: doc.status,
+            end_reason: doc.end_reason,
             stats: doc.stats,
         }
     }
@@ -1,17 +1,37 @@
-//! DAQ control handlers (status, configure, arm, start, stop, reset, run_start)
+//! DAQ control handlers (status, configure, arm, start, stop, reset, run_start, run_abort,
+//! run_bringup)
 
 use std::sync::Arc;
+use std::time::Duration;
 
 use axum::{extract::State, http::StatusCode, Json};
 
-use crate::common::RunConfig;
+use crate::common::{ComponentState, RunConfig};
 
 use super::super::{
-    ApiResponse, ConfigureRequest, CurrentRunInfo, RunStats, RunStatus, StartRequest, SystemState,
+    AbortRequest, ApiResponse, BringUpRequest, CommandResult, ComponentConfig, ComponentStatus,
+    ConfigureRequest, CurrentRunInfo, RunLimits, RunStats, RunStatus, StartRequest, SystemState,
     SystemStatus,
 };
 use super::AppState;
 
+/// How often an active run is checked against its stop limits
+const RUN_LIMIT_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Events recorded so far in the run
+///
+/// The Recorder is the authoritative source for recorded data; without one,
+/// the furthest-along component is used.
+fn recorded_events(components: &[ComponentStatus]) -> u64 {
+    let events = |c: &ComponentStatus| c.metrics.as_ref().map(|m| m.events_processed);
+    components
+        .iter()
+        .find(|c| c.name == "Recorder")
+        .and_then(events)
+        .or_else(|| components.iter().filter_map(events).max())
+        .unwrap_or(0)
+}
+
 /// Get system and component status
 #[utoipa::path(
     get,
@@ -22,8 +42,15 @@ use super::AppState;
     )
 )]
 pub(super) async fn get_status(State(state): State<Arc<AppState>>) -> Json<SystemStatus> {
-    let components = state.client.get_all_status(&state.components).await;
+    Json(system_status(&state).await)
+}
+
+/// Query all components and assemble the system status
+pub(super) async fn system_status(state: &AppState) -> SystemStatus {
+    let components = state.client.get_all_status(&state.components().await).await;
     let system_state = SystemState::from_components(&components);
+    let version_mismatch =
+        ComponentStatus::version_mismatch(&components, crate::common::VERSION_STRING);
 
     // Get current run info and update real-time values
     let run_info = {
@@ -82,14 +109,15 @@ pub(super) async fn get_status(State(state): State<Arc<AppState>>) -> Json<Syste
         (None, None)
     };
 
-    Json(SystemStatus {
+    SystemStatus {
         components,
         system_state,
         run_info,
         experiment_name: state.config.experiment_name.clone(),
         next_run_number,
         last_run_info,
-    })
+        version_mismatch,
+    }
 }
 
 /// Configure all components for a run
@@ -111,7 +139,7 @@ pub(super) async fn configure(
     let run_number = run_config.run_number;
     let results = state
         .client
-        .configure_all(&state.components, run_config)
+        .configure_all(&state.components().await, run_config)
         .await;
 
     let response = ApiResponse::success(format!("Configure command sent for run {}", run_number))
@@ -137,7 +165,7 @@ pub(super) async fn configure(
     )
 )]
 pub(super) async fn arm(State(state): State<Arc<AppState>>) -> (StatusCode, Json<ApiResponse>) {
-    let results = state.client.arm_all(&state.components).await;
+    let results = state.client.arm_all(&state.components().await).await;
 
     let response = ApiResponse::success("Arm command sent").with_results(results);
 
@@ -169,18 +197,20 @@ pub(super) async fn start(
     State(state): State<Arc<AppState>>,
     Json(request): Json<StartRequest>,
 ) -> (StatusCode, Json<ApiResponse>) {
+    let component_configs = state.components().await;
     let run_number = request.run_number;
+    let limits = request.limits();
     let comment = request.comment;
 
     // Check current state
-    let components = state.client.get_all_status(&state.components).await;
+    let components = state.client.get_all_status(&component_configs).await;
     let system_state = SystemState::from_components(&components);
 
     // If Configured, arm first
     if system_state == SystemState::Configured {
         match state
             .client
-            .arm_all_sync(&state.components, state.config.arm_timeout_ms)
+            .arm_all_sync(&component_configs, state.config.arm_timeout_ms)
             .await
         {
             Ok(arm_results) => {
@@ -205,7 +235,11 @@ pub(super) async fn start(
     // Now start with the run number (sequential: wait for each component to reach Running)
     let start_result = state
         .client
-        .start_all_sync(&state.components, run_number, state.config.start_timeout_ms)
+        .start_all_sync(
+            &component_configs,
+            run_number,
+            state.config.start_timeout_ms,
+        )
         .await;
 
     let response = match start_result {
@@ -220,67 +254,7 @@ pub(super) async fn start(
     };
 
     let status = if response.success {
-        let exp_name = &state.config.experiment_name;
-
-        // Record run start in MongoDB and update current_run
-        if let Some(ref repo) = state.run_repo {
-            let mongo_start = std::time::Instant::now();
-            match repo
-                .start_run(run_number as i32, exp_name, &comment, None)
-                .await
-            {
-                Ok(doc) => {
-                    tracing::info!("MongoDB start_run took {:?}", mongo_start.elapsed());
-                    let info = CurrentRunInfo::from_document(&doc);
-                    *state.current_run.write().await = Some(info);
-                }
-                Err(e) => {
-                    tracing::warn!("Failed to record run start in MongoDB: {}", e);
-                    // Still set current_run for in-memory tracking
-                    *state.current_run.write().await = Some(CurrentRunInfo {
-                        run_number: run_number as i32,
-                        exp_name: exp_name.clone(),
-                        comment: comment.clone(),
-                        start_time: chrono::Utc::now(),
-                        elapsed_secs: 0,
-                        status: RunStatus::Running,
-                        stats: RunStats::default(),
-                        notes: Vec::new(),
-                    });
-                }
-            }
-        }
-
-        // Create digitizer config snapshot for this run
-        if let Some(ref digitizer_repo) = state.digitizer_repo {
-            let configs: Vec<_> = state
-                .digitizer_configs
-                .read()
-                .await
-                .values()
-                .cloned()
-                .collect();
-            if !configs.is_empty() {
-                if let Err(e) = digitizer_repo
-                    .create_run_snapshot(run_number as i32, exp_name, configs)
-                    .await
-                {
-                    tracing::warn!("Failed to create config snapshot: {}", e);
-                }
-            }
-        } else {
-            // No MongoDB, just track in memory
-            *state.current_run.write().await = Some(CurrentRunInfo {
-                run_number: run_number as i32,
-                exp_name: exp_name.clone(),
-                comment,
-                start_time: chrono::Utc::now(),
-                elapsed_secs: 0,
-                status: RunStatus::Running,
-                stats: RunStats::default(),
-                notes: Vec::new(),
-            });
-        }
+        record_run_start(&state, run_number, comment, limits).await;
         StatusCode::OK
     } else {
         StatusCode::BAD_REQUEST
@@ -300,51 +274,30 @@ pub(super) async fn start(
     )
 )]
 pub(super) async fn stop(State(state): State<Arc<AppState>>) -> (StatusCode, Json<ApiResponse>) {
+    let (status, response) = stop_run(&state).await;
+    (status, Json(response))
+}
+
+/// Stop all components and record the end of the current run
+async fn stop_run(state: &AppState) -> (StatusCode, ApiResponse) {
+    let component_configs = state.components().await;
     // Get current run info before stopping
     let current_run = state.current_run.read().await.clone();
 
-    let results = state.client.stop_all(&state.components).await;
+    let results = state.client.stop_all(&component_configs).await;
 
     let response = ApiResponse::success("Stop command sent").with_results(results);
 
     let status = if response.success {
-        // Record run end in MongoDB
-        if let (Some(ref repo), Some(run_info)) = (&state.run_repo, current_run) {
-            // Get final stats from components
-            let components = state.client.get_all_status(&state.components).await;
-            let total_events: i64 = components
-                .iter()
-                .filter_map(|c| c.metrics.as_ref())
-                .map(|m| m.events_processed as i64)
-                .sum();
-            let total_bytes: i64 = components
-                .iter()
-                .filter_map(|c| c.metrics.as_ref())
-                .map(|m| m.bytes_transferred as i64)
-                .sum();
-            let average_rate = if run_info.elapsed_secs > 0 {
-                total_events as f64 / run_info.elapsed_secs as f64
-            } else {
-                0.0
-            };
-
-            let stats = RunStats {
-                total_events,
-                total_bytes,
-                average_rate,
-            };
-
-            if let Err(e) = repo
-                .end_run(
-                    run_info.run_number,
-                    &run_info.exp_name,
-                    RunStatus::Completed,
-                    stats,
-                )
-                .await
-            {
-                tracing::warn!("Failed to record run end in MongoDB: {}", e);
-            }
+        if let Some(run_info) = current_run {
+            record_run_end(
+                state,
+                &component_configs,
+                &run_info,
+                RunStatus::Completed,
+                None,
+            )
+            .await;
         }
 
         // Clear current run
@@ -354,9 +307,143 @@ pub(super) async fn stop(State(state): State<Arc<AppState>>) -> (StatusCode, Jso
         StatusCode::BAD_REQUEST
     };
 
+    (status, response)
+}
+
+/// Record the end of a run in MongoDB with final stats from the components
+async fn record_run_end(
+    state: &AppState,
+    component_configs: &[ComponentConfig],
+    run_info: &CurrentRunInfo,
+    status: RunStatus,
+    reason: Option<&str>,
+) {
+    let Some(ref repo) = state.run_repo else {
+        return;
+    };
+
+    // Get final stats from components
+    let components = state.client.get_all_status(component_configs).await;
+    let total_events: i64 = components
+        .iter()
+        .filter_map(|c| c.metrics.as_ref())
+        .map(|m| m.events_processed as i64)
+        .sum();
+    let total_bytes: i64 = components
+        .iter()
+        .filter_map(|c| c.metrics.as_ref())
+        .map(|m| m.bytes_transferred as i64)
+        .sum();
+    let average_rate = if run_info.elapsed_secs > 0 {
+        total_events as f64 / run_info.elapsed_secs as f64
+    } else {
+        0.0
+    };
+
+    let stats = RunStats {
+        total_events,
+        total_bytes,
+        average_rate,
+    };
+
+    if let Err(e) = repo
+        .end_run(
+            run_info.run_number,
+            &run_info.exp_name,
+            status,
+            reason,
+            stats,
+        )
+        .await
+    {
+        tracing::warn!("Failed to record run end in MongoDB: {}", e);
+    }
+}
+
+/// Abort the current run (beam lost, detector fault, ...)
+///
+/// Sends Stop to all components like `/api/stop`, but records the run as
+/// aborted with the given reason instead of completed. The run is recorded
+/// as ended even if a component fails to stop.
+#[utoipa::path(
+    post,
+    path = "/api/run/abort",
+    tag = "DAQ Control",
+    request_body = AbortRequest,
+    responses(
+        (status = 200, description = "Run aborted", body = ApiResponse),
+        (status = 400, description = "Missing reason or a component failed to stop", body = ApiResponse)
+    )
+)]
+pub(super) async fn run_abort(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<AbortRequest>,
+) -> (StatusCode, Json<ApiResponse>) {
+    let reason = request.reason.trim();
+    if reason.is_empty() {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::error("An abort reason is required")),
+        );
+    }
+
+    let component_configs = state.components().await;
+    let results = state.client.stop_all(&component_configs).await;
+
+    match state.current_run.write().await.take() {
+        Some(run_info) => {
+            tracing::warn!(run_number = run_info.run_number, reason, "Run aborted");
+            record_run_end(
+                &state,
+                &component_configs,
+                &run_info,
+                RunStatus::Aborted,
+                Some(reason),
+            )
+            .await;
+        }
+        None => tracing::warn!(reason, "Abort requested without an active run"),
+    }
+
+    let response = ApiResponse::success(format!("Run aborted: {}", reason)).with_results(results);
+    let status = if response.success {
+        StatusCode::OK
+    } else {
+        StatusCode::BAD_REQUEST
+    };
     (status, Json(response))
 }
 
+/// Stop the run once one of its limits is reached
+///
+/// Returns without stopping if the run is stopped or replaced first.
+async fn watch_run_limits(state: Arc<AppState>, run_number: i32, limits: RunLimits) {
+    let mut interval = tokio::time::interval(RUN_LIMIT_POLL_INTERVAL);
+    loop {
+        interval.tick().await;
+
+        let elapsed_secs = match state.current_run.read().await.as_ref() {
+            Some(run) if run.run_number == run_number && run.status == RunStatus::Running => {
+                chrono::Utc::now()
+                    .signed_duration_since(run.start_time)
+                    .num_seconds()
+            }
+            _ => return,
+        };
+
+        let components = state.client.get_all_status(&state.components().await).await;
+        if let Some(reason) = limits.reached(recorded_events(&components), elapsed_secs) {
+            tracing::info!(run_number, %reason, "Run limit reached, stopping run");
+            let (status, response) = stop_run(&state).await;
+            state.status_refresh.notify_one();
+            if status != StatusCode::OK {
+                tracing::warn!(run_number, message = %response.message, "Automatic stop failed");
+            }
+            return;
+        }
+    }
+}
+
 /// Reset all components to Idle state
 #[utoipa::path(
     post,
@@ -367,7 +454,7 @@ pub(super) async fn stop(State(state): State<Arc<AppState>>) -> (StatusCode, Jso
     )
 )]
 pub(super) async fn reset(State(state): State<Arc<AppState>>) -> (StatusCode, Json<ApiResponse>) {
-    let results = state.client.reset_all(&state.components).await;
+    let results = state.client.reset_all(&state.components().await).await;
 
     let response = ApiResponse::success("Reset command sent").with_results(results);
 
@@ -404,6 +491,8 @@ pub(super) async fn run_start(
     State(state): State<Arc<AppState>>,
     Json(request): Json<ConfigureRequest>,
 ) -> (StatusCode, Json<ApiResponse>) {
+    let component_configs = state.components().await;
+    let comment = request.comment.clone();
     let run_config: RunConfig = request.into();
     let run_number = run_config.run_number;
 
@@ -411,7 +500,7 @@ pub(super) async fn run_start(
     let configure_result = state
         .client
         .configure_all_sync(
-            &state.components,
+            &component_configs,
             run_config,
             state.config.configure_timeout_ms,
         )
@@ -436,7 +525,7 @@ pub(super) async fn run_start(
     // Phase 2: Arm (sync point)
     let arm_result = state
         .client
-        .arm_all_sync(&state.components, state.config.arm_timeout_ms)
+        .arm_all_sync(&component_configs, state.config.arm_timeout_ms)
         .await;
 
     match arm_result {
@@ -458,7 +547,11 @@ pub(super) async fn run_start(
     // Phase 3: Start (with run_number)
     let start_result = state
         .client
-        .start_all_sync(&state.components, run_number, state.config.start_timeout_ms)
+        .start_all_sync(
+            &component_configs,
+            run_number,
+            state.config.start_timeout_ms,
+        )
         .await;
 
     match start_result {
@@ -471,25 +564,7 @@ pub(super) async fn run_start(
             Json(ApiResponse::error("Start phase failed").with_results(results)),
         ),
         Ok(results) => {
-            // Create digitizer config snapshot for this run
-            if let Some(ref digitizer_repo) = state.digitizer_repo {
-                let exp_name = &state.config.experiment_name;
-                let configs: Vec<_> = state
-                    .digitizer_configs
-                    .read()
-                    .await
-                    .values()
-                    .cloned()
-                    .collect();
-                if !configs.is_empty() {
-                    if let Err(e) = digitizer_repo
-                        .create_run_snapshot(run_number as i32, exp_name, configs)
-                        .await
-                    {
-                        tracing::warn!("Failed to create config snapshot: {}", e);
-                    }
-                }
-            }
+            record_run_start(&state, run_number, comment, RunLimits::default()).await;
 
             (
                 StatusCode::OK,
@@ -504,3 +579,282 @@ pub(super) async fn run_start(
         }
     }
 }
+
+/// Bookkeeping for a run whose components all reached Running
+///
+/// Records the run in MongoDB (when configured), makes it the current run,
+/// snapshots the digitizer configs and watches the run's stop limits.
+async fn record_run_start(
+    state: &Arc<AppState>,
+    run_number: u32,
+    comment: String,
+    limits: RunLimits,
+) {
+    let exp_name = &state.config.experiment_name;
+
+    let recorded = match state.run_repo {
+        Some(ref repo) => {
+            let mongo_start = std::time::Instant::now();
+            match repo
+                .start_run(run_number as i32, exp_name, &comment, None)
+                .await
+            {
+                Ok(doc) => {
+                    tracing::info!("MongoDB start_run took {:?}", mongo_start.elapsed());
+                    Some(CurrentRunInfo {
+                        limits,
+                        ..CurrentRunInfo::from_document(&doc)
+                    })
+                }
+                Err(e) => {
+                    tracing::warn!("Failed to record run start in MongoDB: {}", e);
+                    None
+                }
+            }
+        }
+        None => None,
+    };
+    // Without MongoDB (or if it failed) the run is tracked in memory only
+    let info = recorded.unwrap_or_else(|| CurrentRunInfo {
+        run_number: run_number as i32,
+        exp_name: exp_name.clone(),
+        comment,
+        start_time: chrono::Utc::now(),
+        elapsed_secs: 0,
+        status: RunStatus::Running,
+        stats: RunStats::default(),
+        notes: Vec::new(),
+        limits,
+    });
+    *state.current_run.write().await = Some(info);
+
+    snapshot_digitizer_configs(state, run_number).await;
+
+    if !limits.is_empty() {
+        tokio::spawn(watch_run_limits(state.clone(), run_number as i32, limits));
+    }
+}
+
+/// Create the digitizer config snapshot for a freshly started run
+async fn snapshot_digitizer_configs(state: &AppState, run_number: u32) {
+    let Some(ref digitizer_repo) = state.digitizer_repo else {
+        return;
+    };
+    let exp_name = &state.config.experiment_name;
+    let configs: Vec<_> = state
+        .digitizer_configs
+        .read()
+        .await
+        .values()
+        .cloned()
+        .collect();
+    if !configs.is_empty() {
+        if let Err(e) = digitizer_repo
+            .create_run_snapshot(run_number as i32, exp_name, configs)
+            .await
+        {
+            tracing::warn!("Failed to create config snapshot: {}", e);
+        }
+    }
+}
+
+/// A bring-up phase that did not complete
+struct PhaseFailure {
+    status: StatusCode,
+    message: String,
+    results: Vec<CommandResult>,
+}
+
+impl PhaseFailure {
+    /// Turn a synchronized phase result into an error if any component failed
+    fn check(
+        phase: &str,
+        result: Result<Vec<CommandResult>, String>,
+    ) -> Result<Vec<CommandResult>, Self> {
+        match result {
+            Err(e) => Err(Self {
+                status: StatusCode::REQUEST_TIMEOUT,
+                message: format!("{} phase failed: {}", phase, e),
+                results: Vec::new(),
+            }),
+            Ok(results) if results.iter().any(|r| !r.success) => Err(Self {
+                status: StatusCode::BAD_REQUEST,
+                message: format!("{} phase failed", phase),
+                results,
+            }),
+            Ok(results) => Ok(results),
+        }
+    }
+}
+
+/// Run configure → arm → start in pipeline order, stopping at the first failure
+async fn bring_up_phases(
+    state: &AppState,
+    configs: &[ComponentConfig],
+    run_config: RunConfig,
+) -> Result<Vec<CommandResult>, PhaseFailure> {
+    let run_number = run_config.run_number;
+    let timeouts = &state.config;
+
+    let configure = state
+        .client
+        .configure_all_sync(configs, run_config, timeouts.configure_timeout_ms)
+        .await;
+    PhaseFailure::check("Configure", configure)?;
+
+    let arm = state
+        .client
+        .arm_all_sync(configs, timeouts.arm_timeout_ms)
+        .await;
+    PhaseFailure::check("Arm", arm)?;
+
+    let start = state
+        .client
+        .start_all_sync(configs, run_number, timeouts.start_timeout_ms)
+        .await;
+    PhaseFailure::check("Start", start)
+}
+
+/// Bring the system up: configure, arm and start as one operation
+///
+/// Once running, the run is recorded and its stop limits are watched the
+/// same way as for `/api/start`.
+///
+/// Idempotent: repeating the request for the run that is already running
+/// returns success without touching the components, and leftovers from an
+/// earlier partial bring-up (components left Configured/Armed/Error) are
+/// reset before starting over. If any phase fails, components that reached
+/// Running are stopped and all components are reset to Idle. The response
+/// carries the per-component results of the failed phase followed by the
+/// rollback results.
+#[utoipa::path(
+    post,
+    path = "/api/run/bringup",
+    tag = "DAQ Control",
+    request_body = BringUpRequest,
+    responses(
+        (status = 200, description = "Run is running", body = ApiResponse),
+        (status = 400, description = "Bring-up failed and was rolled back", body = ApiResponse),
+        (status = 408, description = "Timeout during bring-up; rolled back", body = ApiResponse),
+        (status = 409, description = "Another run is in progress", body = ApiResponse)
+    )
+)]
+pub(super) async fn run_bringup(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<BringUpRequest>,
+) -> (StatusCode, Json<ApiResponse>) {
+    let component_configs = state.components().await;
+    let run_config = request.run_config();
+    let run_number = run_config.run_number;
+
+    let statuses = state.client.get_all_status(&component_configs).await;
+    let all_running = !statuses.is_empty()
+        && statuses
+            .iter()
+            .all(|s| s.online && s.state == ComponentState::Running);
+    if all_running && statuses.iter().all(|s| s.run_number == Some(run_number)) {
+        return (
+            StatusCode::OK,
+            Json(ApiResponse::success(format!(
+                "Run {} is already running",
+                run_number
+            ))),
+        );
+    }
+    if statuses.iter().any(|s| s.state.in_run()) {
+        return (
+            StatusCode::CONFLICT,
+            Json(ApiResponse::error(
+                "Another run is in progress; stop it before bringing up a new run",
+            )),
+        );
+    }
+
+    // Clear leftovers from an earlier partial bring-up
+    if statuses.iter().any(|s| s.state != ComponentState::Idle) {
+        tracing::info!("Bring-up: resetting components left over from a previous attempt");
+        state.client.reset_all(&component_configs).await;
+    }
+
+    match bring_up_phases(&state, &component_configs, run_config).await {
+        Ok(results) => {
+            let limits = request.limits();
+            record_run_start(&state, run_number, request.comment, limits).await;
+
+            (
+                StatusCode::OK,
+                Json(
+                    ApiResponse::success(format!("Run {} brought up successfully", run_number))
+                        .with_results(results),
+                ),
+            )
+        }
+        Err(failure) => {
+            tracing::warn!("Bring-up failed, rolling back: {}", failure.message);
+            let mut results = failure.results;
+            results.extend(
+                state
+                    .client
+                    .rollback_all(&component_configs)
+                    .await
+                    .into_iter()
+                    .map(|mut r| {
+                        r.message = format!("Rollback: {}", r.message);
+                        r
+                    }),
+            );
+
+            let mut response =
+                ApiResponse::error(format!("{}; rolled back to Idle", failure.message));
+            response.results = Some(results);
+            (failure.status, Json(response))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::{ComponentMetrics, ComponentState};
+
+    fn running(name: &str, events_processed: u64) -> ComponentStatus {
+        ComponentStatus {
+            name: name.to_string(),
+            address: String::new(),
+            state: ComponentState::Running,
+            run_number: Some(1),
+            metrics: Some(ComponentMetrics {
+                events_processed,
+                ..Default::default()
+            }),
+            error: None,
+            online: true,
+            version: None,
+        }
+    }
+
+    #[test]
+    fn test_run_stops_when_event_limit_exceeded() {
+        let limits = RunLimits {
+            stop_after_events: Some(1_000_000),
+            stop_after_secs: None,
+        };
+
+        // The Recorder's count decides, even if upstream is further along
+        let polls = [400_000, 999_999, 1_000_250];
+        let stopped_at = polls.iter().position(|&recorded| {
+            let components = [
+                running("Reader", recorded + 5_000),
+                running("Recorder", recorded),
+            ];
+            limits.reached(recorded_events(&components), 30).is_some()
+        });
+        assert_eq!(stopped_at, Some(2));
+
+        // Without a Recorder the largest count is used
+        let components = [running("Reader", 1_200_000), running("Merger", 900_000)];
+        assert_eq!(recorded_events(&components), 1_200_000);
+        assert!(limits.reached(recorded_events(&components), 30).is_some());
+        assert_eq!(recorded_events(&[]), 0);
+    }
+}
@@ -0,0 +1,69 @@
+//! Build information of the Operator
+//!
+//! `GET /api/version` reports the crate version, git commit, build time and
+//! the config file in use. Component versions come with their status; see
+//! `SystemStatus::version_mismatch`.
+
+use std::path::Path;
+use std::sync::Arc;
+
+use axum::{extract::State, Json};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use super::AppState;
+use crate::common::version::{BUILD_TIMESTAMP, GIT_COMMIT, VERSION, VERSION_STRING};
+
+/// Operator build information
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct VersionResponse {
+    /// Crate version (e.g. "0.1.0")
+    pub version: String,
+    /// Short git commit ("unknown" if built outside a checkout)
+    pub git_commit: String,
+    /// Build time in seconds since the Unix epoch
+    pub build_timestamp: u64,
+    /// Version string components report in GetStatus
+    pub version_string: String,
+    /// Config file the Operator loaded (None = built-in defaults)
+    pub config_path: Option<String>,
+}
+
+fn version_info(config_path: Option<&Path>) -> VersionResponse {
+    VersionResponse {
+        version: VERSION.to_string(),
+        git_commit: GIT_COMMIT.to_string(),
+        build_timestamp: BUILD_TIMESTAMP.parse().unwrap_or(0),
+        version_string: VERSION_STRING.to_string(),
+        config_path: config_path.map(|path| path.display().to_string()),
+    }
+}
+
+/// Operator version and build information
+#[utoipa::path(
+    get,
+    path = "/api/version",
+    tag = "DAQ Control",
+    responses(
+        (status = 200, description = "Build information", body = VersionResponse)
+    )
+)]
+pub(super) async fn get_version(State(state): State<Arc<AppState>>) -> Json<VersionResponse> {
+    Json(version_info(state.config_path.as_deref()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_version_info() {
+        let info = version_info(Some(Path::new("config.toml")));
+        assert!(!info.version.is_empty());
+        assert_eq!(info.version, env!("CARGO_PKG_VERSION"));
+        assert!(!info.git_commit.is_empty());
+        assert!(info.build_timestamp > 0);
+        assert!(info.version_string.starts_with(&info.version));
+        assert_eq!(info.config_path.as_deref(), Some("config.toml"));
+    }
+}
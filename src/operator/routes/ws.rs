@@ -0,0 +1,99 @@
+//! Live system status over WebSocket
+//!
+//! A single background poller queries the components once per
+//! `status_interval_ms` and broadcasts the serialized `SystemStatus` to every
+//! connected client, so the component load does not grow with the number of
+//! UIs. Control commands wake the poller early so state changes are pushed
+//! without waiting for the next tick. No queries are made while no client is
+//! connected.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::{
+    extract::{
+        ws::{Message as WsMessage, WebSocket, WebSocketUpgrade},
+        Request, State,
+    },
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use tokio::sync::broadcast;
+use tracing::{debug, warn};
+
+use super::status::system_status;
+use super::AppState;
+
+/// GET /api/ws/status - Live system status push
+pub(super) async fn ws_status(
+    ws: WebSocketUpgrade,
+    State(state): State<Arc<AppState>>,
+) -> impl IntoResponse {
+    ws.on_upgrade(move |socket| status_session(socket, state))
+}
+
+/// Serve one WebSocket client until it disconnects
+async fn status_session(mut socket: WebSocket, state: Arc<AppState>) {
+    let mut updates = state.status_tx.subscribe();
+    debug!("Status WebSocket client connected");
+
+    // Give the new client a frame right away instead of after a full interval
+    state.status_refresh.notify_one();
+
+    loop {
+        tokio::select! {
+            update = updates.recv() => {
+                let frame = match update {
+                    Ok(json) => json.as_ref().clone(),
+                    // Every frame is a full status, so skipped ones need no resync
+                    Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                    Err(broadcast::error::RecvError::Closed) => break,
+                };
+                if socket.send(WsMessage::Text(frame)).await.is_err() {
+                    break;
+                }
+            }
+            msg = socket.recv() => match msg {
+                Some(Ok(WsMessage::Close(_))) | Some(Err(_)) | None => break,
+                // Client messages are ignored (pings are answered by axum)
+                Some(Ok(_)) => {}
+            },
+        }
+    }
+
+    debug!("Status WebSocket client disconnected");
+}
+
+/// Poll component status and broadcast it to WebSocket clients
+pub(super) async fn status_poller(state: Arc<AppState>) {
+    let interval = Duration::from_millis(state.config.status_interval_ms.max(100));
+    let mut ticker = tokio::time::interval(interval);
+
+    loop {
+        tokio::select! {
+            _ = ticker.tick() => {}
+            _ = state.status_refresh.notified() => ticker.reset(),
+        }
+        if state.status_tx.receiver_count() == 0 {
+            continue;
+        }
+
+        match serde_json::to_string(&system_status(&state).await) {
+            Ok(json) => {
+                let _ = state.status_tx.send(Arc::new(json));
+            }
+            Err(e) => warn!("Failed to serialize system status: {}", e),
+        }
+    }
+}
+
+/// Middleware that wakes the status poller after a control command
+pub(super) async fn refresh_status_after(
+    State(state): State<Arc<AppState>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let response = next.run(request).await;
+    state.status_refresh.notify_one();
+    response
+}
@@ -4,7 +4,7 @@
 
 use chrono::{DateTime, Utc};
 use mongodb::{
-    bson::{doc, oid::ObjectId},
+    bson::{doc, oid::ObjectId, Document},
     options::ClientOptions,
     Client, Collection,
 };
@@ -31,6 +31,39 @@ pub struct RunStats {
     pub average_rate: f64,
 }
 
+/// Automatic stop conditions for a run
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct RunLimits {
+    /// Stop once this many events have been recorded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stop_after_events: Option<u64>,
+    /// Stop once the run has lasted this many seconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stop_after_secs: Option<u64>,
+}
+
+impl RunLimits {
+    /// Whether no limit is set
+    pub fn is_empty(&self) -> bool {
+        self.stop_after_events.is_none() && self.stop_after_secs.is_none()
+    }
+
+    /// Reason to stop the run, if `events` or `elapsed_secs` reached a limit
+    pub fn reached(&self, events: u64, elapsed_secs: i64) -> Option<String> {
+        if let Some(limit) = self.stop_after_events {
+            if events >= limit {
+                return Some(format!("{} events recorded (limit {})", events, limit));
+            }
+        }
+        if let Some(limit) = self.stop_after_secs {
+            if elapsed_secs >= 0 && elapsed_secs as u64 >= limit {
+                return Some(format!("run lasted {} s (limit {} s)", elapsed_secs, limit));
+            }
+        }
+        None
+    }
+}
+
 /// Error log entry
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ErrorLogEntry {
@@ -70,6 +103,9 @@ pub struct RunDocument {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub duration_secs: Option<i32>,
     pub status: RunStatus,
+    /// Why the run was aborted (only for `RunStatus::Aborted`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end_reason: Option<String>,
     #[serde(default)]
     pub stats: RunStats,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -95,6 +131,9 @@ pub struct CurrentRunInfo {
     /// Append-only notes (logbook style)
     #[serde(default)]
     pub notes: Vec<RunNote>,
+    /// Automatic stop conditions (empty = stop manually)
+    #[serde(default, skip_serializing_if = "RunLimits::is_empty")]
+    pub limits: RunLimits,
 }
 
 impl CurrentRunInfo {
@@ -112,6 +151,7 @@ impl CurrentRunInfo {
             status: doc.status,
             stats: doc.stats.clone(),
             notes: doc.notes.clone(),
+            limits: RunLimits::default(),
         }
     }
 }
@@ -129,6 +169,26 @@ pub enum RepositoryError {
     AlreadyExists(i32),
 }
 
+/// `$set` fields written when a run ends
+fn end_run_update(
+    end_time: DateTime<Utc>,
+    duration_secs: i32,
+    status: RunStatus,
+    reason: Option<&str>,
+    stats: &RunStats,
+) -> Document {
+    let mut update = doc! {
+        "end_time": mongodb::bson::DateTime::from_millis(end_time.timestamp_millis()),
+        "duration_secs": duration_secs,
+        "status": mongodb::bson::to_bson(&status).unwrap(),
+        "stats": mongodb::bson::to_bson(stats).unwrap(),
+    };
+    if let Some(reason) = reason {
+        update.insert("end_reason", reason);
+    }
+    update
+}
+
 /// MongoDB repository for run history
 #[derive(Clone)]
 pub struct RunRepository {
@@ -183,6 +243,7 @@ impl RunRepository {
             end_time: None,
             duration_secs: None,
             status: RunStatus::Running,
+            end_reason: None,
             stats: RunStats::default(),
             config_snapshot,
             errors: Vec::new(),
@@ -197,18 +258,20 @@ impl RunRepository {
     }
 
     /// End a run (completed, error, or aborted)
+    ///
+    /// `reason` is stored as `end_reason`, e.g. why a run was aborted.
     pub async fn end_run(
         &self,
         run_number: i32,
         exp_name: &str,
         status: RunStatus,
+        reason: Option<&str>,
         stats: RunStats,
     ) -> Result<(), RepositoryError> {
         let now = Utc::now();
 
         // Get start time to calculate duration (filter by exp_name + run_number)
         // Use raw Document to handle both BSON Date and string formats
-        use mongodb::bson::Document;
         let raw_collection = self.collection.clone_with_type::<Document>();
         let raw_doc = raw_collection
             .find_one(doc! { "run_number": run_number, "exp_name": exp_name })
@@ -232,14 +295,7 @@ impl RunRepository {
         self.collection
             .update_one(
                 doc! { "run_number": run_number, "exp_name": exp_name },
-                doc! {
-                    "$set": {
-                        "end_time": mongodb::bson::DateTime::from_millis(now.timestamp_millis()),
-                        "duration_secs": duration,
-                        "status": mongodb::bson::to_bson(&status).unwrap(),
-                        "stats": mongodb::bson::to_bson(&stats).unwrap(),
-                    }
-                },
+                doc! { "$set": end_run_update(now, duration, status, reason, &stats) },
             )
             .await?;
 
@@ -247,6 +303,7 @@ impl RunRepository {
             run_number = run_number,
             exp_name = exp_name,
             status = ?status,
+            reason = reason.unwrap_or_default(),
             duration_secs = duration,
             "Run ended"
         );
@@ -510,6 +567,33 @@ mod tests {
         assert_eq!(json, "\"completed\"");
     }
 
+    #[test]
+    fn test_end_run_update_aborted_vs_completed() {
+        let stats = RunStats {
+            total_events: 1000,
+            total_bytes: 64_000,
+            average_rate: 10.0,
+        };
+        let end = Utc::now();
+
+        let aborted = end_run_update(end, 100, RunStatus::Aborted, Some("beam lost"), &stats);
+        assert_eq!(aborted.get_str("status").unwrap(), "aborted");
+        assert_eq!(aborted.get_str("end_reason").unwrap(), "beam lost");
+        assert_eq!(aborted.get_i32("duration_secs").unwrap(), 100);
+
+        let completed = end_run_update(end, 100, RunStatus::Completed, None, &stats);
+        assert_eq!(completed.get_str("status").unwrap(), "completed");
+        assert!(completed.get("end_reason").is_none());
+        assert_eq!(
+            completed
+                .get_document("stats")
+                .unwrap()
+                .get_i64("total_events")
+                .unwrap(),
+            1000
+        );
+    }
+
     #[test]
     fn test_run_stats_default() {
         let stats = RunStats::default();
@@ -518,6 +602,30 @@ mod tests {
         assert_eq!(stats.average_rate, 0.0);
     }
 
+    #[test]
+    fn test_run_limits_reached() {
+        let limits = RunLimits {
+            stop_after_events: Some(1_000_000),
+            stop_after_secs: None,
+        };
+        assert!(limits.reached(999_999, 3600).is_none());
+        assert!(limits.reached(1_000_000, 0).is_some());
+        assert!(limits
+            .reached(1_000_500, 10)
+            .unwrap()
+            .contains("1000500 events"));
+
+        let limits = RunLimits {
+            stop_after_events: None,
+            stop_after_secs: Some(600),
+        };
+        assert!(limits.reached(u64::MAX, 599).is_none());
+        assert!(limits.reached(0, 600).is_some());
+
+        assert!(RunLimits::default().is_empty());
+        assert!(RunLimits::default().reached(u64::MAX, i64::MAX).is_none());
+    }
+
     #[test]
     fn test_current_run_info_elapsed() {
         let doc = RunDocument {
@@ -529,6 +637,7 @@ mod tests {
             end_time: None,
             duration_secs: None,
             status: RunStatus::Running,
+            end_reason: None,
             stats: RunStats::default(),
             config_snapshot: None,
             errors: Vec::new(),
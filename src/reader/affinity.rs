@@ -0,0 +1,82 @@
+//! CPU core pinning for the Reader hot path
+//!
+//! On multi-socket servers the scheduler may migrate the read and decode
+//! threads between cores (and NUMA nodes), which costs cache locality at
+//! high rates. With `read_core` / `decode_core` set, each thread pins itself
+//! before entering its loop. Pinning is best effort: an unknown core or a
+//! refused request is logged and the thread keeps running unpinned.
+//!
+//! Only threads created by [`spawn_dedicated`] are pinned. A `spawn_blocking`
+//! thread returns to Tokio's pool afterwards and would carry the pin into
+//! unrelated blocking work.
+
+use tokio::sync::oneshot;
+use tracing::{info, warn};
+
+/// Run `f` on a new thread named `name`, pinned to `core` if set
+///
+/// The receiver yields the result of `f`; it reports an error if `f`
+/// panicked. Panics if the thread cannot be created, like
+/// [`std::thread::spawn`].
+pub(crate) fn spawn_dedicated<F, T>(name: &str, core: Option<usize>, f: F) -> oneshot::Receiver<T>
+where
+    F: FnOnce() -> T + Send + 'static,
+    T: Send + 'static,
+{
+    let (tx, rx) = oneshot::channel();
+    let task = name.to_string();
+    std::thread::Builder::new()
+        .name(name.to_string())
+        .spawn(move || {
+            if let Some(core) = core {
+                pin_current_thread(core, &task);
+            }
+            let _ = tx.send(f());
+        })
+        .unwrap_or_else(|e| panic!("failed to spawn {} thread: {}", name, e));
+    rx
+}
+
+/// Pin the calling thread to `core`; returns whether the pin took effect
+pub(crate) fn pin_current_thread(core: usize, task: &str) -> bool {
+    let Some(core_ids) = core_affinity::get_core_ids() else {
+        warn!(task, core, "Cannot list CPU cores, running unpinned");
+        return false;
+    };
+    let Some(core_id) = core_ids.into_iter().find(|c| c.id == core) else {
+        warn!(task, core, "CPU core not available, running unpinned");
+        return false;
+    };
+    if core_affinity::set_for_current(core_id) {
+        info!(task, core, "Pinned to CPU core");
+        true
+    } else {
+        warn!(task, core, "Failed to set CPU affinity, running unpinned");
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unavailable_core_falls_back() {
+        let pinned = std::thread::spawn(|| pin_current_thread(usize::MAX, "test"))
+            .join()
+            .expect("pinning must not panic");
+        assert!(!pinned);
+    }
+
+    #[tokio::test]
+    async fn test_dedicated_thread_returns_result() {
+        let rx = spawn_dedicated("test-dedicated", None, || {
+            std::thread::current().name().map(str::to_string)
+        });
+        assert_eq!(rx.await.unwrap().as_deref(), Some("test-dedicated"));
+
+        // A panic is reported as a closed channel, not propagated
+        let rx = spawn_dedicated("test-panic", None, || -> u32 { panic!("boom") });
+        assert!(rx.await.is_err());
+    }
+}
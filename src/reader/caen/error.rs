@@ -70,6 +70,17 @@ impl CaenError {
             Some(err) => Err(err),
         }
     }
+
+    /// Whether this error means the device link is lost
+    ///
+    /// Fatal errors require closing and reopening the handle; anything else
+    /// (timeouts, parameter errors) can be retried on the same handle.
+    pub fn is_fatal(&self) -> bool {
+        matches!(
+            self.code,
+            codes::COMMUNICATION_ERROR | codes::INVALID_HANDLE | codes::DEVICE_NOT_FOUND
+        )
+    }
 }
 
 /// Common CAEN error codes (for pattern matching)
@@ -96,6 +107,23 @@ pub mod codes {
 mod tests {
     use super::*;
 
+    fn error_with_code(code: i32) -> CaenError {
+        CaenError {
+            code,
+            name: String::new(),
+            description: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_is_fatal() {
+        assert!(error_with_code(codes::COMMUNICATION_ERROR).is_fatal());
+        assert!(error_with_code(codes::INVALID_HANDLE).is_fatal());
+        assert!(!error_with_code(codes::TIMEOUT).is_fatal());
+        assert!(!error_with_code(codes::STOP).is_fatal());
+        assert!(!error_with_code(codes::INVALID_PARAM).is_fatal());
+    }
+
     #[test]
     fn test_success_returns_none() {
         assert!(CaenError::from_code(codes::SUCCESS).is_none());
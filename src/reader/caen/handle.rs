@@ -4,6 +4,8 @@
 
 use super::error::CaenError;
 use super::ffi;
+use crate::config::digitizer::{ParamRange, ValidationError};
+use std::collections::HashMap;
 use std::ffi::CString;
 
 // C wrapper for variadic CAEN_FELib_ReadData function
@@ -86,6 +88,47 @@ pub struct ParamInfo {
     pub unit: Option<String>,
 }
 
+/// Parameter write access, abstracted so logic can be tested without hardware
+pub trait ParamSetter {
+    /// Set a parameter value by path
+    fn set_value(&self, path: &str, value: &str) -> Result<(), CaenError>;
+}
+
+impl ParamSetter for CaenHandle {
+    fn set_value(&self, path: &str, value: &str) -> Result<(), CaenError> {
+        CaenHandle::set_value(self, path, value)
+    }
+}
+
+/// Acquisition commands, abstracted so arm/start sequencing can be tested without hardware
+pub trait AcquisitionControl {
+    /// Read a parameter value by path
+    fn get_value(&self, path: &str) -> Result<String, CaenError>;
+    /// Send a command by path
+    fn send_command(&self, path: &str) -> Result<(), CaenError>;
+}
+
+impl AcquisitionControl for CaenHandle {
+    fn get_value(&self, path: &str) -> Result<String, CaenError> {
+        CaenHandle::get_value(self, path)
+    }
+
+    fn send_command(&self, path: &str) -> Result<(), CaenError> {
+        CaenHandle::send_command(self, path)
+    }
+}
+
+impl From<&ParamInfo> for ParamRange {
+    fn from(info: &ParamInfo) -> Self {
+        let parse = |v: &Option<String>| v.as_deref().and_then(|s| s.trim().parse::<f64>().ok());
+        Self {
+            min: parse(&info.min_value),
+            max: parse(&info.max_value),
+            allowed_values: info.allowed_values.clone(),
+        }
+    }
+}
+
 impl CaenHandle {
     /// Open a connection to a CAEN device
     ///
@@ -505,6 +548,57 @@ impl CaenHandle {
         })
     }
 
+    /// Validate a DigitizerConfig against the declared limits in the DevTree
+    ///
+    /// Fetches the DevTree once and checks every parameter against its
+    /// `minvalue`/`maxvalue`/`allowedvalues`. Nothing is written to the device.
+    ///
+    /// # Returns
+    /// * `Ok(violations)` - Out-of-range parameters (empty if all valid)
+    /// * `Err(...)` - DevTree could not be read or parsed
+    pub fn validate_config(
+        &self,
+        config: &crate::config::digitizer::DigitizerConfig,
+    ) -> Result<Vec<ValidationError>, CaenError> {
+        let tree_json = self.get_device_tree()?;
+        let tree: serde_json::Value = serde_json::from_str(&tree_json).map_err(|e| CaenError {
+            code: -1,
+            name: "JsonParseError".to_string(),
+            description: format!("Failed to parse DevTree JSON: {}", e),
+        })?;
+        Ok(Self::validate_config_with_tree(&tree, config))
+    }
+
+    /// Validate a DigitizerConfig against the limits declared in a DevTree
+    ///
+    /// Pure lookup in the JSON, so it also works offline with a DevTree
+    /// saved earlier (e.g. by `caen_info`) and no device connected.
+    pub fn validate_config_with_tree(
+        tree: &serde_json::Value,
+        config: &crate::config::digitizer::DigitizerConfig,
+    ) -> Vec<ValidationError> {
+        let mut cache: HashMap<String, Option<ParamRange>> = HashMap::new();
+        config.validate(|param| {
+            let name = param.name();
+            cache
+                .entry(name.to_string())
+                .or_insert_with(|| {
+                    Self::find_param_in_tree(tree, name)
+                        .and_then(|node| Self::extract_param_info(name, node).ok())
+                        .map(|info| ParamRange::from(&info))
+                })
+                .clone()
+        })
+    }
+
+    /// Number of channels in a DevTree (the numbered entries of its `ch` folder)
+    pub fn num_channels_in_tree(tree: &serde_json::Value) -> u32 {
+        tree.get("ch")
+            .and_then(|ch| ch.as_object())
+            .map(|ch| ch.keys().filter(|k| k.parse::<u32>().is_ok()).count() as u32)
+            .unwrap_or(0)
+    }
+
     /// Apply digitizer configuration
     ///
     /// Applies all parameters from DigitizerConfig to the device.
@@ -523,6 +617,18 @@ impl CaenHandle {
     ) -> Result<usize, CaenError> {
         use tracing::{debug, info, warn};
 
+        // Validation pass: report out-of-range values before writing anything
+        match self.validate_config(config) {
+            Ok(violations) => {
+                for v in &violations {
+                    warn!(path = %v.path, value = %v.value, allowed = %v.allowed, "Parameter out of range");
+                }
+            }
+            Err(e) => {
+                debug!(error = %e, "Parameter validation skipped");
+            }
+        }
+
         let params = config.to_caen_parameters();
         info!("Applying {} parameters to digitizer", params.len());
 
@@ -614,8 +720,31 @@ impl EndpointHandle {
         timeout_ms: i32,
         buffer_size: usize,
     ) -> Result<Option<RawData>, CaenError> {
-        // Allocate buffer - must be pre-allocated like C++ version
-        let mut data = vec![0u8; buffer_size];
+        self.read_data_into(timeout_ms, buffer_size, &mut Vec::new())
+    }
+
+    /// Read raw data into a reusable buffer
+    ///
+    /// Same as [`read_data`](Self::read_data), but reads into `buffer`,
+    /// growing it to `buffer_size` if needed. On success the buffer is moved
+    /// into the returned `RawData` (leaving `buffer` empty); on timeout or
+    /// error it stays with the caller for the next read.
+    ///
+    /// RAW ReadData takes no buffer length and writes the whole aggregate, so
+    /// `buffer_size` must be at least the digitizer's `/par/maxrawdatasize`.
+    ///
+    /// # Panics
+    /// If the library reports more bytes than `buffer_size`: the write has
+    /// already run past the allocation and memory can no longer be trusted.
+    pub fn read_data_into(
+        &self,
+        timeout_ms: i32,
+        buffer_size: usize,
+        buffer: &mut Vec<u8>,
+    ) -> Result<Option<RawData>, CaenError> {
+        // Must be pre-allocated like C++ version; contents are written by the library
+        buffer.clear();
+        buffer.reserve(buffer_size);
         let mut size: usize = 0;
         let mut n_events: u32 = 0;
 
@@ -624,17 +753,24 @@ impl EndpointHandle {
             caen_read_data_raw(
                 self.handle,
                 timeout_ms,
-                data.as_mut_ptr(),
+                buffer.as_mut_ptr(),
                 &mut size,
                 &mut n_events,
             )
         };
 
         if ret == 0 {
-            // Success - truncate data to actual size
-            data.truncate(size);
+            assert!(
+                size <= buffer.capacity(),
+                "ReadData wrote {} bytes into a {}-byte buffer; buffer_size is below maxrawdatasize",
+                size,
+                buffer_size
+            );
+            // Success - expose the bytes written by the library
+            // SAFETY: ReadData wrote `size` bytes, within the reserved capacity
+            unsafe { buffer.set_len(size) };
             Ok(Some(RawData {
-                data,
+                data: std::mem::take(buffer),
                 size,
                 n_events,
             }))
@@ -687,6 +823,16 @@ mod tests {
         assert_eq!(raw.n_events, 1);
     }
 
+    #[test]
+    fn test_num_channels_in_tree() {
+        let tree = serde_json::json!({
+            "ch": { "0": {}, "1": {}, "2": {}, "handle": "0x42" },
+            "par": { "NumCh": { "value": "3" } }
+        });
+        assert_eq!(CaenHandle::num_channels_in_tree(&tree), 3);
+        assert_eq!(CaenHandle::num_channels_in_tree(&serde_json::json!({})), 0);
+    }
+
     #[test]
     fn test_raw_data_debug() {
         let raw = RawData {
@@ -907,4 +1053,21 @@ mod tests {
         assert!(info.allowed_values.contains(&"Positive".to_string()));
         assert!(info.allowed_values.contains(&"Negative".to_string()));
     }
+
+    #[test]
+    fn test_param_range_from_param_info() {
+        let info = ParamInfo {
+            name: "TriggerThr".to_string(),
+            datatype: "NUMBER".to_string(),
+            access_mode: "READ_WRITE".to_string(),
+            setinrun: true,
+            min_value: Some("0".to_string()),
+            max_value: Some("16383".to_string()),
+            allowed_values: vec![],
+            unit: None,
+        };
+        let range = ParamRange::from(&info);
+        assert!(range.contains("16383"));
+        assert!(!range.contains("16384"));
+    }
 }
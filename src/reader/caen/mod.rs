@@ -8,4 +8,6 @@ pub mod handle;
 
 // Re-exports for convenience
 pub use error::CaenError;
-pub use handle::{CaenHandle, DeviceInfo, EndpointHandle, ParamInfo, RawData};
+pub use handle::{
+    AcquisitionControl, CaenHandle, DeviceInfo, EndpointHandle, ParamInfo, ParamSetter, RawData,
+};
@@ -0,0 +1,118 @@
+//! Per-channel dead-time estimate from trigger-lost flags
+//!
+//! The digitizer flags the next event after triggers were lost while the
+//! channel was busy. Counting those events per channel against all events
+//! gives a busy fraction that physicists use to correct rates. It is a lower
+//! bound: a flag marks *at least* one lost trigger, not how many.
+//!
+//! Events keep the firmware's raw flag layout, so the caller passes the
+//! decoder's [`FlagMasks::trigger_lost`](super::decoder::FlagMasks) mask.
+
+use std::collections::BTreeMap;
+
+use crate::common::{ChannelDeadTime, EventData};
+
+/// Event and lost-flag counts per channel (reset on Start)
+#[derive(Debug, Clone, Default)]
+pub struct DeadTimeCounter {
+    /// channel -> (events, events with a lost flag)
+    channels: BTreeMap<u8, (u64, u64)>,
+}
+
+impl DeadTimeCounter {
+    /// Count the events of one batch, `lost_mask` selecting the lost flags
+    pub fn record(&mut self, events: &[EventData], lost_mask: u32) {
+        for event in events {
+            let (total, lost) = self.channels.entry(event.channel).or_default();
+            *total += 1;
+            if event.flags & u64::from(lost_mask) != 0 {
+                *lost += 1;
+            }
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.channels.clear();
+    }
+
+    /// Busy fraction of every channel that saw events, by channel number
+    pub fn channels(&self) -> Vec<ChannelDeadTime> {
+        self.channels
+            .iter()
+            .map(|(&channel, &(events, lost_flagged))| ChannelDeadTime {
+                channel,
+                events,
+                lost_flagged,
+                busy_fraction: fraction(lost_flagged, events),
+            })
+            .collect()
+    }
+
+    /// Busy fraction over all channels
+    pub fn total_fraction(&self) -> f64 {
+        let (events, lost) = self
+            .channels
+            .values()
+            .fold((0, 0), |(e, l), &(events, lost)| (e + events, l + lost));
+        fraction(lost, events)
+    }
+}
+
+fn fraction(lost: u64, events: u64) -> f64 {
+    if events == 0 {
+        0.0
+    } else {
+        lost as f64 / events as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reader::decoder::Psd1Decoder;
+
+    const LOST: u32 = Psd1Decoder::FLAG_MASKS.trigger_lost;
+
+    fn event(channel: u8, flags: u64) -> EventData {
+        EventData::new(0, channel, 100, 50, 0.0, flags)
+    }
+
+    #[test]
+    fn test_busy_fraction_from_lost_flags() {
+        let mut counter = DeadTimeCounter::default();
+        // Raw PSD1 flags: trigger lost 0x20, N lost 0x04, over-range 0x10.
+        // Channel 0: 1 of 4 flagged; channel 3: 2 of 2 flagged (either flag counts)
+        counter.record(
+            &[event(0, 0), event(0, 0x20), event(0, 0x10), event(3, 0x04)],
+            LOST,
+        );
+        counter.record(&[event(3, 0x20 | 0x04), event(0, 0)], LOST);
+
+        let channels = counter.channels();
+        assert_eq!(channels.len(), 2);
+        assert_eq!(
+            (
+                channels[0].channel,
+                channels[0].events,
+                channels[0].lost_flagged
+            ),
+            (0, 4, 1)
+        );
+        assert_eq!(channels[0].busy_fraction, 0.25);
+        assert_eq!(channels[1].channel, 3);
+        assert_eq!(channels[1].busy_fraction, 1.0);
+        assert_eq!(counter.total_fraction(), 0.5);
+
+        counter.reset();
+        assert!(counter.channels().is_empty());
+        assert_eq!(counter.total_fraction(), 0.0);
+    }
+
+    #[test]
+    fn test_no_trigger_lost_flag_counts_nothing() {
+        let mut counter = DeadTimeCounter::default();
+        counter.record(&[event(0, 0x20), event(0, 0xFFFF)], 0);
+        assert_eq!(counter.channels()[0].events, 2);
+        assert_eq!(counter.total_fraction(), 0.0);
+    }
+}
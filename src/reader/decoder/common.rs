@@ -2,6 +2,8 @@
 
 use serde::{Deserialize, Serialize};
 
+use crate::common::flags;
+
 /// Raw data from digitizer
 #[derive(Debug, Clone)]
 pub struct RawData {
@@ -55,6 +57,41 @@ pub enum DecodeResult {
     OutOfBounds,
 }
 
+/// Energy resolution assumed when none is configured (full 16-bit word)
+pub const DEFAULT_ADC_BITS: u8 = 16;
+
+/// Largest energy value representable with `adc_bits` (clamped to 1..=16 bits)
+pub fn adc_max(adc_bits: u8) -> u16 {
+    let bits = adc_bits.clamp(1, 16);
+    (u32::MAX >> (32 - bits as u32)) as u16
+}
+
+/// Flag bits for energy words above the ADC resolution
+///
+/// Such values can only come from overflowed charge integration. They are
+/// kept as read and marked with [`flags::FLAG_ENERGY_OUT_OF_RANGE`], so
+/// analysis decides what to do with them.
+pub fn energy_range_flags(adc_bits: u8, energies: &[u16]) -> u32 {
+    let max = adc_max(adc_bits);
+    if energies.iter().any(|&energy| energy > max) {
+        flags::FLAG_ENERGY_OUT_OF_RANGE as u32
+    } else {
+        0
+    }
+}
+
+/// Raw flag bits a firmware uses for conditions the Reader acts on
+///
+/// Decoders keep the digitizer's own flag layout in [`EventData::flags`],
+/// so code that interprets flags asks the decoder where they are.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FlagMasks {
+    /// Triggers were lost while the channel was busy (0 = not reported)
+    pub trigger_lost: u32,
+    /// Pileup detected
+    pub pileup: u32,
+}
+
 /// Waveform data from digitizer
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Waveform {
@@ -86,8 +123,8 @@ pub struct EventData {
     pub module: u8,
     /// Channel number (0-127 for PSD2)
     pub channel: u8,
-    /// Energy (long gate integral)
-    pub energy: u16,
+    /// Energy (long gate integral; 32 bits for firmware with wider energies)
+    pub energy: u32,
     /// Energy short (short gate integral)
     pub energy_short: u16,
     /// Fine timestamp (0-1023, /1024 scale)
@@ -5,7 +5,14 @@
 pub mod common;
 pub mod psd1;
 pub mod psd2;
+pub mod registry;
+pub mod zle;
 
-pub use common::{DataType, DecodeResult, EventData, RawData, Waveform};
+pub use common::{
+    adc_max, energy_range_flags, DataType, DecodeResult, EventData, FlagMasks, RawData, Waveform,
+    DEFAULT_ADC_BITS,
+};
 pub use psd1::{Psd1Config, Psd1Decoder};
 pub use psd2::{Psd2Config, Psd2Decoder};
+pub use registry::{Decoder, DecoderFactory, DecoderParams, DecoderRegistry};
+pub use zle::{ZleConfig, ZleDecoder};
@@ -14,7 +14,9 @@
 //! - Channel pairing: pair * 2 + channel_flag
 //! - 47-bit timestamp: (extended_time << 31) | trigger_time_tag
 
-use super::common::{DataType, EventData, RawData, Waveform};
+use super::common::{
+    energy_range_flags, DataType, EventData, FlagMasks, RawData, Waveform, DEFAULT_ADC_BITS,
+};
 
 // ---------------------------------------------------------------------------
 // Constants
@@ -75,6 +77,11 @@ mod constants {
         pub const FINE_TIME_MASK: u32 = 0x3FF;
         pub const FLAGS_SHIFT: u32 = 10;
         pub const FLAGS_MASK: u32 = 0x3F;
+        // Flag bits once shifted down by FLAGS_SHIFT
+        pub const FLAG_N_LOST_TRIGGERS: u32 = 0x04;
+        pub const FLAG_TRIGGER_LOST: u32 = 0x20;
+        // Pileup bit of the charge word, kept at the same position in flags
+        pub const FLAG_PILEUP: u32 = 1 << PILEUP_SHIFT;
         pub const EXTENDED_TIME_SHIFT: u32 = 16;
         pub const EXTENDED_TIME_MASK: u32 = 0xFFFF;
 
@@ -164,8 +171,11 @@ pub struct Psd1Config {
     pub time_step_ns: f64,
     /// Module identifier for EventData output
     pub module_id: u8,
-    /// Enable debug dump output
+    /// Print decode diagnostics to stdout (file dumps are written by the
+    /// Reader, see `ReaderConfig::dump_raw`)
     pub dump_enabled: bool,
+    /// Charge resolution in bits (default: 16); larger charges are flagged
+    pub adc_bits: u8,
 }
 
 impl Default for Psd1Config {
@@ -174,6 +184,7 @@ impl Default for Psd1Config {
             time_step_ns: 2.0, // DT5730: 500 MS/s
             module_id: 0,
             dump_enabled: false,
+            adc_bits: DEFAULT_ADC_BITS,
         }
     }
 }
@@ -185,6 +196,12 @@ pub struct Psd1Decoder {
 }
 
 impl Psd1Decoder {
+    /// Extras-word flags as they land in [`EventData::flags`]
+    pub const FLAG_MASKS: FlagMasks = FlagMasks {
+        trigger_lost: constants::event::FLAG_TRIGGER_LOST | constants::event::FLAG_N_LOST_TRIGGERS,
+        pileup: constants::event::FLAG_PILEUP,
+    };
+
     /// Create a new PSD1 decoder with given configuration
     pub fn new(config: Psd1Config) -> Self {
         Self {
@@ -232,22 +249,31 @@ impl Psd1Decoder {
 
     /// Decode raw data into events
     pub fn decode(&mut self, raw: &RawData) -> Vec<EventData> {
+        let mut events = Vec::new();
+        self.decode_into(raw, &mut events);
+        events
+    }
+
+    /// Decode raw data into `events`, reusing its allocation
+    ///
+    /// `events` is cleared first; it is left empty for non-event buffers.
+    pub fn decode_into(&mut self, raw: &RawData, events: &mut Vec<EventData>) {
+        events.clear();
         let data_type = self.classify(raw);
         if data_type != DataType::Event {
             if self.config.dump_enabled {
                 println!("[PSD1] Non-event data, size={}", raw.size);
             }
-            return vec![];
+            return;
         }
 
         let total_bytes = raw.size;
         let mut offset: usize = 0;
-        let mut all_events = Vec::new();
 
         // Process multiple board aggregate blocks
         while offset + constants::board_header::HEADER_SIZE_BYTES <= total_bytes {
             match self.decode_board_aggregate(&raw.data, &mut offset) {
-                Ok(mut events) => all_events.append(&mut events),
+                Ok(mut aggregate_events) => events.append(&mut aggregate_events),
                 Err(msg) => {
                     if self.config.dump_enabled {
                         println!("[PSD1] Board aggregate error: {}", msg);
@@ -258,17 +284,15 @@ impl Psd1Decoder {
         }
 
         // Sort by timestamp
-        all_events.sort_by(|a, b| {
+        events.sort_by(|a, b| {
             a.timestamp_ns
                 .partial_cmp(&b.timestamp_ns)
                 .unwrap_or(std::cmp::Ordering::Equal)
         });
 
         if self.config.dump_enabled {
-            println!("[PSD1] Decoded {} events", all_events.len());
+            println!("[PSD1] Decoded {} events", events.len());
         }
-
-        all_events
     }
 
     // -----------------------------------------------------------------------
@@ -491,8 +515,9 @@ impl Psd1Decoder {
             let (cl, cs, pileup) = decode_charge_word(w);
             charge_long = cl;
             charge_short = cs;
+            flags |= energy_range_flags(self.config.adc_bits, &[cl, cs]);
             if pileup {
-                flags |= 1 << 15; // Pileup flag at bit 15
+                flags |= constants::event::FLAG_PILEUP;
             }
         }
 
@@ -514,7 +539,7 @@ impl Psd1Decoder {
             timestamp_ns,
             module: self.config.module_id,
             channel,
-            energy: charge_long,
+            energy: charge_long.into(),
             energy_short: charge_short,
             fine_time,
             flags,
@@ -779,6 +804,7 @@ mod tests {
             time_step_ns: 2.0,
             module_id: 0,
             dump_enabled: false,
+            adc_bits: 16,
         })
     }
 
@@ -800,6 +826,7 @@ mod tests {
             time_step_ns: 4.0,
             module_id: 5,
             dump_enabled: true,
+            adc_bits: 16,
         });
         assert_eq!(dec.config.time_step_ns, 4.0);
         assert_eq!(dec.config.module_id, 5);
@@ -1175,7 +1202,7 @@ mod tests {
 
         let mut data = make_board_header(total_size as u32, 0x01, 0, 1);
         data.extend(make_dual_channel_header(ch_size as u32, &ch_flags));
-        // flags = 0x2A (0b101010): trigger_lost + 1024_triggers
+        // flags = 0x2A (0b101010): trigger_lost + 1024_triggers + bit 1
         data.extend(make_event(1000, false, 0, 0x2A, 0, 100, 50));
 
         let raw = RawData::new(data);
@@ -1183,6 +1210,30 @@ mod tests {
         assert_eq!(events[0].flags, 0x2A);
     }
 
+    #[test]
+    fn test_flag_masks_trigger_lost() {
+        let mut dec = default_decoder();
+
+        let ch_flags = DualChFlags::default();
+        let ch_size = 2 + 3 * 3;
+        let total_size = 4 + ch_size;
+
+        let mut data = make_board_header(total_size as u32, 0x01, 0, 1);
+        data.extend(make_dual_channel_header(ch_size as u32, &ch_flags));
+        // trigger lost, N lost triggers, over-range
+        data.extend(make_event(1000, false, 0, 0x20, 0, 100, 50));
+        data.extend(make_event(2000, false, 0, 0x04, 0, 100, 50));
+        data.extend(make_event(3000, false, 0, 0x10, 0, 100, 50));
+
+        let raw = RawData::new(data);
+        let events = dec.decode(&raw);
+        let lost: Vec<bool> = events
+            .iter()
+            .map(|e| e.flags & Psd1Decoder::FLAG_MASKS.trigger_lost != 0)
+            .collect();
+        assert_eq!(lost, vec![true, true, false]);
+    }
+
     #[test]
     fn test_decode_pileup_flag() {
         let mut dec = default_decoder();
@@ -1201,6 +1252,7 @@ mod tests {
         let raw = RawData::new(data);
         let events = dec.decode(&raw);
         assert_ne!(events[0].flags & (1 << 15), 0); // pileup at bit 15
+        assert_ne!(events[0].flags & Psd1Decoder::FLAG_MASKS.pileup, 0);
     }
 
     // -----------------------------------------------------------------------
@@ -1484,6 +1536,7 @@ mod tests {
             time_step_ns: 2.0,
             module_id: 7,
             dump_enabled: false,
+            adc_bits: 16,
         });
 
         let ch_flags = DualChFlags::default();
@@ -2,7 +2,9 @@
 //!
 //! Decodes 64-bit word format data from DPP-PSD firmware.
 
-use super::common::{DataType, EventData, RawData, Waveform};
+use super::common::{
+    energy_range_flags, DataType, EventData, FlagMasks, RawData, Waveform, DEFAULT_ADC_BITS,
+};
 
 /// PSD2 constants (64-bit words, Little Endian)
 mod constants {
@@ -34,6 +36,8 @@ mod constants {
     pub const FLAGS_LOW_PRIORITY_MASK: u64 = 0xFFF; // 12 bits (C++ dpppsd.hpp: flag_low_priority{12})
     pub const FLAGS_HIGH_PRIORITY_SHIFT: u32 = 42;
     pub const FLAGS_HIGH_PRIORITY_MASK: u64 = 0xFF;
+    // flags_high lands at bit 12 of the event flags; its bit 0 is pileup
+    pub const FLAG_PILEUP: u32 = 1 << 12;
     pub const ENERGY_SHORT_SHIFT: u32 = 26;
     pub const ENERGY_SHORT_MASK: u64 = 0xFFFF;
     pub const FINE_TIME_SHIFT: u32 = 16;
@@ -86,10 +90,13 @@ pub struct Psd2Config {
     pub time_step_ns: f64,
     /// Module ID for identification
     pub module_id: u8,
-    /// Enable dump output for debugging
+    /// Print raw words and decode diagnostics to stdout (file dumps are
+    /// written by the Reader, see `ReaderConfig::dump_raw`)
     pub dump_enabled: bool,
     /// Number of physical channels (events with channel >= this are logged as warnings)
     pub num_channels: u8,
+    /// Energy resolution in bits (default: 16); larger energies are flagged
+    pub adc_bits: u8,
 }
 
 impl Default for Psd2Config {
@@ -99,6 +106,7 @@ impl Default for Psd2Config {
             module_id: 0,
             dump_enabled: false,
             num_channels: 32,
+            adc_bits: DEFAULT_ADC_BITS,
         }
     }
 }
@@ -111,6 +119,16 @@ pub struct Psd2Decoder {
 }
 
 impl Psd2Decoder {
+    /// Flags as they land in [`EventData::flags`] (`flags_high << 12 | flags_low`)
+    ///
+    /// The trigger-lost bit of the PSD2 flag words has not been confirmed
+    /// against the CAEN format yet, so it is left unset: dead time then
+    /// reads 0 instead of counting an unrelated flag.
+    pub const FLAG_MASKS: FlagMasks = FlagMasks {
+        trigger_lost: 0,
+        pileup: constants::FLAG_PILEUP,
+    };
+
     /// Create a new PSD2 decoder with given configuration
     pub fn new(config: Psd2Config) -> Self {
         Self {
@@ -150,6 +168,16 @@ impl Psd2Decoder {
 
     /// Decode raw data into events
     pub fn decode(&mut self, raw: &RawData) -> Vec<EventData> {
+        let mut events = Vec::new();
+        self.decode_into(raw, &mut events);
+        events
+    }
+
+    /// Decode raw data into `events`, reusing its allocation
+    ///
+    /// `events` is cleared first; it is left empty for non-event buffers.
+    pub fn decode_into(&mut self, raw: &RawData, events: &mut Vec<EventData>) {
+        events.clear();
         if self.config.dump_enabled {
             self.dump_raw_data(raw);
         }
@@ -161,19 +189,19 @@ impl Psd2Decoder {
                 if self.config.dump_enabled {
                     println!("[PSD2] Start signal detected");
                 }
-                return vec![];
+                return;
             }
             DataType::Stop => {
                 if self.config.dump_enabled {
                     println!("[PSD2] Stop signal detected");
                 }
-                return vec![];
+                return;
             }
             DataType::Unknown => {
                 if self.config.dump_enabled {
                     println!("[PSD2] Unknown data type, size={}", raw.size);
                 }
-                return vec![];
+                return;
             }
             DataType::Event => {}
         }
@@ -181,12 +209,12 @@ impl Psd2Decoder {
         // Read header
         let header = self.read_u64(&raw.data, 0);
         if !self.validate_header(header, raw.size) {
-            return vec![];
+            return;
         }
 
         let total_size = (header & constants::TOTAL_SIZE_MASK) as usize;
         let total_words = raw.data.len() / constants::WORD_SIZE;
-        let mut events = Vec::with_capacity(total_size / 2);
+        events.reserve(total_size / 2);
         let mut word_index = 1; // Skip header
         let mut out_of_range_count = 0u32;
 
@@ -259,8 +287,6 @@ impl Psd2Decoder {
         if self.config.dump_enabled {
             println!("[PSD2] Decoded {} events", events.len());
         }
-
-        events
     }
 
     /// Dump raw data for debugging
@@ -429,11 +455,11 @@ impl Psd2Decoder {
             & constants::FLAGS_LOW_PRIORITY_MASK;
         let flags_high = (second_word >> constants::FLAGS_HIGH_PRIORITY_SHIFT)
             & constants::FLAGS_HIGH_PRIORITY_MASK;
-        let flags = ((flags_high << 12) | flags_low) as u32;
-
         let energy = (second_word & constants::ENERGY_MASK) as u16;
         let energy_short =
             ((second_word >> constants::ENERGY_SHORT_SHIFT) & constants::ENERGY_SHORT_MASK) as u16;
+        let flags = ((flags_high << 12) | flags_low) as u32
+            | energy_range_flags(self.config.adc_bits, &[energy, energy_short]);
 
         let fine_time =
             ((second_word >> constants::FINE_TIME_SHIFT) & constants::FINE_TIME_MASK) as u16;
@@ -471,7 +497,7 @@ impl Psd2Decoder {
             timestamp_ns,
             module: self.config.module_id,
             channel,
-            energy,
+            energy: energy.into(),
             energy_short,
             fine_time,
             flags,
@@ -494,7 +520,8 @@ impl Psd2Decoder {
         let energy = (word & constants::ENERGY_MASK) as u16;
 
         let timestamp_ns = (timestamp_reduced as f64) * self.config.time_step_ns;
-        let flags = flags_high << 12; // high priority only, low priority not available
+        // High priority only, low priority not available
+        let flags = (flags_high << 12) | energy_range_flags(self.config.adc_bits, &[energy]);
 
         if self.config.dump_enabled {
             println!("--- Single-word Event ---");
@@ -508,7 +535,7 @@ impl Psd2Decoder {
             timestamp_ns,
             module: self.config.module_id,
             channel,
-            energy,
+            energy: energy.into(),
             energy_short: 0,
             fine_time: 0,
             flags,
@@ -672,6 +699,7 @@ impl Psd2Decoder {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::common::flags::FLAG_ENERGY_OUT_OF_RANGE;
 
     #[test]
     fn test_decoder_creation() {
@@ -687,6 +715,7 @@ mod tests {
             module_id: 5,
             dump_enabled: true,
             num_channels: 32,
+            adc_bits: 16,
         };
         let decoder = Psd2Decoder::new(config);
         assert_eq!(decoder.config.time_step_ns, 4.0);
@@ -955,6 +984,83 @@ mod tests {
         ((last_word as u64) << 63) | (((extra_type as u64) & 0x7) << 60)
     }
 
+    #[test]
+    fn test_decode_into_reuses_vector() {
+        let mut decoder = Psd2Decoder::with_defaults();
+        let data = words_to_bytes(&[
+            make_header(5),
+            make_first_word(1, 2000),
+            make_second_word(true, false, 0, 0, 10, 0, 300),
+            make_first_word(2, 1000),
+            make_second_word(true, false, 0, 0, 20, 0, 400),
+        ]);
+        let raw = RawData {
+            size: data.len(),
+            data,
+            n_events: 2,
+        };
+
+        let mut events = Vec::new();
+        decoder.decode_into(&raw, &mut events);
+        assert_eq!(events.len(), 2);
+        let capacity = events.capacity();
+
+        // Second decode replaces the previous events without reallocating
+        decoder.decode_into(&raw, &mut events);
+        assert_eq!(events.len(), 2);
+        assert_eq!(events.capacity(), capacity);
+        assert_eq!(events[0].channel, 2);
+        assert_eq!(events[1].energy, 300);
+
+        // Non-event buffers leave it empty
+        let too_short = RawData {
+            data: vec![0u8; 8],
+            size: 8,
+            n_events: 0,
+        };
+        decoder.decode_into(&too_short, &mut events);
+        assert!(events.is_empty());
+    }
+
+    #[test]
+    fn test_energy_above_adc_bits_is_flagged() {
+        let mut decoder = Psd2Decoder::new(Psd2Config {
+            adc_bits: 12,
+            ..Psd2Config::default()
+        });
+        let data = words_to_bytes(&[
+            make_header(3),
+            make_first_word(0, 1000),
+            make_second_word(true, false, 0, 0, 5000, 0, 4000),
+        ]);
+        let raw = RawData {
+            size: data.len(),
+            data,
+            n_events: 1,
+        };
+
+        let events = decoder.decode(&raw);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].energy, 4000);
+        // Kept as read, not clamped
+        assert_eq!(events[0].energy_short, 5000);
+        assert_ne!(events[0].flags & FLAG_ENERGY_OUT_OF_RANGE as u32, 0);
+
+        // The top code is in range
+        let data = words_to_bytes(&[
+            make_header(3),
+            make_first_word(0, 1000),
+            make_second_word(true, false, 0, 0, 4095, 0, 4095),
+        ]);
+        let raw = RawData {
+            size: data.len(),
+            data,
+            n_events: 1,
+        };
+        let events = decoder.decode(&raw);
+        assert_eq!(events[0].flags & FLAG_ENERGY_OUT_OF_RANGE as u32, 0);
+    }
+
     #[test]
     fn test_decode_single_word_event() {
         let mut decoder = Psd2Decoder::with_defaults();
@@ -1068,6 +1174,32 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_flag_masks_pileup() {
+        let mut decoder = Psd2Decoder::with_defaults();
+
+        // Pileup (flags_high bit 0), then flags_low bit 0 only
+        let data = words_to_bytes(&[
+            make_header(5),
+            make_first_word(1, 500),
+            make_second_word(true, false, 0, 0x01, 100, 50, 3000),
+            make_first_word(1, 600),
+            make_second_word(true, false, 0x001, 0, 100, 50, 3000),
+        ]);
+        let raw = RawData {
+            size: data.len(),
+            data,
+            n_events: 2,
+        };
+
+        let events = decoder.decode(&raw);
+        let pileup: Vec<bool> = events
+            .iter()
+            .map(|e| e.flags & Psd2Decoder::FLAG_MASKS.pileup != 0)
+            .collect();
+        assert_eq!(pileup, vec![true, false]);
+    }
+
     #[test]
     fn test_decode_mixed_single_and_standard_events() {
         let mut decoder = Psd2Decoder::with_defaults();
@@ -0,0 +1,217 @@
+//! Decoder selection by firmware type
+//!
+//! The Reader looks up its decoder in a [`DecoderRegistry`] keyed by
+//! [`FirmwareType`], so a new firmware only needs a [`Decoder`] impl and a
+//! `register` call. Dispatch goes through `Box<dyn Decoder>` once per raw
+//! buffer; the per-event loop stays inside the concrete decoder.
+
+use std::collections::HashMap;
+
+use super::common::{DataType, EventData, FlagMasks, RawData};
+use super::psd1::{Psd1Config, Psd1Decoder};
+use super::psd2::{Psd2Config, Psd2Decoder};
+use super::zle::{ZleConfig, ZleDecoder};
+use crate::config::FirmwareType;
+
+/// Raw data decoder for one firmware
+pub trait Decoder: Send {
+    /// Classify a raw buffer (Start/Stop/Event/Unknown)
+    fn classify(&self, raw: &RawData) -> DataType;
+
+    /// Decode all events of a raw buffer, appending to `events`
+    fn decode_into(&mut self, raw: &RawData, events: &mut Vec<EventData>);
+
+    /// Where this firmware puts the flags the Reader interprets
+    fn flag_masks(&self) -> FlagMasks {
+        FlagMasks::default()
+    }
+}
+
+/// Per-source settings a decoder is built with
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DecoderParams {
+    /// Time step in nanoseconds (2 ns for 500 MS/s)
+    pub time_step_ns: f64,
+    /// Module ID for decoded events
+    pub module_id: u8,
+    /// Energy resolution in bits
+    pub adc_bits: u8,
+    /// Print decode diagnostics (`dump_raw` debugging)
+    pub dump_enabled: bool,
+}
+
+/// Builds a decoder from per-source settings
+pub type DecoderFactory = fn(&DecoderParams) -> Box<dyn Decoder>;
+
+/// Decoder factories keyed by firmware type
+#[derive(Debug, Clone)]
+pub struct DecoderRegistry {
+    factories: HashMap<FirmwareType, DecoderFactory>,
+}
+
+impl DecoderRegistry {
+    /// Registry without any decoder
+    pub fn empty() -> Self {
+        Self {
+            factories: HashMap::new(),
+        }
+    }
+
+    /// Register (or replace) the decoder for a firmware type
+    pub fn register(&mut self, firmware: FirmwareType, factory: DecoderFactory) -> &mut Self {
+        self.factories.insert(firmware, factory);
+        self
+    }
+
+    /// Whether a decoder is registered for `firmware`
+    pub fn supports(&self, firmware: FirmwareType) -> bool {
+        self.factories.contains_key(&firmware)
+    }
+
+    /// Build the decoder for `firmware` (None if none is registered)
+    pub fn create(
+        &self,
+        firmware: FirmwareType,
+        params: &DecoderParams,
+    ) -> Option<Box<dyn Decoder>> {
+        self.factories.get(&firmware).map(|factory| factory(params))
+    }
+}
+
+/// Registry with the built-in PSD1, PSD2 and ZLE decoders (no PHA yet)
+impl Default for DecoderRegistry {
+    fn default() -> Self {
+        let mut registry = Self::empty();
+        registry
+            .register(FirmwareType::PSD2, |p| {
+                Box::new(Psd2Decoder::new(Psd2Config {
+                    time_step_ns: p.time_step_ns,
+                    module_id: p.module_id,
+                    dump_enabled: p.dump_enabled,
+                    num_channels: 32,
+                    adc_bits: p.adc_bits,
+                }))
+            })
+            .register(FirmwareType::PSD1, |p| {
+                Box::new(Psd1Decoder::new(Psd1Config {
+                    time_step_ns: p.time_step_ns,
+                    module_id: p.module_id,
+                    dump_enabled: p.dump_enabled,
+                    adc_bits: p.adc_bits,
+                }))
+            })
+            // ZLE records carry no energy, so adc_bits does not apply
+            .register(FirmwareType::ZLE, |p| {
+                Box::new(ZleDecoder::new(ZleConfig {
+                    time_step_ns: p.time_step_ns,
+                    module_id: p.module_id,
+                    dump_enabled: p.dump_enabled,
+                }))
+            });
+        registry
+    }
+}
+
+impl Decoder for Psd2Decoder {
+    fn classify(&self, raw: &RawData) -> DataType {
+        Psd2Decoder::classify(self, raw)
+    }
+
+    fn decode_into(&mut self, raw: &RawData, events: &mut Vec<EventData>) {
+        Psd2Decoder::decode_into(self, raw, events)
+    }
+
+    fn flag_masks(&self) -> FlagMasks {
+        Psd2Decoder::FLAG_MASKS
+    }
+}
+
+impl Decoder for Psd1Decoder {
+    fn classify(&self, raw: &RawData) -> DataType {
+        Psd1Decoder::classify(self, raw)
+    }
+
+    fn decode_into(&mut self, raw: &RawData, events: &mut Vec<EventData>) {
+        Psd1Decoder::decode_into(self, raw, events)
+    }
+
+    fn flag_masks(&self) -> FlagMasks {
+        Psd1Decoder::FLAG_MASKS
+    }
+}
+
+impl Decoder for ZleDecoder {
+    fn classify(&self, raw: &RawData) -> DataType {
+        ZleDecoder::classify(self, raw)
+    }
+
+    fn decode_into(&mut self, raw: &RawData, events: &mut Vec<EventData>) {
+        ZleDecoder::decode_into(self, raw, events)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn params() -> DecoderParams {
+        DecoderParams {
+            time_step_ns: 2.0,
+            module_id: 4,
+            adc_bits: 16,
+            dump_enabled: false,
+        }
+    }
+
+    #[test]
+    fn test_default_registry_has_builtin_decoders() {
+        let registry = DecoderRegistry::default();
+        assert!(registry.supports(FirmwareType::PSD2));
+        assert!(registry.supports(FirmwareType::PSD1));
+        assert!(registry.supports(FirmwareType::ZLE));
+        assert!(registry.create(FirmwareType::PHA, &params()).is_none());
+    }
+
+    /// Emits one event per buffer with the buffer size as energy
+    struct SizeDecoder {
+        module_id: u8,
+    }
+
+    impl Decoder for SizeDecoder {
+        fn classify(&self, _raw: &RawData) -> DataType {
+            DataType::Event
+        }
+
+        fn decode_into(&mut self, raw: &RawData, events: &mut Vec<EventData>) {
+            events.push(EventData {
+                timestamp_ns: 0.0,
+                module: self.module_id,
+                channel: 0,
+                energy: raw.size as u32,
+                energy_short: 0,
+                fine_time: 0,
+                flags: 0,
+                waveform: None,
+            });
+        }
+    }
+
+    #[test]
+    fn test_registered_decoder_is_selected() {
+        let mut registry = DecoderRegistry::default();
+        registry.register(FirmwareType::PHA, |p| {
+            Box::new(SizeDecoder {
+                module_id: p.module_id,
+            })
+        });
+
+        let mut decoder = registry.create(FirmwareType::PHA, &params()).unwrap();
+        let raw = RawData::new(vec![0u8; 24]);
+        assert_eq!(decoder.classify(&raw), DataType::Event);
+
+        let mut events = Vec::new();
+        decoder.decode_into(&raw, &mut events);
+        assert_eq!(events.len(), 1);
+        assert_eq!((events[0].module, events[0].energy), (4, 24));
+    }
+}
@@ -0,0 +1,396 @@
+//! ZLE Decoder for CAEN x27xx series digitizers (DPP-ZLE firmware)
+//!
+//! Zero length encoding drops the samples of a record that stay close to the
+//! baseline and ships only the regions around pulses ("chunks"). Aggregates
+//! use the same framing as PSD2 (64-bit Big Endian words, type 0x2 header,
+//! 3/4-word Stop/Start signals); each event is laid out as:
+//!
+//! ```text
+//! word 0      [63] 0 | [62:56] channel | [55:48] reserved | [47:0] timestamp (samples)
+//! word 1      [63:48] flags | [47:32] baseline | [31:16] n_chunks | [15:0] record length
+//! per chunk:
+//!   descriptor  [63:32] first sample index | [31:0] chunk size (samples)
+//!   data        ceil(size / 4) words, 4 x 16-bit samples each, first sample in [15:0]
+//! ```
+//!
+//! # Gaps
+//!
+//! The decoder reconstructs the full record in `Waveform::analog_probe1`:
+//! suppressed regions are filled with the event baseline, so sample `i` is
+//! always at `timestamp + i * time_step` and downstream code (Monitor,
+//! Recorder) needs no ZLE-specific handling. `digital_probe1` marks the
+//! samples that were actually transmitted (1) versus reconstructed (0).
+//!
+//! ZLE records carry no charge integration, so `energy` and `energy_short`
+//! are 0.
+//!
+//! # Status
+//!
+//! The event layout above is provisional: it has not yet been checked
+//! against CAEN's DPP-ZLE raw data format documentation or a buffer
+//! captured from a ZLE board. The unit tests build aggregates in the same
+//! layout, so they only cover reconstruction and bounds handling. Once a
+//! capture is recorded with `raw_record_dir`, `tests/zle_capture_test.rs`
+//! checks the decoder against it.
+
+use super::common::{DataType, EventData, RawData, Waveform};
+
+/// ZLE constants (64-bit words, Big Endian on the wire)
+mod constants {
+    pub const WORD_SIZE: usize = 8;
+
+    // Header (shared with PSD2)
+    pub const HEADER_TYPE_SHIFT: u32 = 60;
+    pub const HEADER_TYPE_MASK: u64 = 0xF;
+    pub const HEADER_TYPE_DATA: u64 = 0x2;
+    pub const TOTAL_SIZE_MASK: u64 = 0xFFFFFFFF;
+
+    // Event first word
+    pub const CHANNEL_SHIFT: u32 = 56;
+    pub const CHANNEL_MASK: u64 = 0x7F;
+    pub const TIMESTAMP_MASK: u64 = 0xFFFFFFFFFFFF;
+
+    // Event second word
+    pub const FLAGS_SHIFT: u32 = 48;
+    pub const FLAGS_MASK: u64 = 0xFFFF;
+    pub const BASELINE_SHIFT: u32 = 32;
+    pub const BASELINE_MASK: u64 = 0xFFFF;
+    pub const N_CHUNKS_SHIFT: u32 = 16;
+    pub const N_CHUNKS_MASK: u64 = 0xFFFF;
+    pub const RECORD_LENGTH_MASK: u64 = 0xFFFF;
+
+    // Chunk descriptor
+    pub const CHUNK_BEGIN_SHIFT: u32 = 32;
+    pub const CHUNK_SIZE_MASK: u64 = 0xFFFFFFFF;
+    pub const SAMPLES_PER_WORD: usize = 4;
+
+    // Start/Stop signals (shared with PSD2)
+    pub const SIGNAL_TYPE_SHIFT: u32 = 60;
+    pub const SIGNAL_SUBTYPE_SHIFT: u32 = 56;
+    pub const SIGNAL_TYPE_MASK: u64 = 0xF;
+    pub const SIGNAL_TYPE: u64 = 0x3;
+    pub const START_SIGNAL_SUBTYPE: u64 = 0x0;
+    pub const STOP_SIGNAL_SUBTYPE: u64 = 0x2;
+
+    // Validation
+    pub const MIN_DATA_SIZE: usize = 3 * WORD_SIZE; // header + 2-word event
+    pub const START_SIGNAL_SIZE: usize = 4 * WORD_SIZE;
+    pub const STOP_SIGNAL_SIZE: usize = 3 * WORD_SIZE;
+}
+
+/// ZLE decoder configuration
+#[derive(Debug, Clone)]
+pub struct ZleConfig {
+    /// Time step in nanoseconds (VX2730: 2 ns at 500 MS/s)
+    pub time_step_ns: f64,
+    /// Module identifier for EventData output
+    pub module_id: u8,
+    /// Print decode diagnostics to stdout
+    pub dump_enabled: bool,
+}
+
+impl Default for ZleConfig {
+    fn default() -> Self {
+        Self {
+            time_step_ns: 2.0, // 500 MS/s
+            module_id: 0,
+            dump_enabled: false,
+        }
+    }
+}
+
+/// ZLE Decoder for x27xx series digitizers
+#[derive(Debug, Clone)]
+pub struct ZleDecoder {
+    config: ZleConfig,
+}
+
+impl ZleDecoder {
+    /// Create a new ZLE decoder with given configuration
+    pub fn new(config: ZleConfig) -> Self {
+        Self { config }
+    }
+
+    /// Create a decoder with default configuration
+    pub fn with_defaults() -> Self {
+        Self::new(ZleConfig::default())
+    }
+
+    /// Enable or disable dump output
+    pub fn set_dump_enabled(&mut self, enabled: bool) {
+        self.config.dump_enabled = enabled;
+    }
+
+    /// Classify the data type (Start/Stop/Event/Unknown)
+    pub fn classify(&self, raw: &RawData) -> DataType {
+        if raw.size == constants::STOP_SIGNAL_SIZE
+            && self.is_signal(&raw.data, constants::STOP_SIGNAL_SUBTYPE)
+        {
+            return DataType::Stop;
+        }
+        if raw.size == constants::START_SIGNAL_SIZE
+            && self.is_signal(&raw.data, constants::START_SIGNAL_SUBTYPE)
+        {
+            return DataType::Start;
+        }
+        if raw.size < constants::MIN_DATA_SIZE {
+            return DataType::Unknown;
+        }
+        DataType::Event
+    }
+
+    /// Decode raw data into events
+    pub fn decode(&mut self, raw: &RawData) -> Vec<EventData> {
+        let mut events = Vec::new();
+        self.decode_into(raw, &mut events);
+        events
+    }
+
+    /// Decode raw data into `events`, reusing its allocation
+    ///
+    /// `events` is cleared first; it is left empty for non-event buffers.
+    pub fn decode_into(&mut self, raw: &RawData, events: &mut Vec<EventData>) {
+        events.clear();
+        if self.classify(raw) != DataType::Event {
+            if self.config.dump_enabled {
+                println!("[ZLE] Non-event buffer, size={}", raw.size);
+            }
+            return;
+        }
+
+        let header = self.read_u64(&raw.data, 0);
+        let header_type = (header >> constants::HEADER_TYPE_SHIFT) & constants::HEADER_TYPE_MASK;
+        if header_type != constants::HEADER_TYPE_DATA {
+            if self.config.dump_enabled {
+                println!("[ZLE] Invalid header type: 0x{:x}", header_type);
+            }
+            return;
+        }
+
+        // Never read past the buffer, whatever the header claims
+        let total_words = ((header & constants::TOTAL_SIZE_MASK) as usize)
+            .min(raw.data.len() / constants::WORD_SIZE);
+        let mut word_index = 1; // Skip header
+
+        while word_index < total_words {
+            if let Some(event) = self.decode_event(&raw.data, total_words, &mut word_index) {
+                events.push(event);
+            }
+        }
+
+        events.sort_by(|a, b| a.timestamp_ns.total_cmp(&b.timestamp_ns));
+
+        if self.config.dump_enabled {
+            println!("[ZLE] Decoded {} events", events.len());
+        }
+    }
+
+    /// Decode one event, advancing `word_index` past all of its words
+    ///
+    /// Malformed chunk lists drop the event but keep the word alignment.
+    fn decode_event(
+        &self,
+        data: &[u8],
+        total_words: usize,
+        word_index: &mut usize,
+    ) -> Option<EventData> {
+        if *word_index + 2 > total_words {
+            *word_index = total_words;
+            return None;
+        }
+        let first_word = self.read_u64(data, *word_index);
+        let second_word = self.read_u64(data, *word_index + 1);
+        *word_index += 2;
+
+        let channel = ((first_word >> constants::CHANNEL_SHIFT) & constants::CHANNEL_MASK) as u8;
+        let timestamp = first_word & constants::TIMESTAMP_MASK;
+        let flags = ((second_word >> constants::FLAGS_SHIFT) & constants::FLAGS_MASK) as u32;
+        let baseline =
+            ((second_word >> constants::BASELINE_SHIFT) & constants::BASELINE_MASK) as i16;
+        let n_chunks =
+            ((second_word >> constants::N_CHUNKS_SHIFT) & constants::N_CHUNKS_MASK) as usize;
+        let record_length = (second_word & constants::RECORD_LENGTH_MASK) as usize;
+
+        let mut samples = vec![baseline; record_length];
+        let mut kept = vec![0u8; record_length];
+        let mut valid = true;
+        let mut next_free = 0usize;
+
+        for _ in 0..n_chunks {
+            if *word_index >= total_words {
+                return None;
+            }
+            let descriptor = self.read_u64(data, *word_index);
+            *word_index += 1;
+
+            let begin = (descriptor >> constants::CHUNK_BEGIN_SHIFT) as usize;
+            let size = (descriptor & constants::CHUNK_SIZE_MASK) as usize;
+            let n_words = size.div_ceil(constants::SAMPLES_PER_WORD);
+            if *word_index + n_words > total_words {
+                *word_index = total_words;
+                return None;
+            }
+
+            // Chunks must be ordered, disjoint and inside the record
+            if begin < next_free || begin + size > record_length {
+                if self.config.dump_enabled {
+                    println!(
+                        "[ZLE] Invalid chunk ch={} begin={} size={} (record length {})",
+                        channel, begin, size, record_length
+                    );
+                }
+                valid = false;
+            }
+
+            if valid {
+                for i in 0..size {
+                    let word = self.read_u64(data, *word_index + i / constants::SAMPLES_PER_WORD);
+                    let shift = 16 * (i % constants::SAMPLES_PER_WORD) as u32;
+                    samples[begin + i] = ((word >> shift) & 0xFFFF) as u16 as i16;
+                    kept[begin + i] = 1;
+                }
+                next_free = begin + size;
+            }
+            *word_index += n_words;
+        }
+
+        if !valid {
+            return None;
+        }
+
+        let timestamp_ns = timestamp as f64 * self.config.time_step_ns;
+        if self.config.dump_enabled {
+            println!(
+                "[ZLE] ch={} t={:.3} ns, {} chunks, record length {}",
+                channel, timestamp_ns, n_chunks, record_length
+            );
+        }
+
+        Some(EventData {
+            timestamp_ns,
+            module: self.config.module_id,
+            channel,
+            energy: 0,
+            energy_short: 0,
+            fine_time: 0,
+            flags,
+            waveform: Some(Waveform {
+                analog_probe1: samples,
+                digital_probe1: kept,
+                ..Waveform::default()
+            }),
+        })
+    }
+
+    /// Check for a Start/Stop signal with the given subtype
+    fn is_signal(&self, data: &[u8], subtype: u64) -> bool {
+        if data.len() < constants::WORD_SIZE {
+            return false;
+        }
+        let first_word = self.read_u64(data, 0);
+        let signal_type =
+            (first_word >> constants::SIGNAL_TYPE_SHIFT) & constants::SIGNAL_TYPE_MASK;
+        let signal_subtype =
+            (first_word >> constants::SIGNAL_SUBTYPE_SHIFT) & constants::SIGNAL_TYPE_MASK;
+        signal_type == constants::SIGNAL_TYPE && signal_subtype == subtype
+    }
+
+    /// Read a Big Endian u64 from data at given word index
+    #[inline]
+    fn read_u64(&self, data: &[u8], word_index: usize) -> u64 {
+        let offset = word_index * constants::WORD_SIZE;
+        u64::from_be_bytes(
+            data[offset..offset + constants::WORD_SIZE]
+                .try_into()
+                .unwrap(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Aggregate with two events in the (provisional) layout described in the
+    /// module docs; not a hardware capture
+    ///
+    /// ch 3 @ 1000 samples: baseline 100, record 16, chunks [2..5) and [9..14)
+    /// ch 1 @  400 samples: baseline 50, record 4, no chunks (fully suppressed)
+    const ZLE_AGGREGATE: [u64; 10] = [
+        0x2000_0001_0000_000A, // header: type 2, counter 1, 10 words
+        0x0300_0000_0000_03E8, // ch 3, timestamp 1000
+        0x0001_0064_0002_0010, // flags 1, baseline 100, 2 chunks, record 16
+        0x0000_0002_0000_0003, // chunk: begin 2, 3 samples
+        0x0000_00C8_012C_00C8, // 200, 300, 200 (+ padding)
+        0x0000_0009_0000_0005, // chunk: begin 9, 5 samples
+        0x0190_01F4_0258_01F4, // 500, 600, 500, 400
+        0x0000_0000_0000_0064, // 100 (+ padding)
+        0x0100_0000_0000_0190, // ch 1, timestamp 400
+        0x0000_0032_0000_0004, // baseline 50, 0 chunks, record 4
+    ];
+
+    fn to_raw(words: &[u64]) -> RawData {
+        RawData::new(words.iter().flat_map(|w| w.to_be_bytes()).collect())
+    }
+
+    #[test]
+    fn test_reconstructs_sample_positions() {
+        let mut decoder = ZleDecoder::with_defaults();
+        let events = decoder.decode(&to_raw(&ZLE_AGGREGATE));
+        assert_eq!(events.len(), 2);
+
+        // Sorted by timestamp: the suppressed ch 1 event comes first
+        let quiet = &events[0];
+        assert_eq!(quiet.channel, 1);
+        assert_eq!(quiet.timestamp_ns, 800.0);
+        let wf = quiet.waveform.as_ref().unwrap();
+        assert_eq!(wf.analog_probe1, vec![50; 4]);
+        assert_eq!(wf.digital_probe1, vec![0; 4]);
+
+        let pulse = &events[1];
+        assert_eq!(pulse.channel, 3);
+        assert_eq!(pulse.timestamp_ns, 2000.0);
+        assert_eq!(pulse.flags, 1);
+        assert_eq!(pulse.energy, 0);
+        let wf = pulse.waveform.as_ref().unwrap();
+        assert_eq!(
+            wf.analog_probe1,
+            vec![100, 100, 200, 300, 200, 100, 100, 100, 100, 500, 600, 500, 400, 100, 100, 100]
+        );
+        assert_eq!(
+            wf.digital_probe1,
+            vec![0, 0, 1, 1, 1, 0, 0, 0, 0, 1, 1, 1, 1, 1, 0, 0]
+        );
+    }
+
+    #[test]
+    fn test_invalid_chunk_keeps_alignment() {
+        let mut words = ZLE_AGGREGATE;
+        // Second chunk now overlaps the first: the ch 3 event is dropped
+        words[5] = 0x0000_0003_0000_0005;
+
+        let mut decoder = ZleDecoder::with_defaults();
+        let events = decoder.decode(&to_raw(&words));
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].channel, 1);
+    }
+
+    #[test]
+    fn test_truncated_aggregate() {
+        let mut decoder = ZleDecoder::with_defaults();
+        // Cut inside the second chunk's data
+        let events = decoder.decode(&to_raw(&ZLE_AGGREGATE[..7]));
+        assert!(events.is_empty());
+    }
+
+    #[test]
+    fn test_classify_signals() {
+        let decoder = ZleDecoder::with_defaults();
+        let start = to_raw(&[0x3000_0000_0000_0004, 0, 0, 0]);
+        let stop = to_raw(&[0x3200_0000_0000_0003, 0, 0]);
+        assert_eq!(decoder.classify(&start), DataType::Start);
+        assert_eq!(decoder.classify(&stop), DataType::Stop);
+        assert_eq!(decoder.classify(&to_raw(&[0])), DataType::Unknown);
+        assert_eq!(decoder.classify(&to_raw(&ZLE_AGGREGATE)), DataType::Event);
+    }
+}
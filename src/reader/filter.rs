@@ -0,0 +1,125 @@
+//! Source-side event filtering
+//!
+//! Applies the declarative [`EventFilter`] from the source config to each
+//! decoded batch, so uninteresting events never cost bandwidth downstream.
+//! Dead-time accounting runs before the filter and still sees every event.
+//! Events keep the firmware's raw flags, so pileup is tested against the
+//! decoder's [`FlagMasks::pileup`](super::decoder::FlagMasks) mask.
+
+use crate::common::EventData;
+use crate::config::EventFilter;
+
+/// Whether `filter` drops anything at all (the default passes everything)
+pub fn is_active(filter: &EventFilter) -> bool {
+    *filter != EventFilter::default()
+}
+
+/// Whether a single event passes every condition of `filter`
+pub fn accepts(filter: &EventFilter, event: &EventData, pileup_mask: u32) -> bool {
+    filter.min_energy.is_none_or(|min| event.energy >= min)
+        && filter.max_energy.is_none_or(|max| event.energy <= max)
+        && filter
+            .channels
+            .as_ref()
+            .is_none_or(|channels| channels.contains(&event.channel))
+        && !(filter.drop_pileup && event.flags & u64::from(pileup_mask) != 0)
+}
+
+/// Remove rejected events from `events`; returns how many were dropped
+pub fn apply(filter: &EventFilter, events: &mut Vec<EventData>, pileup_mask: u32) -> u64 {
+    let before = events.len();
+    events.retain(|event| accepts(filter, event, pileup_mask));
+    (before - events.len()) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reader::decoder::{Psd1Decoder, Psd2Decoder};
+
+    const PILEUP: u32 = Psd1Decoder::FLAG_MASKS.pileup;
+
+    fn batch() -> Vec<EventData> {
+        // (channel, energy, flags)
+        [
+            (0, 50, 0),
+            (0, 150, 0),
+            (1, 3000, 0),
+            (2, 120, PILEUP.into()),
+            (5, 900, 0),
+        ]
+        .iter()
+        .enumerate()
+        .map(|(i, &(channel, energy, flags))| {
+            EventData::new(0, channel, energy, 0, i as f64, flags)
+        })
+        .collect()
+    }
+
+    fn kept(events: &[EventData]) -> Vec<(u8, u32)> {
+        events.iter().map(|e| (e.channel, e.energy)).collect()
+    }
+
+    #[test]
+    fn test_energy_threshold() {
+        let filter = EventFilter {
+            min_energy: Some(100),
+            max_energy: Some(2000),
+            ..Default::default()
+        };
+        let mut events = batch();
+        assert_eq!(apply(&filter, &mut events, PILEUP), 2);
+        assert_eq!(kept(&events), vec![(0, 150), (2, 120), (5, 900)]);
+    }
+
+    #[test]
+    fn test_channel_allow_list_and_pileup() {
+        let filter = EventFilter {
+            channels: Some(vec![0, 1, 2]),
+            ..Default::default()
+        };
+        let mut events = batch();
+        assert_eq!(apply(&filter, &mut events, PILEUP), 1);
+        assert_eq!(kept(&events), vec![(0, 50), (0, 150), (1, 3000), (2, 120)]);
+
+        // `energy > 100 && channel in {0,1,2}`, without pileup
+        let filter = EventFilter {
+            min_energy: Some(101),
+            channels: Some(vec![0, 1, 2]),
+            drop_pileup: true,
+            ..Default::default()
+        };
+        let mut events = batch();
+        assert_eq!(apply(&filter, &mut events, PILEUP), 3);
+        assert_eq!(kept(&events), vec![(0, 150), (1, 3000)]);
+    }
+
+    #[test]
+    fn test_pileup_follows_firmware_mask() {
+        let filter = EventFilter {
+            drop_pileup: true,
+            ..Default::default()
+        };
+        let psd2_pileup = Psd2Decoder::FLAG_MASKS.pileup;
+        let event = |flags| EventData::new(0, 0, 100, 0, 0.0, flags);
+        let mut events = vec![event(psd2_pileup.into()), event(0x01), event(0)];
+        assert_eq!(apply(&filter, &mut events, psd2_pileup), 1);
+        assert_eq!(
+            events.iter().map(|e| e.flags).collect::<Vec<_>>(),
+            vec![0x01, 0]
+        );
+    }
+
+    #[test]
+    fn test_default_filter_is_inactive() {
+        let filter = EventFilter::default();
+        assert!(!is_active(&filter));
+        let mut events = batch();
+        assert_eq!(apply(&filter, &mut events, PILEUP), 0);
+        assert_eq!(events.len(), 5);
+        assert!(is_active(&EventFilter {
+            drop_pileup: true,
+            ..Default::default()
+        }));
+    }
+}
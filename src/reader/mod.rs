@@ -5,27 +5,53 @@
 //! - Data decoders (decoder)
 //! - Reader integration with two-task architecture
 
+mod affinity;
 pub mod caen;
+mod dead_time;
 pub mod decoder;
+mod filter;
+mod pool;
+pub mod raw_file;
+mod recent;
+mod sanity;
+pub mod trigger;
+mod workers;
 
 // Re-exports
-pub use crate::config::FirmwareType;
+pub use crate::config::{
+    DecodeQueuePolicy, EventFilter, FirmwareType, DEFAULT_MAX_RECONNECT_ATTEMPTS,
+    DEFAULT_RECONNECT_BACKOFF_MS,
+};
 pub use caen::{CaenError, CaenHandle, EndpointHandle};
 pub use decoder::{
-    DataType, DecodeResult, EventData, Psd1Config, Psd1Decoder, Psd2Config, Psd2Decoder, Waveform,
+    DataType, DecodeResult, Decoder, DecoderParams, DecoderRegistry, EventData, FlagMasks,
+    Psd1Config, Psd1Decoder, Psd2Config, Psd2Decoder, Waveform, ZleConfig, ZleDecoder,
+    DEFAULT_ADC_BITS,
 };
+pub use raw_file::{RawFileHeader, RawFileReader, RawFileWriter};
+
+use caen::AcquisitionControl;
+use dead_time::DeadTimeCounter;
+use pool::{BufferPool, DecodeBuffers};
+use raw_file::RawFileThread;
+use recent::RecentEvents;
+use sanity::TimestampSanity;
+use workers::DecodeWorkers;
 
 use crate::common::{
-    handle_command, run_command_task, CommandHandlerExt, ComponentSharedState, ComponentState,
-    EventData as CommonEventData, EventDataBatch, Message, Waveform as CommonWaveform,
+    encode_with_limit, enter_error_state_blocking, handle_command, isolate, isolate_blocking,
+    next_epoch, run_command_task, topic, CommandHandlerExt, ComponentSharedState, ComponentState,
+    DigitizerConfigSource, EventData as CommonEventData, EventDataBatch, FilterCounts, Message,
+    RunConfig, SocketOptions, TriggerMode, Waveform as CommonWaveform, DEFAULT_MAX_MESSAGE_BYTES,
 };
 use futures::SinkExt;
+use std::collections::HashMap;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use thiserror::Error;
-use tmq::publish;
 use tmq::Context;
+use tmq::{publish, AsZmqSocket};
 use tokio::sync::{mpsc, watch, Mutex};
 use tokio::time::interval;
 use tracing::{debug, error, info, warn};
@@ -50,29 +76,28 @@ pub enum ReaderError {
 
     #[error("Channel send error")]
     ChannelSend,
-}
 
-/// Enum-based decoder dispatch (KISS: only PSD1/PSD2/PHA1, no trait object needed)
-enum DecoderKind {
-    Psd2(Psd2Decoder),
-    Psd1(Psd1Decoder),
+    #[error("Raw file error: {0}")]
+    RawFile(#[from] raw_file::RawFileError),
+
+    #[error("Reconnection failed after {attempts} attempts: {last_error}")]
+    ReconnectFailed {
+        attempts: u32,
+        last_error: CaenError,
+    },
 }
 
-impl DecoderKind {
-    fn classify(&self, raw: &decoder::RawData) -> DataType {
-        match self {
-            Self::Psd2(d) => d.classify(raw),
-            Self::Psd1(d) => d.classify(raw),
-        }
-    }
+/// Spare raw buffers kept for the ReadLoop (more in flight are allocated and dropped)
+const RAW_BUFFER_POOL_SIZE: usize = 16;
 
-    fn decode(&mut self, raw: &decoder::RawData) -> Vec<decoder::EventData> {
-        match self {
-            Self::Psd2(d) => d.decode(raw),
-            Self::Psd1(d) => d.decode(raw),
-        }
-    }
-}
+/// Longest the DecodeLoop keeps decoding queued buffers after shutdown
+const SHUTDOWN_DRAIN_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Longest a command waits for the ReadLoop to run its digitizer job
+const DIGITIZER_JOB_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Decoded events kept for GetRecentEvents unless configured otherwise
+pub const DEFAULT_RECENT_EVENTS_CAPACITY: usize = 1000;
 
 /// Reader configuration
 #[derive(Debug, Clone)]
@@ -81,6 +106,8 @@ pub struct ReaderConfig {
     pub url: String,
     /// ZMQ data publish address
     pub data_address: String,
+    /// Additional addresses bound on the same PUB socket (fan-out)
+    pub extra_data_addresses: Vec<String>,
     /// ZMQ command address (REP socket)
     pub command_address: String,
     /// Source ID for this reader
@@ -89,16 +116,72 @@ pub struct ReaderConfig {
     pub firmware: FirmwareType,
     /// Module ID for decoded events
     pub module_id: u8,
+    /// Channel → module overrides (unlisted channels use `module_id`)
+    pub module_map: HashMap<u8, u8>,
     /// Read timeout in milliseconds
     pub read_timeout_ms: i32,
-    /// Buffer size for raw data reads
+    /// Raw data read buffer size, used only if the digitizer does not
+    /// report `/par/maxrawdatasize`
     pub buffer_size: usize,
     /// Heartbeat interval in milliseconds (0 = disabled)
     pub heartbeat_interval_ms: u64,
     /// Time step in nanoseconds (for timestamp calculation)
     pub time_step_ns: f64,
+    /// Energy resolution in bits; larger decoded energies are flagged
+    pub adc_bits: u8,
     /// Path to digitizer configuration JSON file (optional)
     pub config_file: Option<String>,
+    /// Without `config_file`, apply the built-in defaults for the firmware
+    /// instead of keeping the settings already on the board
+    pub apply_defaults: bool,
+    /// Reject Configure when digitizer parameters fail range validation
+    pub strict_validation: bool,
+    /// Wait between reconnection attempts after a lost link (milliseconds)
+    pub reconnect_backoff_ms: u64,
+    /// Reconnection attempts before giving up (0 = no reconnection)
+    pub max_reconnect_attempts: u32,
+    /// Maximum serialized message size; larger batches are split (0 = no limit)
+    pub max_message_bytes: usize,
+    /// Directory for raw (undecoded) buffer files (None = disabled)
+    pub raw_record_dir: Option<String>,
+    /// Write every raw buffer to a `.dlraw` dump file per run and print
+    /// decoder diagnostics
+    pub dump_raw: bool,
+    /// Directory for dump files
+    pub dump_dir: String,
+    /// Issue the software start on Start (false = slave, started by the
+    /// master's TrgOut cascade after being armed)
+    pub is_master: bool,
+    /// Raw buffers queued between the read and decode loops
+    pub decode_channel_capacity: usize,
+    /// Block or drop when the decode queue is full
+    pub decode_queue_policy: DecodeQueuePolicy,
+    /// Threads decoding event buffers in parallel (1 = decode in the DecodeLoop)
+    pub decode_workers: usize,
+    /// Topic frame sent before every data message (None = payload only)
+    pub topic_prefix: Option<String>,
+    /// CPU core for the ReadLoop thread (None = unpinned)
+    pub read_core: Option<usize>,
+    /// CPU core for the DecodeLoop thread (None = unpinned, runs on the tokio pool);
+    /// decode workers are pinned to the cores after it
+    pub decode_core: Option<usize>,
+    /// Delta-code analog waveform probes before publishing
+    pub compress_waveforms: bool,
+    /// Publish energies above 16 bits instead of clamping them
+    pub wide_energy: bool,
+    /// Flag events whose timestamp jumps further than this from the last
+    /// good one (None = no check)
+    pub timestamp_sanity_window_ns: Option<f64>,
+    /// Drop flagged timestamp outliers instead of publishing them
+    pub drop_timestamp_outliers: bool,
+    /// Decoders by firmware type (built-in PSD1/PSD2/ZLE by default)
+    pub decoders: DecoderRegistry,
+    /// ZMQ send HWM / linger of the data socket (default: ZMQ's)
+    pub socket_options: SocketOptions,
+    /// Decoded events kept for GetRecentEvents (0 = disabled)
+    pub recent_events_capacity: usize,
+    /// Events dropped before publishing (default: none)
+    pub event_filter: EventFilter,
 }
 
 impl Default for ReaderConfig {
@@ -106,15 +189,41 @@ impl Default for ReaderConfig {
         Self {
             url: "dig2://localhost".to_string(),
             data_address: "tcp://*:5555".to_string(),
+            extra_data_addresses: Vec::new(),
             command_address: "tcp://*:5556".to_string(),
             source_id: 0,
             firmware: FirmwareType::PSD2,
             module_id: 0,
+            module_map: HashMap::new(),
             read_timeout_ms: 100,
             buffer_size: 1024 * 1024, // 1MB
             heartbeat_interval_ms: 1000,
             time_step_ns: 2.0, // 500 MHz ADC = 2ns per sample
+            adc_bits: DEFAULT_ADC_BITS,
             config_file: None,
+            apply_defaults: false,
+            strict_validation: false,
+            reconnect_backoff_ms: DEFAULT_RECONNECT_BACKOFF_MS,
+            max_reconnect_attempts: DEFAULT_MAX_RECONNECT_ATTEMPTS,
+            max_message_bytes: DEFAULT_MAX_MESSAGE_BYTES,
+            raw_record_dir: None,
+            dump_raw: false,
+            dump_dir: "./dump".to_string(),
+            is_master: true,
+            decode_channel_capacity: 256,
+            decode_queue_policy: DecodeQueuePolicy::Block,
+            decode_workers: 1,
+            topic_prefix: None,
+            read_core: None,
+            decode_core: None,
+            compress_waveforms: false,
+            wide_energy: false,
+            timestamp_sanity_window_ns: None,
+            drop_timestamp_outliers: false,
+            decoders: DecoderRegistry::default(),
+            socket_options: SocketOptions::default(),
+            recent_events_capacity: DEFAULT_RECENT_EVENTS_CAPACITY,
+            event_filter: EventFilter::default(),
         }
     }
 }
@@ -122,7 +231,8 @@ impl Default for ReaderConfig {
 impl ReaderConfig {
     /// Create ReaderConfig from Config and source ID
     ///
-    /// Returns None if source_id is not found or source has no digitizer_url
+    /// Returns None if source_id is not found or source has no digitizer_url.
+    /// A digitizer is a slave only when another source is the master digitizer.
     pub fn from_config(config: &crate::config::Config, source_id: u32) -> Option<Self> {
         let source = config.get_source(source_id)?;
         let url = source.digitizer_url.as_ref()?;
@@ -131,24 +241,91 @@ impl ReaderConfig {
             crate::config::SourceType::Psd2 => FirmwareType::PSD2,
             crate::config::SourceType::Psd1 => FirmwareType::PSD1,
             crate::config::SourceType::Pha1 => FirmwareType::PHA,
-            // Emulator/Zle sources shouldn't create a Reader — caller should handle
+            crate::config::SourceType::Zle => FirmwareType::ZLE,
+            // Emulator sources shouldn't create a Reader — caller should handle
             _ => return None,
         };
+        let has_master = config
+            .network
+            .sources
+            .iter()
+            .any(|s| s.is_master_digitizer());
 
         Some(Self {
             url: url.clone(),
             data_address: source.bind.clone(),
+            extra_data_addresses: source.extra_binds.clone(),
             command_address: source.command_address(),
             source_id,
             firmware,
             module_id: source.module_id.unwrap_or(source_id as u8),
+            module_map: source.module_map.clone(),
             read_timeout_ms: 100,
             buffer_size: 1024 * 1024, // 1MB
             heartbeat_interval_ms: 1000,
             time_step_ns: source.time_step_ns.unwrap_or(2.0),
+            adc_bits: source.adc_bits.unwrap_or(DEFAULT_ADC_BITS),
             config_file: source.config_file.clone(),
+            apply_defaults: source.apply_defaults,
+            strict_validation: source.strict_validation,
+            reconnect_backoff_ms: source.reconnect_backoff_ms,
+            max_reconnect_attempts: source.max_reconnect_attempts,
+            max_message_bytes: source.max_message_bytes,
+            raw_record_dir: source.raw_record_dir.clone(),
+            dump_raw: source.dump_raw,
+            dump_dir: source.dump_dir.clone(),
+            is_master: source.is_master_digitizer() || !has_master,
+            decode_channel_capacity: source.decode_channel_capacity,
+            decode_queue_policy: source.decode_queue_policy,
+            decode_workers: source.decode_workers,
+            topic_prefix: source.topic_prefix.clone(),
+            read_core: source.read_core,
+            decode_core: source.decode_core,
+            compress_waveforms: source.compress_waveforms,
+            wide_energy: source.wide_energy,
+            timestamp_sanity_window_ns: source.timestamp_sanity_window_ns,
+            drop_timestamp_outliers: source.drop_timestamp_outliers,
+            decoders: DecoderRegistry::default(),
+            socket_options: source.socket_options(),
+            recent_events_capacity: source.recent_events_capacity,
+            event_filter: source.event_filter.clone(),
         })
     }
+
+    /// Digitizer configuration applied on Configure
+    ///
+    /// `config_file` if set, otherwise the firmware defaults with
+    /// `apply_defaults`, otherwise None (the board keeps its settings).
+    pub fn digitizer_config(
+        &self,
+    ) -> Result<
+        Option<crate::config::digitizer::DigitizerConfig>,
+        crate::config::digitizer::DigitizerConfigError,
+    > {
+        use crate::config::digitizer::DigitizerConfig;
+        match self.config_file {
+            Some(ref path) => DigitizerConfig::load(path).map(Some),
+            None if self.apply_defaults => {
+                let mut config = DigitizerConfig::firmware_defaults(self.firmware);
+                config.digitizer_id = self.source_id;
+                Ok(Some(config))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Build the decoder registered for this source's firmware
+    pub fn create_decoder(&self) -> Option<Box<dyn Decoder>> {
+        self.decoders.create(
+            self.firmware,
+            &DecoderParams {
+                time_step_ns: self.time_step_ns,
+                module_id: self.module_id,
+                adc_bits: self.adc_bits,
+                dump_enabled: self.dump_raw,
+            },
+        )
+    }
 }
 
 /// Metrics for monitoring
@@ -162,9 +339,81 @@ pub struct ReaderMetrics {
     pub batches_published: AtomicU64,
     /// Current decode queue length (approximate)
     pub queue_length: AtomicU64,
+    /// High-water mark of the decode queue length since run start
+    pub queue_max: AtomicU64,
+    /// Raw buffers discarded because the decode queue was full
+    pub dropped_raw: AtomicU64,
+    /// Events outside the timestamp sanity window (flagged or dropped)
+    pub timestamp_outliers: AtomicU64,
+    /// Events that passed the event filter (only counted with a filter set)
+    pub filter_passed: AtomicU64,
+    /// Events dropped by the event filter
+    pub filter_dropped: AtomicU64,
+    /// Per-channel trigger-lost counts of the current run (locked once per batch)
+    pub dead_time: parking_lot::Mutex<DeadTimeCounter>,
+    /// Last decoded events for GetRecentEvents (disabled by default)
+    pub recent_events: RecentEvents,
+}
+
+impl ReaderMetrics {
+    /// Metrics keeping the last `recent_events_capacity` decoded events
+    pub fn new(recent_events_capacity: usize) -> Self {
+        Self {
+            recent_events: RecentEvents::new(recent_events_capacity),
+            ..Default::default()
+        }
+    }
+
+    /// Record a buffer handed to the decode queue
+    fn record_enqueued(&self) {
+        let len = self.queue_length.fetch_add(1, Ordering::Relaxed) + 1;
+        self.queue_max.fetch_max(len, Ordering::Relaxed);
+    }
+
+    /// Record a buffer taken from the decode queue
+    fn record_dequeued(&self) {
+        self.queue_length.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// Hand a raw buffer to the decode queue according to `policy`
+///
+/// A buffer dropped on a full queue is counted in `dropped_raw` and its
+/// memory returned to `raw_pool`. Returns false once the decode loop is gone.
+fn enqueue_raw(
+    tx: &mpsc::Sender<decoder::RawData>,
+    raw: decoder::RawData,
+    policy: DecodeQueuePolicy,
+    metrics: &ReaderMetrics,
+    raw_pool: &BufferPool<Vec<u8>>,
+) -> bool {
+    // Count before sending so the decode loop never sees a negative length
+    metrics.record_enqueued();
+    let sent = match policy {
+        DecodeQueuePolicy::Block => tx.blocking_send(raw).is_ok(),
+        DecodeQueuePolicy::Drop => match tx.try_send(raw) {
+            Ok(()) => true,
+            Err(mpsc::error::TrySendError::Full(raw)) => {
+                metrics.record_dequeued();
+                let dropped = metrics.dropped_raw.fetch_add(1, Ordering::Relaxed) + 1;
+                if dropped.is_power_of_two() {
+                    warn!(dropped, "Decode queue full, dropping raw buffers");
+                }
+                raw_pool.give(raw.data);
+                return true;
+            }
+            Err(mpsc::error::TrySendError::Closed(_)) => false,
+        },
+    };
+    if !sent {
+        metrics.record_dequeued();
+    }
+    sent
 }
 
 /// Rate tracker for 1-second interval rate calculation
+///
+/// Tracks any monotonically increasing counter (events, bytes).
 #[derive(Debug)]
 struct RateTracker {
     prev_events: AtomicU64,
@@ -182,7 +431,10 @@ impl RateTracker {
     }
 
     fn update(&self, current_events: u64) {
-        let now = Instant::now();
+        self.update_at(current_events, Instant::now());
+    }
+
+    fn update_at(&self, current_events: u64, now: Instant) {
         let mut prev_time_guard = self.prev_time.lock().unwrap();
 
         if let Some(prev_time) = *prev_time_guard {
@@ -216,8 +468,24 @@ impl RateTracker {
 struct ReaderCommandExt {
     metrics: Arc<ReaderMetrics>,
     rate_tracker: Arc<RateTracker>,
+    /// Bytes/s tracker for data_rate
+    byte_rate_tracker: Arc<RateTracker>,
     /// Digitizer URL for Detect command (e.g., "dig2://172.18.4.56")
     url: String,
+    /// Digitizer configuration file validated on Configure
+    config_file: Option<String>,
+    /// Firmware defaults are applied (and validated) without a config file
+    apply_defaults: bool,
+    /// Reject Configure on validation failure
+    strict_validation: bool,
+    /// Firmware type (selects trigger mode parameters)
+    firmware: FirmwareType,
+    /// Module ID reported by GetConfig
+    module_id: u8,
+    /// An event filter is configured (filter counts are reported)
+    filter_active: bool,
+    /// Applied parameters and DevTree from the ReadLoop's connection
+    digitizer: SharedDigitizer,
 }
 
 impl CommandHandlerExt for ReaderCommandExt {
@@ -229,9 +497,22 @@ impl CommandHandlerExt for ReaderCommandExt {
         let events = self.metrics.events_decoded.load(Ordering::Relaxed);
         let batches = self.metrics.batches_published.load(Ordering::Relaxed);
         let bytes = self.metrics.bytes_read.load(Ordering::Relaxed);
+        let dropped = self.metrics.dropped_raw.load(Ordering::Relaxed);
+        let outliers = self.metrics.timestamp_outliers.load(Ordering::Relaxed);
+        let passed = self.metrics.filter_passed.load(Ordering::Relaxed);
+        let filtered = self.metrics.filter_dropped.load(Ordering::Relaxed);
+        let dead_time = self.metrics.dead_time.lock().total_fraction();
         Some(format!(
-            "Events: {}, Batches: {}, Bytes: {}",
-            events, batches, bytes
+            "Events: {}, Batches: {}, Bytes: {}, Dropped buffers: {}, Timestamp outliers: {}, \
+             Passed filter: {}, Filtered out: {}, Dead time: {:.2}%",
+            events,
+            batches,
+            bytes,
+            dropped,
+            outliers,
+            passed,
+            filtered,
+            dead_time * 100.0
         ))
     }
 
@@ -239,22 +520,114 @@ impl CommandHandlerExt for ReaderCommandExt {
         let events = self.metrics.events_decoded.load(Ordering::Relaxed);
         let bytes = self.metrics.bytes_read.load(Ordering::Relaxed);
         let queue = self.metrics.queue_length.load(Ordering::Relaxed);
+        let queue_max = self.metrics.queue_max.load(Ordering::Relaxed);
         self.rate_tracker.update(events);
+        self.byte_rate_tracker.update(bytes);
         Some(crate::common::ComponentMetrics {
             events_processed: events,
             bytes_transferred: bytes,
             queue_size: queue as u32,
-            queue_max: 0,
+            queue_max: queue_max as u32,
             event_rate: self.rate_tracker.get_rate(),
-            data_rate: 0.0,
+            data_rate: self.byte_rate_tracker.get_rate(),
+            channel_dead_time: self.metrics.dead_time.lock().channels(),
+            event_filter: self.filter_active.then(|| FilterCounts {
+                passed: self.metrics.filter_passed.load(Ordering::Relaxed),
+                dropped: self.metrics.filter_dropped.load(Ordering::Relaxed),
+            }),
         })
     }
 
+    fn on_configure(&mut self, _config: &RunConfig) -> Result<(), String> {
+        if !self.strict_validation {
+            return Ok(());
+        }
+        let dig_config = match self.config_file {
+            Some(ref path) => crate::config::digitizer::DigitizerConfig::load(path)
+                .map_err(|e| format!("Failed to load {}: {}", path, e))?,
+            None if self.apply_defaults => {
+                crate::config::digitizer::DigitizerConfig::firmware_defaults(self.firmware)
+            }
+            None => return Ok(()),
+        };
+        self.validate_strict(&dig_config)
+    }
+
     fn on_start(&mut self, _run_number: u32) -> Result<(), String> {
         self.rate_tracker.reset();
+        self.byte_rate_tracker.reset();
+        let queue = self.metrics.queue_length.load(Ordering::Relaxed);
+        self.metrics.queue_max.store(queue, Ordering::Relaxed);
+        self.metrics.dead_time.lock().reset();
+        self.metrics.recent_events.clear();
         Ok(())
     }
 
+    fn on_get_recent_events(&mut self, n: usize) -> Result<serde_json::Value, String> {
+        let recent = &self.metrics.recent_events;
+        if recent.capacity() == 0 {
+            return Err("Recent events are disabled (recent_events_capacity = 0)".to_string());
+        }
+        serde_json::to_value(recent.last(n)).map_err(|e| e.to_string())
+    }
+
+    fn on_set_trigger_mode(&mut self, mode: TriggerMode) -> Result<serde_json::Value, String> {
+        let num_channels = self
+            .digitizer
+            .device_tree
+            .lock()
+            .as_ref()
+            .map(CaenHandle::num_channels_in_tree)
+            .ok_or_else(|| format!("Cannot set trigger mode: not connected to {} yet", self.url))?;
+        let params = trigger::trigger_mode_parameters(self.firmware, mode, num_channels)?;
+        let writes = params.clone();
+        self.digitizer
+            .run_on_connection(move |handle| trigger::write_parameters(handle, &writes))??;
+
+        Ok(serde_json::Value::Array(
+            params
+                .iter()
+                .map(|p| serde_json::json!({ "path": p.path, "value": p.value }))
+                .collect(),
+        ))
+    }
+
+    fn on_reconfigure_digitizer(
+        &mut self,
+        source: &DigitizerConfigSource,
+    ) -> Result<usize, String> {
+        let dig_config = match source {
+            DigitizerConfigSource::Path(path) => {
+                crate::config::digitizer::DigitizerConfig::load(path)
+                    .map_err(|e| format!("Failed to load {}: {}", path, e))?
+            }
+            DigitizerConfigSource::Json(value) => serde_json::from_value(value.clone())
+                .map_err(|e| format!("Invalid digitizer configuration: {}", e))?,
+        };
+
+        if self.strict_validation {
+            self.validate_strict(&dig_config)?;
+        }
+
+        let job_config = dig_config.clone();
+        let applied = self
+            .digitizer
+            .run_on_connection(move |handle| handle.apply_config(&job_config))?
+            .map_err(|e| format!("Failed to apply configuration: {}", e))?;
+        *self.digitizer.applied_config.lock() = Some(dig_config);
+        Ok(applied)
+    }
+
+    fn effective_config(&self) -> Option<serde_json::Value> {
+        Some(serde_json::json!({
+            "url": self.url,
+            "firmware": self.firmware,
+            "module_id": self.module_id,
+            "config_file": self.config_file,
+            "digitizer": *self.digitizer.applied_config.lock(),
+        }))
+    }
+
     fn on_detect(&mut self) -> Result<serde_json::Value, String> {
         // Temporarily connect to digitizer, read DeviceInfo, and disconnect.
         // This blocks briefly (< 1s) but is acceptable for an infrequent
@@ -269,11 +642,76 @@ impl CommandHandlerExt for ReaderCommandExt {
     }
 }
 
+impl ReaderCommandExt {
+    /// Reject parameters outside the limits of the DevTree read on the
+    /// ReadLoop's own connection
+    fn validate_strict(
+        &self,
+        dig_config: &crate::config::digitizer::DigitizerConfig,
+    ) -> Result<(), String> {
+        let device_tree = self.digitizer.device_tree.lock();
+        let Some(tree) = device_tree.as_ref() else {
+            return Err(format!(
+                "Cannot validate parameters: not connected to {} yet",
+                self.url
+            ));
+        };
+        let violations = CaenHandle::validate_config_with_tree(tree, dig_config);
+
+        if violations.is_empty() {
+            return Ok(());
+        }
+        Err(format!(
+            "Strict validation failed: {}",
+            report_violations(&violations)
+        ))
+    }
+}
+
+/// Log every parameter violation and join them into one message
+fn report_violations(violations: &[crate::config::digitizer::ValidationError]) -> String {
+    for v in violations {
+        error!(path = %v.path, value = %v.value, allowed = %v.allowed, "Parameter out of range");
+    }
+    violations
+        .iter()
+        .map(|v| v.to_string())
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+/// Read buffer size that fits any aggregate of the digitizer
+///
+/// RAW ReadData takes no buffer length, so the buffer is sized from
+/// `/par/maxrawdatasize`. `fallback` is used only if that cannot be read.
+fn raw_buffer_size<H: AcquisitionControl>(handle: &H, fallback: usize) -> usize {
+    match handle.get_value("/par/maxrawdatasize") {
+        Ok(value) => match value.trim().parse::<f64>() {
+            Ok(size) if size >= 1.0 => {
+                let size = size as usize;
+                info!(size, "Read buffer sized from maxrawdatasize");
+                size
+            }
+            _ => {
+                warn!(value = %value, fallback, "Invalid maxrawdatasize, using configured buffer size");
+                fallback
+            }
+        },
+        Err(e) => {
+            warn!(error = %e, fallback, "Cannot read maxrawdatasize, using configured buffer size");
+            fallback
+        }
+    }
+}
+
 /// Send firmware-specific arm command to the digitizer.
 ///
 /// For DIG1 (PSD1/PHA) with START_MODE_SW, the actual arm is deferred to start phase.
 /// For DIG2 (PSD2), always sends armacquisition immediately.
-fn send_arm_command(handle: &CaenHandle, firmware: FirmwareType) -> Result<(), caen::CaenError> {
+fn send_arm_command<H: AcquisitionControl>(
+    handle: &H,
+    firmware: FirmwareType,
+) -> Result<(), caen::CaenError> {
     if firmware.is_dig1() {
         let startmode = handle.get_value("/par/startmode").unwrap_or_default();
         if startmode == "START_MODE_SW" {
@@ -293,8 +731,20 @@ fn send_arm_command(handle: &CaenHandle, firmware: FirmwareType) -> Result<(), c
 ///
 /// For DIG2 (PSD2), sends swstartacquisition.
 /// For DIG1 (PSD1/PHA) with START_MODE_SW, sends armacquisition (arm=start).
-fn send_start_command(handle: &CaenHandle, firmware: FirmwareType) -> Result<(), caen::CaenError> {
-    if firmware.is_dig1() {
+/// Slaves send nothing: they are armed and start on the master's TrgOut cascade.
+fn send_start_command<H: AcquisitionControl>(
+    handle: &H,
+    firmware: FirmwareType,
+    is_master: bool,
+) -> Result<(), caen::CaenError> {
+    if !is_master {
+        if firmware.is_dig1()
+            && handle.get_value("/par/startmode").unwrap_or_default() == "START_MODE_SW"
+        {
+            warn!("Slave digitizer in START_MODE_SW is never armed - check its start mode");
+        }
+        info!("Slave digitizer armed - waiting for start via TrgOut cascade");
+    } else if firmware.is_dig1() {
         let startmode = handle.get_value("/par/startmode").unwrap_or_default();
         if startmode == "START_MODE_SW" {
             info!("Starting acquisition (DIG1, START_MODE_SW)");
@@ -309,6 +759,217 @@ fn send_start_command(handle: &CaenHandle, firmware: FirmwareType) -> Result<(),
     Ok(())
 }
 
+/// Work for the digitizer, run by the ReadLoop on its connection
+type DigitizerJob = Box<dyn FnOnce(&CaenHandle) + Send>;
+
+/// Digitizer state shared between the ReadLoop (which owns the connection)
+/// and the command handler (which reports and validates against it)
+#[derive(Default)]
+struct DigitizerShared {
+    /// Parameters last applied by the ReadLoop
+    applied_config: parking_lot::Mutex<Option<crate::config::digitizer::DigitizerConfig>>,
+    /// DevTree read on the ReadLoop's connection (None until connected)
+    device_tree: parking_lot::Mutex<Option<serde_json::Value>>,
+    /// Jobs queued by commands, run by the ReadLoop between reads
+    jobs: parking_lot::Mutex<Vec<DigitizerJob>>,
+}
+
+impl DigitizerShared {
+    /// Run `job` on the ReadLoop's connection and wait for its result
+    ///
+    /// The ReadLoop picks jobs up between reads; outside a run that is
+    /// within a few milliseconds.
+    fn run_on_connection<T, F>(&self, job: F) -> Result<T, String>
+    where
+        T: Send + 'static,
+        F: FnOnce(&CaenHandle) -> T + Send + 'static,
+    {
+        let (tx, rx) = std::sync::mpsc::channel();
+        self.jobs.lock().push(Box::new(move |handle| {
+            let _ = tx.send(job(handle));
+        }));
+        rx.recv_timeout(DIGITIZER_JOB_TIMEOUT).map_err(|e| {
+            // Not picked up: must not be applied behind the caller's back later
+            self.jobs.lock().clear();
+            match e {
+                std::sync::mpsc::RecvTimeoutError::Timeout => {
+                    "Timed out waiting for the digitizer connection".to_string()
+                }
+                std::sync::mpsc::RecvTimeoutError::Disconnected => {
+                    "Digitizer connection is closed".to_string()
+                }
+            }
+        })
+    }
+}
+
+type SharedDigitizer = Arc<DigitizerShared>;
+
+/// Inputs of the DecodeLoop: the raw queue, its buffer pool, the raw file
+/// writer and the state/shutdown channels
+struct DecodeContext {
+    rx: mpsc::Receiver<decoder::RawData>,
+    raw_pool: Arc<BufferPool<Vec<u8>>>,
+    raw_files: RawFileThread,
+    state_rx: watch::Receiver<ComponentState>,
+    shared_state: Arc<tokio::sync::Mutex<ComponentSharedState>>,
+    shutdown: tokio::sync::broadcast::Receiver<()>,
+}
+
+/// Publishing side of the DecodeLoop
+///
+/// Batches pass through here one at a time in read order, whichever task
+/// decoded them, so the sequence number is assigned at publish time.
+struct BatchPublisher {
+    data_socket: publish::Publish,
+    metrics: Arc<ReaderMetrics>,
+    sanity: Option<TimestampSanity>,
+    filter_active: bool,
+    /// Where the decoder puts the flags dead time and the filter read
+    flag_masks: FlagMasks,
+    /// Sequence number of the next published batch (reset on Start)
+    sequence_number: u64,
+    /// Run epoch, a new one on every run and every digitizer Start
+    epoch: u32,
+    /// Decoder output and batch vectors reused across buffers
+    buffers: DecodeBuffers,
+}
+
+impl BatchPublisher {
+    fn new(
+        config: &ReaderConfig,
+        data_socket: publish::Publish,
+        metrics: Arc<ReaderMetrics>,
+        flag_masks: FlagMasks,
+    ) -> Self {
+        let filter_active = filter::is_active(&config.event_filter);
+        if filter_active {
+            info!(filter = ?config.event_filter, "Event filter enabled");
+        }
+        Self {
+            data_socket,
+            metrics,
+            sanity: config
+                .timestamp_sanity_window_ns
+                .map(|window| TimestampSanity::new(window, config.drop_timestamp_outliers)),
+            filter_active,
+            flag_masks,
+            sequence_number: 0,
+            epoch: next_epoch(0),
+            buffers: DecodeBuffers::default(),
+        }
+    }
+
+    /// Run start or digitizer Start: sequence numbers restart in a new epoch
+    fn restart(&mut self) {
+        self.sequence_number = 0;
+        self.epoch = next_epoch(self.epoch);
+        if let Some(ref mut sanity) = self.sanity {
+            sanity.reset();
+        }
+    }
+
+    /// Sanity-check, filter, count and send one decoded batch
+    async fn publish(
+        &mut self,
+        mut batch: EventDataBatch,
+        config: &ReaderConfig,
+    ) -> Result<(), ReaderError> {
+        let metrics = &self.metrics;
+        if let Some(ref mut sanity) = self.sanity {
+            let outliers = sanity.filter(&mut batch.events);
+            if outliers > 0 {
+                metrics
+                    .timestamp_outliers
+                    .fetch_add(outliers, Ordering::Relaxed);
+                debug!(
+                    outliers,
+                    seq = self.sequence_number,
+                    "Timestamp outliers in batch"
+                );
+            }
+        }
+        // Dead time needs every event, including those the filter drops
+        metrics
+            .dead_time
+            .lock()
+            .record(&batch.events, self.flag_masks.trigger_lost);
+        if self.filter_active {
+            let dropped = filter::apply(
+                &config.event_filter,
+                &mut batch.events,
+                self.flag_masks.pileup,
+            );
+            metrics.filter_dropped.fetch_add(dropped, Ordering::Relaxed);
+            metrics
+                .filter_passed
+                .fetch_add(batch.len() as u64, Ordering::Relaxed);
+        }
+        if batch.is_empty() {
+            self.buffers.recycle(batch);
+            return Ok(());
+        }
+        batch.sequence_number = self.sequence_number;
+        batch.epoch = self.epoch;
+        if !config.wide_energy {
+            batch.clamp_energies();
+        }
+        if config.compress_waveforms {
+            batch.compress_waveforms();
+        }
+        let num_events = batch.len();
+
+        // Update metrics
+        metrics
+            .events_decoded
+            .fetch_add(num_events as u64, Ordering::Relaxed);
+        metrics.recent_events.record(&batch.events);
+
+        // Publish (split into fragments if oversized)
+        let msg = Message::data(batch);
+        let parts = encode_with_limit(&msg, config.max_message_bytes)?;
+        if parts.len() > 1 {
+            debug!(
+                seq = self.sequence_number,
+                fragments = parts.len(),
+                "Split oversized batch"
+            );
+        }
+        for bytes in parts {
+            let zmq_msg = topic::data_message(config.topic_prefix.as_deref(), &bytes);
+            self.data_socket.send(zmq_msg).await?;
+        }
+        if let Message::Data(batch) = msg {
+            self.buffers.recycle(batch);
+        }
+
+        self.sequence_number += 1;
+        metrics.batches_published.fetch_add(1, Ordering::Relaxed);
+
+        debug!(
+            events = num_events,
+            seq = self.sequence_number - 1,
+            "Decoded and published batch"
+        );
+        Ok(())
+    }
+
+    /// Publish worker results, oldest first, until at most `keep` are in flight
+    async fn collect(
+        &mut self,
+        workers: &mut DecodeWorkers,
+        keep: usize,
+        config: &ReaderConfig,
+    ) -> Result<(), ReaderError> {
+        while workers.in_flight() > keep {
+            if let Some(batch) = workers.next().await? {
+                self.publish(batch, config).await?;
+            }
+        }
+        Ok(())
+    }
+}
+
 /// Reader for CAEN digitizer data acquisition
 ///
 /// Uses two-task architecture:
@@ -316,12 +977,15 @@ fn send_start_command(handle: &CaenHandle, firmware: FirmwareType) -> Result<(),
 /// - DecodeLoop: Async decoding and ZMQ publishing
 pub struct Reader {
     config: ReaderConfig,
-    data_socket: publish::Publish,
+    /// Taken by the DecodeLoop when `run` starts
+    data_socket: Option<publish::Publish>,
     shared_state: Arc<Mutex<ComponentSharedState>>,
     state_rx: watch::Receiver<ComponentState>,
     state_tx: watch::Sender<ComponentState>,
     metrics: Arc<ReaderMetrics>,
     rate_tracker: Arc<RateTracker>,
+    byte_rate_tracker: Arc<RateTracker>,
+    digitizer: SharedDigitizer,
 }
 
 impl Reader {
@@ -329,24 +993,36 @@ impl Reader {
     pub async fn new(config: ReaderConfig) -> Result<Self, ReaderError> {
         let context = Context::new();
         let data_socket = publish(&context).bind(&config.data_address)?;
+        config.socket_options.apply(data_socket.get_socket())?;
+        // One PUB socket serves every endpoint, so each message is sent once
+        for addr in &config.extra_data_addresses {
+            data_socket
+                .get_socket()
+                .bind(addr)
+                .map_err(tmq::TmqError::from)?;
+        }
 
         info!(
             data_address = %config.data_address,
+            extra_data_addresses = ?config.extra_data_addresses,
             command_address = %config.command_address,
             url = %config.url,
             "Reader bound to data address"
         );
 
         let (state_tx, state_rx) = watch::channel(ComponentState::Idle);
+        let metrics = Arc::new(ReaderMetrics::new(config.recent_events_capacity));
 
         Ok(Self {
             config,
-            data_socket,
+            data_socket: Some(data_socket),
             shared_state: Arc::new(Mutex::new(ComponentSharedState::new())),
             state_rx,
             state_tx,
-            metrics: Arc::new(ReaderMetrics::default()),
+            metrics,
             rate_tracker: Arc::new(RateTracker::new()),
+            byte_rate_tracker: Arc::new(RateTracker::new()),
+            digitizer: SharedDigitizer::default(),
         })
     }
 
@@ -355,6 +1031,16 @@ impl Reader {
         *self.state_rx.borrow()
     }
 
+    /// Move the data socket out for the DecodeLoop
+    ///
+    /// There is exactly one socket per Reader: no placeholder is left behind,
+    /// so a second call fails instead of binding another endpoint.
+    fn take_data_socket(&mut self) -> Result<publish::Publish, ReaderError> {
+        self.data_socket
+            .take()
+            .ok_or_else(|| ReaderError::Config("data socket already in use".to_string()))
+    }
+
     /// Get metrics
     pub fn metrics(&self) -> &Arc<ReaderMetrics> {
         &self.metrics
@@ -379,6 +1065,7 @@ impl Reader {
                     digital_probe4: wf.digital_probe4.clone(),
                     time_resolution: wf.time_resolution,
                     trigger_threshold: wf.trigger_threshold,
+                    packed_analog: Vec::new(),
                 },
             )
         } else {
@@ -393,42 +1080,115 @@ impl Reader {
         }
     }
 
-    /// Publish a message via ZMQ
-    async fn publish_message(&mut self, message: &Message) -> Result<(), ReaderError> {
-        let bytes = message.to_msgpack()?;
-        let msg: tmq::Multipart = vec![tmq::Message::from(bytes.as_slice())].into();
-        self.data_socket.send(msg).await?;
+    /// Decode one event buffer into a batch (None if it holds no events)
+    ///
+    /// Shared by the live decode loop and offline raw file decoding. Events
+    /// keep the decoder's module ID (raw files do not record `module_map`).
+    fn decode_batch(
+        decoder: &mut dyn Decoder,
+        raw: &decoder::RawData,
+        source_id: u32,
+        sequence_number: u64,
+    ) -> Option<EventDataBatch> {
+        Self::decode_batch_into(
+            decoder,
+            raw,
+            source_id,
+            sequence_number,
+            &HashMap::new(),
+            &mut DecodeBuffers::default(),
+        )
+    }
 
-        match message {
-            Message::Data(batch) => {
-                debug!(
-                    seq = batch.sequence_number,
-                    events = batch.len(),
-                    "Published batch"
-                );
-                self.metrics
-                    .batches_published
-                    .fetch_add(1, Ordering::Relaxed);
-            }
-            Message::EndOfStream { source_id } => {
-                info!(source_id = source_id, "Published EOS");
-            }
-            Message::Heartbeat(hb) => {
-                debug!(
-                    source_id = hb.source_id,
-                    counter = hb.counter,
-                    "Published heartbeat"
-                );
+    /// Like [`decode_batch`](Self::decode_batch), reusing the vectors in `buffers`
+    ///
+    /// Channels listed in `module_map` are tagged with the mapped module.
+    fn decode_batch_into(
+        decoder: &mut dyn Decoder,
+        raw: &decoder::RawData,
+        source_id: u32,
+        sequence_number: u64,
+        module_map: &HashMap<u8, u8>,
+        buffers: &mut DecodeBuffers,
+    ) -> Option<EventDataBatch> {
+        decoder.decode_into(raw, &mut buffers.decoded);
+        if buffers.decoded.is_empty() {
+            return None;
+        }
+        let mut batch = EventDataBatch::new(source_id, sequence_number);
+        batch.events = std::mem::take(&mut buffers.events);
+        batch.events.reserve(buffers.decoded.len());
+        for event in &buffers.decoded {
+            let mut event = Self::convert_event(event);
+            if let Some(&module) = module_map.get(&event.channel) {
+                event.module = module;
             }
+            batch.push(event);
         }
+        Some(batch)
+    }
 
-        Ok(())
+    /// Create the raw buffer file for a run in `dir`
+    ///
+    /// Recordings are named `run{run}_src{id}_{time}`, debug dumps
+    /// `dump_{id}_{time}`.
+    fn open_raw_file(
+        config: &ReaderConfig,
+        dir: &str,
+        run_number: u32,
+        dump: bool,
+    ) -> Result<RawFileWriter<std::io::BufWriter<std::fs::File>>, raw_file::RawFileError> {
+        let mut header = RawFileHeader::new(
+            config.firmware,
+            config.source_id,
+            config.module_id,
+            config.time_step_ns,
+        );
+        header.run_number = run_number;
+        header.adc_bits = config.adc_bits;
+        header.config_snapshot = match config.config_file {
+            Some(ref path) => match std::fs::read_to_string(path) {
+                Ok(json) => Some(json),
+                Err(e) => {
+                    warn!(path = %path, error = %e, "Could not snapshot digitizer config");
+                    None
+                }
+            },
+            None if config.apply_defaults => {
+                let defaults =
+                    crate::config::digitizer::DigitizerConfig::firmware_defaults(config.firmware);
+                serde_json::to_string_pretty(&defaults).ok()
+            }
+            None => None,
+        };
+
+        let dir = std::path::Path::new(dir);
+        std::fs::create_dir_all(dir)?;
+        let start_s = header.file_start_time_ns / 1_000_000_000;
+        let stem = if dump {
+            format!("dump_{}_{}", config.source_id, start_s)
+        } else {
+            format!(
+                "run{:04}_src{:02}_{}",
+                run_number, config.source_id, start_s
+            )
+        };
+        let path = dir.join(format!("{}.{}", stem, raw_file::RAW_FILE_EXTENSION));
+        let writer = RawFileWriter::create(&path, &header)?;
+        info!(path = %path.display(), "Recording raw buffers");
+        Ok(writer)
     }
 
-    /// Send EOS (End Of Stream) signal
-    async fn send_eos(&mut self) -> Result<(), ReaderError> {
-        let eos = Message::eos(self.config.source_id);
-        self.publish_message(&eos).await
+    /// Publish EOS (End Of Stream) on the data socket
+    async fn publish_eos(
+        data_socket: &mut publish::Publish,
+        config: &ReaderConfig,
+    ) -> Result<(), ReaderError> {
+        let bytes = Message::eos(config.source_id).to_msgpack()?;
+        let msg = topic::data_message(config.topic_prefix.as_deref(), &bytes);
+        data_socket.send(msg).await?;
+        info!(source_id = config.source_id, "Published EOS");
+        Ok(())
     }
 
     /// ReadLoop task - runs in spawn_blocking to avoid blocking tokio runtime
@@ -437,20 +1197,23 @@ impl Reader {
     /// Respects state machine: only arms/starts digitizer when state transitions occur.
     fn read_loop(
         config: ReaderConfig,
-        tx: mpsc::UnboundedSender<decoder::RawData>,
+        tx: mpsc::Sender<decoder::RawData>,
+        raw_pool: Arc<BufferPool<Vec<u8>>>,
         state_rx: watch::Receiver<ComponentState>,
         metrics: Arc<ReaderMetrics>,
+        digitizer: SharedDigitizer,
         shutdown: Arc<std::sync::atomic::AtomicBool>,
     ) -> Result<(), ReaderError> {
         info!(url = %config.url, "ReadLoop starting, connecting to digitizer");
 
         // Open connection to digitizer
-        let handle = CaenHandle::open(&config.url)?;
+        let mut handle = CaenHandle::open(&config.url)?;
         info!("Connected to digitizer");
+        Self::cache_device_tree(&handle, &digitizer);
 
         // Configure endpoint for RAW data
         let include_n_events = config.firmware.includes_n_events();
-        let endpoint = handle.configure_endpoint(include_n_events)?;
+        let mut endpoint = handle.configure_endpoint(include_n_events)?;
         info!("Endpoint configured");
 
         // Track digitizer hardware state
@@ -458,6 +1221,10 @@ impl Reader {
         let mut hw_running = false;
         let mut prev_state = ComponentState::Idle;
 
+        // Read buffer, recycled from the DecodeLoop when available
+        let mut read_buffer: Vec<u8> = Vec::new();
+        let mut buffer_size = raw_buffer_size(&handle, config.buffer_size);
+
         loop {
             // Check shutdown flag
             if shutdown.load(Ordering::Relaxed) {
@@ -475,28 +1242,38 @@ impl Reader {
                 match (prev_state, current_state) {
                     // Configure digitizer when entering Configured state from Idle
                     (ComponentState::Idle, ComponentState::Configured) => {
-                        // Apply configuration from JSON file if specified
-                        if let Some(ref config_path) = config.config_file {
-                            info!(path = %config_path, "Loading digitizer configuration");
-                            match crate::config::digitizer::DigitizerConfig::load(config_path) {
-                                Ok(dig_config) => {
-                                    match handle.apply_config(&dig_config) {
-                                        Ok(count) => {
-                                            info!(count, "Digitizer configuration applied");
-                                        }
-                                        Err(e) => {
-                                            error!(error = %e, "Failed to apply digitizer configuration");
-                                            // Continue anyway - some parameters may have been applied
-                                        }
+                        // Apply configuration from the JSON file or the built-in defaults
+                        match config.digitizer_config() {
+                            Ok(Some(dig_config)) => {
+                                match config.config_file {
+                                    Some(ref path) => {
+                                        info!(path = %path, "Applying digitizer configuration");
+                                    }
+                                    None => {
+                                        info!(
+                                            firmware = ?config.firmware,
+                                            "No config_file specified, applying firmware defaults"
+                                        );
                                     }
                                 }
-                                Err(e) => {
-                                    error!(error = %e, path = %config_path, "Failed to load digitizer configuration");
-                                    // Continue without configuration
+                                match handle.apply_config(&dig_config) {
+                                    Ok(count) => {
+                                        info!(count, "Digitizer configuration applied");
+                                    }
+                                    Err(e) => {
+                                        error!(error = %e, "Failed to apply digitizer configuration");
+                                        // Continue anyway - some parameters may have been applied
+                                    }
                                 }
+                                *digitizer.applied_config.lock() = Some(dig_config);
+                            }
+                            Ok(None) => {
+                                info!("No config_file specified, using current digitizer settings");
+                            }
+                            Err(e) => {
+                                error!(error = %e, path = ?config.config_file, "Failed to load digitizer configuration");
+                                // Continue without configuration
                             }
-                        } else {
-                            info!("No config_file specified, using current digitizer settings");
                         }
                     }
 
@@ -518,13 +1295,16 @@ impl Reader {
                                 send_arm_command(&handle, config.firmware)?;
                                 hw_armed = true;
                             }
-                            send_start_command(&handle, config.firmware)?;
+                            send_start_command(&handle, config.firmware, config.is_master)?;
                             hw_running = true;
                         }
                     }
 
-                    // Stop acquisition when leaving Running state
-                    (ComponentState::Running, ComponentState::Configured) => {
+                    // Stop acquisition when leaving Running (or Paused) state
+                    (
+                        ComponentState::Running | ComponentState::Paused,
+                        ComponentState::Configured,
+                    ) => {
                         if hw_running {
                             info!("Stopping digitizer acquisition");
                             let _ = handle.send_command("/cmd/disarmacquisition");
@@ -549,7 +1329,13 @@ impl Reader {
                 prev_state = current_state;
             }
 
-            // Only read data when Running
+            // Parameter changes from commands go through this connection
+            let jobs = std::mem::take(&mut *digitizer.jobs.lock());
+            for job in jobs {
+                job(&handle);
+            }
+
+            // Only read data when Running (Paused keeps the digitizer armed but unread)
             if current_state != ComponentState::Running {
                 // Not running, sleep briefly and check again
                 std::thread::sleep(Duration::from_millis(10));
@@ -557,19 +1343,24 @@ impl Reader {
             }
 
             // Read data from digitizer
-            match endpoint.read_data(config.read_timeout_ms, config.buffer_size) {
+            if read_buffer.capacity() == 0 {
+                read_buffer = raw_pool.take().unwrap_or_default();
+            }
+            match endpoint.read_data_into(config.read_timeout_ms, buffer_size, &mut read_buffer) {
                 Ok(Some(raw)) => {
                     metrics
                         .bytes_read
                         .fetch_add(raw.size as u64, Ordering::Relaxed);
 
-                    // Convert to decoder RawData and send
+                    // Convert to decoder RawData and send (blocks or drops when full)
                     let decoder_raw = decoder::RawData::from(raw);
-
-                    // Update queue length metric (approximate)
-                    metrics.queue_length.fetch_add(1, Ordering::Relaxed);
-
-                    if tx.send(decoder_raw).is_err() {
+                    if !enqueue_raw(
+                        &tx,
+                        decoder_raw,
+                        config.decode_queue_policy,
+                        &metrics,
+                        &raw_pool,
+                    ) {
                         warn!("Decode channel closed, stopping read loop");
                         break;
                     }
@@ -583,8 +1374,49 @@ impl Reader {
                         info!("Received STOP signal from digitizer");
                         break;
                     }
-                    error!(error = %e, "Read error");
-                    // Continue on non-fatal errors
+                    if !e.is_fatal() {
+                        error!(error = %e, "Read error");
+                        // Continue on non-fatal errors
+                        continue;
+                    }
+
+                    error!(error = %e, "Digitizer link lost");
+                    // Close the dead handle before reopening; the endpoint is a
+                    // sub-handle and goes with it
+                    drop(handle);
+                    hw_armed = false;
+                    hw_running = false;
+
+                    let Some((new_handle, new_endpoint)) = Self::reconnect(&config, &shutdown, e)?
+                    else {
+                        info!("ReadLoop received shutdown signal during reconnection");
+                        return Ok(());
+                    };
+                    handle = new_handle;
+                    endpoint = new_endpoint;
+                    Self::cache_device_tree(&handle, &digitizer);
+
+                    // A power-cycled digitizer comes back with defaults
+                    if let Some(dig_config) = digitizer.applied_config.lock().as_ref() {
+                        match handle.apply_config(dig_config) {
+                            Ok(count) => {
+                                info!(count, "Digitizer configuration re-applied");
+                            }
+                            Err(e) => {
+                                error!(error = %e, "Failed to re-apply digitizer configuration");
+                            }
+                        }
+                    }
+                    buffer_size = raw_buffer_size(&handle, config.buffer_size);
+
+                    // Resume acquisition if the run is still going
+                    if state_rx.borrow().in_run() {
+                        send_arm_command(&handle, config.firmware)?;
+                        send_start_command(&handle, config.firmware, config.is_master)?;
+                        hw_armed = true;
+                        hw_running = true;
+                        info!("Acquisition resumed after reconnection");
+                    }
                 }
             }
         }
@@ -597,67 +1429,178 @@ impl Reader {
         Ok(())
     }
 
+    /// Keep the DevTree of a new connection for parameter validation
+    fn cache_device_tree(handle: &CaenHandle, digitizer: &DigitizerShared) {
+        let tree = handle.get_device_tree().and_then(|json| {
+            serde_json::from_str(&json).map_err(|e| CaenError {
+                code: -1,
+                name: "JsonParseError".to_string(),
+                description: format!("Failed to parse DevTree JSON: {}", e),
+            })
+        });
+        match tree {
+            Ok(tree) => *digitizer.device_tree.lock() = Some(tree),
+            Err(e) => warn!(error = %e, "Failed to read DevTree, strict validation unavailable"),
+        }
+    }
+
+    /// Reopen the digitizer after a fatal link error
+    ///
+    /// Retries `open` + `configure_endpoint` up to `max_reconnect_attempts` times,
+    /// waiting `reconnect_backoff_ms` before each attempt.
+    /// Returns `Ok(None)` if shutdown was requested while waiting.
+    fn reconnect(
+        config: &ReaderConfig,
+        shutdown: &std::sync::atomic::AtomicBool,
+        cause: CaenError,
+    ) -> Result<Option<(CaenHandle, EndpointHandle)>, ReaderError> {
+        Self::retry_connect(config, shutdown, cause, || {
+            let handle = CaenHandle::open(&config.url)?;
+            let endpoint = handle.configure_endpoint(config.firmware.includes_n_events())?;
+            Ok((handle, endpoint))
+        })
+    }
+
+    /// Retry loop of [`Self::reconnect`] around an arbitrary `connect`
+    fn retry_connect<T>(
+        config: &ReaderConfig,
+        shutdown: &std::sync::atomic::AtomicBool,
+        cause: CaenError,
+        mut connect: impl FnMut() -> Result<T, CaenError>,
+    ) -> Result<Option<T>, ReaderError> {
+        let mut last_error = cause;
+
+        for attempt in 1..=config.max_reconnect_attempts {
+            std::thread::sleep(Duration::from_millis(config.reconnect_backoff_ms));
+            if shutdown.load(Ordering::Relaxed) {
+                return Ok(None);
+            }
+
+            warn!(
+                attempt,
+                max_attempts = config.max_reconnect_attempts,
+                url = %config.url,
+                "Reconnecting to digitizer"
+            );
+
+            match connect() {
+                Ok(connection) => {
+                    info!(attempt, "Reconnected to digitizer");
+                    return Ok(Some(connection));
+                }
+                Err(e) => {
+                    warn!(attempt, error = %e, "Reconnection attempt failed");
+                    last_error = e;
+                }
+            }
+        }
+
+        Err(ReaderError::ReconnectFailed {
+            attempts: config.max_reconnect_attempts,
+            last_error,
+        })
+    }
+
     /// DecodeLoop task - decodes raw data and publishes via ZMQ
+    ///
+    /// With `decode_workers > 1` event buffers are decoded by worker threads
+    /// (see [`workers`]); everything else, including publishing, stays here.
+    ///
+    /// On shutdown the buffers already queued by the ReadLoop are still
+    /// decoded and published (for at most [`SHUTDOWN_DRAIN_TIMEOUT`]) before
+    /// the EOS, so the tail of the run is not lost. Buffers that arrive
+    /// after the queue has run empty are not waited for.
     async fn decode_loop(
         config: ReaderConfig,
-        mut rx: mpsc::UnboundedReceiver<decoder::RawData>,
-        mut data_socket: publish::Publish,
+        context: DecodeContext,
+        data_socket: publish::Publish,
         metrics: Arc<ReaderMetrics>,
-        state_rx: watch::Receiver<ComponentState>,
-        mut shutdown: tokio::sync::broadcast::Receiver<()>,
     ) -> Result<(), ReaderError> {
-        info!("DecodeLoop starting");
-
-        // Create decoder based on firmware type
-        let mut decoder = match config.firmware {
-            FirmwareType::PSD2 => {
-                let psd2_config = Psd2Config {
-                    time_step_ns: config.time_step_ns,
-                    module_id: config.module_id,
-                    dump_enabled: false,
-                    num_channels: 32,
-                };
-                DecoderKind::Psd2(Psd2Decoder::new(psd2_config))
-            }
-            FirmwareType::PSD1 => {
-                let psd1_config = Psd1Config {
-                    time_step_ns: config.time_step_ns,
-                    module_id: config.module_id,
-                    dump_enabled: false,
-                };
-                DecoderKind::Psd1(Psd1Decoder::new(psd1_config))
-            }
-            FirmwareType::PHA => {
-                return Err(ReaderError::Config(
-                    "PHA1 decoder not yet implemented".to_string(),
-                ));
-            }
-        };
-
-        let mut sequence_number: u64 = 0;
+        let DecodeContext {
+            mut rx,
+            raw_pool,
+            mut raw_files,
+            mut state_rx,
+            shared_state,
+            mut shutdown,
+        } = context;
+        info!(
+            workers = config.decode_workers.max(1),
+            "DecodeLoop starting"
+        );
+
+        // Create decoder based on firmware type (only classifies when workers decode)
+        let mut decoder = config.create_decoder().ok_or_else(|| {
+            ReaderError::Config(format!("No decoder registered for {:?}", config.firmware))
+        })?;
+        let mut workers = if config.decode_workers > 1 {
+            Some(DecodeWorkers::spawn(
+                &config,
+                config.decode_workers,
+                &raw_pool,
+            )?)
+        } else {
+            None
+        };
+
+        let mut publisher =
+            BatchPublisher::new(&config, data_socket, metrics.clone(), decoder.flag_masks());
         let mut heartbeat_counter: u64 = 0;
 
+        // Run starts, for firmware that sends no Start signal (PSD1)
+        let mut last_state = *state_rx.borrow_and_update();
+        let mut state_open = true;
+
         // Heartbeat ticker
         let use_heartbeat = config.heartbeat_interval_ms > 0;
         let mut heartbeat_ticker =
             interval(Duration::from_millis(config.heartbeat_interval_ms.max(100)));
 
+        // Set on shutdown: decode what is queued until then, then stop
+        let mut drain_deadline: Option<tokio::time::Instant> = None;
+
         loop {
+            if drain_deadline.is_some() && rx.is_empty() {
+                break;
+            }
+
             tokio::select! {
                 biased;
 
-                _ = shutdown.recv() => {
-                    info!("DecodeLoop received shutdown signal");
+                _ = shutdown.recv(), if drain_deadline.is_none() => {
+                    info!(queued = rx.len(), "DecodeLoop received shutdown signal, draining queued buffers");
+                    drain_deadline = Some(tokio::time::Instant::now() + SHUTDOWN_DRAIN_TIMEOUT);
+                }
+
+                // A new run (not a Resume) starts a new epoch
+                changed = state_rx.changed(), if state_open => {
+                    match changed {
+                        Ok(()) => {
+                            let current = *state_rx.borrow_and_update();
+                            if current == ComponentState::Running && last_state != ComponentState::Paused {
+                                publisher.restart();
+                                heartbeat_counter = 0;
+                                info!(epoch = publisher.epoch, "Run started, new epoch");
+                            }
+                            last_state = current;
+                        }
+                        Err(_) => state_open = false,
+                    }
+                }
+
+                _ = tokio::time::sleep_until(drain_deadline.unwrap_or_else(tokio::time::Instant::now)),
+                    if drain_deadline.is_some() => {
+                    warn!(remaining = rx.len(), "Drain timeout, discarding queued raw buffers");
                     break;
                 }
 
-                // Heartbeat (only when Running)
-                _ = heartbeat_ticker.tick(), if use_heartbeat && *state_rx.borrow() == ComponentState::Running => {
+                // Heartbeat (only when Running or Paused)
+                _ = heartbeat_ticker.tick(), if use_heartbeat && drain_deadline.is_none() && state_rx.borrow().in_run() => {
                     let hb = Message::heartbeat(config.source_id, heartbeat_counter);
                     heartbeat_counter += 1;
                     let bytes = hb.to_msgpack()?;
-                    let msg: tmq::Multipart = vec![tmq::Message::from(bytes.as_slice())].into();
-                    data_socket.send(msg).await?;
+                    let msg = topic::data_message(config.topic_prefix.as_deref(), &bytes);
+                    publisher.data_socket.send(msg).await?;
                     debug!(counter = heartbeat_counter, "Published heartbeat");
                 }
 
@@ -666,63 +1609,73 @@ impl Reader {
                     match raw {
                         Some(raw_data) => {
                             // Update queue length metric
-                            metrics.queue_length.fetch_sub(1, Ordering::Relaxed);
+                            metrics.record_dequeued();
+
+                            // Record the undecoded buffer first (includes Start/Stop signals)
+                            if raw_files.wants_open() && state_rx.borrow().in_run() {
+                                let run_number = shared_state
+                                    .lock()
+                                    .await
+                                    .run_config
+                                    .as_ref()
+                                    .map(|c| c.run_number)
+                                    .unwrap_or(0);
+                                raw_files.open(run_number).await;
+                            }
+                            raw_files.write(&raw_data).await;
 
-                            // Classify and decode
+                            // Classify, then decode here or on the workers
                             let data_type = decoder.classify(&raw_data);
-                            match data_type {
-                                DataType::Event => {
-                                    // Decode events
-                                    let events = decoder.decode(&raw_data);
-
-                                    if events.is_empty() {
-                                        continue;
+                            if data_type == DataType::Event {
+                                match workers {
+                                    Some(ref mut workers) => {
+                                        workers.submit(raw_data).await?;
+                                        // Keep the workers busy while buffers are queued; once
+                                        // the queue is empty, publish everything decoded
+                                        let keep = if rx.is_empty() { 0 } else { workers.max_in_flight() - 1 };
+                                        publisher.collect(workers, keep, &config).await?;
                                     }
-
-                                    // Convert to EventDataBatch
-                                    let mut batch = EventDataBatch::with_capacity(
-                                        config.source_id,
-                                        sequence_number,
-                                        events.len(),
-                                    );
-
-                                    for event in &events {
-                                        batch.push(Self::convert_event(event));
+                                    None => {
+                                        let decoded = Self::decode_batch_into(
+                                            decoder.as_mut(),
+                                            &raw_data,
+                                            config.source_id,
+                                            publisher.sequence_number,
+                                            &config.module_map,
+                                            &mut publisher.buffers,
+                                        );
+                                        // Hand the raw buffer back to the ReadLoop
+                                        raw_pool.give(raw_data.data);
+                                        if let Some(batch) = decoded {
+                                            publisher.publish(batch, &config).await?;
+                                        }
                                     }
-
-                                    // Update metrics
-                                    metrics.events_decoded.fetch_add(events.len() as u64, Ordering::Relaxed);
-
-                                    // Publish
-                                    let msg = Message::data(batch);
-                                    let bytes = msg.to_msgpack()?;
-                                    let zmq_msg: tmq::Multipart = vec![tmq::Message::from(bytes.as_slice())].into();
-                                    data_socket.send(zmq_msg).await?;
-
-                                    sequence_number += 1;
-                                    metrics.batches_published.fetch_add(1, Ordering::Relaxed);
-
-                                    debug!(events = events.len(), seq = sequence_number - 1, "Decoded and published batch");
                                 }
+                                continue;
+                            }
+                            raw_pool.give(raw_data.data);
+
+                            // Signals apply after every event read before them
+                            if let Some(ref mut workers) = workers {
+                                publisher.collect(workers, 0, &config).await?;
+                            }
+                            match data_type {
                                 DataType::Start => {
                                     info!("Received START signal from digitizer");
-                                    // Reset sequence number on Start
-                                    sequence_number = 0;
+                                    // Reset sequence number on Start; the new epoch marks the restart
+                                    publisher.restart();
                                     heartbeat_counter = 0;
-                                    info!("Sequence number reset to 0 on Start");
+                                    info!(epoch = publisher.epoch, "Sequence number reset to 0 on Start");
                                 }
                                 DataType::Stop => {
                                     info!("Received STOP signal from digitizer");
-                                    // Send EOS
-                                    let eos = Message::eos(config.source_id);
-                                    let bytes = eos.to_msgpack()?;
-                                    let zmq_msg: tmq::Multipart = vec![tmq::Message::from(bytes.as_slice())].into();
-                                    data_socket.send(zmq_msg).await?;
-                                    info!(source_id = config.source_id, "Published EOS");
+                                    raw_files.close().await;
+                                    Self::publish_eos(&mut publisher.data_socket, &config).await?;
                                 }
                                 DataType::Unknown => {
                                     warn!("Received unknown data type");
                                 }
+                                DataType::Event => {} // Decoded above
                             }
                         }
                         None => {
@@ -734,8 +1687,18 @@ impl Reader {
             }
         }
 
+        if let Some(ref mut workers) = workers {
+            publisher.collect(workers, 0, &config).await?;
+        }
+        // This loop owns the data socket: EOS must go out before it is dropped
+        if drain_deadline.is_some() && state_rx.borrow().in_run() {
+            Self::publish_eos(&mut publisher.data_socket, &config).await?;
+        }
+
+        raw_files.finish().await;
+
         info!(
-            total_batches = sequence_number,
+            total_batches = publisher.sequence_number,
             total_events = metrics.events_decoded.load(Ordering::Relaxed),
             "DecodeLoop stopped"
         );
@@ -758,8 +1721,13 @@ impl Reader {
             "Reader ready, waiting for commands"
         );
 
+        // The decode loop owns the socket from here on (it also sends the shutdown EOS)
+        let data_socket = self.take_data_socket()?;
+
         // Create channels
-        let (raw_tx, raw_rx) = mpsc::unbounded_channel::<decoder::RawData>();
+        let (raw_tx, raw_rx) =
+            mpsc::channel::<decoder::RawData>(self.config.decode_channel_capacity.max(1));
+        let raw_pool = Arc::new(BufferPool::new(RAW_BUFFER_POOL_SIZE));
 
         // Shutdown flag for ReadLoop (it runs in spawn_blocking, can't use async channel)
         let read_shutdown = Arc::new(std::sync::atomic::AtomicBool::new(false));
@@ -772,7 +1740,15 @@ impl Reader {
         let shutdown_for_cmd = shutdown.resubscribe();
         let metrics_for_cmd = self.metrics.clone();
         let rate_tracker_for_cmd = self.rate_tracker.clone();
+        let byte_rate_tracker_for_cmd = self.byte_rate_tracker.clone();
         let url_for_cmd = self.config.url.clone();
+        let config_file_for_cmd = self.config.config_file.clone();
+        let apply_defaults = self.config.apply_defaults;
+        let strict_validation = self.config.strict_validation;
+        let firmware = self.config.firmware;
+        let module_id = self.config.module_id;
+        let filter_active = filter::is_active(&self.config.event_filter);
+        let digitizer_for_cmd = self.digitizer.clone();
 
         let cmd_handle = tokio::spawn(async move {
             run_command_task(
@@ -784,7 +1760,15 @@ impl Reader {
                     let mut ext = ReaderCommandExt {
                         metrics: metrics_for_cmd.clone(),
                         rate_tracker: rate_tracker_for_cmd.clone(),
+                        byte_rate_tracker: byte_rate_tracker_for_cmd.clone(),
                         url: url_for_cmd.clone(),
+                        config_file: config_file_for_cmd.clone(),
+                        apply_defaults,
+                        strict_validation,
+                        firmware,
+                        module_id,
+                        filter_active,
+                        digitizer: digitizer_for_cmd.clone(),
                     };
                     handle_command(state, tx, cmd, Some(&mut ext))
                 },
@@ -797,43 +1781,86 @@ impl Reader {
         let read_config = self.config.clone();
         let read_state_rx = self.state_rx.clone();
         let read_metrics = self.metrics.clone();
-
-        let read_handle = tokio::task::spawn_blocking(move || {
-            Self::read_loop(
-                read_config,
-                raw_tx,
-                read_state_rx,
-                read_metrics,
-                read_shutdown_clone,
-            )
+        let read_shared_state = self.shared_state.clone();
+        let read_state_tx = self.state_tx.clone();
+        let read_raw_pool = raw_pool.clone();
+        let read_digitizer = self.digitizer.clone();
+
+        // Panics are isolated: only this source goes to Error, the process keeps serving commands.
+        // Neither loop is respawned; the Reader must be restarted to read data again.
+        // A dedicated thread, not spawn_blocking: it runs for the whole process
+        // and may be pinned to a core
+        let read_core = read_config.read_core;
+        let read_handle = affinity::spawn_dedicated("ReadLoop", read_core, move || {
+            let result = isolate_blocking(
+                "ReadLoop",
+                read_shared_state.clone(),
+                read_state_tx.clone(),
+                || {
+                    Self::read_loop(
+                        read_config,
+                        raw_tx,
+                        read_raw_pool,
+                        read_state_rx,
+                        read_metrics,
+                        read_digitizer,
+                        read_shutdown_clone,
+                    )
+                },
+            );
+            // Unrecoverable hardware errors (e.g. reconnection gave up) → Error state
+            if let Some(Err(ref e)) = result {
+                error!(error = %e, "ReadLoop failed, entering Error state");
+                enter_error_state_blocking(
+                    &read_shared_state,
+                    &read_state_tx,
+                    format!("ReadLoop failed: {}", e),
+                );
+            }
+            result
         });
 
-        // Take ownership of data_socket for decode loop
-        let data_socket = std::mem::replace(
-            &mut self.data_socket,
-            // Dummy socket - will not be used after this
-            publish(&Context::new()).bind("tcp://127.0.0.1:0").unwrap(),
-        );
-
         // Spawn DecodeLoop task
         let decode_config = self.config.clone();
         let decode_metrics = self.metrics.clone();
-        let decode_state_rx = self.state_rx.clone();
-        let shutdown_for_decode = shutdown.resubscribe();
+        let decode_shared_state = self.shared_state.clone();
+        let decode_state_tx = self.state_tx.clone();
+        let decode_context = DecodeContext {
+            rx: raw_rx,
+            raw_pool,
+            raw_files: RawFileThread::spawn(&decode_config),
+            state_rx: self.state_rx.clone(),
+            shared_state: self.shared_state.clone(),
+            shutdown: shutdown.resubscribe(),
+        };
 
-        let decode_handle = tokio::spawn(async move {
-            Self::decode_loop(
-                decode_config,
-                raw_rx,
-                data_socket,
-                decode_metrics,
-                decode_state_rx,
-                shutdown_for_decode,
+        let decode_core = decode_config.decode_core;
+        let decode_future = async move {
+            isolate(
+                "DecodeLoop",
+                decode_shared_state,
+                decode_state_tx,
+                Self::decode_loop(decode_config, decode_context, data_socket, decode_metrics),
             )
             .await
-        });
-
-        // Wait for shutdown signal
+        };
+        // A pinned DecodeLoop gets a dedicated thread driven by this runtime
+        let decode_handle = match decode_core {
+            Some(core) => {
+                let runtime = tokio::runtime::Handle::current();
+                let done = affinity::spawn_dedicated("DecodeLoop", Some(core), move || {
+                    runtime.block_on(decode_future)
+                });
+                tokio::spawn(async move {
+                    let _ = done.await;
+                })
+            }
+            None => tokio::spawn(async move {
+                let _ = decode_future.await;
+            }),
+        };
+
+        // Wait for shutdown signal
         let _ = shutdown.recv().await;
         info!("Reader received shutdown signal");
 
@@ -845,11 +1872,6 @@ impl Reader {
         let _ = read_handle.await;
         let _ = decode_handle.await;
 
-        // Send EOS if we were running
-        if *self.state_rx.borrow() == ComponentState::Running {
-            self.send_eos().await?;
-        }
-
         info!(
             total_events = self.metrics.events_decoded.load(Ordering::Relaxed),
             total_bytes = self.metrics.bytes_read.load(Ordering::Relaxed),
@@ -864,6 +1886,651 @@ impl Reader {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::common::Command;
+
+    /// DecodeLoop inputs around a test raw queue
+    fn decode_context(
+        rx: mpsc::Receiver<decoder::RawData>,
+        pool_size: usize,
+        state_rx: watch::Receiver<ComponentState>,
+        shutdown: tokio::sync::broadcast::Receiver<()>,
+    ) -> DecodeContext {
+        DecodeContext {
+            rx,
+            raw_pool: Arc::new(BufferPool::new(pool_size)),
+            raw_files: RawFileThread::spawn(&ReaderConfig::default()),
+            state_rx,
+            shared_state: Arc::new(Mutex::new(ComponentSharedState::new())),
+            shutdown,
+        }
+    }
+
+    /// PSD2 aggregate of two-word events at (channel, coarse timestamp)
+    fn psd2_aggregate(events: &[(u64, u64)]) -> decoder::RawData {
+        let mut words = vec![(0x2u64 << 60) | (1 + 2 * events.len() as u64)];
+        for (i, &(channel, timestamp)) in events.iter().enumerate() {
+            let last = (i + 1 == events.len()) as u64;
+            words.push((channel << 56) | timestamp);
+            words.push((last << 63) | (100 << 26) | 500);
+        }
+        decoder::RawData::new(words.iter().flat_map(|w| w.to_be_bytes()).collect())
+    }
+
+    #[test]
+    fn test_byte_rate_tracker() {
+        let tracker = RateTracker::new();
+        let t0 = Instant::now();
+        tracker.update_at(1_000, t0);
+        assert_eq!(tracker.get_rate(), 0.0);
+
+        // Less than a second: rate not updated yet
+        tracker.update_at(500_000, t0 + Duration::from_millis(500));
+        assert_eq!(tracker.get_rate(), 0.0);
+
+        tracker.update_at(4_001_000, t0 + Duration::from_secs(2));
+        assert_eq!(tracker.get_rate(), 2_000_000.0);
+
+        tracker.reset();
+        assert_eq!(tracker.get_rate(), 0.0);
+    }
+
+    #[test]
+    fn test_metrics_report_queue_max_and_data_rate() {
+        let metrics = Arc::new(ReaderMetrics::default());
+        let byte_rate_tracker = Arc::new(RateTracker::new());
+        let ext = ReaderCommandExt {
+            metrics: metrics.clone(),
+            rate_tracker: Arc::new(RateTracker::new()),
+            byte_rate_tracker: byte_rate_tracker.clone(),
+            url: String::new(),
+            config_file: None,
+            apply_defaults: false,
+            strict_validation: false,
+            firmware: FirmwareType::PSD2,
+            module_id: 0,
+            filter_active: false,
+            digitizer: SharedDigitizer::default(),
+        };
+
+        // Simulated traffic: three buffers queued, one decoded, 3 MB read
+        let start = Instant::now() - Duration::from_secs(2);
+        byte_rate_tracker.update_at(0, start);
+        for _ in 0..3 {
+            metrics.record_enqueued();
+        }
+        metrics.record_dequeued();
+        metrics.bytes_read.fetch_add(3_000_000, Ordering::Relaxed);
+
+        let m = ext.get_metrics().unwrap();
+        assert_eq!(m.queue_size, 2);
+        assert_eq!(m.queue_max, 3);
+        assert!(m.data_rate > 0.0);
+        assert_eq!(m.bytes_transferred, 3_000_000);
+    }
+
+    #[test]
+    fn test_get_recent_events_returns_last_n() {
+        let metrics = Arc::new(ReaderMetrics::new(100));
+        let mut ext = ReaderCommandExt {
+            metrics: metrics.clone(),
+            rate_tracker: Arc::new(RateTracker::new()),
+            byte_rate_tracker: Arc::new(RateTracker::new()),
+            url: String::new(),
+            config_file: None,
+            apply_defaults: false,
+            strict_validation: false,
+            firmware: FirmwareType::PSD2,
+            module_id: 0,
+            filter_active: false,
+            digitizer: SharedDigitizer::default(),
+        };
+        let events: Vec<CommonEventData> = (0..250)
+            .map(|i| CommonEventData::new(0, (i % 4) as u8, 100, 50, i as f64, 0))
+            .collect();
+        for batch in events.chunks(30) {
+            metrics.recent_events.record(batch);
+        }
+
+        let mut state = ComponentSharedState::new();
+        let (state_tx, _state_rx) = watch::channel(ComponentState::Running);
+        let resp = handle_command(
+            &mut state,
+            &state_tx,
+            Command::GetRecentEvents(3),
+            Some(&mut ext),
+        );
+        assert!(resp.success, "{}", resp.message);
+        let recent: Vec<CommonEventData> = serde_json::from_value(resp.data.unwrap()).unwrap();
+        assert_eq!(recent, events[247..].to_vec());
+
+        // Disabled ring: the command is rejected rather than returning nothing
+        ext.metrics = Arc::new(ReaderMetrics::default());
+        let resp = handle_command(
+            &mut state,
+            &state_tx,
+            Command::GetRecentEvents(3),
+            Some(&mut ext),
+        );
+        assert!(!resp.success);
+    }
+
+    #[test]
+    fn test_module_map_tags_events_per_channel() {
+        // Events on channels 0, 16 and 5
+        let raw = psd2_aggregate(&[(0, 1000), (16, 2000), (5, 3000)]);
+
+        let mut decoder = ReaderConfig {
+            module_id: 9,
+            ..Default::default()
+        }
+        .create_decoder()
+        .unwrap();
+        let module_map = HashMap::from([(0, 1), (16, 2)]);
+        let batch = Reader::decode_batch_into(
+            decoder.as_mut(),
+            &raw,
+            0,
+            0,
+            &module_map,
+            &mut DecodeBuffers::default(),
+        )
+        .unwrap();
+
+        let tagged: Vec<(u8, u8)> = batch.events.iter().map(|e| (e.channel, e.module)).collect();
+        // Unmapped channels keep the configured module_id
+        assert_eq!(tagged, vec![(0, 1), (16, 2), (5, 9)]);
+    }
+
+    #[test]
+    fn test_get_config_reports_reader_fields() {
+        let digitizer = SharedDigitizer::default();
+        let mut ext = ReaderCommandExt {
+            metrics: Arc::new(ReaderMetrics::default()),
+            rate_tracker: Arc::new(RateTracker::new()),
+            byte_rate_tracker: Arc::new(RateTracker::new()),
+            url: "dig2://172.18.4.56".to_string(),
+            config_file: Some("dig1.json".to_string()),
+            apply_defaults: false,
+            strict_validation: false,
+            firmware: FirmwareType::PSD1,
+            module_id: 3,
+            filter_active: false,
+            digitizer: digitizer.clone(),
+        };
+        let mut state = ComponentSharedState::new();
+        let (state_tx, _state_rx) = watch::channel(ComponentState::Idle);
+
+        let resp = handle_command(&mut state, &state_tx, Command::GetConfig, Some(&mut ext));
+        assert!(resp.success);
+        let data = resp.data.unwrap();
+        assert_eq!(data["url"], "dig2://172.18.4.56");
+        assert_eq!(data["firmware"], "PSD1");
+        assert_eq!(data["module_id"], 3);
+        assert_eq!(data["config_file"], "dig1.json");
+        assert!(data["digitizer"].is_null());
+
+        // Parameters applied by the ReadLoop show up in later queries
+        *digitizer.applied_config.lock() = Some(crate::config::digitizer::DigitizerConfig::new(
+            1,
+            "dig1",
+            FirmwareType::PSD1,
+        ));
+        let resp = handle_command(&mut state, &state_tx, Command::GetConfig, Some(&mut ext));
+        assert_eq!(resp.data.unwrap()["digitizer"]["name"], "dig1");
+    }
+
+    #[test]
+    fn test_strict_validation_uses_cached_devtree() {
+        let digitizer = SharedDigitizer::default();
+        let mut ext = ReaderCommandExt {
+            metrics: Arc::new(ReaderMetrics::default()),
+            rate_tracker: Arc::new(RateTracker::new()),
+            byte_rate_tracker: Arc::new(RateTracker::new()),
+            url: "dig2://172.18.4.56".to_string(),
+            config_file: None,
+            apply_defaults: true,
+            strict_validation: true,
+            firmware: FirmwareType::PSD2,
+            module_id: 0,
+            filter_active: false,
+            digitizer: digitizer.clone(),
+        };
+
+        // No connection yet: nothing to validate against
+        let err = ext.on_configure(&RunConfig::default()).unwrap_err();
+        assert!(err.contains("not connected"), "{err}");
+
+        // The PSD2 default threshold (500) exceeds this DevTree's limit
+        *digitizer.device_tree.lock() = Some(serde_json::json!({
+            "ch": { "0": { "par": { "TriggerThr": {
+                "datatype": { "value": "NUMBER" },
+                "minvalue": { "value": "0" },
+                "maxvalue": { "value": "100" }
+            } } } }
+        }));
+        let err = ext.on_configure(&RunConfig::default()).unwrap_err();
+        assert!(err.starts_with("Strict validation failed"), "{err}");
+        assert!(err.contains("TriggerThr"), "{err}");
+    }
+
+    #[test]
+    fn test_reconfigure_validates_against_cached_devtree() {
+        let digitizer = SharedDigitizer::default();
+        let mut ext = ReaderCommandExt {
+            metrics: Arc::new(ReaderMetrics::default()),
+            rate_tracker: Arc::new(RateTracker::new()),
+            byte_rate_tracker: Arc::new(RateTracker::new()),
+            url: "dig2://172.18.4.56".to_string(),
+            config_file: None,
+            apply_defaults: false,
+            strict_validation: true,
+            firmware: FirmwareType::PSD2,
+            module_id: 0,
+            filter_active: false,
+            digitizer: digitizer.clone(),
+        };
+        let source = DigitizerConfigSource::Json(
+            serde_json::to_value(
+                crate::config::digitizer::DigitizerConfig::firmware_defaults(FirmwareType::PSD2),
+            )
+            .unwrap(),
+        );
+
+        let err = ext.on_reconfigure_digitizer(&source).unwrap_err();
+        assert!(err.contains("not connected"), "{err}");
+
+        // Rejected before anything is queued for the ReadLoop
+        *digitizer.device_tree.lock() = Some(serde_json::json!({
+            "ch": { "0": { "par": { "TriggerThr": {
+                "datatype": { "value": "NUMBER" },
+                "minvalue": { "value": "0" },
+                "maxvalue": { "value": "100" }
+            } } } }
+        }));
+        let err = ext.on_reconfigure_digitizer(&source).unwrap_err();
+        assert!(err.starts_with("Strict validation failed"), "{err}");
+        assert!(digitizer.jobs.lock().is_empty());
+        assert!(digitizer.applied_config.lock().is_none());
+    }
+
+    #[test]
+    fn test_trigger_mode_needs_cached_devtree() {
+        let digitizer = SharedDigitizer::default();
+        let mut ext = ReaderCommandExt {
+            metrics: Arc::new(ReaderMetrics::default()),
+            rate_tracker: Arc::new(RateTracker::new()),
+            byte_rate_tracker: Arc::new(RateTracker::new()),
+            url: "dig1://caen.internal/usb?link_num=0".to_string(),
+            config_file: None,
+            apply_defaults: false,
+            strict_validation: false,
+            firmware: FirmwareType::PSD1,
+            module_id: 0,
+            filter_active: false,
+            digitizer: digitizer.clone(),
+        };
+
+        let err = ext.on_set_trigger_mode(TriggerMode::External).unwrap_err();
+        assert!(err.contains("not connected"), "{err}");
+
+        // PSD1 channel ranges come from the DevTree; none means no job at all
+        *digitizer.device_tree.lock() = Some(serde_json::json!({ "par": {} }));
+        let err = ext.on_set_trigger_mode(TriggerMode::External).unwrap_err();
+        assert!(err.contains("0 channels"), "{err}");
+        assert!(digitizer.jobs.lock().is_empty());
+    }
+
+    #[test]
+    fn test_stalled_decoder_drops_are_counted() {
+        let (tx, mut rx) = mpsc::channel::<decoder::RawData>(4);
+        let metrics = ReaderMetrics::default();
+        let pool = BufferPool::new(2);
+
+        // Nobody drains the queue: only `capacity` buffers are held
+        for _ in 0..100 {
+            let raw = decoder::RawData::new(vec![0u8; 1024]);
+            assert!(enqueue_raw(
+                &tx,
+                raw,
+                DecodeQueuePolicy::Drop,
+                &metrics,
+                &pool
+            ));
+        }
+        assert_eq!(metrics.dropped_raw.load(Ordering::Relaxed), 96);
+        assert_eq!(metrics.queue_length.load(Ordering::Relaxed), 4);
+
+        // Dropped buffers go back to the pool for reuse
+        assert!(pool.take().is_some_and(|b| b.capacity() >= 1024));
+
+        let mut queued = 0;
+        while rx.try_recv().is_ok() {
+            queued += 1;
+        }
+        assert_eq!(queued, 4);
+
+        drop(rx);
+        let raw = decoder::RawData::new(vec![0u8; 16]);
+        assert!(!enqueue_raw(
+            &tx,
+            raw,
+            DecodeQueuePolicy::Drop,
+            &metrics,
+            &pool
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_fan_out_to_extra_data_addresses() {
+        use futures::StreamExt;
+
+        let mut reader = Reader::new(ReaderConfig {
+            data_address: "tcp://127.0.0.1:15584".to_string(),
+            extra_data_addresses: vec!["tcp://127.0.0.1:15585".to_string()],
+            command_address: "tcp://127.0.0.1:15586".to_string(),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+
+        let context = Context::new();
+        let mut subscribers: Vec<_> = ["tcp://127.0.0.1:15584", "tcp://127.0.0.1:15585"]
+            .iter()
+            .map(|addr| {
+                tmq::subscribe(&context)
+                    .connect(addr)
+                    .unwrap()
+                    .subscribe(b"")
+                    .unwrap()
+            })
+            .collect();
+
+        let mut batch = EventDataBatch::new(3, 0);
+        batch.push(CommonEventData::new(3, 1, 100, 50, 42.0, 0));
+        let message = Message::data(batch);
+
+        // Publish until each subscriber has joined (PUB drops messages before that)
+        for sub in subscribers.iter_mut() {
+            let received = tokio::time::timeout(Duration::from_secs(5), async {
+                loop {
+                    let bytes = message.to_msgpack().unwrap();
+                    reader
+                        .data_socket
+                        .as_mut()
+                        .unwrap()
+                        .send(topic::data_message(None, &bytes))
+                        .await
+                        .unwrap();
+                    if let Ok(Some(Ok(multipart))) =
+                        tokio::time::timeout(Duration::from_millis(50), sub.next()).await
+                    {
+                        return multipart;
+                    }
+                }
+            })
+            .await
+            .expect("subscriber receives the batch");
+            let data = topic::payload(received).unwrap();
+            match Message::from_msgpack(&data).unwrap() {
+                Message::Data(batch) => {
+                    assert_eq!(batch.source_id, 3);
+                    assert_eq!(batch.events[0].timestamp_ns, 42.0);
+                }
+                other => panic!("unexpected message: {:?}", other),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_data_socket_binds_only_configured_endpoint() {
+        use futures::StreamExt;
+
+        let mut reader = Reader::new(ReaderConfig {
+            data_address: "tcp://127.0.0.1:15599".to_string(),
+            command_address: "tcp://127.0.0.1:15600".to_string(),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+        let endpoint =
+            |socket: &publish::Publish| socket.get_socket().get_last_endpoint().unwrap().unwrap();
+        assert_eq!(
+            endpoint(reader.data_socket.as_ref().unwrap()),
+            "tcp://127.0.0.1:15599"
+        );
+
+        // Taking the socket (as `run` does) leaves nothing behind to bind or reuse
+        let mut socket = reader.take_data_socket().unwrap();
+        assert!(reader.data_socket.is_none());
+        assert!(matches!(
+            reader.take_data_socket(),
+            Err(ReaderError::Config(_))
+        ));
+        drop(reader);
+
+        // The taken socket is still the live endpoint subscribers connect to
+        assert_eq!(endpoint(&socket), "tcp://127.0.0.1:15599");
+        let context = Context::new();
+        let mut sub = tmq::subscribe(&context)
+            .connect("tcp://127.0.0.1:15599")
+            .unwrap()
+            .subscribe(b"")
+            .unwrap();
+        let bytes = Message::heartbeat(0, 1).to_msgpack().unwrap();
+        tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                socket
+                    .send(topic::data_message(None, &bytes))
+                    .await
+                    .unwrap();
+                if let Ok(Some(Ok(_))) =
+                    tokio::time::timeout(Duration::from_millis(50), sub.next()).await
+                {
+                    return;
+                }
+            }
+        })
+        .await
+        .expect("subscriber receives from the taken socket");
+    }
+
+    /// Decode the same 40 aggregates with `workers` decode workers; returns the
+    /// decoded event count and the published (sequence number, first timestamp)
+    async fn decode_with_workers(workers: usize, address: &str) -> (u64, Vec<(u64, f64)>) {
+        use futures::StreamExt;
+
+        let config = ReaderConfig {
+            decode_workers: workers,
+            heartbeat_interval_ms: 100,
+            ..Default::default()
+        };
+        let context = Context::new();
+        let data_socket = publish(&context).bind(address).unwrap();
+        let mut sub = tmq::subscribe(&context)
+            .connect(address)
+            .unwrap()
+            .subscribe(b"")
+            .unwrap();
+
+        let (raw_tx, raw_rx) = mpsc::channel(64);
+        let (_state_tx, state_rx) = watch::channel(ComponentState::Running);
+        let (_shutdown_tx, shutdown_rx) = tokio::sync::broadcast::channel(1);
+        let metrics = Arc::new(ReaderMetrics::default());
+        let decode = tokio::spawn(Reader::decode_loop(
+            config,
+            decode_context(raw_rx, 4, state_rx, shutdown_rx),
+            data_socket,
+            metrics.clone(),
+        ));
+
+        // A heartbeat proves the subscriber has joined
+        tokio::time::timeout(Duration::from_secs(5), sub.next())
+            .await
+            .expect("heartbeat within timeout");
+
+        // Uneven batch sizes so workers finish out of order
+        for k in 0..40u64 {
+            let events: Vec<(u64, u64)> = (0..2 + (k * 7) % 13)
+                .map(|i| (i % 8, k * 100 + i))
+                .collect();
+            raw_tx.send(psd2_aggregate(&events)).await.unwrap();
+        }
+        drop(raw_tx);
+        decode.await.unwrap().unwrap();
+
+        let mut published = Vec::new();
+        while let Ok(Some(multipart)) =
+            tokio::time::timeout(Duration::from_millis(500), sub.next()).await
+        {
+            let payload = topic::payload(multipart.unwrap()).unwrap();
+            if let Message::Data(batch) = Message::from_msgpack(&payload).unwrap() {
+                published.push((batch.sequence_number, batch.events[0].timestamp_ns));
+            }
+        }
+        (metrics.events_decoded.load(Ordering::Relaxed), published)
+    }
+
+    #[tokio::test]
+    async fn test_parallel_decode_matches_single_worker() {
+        let (single_events, single) = decode_with_workers(1, "tcp://127.0.0.1:15601").await;
+        let (parallel_events, parallel) = decode_with_workers(4, "tcp://127.0.0.1:15602").await;
+
+        assert_eq!(
+            single_events,
+            (0..40).map(|k| 2 + (k * 7) % 13).sum::<u64>()
+        );
+        assert_eq!(parallel_events, single_events);
+        // Same batches in the same order, numbered 0, 1, 2, ...
+        assert_eq!(parallel, single);
+        let sequence: Vec<u64> = parallel.iter().map(|&(seq, _)| seq).collect();
+        assert_eq!(sequence, (0..40).collect::<Vec<_>>());
+    }
+
+    #[tokio::test]
+    async fn test_decode_worker_panic_reaches_decode_loop() {
+        struct PanickingDecoder;
+
+        impl Decoder for PanickingDecoder {
+            fn classify(&self, _raw: &decoder::RawData) -> DataType {
+                DataType::Event
+            }
+
+            fn decode_into(&mut self, _raw: &decoder::RawData, _events: &mut Vec<EventData>) {
+                panic!("corrupt buffer");
+            }
+        }
+
+        let mut config = ReaderConfig {
+            firmware: FirmwareType::PHA,
+            ..Default::default()
+        };
+        config
+            .decoders
+            .register(FirmwareType::PHA, |_| Box::new(PanickingDecoder));
+        let mut workers =
+            workers::DecodeWorkers::spawn(&config, 2, &Arc::new(BufferPool::new(1))).unwrap();
+        workers
+            .submit(decoder::RawData::new(vec![0u8; 8]))
+            .await
+            .unwrap();
+
+        // The worker's panic comes out of the DecodeLoop side, where `isolate` catches it
+        let result = tokio::spawn(async move { workers.next().await }).await;
+        let payload = result.unwrap_err().into_panic();
+        assert_eq!(payload.downcast_ref::<&str>(), Some(&"corrupt buffer"));
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_while_running_publishes_eos() {
+        use futures::StreamExt;
+
+        let config = ReaderConfig {
+            source_id: 4,
+            heartbeat_interval_ms: 100,
+            ..Default::default()
+        };
+        let context = Context::new();
+        let data_socket = publish(&context).bind("tcp://127.0.0.1:15594").unwrap();
+        let mut sub = tmq::subscribe(&context)
+            .connect("tcp://127.0.0.1:15594")
+            .unwrap()
+            .subscribe(b"")
+            .unwrap();
+
+        let (_raw_tx, raw_rx) = mpsc::channel(4);
+        let (_state_tx, state_rx) = watch::channel(ComponentState::Running);
+        let (shutdown_tx, shutdown_rx) = tokio::sync::broadcast::channel(1);
+        let decode = tokio::spawn(Reader::decode_loop(
+            config,
+            decode_context(raw_rx, 1, state_rx, shutdown_rx),
+            data_socket,
+            Arc::new(ReaderMetrics::default()),
+        ));
+
+        async fn next_message(sub: &mut tmq::subscribe::Subscribe) -> Message {
+            let multipart = tokio::time::timeout(Duration::from_secs(5), sub.next())
+                .await
+                .expect("message within timeout")
+                .unwrap()
+                .unwrap();
+            Message::from_msgpack(&topic::payload(multipart).unwrap()).unwrap()
+        }
+
+        // A heartbeat proves the subscriber has joined
+        assert!(matches!(
+            next_message(&mut sub).await,
+            Message::Heartbeat(_)
+        ));
+
+        shutdown_tx.send(()).unwrap();
+        decode.await.unwrap().unwrap();
+
+        let eos = loop {
+            match next_message(&mut sub).await {
+                Message::Heartbeat(_) => continue,
+                other => break other,
+            }
+        };
+        assert!(matches!(eos, Message::EndOfStream { source_id: 4 }));
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_drains_queued_buffers() {
+        let config = ReaderConfig {
+            heartbeat_interval_ms: 100,
+            ..Default::default()
+        };
+        let context = Context::new();
+        let data_socket = publish(&context).bind("tcp://127.0.0.1:15603").unwrap();
+
+        // Buffers the ReadLoop queued before Stop; the sender stays open
+        let (raw_tx, raw_rx) = mpsc::channel(8);
+        for k in 0..3u64 {
+            let events: Vec<(u64, u64)> = (0..4).map(|i| (i, k * 100 + i)).collect();
+            raw_tx.try_send(psd2_aggregate(&events)).unwrap();
+        }
+        let (_state_tx, state_rx) = watch::channel(ComponentState::Running);
+        let (shutdown_tx, shutdown_rx) = tokio::sync::broadcast::channel(1);
+        shutdown_tx.send(()).unwrap();
+
+        let metrics = Arc::new(ReaderMetrics::default());
+        tokio::time::timeout(
+            Duration::from_secs(5),
+            Reader::decode_loop(
+                config,
+                decode_context(raw_rx, 4, state_rx, shutdown_rx),
+                data_socket,
+                metrics.clone(),
+            ),
+        )
+        .await
+        .expect("decode loop stops after draining")
+        .unwrap();
+
+        assert_eq!(metrics.batches_published.load(Ordering::Relaxed), 3);
+        assert_eq!(metrics.events_decoded.load(Ordering::Relaxed), 12);
+        drop(raw_tx);
+    }
 
     #[test]
     fn test_default_config() {
@@ -871,6 +2538,77 @@ mod tests {
         assert_eq!(config.source_id, 0);
         assert_eq!(config.firmware, FirmwareType::PSD2);
         assert_eq!(config.buffer_size, 1024 * 1024);
+        assert_eq!(config.reconnect_backoff_ms, 1000);
+        assert_eq!(config.max_reconnect_attempts, 5);
+        assert_eq!(config.max_message_bytes, DEFAULT_MAX_MESSAGE_BYTES);
+    }
+
+    fn link_lost() -> CaenError {
+        CaenError {
+            code: -6,
+            name: "CommunicationError".to_string(),
+            description: "link lost".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_reconnect_honours_backoff_and_attempts() {
+        let config = ReaderConfig {
+            reconnect_backoff_ms: 20,
+            max_reconnect_attempts: 3,
+            ..Default::default()
+        };
+        let shutdown = std::sync::atomic::AtomicBool::new(false);
+
+        // Succeeds on the second attempt, after two backoff periods
+        let mut calls = 0;
+        let started = Instant::now();
+        let result = Reader::retry_connect(&config, &shutdown, link_lost(), || {
+            calls += 1;
+            if calls < 2 {
+                Err(link_lost())
+            } else {
+                Ok(calls)
+            }
+        });
+        assert_eq!(result.unwrap(), Some(2));
+        assert!(started.elapsed() >= Duration::from_millis(40));
+
+        // Gives up after max_reconnect_attempts
+        let mut calls = 0;
+        let result = Reader::retry_connect(&config, &shutdown, link_lost(), || {
+            calls += 1;
+            Err::<(), _>(link_lost())
+        });
+        assert_eq!(calls, 3);
+        assert!(matches!(
+            result,
+            Err(ReaderError::ReconnectFailed { attempts: 3, .. })
+        ));
+
+        // 0 attempts: no reconnection at all
+        let config = ReaderConfig {
+            max_reconnect_attempts: 0,
+            ..config
+        };
+        let result = Reader::retry_connect(&config, &shutdown, link_lost(), || -> Result<(), _> {
+            panic!("must not connect")
+        });
+        assert!(matches!(
+            result,
+            Err(ReaderError::ReconnectFailed { attempts: 0, .. })
+        ));
+
+        // Shutdown during the backoff stops retrying
+        shutdown.store(true, Ordering::Relaxed);
+        let config = ReaderConfig {
+            max_reconnect_attempts: 3,
+            ..config
+        };
+        let result = Reader::retry_connect(&config, &shutdown, link_lost(), || -> Result<(), _> {
+            panic!("must not connect")
+        });
+        assert!(matches!(result, Ok(None)));
     }
 
     #[test]
@@ -879,7 +2617,7 @@ mod tests {
             timestamp_ns: 1234567.0,
             module: 1,
             channel: 5,
-            energy: 1000,
+            energy: 100_000,
             energy_short: 800,
             fine_time: 512,
             flags: 0x01,
@@ -897,7 +2635,7 @@ mod tests {
 
         assert_eq!(module, 1);
         assert_eq!(channel, 5);
-        assert_eq!(energy, 1000);
+        assert_eq!(energy, 100_000);
         assert_eq!(energy_short, 800);
         assert_eq!(timestamp_ns, 1234567.0);
         assert_eq!(flags, 0x01);
@@ -946,6 +2684,217 @@ mod tests {
         assert_eq!(reader_config.firmware, FirmwareType::PSD1);
     }
 
+    #[test]
+    fn test_from_config_zle_maps_firmware() {
+        let toml = r#"
+            [[network.sources]]
+            id = 0
+            type = "zle"
+            bind = "tcp://*:5555"
+            digitizer_url = "dig2://172.18.4.57"
+
+            [network.merger]
+            subscribe = ["tcp://localhost:5555"]
+            publish = "tcp://*:5557"
+
+            [network.recorder]
+            subscribe = "tcp://localhost:5557"
+        "#;
+        let config = crate::config::Config::from_toml(toml).unwrap();
+        let reader_config = ReaderConfig::from_config(&config, 0).unwrap();
+        assert_eq!(reader_config.firmware, FirmwareType::ZLE);
+        assert!(reader_config.create_decoder().is_some());
+    }
+
+    #[test]
+    fn test_registered_decoder_routes_reader_data() {
+        /// Tags every buffer with one event carrying the buffer size
+        struct CountingDecoder(u8);
+
+        impl Decoder for CountingDecoder {
+            fn classify(&self, _raw: &decoder::RawData) -> DataType {
+                DataType::Event
+            }
+
+            fn decode_into(&mut self, raw: &decoder::RawData, events: &mut Vec<EventData>) {
+                events.push(EventData {
+                    timestamp_ns: 0.0,
+                    module: self.0,
+                    channel: 7,
+                    energy: raw.size as u32,
+                    energy_short: 0,
+                    fine_time: 0,
+                    flags: 0,
+                    waveform: None,
+                });
+            }
+        }
+
+        let mut config = ReaderConfig {
+            firmware: FirmwareType::PHA,
+            module_id: 5,
+            ..Default::default()
+        };
+        // No built-in PHA decoder yet
+        assert!(config.create_decoder().is_none());
+
+        config.decoders.register(FirmwareType::PHA, |p| {
+            Box::new(CountingDecoder(p.module_id))
+        });
+        let mut decoder = config.create_decoder().unwrap();
+        let raw = decoder::RawData::new(vec![0u8; 40]);
+        assert_eq!(decoder.classify(&raw), DataType::Event);
+
+        let batch = Reader::decode_batch(&mut *decoder, &raw, 2, 0).unwrap();
+        assert_eq!(batch.source_id, 2);
+        let events: Vec<_> = batch
+            .events
+            .iter()
+            .map(|e| (e.module, e.channel, e.energy))
+            .collect();
+        assert_eq!(events, vec![(5, 7, 40)]);
+    }
+
+    #[test]
+    fn test_time_step_per_source_scales_timestamps() {
+        // A 500 MHz and a 250 MHz board in the same setup
+        let toml = r#"
+            [[network.sources]]
+            id = 0
+            type = "psd2"
+            bind = "tcp://*:5555"
+            digitizer_url = "dig2://172.18.4.56"
+            time_step_ns = 2.0
+
+            [[network.sources]]
+            id = 1
+            type = "psd2"
+            bind = "tcp://*:5556"
+            digitizer_url = "dig2://172.18.4.57"
+            time_step_ns = 4.0
+        "#;
+        let config = crate::config::Config::from_toml(toml).unwrap();
+        let fast = ReaderConfig::from_config(&config, 0).unwrap();
+        let slow = ReaderConfig::from_config(&config, 1).unwrap();
+        assert_eq!(fast.time_step_ns, 2.0);
+        assert_eq!(slow.time_step_ns, 4.0);
+
+        // Identical raw coarse counters on both boards
+        let counters = [1000u64, 250_000, 1 << 40];
+        let raw = psd2_aggregate(&counters.map(|counter| (3, counter)));
+
+        // Decoders are built the way the DecodeLoop builds them
+        let timestamps = |config: &ReaderConfig| -> Vec<f64> {
+            let mut decoder = config.create_decoder().unwrap();
+            let batch = Reader::decode_batch(decoder.as_mut(), &raw, config.source_id, 0).unwrap();
+            batch.events.iter().map(|e| e.timestamp_ns).collect()
+        };
+        let fast_ts = timestamps(&fast);
+        let slow_ts = timestamps(&slow);
+
+        assert_eq!(fast_ts.len(), counters.len());
+        for ((&counter, &f), &s) in counters.iter().zip(&fast_ts).zip(&slow_ts) {
+            // Fine time is zero in these records, so only the coarse counter counts
+            assert_eq!(f, counter as f64 * 2.0);
+            assert_eq!(s, counter as f64 * 4.0);
+        }
+    }
+
+    #[test]
+    fn test_from_config_master_and_slave() {
+        let toml = r#"
+            [[network.sources]]
+            id = 0
+            type = "psd2"
+            bind = "tcp://*:5555"
+            digitizer_url = "dig2://172.18.4.56"
+            is_master = true
+
+            [[network.sources]]
+            id = 1
+            type = "psd2"
+            bind = "tcp://*:5556"
+            digitizer_url = "dig2://172.18.4.57"
+        "#;
+        let config = crate::config::Config::from_toml(toml).unwrap();
+        assert!(ReaderConfig::from_config(&config, 0).unwrap().is_master);
+        assert!(!ReaderConfig::from_config(&config, 1).unwrap().is_master);
+    }
+
+    #[test]
+    fn test_from_config_without_master_starts_itself() {
+        let toml = r#"
+            [[network.sources]]
+            id = 0
+            type = "psd2"
+            bind = "tcp://*:5555"
+            digitizer_url = "dig2://172.18.4.56"
+        "#;
+        let config = crate::config::Config::from_toml(toml).unwrap();
+        assert!(ReaderConfig::from_config(&config, 0).unwrap().is_master);
+    }
+
+    /// Records commands instead of talking to hardware
+    #[derive(Default)]
+    struct MockHandle {
+        startmode: String,
+        commands: std::cell::RefCell<Vec<String>>,
+    }
+
+    impl AcquisitionControl for MockHandle {
+        fn get_value(&self, _path: &str) -> Result<String, CaenError> {
+            Ok(self.startmode.clone())
+        }
+
+        fn send_command(&self, path: &str) -> Result<(), CaenError> {
+            self.commands.borrow_mut().push(path.to_string());
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_raw_buffer_size_from_device() {
+        let handle = MockHandle {
+            startmode: "4194304".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(raw_buffer_size(&handle, 1024), 4 * 1024 * 1024);
+
+        // Unusable value: configured size
+        let handle = MockHandle {
+            startmode: "0".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(raw_buffer_size(&handle, 1024), 1024);
+    }
+
+    #[test]
+    fn test_master_arms_and_starts() {
+        let handle = MockHandle::default();
+        send_arm_command(&handle, FirmwareType::PSD2).unwrap();
+        send_start_command(&handle, FirmwareType::PSD2, true).unwrap();
+        assert_eq!(
+            *handle.commands.borrow(),
+            vec!["/cmd/armacquisition", "/cmd/swstartacquisition"]
+        );
+    }
+
+    #[test]
+    fn test_slave_only_arms() {
+        let handle = MockHandle::default();
+        send_arm_command(&handle, FirmwareType::PSD2).unwrap();
+        send_start_command(&handle, FirmwareType::PSD2, false).unwrap();
+        assert_eq!(*handle.commands.borrow(), vec!["/cmd/armacquisition"]);
+
+        let dig1 = MockHandle {
+            startmode: "START_MODE_S_IN".to_string(),
+            ..Default::default()
+        };
+        send_arm_command(&dig1, FirmwareType::PSD1).unwrap();
+        send_start_command(&dig1, FirmwareType::PSD1, false).unwrap();
+        assert_eq!(*dig1.commands.borrow(), vec!["/cmd/armacquisition"]);
+    }
+
     #[test]
     fn test_from_config_emulator_returns_none() {
         let toml = r#"
@@ -0,0 +1,101 @@
+//! Buffer reuse between the ReadLoop and DecodeLoop
+//!
+//! At high rates the Reader used to allocate a fresh raw buffer per read and
+//! fresh event vectors per decoded batch. Raw buffers now travel back from
+//! the DecodeLoop to the ReadLoop through a [`BufferPool`], and the
+//! DecodeLoop keeps its decode vectors in [`DecodeBuffers`], taking the event
+//! vector back from each batch once it has been published.
+
+use parking_lot::Mutex;
+
+use super::decoder;
+use crate::common::{EventData, EventDataBatch};
+
+/// Bounded free list of reusable buffers shared between threads
+#[derive(Debug)]
+pub(crate) struct BufferPool<T> {
+    free: Mutex<Vec<T>>,
+    max_buffers: usize,
+}
+
+impl<T> BufferPool<T> {
+    /// Create an empty pool keeping at most `max_buffers` spare buffers
+    pub(crate) fn new(max_buffers: usize) -> Self {
+        Self {
+            free: Mutex::new(Vec::with_capacity(max_buffers)),
+            max_buffers,
+        }
+    }
+
+    /// Take a spare buffer, if any
+    pub(crate) fn take(&self) -> Option<T> {
+        self.free.lock().pop()
+    }
+
+    /// Return a buffer for reuse (dropped if the pool is full)
+    pub(crate) fn give(&self, buffer: T) {
+        let mut free = self.free.lock();
+        if free.len() < self.max_buffers {
+            free.push(buffer);
+        }
+    }
+
+    /// Number of spare buffers
+    #[cfg(test)]
+    pub(crate) fn len(&self) -> usize {
+        self.free.lock().len()
+    }
+}
+
+/// Vectors reused across decoded batches by a single DecodeLoop
+#[derive(Debug, Default)]
+pub(crate) struct DecodeBuffers {
+    /// Decoder output for the current raw buffer
+    pub(crate) decoded: Vec<decoder::EventData>,
+    /// Event storage for the next published batch
+    pub(crate) events: Vec<EventData>,
+}
+
+impl DecodeBuffers {
+    /// Take back the event vector of a batch that has been published
+    pub(crate) fn recycle(&mut self, batch: EventDataBatch) {
+        let mut events = batch.events;
+        if events.capacity() > self.events.capacity() {
+            events.clear();
+            self.events = events;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pool_reuses_and_caps_buffers() {
+        let pool = BufferPool::new(2);
+        assert!(pool.take().is_none());
+
+        let buffer: Vec<u8> = Vec::with_capacity(4096);
+        let ptr = buffer.as_ptr();
+        pool.give(buffer);
+        pool.give(Vec::with_capacity(16));
+        pool.give(Vec::with_capacity(16));
+        assert_eq!(pool.len(), 2);
+
+        pool.take();
+        let reused = pool.take().unwrap();
+        assert_eq!(reused.as_ptr(), ptr);
+        assert!(reused.capacity() >= 4096);
+    }
+
+    #[test]
+    fn test_recycle_keeps_event_capacity() {
+        let mut buffers = DecodeBuffers::default();
+        let mut batch = EventDataBatch::with_capacity(0, 0, 128);
+        batch.push(EventData::new(0, 0, 1, 1, 0.0, 0));
+        buffers.recycle(batch);
+        assert!(buffers.events.is_empty());
+        assert!(buffers.events.capacity() >= 128);
+    }
+}
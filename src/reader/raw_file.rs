@@ -0,0 +1,723 @@
+//! Raw CAEN buffer recording and offline decoding
+//!
+//! When `raw_record_dir` is set, the Reader writes every buffer it receives
+//! from the digitizer to disk before decoding. The file can later be decoded
+//! offline through the same [`DecoderRegistry`] as the live path, so improved
+//! decoders (e.g. Fine TS handling) can be re-applied to old data.
+//!
+//! File structure:
+//! ```text
+//! ┌─────────────────────────────────────────┐
+//! │  Magic "DLRAW001" (8 bytes)             │
+//! │  Header length (u32 LE)                 │
+//! │  Header (MsgPack RawFileHeader)         │
+//! ├─────────────────────────────────────────┤
+//! │  Frame 1                                │
+//! │  - Data length (u32 LE)                 │
+//! │  - n_events (u32 LE)                    │
+//! │  - Raw buffer bytes                     │
+//! ├─────────────────────────────────────────┤
+//! │  ...                                    │
+//! └─────────────────────────────────────────┘
+//! ```
+//!
+//! Frames include Start/Stop signal buffers, so offline decoding sees the
+//! same stream as the live decode loop.
+//!
+//! The same format serves the `dump_raw` debug dumps. A write error does not
+//! stop the Reader: it is logged and the file stays off until the next Stop
+//! signal.
+
+use std::fs::{File, OpenOptions};
+use std::io::{BufReader, BufWriter, ErrorKind, Read, Write};
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use super::decoder::{
+    DataType, Decoder, DecoderParams, DecoderRegistry, RawData, DEFAULT_ADC_BITS,
+};
+use super::{affinity, FirmwareType, Reader, ReaderConfig};
+use crate::common::EventDataBatch;
+use tokio::sync::{mpsc, oneshot};
+use tracing::{error, info};
+
+/// Buffers queued for the raw file thread before the DecodeLoop waits
+const RAW_FILE_QUEUE_SIZE: usize = 64;
+
+/// Magic bytes for raw buffer files
+pub const RAW_FILE_MAGIC: [u8; 8] = *b"DLRAW001";
+
+/// File extension for raw buffer files
+pub const RAW_FILE_EXTENSION: &str = "dlraw";
+
+/// Metadata needed to decode a raw file offline
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RawFileHeader {
+    /// Firmware type (selects the decoder)
+    pub firmware: FirmwareType,
+    /// Source ID of the recording Reader
+    pub source_id: u32,
+    /// Module ID assigned to decoded events
+    pub module_id: u8,
+    /// ADC time step in nanoseconds
+    pub time_step_ns: f64,
+    /// Run number at recording time
+    pub run_number: u32,
+    /// File creation time (Unix timestamp in nanoseconds)
+    pub file_start_time_ns: u64,
+    /// Digitizer configuration (JSON) in effect during the run
+    pub config_snapshot: Option<String>,
+    /// Energy resolution of the recording Reader (absent in older files)
+    #[serde(default = "default_adc_bits")]
+    pub adc_bits: u8,
+}
+
+fn default_adc_bits() -> u8 {
+    DEFAULT_ADC_BITS
+}
+
+impl RawFileHeader {
+    /// Create a header with the current time as start time
+    pub fn new(firmware: FirmwareType, source_id: u32, module_id: u8, time_step_ns: f64) -> Self {
+        Self {
+            firmware,
+            source_id,
+            module_id,
+            time_step_ns,
+            run_number: 0,
+            file_start_time_ns: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_nanos() as u64,
+            config_snapshot: None,
+            adc_bits: DEFAULT_ADC_BITS,
+        }
+    }
+}
+
+/// Raw file errors
+#[derive(Debug, thiserror::Error)]
+pub enum RawFileError {
+    #[error("Invalid raw file magic bytes")]
+    InvalidMagic,
+
+    #[error("Truncated frame at offset {0}")]
+    TruncatedFrame(u64),
+
+    #[error("No decoder for firmware {0:?}")]
+    UnsupportedFirmware(FirmwareType),
+
+    #[error("Deserialization error: {0}")]
+    Deserialization(#[from] rmp_serde::decode::Error),
+
+    #[error("Serialization error: {0}")]
+    Serialization(#[from] rmp_serde::encode::Error),
+
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// Writes framed raw buffers
+pub struct RawFileWriter<W: Write> {
+    writer: W,
+    frames: u64,
+    bytes: u64,
+}
+
+impl RawFileWriter<BufWriter<File>> {
+    /// Create a new raw file; fails if the file already exists
+    pub fn create(path: &Path, header: &RawFileHeader) -> Result<Self, RawFileError> {
+        let file = OpenOptions::new().write(true).create_new(true).open(path)?;
+        Self::new(BufWriter::with_capacity(1024 * 1024, file), header)
+    }
+}
+
+impl<W: Write> RawFileWriter<W> {
+    /// Write the file header and return a writer ready for frames
+    pub fn new(mut writer: W, header: &RawFileHeader) -> Result<Self, RawFileError> {
+        let header_bytes = rmp_serde::to_vec(header)?;
+        writer.write_all(&RAW_FILE_MAGIC)?;
+        writer.write_all(&(header_bytes.len() as u32).to_le_bytes())?;
+        writer.write_all(&header_bytes)?;
+        Ok(Self {
+            writer,
+            frames: 0,
+            bytes: 0,
+        })
+    }
+
+    /// Append one raw buffer
+    pub fn write_frame(&mut self, raw: &RawData) -> Result<(), RawFileError> {
+        let data = &raw.data[..raw.size.min(raw.data.len())];
+        self.writer.write_all(&(data.len() as u32).to_le_bytes())?;
+        self.writer.write_all(&raw.n_events.to_le_bytes())?;
+        self.writer.write_all(data)?;
+        self.frames += 1;
+        self.bytes += data.len() as u64;
+        Ok(())
+    }
+
+    /// Number of frames written
+    pub fn frames(&self) -> u64 {
+        self.frames
+    }
+
+    /// Number of raw payload bytes written
+    pub fn bytes(&self) -> u64 {
+        self.bytes
+    }
+
+    /// Flush and return the underlying writer
+    pub fn finish(mut self) -> Result<W, RawFileError> {
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+/// Raw file kept for one run by [`RawFileThread`] (recording or debug dump)
+///
+/// The file is opened on the first buffer of a run and closed on Stop. An
+/// I/O error is logged and turns the file off until the next Stop, so it
+/// never stops acquisition.
+pub(crate) struct RunRawFile {
+    kind: &'static str,
+    enabled: bool,
+    writer: Option<RawFileWriter<BufWriter<File>>>,
+    failed: bool,
+}
+
+impl RunRawFile {
+    pub(crate) fn new(kind: &'static str, enabled: bool) -> Self {
+        Self {
+            kind,
+            enabled,
+            writer: None,
+            failed: false,
+        }
+    }
+
+    /// Whether the next in-run buffer should open a file
+    pub(crate) fn wants_open(&self) -> bool {
+        self.enabled && self.writer.is_none() && !self.failed
+    }
+
+    /// Open the file with `create` unless it is open or failed
+    pub(crate) fn open_with(
+        &mut self,
+        create: impl FnOnce() -> Result<RawFileWriter<BufWriter<File>>, RawFileError>,
+    ) {
+        if !self.wants_open() {
+            return;
+        }
+        match create() {
+            Ok(writer) => self.writer = Some(writer),
+            Err(e) => {
+                error!(kind = self.kind, error = %e, "Cannot create raw file, disabled until Stop");
+                self.failed = true;
+            }
+        }
+    }
+
+    /// Append a buffer if the file is open
+    pub(crate) fn write(&mut self, raw: &RawData) {
+        if let Some(ref mut writer) = self.writer {
+            if let Err(e) = writer.write_frame(raw) {
+                error!(kind = self.kind, error = %e, frames = writer.frames(), "Raw file write failed, disabled until Stop");
+                self.writer = None;
+                self.failed = true;
+            }
+        }
+    }
+
+    /// Flush and close the file; the next run opens a new one
+    pub(crate) fn close(&mut self) {
+        self.failed = false;
+        if let Some(writer) = self.writer.take() {
+            let frames = writer.frames();
+            match writer.finish() {
+                Ok(_) => info!(kind = self.kind, frames, "Closed raw file"),
+                Err(e) => error!(kind = self.kind, error = %e, frames, "Failed to flush raw file"),
+            }
+        }
+    }
+}
+
+/// Work for the raw file thread
+enum RawFileCommand {
+    /// Open the files for a run (unless open or failed)
+    Open(u32),
+    /// Append a buffer to the open files
+    Write(RawData),
+    /// Flush and close the files at Stop
+    Close,
+}
+
+/// Raw recording and debug dump of the DecodeLoop, written on their own thread
+///
+/// File I/O blocks, so the DecodeLoop only queues buffers here. A slow disk
+/// holds the DecodeLoop up through the bounded queue instead of stalling
+/// the runtime. Without recording or dump no thread is started.
+pub(crate) struct RawFileThread {
+    tx: Option<mpsc::Sender<RawFileCommand>>,
+    done: Option<oneshot::Receiver<()>>,
+    open: bool,
+}
+
+impl RawFileThread {
+    /// Start the thread if `config` records or dumps raw buffers
+    pub(crate) fn spawn(config: &ReaderConfig) -> Self {
+        if config.raw_record_dir.is_none() && !config.dump_raw {
+            return Self {
+                tx: None,
+                done: None,
+                open: false,
+            };
+        }
+        let (tx, mut rx) = mpsc::channel(RAW_FILE_QUEUE_SIZE);
+        let config = config.clone();
+        let done = affinity::spawn_dedicated("RawFiles", None, move || {
+            let mut record = RunRawFile::new("record", config.raw_record_dir.is_some());
+            let mut dump = RunRawFile::new("dump", config.dump_raw);
+            while let Some(command) = rx.blocking_recv() {
+                match command {
+                    RawFileCommand::Open(run_number) => {
+                        if let Some(ref dir) = config.raw_record_dir {
+                            record.open_with(|| {
+                                Reader::open_raw_file(&config, dir, run_number, false)
+                            });
+                        }
+                        dump.open_with(|| {
+                            Reader::open_raw_file(&config, &config.dump_dir, run_number, true)
+                        });
+                    }
+                    RawFileCommand::Write(raw) => {
+                        record.write(&raw);
+                        dump.write(&raw);
+                    }
+                    RawFileCommand::Close => {
+                        record.close();
+                        dump.close();
+                    }
+                }
+            }
+            record.close();
+            dump.close();
+        });
+        Self {
+            tx: Some(tx),
+            done: Some(done),
+            open: false,
+        }
+    }
+
+    /// Whether the next in-run buffer should open the files
+    pub(crate) fn wants_open(&self) -> bool {
+        self.tx.is_some() && !self.open
+    }
+
+    async fn send(&self, command: RawFileCommand) {
+        if let Some(ref tx) = self.tx {
+            if tx.send(command).await.is_err() {
+                error!("Raw file thread is gone, buffer not written");
+            }
+        }
+    }
+
+    /// Open the files for `run_number`
+    pub(crate) async fn open(&mut self, run_number: u32) {
+        self.send(RawFileCommand::Open(run_number)).await;
+        self.open = true;
+    }
+
+    /// Queue a copy of a buffer if the files are open
+    pub(crate) async fn write(&self, raw: &RawData) {
+        if self.open {
+            self.send(RawFileCommand::Write(raw.clone())).await;
+        }
+    }
+
+    /// Close the files; the next run opens new ones
+    pub(crate) async fn close(&mut self) {
+        if self.open {
+            self.send(RawFileCommand::Close).await;
+            self.open = false;
+        }
+    }
+
+    /// Close the files and wait until the thread has flushed them
+    pub(crate) async fn finish(mut self) {
+        self.tx = None;
+        if let Some(done) = self.done.take() {
+            let _ = done.await;
+        }
+    }
+}
+
+/// Reads framed raw buffers
+pub struct RawFileReader<R: Read> {
+    reader: R,
+    header: RawFileHeader,
+    offset: u64,
+}
+
+impl RawFileReader<BufReader<File>> {
+    /// Open a raw file for reading
+    pub fn open(path: &Path) -> Result<Self, RawFileError> {
+        Self::new(BufReader::new(File::open(path)?))
+    }
+}
+
+impl<R: Read> RawFileReader<R> {
+    /// Read and validate the file header
+    pub fn new(mut reader: R) -> Result<Self, RawFileError> {
+        let mut magic = [0u8; 8];
+        reader.read_exact(&mut magic)?;
+        if magic != RAW_FILE_MAGIC {
+            return Err(RawFileError::InvalidMagic);
+        }
+        let mut len = [0u8; 4];
+        reader.read_exact(&mut len)?;
+        let len = u32::from_le_bytes(len) as usize;
+        let mut header_bytes = vec![0u8; len];
+        reader.read_exact(&mut header_bytes)?;
+        let header = rmp_serde::from_slice(&header_bytes)?;
+        Ok(Self {
+            reader,
+            header,
+            offset: (8 + 4 + len) as u64,
+        })
+    }
+
+    /// File header
+    pub fn header(&self) -> &RawFileHeader {
+        &self.header
+    }
+
+    /// Read the next frame; `Ok(None)` at a clean end of file
+    pub fn next_frame(&mut self) -> Result<Option<RawData>, RawFileError> {
+        let mut prefix = [0u8; 8];
+        match self.reader.read_exact(&mut prefix[..4]) {
+            Ok(()) => {}
+            Err(e) if e.kind() == ErrorKind::UnexpectedEof => return Ok(None),
+            Err(e) => return Err(e.into()),
+        }
+        let frame_start = self.offset;
+        self.reader
+            .read_exact(&mut prefix[4..])
+            .map_err(|_| RawFileError::TruncatedFrame(frame_start))?;
+
+        let size = u32::from_le_bytes([prefix[0], prefix[1], prefix[2], prefix[3]]) as usize;
+        let n_events = u32::from_le_bytes([prefix[4], prefix[5], prefix[6], prefix[7]]);
+        let mut data = vec![0u8; size];
+        self.reader
+            .read_exact(&mut data)
+            .map_err(|_| RawFileError::TruncatedFrame(frame_start))?;
+        self.offset += 8 + size as u64;
+
+        Ok(Some(RawData {
+            data,
+            size,
+            n_events,
+        }))
+    }
+}
+
+impl<R: Read> Iterator for RawFileReader<R> {
+    type Item = Result<RawData, RawFileError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_frame().transpose()
+    }
+}
+
+/// Decodes the frames of a raw file one at a time, as the live decode loop would
+///
+/// Only the current frame is held in memory. Sequence numbers start at 0 and
+/// reset on each Start signal.
+pub struct RawDecoder<R: Read> {
+    reader: RawFileReader<R>,
+    decoder: Box<dyn Decoder>,
+    sequence_number: u64,
+}
+
+impl<R: Read> RawDecoder<R> {
+    /// File header
+    pub fn header(&self) -> &RawFileHeader {
+        self.reader.header()
+    }
+
+    /// Decode up to the next frame holding events; `Ok(None)` at end of file
+    pub fn next_batch(&mut self) -> Result<Option<EventDataBatch>, RawFileError> {
+        while let Some(raw) = self.reader.next_frame()? {
+            match self.decoder.classify(&raw) {
+                DataType::Event => {
+                    let source_id = self.reader.header().source_id;
+                    if let Some(batch) = Reader::decode_batch(
+                        self.decoder.as_mut(),
+                        &raw,
+                        source_id,
+                        self.sequence_number,
+                    ) {
+                        self.sequence_number += 1;
+                        return Ok(Some(batch));
+                    }
+                }
+                DataType::Start => self.sequence_number = 0,
+                DataType::Stop | DataType::Unknown => {}
+            }
+        }
+        Ok(None)
+    }
+}
+
+impl<R: Read> Iterator for RawDecoder<R> {
+    type Item = Result<EventDataBatch, RawFileError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_batch().transpose()
+    }
+}
+
+/// Create the decoder for a raw file's firmware
+pub fn decode_raw<R: Read>(reader: RawFileReader<R>) -> Result<RawDecoder<R>, RawFileError> {
+    let header = reader.header();
+    let decoder = DecoderRegistry::default()
+        .create(
+            header.firmware,
+            &DecoderParams {
+                time_step_ns: header.time_step_ns,
+                module_id: header.module_id,
+                adc_bits: header.adc_bits,
+                dump_enabled: false,
+            },
+        )
+        .ok_or(RawFileError::UnsupportedFirmware(header.firmware))?;
+    Ok(RawDecoder {
+        reader,
+        decoder,
+        sequence_number: 0,
+    })
+}
+
+/// Open a raw file from disk for decoding
+pub fn decode_raw_file(path: &Path) -> Result<RawDecoder<BufReader<File>>, RawFileError> {
+    decode_raw(RawFileReader::open(path)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn words_to_raw(words: &[u64]) -> RawData {
+        RawData::new(words.iter().flat_map(|w| w.to_be_bytes()).collect())
+    }
+
+    /// PSD2 aggregate: header word followed by two-word events
+    fn psd2_buffer(events: &[(u8, u64, u16, u16)]) -> RawData {
+        let mut words = vec![(0x2u64 << 60) | (1 + 2 * events.len() as u64)];
+        for (i, &(channel, timestamp, energy, energy_short)) in events.iter().enumerate() {
+            let last = i + 1 == events.len();
+            words.push(((channel as u64) << 56) | (timestamp & 0xFFFF_FFFF_FFFF));
+            words.push(((last as u64) << 63) | ((energy_short as u64) << 26) | energy as u64);
+        }
+        let mut raw = words_to_raw(&words);
+        raw.n_events = events.len() as u32;
+        raw
+    }
+
+    #[test]
+    fn test_raw_record_then_offline_decode_matches_live() {
+        let buffers = vec![
+            psd2_buffer(&[(1, 1000, 1200, 300), (2, 1500, 800, 700)]),
+            psd2_buffer(&[(5, 4000, 2500, 1000)]),
+        ];
+
+        // Live decode path
+        let params = DecoderParams {
+            time_step_ns: 2.0,
+            module_id: 3,
+            adc_bits: 14,
+            dump_enabled: false,
+        };
+        let mut live_decoder = DecoderRegistry::default()
+            .create(FirmwareType::PSD2, &params)
+            .unwrap();
+        let live: Vec<EventDataBatch> = buffers
+            .iter()
+            .enumerate()
+            .filter_map(|(seq, raw)| {
+                assert_eq!(live_decoder.classify(raw), DataType::Event);
+                Reader::decode_batch(live_decoder.as_mut(), raw, 7, seq as u64)
+            })
+            .collect();
+        assert_eq!(live.len(), 2);
+        assert_eq!(live[0].len(), 2);
+
+        // Raw record
+        let mut header = RawFileHeader::new(FirmwareType::PSD2, 7, 3, 2.0);
+        header.run_number = 12;
+        header.adc_bits = 14;
+        header.config_snapshot = Some(r#"{"name":"dig0"}"#.to_string());
+        let mut writer = RawFileWriter::new(Vec::new(), &header).unwrap();
+        for raw in &buffers {
+            writer.write_frame(raw).unwrap();
+        }
+        assert_eq!(writer.frames(), 2);
+        let bytes = writer.finish().unwrap();
+
+        // Offline decode
+        let reader = RawFileReader::new(Cursor::new(bytes)).unwrap();
+        assert_eq!(reader.header(), &header);
+        let offline: Vec<EventDataBatch> = decode_raw(reader)
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+
+        assert_eq!(offline.len(), live.len());
+        for (o, l) in offline.iter().zip(&live) {
+            assert_eq!(o.source_id, l.source_id);
+            assert_eq!(o.sequence_number, l.sequence_number);
+            assert_eq!(o.events, l.events);
+        }
+        assert_eq!({ offline[1].events[0].module }, 3);
+    }
+
+    #[test]
+    fn test_truncated_frame_is_reported() {
+        let header = RawFileHeader::new(FirmwareType::PSD2, 0, 0, 2.0);
+        let mut writer = RawFileWriter::new(Vec::new(), &header).unwrap();
+        writer
+            .write_frame(&psd2_buffer(&[(0, 10, 100, 50)]))
+            .unwrap();
+        let mut bytes = writer.finish().unwrap();
+        bytes.truncate(bytes.len() - 3);
+
+        let mut reader = RawFileReader::new(Cursor::new(bytes)).unwrap();
+        assert!(matches!(
+            reader.next_frame(),
+            Err(RawFileError::TruncatedFrame(_))
+        ));
+    }
+
+    #[test]
+    fn test_dump_contains_original_bytes() {
+        let dir = std::env::temp_dir().join(format!("delila_dump_test_{}", std::process::id()));
+        let config = crate::reader::ReaderConfig {
+            source_id: 7,
+            dump_raw: true,
+            ..Default::default()
+        };
+        let raw = psd2_buffer(&[(3, 5000, 1234, 600)]);
+
+        let mut dump = RunRawFile::new("dump", config.dump_raw);
+        dump.open_with(|| Reader::open_raw_file(&config, dir.to_str().unwrap(), 0, true));
+        assert!(!dump.wants_open());
+        dump.write(&raw);
+        dump.close();
+
+        let path = std::fs::read_dir(&dir)
+            .unwrap()
+            .next()
+            .unwrap()
+            .unwrap()
+            .path();
+        let name = path.file_name().unwrap().to_string_lossy().into_owned();
+        let mut reader = RawFileReader::open(&path).unwrap();
+        let frame = reader.next_frame().unwrap().unwrap();
+        let _ = std::fs::remove_dir_all(&dir);
+        assert!(name.starts_with("dump_7_"));
+        assert_eq!(frame.data, raw.data);
+        assert_eq!(frame.n_events, 1);
+    }
+
+    #[tokio::test]
+    async fn test_raw_file_thread_records_until_close() {
+        let dir = std::env::temp_dir().join(format!("delila_raw_thread_{}", std::process::id()));
+        let config = crate::reader::ReaderConfig {
+            source_id: 2,
+            raw_record_dir: Some(dir.to_str().unwrap().to_string()),
+            ..Default::default()
+        };
+        let first = psd2_buffer(&[(1, 100, 500, 200)]);
+        let second = psd2_buffer(&[(2, 200, 600, 300), (3, 300, 700, 400)]);
+
+        let mut files = RawFileThread::spawn(&config);
+        files.write(&first).await; // not open yet: dropped
+        assert!(files.wants_open());
+        files.open(5).await;
+        assert!(!files.wants_open());
+        files.write(&first).await;
+        files.write(&second).await;
+        files.close().await;
+        files.write(&first).await; // closed: dropped
+        files.finish().await;
+
+        let path = std::fs::read_dir(&dir)
+            .unwrap()
+            .next()
+            .unwrap()
+            .unwrap()
+            .path();
+        let reader = RawFileReader::open(&path).unwrap();
+        assert_eq!(reader.header().run_number, 5);
+        let frames: Vec<RawData> = reader.collect::<Result<_, _>>().unwrap();
+        let _ = std::fs::remove_dir_all(&dir);
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames[1].data, second.data);
+        assert_eq!(frames[1].n_events, 2);
+    }
+
+    #[test]
+    fn test_failed_raw_file_stays_off_until_close() {
+        let mut file = RunRawFile::new("record", true);
+        file.open_with(|| Err(RawFileError::InvalidMagic));
+        assert!(!file.wants_open());
+        // Nothing open: writing is a no-op, not an error
+        file.write(&psd2_buffer(&[(0, 10, 100, 50)]));
+
+        // The next run tries again
+        file.close();
+        assert!(file.wants_open());
+        assert!(!RunRawFile::new("dump", false).wants_open());
+    }
+
+    #[test]
+    fn test_header_without_adc_bits_uses_default() {
+        // Header layout before adc_bits was recorded
+        #[derive(Serialize)]
+        struct OldHeader {
+            firmware: FirmwareType,
+            source_id: u32,
+            module_id: u8,
+            time_step_ns: f64,
+            run_number: u32,
+            file_start_time_ns: u64,
+            config_snapshot: Option<String>,
+        }
+        let header_bytes = rmp_serde::to_vec(&OldHeader {
+            firmware: FirmwareType::PSD1,
+            source_id: 1,
+            module_id: 1,
+            time_step_ns: 4.0,
+            run_number: 3,
+            file_start_time_ns: 0,
+            config_snapshot: None,
+        })
+        .unwrap();
+        let mut bytes = RAW_FILE_MAGIC.to_vec();
+        bytes.extend_from_slice(&(header_bytes.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(&header_bytes);
+
+        let reader = RawFileReader::new(Cursor::new(bytes)).unwrap();
+        assert_eq!(reader.header().run_number, 3);
+        assert_eq!(reader.header().adc_bits, DEFAULT_ADC_BITS);
+    }
+
+    #[test]
+    fn test_invalid_magic() {
+        let result = RawFileReader::new(Cursor::new(b"NOTARAWFILE.....".to_vec()));
+        assert!(matches!(result, Err(RawFileError::InvalidMagic)));
+    }
+}
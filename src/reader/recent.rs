@@ -0,0 +1,112 @@
+//! Bounded snapshot of the most recently decoded events
+//!
+//! Keeps the last `capacity` events of the decode loop so `GetRecentEvents`
+//! can show what a running Reader produces without a Recorder. Waveforms
+//! are not kept, so memory stays at roughly `capacity` small structs.
+
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+use crate::common::EventData;
+
+/// Ring of recent events, shared by the decode loop and the command handler
+#[derive(Debug, Default)]
+pub struct RecentEvents {
+    capacity: usize,
+    events: Mutex<VecDeque<EventData>>,
+}
+
+impl RecentEvents {
+    /// Ring holding up to `capacity` events (0 = disabled)
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            events: Mutex::new(VecDeque::with_capacity(capacity)),
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Append a batch, evicting the oldest events beyond capacity
+    pub fn record(&self, batch: &[EventData]) {
+        if self.capacity == 0 {
+            return;
+        }
+        // Only the tail of an oversized batch can survive
+        let tail = &batch[batch.len().saturating_sub(self.capacity)..];
+        let mut events = self.events.lock().unwrap();
+        let overflow = (events.len() + tail.len()).saturating_sub(self.capacity);
+        events.drain(..overflow);
+        events.extend(tail.iter().map(|e| EventData {
+            waveform: None,
+            ..*e
+        }));
+    }
+
+    /// The last `n` events, oldest first
+    pub fn last(&self, n: usize) -> Vec<EventData> {
+        let events = self.events.lock().unwrap();
+        events
+            .iter()
+            .skip(events.len().saturating_sub(n))
+            .cloned()
+            .collect()
+    }
+
+    pub fn clear(&self) {
+        self.events.lock().unwrap().clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn batch(timestamps: std::ops::Range<u32>) -> Vec<EventData> {
+        timestamps
+            .map(|t| EventData::new(0, 0, 100, 50, t as f64, 0))
+            .collect()
+    }
+
+    fn timestamps(events: &[EventData]) -> Vec<f64> {
+        events.iter().map(|e| e.timestamp_ns).collect()
+    }
+
+    #[test]
+    fn test_last_returns_most_recent_in_order() {
+        let recent = RecentEvents::new(8);
+        // M = 25 events in uneven batches, including one larger than the ring
+        recent.record(&batch(0..3));
+        recent.record(&batch(3..15));
+        recent.record(&batch(15..20));
+        recent.record(&batch(20..25));
+
+        assert_eq!(
+            timestamps(&recent.last(5)),
+            vec![20.0, 21.0, 22.0, 23.0, 24.0]
+        );
+        // Asking for more than is kept returns the whole ring
+        assert_eq!(
+            timestamps(&recent.last(100)),
+            (17..25).map(f64::from).collect::<Vec<_>>()
+        );
+
+        recent.clear();
+        assert!(recent.last(5).is_empty());
+    }
+
+    #[test]
+    fn test_disabled_and_waveforms_dropped() {
+        let disabled = RecentEvents::default();
+        disabled.record(&batch(0..3));
+        assert!(disabled.last(3).is_empty());
+
+        let recent = RecentEvents::new(2);
+        let mut events = batch(0..1);
+        events[0].waveform = Some(Default::default());
+        recent.record(&events);
+        assert!(recent.last(1)[0].waveform.is_none());
+    }
+}
@@ -0,0 +1,137 @@
+//! Timestamp sanity filtering for decoded events
+//!
+//! A corrupted aggregate occasionally decodes to an absurd timestamp (far in
+//! the future or past) that would pollute histograms and the Merger's sort
+//! buffers. With `timestamp_sanity_window_ns` set, every event is compared
+//! with the last accepted timestamp: events further away than the window are
+//! flagged with [`flags::FLAG_TIMESTAMP_OUTLIER`] and, with
+//! `drop_timestamp_outliers`, removed from the batch.
+//!
+//! Outliers never become the reference, so a single bad event does not drag
+//! the window along. A genuine clock jump (e.g. a timestamp reset without a
+//! Start signal) shows up as a run of outliers; after [`RESYNC_AFTER`]
+//! consecutive ones the filter adopts the new position.
+
+use crate::common::{flags, EventData};
+
+/// Consecutive outliers after which the filter re-anchors on the new timestamps
+pub const RESYNC_AFTER: u32 = 16;
+
+/// Flags (or drops) events whose timestamp jumps outside a window
+#[derive(Debug, Clone)]
+pub struct TimestampSanity {
+    window_ns: f64,
+    drop: bool,
+    /// Last accepted timestamp (None until the first event of a run)
+    reference_ns: Option<f64>,
+    consecutive_outliers: u32,
+}
+
+impl TimestampSanity {
+    pub fn new(window_ns: f64, drop: bool) -> Self {
+        Self {
+            window_ns,
+            drop,
+            reference_ns: None,
+            consecutive_outliers: 0,
+        }
+    }
+
+    /// Forget the reference (digitizer Start: timestamps restart from zero)
+    pub fn reset(&mut self) {
+        self.reference_ns = None;
+        self.consecutive_outliers = 0;
+    }
+
+    /// Flag or drop outliers in `events`; returns how many were found
+    pub fn filter(&mut self, events: &mut Vec<EventData>) -> u64 {
+        let mut outliers = 0u64;
+        for event in events.iter_mut() {
+            if self.accept(event.timestamp_ns) {
+                continue;
+            }
+            event.flags |= flags::FLAG_TIMESTAMP_OUTLIER;
+            outliers += 1;
+        }
+        if self.drop && outliers > 0 {
+            events.retain(|e| e.flags & flags::FLAG_TIMESTAMP_OUTLIER == 0);
+        }
+        outliers
+    }
+
+    /// Check one timestamp against the reference, updating it if accepted
+    fn accept(&mut self, timestamp_ns: f64) -> bool {
+        let within = match self.reference_ns {
+            None => true,
+            Some(reference) => (timestamp_ns - reference).abs() <= self.window_ns,
+        };
+        if within {
+            self.reference_ns = Some(timestamp_ns);
+            self.consecutive_outliers = 0;
+            return true;
+        }
+
+        self.consecutive_outliers += 1;
+        if self.consecutive_outliers >= RESYNC_AFTER {
+            self.reference_ns = Some(timestamp_ns);
+            self.consecutive_outliers = 0;
+        }
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn events(timestamps: &[f64]) -> Vec<EventData> {
+        timestamps
+            .iter()
+            .map(|&t| EventData::new(0, 0, 100, 50, t, 0))
+            .collect()
+    }
+
+    #[test]
+    fn test_outlier_is_flagged_or_dropped() {
+        // 1 µs spacing with one corrupted timestamp ~3 hours in the future
+        let timestamps = [1000.0, 2000.0, 1.0e13, 3000.0, 4000.0];
+
+        let mut flagging = TimestampSanity::new(1.0e6, false);
+        let mut batch = events(&timestamps);
+        assert_eq!(flagging.filter(&mut batch), 1);
+        assert_eq!(batch.len(), 5);
+        let flagged: Vec<bool> = batch
+            .iter()
+            .map(|e| e.flags & flags::FLAG_TIMESTAMP_OUTLIER != 0)
+            .collect();
+        assert_eq!(flagged, vec![false, false, true, false, false]);
+
+        let mut dropping = TimestampSanity::new(1.0e6, true);
+        let mut batch = events(&timestamps);
+        assert_eq!(dropping.filter(&mut batch), 1);
+        let kept: Vec<f64> = batch.iter().map(|e| e.timestamp_ns).collect();
+        assert_eq!(kept, vec![1000.0, 2000.0, 3000.0, 4000.0]);
+
+        // The reference carries over to the next batch
+        let mut batch = events(&[-5.0e12, 5000.0]);
+        assert_eq!(dropping.filter(&mut batch), 1);
+        assert_eq!(batch.len(), 1);
+    }
+
+    #[test]
+    fn test_resyncs_after_sustained_jump() {
+        let mut sanity = TimestampSanity::new(1.0e6, false);
+        sanity.filter(&mut events(&[1000.0]));
+
+        // The clock really jumped: flagged until RESYNC_AFTER, then accepted
+        let jumped: Vec<f64> = (0..RESYNC_AFTER + 2)
+            .map(|i| 1.0e12 + i as f64 * 1000.0)
+            .collect();
+        let mut batch = events(&jumped);
+        assert_eq!(sanity.filter(&mut batch), RESYNC_AFTER as u64);
+
+        // Start resets the reference entirely
+        sanity.reset();
+        assert_eq!(sanity.filter(&mut events(&[0.0, 1000.0])), 0);
+    }
+}
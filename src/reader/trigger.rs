@@ -0,0 +1,146 @@
+//! Trigger mode switching for CAEN digitizers
+//!
+//! Maps a firmware-independent `TriggerMode` to the CAEN parameters that
+//! select it. Used by the Reader's `SetTriggerMode` command.
+//!
+//! | Mode        | PSD2 `/par/globaltriggersource` | PSD1 (self / ext / sw enable) |
+//! |-------------|---------------------------------|-------------------------------|
+//! | SelfTrigger | `ITLA`                          | TRUE / FALSE / FALSE          |
+//! | External    | `TrgIn`                         | FALSE / TRUE / FALSE          |
+//! | Software    | `SwTrg`                         | FALSE / FALSE / TRUE          |
+
+use super::caen::ParamSetter;
+use crate::common::TriggerMode;
+use crate::config::digitizer::CaenParameter;
+use crate::config::FirmwareType;
+
+/// CAEN parameters that select a trigger mode on the given firmware
+///
+/// Returns Err if the firmware does not support trigger mode switching.
+pub fn trigger_mode_parameters(
+    firmware: FirmwareType,
+    mode: TriggerMode,
+    num_channels: u32,
+) -> Result<Vec<CaenParameter>, String> {
+    let param = |path: &str, value: &str| CaenParameter {
+        path: path.to_string(),
+        value: value.to_string(),
+    };
+    let flag = |enabled: bool| if enabled { "TRUE" } else { "FALSE" };
+
+    match firmware {
+        // x27xx firmwares share the dig2 trigger parameters
+        FirmwareType::PSD2 | FirmwareType::ZLE => {
+            let source = match mode {
+                TriggerMode::SelfTrigger => "ITLA",
+                TriggerMode::External => "TrgIn",
+                TriggerMode::Software => "SwTrg",
+            };
+            Ok(vec![param("/par/globaltriggersource", source)])
+        }
+        FirmwareType::PSD1 => {
+            if num_channels == 0 {
+                return Err("Cannot set trigger mode: digitizer reports 0 channels".to_string());
+            }
+            let ch_range = format!("/ch/0..{}/par/ch_self_trg_enable", num_channels - 1);
+            Ok(vec![
+                param(&ch_range, flag(mode == TriggerMode::SelfTrigger)),
+                param("/par/trg_ext_enable", flag(mode == TriggerMode::External)),
+                param("/par/trg_sw_enable", flag(mode == TriggerMode::Software)),
+            ])
+        }
+        FirmwareType::PHA => Err(format!(
+            "Trigger mode {} not supported for {:?} firmware",
+            mode, firmware
+        )),
+    }
+}
+
+/// Write the parameters for a trigger mode to the device
+///
+/// Returns the parameters that were written.
+pub fn apply_trigger_mode<H: ParamSetter>(
+    handle: &H,
+    firmware: FirmwareType,
+    mode: TriggerMode,
+    num_channels: u32,
+) -> Result<Vec<CaenParameter>, String> {
+    let params = trigger_mode_parameters(firmware, mode, num_channels)?;
+    write_parameters(handle, &params)?;
+    Ok(params)
+}
+
+/// Write parameters in order, stopping at the first one the device rejects
+pub fn write_parameters<H: ParamSetter>(
+    handle: &H,
+    params: &[CaenParameter],
+) -> Result<(), String> {
+    for p in params {
+        handle
+            .set_value(&p.path, &p.value)
+            .map_err(|e| format!("Failed to set {} = {}: {}", p.path, p.value, e))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reader::caen::CaenError;
+    use std::cell::RefCell;
+
+    /// Records every set_value call instead of talking to hardware
+    #[derive(Default)]
+    struct MockHandle {
+        writes: RefCell<Vec<(String, String)>>,
+    }
+
+    impl ParamSetter for MockHandle {
+        fn set_value(&self, path: &str, value: &str) -> Result<(), CaenError> {
+            self.writes
+                .borrow_mut()
+                .push((path.to_string(), value.to_string()));
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_psd2_external_trigger_sets_global_source() {
+        let handle = MockHandle::default();
+        apply_trigger_mode(&handle, FirmwareType::PSD2, TriggerMode::External, 32).unwrap();
+
+        assert_eq!(
+            *handle.writes.borrow(),
+            vec![("/par/globaltriggersource".to_string(), "TrgIn".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_psd1_self_trigger_sets_enable_flags() {
+        let handle = MockHandle::default();
+        apply_trigger_mode(&handle, FirmwareType::PSD1, TriggerMode::SelfTrigger, 16).unwrap();
+
+        let writes = handle.writes.borrow();
+        assert_eq!(writes.len(), 3);
+        assert_eq!(
+            writes[0],
+            (
+                "/ch/0..15/par/ch_self_trg_enable".to_string(),
+                "TRUE".to_string()
+            )
+        );
+        assert_eq!(
+            writes[1],
+            ("/par/trg_ext_enable".to_string(), "FALSE".to_string())
+        );
+    }
+
+    #[test]
+    fn test_unsupported_firmware_is_rejected() {
+        let handle = MockHandle::default();
+        let result = apply_trigger_mode(&handle, FirmwareType::PHA, TriggerMode::Software, 16);
+
+        assert!(result.is_err());
+        assert!(handle.writes.borrow().is_empty());
+    }
+}
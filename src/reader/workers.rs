@@ -0,0 +1,145 @@
+//! Parallel decoding for `decode_workers > 1`
+//!
+//! The DecodeLoop still receives, records and classifies every raw buffer,
+//! but hands event buffers to N worker threads, each with its own decoder.
+//! Decoding is CPU-bound, so workers run on dedicated threads rather than
+//! tokio tasks, which would hold up the heartbeat and command tasks.
+//! Buffers are dealt out round-robin and results are collected in the same
+//! round-robin order, so batches come back in read order and the DecodeLoop
+//! assigns sequence numbers exactly as a single decoder would. Decoders only
+//! keep diagnostic state between buffers (aggregate counters), so splitting
+//! the stream across instances does not change the decoded events.
+//!
+//! A panicking worker is re-raised in the DecodeLoop the next time it talks
+//! to that worker, so the DecodeLoop's supervisor puts the Reader into Error
+//! just as for a panic in a single decoder.
+
+use std::panic::{catch_unwind, resume_unwind, AssertUnwindSafe};
+use std::sync::Arc;
+
+use tokio::sync::{mpsc, oneshot};
+
+use super::affinity;
+use super::decoder::RawData;
+use super::pool::{BufferPool, DecodeBuffers};
+use super::{Reader, ReaderConfig, ReaderError};
+use crate::common::EventDataBatch;
+
+/// Buffers in flight per worker (bounds memory and keeps submit non-blocking)
+const IN_FLIGHT_PER_WORKER: usize = 2;
+
+/// Ordered fan-out of event buffers to decode worker threads
+pub(crate) struct DecodeWorkers {
+    inputs: Vec<mpsc::Sender<RawData>>,
+    outputs: Vec<mpsc::Receiver<Option<EventDataBatch>>>,
+    /// Outcome of each worker thread (`Err` holds its panic payload)
+    handles: Vec<oneshot::Receiver<std::thread::Result<()>>>,
+    next_in: usize,
+    next_out: usize,
+    in_flight: usize,
+}
+
+impl DecodeWorkers {
+    /// Start `workers` decode threads for `config`'s firmware
+    ///
+    /// With `decode_core` set, worker `i` is pinned to `decode_core + 1 + i`,
+    /// next to the DecodeLoop. Workers return raw buffers to `raw_pool` and
+    /// stop when this is dropped.
+    pub(crate) fn spawn(
+        config: &ReaderConfig,
+        workers: usize,
+        raw_pool: &Arc<BufferPool<Vec<u8>>>,
+    ) -> Result<Self, ReaderError> {
+        let mut inputs = Vec::with_capacity(workers);
+        let mut outputs = Vec::with_capacity(workers);
+        let mut handles = Vec::with_capacity(workers);
+        for i in 0..workers {
+            let mut decoder = config.create_decoder().ok_or_else(|| {
+                ReaderError::Config(format!("No decoder registered for {:?}", config.firmware))
+            })?;
+            let (input_tx, mut input_rx) = mpsc::channel::<RawData>(IN_FLIGHT_PER_WORKER);
+            let (output_tx, output_rx) = mpsc::channel(IN_FLIGHT_PER_WORKER);
+            let raw_pool = raw_pool.clone();
+            let source_id = config.source_id;
+            let module_map = config.module_map.clone();
+            let core = config.decode_core.map(|core| core + 1 + i);
+
+            let worker = move || {
+                // Event vectors are not recycled across threads; each batch allocates its own
+                let mut buffers = DecodeBuffers::default();
+                while let Some(raw) = input_rx.blocking_recv() {
+                    // The DecodeLoop assigns the real sequence number when publishing
+                    let batch = Reader::decode_batch_into(
+                        decoder.as_mut(),
+                        &raw,
+                        source_id,
+                        0,
+                        &module_map,
+                        &mut buffers,
+                    );
+                    raw_pool.give(raw.data);
+                    if output_tx.blocking_send(batch).is_err() {
+                        break;
+                    }
+                }
+            };
+            // Catch the panic here so its payload reaches the DecodeLoop
+            let handle = affinity::spawn_dedicated(&format!("DecodeWorker-{}", i), core, || {
+                catch_unwind(AssertUnwindSafe(worker))
+            });
+            inputs.push(input_tx);
+            outputs.push(output_rx);
+            handles.push(handle);
+        }
+
+        Ok(Self {
+            inputs,
+            outputs,
+            handles,
+            next_in: 0,
+            next_out: 0,
+            in_flight: 0,
+        })
+    }
+
+    /// Most buffers that may be submitted and not yet collected
+    pub(crate) fn max_in_flight(&self) -> usize {
+        self.inputs.len() * IN_FLIGHT_PER_WORKER
+    }
+
+    /// Buffers submitted and not yet collected
+    pub(crate) fn in_flight(&self) -> usize {
+        self.in_flight
+    }
+
+    /// Hand an event buffer to the next worker
+    ///
+    /// Never waits as long as fewer than `max_in_flight` buffers are in flight.
+    pub(crate) async fn submit(&mut self, raw: RawData) -> Result<(), ReaderError> {
+        if self.inputs[self.next_in].send(raw).await.is_err() {
+            return Err(self.stopped(self.next_in, ReaderError::ChannelSend).await);
+        }
+        self.next_in = (self.next_in + 1) % self.inputs.len();
+        self.in_flight += 1;
+        Ok(())
+    }
+
+    /// Decoded result of the oldest submitted buffer (None if it held no events)
+    pub(crate) async fn next(&mut self) -> Result<Option<EventDataBatch>, ReaderError> {
+        let Some(batch) = self.outputs[self.next_out].recv().await else {
+            let error = ReaderError::Decode("decode worker stopped".to_string());
+            return Err(self.stopped(self.next_out, error).await);
+        };
+        self.next_out = (self.next_out + 1) % self.outputs.len();
+        self.in_flight -= 1;
+        Ok(batch)
+    }
+
+    /// `error` for worker `i` having stopped; re-raises the worker's panic
+    async fn stopped(&mut self, i: usize, error: ReaderError) -> ReaderError {
+        if let Ok(Err(payload)) = (&mut self.handles[i]).await {
+            resume_unwind(payload);
+        }
+        error
+    }
+}
@@ -0,0 +1,122 @@
+//! Optional on-the-fly compression of recorder output files
+//!
+//! The whole file (header, data blocks, footer) is passed through the
+//! encoder, so a decompressed file is byte-identical to an uncompressed
+//! recording and can be read with [`DataFileReader`](super::DataFileReader).
+
+use std::fs::File;
+use std::io::{self, BufWriter, Read, Write};
+use std::path::Path;
+
+use flate2::write::GzEncoder;
+use serde::{Deserialize, Serialize};
+
+/// Compression applied to recorder output files
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CompressionKind {
+    /// Plain `.delila` files
+    #[default]
+    None,
+    /// gzip (`.delila.gz`)
+    Gzip,
+    /// Zstandard (`.delila.zst`)
+    Zstd,
+}
+
+/// zstd level used for recording: fast enough to keep up with the DAQ
+const ZSTD_LEVEL: i32 = 3;
+
+impl CompressionKind {
+    /// Suffix appended after the `.delila` extension
+    pub fn suffix(&self) -> &'static str {
+        match self {
+            CompressionKind::None => "",
+            CompressionKind::Gzip => ".gz",
+            CompressionKind::Zstd => ".zst",
+        }
+    }
+
+    /// Detect the compression of a file from its name
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("gz") => CompressionKind::Gzip,
+            Some("zst") => CompressionKind::Zstd,
+            _ => CompressionKind::None,
+        }
+    }
+
+    /// Wrap a reader with the matching decoder
+    pub fn decoder<'a, R: Read + Send + 'a>(
+        &self,
+        reader: R,
+    ) -> io::Result<Box<dyn Read + Send + 'a>> {
+        Ok(match self {
+            CompressionKind::None => Box::new(reader),
+            CompressionKind::Gzip => Box::new(flate2::read::GzDecoder::new(reader)),
+            CompressionKind::Zstd => Box::new(zstd::stream::read::Decoder::new(reader)?),
+        })
+    }
+}
+
+/// Output file stream, optionally compressed
+pub(crate) enum OutputStream {
+    Plain(BufWriter<File>),
+    Gzip(GzEncoder<BufWriter<File>>),
+    Zstd(zstd::stream::write::Encoder<'static, BufWriter<File>>),
+}
+
+impl OutputStream {
+    /// Wrap a buffered file with the encoder for `kind`
+    pub(crate) fn new(kind: CompressionKind, file: BufWriter<File>) -> io::Result<Self> {
+        Ok(match kind {
+            CompressionKind::None => OutputStream::Plain(file),
+            CompressionKind::Gzip => {
+                OutputStream::Gzip(GzEncoder::new(file, flate2::Compression::default()))
+            }
+            CompressionKind::Zstd => {
+                OutputStream::Zstd(zstd::stream::write::Encoder::new(file, ZSTD_LEVEL)?)
+            }
+        })
+    }
+
+    /// Write the compression trailer, flush and fsync the file
+    pub(crate) fn finish(self) -> io::Result<()> {
+        let mut file = match self {
+            OutputStream::Plain(file) => file,
+            OutputStream::Gzip(encoder) => encoder.finish()?,
+            OutputStream::Zstd(encoder) => encoder.finish()?,
+        };
+        file.flush()?;
+        file.get_ref().sync_data()
+    }
+
+    /// Flush buffered data (ending the current compressed block) and fsync
+    pub(crate) fn sync(&mut self) -> io::Result<()> {
+        self.flush()?;
+        let file = match self {
+            OutputStream::Plain(w) => w.get_ref(),
+            OutputStream::Gzip(w) => w.get_ref().get_ref(),
+            OutputStream::Zstd(w) => w.get_ref().get_ref(),
+        };
+        file.sync_data()
+    }
+}
+
+impl Write for OutputStream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            OutputStream::Plain(w) => w.write(buf),
+            OutputStream::Gzip(w) => w.write(buf),
+            OutputStream::Zstd(w) => w.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            OutputStream::Plain(w) => w.flush(),
+            OutputStream::Gzip(w) => w.flush(),
+            OutputStream::Zstd(w) => w.flush(),
+        }
+    }
+}
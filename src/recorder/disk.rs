@@ -0,0 +1,76 @@
+//! Free disk space monitoring
+//!
+//! Before each new output file is opened the Recorder checks the free space
+//! of `output_dir` against `min_free_bytes`. Refusing to start a file is much
+//! easier to recover from than a write failing half way through a block.
+
+use std::io;
+use std::path::Path;
+
+use super::RecorderError;
+
+/// Free-space query, replaceable in tests
+pub(super) type FreeSpaceFn = fn(&Path) -> io::Result<u64>;
+
+/// Bytes available to unprivileged users on the filesystem holding `path`
+#[cfg(unix)]
+pub fn free_space(path: &Path) -> io::Result<u64> {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+
+    let c_path = CString::new(path.as_os_str().as_bytes())
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    // SAFETY: c_path is a valid NUL-terminated string and stat is a valid out-pointer
+    if unsafe { libc::statvfs(c_path.as_ptr(), &mut stat) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    // Field widths differ between platforms, hence the casts
+    Ok(stat.f_bavail as u64 * stat.f_frsize as u64)
+}
+
+/// Bytes available on the filesystem holding `path` (unsupported platform)
+#[cfg(not(unix))]
+pub fn free_space(_path: &Path) -> io::Result<u64> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "free space query is only implemented on unix",
+    ))
+}
+
+/// Fail with `DiskFull` if `free` is below `min_free_bytes` (0 disables the check)
+pub fn check_free_space(free: u64, min_free_bytes: u64) -> Result<(), RecorderError> {
+    if free < min_free_bytes {
+        return Err(RecorderError::DiskFull {
+            free,
+            required: min_free_bytes,
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_free_space_threshold() {
+        assert!(check_free_space(10_000, 4_096).is_ok());
+        assert!(check_free_space(4_096, 4_096).is_ok());
+        assert!(matches!(
+            check_free_space(4_095, 4_096),
+            Err(RecorderError::DiskFull {
+                free: 4_095,
+                required: 4_096
+            })
+        ));
+        // Threshold 0 never fails
+        assert!(check_free_space(0, 0).is_ok());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_free_space_of_temp_dir() {
+        assert!(free_space(&std::env::temp_dir()).unwrap() > 0);
+    }
+}
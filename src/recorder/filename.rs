@@ -0,0 +1,173 @@
+//! Output filename templates
+//!
+//! `filename_template` sets the file stem; the extension follows from the
+//! format. Tokens:
+//!
+//! | Token    | Value                                          |
+//! |----------|------------------------------------------------|
+//! | `{run}`  | run number, 4 digits (`0012`)                  |
+//! | `{seq}`  | file sequence within the run, 4 digits         |
+//! | `{exp}`  | experiment name (plus `_stream` with routing)  |
+//! | `{date}` | local date the file was opened (`YYYYMMDD`)    |
+//! | `{host}` | host name of the Recorder                      |
+//!
+//! Templates are checked when the Recorder is created: unknown tokens,
+//! unbalanced braces and characters that are not allowed in file names
+//! are rejected, and so are templates without `{run}` and `{seq}`, whose
+//! files would collide across runs or rotations.
+
+/// Stem of the default `run{XXXX}_{YYYY}_{ExpName}` filenames
+pub const DEFAULT_FILENAME_TEMPLATE: &str = "run{run}_{seq}_{exp}";
+
+/// Characters rejected in the literal part of a template
+const INVALID_CHARS: &[char] = &['/', '\\', ':', '*', '?', '"', '<', '>', '|'];
+
+/// Values substituted for the template tokens
+#[derive(Debug, Clone)]
+pub struct FilenameFields<'a> {
+    pub run: u32,
+    pub seq: u32,
+    pub exp: &'a str,
+    pub date: &'a str,
+    pub host: &'a str,
+}
+
+/// Template pieces: literal text or a token name
+enum Piece<'a> {
+    Text(&'a str),
+    Token(&'a str),
+}
+
+fn parse(template: &str) -> Result<Vec<Piece<'_>>, String> {
+    let mut pieces = Vec::new();
+    let mut rest = template;
+    while let Some(open) = rest.find(['{', '}']) {
+        if rest[open..].starts_with('}') {
+            return Err(format!("unbalanced '}}' in filename template {template:?}"));
+        }
+        let close = rest[open..]
+            .find('}')
+            .ok_or_else(|| format!("unclosed '{{' in filename template {template:?}"))?;
+        pieces.push(Piece::Text(&rest[..open]));
+        pieces.push(Piece::Token(&rest[open + 1..open + close]));
+        rest = &rest[open + close + 1..];
+    }
+    pieces.push(Piece::Text(rest));
+    Ok(pieces)
+}
+
+/// Check that `template` has `{run}` and `{seq}`, only uses known tokens and
+/// valid file name characters
+pub fn check_filename_template(template: &str) -> Result<(), String> {
+    let pieces = parse(template)?;
+    if pieces
+        .iter()
+        .all(|piece| matches!(piece, Piece::Text(text) if text.is_empty()))
+    {
+        return Err("filename template is empty".to_string());
+    }
+    for required in ["run", "seq"] {
+        if !pieces
+            .iter()
+            .any(|piece| matches!(piece, Piece::Token(token) if *token == required))
+        {
+            return Err(format!(
+                "filename template {template:?} lacks {{{required}}}, files would collide"
+            ));
+        }
+    }
+    for piece in pieces {
+        match piece {
+            Piece::Token("run" | "seq" | "exp" | "date" | "host") => {}
+            Piece::Token(token) => {
+                return Err(format!("unknown filename template token {{{token}}}"));
+            }
+            Piece::Text(text) => {
+                if let Some(c) = text
+                    .chars()
+                    .find(|c| INVALID_CHARS.contains(c) || c.is_control())
+                {
+                    return Err(format!(
+                        "filename template {template:?} contains invalid character {c:?}"
+                    ));
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// File stem for `fields` (the template must have passed
+/// [`check_filename_template`]; unknown tokens are left as they are)
+pub fn render_filename(template: &str, fields: &FilenameFields) -> String {
+    let Ok(pieces) = parse(template) else {
+        return template.to_string();
+    };
+    let mut stem = String::new();
+    for piece in pieces {
+        match piece {
+            Piece::Text(text) => stem.push_str(text),
+            Piece::Token("run") => stem.push_str(&format!("{:04}", fields.run)),
+            Piece::Token("seq") => stem.push_str(&format!("{:04}", fields.seq)),
+            Piece::Token("exp") => stem.push_str(fields.exp),
+            Piece::Token("date") => stem.push_str(fields.date),
+            Piece::Token("host") => stem.push_str(fields.host),
+            Piece::Token(token) => stem.push_str(&format!("{{{token}}}")),
+        }
+    }
+    stem
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fields() -> FilenameFields<'static> {
+        FilenameFields {
+            run: 12,
+            seq: 3,
+            exp: "CRIB",
+            date: "20261018",
+            host: "daq01",
+        }
+    }
+
+    #[test]
+    fn test_default_template_matches_legacy_names() {
+        assert!(check_filename_template(DEFAULT_FILENAME_TEMPLATE).is_ok());
+        assert_eq!(
+            render_filename(DEFAULT_FILENAME_TEMPLATE, &fields()),
+            "run0012_0003_CRIB"
+        );
+    }
+
+    #[test]
+    fn test_custom_template_with_date_and_host() {
+        let template = "{exp}_{date}_{host}_r{run}-{seq}";
+        assert!(check_filename_template(template).is_ok());
+        assert_eq!(
+            render_filename(template, &fields()),
+            "CRIB_20261018_daq01_r0012-0003"
+        );
+    }
+
+    #[test]
+    fn test_invalid_templates_rejected() {
+        for template in [
+            "",
+            "run{run}/{seq}",
+            "run{run}:{seq}",
+            "run{number}",
+            "run{run",
+            "run}{seq}",
+            "{exp}_{date}",
+            "run{run}_{exp}",
+            "{seq}_{exp}",
+        ] {
+            assert!(
+                check_filename_template(template).is_err(),
+                "{template:?} should be rejected"
+            );
+        }
+    }
+}
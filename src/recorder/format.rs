@@ -7,11 +7,14 @@
 //! │  - Magic, Version, Metadata             │
 //! ├─────────────────────────────────────────┤
 //! │  Data Block 1                           │
+//! │  - CRC32 of batch (u32 LE, version 3)   │
 //! │  - Length prefix (u32 LE)               │
 //! │  - MsgPack serialized batch             │
 //! ├─────────────────────────────────────────┤
 //! │  ...                                    │
 //! ├─────────────────────────────────────────┤
+//! │  Batch index (optional, see `index`)    │
+//! ├─────────────────────────────────────────┤
 //! │  Footer (fixed 64 bytes)                │
 //! │  - Magic, checksums, completion flag    │
 //! └─────────────────────────────────────────┘
@@ -22,12 +25,18 @@ use std::collections::HashMap;
 use std::io::{Read, Write};
 use xxhash_rust::xxh64::xxh64;
 
+use super::index::FileIndex;
+use crate::common::{EventDataBatch, MESSAGE_WIRE_VERSION};
+
 /// Magic bytes for DELILA data files
 pub const FILE_MAGIC: [u8; 8] = *b"DELILA02";
 
 /// Current file format version
 pub const FORMAT_VERSION: u32 = 2;
 
+/// Format version for files whose data blocks carry a CRC32 prefix
+pub const FORMAT_VERSION_CRC: u32 = 3;
+
 /// Footer magic bytes (different from header to detect truncation)
 pub const FOOTER_MAGIC: [u8; 8] = *b"DLEND002";
 
@@ -66,6 +75,15 @@ pub struct FileHeader {
 
     /// Additional key-value metadata
     pub metadata: HashMap<String, String>,
+
+    /// Layout of the serialized batches (see [`EventDataBatch::from_msgpack_version`])
+    #[serde(default = "default_batch_version")]
+    pub batch_version: u8,
+}
+
+/// Files written before `batch_version` existed use batch layout 1
+fn default_batch_version() -> u8 {
+    1
 }
 
 impl FileHeader {
@@ -85,6 +103,7 @@ impl FileHeader {
             is_sorted: false,
             source_ids: Vec::new(),
             metadata: HashMap::new(),
+            batch_version: MESSAGE_WIRE_VERSION,
         }
     }
 
@@ -120,6 +139,11 @@ impl FileHeader {
         rmp_serde::from_slice(&data[12..12 + len]).map_err(FileFormatError::Deserialization)
     }
 
+    /// Check if data blocks carry a per-frame CRC32
+    pub fn has_frame_crc(&self) -> bool {
+        self.version >= FORMAT_VERSION_CRC
+    }
+
     /// Write header to a writer
     pub fn write_to<W: Write>(&self, writer: &mut W) -> Result<usize, FileFormatError> {
         let bytes = self.to_bytes()?;
@@ -396,6 +420,19 @@ pub enum FileFormatError {
     IncompleteFile,
 }
 
+/// A data block whose stored CRC32 does not match its payload
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FrameCrcMismatch {
+    /// Zero-based block index
+    pub frame: usize,
+    /// Byte offset of the block in the file
+    pub offset: u64,
+    /// CRC stored in the file
+    pub stored: u32,
+    /// CRC computed from the payload
+    pub computed: u32,
+}
+
 /// Result of file validation
 #[derive(Debug)]
 pub struct FileValidationResult {
@@ -427,6 +464,12 @@ pub struct DataFileReader<R> {
     footer: Option<FileFooter>,
     header_size: usize,
     file_size: u64,
+    /// End of the data block region (start of index or footer)
+    data_end: u64,
+    /// Data blocks carry a CRC32 prefix (format version 3)
+    frame_crc: bool,
+    /// Batch layout from the header
+    batch_version: u8,
 }
 
 impl<R: std::io::Read + std::io::Seek> DataFileReader<R> {
@@ -434,6 +477,13 @@ impl<R: std::io::Read + std::io::Seek> DataFileReader<R> {
     pub fn new(mut reader: R) -> Result<Self, FileFormatError> {
         // Get file size
         let file_size = reader.seek(std::io::SeekFrom::End(0))?;
+
+        // Data region ends at the index (if present) or file_size - FOOTER_SIZE
+        let data_end = match super::index::locate(&mut reader, file_size)? {
+            Some((index_start, _)) => index_start,
+            None if file_size >= FOOTER_SIZE as u64 => file_size - FOOTER_SIZE as u64,
+            None => file_size,
+        };
         reader.seek(std::io::SeekFrom::Start(0))?;
 
         let mut this = Self {
@@ -442,6 +492,9 @@ impl<R: std::io::Read + std::io::Seek> DataFileReader<R> {
             footer: None,
             header_size: 0,
             file_size,
+            data_end,
+            frame_crc: false,
+            batch_version: MESSAGE_WIRE_VERSION,
         };
 
         // Try to read header
@@ -450,6 +503,31 @@ impl<R: std::io::Read + std::io::Seek> DataFileReader<R> {
         Ok(this)
     }
 
+    /// Open a forward-only stream whose size and data region are already known
+    ///
+    /// For decompressing input (see [`super::replay_file`]): the header is
+    /// read at position 0 and the stream may only move forward afterwards, so
+    /// [`read_footer`](Self::read_footer) and [`read_index`](Self::read_index)
+    /// are not available.
+    pub(crate) fn with_layout(
+        reader: R,
+        file_size: u64,
+        data_end: u64,
+    ) -> Result<Self, FileFormatError> {
+        let mut this = Self {
+            reader,
+            header: None,
+            footer: None,
+            header_size: 0,
+            file_size,
+            data_end,
+            frame_crc: false,
+            batch_version: MESSAGE_WIRE_VERSION,
+        };
+        this.read_header()?;
+        Ok(this)
+    }
+
     /// Read and validate the file header
     fn read_header(&mut self) -> Result<(), FileFormatError> {
         self.reader.seek(std::io::SeekFrom::Start(0))?;
@@ -458,6 +536,8 @@ impl<R: std::io::Read + std::io::Seek> DataFileReader<R> {
         // Calculate header size (magic + length prefix + msgpack data)
         let pos = self.reader.stream_position()?;
         self.header_size = pos as usize;
+        self.frame_crc = header.has_frame_crc();
+        self.batch_version = header.batch_version;
         self.header = Some(header);
         Ok(())
     }
@@ -559,12 +639,7 @@ impl<R: std::io::Read + std::io::Seek> DataFileReader<R> {
             return (0, 0);
         }
 
-        // Data region ends at file_size - FOOTER_SIZE (if footer might exist)
-        let data_end = if self.file_size >= FOOTER_SIZE as u64 {
-            self.file_size - FOOTER_SIZE as u64
-        } else {
-            self.file_size
-        };
+        let data_end = self.data_end;
 
         #[allow(clippy::while_let_loop)]
         loop {
@@ -577,6 +652,12 @@ impl<R: std::io::Read + std::io::Seek> DataFileReader<R> {
                 break;
             }
 
+            // Skip CRC prefix (verified separately by verify_frame_crcs)
+            let mut crc_bytes = [0u8; 4];
+            if self.frame_crc && self.reader.read_exact(&mut crc_bytes).is_err() {
+                break;
+            }
+
             // Try to read length prefix
             let mut len_bytes = [0u8; 4];
             if self.reader.read_exact(&mut len_bytes).is_err() {
@@ -592,7 +673,7 @@ impl<R: std::io::Read + std::io::Seek> DataFileReader<R> {
             }
 
             // Check if we have enough data
-            if pos + 4 + len as u64 > data_end {
+            if pos + self.frame_prefix_len() + len as u64 > data_end {
                 break;
             }
 
@@ -603,7 +684,7 @@ impl<R: std::io::Read + std::io::Seek> DataFileReader<R> {
             }
 
             // Try to deserialize to count events
-            match crate::common::EventDataBatch::from_msgpack(&data) {
+            match EventDataBatch::from_msgpack_version(&data, self.batch_version) {
                 Ok(batch) => {
                     events += batch.events.len() as u64;
                     blocks += 1;
@@ -629,7 +710,7 @@ impl<R: std::io::Read + std::io::Seek> DataFileReader<R> {
         let mut calc = ChecksumCalculator::new();
 
         // Read all data blocks and update checksum
-        let data_end = self.file_size - FOOTER_SIZE as u64;
+        let data_end = self.data_end;
 
         loop {
             let pos = self.reader.stream_position()?;
@@ -637,6 +718,12 @@ impl<R: std::io::Read + std::io::Seek> DataFileReader<R> {
                 break;
             }
 
+            // Read CRC prefix (part of the block for the file checksum)
+            let mut crc_bytes = [0u8; 4];
+            if self.frame_crc && self.reader.read_exact(&mut crc_bytes).is_err() {
+                break;
+            }
+
             // Read length prefix
             let mut len_bytes = [0u8; 4];
             if self.reader.read_exact(&mut len_bytes).is_err() {
@@ -655,6 +742,9 @@ impl<R: std::io::Read + std::io::Seek> DataFileReader<R> {
             }
 
             // Update checksum
+            if self.frame_crc {
+                calc.update(&crc_bytes);
+            }
             calc.update(&len_bytes);
             calc.update(&data);
         }
@@ -663,36 +753,107 @@ impl<R: std::io::Read + std::io::Seek> DataFileReader<R> {
         Ok(computed == footer.data_checksum)
     }
 
+    /// Check the CRC32 of every data block
+    ///
+    /// Returns the blocks whose stored CRC does not match the payload. Files
+    /// without per-frame CRCs (format version 2) always return an empty list.
+    /// Reading stops at the first truncated block.
+    pub fn verify_frame_crcs(&mut self) -> Result<Vec<FrameCrcMismatch>, FileFormatError> {
+        let mut mismatches = Vec::new();
+        if !self.frame_crc {
+            return Ok(mismatches);
+        }
+
+        self.reader
+            .seek(std::io::SeekFrom::Start(self.header_size as u64))?;
+
+        let mut frame = 0;
+        loop {
+            let offset = self.reader.stream_position()?;
+            if offset + self.frame_prefix_len() > self.data_end {
+                break;
+            }
+
+            let mut prefix = [0u8; 8];
+            self.reader.read_exact(&mut prefix)?;
+            let stored = u32::from_le_bytes([prefix[0], prefix[1], prefix[2], prefix[3]]);
+            let len = u32::from_le_bytes([prefix[4], prefix[5], prefix[6], prefix[7]]) as u64;
+            if len == 0 || offset + 8 + len > self.data_end {
+                break;
+            }
+
+            let mut data = vec![0u8; len as usize];
+            self.reader.read_exact(&mut data)?;
+            let computed = crc32fast::hash(&data);
+            if computed != stored {
+                mismatches.push(FrameCrcMismatch {
+                    frame,
+                    offset,
+                    stored,
+                    computed,
+                });
+            }
+            frame += 1;
+        }
+
+        Ok(mismatches)
+    }
+
+    /// Bytes in front of each block's payload
+    fn frame_prefix_len(&self) -> u64 {
+        if self.frame_crc {
+            8
+        } else {
+            4
+        }
+    }
+
     /// Iterator over data blocks (for recovery)
     pub fn data_blocks(&mut self) -> DataBlockIterator<'_, R> {
         // Position after header
-        let _ = self
-            .reader
-            .seek(std::io::SeekFrom::Start(self.header_size as u64));
+        self.data_blocks_from(0)
+    }
 
-        let data_end = if self.file_size >= FOOTER_SIZE as u64 {
-            self.file_size - FOOTER_SIZE as u64
-        } else {
-            self.file_size
-        };
+    /// Iterator over data blocks starting at byte `offset`
+    ///
+    /// `offset` must be the start of a block, e.g. from
+    /// [`FileIndex::seek_offset`]; offsets within the header start at the
+    /// first block.
+    pub fn data_blocks_from(&mut self, offset: u64) -> DataBlockIterator<'_, R> {
+        let _ = self.reader.seek(std::io::SeekFrom::Start(
+            offset.max(self.header_size as u64),
+        ));
+        self.resume_data_blocks()
+    }
 
+    /// Iterator over the blocks following the last one read
+    pub(crate) fn resume_data_blocks(&mut self) -> DataBlockIterator<'_, R> {
         DataBlockIterator {
             reader: &mut self.reader,
-            data_end,
+            data_end: self.data_end,
+            frame_crc: self.frame_crc,
+            batch_version: self.batch_version,
             done: false,
         }
     }
+
+    /// Read the batch index, `None` if the file has none
+    pub fn read_index(&mut self) -> Result<Option<FileIndex>, FileFormatError> {
+        FileIndex::read_from(&mut self.reader)
+    }
 }
 
 /// Iterator over data blocks in a file
 pub struct DataBlockIterator<'a, R> {
     reader: &'a mut R,
     data_end: u64,
+    frame_crc: bool,
+    batch_version: u8,
     done: bool,
 }
 
 impl<'a, R: std::io::Read + std::io::Seek> Iterator for DataBlockIterator<'a, R> {
-    type Item = Result<crate::common::EventDataBatch, FileFormatError>;
+    type Item = Result<EventDataBatch, FileFormatError>;
 
     fn next(&mut self) -> Option<Self::Item> {
         if self.done {
@@ -712,9 +873,10 @@ impl<'a, R: std::io::Read + std::io::Seek> Iterator for DataBlockIterator<'a, R>
             return None;
         }
 
-        // Read length prefix
-        let mut len_bytes = [0u8; 4];
-        if let Err(e) = self.reader.read_exact(&mut len_bytes) {
+        // Read CRC (if present) and length prefix
+        let prefix_len = if self.frame_crc { 8 } else { 4 };
+        let mut prefix = [0u8; 8];
+        if let Err(e) = self.reader.read_exact(&mut prefix[..prefix_len]) {
             self.done = true;
             if e.kind() == std::io::ErrorKind::UnexpectedEof {
                 return None;
@@ -722,14 +884,16 @@ impl<'a, R: std::io::Read + std::io::Seek> Iterator for DataBlockIterator<'a, R>
             return Some(Err(FileFormatError::Io(e)));
         }
 
-        let len = u32::from_le_bytes(len_bytes) as usize;
+        let len_bytes = &prefix[prefix_len - 4..prefix_len];
+        let len =
+            u32::from_le_bytes([len_bytes[0], len_bytes[1], len_bytes[2], len_bytes[3]]) as usize;
         if len == 0 || len > 100_000_000 {
             self.done = true;
             return None;
         }
 
         // Check bounds
-        if pos + 4 + len as u64 > self.data_end {
+        if pos + prefix_len as u64 + len as u64 > self.data_end {
             self.done = true;
             return None;
         }
@@ -742,7 +906,7 @@ impl<'a, R: std::io::Read + std::io::Seek> Iterator for DataBlockIterator<'a, R>
         }
 
         // Deserialize
-        match crate::common::EventDataBatch::from_msgpack(&data) {
+        match EventDataBatch::from_msgpack_version(&data, self.batch_version) {
             Ok(batch) => Some(Ok(batch)),
             Err(e) => {
                 self.done = true;
@@ -769,6 +933,7 @@ mod tests {
         let restored = FileHeader::from_bytes(&bytes).unwrap();
 
         assert_eq!(restored.version, FORMAT_VERSION);
+        assert_eq!(restored.batch_version, MESSAGE_WIRE_VERSION);
         assert_eq!(restored.run_number, 42);
         assert_eq!(restored.exp_name, "CRIB2026");
         assert_eq!(restored.file_sequence, 5);
@@ -929,6 +1094,52 @@ mod tests {
         assert_eq!(restored.file_sequence, 7);
     }
 
+    #[test]
+    fn test_file_without_batch_version_reads_v1_batches() {
+        // Batch layout 1: epoch after the events
+        #[derive(Serialize)]
+        struct V1Batch {
+            source_id: u32,
+            sequence_number: u64,
+            timestamp: u64,
+            events: Vec<crate::common::EventData>,
+            fragment: Option<crate::common::BatchFragment>,
+            epoch: u32,
+        }
+
+        // Header as written before `batch_version`: drop the last array element
+        let mut header_bytes =
+            rmp_serde::to_vec(&FileHeader::new(1, "old".to_string(), 0)).unwrap();
+        assert_eq!(header_bytes[0], 0x9b);
+        header_bytes[0] = 0x9a;
+        header_bytes.pop();
+
+        let batch = rmp_serde::to_vec(&V1Batch {
+            source_id: 3,
+            sequence_number: 8,
+            timestamp: 1,
+            events: vec![crate::common::EventData::new(0, 1, 100, 80, 1000.0, 0)],
+            fragment: None,
+            epoch: 6,
+        })
+        .unwrap();
+
+        let mut file = FILE_MAGIC.to_vec();
+        file.extend_from_slice(&(header_bytes.len() as u32).to_le_bytes());
+        file.extend_from_slice(&header_bytes);
+        file.extend_from_slice(&(batch.len() as u32).to_le_bytes());
+        file.extend_from_slice(&batch);
+        file.extend_from_slice(&FileFooter::new().to_bytes());
+
+        let mut reader = DataFileReader::new(std::io::Cursor::new(file)).unwrap();
+        assert_eq!(reader.header().unwrap().batch_version, 1);
+        let batches: Vec<_> = reader.data_blocks().collect::<Result<_, _>>().unwrap();
+        assert_eq!(batches.len(), 1);
+        assert_eq!(batches[0].sequence_number, 8);
+        assert_eq!(batches[0].epoch, 6);
+        assert_eq!(batches[0].events.len(), 1);
+    }
+
     #[test]
     fn test_footer_write_read() {
         let mut footer = FileFooter::new();
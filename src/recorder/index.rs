@@ -0,0 +1,125 @@
+//! Batch offset index appended to recorder files on close
+//!
+//! Layout between the last data block and the fixed footer:
+//! ```text
+//! ┌─────────────────────────────────────────┐
+//! │  Index blob (MsgPack [(first_ts, off)]) │
+//! │  Index length (u64 LE)                  │
+//! │  Index magic "DLIDX001"                 │
+//! ├─────────────────────────────────────────┤
+//! │  Footer (fixed 64 bytes)                │
+//! └─────────────────────────────────────────┘
+//! ```
+//!
+//! Offsets are absolute positions of a data block's length prefix in the
+//! (uncompressed) file. Files written before the index existed, or files that
+//! were never closed, simply have no index.
+
+use std::fs::File;
+use std::io::{BufReader, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use super::{FileFormatError, FOOTER_SIZE};
+
+/// Magic marker following the index length
+pub const INDEX_MAGIC: [u8; 8] = *b"DLIDX001";
+
+/// Size of the index trailer (length + magic)
+pub const INDEX_TRAILER_SIZE: usize = 16;
+
+/// Index of data blocks in a file: `(first_timestamp_ns, byte_offset)` per batch
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FileIndex {
+    pub entries: Vec<(u64, u64)>,
+}
+
+impl FileIndex {
+    /// Offset of the block to start reading from to reach `timestamp_ns`
+    ///
+    /// Returns the last block whose first timestamp is not after
+    /// `timestamp_ns` (or the first block if all start later).
+    pub fn seek_offset(&self, timestamp_ns: u64) -> Option<u64> {
+        let idx = self
+            .entries
+            .partition_point(|&(ts, _)| ts <= timestamp_ns)
+            .saturating_sub(1);
+        self.entries.get(idx).map(|&(_, offset)| offset)
+    }
+
+    /// Write the index blob and trailer; returns the number of bytes written
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> Result<u64, FileFormatError> {
+        let blob = rmp_serde::to_vec(self)?;
+        writer.write_all(&blob)?;
+        writer.write_all(&(blob.len() as u64).to_le_bytes())?;
+        writer.write_all(&INDEX_MAGIC)?;
+        Ok((blob.len() + INDEX_TRAILER_SIZE) as u64)
+    }
+
+    /// Read the index from a complete file, `None` if the file has no index
+    pub fn read_from<R: Read + Seek>(reader: &mut R) -> Result<Option<Self>, FileFormatError> {
+        let file_size = reader.seek(SeekFrom::End(0))?;
+        let Some((start, len)) = locate(reader, file_size)? else {
+            return Ok(None);
+        };
+        reader.seek(SeekFrom::Start(start))?;
+        let mut blob = vec![0u8; len as usize];
+        reader.read_exact(&mut blob)?;
+        Ok(Some(rmp_serde::from_slice(&blob)?))
+    }
+}
+
+/// Read the batch index of a `.delila` file
+pub fn delila_index(path: &Path) -> Result<Option<FileIndex>, FileFormatError> {
+    let mut reader = BufReader::new(File::open(path)?);
+    FileIndex::read_from(&mut reader)
+}
+
+/// Find the index blob: `Some((start, len))` if the trailer is present
+pub(crate) fn locate<R: Read + Seek>(
+    reader: &mut R,
+    file_size: u64,
+) -> std::io::Result<Option<(u64, u64)>> {
+    let tail = (FOOTER_SIZE + INDEX_TRAILER_SIZE) as u64;
+    if file_size < tail {
+        return Ok(None);
+    }
+    reader.seek(SeekFrom::Start(file_size - tail))?;
+    let mut trailer = [0u8; INDEX_TRAILER_SIZE];
+    reader.read_exact(&mut trailer)?;
+    let Some(len) = trailer_len(&trailer) else {
+        return Ok(None);
+    };
+    match (file_size - tail).checked_sub(len) {
+        Some(start) => Ok(Some((start, len))),
+        None => Ok(None),
+    }
+}
+
+/// Length of the index blob in front of `trailer`, `None` without the index magic
+pub(crate) fn trailer_len(trailer: &[u8; INDEX_TRAILER_SIZE]) -> Option<u64> {
+    if trailer[8..] != INDEX_MAGIC {
+        return None;
+    }
+    Some(u64::from_le_bytes(
+        trailer[..8].try_into().expect("8-byte slice"),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seek_offset() {
+        let index = FileIndex {
+            entries: vec![(100, 10), (200, 20), (300, 30)],
+        };
+        assert_eq!(index.seek_offset(50), Some(10));
+        assert_eq!(index.seek_offset(200), Some(20));
+        assert_eq!(index.seek_offset(250), Some(20));
+        assert_eq!(index.seek_offset(1000), Some(30));
+        assert_eq!(FileIndex::default().seek_offset(0), None);
+    }
+}
@@ -0,0 +1,136 @@
+//! JSON-lines output backend (`format = "jsonlines"`)
+//!
+//! One JSON object per event and line, for tools that do not read MsgPack.
+//! Each object carries the batch's `source_id` and `sequence_number` next to
+//! the event fields (`module`, `channel`, `energy`, `energy_short`,
+//! `timestamp_ns`, `flags`, and `waveform` when present).
+//!
+//! `timestamp_ns` is written in serde_json's shortest round-trip form and
+//! always as a float (`1000.0`), so parsing it back yields exactly the `f64`
+//! that was recorded. Non-finite values would become `null`; decoders never
+//! produce them.
+
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+use crate::common::{EventData, EventDataBatch};
+
+/// File extension of JSON-lines output
+pub const JSONL_EXTENSION: &str = "jsonl";
+
+/// One output line
+#[derive(Serialize)]
+struct JsonLine<'a> {
+    source_id: u32,
+    sequence_number: u64,
+    #[serde(flatten)]
+    event: &'a EventData,
+}
+
+/// Open JSON-lines output file
+pub(crate) struct JsonLinesWriter {
+    path: PathBuf,
+    writer: BufWriter<File>,
+}
+
+impl JsonLinesWriter {
+    /// Create the file at `path`
+    pub(crate) fn create(path: PathBuf) -> std::io::Result<Self> {
+        let file = File::create(&path)?;
+        Ok(Self {
+            path,
+            writer: BufWriter::with_capacity(64 * 1024, file),
+        })
+    }
+
+    /// Append one line per event of `batch`; returns the bytes written
+    pub(crate) fn append(&mut self, batch: &EventDataBatch) -> std::io::Result<u64> {
+        let mut line = Vec::with_capacity(160);
+        let mut bytes = 0;
+        for event in &batch.events {
+            line.clear();
+            serde_json::to_writer(
+                &mut line,
+                &JsonLine {
+                    source_id: batch.source_id,
+                    sequence_number: batch.sequence_number,
+                    event,
+                },
+            )?;
+            line.push(b'\n');
+            self.writer.write_all(&line)?;
+            bytes += line.len() as u64;
+        }
+        Ok(bytes)
+    }
+
+    /// Output path of this file
+    pub(crate) fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Flush buffered lines and fsync, keeping the file open
+    pub(crate) fn sync(&mut self) -> std::io::Result<()> {
+        self.writer.flush()?;
+        self.writer.get_ref().sync_all()
+    }
+
+    /// Flush, fsync and close the file
+    pub(crate) fn finish(mut self) -> std::io::Result<()> {
+        self.sync()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::BufRead;
+
+    use super::*;
+
+    #[test]
+    fn test_lines_parse_back_to_events() {
+        let path = std::env::temp_dir().join(format!(
+            "delila_jsonl_test_{}.{}",
+            std::process::id(),
+            JSONL_EXTENSION
+        ));
+        let mut batch = EventDataBatch::new(3, 17);
+        batch.push(EventData::new(0, 5, 1200, 300, 1000.0, 0));
+        batch.push(EventData::new(
+            1,
+            7,
+            65535,
+            0,
+            123_456_789.015625,
+            0x1_0000_0001,
+        ));
+
+        let mut writer = JsonLinesWriter::create(path.clone()).unwrap();
+        let bytes = writer.append(&batch).unwrap();
+        writer.finish().unwrap();
+        assert_eq!(bytes, std::fs::metadata(&path).unwrap().len());
+
+        let lines: Vec<serde_json::Value> = std::io::BufReader::new(File::open(&path).unwrap())
+            .lines()
+            .map(|line| serde_json::from_str(&line.unwrap()).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+        for (line, event) in lines.iter().zip(&batch.events) {
+            assert_eq!(line["source_id"], 3);
+            assert_eq!(line["sequence_number"], 17);
+            assert_eq!(line["module"], event.module);
+            assert_eq!(line["channel"], event.channel);
+            assert_eq!(line["energy"], event.energy);
+            assert_eq!(line["energy_short"], event.energy_short);
+            assert_eq!(line["flags"], event.flags);
+            // Timestamps survive exactly, including the fractional part
+            assert_eq!(line["timestamp_ns"].as_f64(), Some(event.timestamp_ns));
+            assert!(line.get("waveform").is_none());
+        }
+
+        let _ = std::fs::remove_file(&path);
+    }
+}
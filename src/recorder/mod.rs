@@ -5,25 +5,77 @@
 //! - Writer task: mpsc channel → File I/O
 //! - Command task: ZMQ REP socket for control commands
 //!
-//! Note: This is a Raw Data Recorder - data is written unsorted.
-//! Sorting will be performed by the future Online Event Builder component.
+//! Note: This is a Raw Data Recorder - data is written unsorted, in arrival
+//! order, with no sort buffer or margin. Time-ordered files are obtained by
+//! enabling `merge_by_timestamp` on the upstream Merger (see
+//! [`crate::merger::TimeSorter`]); the header's `is_sorted` flag stays false
+//! either way. Full sorting will be performed by the future Online Event
+//! Builder component.
 //!
 //! File naming: run{XXXX}_{YYYY}_{ExpName}.delila
 //!   - XXXX: Run number (4 digits, zero-padded)
 //!   - YYYY: File sequence within run (4 digits)
 //!   - ExpName: Experiment name from RunConfig
 //!
+//! With PSD routing enabled, each stream gets its own file set:
+//!   run{XXXX}_{YYYY}_{ExpName}_{Stream}.delila
+//!
+//! With compression enabled the whole file is compressed and the name gains
+//! a `.gz` / `.zst` suffix (e.g. `run0001_0000_data.delila.zst`). Rotation by
+//! `max_file_size` counts uncompressed bytes.
+//!
+//! Every file gets a JSON sidecar (`<file>.meta.json`) with the run number,
+//! experiment name, comment, open/close times, host and DELILA version.
+//!
+//! With `min_free_bytes` set, a new file is only opened if the output disk
+//! has that much free space; otherwise recording stops and the Recorder
+//! enters `Error`.
+//!
 //! File format (v2):
 //! - Header: Magic "DELILA02" + length (4 bytes) + MsgPack metadata
-//! - Data blocks: length (4 bytes LE) + MsgPack batch (repeated)
+//! - Data blocks: [CRC32 (4 bytes LE)] + length (4 bytes LE) + MsgPack batch
+//!   (repeated; the CRC is present when `write_checksums` is on, version 3)
+//! - Index: MsgPack (first_timestamp_ns, offset) per block + length + "DLIDX001"
 //! - Footer: Fixed 64 bytes with magic "DLEND002", checksums, completion flag
+//!
+//! With `format = RootTree` (cargo feature `root-export`) files are written as
+//! flat ROOT TTrees instead, named run{XXXX}_{YYYY}_{ExpName}.root.
+//! `filename_template` replaces the run{XXXX}_{YYYY}_{ExpName} stem, e.g. with
+//! the date or host name (see [`filename`] for the tokens).
+//!
+//! With `format = JsonLines` they are JSON-lines text, one event per line,
+//! named run{XXXX}_{YYYY}_{ExpName}.jsonl.
 
+mod compression;
+mod disk;
+mod filename;
 mod format;
-
+mod index;
+mod json_lines;
+mod replay;
+#[cfg(feature = "root-export")]
+mod root_export;
+mod routing;
+mod sidecar;
+
+pub use compression::CompressionKind;
+pub use disk::{check_free_space, free_space};
+pub use filename::{
+    check_filename_template, render_filename, FilenameFields, DEFAULT_FILENAME_TEMPLATE,
+};
 pub use format::{
     ChecksumCalculator, DataBlockIterator, DataFileReader, FileFooter, FileFormatError, FileHeader,
-    FileValidationResult, FOOTER_SIZE, FORMAT_VERSION,
+    FileValidationResult, FrameCrcMismatch, FOOTER_SIZE, FORMAT_VERSION, FORMAT_VERSION_CRC,
 };
+pub use index::{delila_index, FileIndex, INDEX_MAGIC};
+pub use json_lines::JSONL_EXTENSION;
+pub use replay::{replay_file, FileReplay, ReplayPacer};
+#[cfg(feature = "root-export")]
+pub use root_export::TREE_NAME;
+pub use routing::{PsdCut, PsdRouting};
+pub use sidecar::{sidecar_path, FileMetadata, SIDECAR_SUFFIX};
+
+use std::collections::HashMap;
 
 use std::fs::{self, File};
 use std::io::{BufWriter, Write};
@@ -33,16 +85,39 @@ use std::sync::Arc;
 use std::time::{Duration, Instant};
 
 use futures::StreamExt;
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
-use tmq::{subscribe, Context};
-use tokio::sync::{mpsc, watch};
-use tracing::{debug, info, warn};
+use tmq::{subscribe, AsZmqSocket, Context};
+use tokio::sync::{mpsc, oneshot, watch};
+use tracing::{debug, error, info, warn};
+
+use compression::OutputStream;
+use disk::FreeSpaceFn;
+use json_lines::JsonLinesWriter;
+#[cfg(feature = "root-export")]
+use root_export::RootTreeWriter;
 
 use crate::common::{
-    handle_command, run_command_task, CommandHandlerExt, ComponentSharedState, ComponentState,
-    EventDataBatch, Message, RunConfig,
+    enter_error_state, finish_run, handle_command, run_command_task, topic, CommandHandlerExt,
+    ComponentSharedState, ComponentState, EosTracker, EventDataBatch, Message, RecorderTuning,
+    RunConfig, SocketOptions,
 };
 
+/// Output file format
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RecorderFormat {
+    /// DELILA `.delila` files (length-prefixed MsgPack batches)
+    #[default]
+    MsgPack,
+    /// Flat ROOT TTree (`.root`, requires the `root-export` feature)
+    #[serde(alias = "root")]
+    RootTree,
+    /// One JSON object per event and line (`.jsonl`)
+    #[serde(alias = "jsonl")]
+    JsonLines,
+}
+
 /// Recorder configuration
 #[derive(Debug, Clone)]
 pub struct RecorderConfig {
@@ -56,6 +131,34 @@ pub struct RecorderConfig {
     pub max_file_size: u64,
     /// Maximum file duration in seconds (default: 600 = 10min)
     pub max_file_duration_secs: u64,
+    /// Maximum events per file (default: None = unlimited)
+    pub max_file_events: Option<u64>,
+    /// Optional PSD cut routing into two output streams
+    pub psd_routing: Option<PsdRouting>,
+    /// Output file compression (default: none)
+    pub compression: CompressionKind,
+    /// Prefix every data block with a CRC32 of its payload (format version 3)
+    pub write_checksums: bool,
+    /// Output file format (default: MsgPack)
+    pub format: RecorderFormat,
+    /// Write a 32-bit `energy` branch in ROOT files (default: 16-bit, clamped)
+    pub wide_energy: bool,
+    /// Finish the run (close files, back to Configured) once every expected
+    /// source sent EOS; otherwise files are closed on the first EOS
+    pub finish_on_all_eos: bool,
+    /// Source IDs expected to send EOS (empty = finish on the first EOS)
+    pub expected_source_ids: Vec<u32>,
+    /// Topic prefixes to receive (empty = everything)
+    pub subscribe_topics: Vec<String>,
+    /// Minimum free space on `output_dir` to open a new file (0 = no check)
+    pub min_free_bytes: u64,
+    /// fsync the open file every N batches (0 = only on close)
+    pub fsync_interval_batches: u64,
+    /// ZMQ receive HWM / linger of the SUB socket (default: ZMQ's)
+    pub socket_options: SocketOptions,
+    /// Output file stem with `{run}`, `{seq}`, `{exp}`, `{date}`, `{host}`
+    /// (default: [`DEFAULT_FILENAME_TEMPLATE`])
+    pub filename_template: String,
 }
 
 impl Default for RecorderConfig {
@@ -66,6 +169,40 @@ impl Default for RecorderConfig {
             output_dir: PathBuf::from("./data"),
             max_file_size: 1024 * 1024 * 1024, // 1GB
             max_file_duration_secs: 600,       // 10 minutes
+            max_file_events: None,
+            psd_routing: None,
+            compression: CompressionKind::None,
+            write_checksums: false,
+            format: RecorderFormat::MsgPack,
+            wide_energy: false,
+            finish_on_all_eos: false,
+            expected_source_ids: Vec::new(),
+            subscribe_topics: Vec::new(),
+            min_free_bytes: 0,
+            fsync_interval_batches: 0,
+            socket_options: SocketOptions::default(),
+            filename_template: DEFAULT_FILENAME_TEMPLATE.to_string(),
+        }
+    }
+}
+
+impl RecorderConfig {
+    /// Overwrite the thresholds set in `tuning`
+    pub fn apply_tuning(&mut self, tuning: &RecorderTuning) {
+        if let Some(size) = tuning.max_file_size {
+            self.max_file_size = size;
+        }
+        if let Some(secs) = tuning.max_file_duration_secs {
+            self.max_file_duration_secs = secs;
+        }
+        if let Some(events) = tuning.max_file_events {
+            self.max_file_events = Some(events);
+        }
+        if let Some(bytes) = tuning.min_free_bytes {
+            self.min_free_bytes = bytes;
+        }
+        if let Some(batches) = tuning.fsync_interval_batches {
+            self.fsync_interval_batches = batches;
         }
     }
 }
@@ -87,6 +224,15 @@ pub enum RecorderError {
 
     #[error("Channel send error")]
     ChannelSend,
+
+    #[error("Unsupported output format: {0}")]
+    UnsupportedFormat(String),
+
+    #[error("Disk full: {free} bytes free, {required} required")]
+    DiskFull { free: u64, required: u64 },
+
+    #[error("Invalid filename template: {0}")]
+    InvalidFilenameTemplate(String),
 }
 
 /// Lock-free statistics for hot path
@@ -98,6 +244,8 @@ struct AtomicStats {
     written_bytes: AtomicU64,
     files_written: AtomicU64,
     dropped_batches: AtomicU64,
+    /// Last measured free space on the output disk (u64::MAX = unknown)
+    free_bytes: AtomicU64,
 }
 
 impl AtomicStats {
@@ -109,6 +257,7 @@ impl AtomicStats {
             written_bytes: AtomicU64::new(0),
             files_written: AtomicU64::new(0),
             dropped_batches: AtomicU64::new(0),
+            free_bytes: AtomicU64::new(u64::MAX),
         }
     }
 
@@ -119,6 +268,11 @@ impl AtomicStats {
         self.written_bytes.store(0, Ordering::Relaxed);
         self.files_written.store(0, Ordering::Relaxed);
         self.dropped_batches.store(0, Ordering::Relaxed);
+        // free_bytes describes the disk, not the run, so it is kept
+    }
+
+    fn record_free_bytes(&self, free: u64) {
+        self.free_bytes.store(free, Ordering::Relaxed);
     }
 
     fn snapshot(&self) -> RecorderStats {
@@ -129,6 +283,10 @@ impl AtomicStats {
             files_written: self.files_written.load(Ordering::Relaxed) as u32,
             written_events: self.written_events.load(Ordering::Relaxed),
             dropped_batches: self.dropped_batches.load(Ordering::Relaxed),
+            free_bytes: match self.free_bytes.load(Ordering::Relaxed) {
+                u64::MAX => None,
+                free => Some(free),
+            },
         }
     }
 }
@@ -142,6 +300,8 @@ pub struct RecorderStats {
     pub files_written: u32,
     pub written_events: u64,
     pub dropped_batches: u64,
+    /// Free space on the output disk at the last check (None = not measured)
+    pub free_bytes: Option<u64>,
 }
 
 /// Rate tracker for 1-second interval rate calculation
@@ -207,6 +367,10 @@ enum WriterCommand {
     DrainAndStart { run_number: u32 },
     /// Close current file (run stopped)
     CloseFile,
+    /// Apply new write thresholds to every output stream
+    UpdateTuning(RecorderTuning),
+    /// Flush and fsync the open file without rotating, then reply on the ack
+    Flush(oneshot::Sender<Result<(), String>>),
     /// Shutdown writer task
     Shutdown,
 }
@@ -215,9 +379,11 @@ enum WriterCommand {
 struct FileWriter {
     config: RecorderConfig,
     run_config: Option<RunConfig>,
-    writer: Option<BufWriter<File>>,
+    writer: Option<OutputStream>,
     file_sequence: u32,
     current_file_size: u64,
+    /// Events written to the current file
+    current_file_events: u64,
     current_file_start: Option<Instant>,
     stats: Arc<AtomicStats>,
     /// Checksum calculator for current file
@@ -226,8 +392,25 @@ struct FileWriter {
     footer: FileFooter,
     /// Header size for current file (needed for data_bytes calculation)
     header_size: u64,
+    /// (first_timestamp_ns, byte_offset) of each block in the current file
+    index: Vec<(u64, u64)>,
     /// Whether we have an active run (file can be opened)
     run_active: bool,
+    /// Output stream name (filename suffix) when routing is enabled
+    stream: Option<String>,
+    /// Extra header metadata written to every file
+    metadata: HashMap<String, String>,
+    /// Open ROOT output (RootTree format only)
+    #[cfg(feature = "root-export")]
+    root: Option<RootTreeWriter>,
+    /// Open JSON-lines output (JsonLines format only)
+    json: Option<JsonLinesWriter>,
+    /// Free disk space query
+    free_space: FreeSpaceFn,
+    /// Open file and its metadata sidecar contents
+    sidecar: Option<(PathBuf, FileMetadata)>,
+    /// Batches written since the last fsync
+    batches_since_sync: u64,
 }
 
 impl FileWriter {
@@ -238,28 +421,75 @@ impl FileWriter {
             writer: None,
             file_sequence: 0,
             current_file_size: 0,
+            current_file_events: 0,
             current_file_start: None,
             stats,
             checksum: ChecksumCalculator::new(),
             footer: FileFooter::new(),
             header_size: 0,
+            index: Vec::new(),
             run_active: false,
+            stream: None,
+            metadata: HashMap::new(),
+            #[cfg(feature = "root-export")]
+            root: None,
+            json: None,
+            free_space: disk::free_space,
+            sidecar: None,
+            batches_since_sync: 0,
         }
     }
 
+    /// Create one writer per output stream (a single writer without routing)
+    fn create_writers(config: RecorderConfig, stats: Arc<AtomicStats>) -> Vec<FileWriter> {
+        let Some(routing) = config.psd_routing.clone() else {
+            return vec![FileWriter::new(config, stats)];
+        };
+
+        let cut = routing.to_metadata();
+        [routing.inside_stream, routing.outside_stream]
+            .into_iter()
+            .map(|stream| {
+                let mut writer = FileWriter::new(config.clone(), stats.clone());
+                writer.metadata.insert("psd_cut".to_string(), cut.clone());
+                writer.metadata.insert("stream".to_string(), stream.clone());
+                writer.stream = Some(stream);
+                writer
+            })
+            .collect()
+    }
+
     fn generate_filename(&self) -> PathBuf {
         let run_config = self.run_config.as_ref().expect("RunConfig not set");
-        let exp_name = if run_config.exp_name.is_empty() {
+        let mut exp_name = if run_config.exp_name.is_empty() {
             "data".to_string()
         } else {
             run_config.exp_name.clone()
         };
+        if let Some(ref stream) = self.stream {
+            exp_name = format!("{}_{}", exp_name, stream);
+        }
+
+        let extension = match self.config.format {
+            RecorderFormat::MsgPack => format!("delila{}", self.config.compression.suffix()),
+            RecorderFormat::RootTree => "root".to_string(),
+            RecorderFormat::JsonLines => JSONL_EXTENSION.to_string(),
+        };
 
         // Generate base filename
-        let base_filename = format!(
-            "run{:04}_{:04}_{}.delila",
-            run_config.run_number, self.file_sequence, exp_name
+        let date = chrono::Local::now().format("%Y%m%d").to_string();
+        let host = gethostname::gethostname().to_string_lossy().into_owned();
+        let stem = render_filename(
+            &self.config.filename_template,
+            &FilenameFields {
+                run: run_config.run_number,
+                seq: self.file_sequence,
+                exp: &exp_name,
+                date: &date,
+                host: &host,
+            },
         );
+        let base_filename = format!("{}.{}", stem, extension);
         let base_path = self.config.output_dir.join(&base_filename);
 
         // If file doesn't exist, use base filename
@@ -267,38 +497,74 @@ impl FileWriter {
             return base_path;
         }
 
-        // File exists - append Unix timestamp to avoid overwriting
+        // File exists - append Unix timestamp (and a counter within the same
+        // second) to avoid overwriting
         let timestamp = std::time::SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)
             .unwrap_or_default()
             .as_secs();
 
-        let filename_with_ts = format!(
-            "run{:04}_{:04}_{}_{}.delila",
-            run_config.run_number, self.file_sequence, exp_name, timestamp
-        );
+        let mut path = self
+            .config
+            .output_dir
+            .join(format!("{}_{}.{}", stem, timestamp, extension));
+        let mut counter = 1u32;
+        while path.exists() {
+            path = self
+                .config
+                .output_dir
+                .join(format!("{}_{}_{}.{}", stem, timestamp, counter, extension));
+            counter += 1;
+        }
 
         warn!(
             existing = %base_path.display(),
-            new = %filename_with_ts,
+            new = %path.display(),
             "File already exists, using timestamped filename"
         );
 
-        self.config.output_dir.join(filename_with_ts)
+        path
     }
 
     fn open_new_file(&mut self) -> Result<(), RecorderError> {
         self.close_file()?;
+        self.current_file_events = 0;
 
         fs::create_dir_all(&self.config.output_dir)?;
+        self.check_disk_space()?;
 
         let path = self.generate_filename();
+        if self.config.format == RecorderFormat::RootTree {
+            self.open_root_file(path.clone())?;
+            self.write_sidecar(path);
+            return Ok(());
+        }
+        if self.config.format == RecorderFormat::JsonLines {
+            self.footer = FileFooter::new();
+            self.current_file_size = 0;
+            self.current_file_start = Some(Instant::now());
+            self.batches_since_sync = 0;
+            self.json = Some(JsonLinesWriter::create(path.clone())?);
+            info!(
+                path = %path.display(),
+                sequence = self.file_sequence,
+                "Opened new JSON-lines file"
+            );
+            self.write_sidecar(path);
+            return Ok(());
+        }
+
         let file = File::create(&path)?;
-        let mut writer = BufWriter::with_capacity(64 * 1024, file);
+        let mut writer = OutputStream::new(
+            self.config.compression,
+            BufWriter::with_capacity(64 * 1024, file),
+        )?;
 
-        // Reset checksum and footer for new file
+        // Reset checksum, footer and index for new file
         self.checksum.reset();
         self.footer = FileFooter::new();
+        self.index.clear();
+        self.batches_since_sync = 0;
 
         // Create and write header
         let run_config = self.run_config.as_ref().expect("RunConfig not set");
@@ -308,6 +574,10 @@ impl FileWriter {
             self.file_sequence,
         );
         header.comment = run_config.comment.clone();
+        header.metadata.extend(self.metadata.clone());
+        if self.config.write_checksums {
+            header.version = FORMAT_VERSION_CRC;
+        }
 
         let header_bytes = header
             .to_bytes()
@@ -326,12 +596,149 @@ impl FileWriter {
             header_size = self.header_size,
             "Opened new data file"
         );
+        self.write_sidecar(path);
+
+        Ok(())
+    }
+
+    /// Write the metadata sidecar of a newly opened file
+    ///
+    /// A failure is logged and does not stop recording.
+    fn write_sidecar(&mut self, path: PathBuf) {
+        let run_config = self.run_config.as_ref().expect("RunConfig not set");
+        let meta = FileMetadata::new(&path, run_config, self.file_sequence, self.metadata.clone());
+        if let Err(e) = meta.write(&path) {
+            warn!(error = %e, path = %path.display(), "Failed to write metadata sidecar");
+        }
+        self.sidecar = Some((path, meta));
+    }
+
+    /// Rewrite the sidecar of the file being closed with close time and events
+    fn finish_sidecar(&mut self) {
+        if let Some((path, mut meta)) = self.sidecar.take() {
+            meta.finish(self.footer.total_events);
+            if let Err(e) = meta.write(&path) {
+                warn!(error = %e, path = %path.display(), "Failed to update metadata sidecar");
+            }
+        }
+    }
+
+    /// Measure free space on the output disk and enforce `min_free_bytes`
+    ///
+    /// A failing query is logged and does not block recording.
+    fn check_disk_space(&self) -> Result<(), RecorderError> {
+        if self.config.min_free_bytes == 0 {
+            return Ok(());
+        }
+        match (self.free_space)(&self.config.output_dir) {
+            Ok(free) => {
+                self.stats.record_free_bytes(free);
+                check_free_space(free, self.config.min_free_bytes)
+            }
+            Err(e) => {
+                warn!(error = %e, dir = %self.config.output_dir.display(), "Failed to query free disk space");
+                Ok(())
+            }
+        }
+    }
+
+    #[cfg(feature = "root-export")]
+    fn open_root_file(&mut self, path: PathBuf) -> Result<(), RecorderError> {
+        self.footer = FileFooter::new();
+        self.current_file_size = 0;
+        self.current_file_start = Some(Instant::now());
+
+        info!(
+            path = %path.display(),
+            sequence = self.file_sequence,
+            "Opened new ROOT file"
+        );
+        self.root = Some(RootTreeWriter::new(path, self.config.wide_energy));
+        Ok(())
+    }
 
+    #[cfg(not(feature = "root-export"))]
+    fn open_root_file(&mut self, _path: PathBuf) -> Result<(), RecorderError> {
+        Err(RecorderError::UnsupportedFormat(
+            "RootTree requires the `root-export` feature".to_string(),
+        ))
+    }
+
+    /// Flush buffered data of the open file and fsync it, keeping it open
+    ///
+    /// Everything written so far is durable afterwards, but the file has no
+    /// footer until it is closed.
+    fn flush(&mut self) -> Result<(), RecorderError> {
+        #[cfg(feature = "root-export")]
+        if let Some(ref root) = self.root {
+            root.sync()?;
+            self.batches_since_sync = 0;
+            return Ok(());
+        }
+        if let Some(ref mut json) = self.json {
+            json.sync()?;
+            self.batches_since_sync = 0;
+            return Ok(());
+        }
+        let Some(ref mut writer) = self.writer else {
+            return Ok(());
+        };
+        writer.sync()?;
+        self.batches_since_sync = 0;
+        debug!(
+            size_mb = self.current_file_size as f64 / 1_000_000.0,
+            "Flushed data file"
+        );
         Ok(())
     }
 
+    /// Whether an output file is currently open
+    fn is_open(&self) -> bool {
+        #[cfg(feature = "root-export")]
+        if self.root.is_some() {
+            return true;
+        }
+        self.writer.is_some() || self.json.is_some()
+    }
+
     fn close_file(&mut self) -> Result<(), RecorderError> {
+        #[cfg(feature = "root-export")]
+        if let Some(root) = self.root.take() {
+            let path = root.path().to_path_buf();
+            root.finish()?;
+            self.stats.files_written.fetch_add(1, Ordering::Relaxed);
+            self.file_sequence += 1;
+
+            info!(
+                path = %path.display(),
+                events = self.footer.total_events,
+                "Closed ROOT file"
+            );
+        }
+
+        if let Some(json) = self.json.take() {
+            let path = json.path().to_path_buf();
+            json.finish()?;
+            self.stats.files_written.fetch_add(1, Ordering::Relaxed);
+            self.file_sequence += 1;
+
+            info!(
+                path = %path.display(),
+                size_mb = self.current_file_size as f64 / 1_000_000.0,
+                events = self.footer.total_events,
+                "Closed JSON-lines file"
+            );
+        }
+
         if let Some(mut writer) = self.writer.take() {
+            // Batch index goes between the data blocks and the footer
+            let index = FileIndex {
+                entries: std::mem::take(&mut self.index),
+            };
+            self.current_file_size += index
+                .write_to(&mut writer)
+                .map_err(|e| RecorderError::Io(std::io::Error::other(e.to_string())))?;
+
             // Finalize and write footer
             self.footer.data_checksum = self.checksum.finalize();
             self.footer.data_bytes = self.checksum.bytes_processed();
@@ -340,9 +747,8 @@ impl FileWriter {
             let footer_bytes = self.footer.to_bytes();
             writer.write_all(&footer_bytes)?;
 
-            writer.flush()?;
-            // Final fsync on close
-            writer.get_ref().sync_data()?;
+            // Flush (and finish compression), final fsync on close
+            writer.finish()?;
             self.stats.files_written.fetch_add(1, Ordering::Relaxed);
             self.file_sequence += 1;
 
@@ -353,6 +759,7 @@ impl FileWriter {
                 "Closed data file"
             );
         }
+        self.finish_sidecar();
         self.current_file_start = None;
         Ok(())
     }
@@ -369,10 +776,21 @@ impl FileWriter {
             }
         }
 
+        if let Some(max) = self.max_file_events() {
+            if self.current_file_events >= max {
+                return true;
+            }
+        }
+
         false
     }
 
-    fn write_batch(&mut self, batch: EventDataBatch) -> Result<(), RecorderError> {
+    /// Event limit per file (`Some(0)` is treated as unlimited)
+    fn max_file_events(&self) -> Option<u64> {
+        self.config.max_file_events.filter(|&max| max > 0)
+    }
+
+    fn write_batch(&mut self, mut batch: EventDataBatch) -> Result<(), RecorderError> {
         if batch.events.is_empty() {
             return Ok(());
         }
@@ -384,7 +802,7 @@ impl FileWriter {
         }
 
         // Open file if needed
-        if self.writer.is_none() {
+        if !self.is_open() {
             self.open_new_file()?;
         }
 
@@ -393,17 +811,80 @@ impl FileWriter {
             self.open_new_file()?;
         }
 
+        // Split a batch that crosses the event limit so each file holds exactly
+        // max_file_events; the remainder goes to the next file
+        if let Some(max) = self.max_file_events() {
+            let remaining = max.saturating_sub(self.current_file_events) as usize;
+            if batch.events.len() > remaining {
+                let rest = EventDataBatch {
+                    source_id: batch.source_id,
+                    sequence_number: batch.sequence_number,
+                    timestamp: batch.timestamp,
+                    events: batch.events.split_off(remaining),
+                    fragment: batch.fragment,
+                    epoch: batch.epoch,
+                    waveform_encoding: batch.waveform_encoding,
+                };
+                self.write_batch(batch)?;
+                return self.write_batch(rest);
+            }
+        }
+
+        #[cfg(feature = "root-export")]
+        if let Some(ref mut root) = self.root {
+            let event_count = batch.events.len() as u64;
+            let bytes = root.append(&batch.events);
+            self.current_file_size += bytes;
+            self.current_file_events += event_count;
+            self.footer.total_events += event_count;
+            self.stats.written_bytes.fetch_add(bytes, Ordering::Relaxed);
+            self.stats
+                .written_events
+                .fetch_add(event_count, Ordering::Relaxed);
+            return Ok(());
+        }
+
         // Update timestamp range for footer
         if let (Some(first), Some(last)) = (batch.events.first(), batch.events.last()) {
             self.footer
                 .update_timestamp_range(first.timestamp_ns, last.timestamp_ns);
         }
 
+        if let Some(ref mut json) = self.json {
+            let event_count = batch.events.len() as u64;
+            let bytes = json.append(&batch)?;
+            self.current_file_size += bytes;
+            self.current_file_events += event_count;
+            self.footer.total_events += event_count;
+            self.stats.written_bytes.fetch_add(bytes, Ordering::Relaxed);
+            self.stats
+                .written_events
+                .fetch_add(event_count, Ordering::Relaxed);
+            if self.config.fsync_interval_batches > 0 {
+                self.batches_since_sync += 1;
+                if self.batches_since_sync >= self.config.fsync_interval_batches {
+                    json.sync()?;
+                    self.batches_since_sync = 0;
+                }
+            }
+            return Ok(());
+        }
+
         let event_count = batch.events.len() as u64;
+        let batch_first_ts = batch.events[0].timestamp_ns;
         let data = batch.to_msgpack()?;
         let len_bytes = (data.len() as u32).to_le_bytes();
 
         if let Some(ref mut writer) = self.writer {
+            self.index
+                .push((batch_first_ts as u64, self.current_file_size));
+            let mut bytes_written = 4 + data.len() as u64;
+            if self.config.write_checksums {
+                let crc_bytes = crc32fast::hash(&data).to_le_bytes();
+                writer.write_all(&crc_bytes)?;
+                self.checksum.update(&crc_bytes);
+                bytes_written += 4;
+            }
             writer.write_all(&len_bytes)?;
             writer.write_all(&data)?;
 
@@ -411,8 +892,8 @@ impl FileWriter {
             self.checksum.update(&len_bytes);
             self.checksum.update(&data);
 
-            let bytes_written = 4 + data.len() as u64;
             self.current_file_size += bytes_written;
+            self.current_file_events += event_count;
             self.footer.total_events += event_count;
 
             self.stats
@@ -421,6 +902,14 @@ impl FileWriter {
             self.stats
                 .written_events
                 .fetch_add(event_count, Ordering::Relaxed);
+
+            if self.config.fsync_interval_batches > 0 {
+                self.batches_since_sync += 1;
+                if self.batches_since_sync >= self.config.fsync_interval_batches {
+                    writer.sync()?;
+                    self.batches_since_sync = 0;
+                }
+            }
         }
 
         debug!(
@@ -439,7 +928,7 @@ impl FileWriter {
 
     fn start_run(&mut self, run_number: u32) {
         // Close any leftover file from previous run
-        if self.writer.is_some() {
+        if self.is_open() {
             if let Err(e) = self.close_file() {
                 warn!(error = %e, "Failed to close leftover file on start");
             }
@@ -453,6 +942,7 @@ impl FileWriter {
         // Reset file state for new run
         self.file_sequence = 0;
         self.current_file_size = 0;
+        self.current_file_events = 0;
         self.current_file_start = None;
         self.checksum = ChecksumCalculator::new();
         self.footer = FileFooter::new();
@@ -472,6 +962,8 @@ struct RecorderCommandExt {
     stats: Arc<AtomicStats>,
     rate_tracker: Arc<RateTracker>,
     writer_tx: mpsc::UnboundedSender<WriterCommand>,
+    /// Output settings reported by GetConfig (kept in step with tuning updates)
+    config: Arc<parking_lot::Mutex<RecorderConfig>>,
 }
 
 impl CommandHandlerExt for RecorderCommandExt {
@@ -508,12 +1000,50 @@ impl CommandHandlerExt for RecorderCommandExt {
             .map_err(|e| format!("Failed to send reset to writer: {}", e))
     }
 
+    fn effective_config(&self) -> Option<serde_json::Value> {
+        let c = self.config.lock();
+        Some(serde_json::json!({
+            "output_dir": c.output_dir,
+            "format": c.format,
+            "compression": c.compression,
+            "write_checksums": c.write_checksums,
+            "wide_energy": c.wide_energy,
+            "max_file_size": c.max_file_size,
+            "max_file_duration_secs": c.max_file_duration_secs,
+            "max_file_events": c.max_file_events,
+            "min_free_bytes": c.min_free_bytes,
+            "fsync_interval_batches": c.fsync_interval_batches,
+        }))
+    }
+
+    fn on_update_recorder_tuning(&mut self, tuning: &RecorderTuning) -> Result<(), String> {
+        self.writer_tx
+            .send(WriterCommand::UpdateTuning(tuning.clone()))
+            .map_err(|e| format!("Failed to send tuning to writer: {}", e))?;
+        self.config.lock().apply_tuning(tuning);
+        Ok(())
+    }
+
+    fn on_flush(&mut self) -> Result<(), String> {
+        let (ack_tx, ack_rx) = oneshot::channel();
+        self.writer_tx
+            .send(WriterCommand::Flush(ack_tx))
+            .map_err(|e| format!("Failed to send flush to writer: {}", e))?;
+        // Reply only once the writer has fsynced
+        tokio::task::block_in_place(|| ack_rx.blocking_recv())
+            .map_err(|_| "Writer stopped before flushing".to_string())?
+    }
+
     fn status_details(&self) -> Option<String> {
         let stats = self.stats.snapshot();
-        Some(format!(
+        let mut details = format!(
             "Received: {} events, Written: {} events, Files: {}, Dropped: {}",
             stats.total_events, stats.written_events, stats.files_written, stats.dropped_batches
-        ))
+        );
+        if let Some(free) = stats.free_bytes {
+            details.push_str(&format!(", Free: {:.1} GB", free as f64 / 1e9));
+        }
+        Some(details)
     }
 
     fn get_metrics(&self) -> Option<crate::common::ComponentMetrics> {
@@ -527,6 +1057,7 @@ impl CommandHandlerExt for RecorderCommandExt {
             queue_max: 0,
             event_rate: self.rate_tracker.get_rate(),
             data_rate: 0.0,
+            ..Default::default()
         })
     }
 }
@@ -547,6 +1078,8 @@ impl Recorder {
         let (state_tx, state_rx) = watch::channel(ComponentState::Idle);
         let stats = Arc::new(AtomicStats::new());
         let rate_tracker = Arc::new(RateTracker::new());
+        check_filename_template(&config.filename_template)
+            .map_err(RecorderError::InvalidFilenameTemplate)?;
 
         info!(
             subscribe = %config.subscribe_address,
@@ -582,14 +1115,22 @@ impl Recorder {
         &mut self,
         mut shutdown: tokio::sync::broadcast::Receiver<()>,
     ) -> Result<(), RecorderError> {
+        if self.config.format == RecorderFormat::RootTree && !cfg!(feature = "root-export") {
+            return Err(RecorderError::UnsupportedFormat(
+                "RootTree requires the `root-export` feature".to_string(),
+            ));
+        }
+
         // Create channel: Receiver → Writer
         let (writer_tx, writer_rx) = mpsc::unbounded_channel::<WriterCommand>();
 
         // Create ZMQ SUB socket
         let context = Context::new();
-        let socket = subscribe(&context)
-            .connect(&self.config.subscribe_address)?
-            .subscribe(b"")?;
+        let socket = topic::subscribe_topics(
+            subscribe(&context).connect(&self.config.subscribe_address)?,
+            &self.config.subscribe_topics,
+        )?;
+        self.config.socket_options.apply(socket.get_socket())?;
 
         info!(
             address = %self.config.subscribe_address,
@@ -600,8 +1141,18 @@ impl Recorder {
         let writer_config = self.config.clone();
         let writer_stats = self.stats.clone();
         let writer_state_rx = self.state_rx.clone();
+        let writer_shared_state = self.shared_state.clone();
+        let writer_state_tx = self.state_tx.clone();
         let writer_handle = tokio::spawn(async move {
-            Self::writer_task(writer_rx, writer_config, writer_stats, writer_state_rx).await
+            Self::writer_task(
+                writer_rx,
+                writer_config,
+                writer_stats,
+                writer_state_rx,
+                writer_shared_state,
+                writer_state_tx,
+            )
+            .await
         });
 
         // === Spawn Receiver Task ===
@@ -628,6 +1179,7 @@ impl Recorder {
         let cmd_stats = self.stats.clone();
         let cmd_rate_tracker = self.rate_tracker.clone();
         let cmd_writer_tx = writer_tx.clone();
+        let cmd_config = Arc::new(parking_lot::Mutex::new(self.config.clone()));
 
         let cmd_handle = tokio::spawn(async move {
             run_command_task(
@@ -640,6 +1192,7 @@ impl Recorder {
                         stats: cmd_stats.clone(),
                         rate_tracker: cmd_rate_tracker.clone(),
                         writer_tx: cmd_writer_tx.clone(),
+                        config: cmd_config.clone(),
                     };
                     handle_command(state, tx, cmd, Some(&mut ext))
                 },
@@ -662,6 +1215,9 @@ impl Recorder {
                 }
 
                 _ = stats_interval.tick() => {
+                    if let Ok(free) = free_space(&self.config.output_dir) {
+                        self.stats.record_free_bytes(free);
+                    }
                     if *self.state_rx.borrow() == ComponentState::Running {
                         let stats = self.stats.snapshot();
                         info!(
@@ -709,7 +1265,8 @@ impl Recorder {
         mut state_rx: watch::Receiver<ComponentState>,
     ) {
         loop {
-            let is_running = *state_rx.borrow() == ComponentState::Running;
+            // Paused keeps in-flight data of the current run
+            let is_running = state_rx.borrow().in_run();
 
             tokio::select! {
                 biased;
@@ -735,7 +1292,7 @@ impl Recorder {
                                 continue;
                             }
 
-                            if let Some(data) = multipart.into_iter().next() {
+                            if let Some(data) = topic::payload(multipart) {
                                 match Message::from_msgpack(&data) {
                                     Ok(Message::Data(batch)) => {
                                         stats.received_batches.fetch_add(1, Ordering::Relaxed);
@@ -782,9 +1339,16 @@ impl Recorder {
         config: RecorderConfig,
         stats: Arc<AtomicStats>,
         mut state_rx: watch::Receiver<ComponentState>,
+        shared_state: Arc<tokio::sync::Mutex<ComponentSharedState>>,
+        state_tx: watch::Sender<ComponentState>,
     ) {
-        let mut writer = FileWriter::new(config, stats);
+        let router = config.psd_routing.clone();
+        let mut eos_tracker = config
+            .finish_on_all_eos
+            .then(|| EosTracker::new(config.expected_source_ids.iter().copied()));
+        let mut writers = FileWriter::create_writers(config, stats);
         let mut eos_received = false;
+        let mut last_state = *state_rx.borrow();
 
         loop {
             tokio::select! {
@@ -793,29 +1357,83 @@ impl Recorder {
                 cmd = rx.recv() => {
                     match cmd {
                         Some(WriterCommand::WriteBatch(batch)) => {
-                            if let Err(e) = writer.write_batch(batch) {
-                                warn!(error = %e, "Failed to write batch");
+                            // One write per stream; a failing stream does not hold back the other
+                            let parts = match router {
+                                Some(ref routing) => {
+                                    let (inside, outside) = routing.split(batch);
+                                    vec![inside, outside]
+                                }
+                                None => vec![batch],
+                            };
+                            let mut disk_full = None;
+                            for (writer, part) in writers.iter_mut().zip(parts) {
+                                if part.events.is_empty() {
+                                    continue;
+                                }
+                                match writer.write_batch(part) {
+                                    Ok(()) => {}
+                                    Err(e @ RecorderError::DiskFull { .. }) => {
+                                        error!(stream = ?writer.stream, error = %e, "Output disk is full - recording stopped");
+                                        disk_full = Some(e);
+                                    }
+                                    Err(e) => warn!(stream = ?writer.stream, error = %e, "Failed to write batch"),
+                                }
+                            }
+                            if let Some(e) = disk_full {
+                                for writer in writers.iter_mut() {
+                                    if let Err(e) = writer.end_run() {
+                                        warn!(error = %e, "Failed to close file on disk full");
+                                    }
+                                }
+                                enter_error_state(&shared_state, &state_tx, e.to_string()).await;
                             }
                         }
                         Some(WriterCommand::EndOfStream { source_id }) => {
+                            if let Some(ref mut tracker) = eos_tracker {
+                                if !tracker.record(source_id) {
+                                    info!(source_id, pending = ?tracker.pending(), "Writer received EOS - waiting for remaining sources");
+                                    continue;
+                                }
+                                tracker.reset();
+                            }
+
                             info!(source_id, "Writer received EOS - closing file");
-                            if let Err(e) = writer.end_run() {
-                                warn!(error = %e, "Failed to close file on EOS");
+                            for writer in writers.iter_mut() {
+                                if let Err(e) = writer.end_run() {
+                                    warn!(error = %e, "Failed to close file on EOS");
+                                }
                             }
                             eos_received = true;
+
+                            if eos_tracker.is_some() {
+                                finish_run(&shared_state, &state_tx, "Recorder").await;
+                            }
                         }
                         Some(WriterCommand::NewRun(run_config)) => {
-                            writer.new_run(run_config);
+                            for writer in writers.iter_mut() {
+                                writer.new_run(run_config.clone());
+                            }
                             eos_received = false;
+                            if let Some(ref mut tracker) = eos_tracker {
+                                tracker.reset();
+                            }
                             info!("Writer configured for new run");
                         }
                         Some(WriterCommand::DrainAndStart { run_number }) => {
                             // Drain any stale batches from previous run
                             let mut drained = 0u64;
+                            let mut flush_acks = Vec::new();
                             while let Ok(cmd) = rx.try_recv() {
                                 match cmd {
                                     WriterCommand::WriteBatch(_) => drained += 1,
                                     WriterCommand::EndOfStream { .. } => { /* discard */ }
+                                    // start_run closes (and so flushes) any leftover file
+                                    WriterCommand::Flush(ack) => flush_acks.push(ack),
+                                    WriterCommand::UpdateTuning(tuning) => {
+                                        for writer in writers.iter_mut() {
+                                            writer.config.apply_tuning(&tuning);
+                                        }
+                                    }
                                     // Re-queue important commands (shouldn't happen, but be safe)
                                     other => {
                                         warn!("Unexpected command during drain: {:?}", std::mem::discriminant(&other));
@@ -826,17 +1444,42 @@ impl Recorder {
                                 info!(drained, "Drained stale batches from previous run");
                             }
 
-                            writer.start_run(run_number);
+                            for writer in writers.iter_mut() {
+                                writer.start_run(run_number);
+                            }
+                            for ack in flush_acks {
+                                let _ = ack.send(Ok(()));
+                            }
                             info!(run_number, "Writer started - recording enabled");
                         }
+                        Some(WriterCommand::UpdateTuning(tuning)) => {
+                            for writer in writers.iter_mut() {
+                                writer.config.apply_tuning(&tuning);
+                            }
+                            info!(?tuning, "Writer tuning updated");
+                        }
+                        Some(WriterCommand::Flush(ack)) => {
+                            let mut result = Ok(());
+                            for writer in writers.iter_mut() {
+                                if let Err(e) = writer.flush() {
+                                    warn!(error = %e, "Failed to flush file");
+                                    result = Err(format!("Failed to flush file: {}", e));
+                                }
+                            }
+                            let _ = ack.send(result);
+                        }
                         Some(WriterCommand::CloseFile) => {
-                            if let Err(e) = writer.end_run() {
-                                warn!(error = %e, "Failed to close file");
+                            for writer in writers.iter_mut() {
+                                if let Err(e) = writer.end_run() {
+                                    warn!(error = %e, "Failed to close file");
+                                }
                             }
                         }
                         Some(WriterCommand::Shutdown) => {
-                            if let Err(e) = writer.close_file() {
-                                warn!(error = %e, "Failed to close file on shutdown");
+                            for writer in writers.iter_mut() {
+                                if let Err(e) = writer.close_file() {
+                                    warn!(error = %e, "Failed to close file on shutdown");
+                                }
                             }
                             break;
                         }
@@ -856,15 +1499,21 @@ impl Recorder {
                         && !eos_received
                     {
                         info!("State changed to {} - closing file", current);
-                        if let Err(e) = writer.end_run() {
-                            warn!(error = %e, "Failed to close file on state change");
+                        for writer in writers.iter_mut() {
+                            if let Err(e) = writer.end_run() {
+                                warn!(error = %e, "Failed to close file on state change");
+                            }
                         }
                     }
 
-                    // Reset EOS flag when starting new run
-                    if current == ComponentState::Running {
+                    // Reset EOS flag when starting new run (not on Resume)
+                    if current == ComponentState::Running && last_state != ComponentState::Paused {
                         eos_received = false;
+                        if let Some(ref mut tracker) = eos_tracker {
+                            tracker.reset();
+                        }
                     }
+                    last_state = current;
                 }
             }
         }
@@ -877,6 +1526,15 @@ impl Recorder {
 mod tests {
     use super::*;
 
+    /// Wait until the writer task has handled every command sent so far
+    ///
+    /// Batches sent before `DrainAndStart` is handled would be drained as stale.
+    async fn sync_writer(tx: &mpsc::UnboundedSender<WriterCommand>) {
+        let (ack_tx, ack_rx) = oneshot::channel();
+        tx.send(WriterCommand::Flush(ack_tx)).unwrap();
+        ack_rx.await.unwrap().unwrap();
+    }
+
     #[test]
     fn test_default_config() {
         let config = RecorderConfig::default();
@@ -884,6 +1542,135 @@ mod tests {
         assert_eq!(config.max_file_duration_secs, 600);
     }
 
+    #[test]
+    fn test_update_tuning_changes_live_config() {
+        use crate::common::Command;
+
+        let mut state = ComponentSharedState::new();
+        state.state = ComponentState::Running;
+        let (state_tx, _state_rx) = watch::channel(ComponentState::Running);
+        let (writer_tx, mut writer_rx) = mpsc::unbounded_channel();
+        let mut ext = RecorderCommandExt {
+            stats: Arc::new(AtomicStats::new()),
+            rate_tracker: Arc::new(RateTracker::new()),
+            writer_tx,
+            config: Arc::new(parking_lot::Mutex::new(RecorderConfig::default())),
+        };
+
+        let tuning = RecorderTuning {
+            max_file_events: Some(5000),
+            fsync_interval_batches: Some(20),
+            ..Default::default()
+        };
+        let resp = handle_command(
+            &mut state,
+            &state_tx,
+            Command::UpdateRecorderTuning(tuning.clone()),
+            Some(&mut ext),
+        );
+        assert!(resp.success, "{}", resp.message);
+        assert_eq!(state.state, ComponentState::Running);
+
+        // GetConfig reports the new values, unset fields are unchanged
+        let data = ext.effective_config().unwrap();
+        assert_eq!(data["max_file_events"], 5000);
+        assert_eq!(data["fsync_interval_batches"], 20);
+        assert_eq!(data["max_file_size"], 1024 * 1024 * 1024);
+
+        // The writer task receives the same update
+        match writer_rx.try_recv() {
+            Ok(WriterCommand::UpdateTuning(sent)) => assert_eq!(sent, tuning),
+            _ => panic!("expected UpdateTuning"),
+        }
+
+        // Out of range: rejected as a whole, nothing changes
+        let resp = handle_command(
+            &mut state,
+            &state_tx,
+            Command::UpdateRecorderTuning(RecorderTuning {
+                max_file_events: Some(1),
+                max_file_size: Some(1024),
+                ..Default::default()
+            }),
+            Some(&mut ext),
+        );
+        assert!(!resp.success);
+        assert!(resp.message.contains("max_file_size"));
+        assert_eq!(ext.config.lock().max_file_events, Some(5000));
+        assert!(writer_rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_fsync_interval_keeps_file_valid() {
+        let dir = std::env::temp_dir().join(format!("delila_fsync_test_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let config = RecorderConfig {
+            output_dir: dir.clone(),
+            fsync_interval_batches: 2,
+            ..Default::default()
+        };
+        let mut writer = FileWriter::new(config, Arc::new(AtomicStats::new()));
+        writer.new_run(RunConfig {
+            run_number: 6,
+            exp_name: "SYNC".to_string(),
+            ..Default::default()
+        });
+        writer.start_run(6);
+        for seq in 0..5u64 {
+            let mut batch = EventDataBatch::new(0, seq);
+            batch.push(crate::common::EventData::new(0, 0, 100, 50, seq as f64, 0));
+            writer.write_batch(batch).unwrap();
+        }
+        assert_eq!(writer.batches_since_sync, 1);
+        writer.end_run().unwrap();
+
+        let path = dir.join("run0006_0000_SYNC.delila");
+        let mut reader = DataFileReader::new(File::open(&path).unwrap()).unwrap();
+        assert!(reader.validate().is_valid);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_flush_persists_without_rotating() {
+        let dir = std::env::temp_dir().join(format!("delila_flush_test_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let config = RecorderConfig {
+            output_dir: dir.clone(),
+            ..Default::default()
+        };
+        let stats = Arc::new(AtomicStats::new());
+        let mut writer = FileWriter::new(config, stats.clone());
+        writer.new_run(RunConfig {
+            run_number: 8,
+            exp_name: "FLUSH".to_string(),
+            ..Default::default()
+        });
+        writer.start_run(8);
+        for seq in 0..3u64 {
+            let mut batch = EventDataBatch::new(0, seq);
+            batch.push(crate::common::EventData::new(0, 0, 100, 50, seq as f64, 0));
+            writer.write_batch(batch).unwrap();
+        }
+
+        // Small batches are still in the 64 KiB write buffer
+        let path = dir.join("run0008_0000_FLUSH.delila");
+        assert!(fs::metadata(&path).unwrap().len() < writer.current_file_size);
+
+        writer.flush().unwrap();
+        assert_eq!(fs::metadata(&path).unwrap().len(), writer.current_file_size);
+        assert!(writer.is_open());
+        assert_eq!(stats.snapshot().files_written, 0);
+
+        // The file is still finished normally on close
+        writer.end_run().unwrap();
+        assert_eq!(stats.snapshot().files_written, 1);
+        let mut reader = DataFileReader::new(File::open(&path).unwrap()).unwrap();
+        assert!(reader.validate().is_valid);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
     #[test]
     fn test_filename_generation() {
         let config = RecorderConfig {
@@ -905,4 +1692,591 @@ mod tests {
         let path = writer.generate_filename();
         assert_eq!(path.to_str().unwrap(), "/data/run0042_0005_CRIB2026.delila");
     }
+
+    #[test]
+    fn test_existing_files_are_never_reused() {
+        let dir = std::env::temp_dir().join(format!("delila_exists_test_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let config = RecorderConfig {
+            output_dir: dir.clone(),
+            ..Default::default()
+        };
+        let stats = Arc::new(AtomicStats::new());
+        let mut writer = FileWriter::new(config, stats);
+        writer.new_run(RunConfig {
+            run_number: 7,
+            exp_name: "DUP".to_string(),
+            ..Default::default()
+        });
+
+        // Several fallbacks within the same second must all be new files
+        let mut seen = Vec::new();
+        for _ in 0..4 {
+            let path = writer.generate_filename();
+            assert!(!path.exists(), "{} already exists", path.display());
+            assert!(!seen.contains(&path));
+            fs::write(&path, b"taken").unwrap();
+            seen.push(path);
+        }
+        assert_eq!(seen[0], dir.join("run0007_0000_DUP.delila"));
+        for path in &seen {
+            assert_eq!(fs::read(path).unwrap(), b"taken");
+        }
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_filename_template_with_date() {
+        let config = RecorderConfig {
+            output_dir: PathBuf::from("/data"),
+            filename_template: "{exp}_{date}_run{run}_{seq}".to_string(),
+            ..Default::default()
+        };
+        let stats = Arc::new(AtomicStats::new());
+        let mut writer = FileWriter::new(config, stats);
+        writer.new_run(RunConfig {
+            run_number: 42,
+            exp_name: "CRIB2026".to_string(),
+            ..Default::default()
+        });
+
+        let date = chrono::Local::now().format("%Y%m%d").to_string();
+        let path = writer.generate_filename();
+        assert_eq!(
+            path.to_str().unwrap(),
+            format!("/data/CRIB2026_{date}_run0042_0000.delila")
+        );
+    }
+
+    #[test]
+    fn test_routed_writers_use_stream_suffix() {
+        let config = RecorderConfig {
+            output_dir: PathBuf::from("/data"),
+            psd_routing: Some(PsdRouting {
+                cut: PsdCut::Line {
+                    slope: 0.8,
+                    intercept: 0.0,
+                },
+                inside_stream: "neutron".to_string(),
+                outside_stream: "gamma".to_string(),
+            }),
+            ..Default::default()
+        };
+        let stats = Arc::new(AtomicStats::new());
+        let mut writers = FileWriter::create_writers(config, stats);
+        assert_eq!(writers.len(), 2);
+
+        for writer in writers.iter_mut() {
+            writer.new_run(RunConfig {
+                run_number: 7,
+                exp_name: "PSD".to_string(),
+                ..Default::default()
+            });
+        }
+        assert_eq!(
+            writers[0].generate_filename().to_str().unwrap(),
+            "/data/run0007_0000_PSD_neutron.delila"
+        );
+        assert_eq!(
+            writers[1].generate_filename().to_str().unwrap(),
+            "/data/run0007_0000_PSD_gamma.delila"
+        );
+        assert!(writers[0].metadata["psd_cut"].contains("line"));
+    }
+
+    #[test]
+    fn test_zstd_compressed_file_roundtrip() {
+        use std::io::Read;
+
+        let dir = std::env::temp_dir().join(format!("delila_zstd_test_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let config = RecorderConfig {
+            output_dir: dir.clone(),
+            compression: CompressionKind::Zstd,
+            ..Default::default()
+        };
+        let stats = Arc::new(AtomicStats::new());
+        let mut writer = FileWriter::new(config, stats);
+        writer.new_run(RunConfig {
+            run_number: 3,
+            exp_name: "ZSTD".to_string(),
+            ..Default::default()
+        });
+        writer.start_run(3);
+
+        for seq in 0..3u64 {
+            let mut batch = EventDataBatch::new(1, seq);
+            for i in 0..100u16 {
+                batch.push(crate::common::EventData::new(
+                    0,
+                    (i % 16) as u8,
+                    i.into(),
+                    i / 2,
+                    (seq * 1000 + i as u64) as f64,
+                    0,
+                ));
+            }
+            writer.write_batch(batch).unwrap();
+        }
+        writer.end_run().unwrap();
+
+        let path = dir.join("run0003_0000_ZSTD.delila.zst");
+        assert_eq!(CompressionKind::from_path(&path), CompressionKind::Zstd);
+        let mut decompressed = Vec::new();
+        CompressionKind::Zstd
+            .decoder(File::open(&path).unwrap())
+            .unwrap()
+            .read_to_end(&mut decompressed)
+            .unwrap();
+
+        let mut reader = DataFileReader::new(std::io::Cursor::new(decompressed)).unwrap();
+        assert_eq!(reader.header().unwrap().run_number, 3);
+        let batches: Vec<EventDataBatch> = reader.data_blocks().map(|b| b.unwrap()).collect();
+        assert_eq!(batches.len(), 3);
+        assert_eq!(batches.iter().map(|b| b.events.len()).sum::<usize>(), 300);
+
+        let sample = &batches[1].events[42];
+        assert_eq!(sample.channel, 42 % 16);
+        assert_eq!(sample.energy, 42);
+        assert_eq!(sample.energy_short, 21);
+        assert_eq!(sample.timestamp_ns, 1042.0);
+
+        assert_eq!(reader.read_footer().unwrap().total_events, 300);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_flush_command_replies_after_fsync() {
+        use crate::common::Command;
+
+        let dir = std::env::temp_dir().join(format!("delila_flush_ack_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let config = RecorderConfig {
+            output_dir: dir.clone(),
+            ..Default::default()
+        };
+        let stats = Arc::new(AtomicStats::new());
+        let shared_state = Arc::new(tokio::sync::Mutex::new(ComponentSharedState::new()));
+        let (state_tx, _state_rx) = watch::channel(ComponentState::Running);
+        let (tx, rx) = mpsc::unbounded_channel();
+        let handle = tokio::spawn(Recorder::writer_task(
+            rx,
+            config.clone(),
+            stats.clone(),
+            state_tx.subscribe(),
+            shared_state,
+            state_tx.clone(),
+        ));
+
+        tx.send(WriterCommand::NewRun(RunConfig {
+            run_number: 9,
+            exp_name: "FLUSH".to_string(),
+            ..Default::default()
+        }))
+        .unwrap();
+        tx.send(WriterCommand::DrainAndStart { run_number: 9 })
+            .unwrap();
+        sync_writer(&tx).await;
+        let mut batch_bytes = 0;
+        for seq in 0..3u64 {
+            let mut batch = EventDataBatch::new(0, seq);
+            batch.push(crate::common::EventData::new(0, 0, 100, 50, seq as f64, 0));
+            batch_bytes += batch.to_msgpack().unwrap().len() as u64;
+            tx.send(WriterCommand::WriteBatch(batch)).unwrap();
+        }
+
+        let mut state = ComponentSharedState::new();
+        state.state = ComponentState::Running;
+        let mut ext = RecorderCommandExt {
+            stats: stats.clone(),
+            rate_tracker: Arc::new(RateTracker::new()),
+            writer_tx: tx.clone(),
+            config: Arc::new(parking_lot::Mutex::new(config)),
+        };
+        let resp = handle_command(&mut state, &state_tx, Command::Flush, Some(&mut ext));
+        assert!(resp.success, "{}", resp.message);
+
+        // The reply comes after the batches queued before it are on disk
+        // (without the flush they would still sit in the write buffer)
+        let path = dir.join("run0009_0000_FLUSH.delila");
+        assert!(fs::metadata(&path).unwrap().len() >= batch_bytes);
+        assert_eq!(stats.snapshot().files_written, 0);
+
+        tx.send(WriterCommand::Shutdown).unwrap();
+        handle.await.unwrap();
+
+        // With the writer gone the command fails instead of claiming success
+        let resp = handle_command(&mut state, &state_tx, Command::Flush, Some(&mut ext));
+        assert!(!resp.success);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_finish_on_all_eos_closes_after_last_source() {
+        let dir = std::env::temp_dir().join(format!("delila_eos_test_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let config = RecorderConfig {
+            output_dir: dir.clone(),
+            finish_on_all_eos: true,
+            expected_source_ids: vec![0, 1],
+            ..Default::default()
+        };
+        let stats = Arc::new(AtomicStats::new());
+        let shared_state = Arc::new(tokio::sync::Mutex::new(ComponentSharedState::new()));
+        shared_state.lock().await.state = ComponentState::Running;
+        let (state_tx, mut state_rx) = watch::channel(ComponentState::Running);
+        let (tx, rx) = mpsc::unbounded_channel();
+
+        let handle = tokio::spawn(Recorder::writer_task(
+            rx,
+            config,
+            stats.clone(),
+            state_tx.subscribe(),
+            shared_state.clone(),
+            state_tx,
+        ));
+
+        tx.send(WriterCommand::NewRun(RunConfig {
+            run_number: 5,
+            exp_name: "EOS".to_string(),
+            ..Default::default()
+        }))
+        .unwrap();
+        tx.send(WriterCommand::DrainAndStart { run_number: 5 })
+            .unwrap();
+        sync_writer(&tx).await;
+        let mut batch = EventDataBatch::new(0, 0);
+        batch.push(crate::common::EventData::new(0, 0, 1, 1, 10.0, 0));
+        tx.send(WriterCommand::WriteBatch(batch)).unwrap();
+        tx.send(WriterCommand::EndOfStream { source_id: 0 })
+            .unwrap();
+
+        // Source 1 is still recording after source 0 finished
+        let mut batch = EventDataBatch::new(1, 0);
+        batch.push(crate::common::EventData::new(1, 0, 2, 2, 20.0, 0));
+        tx.send(WriterCommand::WriteBatch(batch)).unwrap();
+        tx.send(WriterCommand::EndOfStream { source_id: 1 })
+            .unwrap();
+
+        tokio::time::timeout(Duration::from_secs(5), state_rx.changed())
+            .await
+            .expect("run finished")
+            .unwrap();
+        assert_eq!(*state_rx.borrow(), ComponentState::Configured);
+        assert_eq!(shared_state.lock().await.state, ComponentState::Configured);
+        assert_eq!(stats.snapshot().files_written, 1);
+
+        let path = dir.join("run0005_0000_EOS.delila");
+        let mut reader = DataFileReader::new(File::open(&path).unwrap()).unwrap();
+        assert_eq!(reader.read_footer().unwrap().total_events, 2);
+
+        tx.send(WriterCommand::Shutdown).unwrap();
+        handle.await.unwrap();
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_rotation_by_event_count() {
+        let dir = std::env::temp_dir().join(format!("delila_events_test_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let config = RecorderConfig {
+            output_dir: dir.clone(),
+            max_file_events: Some(250),
+            ..Default::default()
+        };
+        let stats = Arc::new(AtomicStats::new());
+        let mut writer = FileWriter::new(config, stats);
+        writer.new_run(RunConfig {
+            run_number: 4,
+            exp_name: "EVT".to_string(),
+            ..Default::default()
+        });
+        writer.start_run(4);
+
+        // 6 x 100 events with a limit of 250 -> 250 + 250 + 100
+        for seq in 0..6u64 {
+            let mut batch = EventDataBatch::new(0, seq);
+            for i in 0..100u64 {
+                batch.push(crate::common::EventData::new(
+                    0,
+                    0,
+                    100,
+                    50,
+                    (seq * 100 + i) as f64,
+                    0,
+                ));
+            }
+            writer.write_batch(batch).unwrap();
+        }
+        writer.end_run().unwrap();
+
+        let counts: Vec<(u64, f64)> = (0..3)
+            .map(|seq| {
+                let path = dir.join(format!("run0004_{:04}_EVT.delila", seq));
+                let mut reader = DataFileReader::new(File::open(&path).unwrap()).unwrap();
+                let first = reader.data_blocks().next().unwrap().unwrap().events[0].timestamp_ns;
+                (reader.read_footer().unwrap().total_events, first)
+            })
+            .collect();
+        assert_eq!(counts, vec![(250, 0.0), (250, 250.0), (100, 500.0)]);
+        assert!(!dir.join("run0004_0003_EVT.delila").exists());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_new_file_writes_metadata_sidecar() {
+        let dir = std::env::temp_dir().join(format!("delila_sidecar_test_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let config = RecorderConfig {
+            output_dir: dir.clone(),
+            ..Default::default()
+        };
+        let stats = Arc::new(AtomicStats::new());
+        let mut writer = FileWriter::new(config, stats);
+        writer.new_run(RunConfig {
+            run_number: 42,
+            exp_name: "Exp".to_string(),
+            comment: "beam on target".to_string(),
+        });
+        writer.start_run(42);
+
+        let mut batch = EventDataBatch::new(0, 0);
+        batch.push(crate::common::EventData::new(0, 0, 100, 50, 1.0, 0));
+        writer.write_batch(batch).unwrap();
+
+        let data_path = dir.join("run0042_0000_Exp.delila");
+        assert_eq!(
+            sidecar_path(&data_path),
+            dir.join("run0042_0000_Exp.delila.meta.json")
+        );
+        let meta = FileMetadata::read(&data_path).unwrap();
+        assert_eq!(meta.file_name, "run0042_0000_Exp.delila");
+        assert_eq!(meta.run_number, 42);
+        assert_eq!(meta.exp_name, "Exp");
+        assert_eq!(meta.comment, "beam on target");
+        assert_eq!(meta.file_sequence, 0);
+        assert_eq!(meta.delila_version, env!("CARGO_PKG_VERSION"));
+        assert!(!meta.host.is_empty());
+        assert!(meta.closed_at.is_none());
+
+        // Closing the file adds the close time and event count
+        writer.end_run().unwrap();
+        let meta = FileMetadata::read(&data_path).unwrap();
+        assert!(meta.closed_at.unwrap() >= meta.started_at);
+        assert!(meta.duration_secs.unwrap() >= 0.0);
+        assert_eq!(meta.total_events, Some(1));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_low_disk_space_refuses_new_file() {
+        let dir = std::env::temp_dir().join(format!("delila_disk_test_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let config = RecorderConfig {
+            output_dir: dir.clone(),
+            min_free_bytes: 1_000_000,
+            ..Default::default()
+        };
+        let stats = Arc::new(AtomicStats::new());
+        let mut writer = FileWriter::new(config, stats.clone());
+        writer.free_space = |_| Ok(999_999);
+        writer.new_run(RunConfig {
+            run_number: 3,
+            exp_name: "DISK".to_string(),
+            ..Default::default()
+        });
+        writer.start_run(3);
+
+        let mut batch = EventDataBatch::new(0, 0);
+        batch.push(crate::common::EventData::new(0, 0, 100, 50, 1.0, 0));
+        let result = writer.write_batch(batch.clone());
+        assert!(matches!(
+            result,
+            Err(RecorderError::DiskFull {
+                free: 999_999,
+                required: 1_000_000
+            })
+        ));
+        assert!(!writer.is_open());
+        assert!(!dir.join("run0003_0000_DISK.delila").exists());
+        assert_eq!(stats.snapshot().free_bytes, Some(999_999));
+
+        // Enough space again: the file is opened normally
+        writer.free_space = |_| Ok(1_000_000);
+        writer.write_batch(batch).unwrap();
+        assert!(writer.is_open());
+        writer.end_run().unwrap();
+        assert!(dir.join("run0003_0000_DISK.delila").exists());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_index_offsets_point_at_frames() {
+        use std::io::{Read, Seek, SeekFrom};
+
+        let dir = std::env::temp_dir().join(format!("delila_index_test_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let config = RecorderConfig {
+            output_dir: dir.clone(),
+            ..Default::default()
+        };
+        let stats = Arc::new(AtomicStats::new());
+        let mut writer = FileWriter::new(config, stats);
+        writer.new_run(RunConfig {
+            run_number: 9,
+            exp_name: "IDX".to_string(),
+            ..Default::default()
+        });
+        writer.start_run(9);
+
+        for seq in 0..5u64 {
+            let mut batch = EventDataBatch::new(0, seq);
+            for i in 0..=seq {
+                batch.push(crate::common::EventData::new(
+                    0,
+                    0,
+                    100,
+                    50,
+                    (seq * 10_000 + i) as f64,
+                    0,
+                ));
+            }
+            writer.write_batch(batch).unwrap();
+        }
+        writer.end_run().unwrap();
+
+        let path = dir.join("run0009_0000_IDX.delila");
+        let index = delila_index(&path)
+            .unwrap()
+            .expect("file should have an index");
+        assert_eq!(index.entries.len(), 5);
+
+        let mut file = File::open(&path).unwrap();
+        for (seq, &(first_ts, offset)) in index.entries.iter().enumerate() {
+            file.seek(SeekFrom::Start(offset)).unwrap();
+            let mut len_bytes = [0u8; 4];
+            file.read_exact(&mut len_bytes).unwrap();
+            let mut data = vec![0u8; u32::from_le_bytes(len_bytes) as usize];
+            file.read_exact(&mut data).unwrap();
+
+            let batch = EventDataBatch::from_msgpack(&data).unwrap();
+            assert_eq!(batch.sequence_number, seq as u64);
+            assert_eq!(batch.events[0].timestamp_ns as u64, first_ts);
+        }
+        assert_eq!(index.seek_offset(30_000), Some(index.entries[3].1));
+
+        // The index does not disturb validation or sequential reading
+        let mut reader = DataFileReader::new(File::open(&path).unwrap()).unwrap();
+        let result = reader.validate();
+        assert!(result.is_valid, "{:?}", result.errors);
+        assert_eq!(result.recoverable_blocks, 5);
+        assert_eq!(reader.data_blocks().count(), 5);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_frame_crc_flags_corrupted_frame() {
+        let dir = std::env::temp_dir().join(format!("delila_crc_test_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let config = RecorderConfig {
+            output_dir: dir.clone(),
+            write_checksums: true,
+            ..Default::default()
+        };
+        let stats = Arc::new(AtomicStats::new());
+        let mut writer = FileWriter::new(config, stats);
+        writer.new_run(RunConfig {
+            run_number: 5,
+            exp_name: "CRC".to_string(),
+            ..Default::default()
+        });
+        writer.start_run(5);
+        for seq in 0..4u64 {
+            let mut batch = EventDataBatch::new(0, seq);
+            for i in 0..10u16 {
+                batch.push(crate::common::EventData::new(
+                    0,
+                    1,
+                    i.into(),
+                    i,
+                    i as f64,
+                    0,
+                ));
+            }
+            writer.write_batch(batch).unwrap();
+        }
+        writer.end_run().unwrap();
+
+        let path = dir.join("run0005_0000_CRC.delila");
+        let mut bytes = fs::read(&path).unwrap();
+
+        // Clean file: version 3, valid, no mismatches, blocks readable
+        let mut reader = DataFileReader::new(std::io::Cursor::new(bytes.clone())).unwrap();
+        assert_eq!(reader.header().unwrap().version, FORMAT_VERSION_CRC);
+        assert!(reader.validate().is_valid);
+        assert!(reader.verify_frame_crcs().unwrap().is_empty());
+        assert_eq!(reader.data_blocks().count(), 4);
+
+        // Flip one payload byte in frame 2 (skip CRC + length prefix)
+        let index = delila_index(&path).unwrap().unwrap();
+        let frame_offset = index.entries[2].1;
+        bytes[frame_offset as usize + 8 + 5] ^= 0xFF;
+
+        let mut reader = DataFileReader::new(std::io::Cursor::new(bytes)).unwrap();
+        let mismatches = reader.verify_frame_crcs().unwrap();
+        assert_eq!(mismatches.len(), 1);
+        assert_eq!(mismatches[0].frame, 2);
+        assert_eq!(mismatches[0].offset, frame_offset);
+        assert_ne!(mismatches[0].stored, mismatches[0].computed);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[cfg(feature = "root-export")]
+    #[test]
+    fn test_root_format_writes_tree() {
+        let dir = std::env::temp_dir().join(format!("delila_root_rec_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let config = RecorderConfig {
+            output_dir: dir.clone(),
+            format: RecorderFormat::RootTree,
+            ..Default::default()
+        };
+        let stats = Arc::new(AtomicStats::new());
+        let mut writer = FileWriter::new(config, stats);
+        writer.new_run(RunConfig {
+            run_number: 12,
+            exp_name: "ROOT".to_string(),
+            ..Default::default()
+        });
+        writer.start_run(12);
+        for seq in 0..3u64 {
+            let mut batch = EventDataBatch::new(0, seq);
+            for i in 0..40u16 {
+                batch.push(crate::common::EventData::new(
+                    0,
+                    2,
+                    i.into(),
+                    i,
+                    i as f64,
+                    0,
+                ));
+            }
+            writer.write_batch(batch).unwrap();
+        }
+        writer.end_run().unwrap();
+
+        let path = dir.join("run0012_0000_ROOT.root");
+        let mut file = oxyroot::RootFile::open(&path).unwrap();
+        assert_eq!(file.get_tree(TREE_NAME).unwrap().entries(), 120);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
 }
@@ -0,0 +1,444 @@
+//! Paced, time-windowed reading of recorder files
+//!
+//! [`replay_file`] yields the batches of a `.delila` file in file order,
+//! keeping only events with `from_ts <= timestamp_ns < to_ts`. With a
+//! positive `speed`, each batch is due when the time since the first batch
+//! matches its first event's `timestamp_ns` offset divided by `speed`. This
+//! is the shared core of the emulator's replay source and offline tools.
+//!
+//! With `from_ts` set and a batch index in the file (see [`super::index`]),
+//! reading starts at the last batch beginning before `from_ts` instead of at
+//! the first one. That is exact for time-ordered recordings (Merger with
+//! `merge_by_timestamp`); in arrival-order files, earlier batches of a slower
+//! source may still hold in-range events and are skipped. Likewise, with
+//! `to_ts` set and index timestamps in order, reading stops at the first
+//! batch starting at or after `to_ts`.
+//!
+//! Compressed files (`.delila.gz` / `.delila.zst`) are decompressed as a
+//! stream, twice: a first pass only finds the decompressed size and the
+//! index at the end of the file, the second decodes the batches. Skipping to
+//! the `from_ts` block still decompresses the bytes in front of it.
+
+use std::fs::File;
+use std::io::{self, BufReader, Cursor, Read, Seek, SeekFrom};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use super::index::{trailer_len, INDEX_TRAILER_SIZE};
+use super::{CompressionKind, DataFileReader, FileFormatError, FileIndex, FOOTER_SIZE};
+use crate::common::EventDataBatch;
+
+/// Most decompressed bytes kept from the end of a file to read its index
+///
+/// An index beyond this (about a million batches) is ignored; replay then
+/// reads from the first block and to the end.
+const MAX_TAIL_BYTES: usize = 16 * 1024 * 1024;
+
+/// Readable, seekable input of a replayed file
+trait ReplayInput: Read + Seek + Send {}
+impl<T: Read + Seek + Send> ReplayInput for T {}
+
+/// Reader of a replayed file
+type ReplayReader = DataFileReader<Box<dyn ReplayInput>>;
+
+/// Forward-only [`Seek`] over a decompressing stream
+///
+/// Seeking forward skips the bytes in between; seeking back or from the end
+/// fails with [`io::ErrorKind::Unsupported`].
+struct StreamInput<R> {
+    inner: R,
+    pos: u64,
+}
+
+impl<R: Read> Read for StreamInput<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.pos += n as u64;
+        Ok(n)
+    }
+}
+
+impl<R: Read> Seek for StreamInput<R> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let target = match pos {
+            SeekFrom::Start(n) => Some(n),
+            SeekFrom::Current(n) => self.pos.checked_add_signed(n),
+            SeekFrom::End(_) => None,
+        };
+        let Some(skip) = target.and_then(|n| n.checked_sub(self.pos)) else {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "compressed input can only seek forward",
+            ));
+        };
+        let skipped = io::copy(&mut Read::take(&mut *self, skip), &mut io::sink())?;
+        if skipped < skip {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        Ok(self.pos)
+    }
+}
+
+/// Pacing of batches by their event timestamps
+#[derive(Debug, Clone)]
+pub struct ReplayPacer {
+    speed: f64,
+    start: Option<Instant>,
+    first_ts: Option<f64>,
+    latest_offset_ns: f64,
+}
+
+impl ReplayPacer {
+    /// Pacer for `speed` times the recorded rate (0.0 = as fast as possible)
+    pub fn new(speed: f64) -> Self {
+        Self {
+            speed,
+            start: None,
+            first_ts: None,
+            latest_offset_ns: 0.0,
+        }
+    }
+
+    /// Instant at which `batch` is due; the first call starts the clock
+    ///
+    /// Batches without events, and every batch when pacing is off, are due
+    /// immediately.
+    pub fn due(&mut self, batch: &EventDataBatch) -> Instant {
+        let now = Instant::now();
+        let start = *self.start.get_or_insert(now);
+        if !(self.speed > 0.0 && self.speed.is_finite()) {
+            return now;
+        }
+        let Some(ts) = batch.events.first().map(|e| e.timestamp_ns) else {
+            return now;
+        };
+        let first = *self.first_ts.get_or_insert(ts);
+        // Never wait for a batch that starts earlier than one already due
+        self.latest_offset_ns = self.latest_offset_ns.max(ts - first);
+        start + Duration::from_secs_f64(self.latest_offset_ns / 1e9 / self.speed)
+    }
+}
+
+/// Batches of a recorder file within a time window (see [`replay_file`])
+pub struct FileReplay {
+    reader: ReplayReader,
+    /// Offset of the first block to read; `None` once reading has started
+    start_offset: Option<u64>,
+    from_ts: Option<f64>,
+    to_ts: Option<f64>,
+    /// Index timestamps are in order: no batch after one starting at `to_ts`
+    /// holds in-range events
+    ordered: bool,
+    pacer: ReplayPacer,
+    done: bool,
+}
+
+/// Open `path` for replay of the events in `[from_ts, to_ts)` at `speed`
+///
+/// `speed` is relative to the recording (1.0 = original timing, 0.0 = as
+/// fast as possible). Iterating the result waits for each batch in the
+/// calling thread; async callers use [`FileReplay::next_batch`] and wait for
+/// the returned instant themselves.
+pub fn replay_file(
+    path: &Path,
+    speed: f64,
+    from_ts: Option<f64>,
+    to_ts: Option<f64>,
+) -> Result<FileReplay, FileFormatError> {
+    let (reader, index) = open(path)?;
+    // Index timestamps are truncated to whole ns: seek to a block that
+    // starts strictly before the window so none of its events are missed
+    let start_offset = match (from_ts, &index) {
+        (Some(ts), Some(index)) => index.seek_offset((ts.max(0.0) as u64).saturating_sub(1)),
+        _ => None,
+    };
+    let ordered = index.is_some_and(|index| index.entries.windows(2).all(|w| w[0].0 <= w[1].0));
+    Ok(FileReplay {
+        reader,
+        start_offset: Some(start_offset.unwrap_or(0)),
+        from_ts,
+        to_ts,
+        ordered,
+        pacer: ReplayPacer::new(speed),
+        done: false,
+    })
+}
+
+/// Open a recorder file and read its batch index, if it has one
+fn open(path: &Path) -> Result<(ReplayReader, Option<FileIndex>), FileFormatError> {
+    let kind = CompressionKind::from_path(path);
+    if kind == CompressionKind::None {
+        let input: Box<dyn ReplayInput> = Box::new(BufReader::new(File::open(path)?));
+        let mut reader = DataFileReader::new(input)?;
+        let index = reader.read_index()?;
+        return Ok((reader, index));
+    }
+
+    let (file_size, data_end, index) = scan_compressed(path, kind)?;
+    let input: Box<dyn ReplayInput> = Box::new(StreamInput {
+        inner: kind.decoder(BufReader::new(File::open(path)?))?,
+        pos: 0,
+    });
+    let reader = DataFileReader::with_layout(input, file_size, data_end)?;
+    Ok((reader, index))
+}
+
+/// Decompressed size, end of the data blocks and index of a compressed file
+///
+/// Decompresses the whole file once, keeping at most [`MAX_TAIL_BYTES`] of
+/// its end.
+fn scan_compressed(
+    path: &Path,
+    kind: CompressionKind,
+) -> Result<(u64, u64, Option<FileIndex>), FileFormatError> {
+    let mut decoder = kind.decoder(BufReader::new(File::open(path)?))?;
+    let mut chunk = vec![0u8; 64 * 1024];
+    let mut tail = Vec::new();
+    let mut file_size = 0u64;
+    loop {
+        let n = match decoder.read(&mut chunk) {
+            Ok(0) => break,
+            Ok(n) => n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e.into()),
+        };
+        file_size += n as u64;
+        tail.extend_from_slice(&chunk[..n]);
+        if tail.len() > 2 * MAX_TAIL_BYTES {
+            tail.drain(..tail.len() - MAX_TAIL_BYTES);
+        }
+    }
+    let tail_start = file_size - tail.len() as u64;
+
+    // Same layout rules as DataFileReader::new, on the kept tail
+    let no_index = if file_size >= FOOTER_SIZE as u64 {
+        file_size - FOOTER_SIZE as u64
+    } else {
+        file_size
+    };
+    let Some(trailer_at) = tail.len().checked_sub(FOOTER_SIZE + INDEX_TRAILER_SIZE) else {
+        return Ok((file_size, no_index, None));
+    };
+    let trailer = tail[trailer_at..trailer_at + INDEX_TRAILER_SIZE]
+        .try_into()
+        .expect("trailer-sized slice");
+    let Some(len) = trailer_len(trailer) else {
+        return Ok((file_size, no_index, None));
+    };
+    let Some(data_end) = (tail_start + trailer_at as u64).checked_sub(len) else {
+        return Ok((file_size, no_index, None));
+    };
+    let index = match (trailer_at as u64).checked_sub(len) {
+        Some(start) => FileIndex::read_from(&mut Cursor::new(&tail[start as usize..]))?,
+        None => None,
+    };
+    Ok((file_size, data_end, index))
+}
+
+impl FileReplay {
+    /// Next batch with in-range events and the instant it is due, without waiting
+    ///
+    /// Events outside the window are removed; batches left without events
+    /// are skipped. Reading stops after the first error.
+    pub fn next_batch(&mut self) -> Option<Result<(EventDataBatch, Instant), FileFormatError>> {
+        while !self.done {
+            let next = match self.start_offset.take() {
+                Some(offset) => self.reader.data_blocks_from(offset).next(),
+                None => self.reader.resume_data_blocks().next(),
+            };
+            let mut batch = match next {
+                Some(Ok(batch)) => batch,
+                Some(Err(e)) => {
+                    self.done = true;
+                    return Some(Err(e));
+                }
+                None => {
+                    self.done = true;
+                    return None;
+                }
+            };
+
+            let (from_ts, to_ts) = (self.from_ts, self.to_ts);
+            if self.ordered {
+                let first_ts = batch.events.first().map(|e| e.timestamp_ns);
+                if let (Some(ts), Some(to)) = (first_ts, to_ts) {
+                    if ts >= to {
+                        self.done = true;
+                        return None;
+                    }
+                }
+            }
+            let had_events = !batch.events.is_empty();
+            batch.events.retain(|e| {
+                from_ts.is_none_or(|from| e.timestamp_ns >= from)
+                    && to_ts.is_none_or(|to| e.timestamp_ns < to)
+            });
+            if had_events && batch.events.is_empty() {
+                continue;
+            }
+
+            let due = self.pacer.due(&batch);
+            return Some(Ok((batch, due)));
+        }
+        None
+    }
+}
+
+impl Iterator for FileReplay {
+    type Item = Result<EventDataBatch, FileFormatError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(self.next_batch()?.map(|(batch, due)| {
+            std::thread::sleep(due.saturating_duration_since(Instant::now()));
+            batch
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::super::{delila_index, AtomicStats, FileWriter, RecorderConfig};
+    use super::*;
+    use crate::common::{EventData, RunConfig};
+
+    /// Write `batches` to a new file in `dir`
+    fn write_file(
+        dir: &Path,
+        run_number: u32,
+        compression: CompressionKind,
+        batches: Vec<EventDataBatch>,
+    ) -> std::path::PathBuf {
+        let _ = std::fs::remove_dir_all(dir);
+        let config = RecorderConfig {
+            output_dir: dir.to_path_buf(),
+            compression,
+            ..Default::default()
+        };
+        let mut writer = FileWriter::new(config, Arc::new(AtomicStats::new()));
+        writer.new_run(RunConfig {
+            run_number,
+            exp_name: "REPLAY".to_string(),
+            ..Default::default()
+        });
+        writer.start_run(run_number);
+        for batch in batches {
+            writer.write_batch(batch).unwrap();
+        }
+        writer.end_run().unwrap();
+        dir.join(format!(
+            "run{:04}_0000_REPLAY.delila{}",
+            run_number,
+            compression.suffix()
+        ))
+    }
+
+    /// `batches` batches of 10 events, `spacing_ns` apart per batch
+    fn batches(batches: u64, spacing_ns: f64) -> Vec<EventDataBatch> {
+        (0..batches)
+            .map(|seq| {
+                let mut batch = EventDataBatch::new(0, seq);
+                for i in 0..10 {
+                    let ts = seq as f64 * spacing_ns + i as f64 * spacing_ns / 10.0;
+                    batch.push(EventData::new(0, 0, 100, 50, ts, 0));
+                }
+                batch
+            })
+            .collect()
+    }
+
+    /// Record `batches` batches of 10 events, `spacing_ns` apart per batch
+    fn record(dir: &Path, run_number: u32, count: u64, spacing_ns: f64) -> std::path::PathBuf {
+        write_file(
+            dir,
+            run_number,
+            CompressionKind::None,
+            batches(count, spacing_ns),
+        )
+    }
+
+    #[test]
+    fn test_seek_yields_only_in_range_events() {
+        let dir = std::env::temp_dir().join(format!("delila_seek_test_{}", std::process::id()));
+        let path = record(&dir, 21, 5, 1000.0);
+
+        let mut replay = replay_file(&path, 0.0, Some(2050.0), Some(3500.0)).unwrap();
+        // The index skips the first two batches
+        let index = delila_index(&path).unwrap().unwrap();
+        assert_eq!(replay.start_offset, Some(index.entries[2].1));
+
+        let batches: Vec<EventDataBatch> = replay.by_ref().map(Result::unwrap).collect();
+        assert_eq!(
+            batches
+                .iter()
+                .map(|b| b.sequence_number)
+                .collect::<Vec<_>>(),
+            vec![2, 3]
+        );
+        let timestamps: Vec<f64> = batches
+            .iter()
+            .flat_map(|b| b.events.iter().map(|e| e.timestamp_ns))
+            .collect();
+        assert_eq!(timestamps.len(), 9 + 5);
+        assert!(timestamps.iter().all(|ts| (2050.0..3500.0).contains(ts)));
+        assert!(replay.next().is_none());
+
+        // Without a window every event is replayed
+        let all = replay_file(&path, 0.0, None, None).unwrap();
+        assert_eq!(all.map(|b| b.unwrap().events.len()).sum::<usize>(), 50);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_compressed_file_is_streamed() {
+        let dir = std::env::temp_dir().join(format!("delila_zst_seek_test_{}", std::process::id()));
+        let path = write_file(&dir, 23, CompressionKind::Zstd, batches(5, 1000.0));
+
+        let mut replay = replay_file(&path, 0.0, Some(2050.0), Some(3500.0)).unwrap();
+        assert!(replay.ordered);
+        let timestamps: Vec<f64> = replay
+            .by_ref()
+            .flat_map(|b| b.unwrap().events)
+            .map(|e| e.timestamp_ns)
+            .collect();
+        assert_eq!(timestamps.len(), 9 + 5);
+        assert!(timestamps.iter().all(|ts| (2050.0..3500.0).contains(ts)));
+
+        // Without a window every event is replayed
+        let all = replay_file(&path, 0.0, None, None).unwrap();
+        assert_eq!(all.map(|b| b.unwrap().events.len()).sum::<usize>(), 50);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_ordered_file_stops_past_to_ts() {
+        let dir = std::env::temp_dir().join(format!("delila_stop_test_{}", std::process::id()));
+        // Batch 3 starts past the window but carries a stray early event
+        let mut batches = batches(5, 1000.0);
+        batches[3].push(EventData::new(0, 0, 100, 50, 100.0, 0));
+        let path = write_file(&dir, 24, CompressionKind::None, batches);
+
+        let replay = replay_file(&path, 0.0, None, Some(2500.0)).unwrap();
+        let sequences: Vec<u64> = replay.map(|b| b.unwrap().sequence_number).collect();
+        assert_eq!(sequences, vec![0, 1, 2]);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_speed_paces_batches() {
+        let dir = std::env::temp_dir().join(format!("delila_pace_test_{}", std::process::id()));
+        // Batches 10 ms apart in recorded time, replayed at 2x
+        let path = record(&dir, 22, 5, 10_000_000.0);
+
+        let started = Instant::now();
+        let replay = replay_file(&path, 2.0, None, None).unwrap();
+        assert_eq!(replay.count(), 5);
+        assert!(started.elapsed() >= Duration::from_millis(20));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
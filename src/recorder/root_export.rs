@@ -0,0 +1,159 @@
+//! ROOT TTree output backend (cargo feature `root-export`)
+//!
+//! Events are buffered column-wise while a file is open and written as a flat
+//! TTree named [`TREE_NAME`] when the file is closed (or rewritten with the
+//! entries so far on a Flush). File rotation by
+//! `max_file_size` bounds the buffer; the size counted per entry is
+//! [`ENTRY_BYTES`] ([`ENTRY_BYTES_WIDE`] with `wide_energy`).
+//!
+//! Branches: `module` (u8), `channel` (u8), `energy` (u16, clamped to 65535;
+//! u32 with `wide_energy`), `energy_short` (u16), `timestamp_ns` (f64),
+//! `flags` (u64).
+
+use std::path::{Path, PathBuf};
+
+use oxyroot::{RootFile, WriterTree};
+
+use crate::common::EventData;
+
+/// Name of the event tree in exported files
+pub const TREE_NAME: &str = "delila";
+
+/// Uncompressed size of one tree entry in bytes
+pub const ENTRY_BYTES: u64 = 1 + 1 + 2 + 2 + 8 + 8;
+
+/// Uncompressed size of one tree entry with a 32-bit `energy` branch
+pub const ENTRY_BYTES_WIDE: u64 = 1 + 1 + 4 + 2 + 8 + 8;
+
+/// Branch buffers of one tree
+#[derive(Clone, Default)]
+struct Columns {
+    module: Vec<u8>,
+    channel: Vec<u8>,
+    energy: Vec<u32>,
+    energy_short: Vec<u16>,
+    timestamp_ns: Vec<f64>,
+    flags: Vec<u64>,
+    /// Write `energy` as u32 instead of clamping it to u16
+    wide_energy: bool,
+}
+
+impl Columns {
+    /// Write the columns as the tree of a new file at `path` and fsync it
+    fn write(self, path: &Path) -> std::io::Result<()> {
+        let to_io = |e: oxyroot::Error| std::io::Error::other(e.to_string());
+
+        let mut file = RootFile::create(path).map_err(to_io)?;
+        let mut tree = WriterTree::new(TREE_NAME);
+        tree.new_branch("module", self.module.into_iter());
+        tree.new_branch("channel", self.channel.into_iter());
+        if self.wide_energy {
+            tree.new_branch("energy", self.energy.into_iter());
+        } else {
+            let energy = self.energy.into_iter();
+            tree.new_branch("energy", energy.map(|e| e.min(u16::MAX as u32) as u16));
+        }
+        tree.new_branch("energy_short", self.energy_short.into_iter());
+        tree.new_branch("timestamp_ns", self.timestamp_ns.into_iter());
+        tree.new_branch("flags", self.flags.into_iter());
+        tree.write(&mut file).map_err(to_io)?;
+        file.close().map_err(to_io)?;
+        std::fs::File::open(path)?.sync_all()
+    }
+}
+
+/// Column buffers for one output file
+pub(crate) struct RootTreeWriter {
+    path: PathBuf,
+    columns: Columns,
+}
+
+impl RootTreeWriter {
+    /// Start a new file at `path` (written on [`finish`](Self::finish))
+    pub(crate) fn new(path: PathBuf, wide_energy: bool) -> Self {
+        Self {
+            path,
+            columns: Columns {
+                wide_energy,
+                ..Default::default()
+            },
+        }
+    }
+
+    /// Append events as tree entries; returns the bytes added
+    pub(crate) fn append(&mut self, events: &[EventData]) -> u64 {
+        let c = &mut self.columns;
+        for event in events {
+            c.module.push(event.module);
+            c.channel.push(event.channel);
+            c.energy.push(event.energy);
+            c.energy_short.push(event.energy_short);
+            c.timestamp_ns.push(event.timestamp_ns);
+            c.flags.push(event.flags);
+        }
+        let entry_bytes = if c.wide_energy {
+            ENTRY_BYTES_WIDE
+        } else {
+            ENTRY_BYTES
+        };
+        events.len() as u64 * entry_bytes
+    }
+
+    /// Output path of this file
+    pub(crate) fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Write the entries so far to the file and fsync it, keeping them buffered
+    ///
+    /// The tree cannot be appended to once written, so every flush (and the
+    /// final [`finish`](Self::finish)) rewrites the whole file.
+    pub(crate) fn sync(&self) -> std::io::Result<()> {
+        self.columns.clone().write(&self.path)
+    }
+
+    /// Write the tree and close the file
+    pub(crate) fn finish(self) -> std::io::Result<()> {
+        self.columns.write(&self.path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_root_tree_entry_count() {
+        let dir = std::env::temp_dir().join(format!("delila_root_test_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("run0001_0000_test.root");
+
+        let events: Vec<EventData> = (0..250u16)
+            .map(|i| EventData::new(0, (i % 16) as u8, i.into(), i / 2, i as f64 * 4.0, 0))
+            .collect();
+        let mut writer = RootTreeWriter::new(path.clone(), false);
+        assert_eq!(writer.append(&events[..100]), 100 * ENTRY_BYTES);
+
+        // A flush leaves a readable file with the entries so far
+        writer.sync().unwrap();
+        let mut file = RootFile::open(&path).unwrap();
+        assert_eq!(file.get_tree(TREE_NAME).unwrap().entries(), 100);
+
+        writer.append(&events[100..]);
+        writer.finish().unwrap();
+
+        let mut file = RootFile::open(&path).unwrap();
+        let tree = file.get_tree(TREE_NAME).unwrap();
+        assert_eq!(tree.entries(), 250);
+
+        // The 32-bit energy branch is opt-in
+        let wide_path = dir.join("run0001_0001_test.root");
+        let mut writer = RootTreeWriter::new(wide_path.clone(), true);
+        assert_eq!(writer.append(&events), 250 * ENTRY_BYTES_WIDE);
+        writer.finish().unwrap();
+        let mut file = RootFile::open(&wide_path).unwrap();
+        assert_eq!(file.get_tree(TREE_NAME).unwrap().entries(), 250);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,178 @@
+//! PSD cut-based event routing
+//!
+//! Splits each batch into two output streams by testing every event against a
+//! cut in the (energy, energy_short) plane. Used to write e.g. gamma and neutron
+//! events to separate files at acquisition time.
+//!
+//! Configuration (config.toml):
+//! ```toml
+//! [network.recorder.psd_routing]
+//! inside_stream = "neutron"
+//! outside_stream = "gamma"
+//! cut = { type = "line", slope = 0.8, intercept = 0.0 }
+//! ```
+
+use serde::{Deserialize, Serialize};
+
+use crate::common::EventDataBatch;
+
+/// Cut in the (energy, energy_short) plane
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum PsdCut {
+    /// Line `energy_short = slope * energy + intercept`; events below the line are inside
+    Line { slope: f64, intercept: f64 },
+    /// Closed polygon of `[energy, energy_short]` vertices; events within are inside
+    Polygon { vertices: Vec<[f64; 2]> },
+}
+
+impl PsdCut {
+    /// Test whether a point lies inside the cut
+    pub fn contains(&self, energy: f64, energy_short: f64) -> bool {
+        match self {
+            Self::Line { slope, intercept } => energy_short < slope * energy + intercept,
+            Self::Polygon { vertices } => point_in_polygon(vertices, energy, energy_short),
+        }
+    }
+}
+
+/// Even-odd ray casting test
+fn point_in_polygon(vertices: &[[f64; 2]], x: f64, y: f64) -> bool {
+    if vertices.len() < 3 {
+        return false;
+    }
+    let mut inside = false;
+    let mut prev = vertices[vertices.len() - 1];
+    for &[xi, yi] in vertices {
+        let [xj, yj] = prev;
+        if (yi > y) != (yj > y) && x < (xj - xi) * (y - yi) / (yj - yi) + xi {
+            inside = !inside;
+        }
+        prev = [xi, yi];
+    }
+    inside
+}
+
+/// Routing of events into two recorder streams by a PSD cut
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PsdRouting {
+    /// Cut definition
+    pub cut: PsdCut,
+    /// Stream name for events inside the cut (used as filename suffix)
+    #[serde(default = "default_inside_stream")]
+    pub inside_stream: String,
+    /// Stream name for events outside the cut (used as filename suffix)
+    #[serde(default = "default_outside_stream")]
+    pub outside_stream: String,
+}
+
+fn default_inside_stream() -> String {
+    "inside".to_string()
+}
+
+fn default_outside_stream() -> String {
+    "outside".to_string()
+}
+
+impl PsdRouting {
+    /// Split a batch into (inside, outside) batches
+    ///
+    /// Both batches keep the source ID, sequence number, timestamp and epoch of the input.
+    pub fn split(&self, batch: EventDataBatch) -> (EventDataBatch, EventDataBatch) {
+        let empty_like = |b: &EventDataBatch| EventDataBatch {
+            source_id: b.source_id,
+            sequence_number: b.sequence_number,
+            timestamp: b.timestamp,
+            events: Vec::with_capacity(b.events.len()),
+            fragment: b.fragment,
+            epoch: b.epoch,
+            waveform_encoding: b.waveform_encoding,
+        };
+        let mut inside = empty_like(&batch);
+        let mut outside = empty_like(&batch);
+
+        for event in batch.events {
+            if self
+                .cut
+                .contains(event.energy as f64, event.energy_short as f64)
+            {
+                inside.events.push(event);
+            } else {
+                outside.events.push(event);
+            }
+        }
+
+        (inside, outside)
+    }
+
+    /// Cut definition as JSON for file header metadata
+    pub fn to_metadata(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::EventData;
+
+    fn event(energy: u32, energy_short: u16) -> EventData {
+        EventData::new(0, 0, energy, energy_short, 0.0, 0)
+    }
+
+    #[test]
+    fn test_linear_cut_routes_both_sides() {
+        let routing = PsdRouting {
+            cut: PsdCut::Line {
+                slope: 0.8,
+                intercept: 0.0,
+            },
+            inside_stream: "neutron".to_string(),
+            outside_stream: "gamma".to_string(),
+        };
+
+        let mut batch = EventDataBatch::new(3, 17);
+        batch.push(event(1000, 900)); // above line → gamma
+        batch.push(event(1000, 500)); // below line → neutron
+        batch.push(event(2000, 1700)); // above line → gamma
+
+        let (neutron, gamma) = routing.split(batch);
+
+        assert_eq!(neutron.len(), 1);
+        assert_eq!({ neutron.events[0].energy_short }, 500);
+        assert_eq!(gamma.len(), 2);
+        assert_eq!(neutron.source_id, 3);
+        assert_eq!(gamma.sequence_number, 17);
+    }
+
+    #[test]
+    fn test_polygon_cut() {
+        let cut = PsdCut::Polygon {
+            vertices: vec![[0.0, 0.0], [100.0, 0.0], [100.0, 100.0], [0.0, 100.0]],
+        };
+        assert!(cut.contains(50.0, 50.0));
+        assert!(!cut.contains(150.0, 50.0));
+        assert!(!cut.contains(50.0, -1.0));
+    }
+
+    #[test]
+    fn test_routing_deserialize_and_metadata() {
+        let toml = r#"
+            inside_stream = "neutron"
+            cut = { type = "line", slope = 0.5, intercept = 10.0 }
+        "#;
+        let routing: PsdRouting = toml::from_str(toml).unwrap();
+        assert_eq!(
+            routing.cut,
+            PsdCut::Line {
+                slope: 0.5,
+                intercept: 10.0
+            }
+        );
+        assert_eq!(routing.outside_stream, "outside");
+
+        let json = routing.to_metadata();
+        let parsed: PsdRouting = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, routing);
+    }
+}
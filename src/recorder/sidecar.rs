@@ -0,0 +1,116 @@
+//! JSON metadata sidecar for each recorded file
+//!
+//! Next to `run0042_0000_Exp.delila` the recorder writes
+//! `run0042_0000_Exp.delila.meta.json` holding the run attributes, the host
+//! and the software version, so offline analysis can attribute a file to a
+//! run without parsing the binary header. The sidecar is written when the
+//! file is opened and rewritten with the close time, duration and event
+//! count when it is closed.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::common::RunConfig;
+
+/// Suffix appended to the data file name
+pub const SIDECAR_SUFFIX: &str = ".meta.json";
+
+/// Contents of a `.meta.json` sidecar
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FileMetadata {
+    /// Data file name (without directory)
+    pub file_name: String,
+    pub run_number: u32,
+    pub exp_name: String,
+    pub comment: String,
+    /// File sequence number within the run
+    pub file_sequence: u32,
+    /// Wall-clock time the file was opened
+    pub started_at: DateTime<Utc>,
+    /// Wall-clock time the file was closed (None while recording)
+    pub closed_at: Option<DateTime<Utc>>,
+    /// Seconds between open and close
+    pub duration_secs: Option<f64>,
+    /// Events in the file (set on close)
+    pub total_events: Option<u64>,
+    /// Host the recorder runs on
+    pub host: String,
+    /// DELILA version that wrote the file
+    pub delila_version: String,
+    /// Extra header metadata (e.g. routing stream and PSD cut)
+    pub metadata: HashMap<String, String>,
+}
+
+impl FileMetadata {
+    /// Metadata for a file that is being opened now
+    pub fn new(
+        data_path: &Path,
+        run_config: &RunConfig,
+        file_sequence: u32,
+        metadata: HashMap<String, String>,
+    ) -> Self {
+        Self {
+            file_name: data_path
+                .file_name()
+                .map(|n| n.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            run_number: run_config.run_number,
+            exp_name: run_config.exp_name.clone(),
+            comment: run_config.comment.clone(),
+            file_sequence,
+            started_at: Utc::now(),
+            closed_at: None,
+            duration_secs: None,
+            total_events: None,
+            host: gethostname::gethostname().to_string_lossy().into_owned(),
+            delila_version: env!("CARGO_PKG_VERSION").to_string(),
+            metadata,
+        }
+    }
+
+    /// Record the close time and event count
+    pub fn finish(&mut self, total_events: u64) {
+        let closed_at = Utc::now();
+        self.duration_secs =
+            Some((closed_at - self.started_at).num_milliseconds().max(0) as f64 / 1000.0);
+        self.closed_at = Some(closed_at);
+        self.total_events = Some(total_events);
+    }
+
+    /// Write (or overwrite) the sidecar of `data_path`
+    pub fn write(&self, data_path: &Path) -> io::Result<()> {
+        let json = serde_json::to_vec_pretty(self).map_err(io::Error::other)?;
+        fs::write(sidecar_path(data_path), json)
+    }
+
+    /// Read the sidecar of `data_path`
+    pub fn read(data_path: &Path) -> io::Result<Self> {
+        let json = fs::read(sidecar_path(data_path))?;
+        serde_json::from_slice(&json).map_err(io::Error::other)
+    }
+}
+
+/// Sidecar path of a data file
+pub fn sidecar_path(data_path: &Path) -> PathBuf {
+    let mut name = data_path.as_os_str().to_owned();
+    name.push(SIDECAR_SUFFIX);
+    PathBuf::from(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sidecar_path_appends_suffix() {
+        assert_eq!(
+            sidecar_path(Path::new("/data/run0042_0000_Exp.delila")),
+            PathBuf::from("/data/run0042_0000_Exp.delila.meta.json")
+        );
+    }
+}
@@ -404,6 +404,7 @@ fn test_decode_test_pulse_events() {
         module_id: 0,
         dump_enabled: false,
         num_channels: 32,
+        adc_bits: 16,
     });
 
     // Start acquisition
@@ -635,6 +636,7 @@ fn test_ch4_pulser_signal() {
         module_id: 0,
         dump_enabled: false,
         num_channels: 32,
+        adc_bits: 16,
     });
 
     // Acquire data
@@ -706,9 +708,9 @@ fn test_ch4_pulser_signal() {
     }
 
     // Energy statistics
-    let energies: Vec<u16> = ch4_events.iter().map(|e| e.energy).collect();
+    let energies: Vec<u32> = ch4_events.iter().map(|e| e.energy).collect();
     let energy_shorts: Vec<u16> = ch4_events.iter().map(|e| e.energy_short).collect();
-    let non_zero: Vec<u16> = energies.iter().filter(|&&e| e > 0).cloned().collect();
+    let non_zero: Vec<u32> = energies.iter().filter(|&&e| e > 0).cloned().collect();
 
     let (e_min, e_max) = (
         energies.iter().min().copied().unwrap_or(0),
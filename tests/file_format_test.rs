@@ -18,7 +18,7 @@ fn compute_checksum(ev: &EventData) -> u64 {
     (ev.module as u64)
         ^ ((ev.channel as u64) << 8)
         ^ ((ev.energy as u64) << 16)
-        ^ ((ev.energy_short as u64) << 32)
+        ^ ((ev.energy_short as u64) << 48)
         ^ ts
 }
 
@@ -45,7 +45,7 @@ fn verify_all_checksums<R: std::io::Read + std::io::Seek>(reader: &mut DataFileR
 fn make_random_event(rng: &mut StdRng) -> EventData {
     let module: u8 = rng.gen();
     let channel: u8 = rng.gen();
-    let energy: u16 = rng.gen();
+    let energy: u32 = rng.gen();
     let energy_short: u16 = rng.gen();
     // Positive timestamps only (realistic range: 0 .. 1e18 ns ≈ 31 years)
     let timestamp_ns: f64 = rng.gen_range(0.0..1e15);
@@ -0,0 +1,136 @@
+//! Integration test for the `ipc://` transport
+//!
+//! Runs an emulator and a DataSink with every data and command socket on
+//! `ipc://` paths (no TCP ports at all), drives both through the normal
+//! command sequence and checks that batches and the final EOS arrive.
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+use delila_rs::common::{Command, ComponentState, RunConfig};
+use delila_rs::data_sink::{DataSink, DataSinkConfig};
+use delila_rs::data_source_emulator::{Emulator, EmulatorConfig};
+use delila_rs::operator::ComponentClient;
+use tokio::sync::broadcast;
+
+/// Socket file for `name`, unique per test process
+fn socket_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("delila_ipc_test_{}_{}", std::process::id(), name))
+}
+
+fn ipc(name: &str) -> String {
+    format!("ipc://{}", socket_path(name).display())
+}
+
+/// Configure, arm and start a component
+async fn start(client: &ComponentClient, address: &str, run_number: u32) {
+    let commands = [
+        Command::Configure(RunConfig {
+            run_number,
+            comment: String::new(),
+            exp_name: "ipc".to_string(),
+        }),
+        Command::Arm,
+        Command::Start { run_number },
+    ];
+    for command in &commands {
+        let response = client
+            .send_command(address, command)
+            .await
+            .unwrap_or_else(|e| panic!("{} to {}: {}", command, address, e));
+        assert!(response.success, "{}: {}", command, response.message);
+    }
+}
+
+/// Batches the sink reports as received (parsed from its status details)
+async fn received_batches(client: &ComponentClient, address: &str) -> (ComponentState, u64) {
+    let response = client
+        .send_command(address, &Command::GetStatus)
+        .await
+        .expect("status");
+    let received = response
+        .message
+        .split("Received: ")
+        .nth(1)
+        .and_then(|rest| rest.split(',').next())
+        .and_then(|n| n.parse().ok())
+        .expect("received count in status");
+    (response.state, received)
+}
+
+#[tokio::test]
+async fn emulator_to_data_sink_over_ipc() {
+    let data = ipc("data");
+    let emulator_command = ipc("emulator_cmd");
+    let sink_command = ipc("sink_cmd");
+
+    let mut emulator = Emulator::new(EmulatorConfig {
+        address: data.clone(),
+        command_address: emulator_command.clone(),
+        source_id: 7,
+        events_per_batch: 10,
+        batch_interval_ms: 10,
+        seed: Some(1),
+        ..Default::default()
+    })
+    .await
+    .expect("emulator binds ipc address");
+    let mut sink = DataSink::new(DataSinkConfig {
+        address: data.clone(),
+        command_address: sink_command.clone(),
+        finish_on_all_eos: true,
+        expected_source_ids: vec![7],
+        ..Default::default()
+    })
+    .await
+    .unwrap();
+
+    let (emulator_shutdown, _) = broadcast::channel(1);
+    let (sink_shutdown, _) = broadcast::channel(1);
+    let emulator_rx = emulator_shutdown.subscribe();
+    let emulator_task = tokio::spawn(async move { emulator.run(emulator_rx).await });
+    let sink_rx = sink_shutdown.subscribe();
+    let sink_task = tokio::spawn(async move { sink.run(sink_rx).await });
+    tokio::time::sleep(Duration::from_millis(200)).await;
+
+    // Downstream first, as the Operator does
+    let client = ComponentClient::new();
+    start(&client, &sink_command, 1).await;
+    start(&client, &emulator_command, 1).await;
+
+    let received = tokio::time::timeout(Duration::from_secs(10), async {
+        loop {
+            let (_, received) = received_batches(&client, &sink_command).await;
+            if received >= 5 {
+                return received;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+    })
+    .await
+    .expect("batches flow over ipc");
+    assert!(received >= 5);
+
+    // Shutting the emulator down mid-run publishes EOS, which ends the sink's run
+    let _ = emulator_shutdown.send(());
+    emulator_task.await.unwrap().unwrap();
+    let state = tokio::time::timeout(Duration::from_secs(10), async {
+        loop {
+            let (state, _) = received_batches(&client, &sink_command).await;
+            if state == ComponentState::Configured {
+                return state;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+    })
+    .await
+    .expect("EOS arrives over ipc");
+    assert_eq!(state, ComponentState::Configured);
+
+    let _ = sink_shutdown.send(());
+    sink_task.await.unwrap().unwrap();
+
+    for name in ["data", "emulator_cmd", "sink_cmd"] {
+        let _ = std::fs::remove_file(socket_path(name));
+    }
+}
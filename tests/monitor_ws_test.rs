@@ -0,0 +1,71 @@
+//! Integration test for the Monitor WebSocket live histogram push
+//!
+//! Starts a Monitor (no upstream needed), connects a WebSocket client to
+//! `/ws`, expects the initial snapshot frame and closes cleanly.
+
+use std::time::Duration;
+
+use delila_rs::monitor::{Monitor, MonitorConfig};
+use futures::StreamExt;
+use tokio::sync::broadcast;
+use tokio::time::{sleep, timeout};
+use tokio_tungstenite::connect_async;
+
+#[tokio::test]
+async fn websocket_receives_snapshot_frame() {
+    let http_port = 18_291;
+    let config = MonitorConfig {
+        subscribe_address: "tcp://127.0.0.1:18292".to_string(),
+        command_address: "tcp://127.0.0.1:18293".to_string(),
+        http_port,
+        ws_interval_ms: 100,
+        ..Default::default()
+    };
+
+    let mut monitor = Monitor::new(config).await.expect("create monitor");
+    let (shutdown_tx, shutdown_rx) = broadcast::channel(1);
+    let monitor_handle = tokio::spawn(async move { monitor.run(shutdown_rx).await });
+
+    // Wait for the HTTP server to come up
+    let url = format!("ws://127.0.0.1:{}/ws", http_port);
+    let mut ws = None;
+    for _ in 0..50 {
+        match connect_async(url.as_str()).await {
+            Ok((stream, _)) => {
+                ws = Some(stream);
+                break;
+            }
+            Err(_) => sleep(Duration::from_millis(100)).await,
+        }
+    }
+    let mut ws = ws.expect("connect to /ws");
+
+    let frame = timeout(Duration::from_secs(5), ws.next())
+        .await
+        .expect("snapshot frame within timeout")
+        .expect("stream open")
+        .expect("valid frame");
+    let json: serde_json::Value =
+        serde_json::from_str(frame.to_text().expect("text frame")).expect("JSON frame");
+    assert_eq!(json["type"], "snapshot");
+    assert_eq!(json["total_events"], 0);
+    assert!(json["histograms"].as_array().unwrap().is_empty());
+
+    // A delta frame follows on the push interval
+    let frame = timeout(Duration::from_secs(5), ws.next())
+        .await
+        .expect("delta frame within timeout")
+        .expect("stream open")
+        .expect("valid frame");
+    let json: serde_json::Value = serde_json::from_str(frame.to_text().unwrap()).unwrap();
+    assert_eq!(json["type"], "delta");
+
+    ws.close(None).await.expect("close websocket");
+
+    shutdown_tx.send(()).unwrap();
+    let result = timeout(Duration::from_secs(10), monitor_handle)
+        .await
+        .expect("monitor shuts down")
+        .expect("monitor task joins");
+    assert!(result.is_ok());
+}
@@ -0,0 +1,249 @@
+//! Integration test for the Operator bring-up endpoint
+//!
+//! Serves the Operator router against mock components (plain command tasks
+//! running the shared state machine) and checks that `POST /api/run/bringup`
+//! configures, arms and starts everything, records the run and applies its
+//! stop limits, is a no-op when repeated, and rolls every component back to
+//! Idle when a component fails mid-sequence.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use delila_rs::common::{
+    handle_command, run_command_task, run_command_task_with_state, CommandHandlerExt,
+    ComponentSharedState, ComponentState,
+};
+use delila_rs::operator::{ComponentConfig, OperatorConfig, RouterBuilder};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::sync::{broadcast, watch, Mutex};
+
+type SharedState = Arc<Mutex<ComponentSharedState>>;
+
+fn component(name: &str, address: &str, pipeline_order: u32) -> ComponentConfig {
+    ComponentConfig {
+        name: name.to_string(),
+        address: address.to_string(),
+        pipeline_order,
+        is_master: false,
+        source_id: None,
+        is_digitizer: false,
+    }
+}
+
+/// Mock component that follows the normal state machine
+fn spawn_component(address: &str, shutdown: &broadcast::Sender<()>) -> SharedState {
+    let shared = Arc::new(Mutex::new(ComponentSharedState::new()));
+    let (state_tx, _state_rx) = watch::channel(ComponentState::Idle);
+    tokio::spawn(run_command_task_with_state(
+        address.to_string(),
+        shared.clone(),
+        state_tx,
+        shutdown.subscribe(),
+        "MockComponent",
+    ));
+    shared
+}
+
+/// Hook that rejects every Start
+struct FailingStart;
+
+impl CommandHandlerExt for FailingStart {
+    fn component_name(&self) -> &'static str {
+        "FailingStart"
+    }
+
+    fn on_start(&mut self, _run_number: u32) -> Result<(), String> {
+        Err("hardware refused to start".to_string())
+    }
+}
+
+/// Mock component that configures and arms but fails to start
+fn spawn_failing_component(address: &str, shutdown: &broadcast::Sender<()>) -> SharedState {
+    let shared = Arc::new(Mutex::new(ComponentSharedState::new()));
+    let (state_tx, _state_rx) = watch::channel(ComponentState::Idle);
+    tokio::spawn(run_command_task(
+        address.to_string(),
+        shared.clone(),
+        state_tx,
+        shutdown.subscribe(),
+        |state, tx, cmd| handle_command(state, tx, cmd, Some(&mut FailingStart)),
+        "FailingStart",
+    ));
+    shared
+}
+
+/// Serve the router for `components`; returns the HTTP address
+async fn serve(components: Vec<ComponentConfig>) -> std::net::SocketAddr {
+    let (app, _tasks) = RouterBuilder::new(components)
+        .config(OperatorConfig::default())
+        .config_dir(std::env::temp_dir().join("delila_operator_bringup_test_no_configs"))
+        .build();
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("bind listener");
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await });
+    addr
+}
+
+/// Minimal HTTP/1.1 request; returns status code and JSON body
+async fn http(
+    addr: std::net::SocketAddr,
+    method: &str,
+    path: &str,
+    body: &str,
+) -> (u16, serde_json::Value) {
+    let request = format!(
+        "{} {} HTTP/1.1\r\nHost: {}\r\nContent-Type: application/json\r\n\
+         Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+        method,
+        path,
+        addr,
+        body.len(),
+        body
+    );
+    let mut stream = tokio::net::TcpStream::connect(addr).await.expect("connect");
+    stream.write_all(request.as_bytes()).await.expect("send");
+    let mut response = String::new();
+    tokio::time::timeout(
+        Duration::from_secs(60),
+        stream.read_to_string(&mut response),
+    )
+    .await
+    .expect("response within timeout")
+    .expect("read response");
+
+    let code = response
+        .split_whitespace()
+        .nth(1)
+        .and_then(|code| code.parse().ok())
+        .expect("HTTP status line");
+    let (_, body) = response.split_once("\r\n\r\n").expect("HTTP body");
+    (code, serde_json::from_str(body).expect("JSON body"))
+}
+
+/// `POST /api/run/bringup` for `run_number`
+async fn bringup(addr: std::net::SocketAddr, run_number: u32) -> (u16, serde_json::Value) {
+    let body = serde_json::json!({ "run_number": run_number }).to_string();
+    http(addr, "POST", "/api/run/bringup", &body).await
+}
+
+async fn state_of(shared: &SharedState) -> ComponentState {
+    shared.lock().await.state
+}
+
+#[tokio::test]
+async fn bringup_starts_all_components_and_is_idempotent() {
+    let (shutdown, _) = broadcast::channel(1);
+    let recorder = spawn_component("tcp://127.0.0.1:15595", &shutdown);
+    let reader = spawn_component("tcp://127.0.0.1:15596", &shutdown);
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let addr = serve(vec![
+        component("Recorder", "tcp://127.0.0.1:15595", 2),
+        component("Reader", "tcp://127.0.0.1:15596", 1),
+    ])
+    .await;
+
+    let (code, body) = bringup(addr, 7).await;
+    assert_eq!(code, 200, "{}", body);
+    assert_eq!(body["success"], true);
+    assert_eq!(body["results"].as_array().map(|r| r.len()), Some(2));
+    assert_eq!(state_of(&recorder).await, ComponentState::Running);
+    assert_eq!(state_of(&reader).await, ComponentState::Running);
+
+    // Repeating the request for the same run changes nothing
+    let (code, body) = bringup(addr, 7).await;
+    assert_eq!(code, 200, "{}", body);
+    assert_eq!(state_of(&recorder).await, ComponentState::Running);
+    assert_eq!(recorder.lock().await.run_number(), Some(7));
+
+    // A different run is refused while one is in progress
+    let (code, body) = bringup(addr, 8).await;
+    assert_eq!(code, 409, "{}", body);
+    assert_eq!(state_of(&reader).await, ComponentState::Running);
+    assert_eq!(reader.lock().await.run_number(), Some(7));
+
+    let _ = shutdown.send(());
+}
+
+#[tokio::test]
+async fn bringup_records_run_and_applies_limits() {
+    let (shutdown, _) = broadcast::channel(1);
+    let recorder = spawn_component("tcp://127.0.0.1:15588", &shutdown);
+    let reader = spawn_component("tcp://127.0.0.1:15589", &shutdown);
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let addr = serve(vec![
+        component("Recorder", "tcp://127.0.0.1:15588", 2),
+        component("Reader", "tcp://127.0.0.1:15589", 1),
+    ])
+    .await;
+
+    let body = serde_json::json!({
+        "run_number": 12,
+        "comment": "bring-up",
+        "stop_after_secs": 1,
+    })
+    .to_string();
+    let (code, body) = http(addr, "POST", "/api/run/bringup", &body).await;
+    assert_eq!(code, 200, "{}", body);
+
+    // The run is the current run, like one started with /api/start
+    let (code, status) = http(addr, "GET", "/api/status", "").await;
+    assert_eq!(code, 200, "{}", status);
+    assert_eq!(status["run_info"]["run_number"], 12, "{}", status);
+    assert_eq!(status["run_info"]["comment"], "bring-up", "{}", status);
+
+    // ...and its time limit stops it
+    let mut stopped = false;
+    for _ in 0..50 {
+        if state_of(&reader).await != ComponentState::Running
+            && state_of(&recorder).await != ComponentState::Running
+        {
+            stopped = true;
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    assert!(stopped, "run limit did not stop the run");
+
+    let _ = shutdown.send(());
+}
+
+#[tokio::test]
+async fn bringup_rolls_back_when_start_fails() {
+    let (shutdown, _) = broadcast::channel(1);
+    let recorder = spawn_component("tcp://127.0.0.1:15597", &shutdown);
+    let reader = spawn_failing_component("tcp://127.0.0.1:15598", &shutdown);
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    // The Recorder (downstream) starts first and is Running when the Reader fails
+    let addr = serve(vec![
+        component("Recorder", "tcp://127.0.0.1:15597", 2),
+        component("Reader", "tcp://127.0.0.1:15598", 1),
+    ])
+    .await;
+
+    let (code, body) = bringup(addr, 3).await;
+    assert_ne!(code, 200, "{}", body);
+    assert_eq!(body["success"], false);
+    let message = body["message"].as_str().unwrap();
+    assert!(message.contains("Start phase failed"), "{}", message);
+    assert!(message.contains("rolled back"), "{}", message);
+
+    let rollback: Vec<_> = body["results"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter(|r| r["message"].as_str().unwrap().starts_with("Rollback:"))
+        .collect();
+    // Stop for the running Recorder, then Reset for both
+    assert_eq!(rollback.len(), 3, "{}", body);
+    assert!(rollback.iter().all(|r| r["success"] == true), "{}", body);
+
+    assert_eq!(state_of(&recorder).await, ComponentState::Idle);
+    assert_eq!(state_of(&reader).await, ComponentState::Idle);
+
+    let _ = shutdown.send(());
+}
@@ -0,0 +1,89 @@
+//! Integration test for the Operator liveness / readiness probes
+//!
+//! Serves the Operator router against a mock component (a plain command
+//! task answering GetStatus) and checks that `/readyz` answers 200 while
+//! every component is online and 503 once one of them is unreachable.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use delila_rs::common::{run_command_task_with_state, ComponentSharedState, ComponentState};
+use delila_rs::operator::{ComponentConfig, OperatorConfig, RouterBuilder};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::sync::{broadcast, watch, Mutex};
+
+fn component(name: &str, address: &str) -> ComponentConfig {
+    ComponentConfig {
+        name: name.to_string(),
+        address: address.to_string(),
+        pipeline_order: 1,
+        is_master: false,
+        source_id: None,
+        is_digitizer: false,
+    }
+}
+
+/// Serve the router for `components`; returns the HTTP address
+async fn serve(components: Vec<ComponentConfig>) -> std::net::SocketAddr {
+    let (app, _tasks) = RouterBuilder::new(components)
+        .config(OperatorConfig::default())
+        .config_dir(std::env::temp_dir().join("delila_operator_health_test_no_configs"))
+        .build();
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("bind listener");
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await });
+    addr
+}
+
+/// Minimal HTTP/1.1 GET; returns the status code
+async fn get_status_code(addr: std::net::SocketAddr, path: &str) -> u16 {
+    let mut stream = tokio::net::TcpStream::connect(addr).await.expect("connect");
+    let request = format!(
+        "GET {} HTTP/1.1\r\nHost: {}\r\nConnection: close\r\n\r\n",
+        path, addr
+    );
+    stream.write_all(request.as_bytes()).await.expect("send");
+    let mut response = String::new();
+    tokio::time::timeout(
+        Duration::from_secs(20),
+        stream.read_to_string(&mut response),
+    )
+    .await
+    .expect("response within timeout")
+    .expect("read response");
+    response
+        .split_whitespace()
+        .nth(1)
+        .and_then(|code| code.parse().ok())
+        .expect("HTTP status line")
+}
+
+#[tokio::test]
+async fn readiness_follows_component_availability() {
+    let (_shutdown_tx, shutdown_rx) = broadcast::channel(1);
+    let (state_tx, _state_rx) = watch::channel(ComponentState::Idle);
+    tokio::spawn(run_command_task_with_state(
+        "tcp://127.0.0.1:15592".to_string(),
+        Arc::new(Mutex::new(ComponentSharedState::new())),
+        state_tx,
+        shutdown_rx,
+        "MockComponent",
+    ));
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    // All components online
+    let online = serve(vec![component("mock", "tcp://127.0.0.1:15592")]).await;
+    assert_eq!(get_status_code(online, "/healthz").await, 200);
+    assert_eq!(get_status_code(online, "/readyz").await, 200);
+
+    // Nothing listens on the second address, so the system is Degraded
+    let degraded = serve(vec![
+        component("mock", "tcp://127.0.0.1:15592"),
+        component("offline", "tcp://127.0.0.1:15593"),
+    ])
+    .await;
+    assert_eq!(get_status_code(degraded, "/healthz").await, 200);
+    assert_eq!(get_status_code(degraded, "/readyz").await, 503);
+}
@@ -0,0 +1,59 @@
+//! Integration test for the Operator WebSocket status push
+//!
+//! Serves the Operator router (no components), connects a WebSocket client
+//! to `/api/ws/status`, expects a `SystemStatus` frame and closes cleanly.
+
+use std::time::Duration;
+
+use delila_rs::operator::{OperatorConfig, RouterBuilder};
+use futures::StreamExt;
+use tokio::time::timeout;
+use tokio_tungstenite::connect_async;
+
+#[tokio::test]
+async fn websocket_receives_status_frame() {
+    let (app, tasks) = RouterBuilder::new(Vec::new())
+        .config(OperatorConfig {
+            status_interval_ms: 100,
+            ..OperatorConfig::default()
+        })
+        .config_dir(std::env::temp_dir().join("delila_operator_ws_test_no_configs"))
+        .build();
+    tasks.start();
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("bind listener");
+    let addr = listener.local_addr().unwrap();
+    let server = tokio::spawn(async move { axum::serve(listener, app).await });
+
+    let url = format!("ws://{}/api/ws/status", addr);
+    let (mut ws, _) = connect_async(url.as_str())
+        .await
+        .expect("connect to /api/ws/status");
+
+    let frame = timeout(Duration::from_secs(5), ws.next())
+        .await
+        .expect("status frame within timeout")
+        .expect("stream open")
+        .expect("valid frame");
+    let json: serde_json::Value =
+        serde_json::from_str(frame.to_text().expect("text frame")).expect("JSON frame");
+    assert_eq!(json["system_state"], "Idle");
+    assert!(json["components"].as_array().unwrap().is_empty());
+    assert_eq!(json["experiment_name"], "DefaultExp");
+
+    ws.close(None).await.expect("close websocket");
+    // The server acknowledges the close and ends the stream
+    let rest = timeout(Duration::from_secs(5), async {
+        while let Some(Ok(msg)) = ws.next().await {
+            if msg.is_close() {
+                break;
+            }
+        }
+    })
+    .await;
+    assert!(rest.is_ok());
+
+    server.abort();
+}
@@ -0,0 +1,58 @@
+//! ZLE decoder against a captured DPP-ZLE buffer
+//!
+//! Needs a `.dlraw` file recorded from a DPP-ZLE board with the Reader's
+//! `raw_record_dir`:
+//! ```bash
+//! DELILA_ZLE_CAPTURE=run0001_src00_1700000000.dlraw \
+//!     cargo test --test zle_capture_test -- --ignored
+//! ```
+
+use std::path::PathBuf;
+
+use delila_rs::reader::decoder::{DataType, ZleDecoder};
+use delila_rs::reader::RawFileReader;
+
+fn capture_path() -> PathBuf {
+    std::env::var("DELILA_ZLE_CAPTURE")
+        .map(PathBuf::from)
+        .expect("DELILA_ZLE_CAPTURE must point to a captured DPP-ZLE .dlraw file")
+}
+
+#[test]
+#[ignore = "Requires a captured DPP-ZLE buffer"]
+fn test_decode_captured_zle_buffers() {
+    let reader = RawFileReader::open(&capture_path()).expect("open capture");
+    let mut decoder = ZleDecoder::with_defaults();
+
+    let mut aggregates = 0;
+    for raw in reader {
+        let raw = raw.expect("read frame");
+        if decoder.classify(&raw) != DataType::Event {
+            continue;
+        }
+        aggregates += 1;
+
+        let events = decoder.decode(&raw);
+        // The hardware's event count must match what the layout yields
+        if raw.n_events > 0 {
+            assert_eq!(
+                events.len(),
+                raw.n_events as usize,
+                "aggregate {}",
+                aggregates
+            );
+        }
+        for event in &events {
+            let wf = event
+                .waveform
+                .as_ref()
+                .expect("ZLE events carry a waveform");
+            assert_eq!(wf.analog_probe1.len(), wf.digital_probe1.len());
+            assert!(wf.digital_probe1.iter().all(|&bit| bit <= 1));
+        }
+        assert!(events
+            .windows(2)
+            .all(|pair| pair[0].timestamp_ns <= pair[1].timestamp_ns));
+    }
+    assert!(aggregates > 0, "capture holds no event aggregates");
+}
@@ -7,6 +7,7 @@ export type ComponentState =
   | 'Armed'
   | 'Starting'
   | 'Running'
+  | 'Paused'
   | 'Stopping'
   | 'Error';
 
@@ -19,6 +20,7 @@ export type SystemState =
   | 'Armed'
   | 'Starting'
   | 'Running'
+  | 'Paused'
   | 'Stopping'
   | 'Error'
   | 'Mixed'
@@ -42,6 +44,8 @@ export interface ComponentStatus {
   metrics?: ComponentMetrics;
   error?: string;
   online: boolean;
+  /** Build version reported by the component */
+  version?: string;
 }
 
 // Run status
@@ -90,6 +94,8 @@ export interface SystemStatus {
   next_run_number?: number;
   /** Last run info for pre-filling comment (comment + notes from previous run) */
   last_run_info?: LastRunInfo;
+  /** Components built from a different version than the Operator */
+  version_mismatch?: string[];
 }
 
 // Configure request